@@ -22,6 +22,8 @@ public final class CallOptionTest extends JunitContractTest {
 
   private static final BigInteger SELL_AMOUNT = BigInteger.valueOf(1234L);
   private static final BigInteger PAYMENT_AMOUNT = BigInteger.valueOf(7789L);
+  private static final BigInteger COLLATERAL = BigInteger.valueOf(5000L);
+  private static final BigInteger PREMIUM = BigInteger.valueOf(100L);
 
   private static final long DEADLINE = 1000000L;
   private static final long EXECUTION_WINDOW_START = DEADLINE + 60_000;
@@ -35,6 +37,7 @@ public final class CallOptionTest extends JunitContractTest {
   public BlockchainAddress mpcMpc20;
   public BlockchainAddress usdcMpc20;
   private BlockchainAddress agreementContract;
+  private BlockchainAddress oracle;
 
   @ContractTest
   void prepareTokens() {
@@ -55,9 +58,13 @@ public final class CallOptionTest extends JunitContractTest {
             seller,
             SELL_AMOUNT,
             PAYMENT_AMOUNT,
+            BigInteger.ZERO,
             productionTime,
             productionTime + 100,
-            productionTime + 200);
+            productionTime + 200,
+            new CallOption.SettlementModePhysical(),
+            new CallOption.ExerciseStyleEuropean(),
+            new CallOption.OptionTypeCall());
     Assertions.assertThatCode(
             () -> blockchain.deployContract(buyer, CONTRACT_BYTES, initInvalidDeadline))
         .hasMessageContaining("Deadline has to be in the future");
@@ -74,9 +81,13 @@ public final class CallOptionTest extends JunitContractTest {
             seller,
             SELL_AMOUNT,
             PAYMENT_AMOUNT,
+            BigInteger.ZERO,
             deadline,
             deadline - 1,
-            deadline + 1);
+            deadline + 1,
+            new CallOption.SettlementModePhysical(),
+            new CallOption.ExerciseStyleEuropean(),
+            new CallOption.OptionTypeCall());
     Assertions.assertThatCode(
             () -> blockchain.deployContract(buyer, CONTRACT_BYTES, initInvalidWindowStart))
         .hasMessageContaining("Execution window must start after the deadline");
@@ -89,9 +100,13 @@ public final class CallOptionTest extends JunitContractTest {
             seller,
             SELL_AMOUNT,
             PAYMENT_AMOUNT,
+            BigInteger.ZERO,
             deadline,
             windowStart,
-            windowStart);
+            windowStart,
+            new CallOption.SettlementModePhysical(),
+            new CallOption.ExerciseStyleEuropean(),
+            new CallOption.OptionTypeCall());
     Assertions.assertThatCode(
             () -> blockchain.deployContract(buyer, CONTRACT_BYTES, initInvalidWindowEnd))
         .hasMessageContaining("Execution window cannot end before it starts");
@@ -107,9 +122,13 @@ public final class CallOptionTest extends JunitContractTest {
             seller,
             SELL_AMOUNT,
             PAYMENT_AMOUNT,
+            BigInteger.ZERO,
             DEADLINE,
             EXECUTION_WINDOW_START,
-            EXECUTION_WINDOW_END);
+            EXECUTION_WINDOW_END,
+            new CallOption.SettlementModePhysical(),
+            new CallOption.ExerciseStyleEuropean(),
+            new CallOption.OptionTypeCall());
     agreementContract = blockchain.deployContract(buyer, CONTRACT_BYTES, initInvalidWindowStart);
 
     CallOption.State state = getAgreementState();
@@ -119,10 +138,12 @@ public final class CallOptionTest extends JunitContractTest {
     Assertions.assertThat(state.seller()).isEqualTo(seller);
     Assertions.assertThat(state.tokenAmount()).isEqualTo(SELL_AMOUNT);
     Assertions.assertThat(state.agreedPayment()).isEqualTo(PAYMENT_AMOUNT);
+    Assertions.assertThat(state.premium()).isEqualTo(BigInteger.ZERO);
     Assertions.assertThat(state.deadline()).isEqualTo(DEADLINE);
     Assertions.assertThat(state.executionWindow().start()).isEqualTo(EXECUTION_WINDOW_START);
     Assertions.assertThat(state.executionWindow().end()).isEqualTo(EXECUTION_WINDOW_END);
     Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusPending());
+    Assertions.assertThat(state.exerciseStyle()).isEqualTo(new CallOption.ExerciseStyleEuropean());
   }
 
   /** When seller accepts agreement the tokens to sell are transferred to the contract. */
@@ -130,14 +151,84 @@ public final class CallOptionTest extends JunitContractTest {
   void acceptAgreement() {
     blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT));
 
-    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement());
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT));
 
     CallOption.State state = getAgreementState();
     Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusAccepted());
+    Assertions.assertThat(state.depositedAmount()).isEqualTo(SELL_AMOUNT);
     Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract))
         .isEqualTo(SELL_AMOUNT);
   }
 
+  /**
+   * The seller can deposit the tokens over multiple transactions. The agreement is accepted once
+   * the full amount has been deposited.
+   */
+  @ContractTest(previous = "deployAgreement")
+  void acceptAgreementWithPartialDeposits() {
+    final BigInteger firstDeposit = BigInteger.valueOf(1000L);
+    final BigInteger secondDeposit = SELL_AMOUNT.subtract(firstDeposit);
+
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, firstDeposit));
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(firstDeposit));
+
+    CallOption.State state = getAgreementState();
+    Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusPending());
+    Assertions.assertThat(state.depositedAmount()).isEqualTo(firstDeposit);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract))
+        .isEqualTo(firstDeposit);
+
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, secondDeposit));
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(secondDeposit));
+
+    state = getAgreementState();
+    Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusAccepted());
+    Assertions.assertThat(state.depositedAmount()).isEqualTo(SELL_AMOUNT);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract))
+        .isEqualTo(SELL_AMOUNT);
+  }
+
+  /** A deposit that would exceed the token amount of the agreement is rejected. */
+  @ContractTest(previous = "deployAgreement")
+  void depositExceedingTokenAmountIsRejected() {
+    final BigInteger firstDeposit = BigInteger.valueOf(1000L);
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT));
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(firstDeposit));
+
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
+        .hasMessageContaining("Deposit would exceed the token amount of the agreement");
+
+    CallOption.State state = getAgreementState();
+    Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusPending());
+    Assertions.assertThat(state.depositedAmount()).isEqualTo(firstDeposit);
+  }
+
+  /** A partially deposited agreement can be cancelled by the seller after the deadline. */
+  @ContractTest(previous = "deployAgreement")
+  void sellerCanCancelPartialDepositAfterDeadline() {
+    final BigInteger deposit = BigInteger.valueOf(1000L);
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, deposit));
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(deposit));
+    final BigInteger balanceBefore = getTokenState(mpcMpc20).balances().get(seller);
+
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(seller, agreementContract, CallOption.cancel()))
+        .hasMessageContaining(
+            "It is not possible to cancel a partially deposited agreement prior to the deadline");
+
+    blockchain.waitForBlockProductionTime(DEADLINE);
+    blockchain.sendAction(seller, agreementContract, CallOption.cancel());
+
+    Assertions.assertThat(getAgreementState().status())
+        .isEqualTo(new CallOption.StatusCancelled());
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(seller).subtract(balanceBefore))
+        .isEqualTo(deposit);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract)).isNull();
+  }
+
   /**
    * Acceptance of agreement set the status of the contract to Depositing while transferring tokens.
    */
@@ -146,7 +237,8 @@ public final class CallOptionTest extends JunitContractTest {
     blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT));
 
     TxExecution signedTransactionExecution =
-        blockchain.sendActionAsync(seller, agreementContract, CallOption.enterAgreement());
+        blockchain.sendActionAsync(
+            seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT));
 
     TxExecution contractAction = signedTransactionExecution.getSpawnedEvents().get(0);
     TxExecution execution = blockchain.executeEventAsync(contractAction);
@@ -165,7 +257,8 @@ public final class CallOptionTest extends JunitContractTest {
     long cost = GAS_COST_MPC20_TRANSFER + GAS_CALLBACK;
     Assertions.assertThatThrownBy(
             () ->
-                blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(), cost))
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT), cost))
         .hasMessageContaining("Cannot allocate gas for events");
 
     CallOption.State state = getAgreementState();
@@ -177,7 +270,9 @@ public final class CallOptionTest extends JunitContractTest {
   @ContractTest(previous = "deployAgreement")
   void onlySellerCanAccept() {
     Assertions.assertThatThrownBy(
-            () -> blockchain.sendAction(buyer, agreementContract, CallOption.enterAgreement()))
+            () ->
+                blockchain.sendAction(
+                    buyer, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
         .hasMessageContaining("Only the seller are allowed to enter into the agreement");
   }
 
@@ -185,18 +280,61 @@ public final class CallOptionTest extends JunitContractTest {
   @ContractTest(previous = "deployAgreement")
   void acceptanceFailsWithoutAcceptance() {
     Assertions.assertThatThrownBy(
-            () -> blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement()))
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
         .hasMessageContaining("Insufficient MPC allowance for transfer_from");
 
     Assertions.assertThat(getAgreementState().status()).isEqualTo(new CallOption.StatusPending());
   }
 
+  /**
+   * Acceptance with too small an approval fails, leaves the agreement Pending without any deposit,
+   * and the seller can accept again once enough tokens are approved.
+   */
+  @ContractTest(previous = "deployAgreement")
+  void underApprovedAcceptanceRevertsToPending() {
+    final BigInteger sellerBalanceBefore = getTokenState(mpcMpc20).balances().get(seller);
+    blockchain.sendAction(
+        seller, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT.subtract(BigInteger.ONE)));
+
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
+        .hasMessageContaining("Insufficient MPC allowance for transfer_from");
+
+    CallOption.State state = getAgreementState();
+    Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusPending());
+    Assertions.assertThat(state.depositedAmount()).isEqualTo(BigInteger.ZERO);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract)).isNull();
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(seller))
+        .isEqualTo(sellerBalanceBefore);
+
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT));
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT));
+    Assertions.assertThat(getAgreementState().status()).isEqualTo(new CallOption.StatusAccepted());
+  }
+
+  /** Deposit failed events can only be sent by the call option contract itself. */
+  @ContractTest(previous = "deployAgreement")
+  void usersCannotSendDepositFailedEvents() {
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.depositFailedEvent(SELL_AMOUNT, 0L)))
+        .hasMessageContaining(
+            "Deposit failed events can only be sent by the call option contract itself");
+  }
+
   /** Acceptance fails if the deadline has been passed. */
   @ContractTest(previous = "deployAgreement")
   void acceptanceFailsAfterDeadline() {
     blockchain.waitForBlockProductionTime(DEADLINE);
     Assertions.assertThatThrownBy(
-            () -> blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement()))
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
         .hasMessageContaining("Unable to enter into the agreement after the deadline");
   }
 
@@ -204,7 +342,9 @@ public final class CallOptionTest extends JunitContractTest {
   @ContractTest(previous = "acceptAgreement")
   void unableToEnterTwice() {
     Assertions.assertThatThrownBy(
-            () -> blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement()))
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
         .hasMessageContaining("The contract must be Pending for the agreemet to be accepted");
   }
 
@@ -370,6 +510,416 @@ public final class CallOptionTest extends JunitContractTest {
             "It is not possible to cancel the agreement prior to the execution window ending");
   }
 
+  /** A pending agreement can be expired by anyone after the deadline. */
+  @ContractTest(previous = "deployAgreement")
+  void anyoneCanExpirePendingAgreementAfterDeadline() {
+    BlockchainAddress anyone = blockchain.newAccount(5);
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(anyone, agreementContract, CallOption.expire()))
+        .hasMessageContaining("It is not possible to expire the agreement prior to the deadline");
+
+    blockchain.waitForBlockProductionTime(DEADLINE);
+    blockchain.sendAction(anyone, agreementContract, CallOption.expire());
+
+    Assertions.assertThat(getAgreementState().status())
+        .isEqualTo(new CallOption.StatusCancelled());
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
+        .hasMessageContaining("The contract must be Pending for the agreemet to be accepted");
+  }
+
+  /** Expiring a partially deposited agreement returns the deposited tokens to the seller. */
+  @ContractTest(previous = "deployAgreement")
+  void expireReturnsPartialDepositToSeller() {
+    final BigInteger deposit = BigInteger.valueOf(1000L);
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, deposit));
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(deposit));
+    final BigInteger balanceBefore = getTokenState(mpcMpc20).balances().get(seller);
+
+    blockchain.waitForBlockProductionTime(DEADLINE);
+    blockchain.sendAction(buyer, agreementContract, CallOption.expire());
+
+    Assertions.assertThat(getAgreementState().status())
+        .isEqualTo(new CallOption.StatusCancelled());
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(seller).subtract(balanceBefore))
+        .isEqualTo(deposit);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract)).isNull();
+  }
+
+  /** An accepted agreement cannot be expired. */
+  @ContractTest(previous = "acceptAgreement")
+  void unableToExpireAcceptedAgreement() {
+    blockchain.waitForBlockProductionTime(DEADLINE);
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(buyer, agreementContract, CallOption.expire()))
+        .hasMessageContaining("It is only possible to expire a pending agreement");
+  }
+
+  /** Status change events can only be sent by the call option contract itself. */
+  @ContractTest(previous = "acceptAgreement")
+  void usersCannotSendStatusEvents() {
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    seller,
+                    agreementContract,
+                    CallOption.statusChangedEvent(
+                        new CallOption.StatusAccepted(), new CallOption.StatusDone(), 0L)))
+        .hasMessageContaining(
+            "Status change events can only be sent by the call option contract itself");
+  }
+
+  /** The agreement can be initialized with a premium to be paid by the buyer. */
+  @ContractTest(previous = "prepareTokens")
+  void deployAgreementWithPremium() {
+    byte[] initRpc =
+        CallOption.initialize(
+            mpcMpc20,
+            usdcMpc20,
+            seller,
+            SELL_AMOUNT,
+            PAYMENT_AMOUNT,
+            PREMIUM,
+            DEADLINE,
+            EXECUTION_WINDOW_START,
+            EXECUTION_WINDOW_END,
+            new CallOption.SettlementModePhysical(),
+            new CallOption.ExerciseStyleEuropean(),
+            new CallOption.OptionTypeCall());
+    agreementContract = blockchain.deployContract(buyer, CONTRACT_BYTES, initRpc);
+
+    Assertions.assertThat(getAgreementState().premium()).isEqualTo(PREMIUM);
+  }
+
+  /** When the seller enters the agreement, the premium is transferred from buyer to seller. */
+  @ContractTest(previous = "deployAgreementWithPremium")
+  void premiumIsPaidWhenAccepting() {
+    blockchain.sendAction(buyer, usdcMpc20, Token.approve(agreementContract, PREMIUM));
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT));
+
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT));
+
+    Assertions.assertThat(getAgreementState().status())
+        .isEqualTo(new CallOption.StatusAccepted());
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(seller)).isEqualTo(PREMIUM);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract))
+        .isEqualTo(SELL_AMOUNT);
+  }
+
+  /**
+   * If the premium cannot be transferred, the deposit is returned to the seller, and the agreement
+   * is pending again.
+   */
+  @ContractTest(previous = "deployAgreementWithPremium")
+  void failedPremiumRevertsToPending() {
+    final BigInteger sellerBalanceBefore = getTokenState(mpcMpc20).balances().get(seller);
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT));
+
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
+        .hasMessageContaining("Insufficient USDC allowance for transfer_from");
+
+    CallOption.State state = getAgreementState();
+    Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusPending());
+    Assertions.assertThat(state.depositedAmount()).isEqualTo(BigInteger.ZERO);
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(seller)).isNull();
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract)).isNull();
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(seller))
+        .isEqualTo(sellerBalanceBefore);
+  }
+
+  /** Only the buyer can assign their position to a new buyer. */
+  @ContractTest(previous = "deployAgreement")
+  void onlyBuyerCanAssign() {
+    final BlockchainAddress newBuyer = blockchain.newAccount(4);
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(seller, agreementContract, CallOption.assign(newBuyer)))
+        .hasMessageContaining("Only the buyer are allowed to assign the agreement");
+
+    Assertions.assertThat(getAgreementState().buyer()).isEqualTo(buyer);
+  }
+
+  /** The buyer can assign a pending agreement. */
+  @ContractTest(previous = "deployAgreement")
+  void buyerCanAssignPendingAgreement() {
+    final BlockchainAddress newBuyer = blockchain.newAccount(4);
+    blockchain.sendAction(buyer, agreementContract, CallOption.assign(newBuyer));
+
+    Assertions.assertThat(getAgreementState().buyer()).isEqualTo(newBuyer);
+  }
+
+  /**
+   * After assignment the new buyer can execute the agreement, receiving the tokens, while the old
+   * buyer can no longer execute it.
+   */
+  @ContractTest(previous = "acceptAgreement")
+  void assignedBuyerCanExecute() {
+    final BlockchainAddress newBuyer = blockchain.newAccount(4);
+    blockchain.waitForBlockProductionTime(EXECUTION_WINDOW_START);
+    blockchain.sendAction(buyer, agreementContract, CallOption.assign(newBuyer));
+    Assertions.assertThat(getAgreementState().buyer()).isEqualTo(newBuyer);
+
+    blockchain.sendAction(buyer, usdcMpc20, Token.approve(agreementContract, PAYMENT_AMOUNT));
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(buyer, agreementContract, CallOption.execute()))
+        .hasMessageContaining("Only the buyer are allowed to execute the agreement");
+
+    blockchain.sendAction(buyer, usdcMpc20, Token.transfer(newBuyer, PAYMENT_AMOUNT));
+    blockchain.sendAction(newBuyer, usdcMpc20, Token.approve(agreementContract, PAYMENT_AMOUNT));
+    blockchain.sendAction(newBuyer, agreementContract, CallOption.execute());
+
+    Assertions.assertThat(getAgreementState().status()).isEqualTo(new CallOption.StatusDone());
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(newBuyer)).isEqualTo(SELL_AMOUNT);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(buyer)).isNull();
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(seller))
+        .isEqualTo(PAYMENT_AMOUNT);
+  }
+
+  /** An executed agreement cannot be assigned. */
+  @ContractTest(previous = "executeAgreement")
+  void unableToAssignExecutedAgreement() {
+    final BlockchainAddress newBuyer = blockchain.newAccount(4);
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(buyer, agreementContract, CallOption.assign(newBuyer)))
+        .hasMessageContaining("It is only possible to assign a pending or accepted agreement");
+  }
+
+  /** Initializing a cash-settled call option without collateral fails. */
+  @ContractTest(previous = "prepareTokens")
+  void deployCashSettledWithoutCollateral() {
+    byte[] initRpc =
+        CallOption.initialize(
+            mpcMpc20,
+            usdcMpc20,
+            seller,
+            SELL_AMOUNT,
+            PAYMENT_AMOUNT,
+            BigInteger.ZERO,
+            DEADLINE,
+            EXECUTION_WINDOW_START,
+            EXECUTION_WINDOW_END,
+            new CallOption.SettlementModeCash(blockchain.newAccount(3), BigInteger.ZERO),
+            new CallOption.ExerciseStyleEuropean(),
+            new CallOption.OptionTypeCall());
+    Assertions.assertThatCode(() -> blockchain.deployContract(buyer, CONTRACT_BYTES, initRpc))
+        .hasMessageContaining("Cash-settled call options must have a positive collateral");
+  }
+
+  /** A cash-settled call option escrows collateral in the payment token. */
+  @ContractTest(previous = "prepareTokens")
+  void acceptCashSettledAgreement() {
+    oracle = blockchain.newAccount(3);
+    byte[] initRpc =
+        CallOption.initialize(
+            mpcMpc20,
+            usdcMpc20,
+            seller,
+            SELL_AMOUNT,
+            PAYMENT_AMOUNT,
+            BigInteger.ZERO,
+            DEADLINE,
+            EXECUTION_WINDOW_START,
+            EXECUTION_WINDOW_END,
+            new CallOption.SettlementModeCash(oracle, COLLATERAL),
+            new CallOption.ExerciseStyleEuropean(),
+            new CallOption.OptionTypeCall());
+    agreementContract = blockchain.deployContract(buyer, CONTRACT_BYTES, initRpc);
+
+    blockchain.sendAction(buyer, usdcMpc20, Token.transfer(seller, COLLATERAL));
+    blockchain.sendAction(seller, usdcMpc20, Token.approve(agreementContract, COLLATERAL));
+
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT)))
+        .hasMessageContaining("Deposit would exceed the token amount of the agreement");
+
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(COLLATERAL));
+
+    CallOption.State state = getAgreementState();
+    Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusAccepted());
+    Assertions.assertThat(state.depositedAmount()).isEqualTo(COLLATERAL);
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(agreementContract))
+        .isEqualTo(COLLATERAL);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(agreementContract)).isNull();
+  }
+
+  /**
+   * If the oracle of a cash-settled call option cannot be queried, the call option remains
+   * accepted, and can be executed again.
+   */
+  @ContractTest(previous = "acceptCashSettledAgreement")
+  void cashSettlementWithUnavailableOracle() {
+    blockchain.waitForBlockProductionTime(EXECUTION_WINDOW_START);
+
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(buyer, agreementContract, CallOption.execute()))
+        .isInstanceOf(RuntimeException.class);
+
+    Assertions.assertThat(getAgreementState().status()).isEqualTo(new CallOption.StatusAccepted());
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(agreementContract))
+        .isEqualTo(COLLATERAL);
+  }
+
+  /** The seller gets the collateral back if a cash-settled call option is never executed. */
+  @ContractTest(previous = "acceptCashSettledAgreement")
+  void sellerCanCancelCashSettledAfterWindow() {
+    blockchain.waitForBlockProductionTime(EXECUTION_WINDOW_END);
+    blockchain.sendAction(seller, agreementContract, CallOption.cancel());
+
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(seller)).isEqualTo(COLLATERAL);
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(agreementContract)).isNull();
+  }
+
+  /** An American call option is accepted like a European call option. */
+  @ContractTest(previous = "prepareTokens")
+  void acceptAmericanAgreement() {
+    byte[] initRpc =
+        CallOption.initialize(
+            mpcMpc20,
+            usdcMpc20,
+            seller,
+            SELL_AMOUNT,
+            PAYMENT_AMOUNT,
+            BigInteger.ZERO,
+            DEADLINE,
+            EXECUTION_WINDOW_START,
+            EXECUTION_WINDOW_END,
+            new CallOption.SettlementModePhysical(),
+            new CallOption.ExerciseStyleAmerican(),
+            new CallOption.OptionTypeCall());
+    agreementContract = blockchain.deployContract(buyer, CONTRACT_BYTES, initRpc);
+    Assertions.assertThat(getAgreementState().exerciseStyle())
+        .isEqualTo(new CallOption.ExerciseStyleAmerican());
+
+    blockchain.sendAction(seller, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT));
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(SELL_AMOUNT));
+
+    Assertions.assertThat(getAgreementState().status())
+        .isEqualTo(new CallOption.StatusAccepted());
+  }
+
+  /**
+   * An American call option can be executed before the execution window starts, at the same time
+   * where executing a European call option is rejected, see {@link
+   * #unableToExecuteAgreementPriorToExecutionWindow}.
+   */
+  @ContractTest(previous = "acceptAmericanAgreement")
+  void executeAmericanAgreementPriorToExecutionWindow() {
+    blockchain.sendAction(buyer, usdcMpc20, Token.approve(agreementContract, PAYMENT_AMOUNT));
+
+    blockchain.waitForBlockProductionTime(EXECUTION_WINDOW_START - 2);
+
+    blockchain.sendAction(buyer, agreementContract, CallOption.execute());
+
+    Assertions.assertThat(getAgreementState().status()).isEqualTo(new CallOption.StatusDone());
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(buyer)).isEqualTo(SELL_AMOUNT);
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(seller))
+        .isEqualTo(PAYMENT_AMOUNT);
+  }
+
+  /** An American call option cannot be executed after the execution window has ended. */
+  @ContractTest(previous = "acceptAmericanAgreement")
+  void unableToExecuteAmericanAgreementAfterExecutionWindow() {
+    blockchain.waitForBlockProductionTime(EXECUTION_WINDOW_END);
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(buyer, agreementContract, CallOption.execute()))
+        .hasMessageContaining(
+            "It is only possible to execute the agreement during the execution window");
+  }
+
+  /**
+   * In a put option the seller escrows the agreed payment, as the buyer has the right to sell the
+   * tokens to the seller.
+   */
+  @ContractTest(previous = "prepareTokens")
+  void acceptPutAgreement() {
+    blockchain.sendAction(seller, mpcMpc20, Token.transfer(buyer, SELL_AMOUNT));
+    blockchain.sendAction(buyer, usdcMpc20, Token.transfer(seller, PAYMENT_AMOUNT));
+
+    byte[] initRpc =
+        CallOption.initialize(
+            mpcMpc20,
+            usdcMpc20,
+            seller,
+            SELL_AMOUNT,
+            PAYMENT_AMOUNT,
+            BigInteger.ZERO,
+            DEADLINE,
+            EXECUTION_WINDOW_START,
+            EXECUTION_WINDOW_END,
+            new CallOption.SettlementModePhysical(),
+            new CallOption.ExerciseStyleEuropean(),
+            new CallOption.OptionTypePut());
+    agreementContract = blockchain.deployContract(buyer, CONTRACT_BYTES, initRpc);
+    Assertions.assertThat(getAgreementState().optionType())
+        .isEqualTo(new CallOption.OptionTypePut());
+
+    blockchain.sendAction(seller, usdcMpc20, Token.approve(agreementContract, PAYMENT_AMOUNT));
+    blockchain.sendAction(seller, agreementContract, CallOption.enterAgreement(PAYMENT_AMOUNT));
+
+    CallOption.State state = getAgreementState();
+    Assertions.assertThat(state.status()).isEqualTo(new CallOption.StatusAccepted());
+    Assertions.assertThat(state.depositedAmount()).isEqualTo(PAYMENT_AMOUNT);
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(seller)).isNull();
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(agreementContract))
+        .isEqualTo(PAYMENT_AMOUNT);
+  }
+
+  /**
+   * Executing a put option transfers the tokens being sold from the buyer to the seller, and the
+   * escrowed payment to the buyer.
+   */
+  @ContractTest(previous = "acceptPutAgreement")
+  void executePutAgreement() {
+    BigInteger sellerTokensBefore = getTokenState(mpcMpc20).balances().get(seller);
+    BigInteger buyerPaymentBefore = getTokenState(usdcMpc20).balances().get(buyer);
+    blockchain.sendAction(buyer, mpcMpc20, Token.approve(agreementContract, SELL_AMOUNT));
+
+    blockchain.waitForBlockProductionTime(EXECUTION_WINDOW_START);
+
+    blockchain.sendAction(buyer, agreementContract, CallOption.execute());
+
+    Assertions.assertThat(getAgreementState().status()).isEqualTo(new CallOption.StatusDone());
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(buyer)).isNull();
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(seller))
+        .isEqualTo(sellerTokensBefore.add(SELL_AMOUNT));
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(buyer))
+        .isEqualTo(buyerPaymentBefore.add(PAYMENT_AMOUNT));
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(agreementContract)).isNull();
+  }
+
+  /** A put option cannot be executed if the buyer has not approved the tokens being sold. */
+  @ContractTest(previous = "acceptPutAgreement")
+  void putExecutionFailsIfTokensAreNotApproved() {
+    blockchain.waitForBlockProductionTime(EXECUTION_WINDOW_START);
+
+    Assertions.assertThatThrownBy(
+            () -> blockchain.sendAction(buyer, agreementContract, CallOption.execute()))
+        .hasMessageContaining("Insufficient MPC allowance for transfer_from");
+
+    Assertions.assertThat(getAgreementState().status()).isEqualTo(new CallOption.StatusAccepted());
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(agreementContract))
+        .isEqualTo(PAYMENT_AMOUNT);
+  }
+
+  /** Cancelling an expired put option returns the escrowed payment to the seller. */
+  @ContractTest(previous = "acceptPutAgreement")
+  void sellerCanCancelPutAfterWindow() {
+    blockchain.waitForBlockProductionTime(EXECUTION_WINDOW_END);
+    blockchain.sendAction(seller, agreementContract, CallOption.cancel());
+
+    Assertions.assertThat(getAgreementState().status())
+        .isEqualTo(new CallOption.StatusCancelled());
+    Assertions.assertThat(getTokenState(usdcMpc20).balances().get(seller))
+        .isEqualTo(PAYMENT_AMOUNT);
+    Assertions.assertThat(getTokenState(mpcMpc20).balances().get(buyer)).isEqualTo(SELL_AMOUNT);
+  }
+
   private Token.TokenState getTokenState(BlockchainAddress contract) {
     return new Token(getStateClient(), contract).getState();
   }
@@ -380,11 +930,11 @@ public final class CallOptionTest extends JunitContractTest {
 
   private void initializeTokenContracts() {
     final byte[] initRpcEth =
-        Token.initialize("MPC", "MPC", (byte) 3, BigInteger.valueOf(1_000_000L));
+        Token.initialize("MPC", "MPC", (byte) 3, BigInteger.valueOf(1_000_000L), null);
     mpcMpc20 = blockchain.deployContract(seller, TokenContractTest.CONTRACT_BYTES, initRpcEth);
 
     final byte[] initRpcUsdCoin =
-        Token.initialize("USD Coin", "USDC", (byte) 6, BigInteger.valueOf(1_000_000L));
+        Token.initialize("USD Coin", "USDC", (byte) 6, BigInteger.valueOf(1_000_000L), null);
     usdcMpc20 = blockchain.deployContract(buyer, TokenContractTest.CONTRACT_BYTES, initRpcUsdCoin);
   }
 }
@@ -35,7 +35,8 @@ public final class ConditionalEscrowTransferTest extends JunitContractTest {
   @ContractTest
   public void setup() {
     ownerDoge = blockchain.newAccount(1);
-    byte[] tokenInitRpc = Token.initialize("Doge", "DOGE", (byte) 8, BigInteger.valueOf(1_000_000));
+    byte[] tokenInitRpc =
+        Token.initialize("Doge", "DOGE", (byte) 8, BigInteger.valueOf(1_000_000), null);
     doge = blockchain.deployContract(ownerDoge, TOKEN_CONTRACT_BYTES, tokenInitRpc);
 
     ownerConditionalEscrow = blockchain.newAccount(2);
@@ -34,6 +34,11 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
 
   private static final long PRICE_NUMERATOR = 90;
   private static final long PRICE_DENOMINATOR = 60;
+  private static final int MAX_OPEN_ORDERS_PER_USER = 5;
+  private static final BigInteger MIN_ORDER_AMOUNT = BigInteger.ONE;
+  private static final short MAKER_FEE_PER_MILLE = 10;
+  private static final short TAKER_FEE_PER_MILLE = 20;
+  private static final long AUCTION_END_MILLIS = 1_000_000L;
 
   private BlockchainAddress currencyTokenAddress;
   private BlockchainAddress assetTokenAddress;
@@ -41,6 +46,7 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
   private BlockchainAddress client1;
   private BlockchainAddress client2;
   private BlockchainAddress admin;
+  private BlockchainAddress feeCollector;
 
   private BlockchainAddress orderMatchingAddress;
   private DoubleAuctionOrderMatching orderMatching;
@@ -48,26 +54,25 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
   /** Set up the contracts and blockchain accounts. */
   @ContractTest
   void setUp() {
-    client1 = blockchain.newAccount(1);
-    client2 = blockchain.newAccount(2);
-    admin = blockchain.newAccount(3);
-
-    final byte[] initCurrencyToken = Token.initialize("USD Coin", "USDC", (byte) 18, TOTAL_SUPPLY);
-    currencyTokenAddress =
-        blockchain.deployContract(admin, TokenContractTest.CONTRACT_BYTES, initCurrencyToken);
+    setUpWith(MIN_ORDER_AMOUNT, (short) 0, (short) 0, null);
+  }
 
-    final byte[] initAssetToken = Token.initialize("Polygon", "MATIC", (byte) 18, TOTAL_SUPPLY);
-    assetTokenAddress =
-        blockchain.deployContract(admin, TokenContractTest.CONTRACT_BYTES, initAssetToken);
+  /** Set up the contracts and blockchain accounts, with a minimum order amount of 5 tokens. */
+  @ContractTest
+  void setUpMinOrderAmount() {
+    setUpWith(BigInteger.valueOf(5), (short) 0, (short) 0, null);
+  }
 
-    byte[] initOrderMatching =
-        DoubleAuctionOrderMatching.initialize(
-            currencyTokenAddress, assetTokenAddress, PRICE_NUMERATOR, PRICE_DENOMINATOR);
-    orderMatchingAddress =
-        blockchain.deployContract(admin, ORDER_MATCHING_CONTRACT_BYTES, initOrderMatching);
-    orderMatching = new DoubleAuctionOrderMatching(getStateClient(), orderMatchingAddress);
+  /** Set up the contracts and blockchain accounts, with a maker fee and a taker fee. */
+  @ContractTest
+  void setUpFees() {
+    setUpWith(MIN_ORDER_AMOUNT, MAKER_FEE_PER_MILLE, TAKER_FEE_PER_MILLE, null);
+  }
 
-    depositInitialTokens();
+  /** Set up the contracts and blockchain accounts, with a periodic auction. */
+  @ContractTest
+  void setUpPeriodicAuction() {
+    setUpWith(MIN_ORDER_AMOUNT, (short) 0, (short) 0, AUCTION_END_MILLIS);
   }
 
   /**
@@ -183,6 +188,37 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
     assertThat(state.bids().get(expensiveEarly(price, 0)).tokenAmount()).isEqualTo(2);
   }
 
+  /**
+   * Asks at the same price are met in the order they were submitted, such that a crossing bid meets
+   * the earlier ask, with the lower id, before the later ask.
+   */
+  @ContractTest(previous = "setUp")
+  void asksAtSamePriceAreMetInSubmissionOrder() {
+    int price = 40;
+    BlockchainAddress bidder = blockchain.newAccount(5);
+    transfer(currencyTokenAddress, admin, bidder, INITIAL_CLIENT_CURRENCY_TOKENS);
+    approve(bidder, currencyTokenAddress, orderMatchingAddress, INITIAL_CLIENT_CURRENCY_TOKENS);
+    deposit(bidder, currencyTokenAddress, INITIAL_CLIENT_CURRENCY_TOKENS);
+
+    submitAsk(client2, price, BigInteger.valueOf(3), 0);
+    submitAsk(client1, price, BigInteger.valueOf(3), 0);
+
+    submitBid(bidder, price, BigInteger.valueOf(4), 0);
+
+    DoubleAuctionOrderMatching.DoubleAuctionContractState state = orderMatching.getState();
+
+    assertThat(state.asks().get(cheapEarly(price, 0))).isNull();
+    assertThat(state.asks().get(cheapEarly(price, 1)).owner()).isEqualTo(client1);
+    assertThat(state.asks().get(cheapEarly(price, 1)).tokenAmount()).isEqualTo(2);
+    assertThat(state.bids().size()).isEqualTo(0);
+
+    assertAssetTokenBalance(bidder, BigInteger.valueOf(4));
+    assertCurrencyTokenBalance(
+        client2, INITIAL_CLIENT_CURRENCY_TOKENS.add(BigInteger.valueOf(totalPrice(3, price))));
+    assertCurrencyTokenBalance(
+        client1, INITIAL_CLIENT_CURRENCY_TOKENS.add(BigInteger.valueOf(totalPrice(1, price))));
+  }
+
   /** An account can cancel a previously placed bid. */
   @ContractTest(previous = "setUp")
   void cancelBid() {
@@ -216,6 +252,41 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
     assertThat(state.asks().size()).isEqualTo(0);
   }
 
+  /** An account can cancel all of its placed bids and asks at once, and is fully refunded. */
+  @ContractTest(previous = "setUp")
+  void cancelAllOrders() {
+    int bidPrice = 30;
+    int askPrice = 50;
+
+    submitBid(client1, bidPrice, BigInteger.valueOf(2), 0);
+    submitBid(client1, bidPrice, BigInteger.valueOf(3), 1);
+    submitBid(client1, bidPrice - 1, BigInteger.valueOf(4), 2);
+    submitAsk(client1, askPrice, BigInteger.valueOf(5), 3);
+    submitAsk(client1, askPrice + 1, BigInteger.valueOf(6), 4);
+    submitBid(client2, bidPrice, BigInteger.valueOf(1), 0);
+
+    cancelAllOrders(client1);
+
+    DoubleAuctionOrderMatching.DoubleAuctionContractState state = orderMatching.getState();
+
+    assertCurrencyTokenBalance(client1, INITIAL_CLIENT_CURRENCY_TOKENS);
+    assertAssetTokenBalance(client1, INITIAL_CLIENT_ASSET_TOKENS);
+    assertThat(state.asks().size()).isEqualTo(0);
+    assertThat(state.bids().size()).isEqualTo(1);
+    assertThat(state.bids().get(expensiveEarly(bidPrice, 5)).owner()).isEqualTo(client2);
+  }
+
+  /** Cancelling all orders of an account without any placed orders changes nothing. */
+  @ContractTest(previous = "setUp")
+  void cancelAllOrdersWithoutOrders() {
+    submitBid(client2, 40, BigInteger.valueOf(1), 0);
+
+    cancelAllOrders(client1);
+
+    assertCurrencyTokenBalance(client1, INITIAL_CLIENT_CURRENCY_TOKENS);
+    assertThat(orderMatching.getState().bids().size()).isEqualTo(1);
+  }
+
   /** An account cannot cancel a limit order that has not been placed. */
   @ContractTest(previous = "setUp")
   void invalidCancel() {
@@ -229,6 +300,78 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
         .hasMessageContaining("The given cancelation request did not match any orders.");
   }
 
+  /** An account cannot place more open limit orders than the maximum allowed per user. */
+  @ContractTest(previous = "setUp")
+  void openOrderLimitReached() {
+    int price = 40;
+    for (int cancelationId = 0; cancelationId < MAX_OPEN_ORDERS_PER_USER; cancelationId++) {
+      submitBid(client1, price, BigInteger.ONE, cancelationId);
+    }
+
+    assertThatThrownBy(() -> submitAsk(client1, price + 1, BigInteger.ONE, 100))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("has reached the maximum of 5 open orders.");
+    assertThat(orderMatching.getState().bids().size()).isEqualTo(MAX_OPEN_ORDERS_PER_USER);
+    assertThat(orderMatching.getState().asks().size()).isEqualTo(0);
+  }
+
+  /** The open order limit is counted per user, such that other users can still place orders. */
+  @ContractTest(previous = "setUp")
+  void openOrderLimitIsPerUser() {
+    int price = 40;
+    for (int cancelationId = 0; cancelationId < MAX_OPEN_ORDERS_PER_USER; cancelationId++) {
+      submitBid(client1, price, BigInteger.ONE, cancelationId);
+    }
+
+    submitBid(client2, price, BigInteger.ONE, 0);
+
+    assertThat(orderMatching.getState().bids().size()).isEqualTo(MAX_OPEN_ORDERS_PER_USER + 1);
+  }
+
+  /** Cancelling an open limit order frees up a slot for placing a new order. */
+  @ContractTest(previous = "setUp")
+  void cancelFreesOpenOrderSlot() {
+    int price = 40;
+    for (int cancelationId = 0; cancelationId < MAX_OPEN_ORDERS_PER_USER; cancelationId++) {
+      submitBid(client1, price, BigInteger.ONE, cancelationId);
+    }
+
+    cancelLimitOrder(client1, 0);
+    submitBid(client1, price, BigInteger.ONE, 100);
+
+    assertThat(orderMatching.getState().bids().size()).isEqualTo(MAX_OPEN_ORDERS_PER_USER);
+  }
+
+  /** An open limit order that is met by another order frees up a slot for placing a new order. */
+  @ContractTest(previous = "setUp")
+  void filledOrderFreesOpenOrderSlot() {
+    int price = 40;
+    for (int cancelationId = 0; cancelationId < MAX_OPEN_ORDERS_PER_USER; cancelationId++) {
+      submitBid(client1, price, BigInteger.ONE, cancelationId);
+    }
+    submitAsk(client2, price, BigInteger.ONE, 0);
+
+    submitBid(client1, price, BigInteger.ONE, 100);
+
+    assertThat(orderMatching.getState().bids().size()).isEqualTo(MAX_OPEN_ORDERS_PER_USER);
+  }
+
+  /** An order that is fully met when submitted can be placed even when the limit is reached. */
+  @ContractTest(previous = "setUp")
+  void fullyMetOrderIgnoresOpenOrderLimit() {
+    int price = 40;
+    for (int cancelationId = 0; cancelationId < MAX_OPEN_ORDERS_PER_USER; cancelationId++) {
+      submitBid(client1, price, BigInteger.ONE, cancelationId);
+    }
+    submitAsk(client2, price + 10, BigInteger.ONE, 0);
+
+    submitBid(client1, price + 10, BigInteger.ONE, 100);
+
+    DoubleAuctionOrderMatching.DoubleAuctionContractState state = orderMatching.getState();
+    assertThat(state.bids().size()).isEqualTo(MAX_OPEN_ORDERS_PER_USER);
+    assertThat(state.asks().size()).isEqualTo(0);
+  }
+
   /** An amount of assets cannot be transferred when it exceeds 64 bits. */
   @ContractTest(previous = "setUp")
   void tooLargeAssetAmount() {
@@ -244,6 +387,221 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
         .hasMessageContaining("Token amounts larger than u64 are not allowed.");
   }
 
+  /**
+   * An order placed for less than the minimum order amount is rejected, and nothing is placed on
+   * the contract.
+   */
+  @ContractTest(previous = "setUpMinOrderAmount")
+  void orderBelowMinOrderAmount() {
+    int price = 40;
+
+    assertThatThrownBy(() -> submitBid(client1, price, BigInteger.valueOf(4), 0))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Order amount 4 is below the minimum order amount of 5.");
+    assertThatThrownBy(() -> submitAsk(client1, price, BigInteger.valueOf(4), 1))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Order amount 4 is below the minimum order amount of 5.");
+
+    DoubleAuctionOrderMatching.DoubleAuctionContractState state = orderMatching.getState();
+    assertThat(state.bids().size()).isEqualTo(0);
+    assertThat(state.asks().size()).isEqualTo(0);
+    assertCurrencyTokenBalance(client1, INITIAL_CLIENT_CURRENCY_TOKENS);
+    assertAssetTokenBalance(client1, INITIAL_CLIENT_ASSET_TOKENS);
+  }
+
+  /**
+   * When the remaining amount of a partially met order is below the minimum order amount, the
+   * remainder is not placed on the contract, and stays on the balance of the account.
+   */
+  @ContractTest(previous = "setUpMinOrderAmount")
+  void remainderBelowMinOrderAmountIsNotPlaced() {
+    int price = 40;
+
+    submitAsk(client2, price, BigInteger.valueOf(5), 0);
+
+    submitBid(client1, price, BigInteger.valueOf(8), 0);
+
+    DoubleAuctionOrderMatching.DoubleAuctionContractState state = orderMatching.getState();
+    assertThat(state.asks().size()).isEqualTo(0);
+    assertThat(state.bids().size()).isEqualTo(0);
+    assertCurrencyTokenBalance(
+        client1, INITIAL_CLIENT_CURRENCY_TOKENS.subtract(BigInteger.valueOf(totalPrice(5, price))));
+    assertAssetTokenBalance(client1, INITIAL_CLIENT_ASSET_TOKENS.add(BigInteger.valueOf(5)));
+  }
+
+  /**
+   * When a bid meets a placed ask, the maker fee is deducted from the currency tokens received by
+   * the asker, and the taker fee is deducted from the asset tokens received by the bidder. Both
+   * fees are credited to the fee collector.
+   */
+  @ContractTest(previous = "setUpFees")
+  void feesAreSplitWhenBidMeetsAsk() {
+    int amount = 100;
+    int price = 4;
+
+    submitAsk(client2, price, BigInteger.valueOf(amount), 0);
+    submitBid(client1, price, BigInteger.valueOf(amount), 0);
+
+    // The total price is 540 currency tokens, with a maker fee of 5, and a taker fee of 2 assets.
+    assertThat(totalPrice(amount, price)).isEqualTo(540);
+    assertCurrencyTokenBalance(
+        client1, INITIAL_CLIENT_CURRENCY_TOKENS.subtract(BigInteger.valueOf(540)));
+    assertAssetTokenBalance(client1, INITIAL_CLIENT_ASSET_TOKENS.add(BigInteger.valueOf(98)));
+    assertCurrencyTokenBalance(
+        client2, INITIAL_CLIENT_CURRENCY_TOKENS.add(BigInteger.valueOf(535)));
+    assertAssetTokenBalance(
+        client2, INITIAL_CLIENT_ASSET_TOKENS.subtract(BigInteger.valueOf(amount)));
+    assertCurrencyTokenBalance(feeCollector, BigInteger.valueOf(5));
+    assertAssetTokenBalance(feeCollector, BigInteger.valueOf(2));
+  }
+
+  /**
+   * When an ask meets a placed bid, the maker fee is deducted from the asset tokens received by the
+   * bidder, and the taker fee is deducted from the currency tokens received by the asker. Both
+   * fees are credited to the fee collector.
+   */
+  @ContractTest(previous = "setUpFees")
+  void feesAreSplitWhenAskMeetsBid() {
+    int amount = 100;
+    int price = 4;
+
+    submitBid(client2, price, BigInteger.valueOf(amount), 0);
+    submitAsk(client1, price, BigInteger.valueOf(amount), 0);
+
+    // The total price is 540 currency tokens, with a taker fee of 10, and a maker fee of 1 asset.
+    assertCurrencyTokenBalance(
+        client1, INITIAL_CLIENT_CURRENCY_TOKENS.add(BigInteger.valueOf(530)));
+    assertAssetTokenBalance(
+        client1, INITIAL_CLIENT_ASSET_TOKENS.subtract(BigInteger.valueOf(amount)));
+    assertCurrencyTokenBalance(
+        client2, INITIAL_CLIENT_CURRENCY_TOKENS.subtract(BigInteger.valueOf(540)));
+    assertAssetTokenBalance(client2, INITIAL_CLIENT_ASSET_TOKENS.add(BigInteger.valueOf(99)));
+    assertCurrencyTokenBalance(feeCollector, BigInteger.valueOf(10));
+    assertAssetTokenBalance(feeCollector, BigInteger.ONE);
+  }
+
+  /** No fees are taken from placed orders that have not been met. */
+  @ContractTest(previous = "setUpFees")
+  void noFeesForPlacedOrders() {
+    submitBid(client1, 4, BigInteger.valueOf(100), 0);
+    cancelLimitOrder(client1, 0);
+
+    assertCurrencyTokenBalance(client1, INITIAL_CLIENT_CURRENCY_TOKENS);
+    assertThat(orderMatching.getState().tokenBalances().balances().get(feeCollector)).isNull();
+  }
+
+  /**
+   * With zero fees, matching orders moves the full proceeds between the accounts, and the fee
+   * collector is never credited.
+   */
+  @ContractTest(previous = "setUp")
+  void zeroFeesPreserveProceeds() {
+    int amount = 100;
+    int price = 4;
+
+    submitAsk(client2, price, BigInteger.valueOf(amount), 0);
+    submitBid(client1, price, BigInteger.valueOf(amount), 0);
+
+    assertCurrencyTokenBalance(
+        client1, INITIAL_CLIENT_CURRENCY_TOKENS.subtract(BigInteger.valueOf(540)));
+    assertAssetTokenBalance(client1, INITIAL_CLIENT_ASSET_TOKENS.add(BigInteger.valueOf(amount)));
+    assertCurrencyTokenBalance(
+        client2, INITIAL_CLIENT_CURRENCY_TOKENS.add(BigInteger.valueOf(540)));
+    assertAssetTokenBalance(
+        client2, INITIAL_CLIENT_ASSET_TOKENS.subtract(BigInteger.valueOf(amount)));
+    assertThat(orderMatching.getState().tokenBalances().balances().get(feeCollector)).isNull();
+  }
+
+  /** The contract cannot be initialized with a fee above 1000 per mille. */
+  @ContractTest(previous = "setUp")
+  void invalidFee() {
+    byte[] initOrderMatching =
+        DoubleAuctionOrderMatching.initialize(
+            currencyTokenAddress,
+            assetTokenAddress,
+            PRICE_NUMERATOR,
+            PRICE_DENOMINATOR,
+            MAX_OPEN_ORDERS_PER_USER,
+            MIN_ORDER_AMOUNT,
+            (short) 1001,
+            (short) 0,
+            feeCollector,
+            admin,
+            null);
+
+    assertThatThrownBy(
+            () ->
+                blockchain.deployContract(admin, ORDER_MATCHING_CONTRACT_BYTES, initOrderMatching))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("per mille value was 1001‰, but must be between 0‰ and 1000‰");
+  }
+
+  /** The administrator can update the price when no limit orders are placed. */
+  @ContractTest(previous = "setUp")
+  void updatePriceWithoutPlacedOrders() {
+    updatePrice(admin, 30, 20);
+
+    DoubleAuctionOrderMatching.DoubleAuctionContractState state = orderMatching.getState();
+    assertThat(state.priceNumerator()).isEqualTo(30);
+    assertThat(state.priceDenominator()).isEqualTo(20);
+
+    // Orders are placed at the new price.
+    submitBid(client1, 40, BigInteger.valueOf(2), 0);
+    assertCurrencyTokenBalance(
+        client1, INITIAL_CLIENT_CURRENCY_TOKENS.subtract(BigInteger.valueOf(2 * 40 / 20 * 30)));
+  }
+
+  /** The administrator can update the price again once all placed orders are met or cancelled. */
+  @ContractTest(previous = "setUp")
+  void updatePriceAfterOrdersAreCleared() {
+    submitBid(client1, 40, BigInteger.valueOf(2), 0);
+    submitAsk(client2, 40, BigInteger.valueOf(2), 0);
+    submitAsk(client2, 50, BigInteger.valueOf(2), 1);
+    cancelLimitOrder(client2, 1);
+
+    updatePrice(admin, 30, 20);
+
+    assertThat(orderMatching.getState().priceNumerator()).isEqualTo(30);
+  }
+
+  /** The price cannot be updated while bids are placed, as they would be repriced. */
+  @ContractTest(previous = "setUp")
+  void updatePriceWhileBidsArePlaced() {
+    submitBid(client1, 40, BigInteger.valueOf(2), 0);
+
+    assertThatThrownBy(() -> updatePrice(admin, 30, 20))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Cannot update the price while limit orders are placed.");
+    assertThat(orderMatching.getState().priceNumerator()).isEqualTo(PRICE_NUMERATOR);
+  }
+
+  /** The price cannot be updated while asks are placed, as they would be repriced. */
+  @ContractTest(previous = "setUp")
+  void updatePriceWhileAsksArePlaced() {
+    submitAsk(client2, 40, BigInteger.valueOf(2), 0);
+
+    assertThatThrownBy(() -> updatePrice(admin, 30, 20))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Cannot update the price while limit orders are placed.");
+    assertThat(orderMatching.getState().priceNumerator()).isEqualTo(PRICE_NUMERATOR);
+  }
+
+  /** Only the administrator can update the price. */
+  @ContractTest(previous = "setUp")
+  void nonAdministratorCannotUpdatePrice() {
+    assertThatThrownBy(() -> updatePrice(client1, 30, 20))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Only the administrator can update the price.");
+  }
+
+  /** The price denominator cannot be updated to zero. */
+  @ContractTest(previous = "setUp")
+  void updatePriceWithZeroDenominator() {
+    assertThatThrownBy(() -> updatePrice(admin, 30, 0))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Price denominator must be positive.");
+  }
+
   /** An account can deposit and withdraw the currency and asset tokens. */
   @Nested
   final class DepositWithdraw extends DepositWithdrawTest {
@@ -254,7 +612,17 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
     @Override
     protected byte[] initContractUnderTestRpc(BlockchainAddress token1, BlockchainAddress token2) {
       return DoubleAuctionOrderMatching.initialize(
-          token1, token2, PRICE_NUMERATOR, PRICE_DENOMINATOR);
+          token1,
+          token2,
+          PRICE_NUMERATOR,
+          PRICE_DENOMINATOR,
+          MAX_OPEN_ORDERS_PER_USER,
+          MIN_ORDER_AMOUNT,
+          (short) 0,
+          (short) 0,
+          token1,
+          token1,
+          null);
     }
 
     @Override
@@ -265,6 +633,58 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
     }
   }
 
+  /** In a periodic auction, orders cannot be submitted after the auction has ended. */
+  @ContractTest(previous = "setUpPeriodicAuction")
+  void periodicAuctionRejectsOrdersAfterEnd() {
+    blockchain.waitForBlockProductionTime(AUCTION_END_MILLIS);
+
+    assertThatThrownBy(() -> submitBid(client1, 40, BigInteger.ONE, 0))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Orders cannot be submitted after the auction ended at");
+    assertThatThrownBy(() -> submitAsk(client2, 40, BigInteger.ONE, 0))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Orders cannot be submitted after the auction ended at");
+  }
+
+  /**
+   * In a periodic auction, crossing orders are placed without being met, and are matched at a
+   * single uniform price when the auction is cleared.
+   */
+  @ContractTest(previous = "setUpPeriodicAuction")
+  void clearAuctionMatchesAtUniformPrice() {
+    submitAsk(client2, 40, BigInteger.valueOf(6), 0);
+    submitAsk(client2, 45, BigInteger.valueOf(6), 1);
+    submitBid(client1, 50, BigInteger.TEN, 0);
+
+    assertAssetTokenBalance(client1, INITIAL_CLIENT_ASSET_TOKENS);
+    assertThatThrownBy(this::clearAuction)
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("The auction cannot be cleared before it ends at");
+
+    blockchain.waitForBlockProductionTime(AUCTION_END_MILLIS);
+    clearAuction();
+
+    // All 10 tokens bid are matched at 45, which matches the most tokens.
+    int paid = totalPrice(6, 45) + totalPrice(4, 45);
+    assertCurrencyTokenBalance(
+        client1, INITIAL_CLIENT_CURRENCY_TOKENS.subtract(BigInteger.valueOf(paid)));
+    assertAssetTokenBalance(client1, INITIAL_CLIENT_ASSET_TOKENS.add(BigInteger.TEN));
+    assertCurrencyTokenBalance(
+        client2, INITIAL_CLIENT_CURRENCY_TOKENS.add(BigInteger.valueOf(paid)));
+    assertAssetTokenBalance(client2, INITIAL_CLIENT_ASSET_TOKENS.subtract(BigInteger.valueOf(12)));
+    assertThat(orderMatching.getState().bids().size()).isZero();
+    assertThat(orderMatching.getState().asks().get(cheapEarly(45, 1)).tokenAmount()).isEqualTo(2);
+
+    assertThatThrownBy(this::clearAuction)
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("The auction has already been cleared.");
+  }
+
+  private void clearAuction() {
+    final byte[] rpc = DoubleAuctionOrderMatching.clearAuction();
+    blockchain.sendAction(client1, orderMatchingAddress, rpc);
+  }
+
   private void deposit(
       BlockchainAddress executor, BlockchainAddress tokenAddress, BigInteger amount) {
     final byte[] rpc = DoubleAuctionOrderMatching.deposit(tokenAddress, amount);
@@ -288,6 +708,16 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
     blockchain.sendAction(executor, orderMatchingAddress, rpc);
   }
 
+  private void cancelAllOrders(BlockchainAddress executor) {
+    final byte[] rpc = DoubleAuctionOrderMatching.cancelAllOrders();
+    blockchain.sendAction(executor, orderMatchingAddress, rpc);
+  }
+
+  private void updatePrice(BlockchainAddress executor, long numerator, long denominator) {
+    final byte[] rpc = DoubleAuctionOrderMatching.updatePrice(numerator, denominator);
+    blockchain.sendAction(executor, orderMatchingAddress, rpc);
+  }
+
   private int totalPrice(long amount, long price) {
     return (int) (amount * price / PRICE_DENOMINATOR * PRICE_NUMERATOR);
   }
@@ -329,6 +759,46 @@ public final class DoubleAuctionOrderMatchingTest extends JunitContractTest {
     return tokenBalance;
   }
 
+  private void setUpWith(
+      BigInteger minOrderAmount,
+      short makerFeePerMille,
+      short takerFeePerMille,
+      Long auctionEndMillis) {
+    client1 = blockchain.newAccount(1);
+    client2 = blockchain.newAccount(2);
+    admin = blockchain.newAccount(3);
+    feeCollector = blockchain.newAccount(4);
+
+    final byte[] initCurrencyToken =
+        Token.initialize("USD Coin", "USDC", (byte) 18, TOTAL_SUPPLY, null);
+    currencyTokenAddress =
+        blockchain.deployContract(admin, TokenContractTest.CONTRACT_BYTES, initCurrencyToken);
+
+    final byte[] initAssetToken =
+        Token.initialize("Polygon", "MATIC", (byte) 18, TOTAL_SUPPLY, null);
+    assetTokenAddress =
+        blockchain.deployContract(admin, TokenContractTest.CONTRACT_BYTES, initAssetToken);
+
+    byte[] initOrderMatching =
+        DoubleAuctionOrderMatching.initialize(
+            currencyTokenAddress,
+            assetTokenAddress,
+            PRICE_NUMERATOR,
+            PRICE_DENOMINATOR,
+            MAX_OPEN_ORDERS_PER_USER,
+            minOrderAmount,
+            makerFeePerMille,
+            takerFeePerMille,
+            feeCollector,
+            admin,
+            auctionEndMillis);
+    orderMatchingAddress =
+        blockchain.deployContract(admin, ORDER_MATCHING_CONTRACT_BYTES, initOrderMatching);
+    orderMatching = new DoubleAuctionOrderMatching(getStateClient(), orderMatchingAddress);
+
+    depositInitialTokens();
+  }
+
   private void depositInitialTokens() {
     transfer(currencyTokenAddress, admin, client1, INITIAL_CLIENT_CURRENCY_TOKENS);
     approve(client1, currencyTokenAddress, orderMatchingAddress, INITIAL_CLIENT_CURRENCY_TOKENS);
@@ -56,7 +56,8 @@ public final class LiquidStakingContractTest {
       BigInteger initialStakingSupply,
       ContractBytes contractBytes) {
 
-    byte[] tokenInitRpc = Token.initialize(tokenName, tokenSymbol, (byte) 8, initialTokenSupply);
+    byte[] tokenInitRpc =
+        Token.initialize(tokenName, tokenSymbol, (byte) 8, initialTokenSupply, null);
     BlockchainAddress tokenAddress =
         blockchain.deployContract(initialTokenHolder, TOKEN_CONTRACT_BYTES, tokenInitRpc);
 
@@ -65,9 +66,12 @@ public final class LiquidStakingContractTest {
             tokenAddress,
             creator,
             creator,
+            creator,
             100,
             100,
             BigInteger.ZERO,
+            (short) 0,
+            BigInteger.ONE,
             tokenName,
             tokenSymbol,
             decimals);
@@ -78,7 +82,7 @@ public final class LiquidStakingContractTest {
     byte[] approveRpc = Token.approve(liquidStakingAddress, initialStakingSupply);
     blockchain.sendAction(initialTokenHolder, tokenAddress, approveRpc);
 
-    byte[] rpc = LiquidStaking.submit(initialStakingSupply);
+    byte[] rpc = LiquidStaking.submit(initialStakingSupply, BigInteger.ZERO);
     blockchain.sendAction(initialTokenHolder, liquidStakingAddress, rpc);
 
     return liquidStakingAddress;
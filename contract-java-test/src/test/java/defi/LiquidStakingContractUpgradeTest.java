@@ -60,4 +60,22 @@ public final class LiquidStakingContractUpgradeTest extends LiquidStakingBaseTes
 
     assertLiquidStakingStateInvariant();
   }
+
+  /** The fee administrator cannot upgrade the contract. */
+  @ContractTest(previous = "setupCurrentVersion")
+  void feeAdministratorCannotUpgrade() {
+    initialSetupWithAsserts(20, 0, 0, 0);
+
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.upgradeContract(
+                    liquidStakingFeeAdministrator,
+                    liquidStakingAddress,
+                    CONTRACT_BYTES_CURRENT,
+                    new byte[0]))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Contract did not allow this upgrade");
+
+    assertLiquidStakingStateInvariant();
+  }
 }
@@ -44,7 +44,7 @@ public abstract class LiquiditySwapLockBaseTest extends JunitContractTest {
   }
 
   Token.TokenState getTokenState(BlockchainAddress tokenAddress) {
-    return Token.TokenState.deserialize(blockchain.getContractState(tokenAddress));
+    return new Token(getStateClient(), tokenAddress).getState();
   }
 
   LiquiditySwapLock.VirtualState getVirtualSwapState(BlockchainAddress swapAddress) {
@@ -79,11 +79,11 @@ public final class LiquiditySwapLockPermissionTest extends LiquiditySwapLockBase
     nonOwnerAddress2 = blockchain.newAccount(11);
 
     // Deploy token contracts.
-    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A);
+    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A, null);
     contractTokenA =
         blockchain.deployContract(contractOwnerAddress, TokenContractTest.CONTRACT_BYTES, initRpcA);
 
-    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B);
+    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B, null);
     contractTokenB =
         blockchain.deployContract(contractOwnerAddress, TokenContractTest.CONTRACT_BYTES, initRpcB);
 
@@ -105,7 +105,7 @@ public final class LiquiditySwapLockPermissionTest extends LiquiditySwapLockBase
     // Deploy the LiquiditySwapContract.
     byte[] initRpcSwapAtoB =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenB, FEE, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA, contractTokenB, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
     swapLockContractAddressAtoB =
         blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapAtoB);
 
@@ -125,7 +125,8 @@ public final class LiquiditySwapLockPermissionTest extends LiquiditySwapLockBase
             contractTokenA,
             contractTokenB,
             FEE,
-            new LiquiditySwapLock.PermissionSpecific(List.of(nonOwnerAddress1)));
+            new LiquiditySwapLock.PermissionSpecific(List.of(nonOwnerAddress1)),
+            null);
     swapLockContractAddressAtoB =
         blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapAtoB);
 
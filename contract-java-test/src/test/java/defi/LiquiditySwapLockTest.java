@@ -36,6 +36,8 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
   private static final BigInteger NON_OWNER_TOKEN_AMOUNT_C = BigInteger.ONE.shiftLeft(43);
   private static final BigInteger NON_OWNER_TOKEN_AMOUNT_D = BigInteger.ONE.shiftLeft(42);
 
+  private static final long LOCK_DURATION = 60_000;
+
   private BlockchainAddress contractOwnerAddress;
   private BlockchainAddress nonOwnerAddress1;
   private BlockchainAddress nonOwnerAddress2;
@@ -96,19 +98,19 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
     // Deploy the LiquiditySwapContracts.
     byte[] initRpcSwapAtoB =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenB, FEE, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA, contractTokenB, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
     swapLockContractAddressAtoB =
         blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapAtoB);
 
     byte[] initRpcSwapBtoC =
         LiquiditySwapLock.initialize(
-            contractTokenB, contractTokenC, FEE, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenB, contractTokenC, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
     swapLockContractAddressBtoC =
         blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapBtoC);
 
     byte[] initRpcSwapCtoD =
         LiquiditySwapLock.initialize(
-            contractTokenC, contractTokenD, FEE, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenC, contractTokenD, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
     swapLockContractAddressCtoD =
         blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapCtoD);
 
@@ -154,7 +156,11 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
 
     byte[] initRpcSwapAtoB =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenB, (short) -1, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA,
+            contractTokenB,
+            (short) -1,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     Assertions.assertThatCode(
             () -> blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapAtoB))
         .isInstanceOf(RuntimeException.class)
@@ -174,7 +180,8 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
             contractTokenA,
             contractTokenB,
             (short) 1001,
-            new LiquiditySwapLock.PermissionAnybody());
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     Assertions.assertThatCode(
             () -> blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapAtoB))
         .isInstanceOf(RuntimeException.class)
@@ -192,7 +199,7 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
 
     byte[] initRpcAccountA =
         LiquiditySwapLock.initialize(
-            nonOwnerAddress1, contractTokenB, FEE, new LiquiditySwapLock.PermissionAnybody());
+            nonOwnerAddress1, contractTokenB, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
     Assertions.assertThatCode(
             () -> blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcAccountA))
         .hasMessageContaining("Token address A must be a contract address");
@@ -200,7 +207,7 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
     // Cover both sides.
     byte[] initRpcAccountB =
         LiquiditySwapLock.initialize(
-            contractTokenA, nonOwnerAddress1, FEE, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA, nonOwnerAddress1, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
     Assertions.assertThatCode(
             () -> blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcAccountB))
         .hasMessageContaining("Token address B must be a contract address");
@@ -217,7 +224,7 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
 
     byte[] initRpcSwapAtoB =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenA, FEE, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA, contractTokenA, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
     Assertions.assertThatCode(
             () -> blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapAtoB))
         .isInstanceOf(RuntimeException.class)
@@ -238,7 +245,7 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
     // Deploy the LiquiditySwapContract.
     byte[] initRpcSwapAtoB =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenB, FEE, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA, contractTokenB, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
     swapLockContractAddressAtoB =
         blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwapAtoB);
 
@@ -458,10 +465,10 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
         .isEqualTo(b.balances().get(swapLockContractAddressAtoB));
 
     // User has received the tokens at the token contract.
-    Assertions.assertThat(getTokenState(contractTokenB).balances())
-        .containsEntry(nonOwnerAddress1, NON_OWNER_TOKEN_AMOUNT_B.add(receivingAmount));
-    Assertions.assertThat(getTokenState(contractTokenB).balances())
-        .containsEntry(swapLockContractAddressAtoB, INITIAL_LIQUIDITY_B.subtract(receivingAmount));
+    Assertions.assertThat(getTokenState(contractTokenB).balances().get(nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_B.add(receivingAmount));
+    Assertions.assertThat(getTokenState(contractTokenB).balances().get(swapLockContractAddressAtoB))
+        .isEqualTo(INITIAL_LIQUIDITY_B.subtract(receivingAmount));
   }
 
   /** A user cannot execute an acquired lock, when the lock is on more tokens than deposited. */
@@ -1168,6 +1175,79 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
     assertLiquidityInvariant(swapLockContractAddressAtoB);
   }
 
+  /** Locks acquired at a contract with a lock duration expire after the duration. */
+  @ContractTest(previous = "contractInit")
+  void acquiredLockHasExpiry() {
+    BlockchainAddress expiringSwap = deployExpiringSwap();
+    long acquiredAt = blockchain.getBlockProductionTime();
+    LiquiditySwapLock.LiquidityLockId lockId =
+        acquireLock(expiringSwap, nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, ZERO);
+
+    LiquiditySwapLock.LiquidityLock lock = getVirtualSwapState(expiringSwap).locks().get(lockId);
+    Assertions.assertThat(lock.expiresAt()).isGreaterThanOrEqualTo(acquiredAt + LOCK_DURATION);
+  }
+
+  /** Anybody can release expired locks, which frees the locked liquidity. */
+  @ContractTest(previous = "contractInit")
+  void releaseExpiredLocks() {
+    BlockchainAddress expiringSwap = deployExpiringSwap();
+    LiquiditySwapLock.LiquidityLockId lockId =
+        acquireLock(expiringSwap, nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, ZERO);
+    long expiresAt = getVirtualSwapState(expiringSwap).locks().get(lockId).expiresAt();
+
+    // Locks are not released before they expire.
+    blockchain.sendAction(nonOwnerAddress2, expiringSwap, LiquiditySwapLock.releaseExpiredLocks());
+    Assertions.assertThat(getLockIds(expiringSwap)).containsExactly(lockId);
+
+    blockchain.waitForBlockProductionTime(expiresAt);
+    blockchain.sendAction(nonOwnerAddress2, expiringSwap, LiquiditySwapLock.releaseExpiredLocks());
+    Assertions.assertThat(getLockIds(expiringSwap)).isEmpty();
+    Assertions.assertThat(getVirtualSwapState(expiringSwap).lockSums())
+        .isEqualTo(new LiquiditySwapLock.LockSums(ZERO, ZERO));
+    assertLiquidityInvariant(expiringSwap);
+  }
+
+  /** An expired lock is released, and can no longer be executed by its owner. */
+  @ContractTest(previous = "contractInit")
+  void expiredLockCannotBeExecuted() {
+    BlockchainAddress expiringSwap = deployExpiringSwap();
+    depositIntoSwap(expiringSwap, nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    LiquiditySwapLock.LiquidityLockId lockId =
+        acquireLock(expiringSwap, nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, ZERO);
+    long expiresAt = getVirtualSwapState(expiringSwap).locks().get(lockId).expiresAt();
+
+    blockchain.waitForBlockProductionTime(expiresAt);
+    Assertions.assertThatCode(() -> executeLockSwap(nonOwnerAddress1, expiringSwap, lockId))
+        .isInstanceOf(RuntimeException.class)
+        .hasMessageContaining(
+            "LiquidityLockId { raw_id: %s } is not a valid lock id.", lockId.rawId());
+  }
+
+  /** Expired locks no longer block users from providing liquidity. */
+  @ContractTest(previous = "contractInit")
+  void expiredLockDoesNotBlockProvidingLiquidity() {
+    BlockchainAddress expiringSwap = deployExpiringSwap();
+    LiquiditySwapLock.LiquidityLockId lockId =
+        acquireLock(expiringSwap, nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, ZERO);
+    long expiresAt = getVirtualSwapState(expiringSwap).locks().get(lockId).expiresAt();
+
+    final BigInteger equivalentAmount =
+        calculateEquivalentLiquidity(expiringSwap, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    depositIntoSwap(expiringSwap, nonOwnerAddress2, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    depositIntoSwap(expiringSwap, nonOwnerAddress2, contractTokenB, equivalentAmount);
+
+    blockchain.waitForBlockProductionTime(expiresAt);
+    blockchain.sendAction(
+        nonOwnerAddress2,
+        expiringSwap,
+        LiquiditySwapLock.provideLiquidity(contractTokenA, NON_OWNER_TOKEN_AMOUNT_A));
+
+    Assertions.assertThat(getLockIds(expiringSwap)).isEmpty();
+    Assertions.assertThat(getActualContractBalance(expiringSwap).liquidityTokens())
+        .isGreaterThan(INITIAL_LIQUIDITY_TOKENS_AB);
+    assertLiquidityInvariant(expiringSwap);
+  }
+
   /** A user who tries to execute a lock-swap with an invalid lock id will receive an error. */
   @ContractTest(previous = "contractInit")
   void executeLockInvalidLockId() {
@@ -1355,10 +1435,10 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
     BlockchainAddress nonOwnerAddress = blockchain.newAccount(17);
 
     // Deploy token contracts.
-    byte[] initRpcA = Token.initialize("Token tA", "tA", (byte) 8, TOTAL_SUPPLY_A);
+    byte[] initRpcA = Token.initialize("Token tA", "tA", (byte) 8, TOTAL_SUPPLY_A, null);
     BlockchainAddress tokenA =
         blockchain.deployContract(ownerAddress, TokenContractTest.CONTRACT_BYTES, initRpcA);
-    byte[] initRpcB = Token.initialize("Token tB", "tB", (byte) 8, TOTAL_SUPPLY_B);
+    byte[] initRpcB = Token.initialize("Token tB", "tB", (byte) 8, TOTAL_SUPPLY_B, null);
     BlockchainAddress tokenB =
         blockchain.deployContract(ownerAddress, TokenContractTest.CONTRACT_BYTES, initRpcB);
 
@@ -1366,7 +1446,7 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
         () -> {
           byte[] initRpc =
               LiquiditySwapLock.initialize(
-                  tokenA, tokenB, FEE, new LiquiditySwapLock.PermissionAnybody());
+                  tokenA, tokenB, FEE, new LiquiditySwapLock.PermissionAnybody(), null);
           BlockchainAddress res = blockchain.deployContract(ownerAddress, CONTRACT_BYTES, initRpc);
 
           // Provide initial liquidity
@@ -1459,6 +1539,27 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
     Assertions.assertThat(vb.lockSums()).isEqualTo(new LiquiditySwapLock.LockSums(ZERO, ZERO));
   }
 
+  /** Deploys a swap between token A and B, where locks expire after {@link #LOCK_DURATION}. */
+  private BlockchainAddress deployExpiringSwap() {
+    byte[] initRpc =
+        LiquiditySwapLock.initialize(
+            contractTokenA,
+            contractTokenB,
+            FEE,
+            new LiquiditySwapLock.PermissionAnybody(),
+            LOCK_DURATION);
+    BlockchainAddress expiringSwap =
+        blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpc);
+
+    depositIntoSwap(expiringSwap, contractOwnerAddress, contractTokenA, INITIAL_LIQUIDITY_A);
+    depositIntoSwap(expiringSwap, contractOwnerAddress, contractTokenB, INITIAL_LIQUIDITY_B);
+    blockchain.sendAction(
+        contractOwnerAddress,
+        expiringSwap,
+        LiquiditySwapLock.provideInitialLiquidity(INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B));
+    return expiringSwap;
+  }
+
   private void provideInitialLiquidity() {
     depositIntoSwap(
         swapLockContractAddressAtoB, contractOwnerAddress, contractTokenA, INITIAL_LIQUIDITY_A);
@@ -1525,16 +1626,16 @@ public final class LiquiditySwapLockTest extends LiquiditySwapLockBaseTest {
   }
 
   private void initializeTokenContracts(BlockchainAddress owner) {
-    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A);
+    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A, null);
     contractTokenA = blockchain.deployContract(owner, TokenContractTest.CONTRACT_BYTES, initRpcA);
 
-    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B);
+    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B, null);
     contractTokenB = blockchain.deployContract(owner, TokenContractTest.CONTRACT_BYTES, initRpcB);
 
-    byte[] initRpcC = Token.initialize("Token C", "C", (byte) 8, TOTAL_SUPPLY_C);
+    byte[] initRpcC = Token.initialize("Token C", "C", (byte) 8, TOTAL_SUPPLY_C, null);
     contractTokenC = blockchain.deployContract(owner, TokenContractTest.CONTRACT_BYTES, initRpcC);
 
-    byte[] initRpcD = Token.initialize("Token D", "D", (byte) 8, TOTAL_SUPPLY_D);
+    byte[] initRpcD = Token.initialize("Token D", "D", (byte) 8, TOTAL_SUPPLY_D, null);
     contractTokenD = blockchain.deployContract(owner, TokenContractTest.CONTRACT_BYTES, initRpcD);
   }
 
@@ -83,7 +83,11 @@ public final class LiquiditySwapRegressionTest extends LiquiditySwapLockBaseTest
     // Deploy the LiquiditySwapContracts.
     byte[] initRpcSwap =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenB, (short) 0, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA,
+            contractTokenB,
+            (short) 0,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     swapLockContractAddress =
         blockchain.deployContract(contractOwnerAddress, CONTRACT_BYTES, initRpcSwap);
 
@@ -171,10 +175,10 @@ public final class LiquiditySwapRegressionTest extends LiquiditySwapLockBaseTest
   }
 
   private void initializeTokenContracts(BlockchainAddress owner) {
-    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A);
+    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A, null);
     contractTokenA = blockchain.deployContract(owner, TokenContractTest.CONTRACT_BYTES, initRpcA);
 
-    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B);
+    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B, null);
     contractTokenB = blockchain.deployContract(owner, TokenContractTest.CONTRACT_BYTES, initRpcB);
   }
 }
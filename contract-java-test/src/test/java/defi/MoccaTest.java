@@ -28,6 +28,7 @@ public final class MoccaTest extends JunitContractTest {
 
   private static final BigInteger TOTAL_SUPPLY =
       BigInteger.valueOf(1200).multiply(BigInteger.TEN.pow(18));
+  private static final long PROPOSAL_LIFETIME = 60_000L;
   private BlockchainAddress token;
   private BlockchainAddress mocca;
   private Mocca moccaContract;
@@ -50,7 +51,8 @@ public final class MoccaTest extends JunitContractTest {
     voter3 = blockchain.newAccount(6);
     voter4 = blockchain.newAccount(7);
 
-    final byte[] initRpcUsdCoin = Token.initialize("USD Coin", "USDC", (byte) 18, TOTAL_SUPPLY);
+    final byte[] initRpcUsdCoin =
+        Token.initialize("USD Coin", "USDC", (byte) 18, TOTAL_SUPPLY, null);
     token = blockchain.deployContract(tokenOwner, TOKEN_CONTRACT, initRpcUsdCoin);
   }
 
@@ -69,8 +71,8 @@ public final class MoccaTest extends JunitContractTest {
             new Mocca.Voter(voter2, 10),
             new Mocca.Voter(voter3, 15),
             new Mocca.Voter(voter4, 30));
-    Mocca.Criteria criteria = new Mocca.Criteria(voters, 30);
-    final byte[] initMocca = Mocca.initialize(criteria, token);
+    Mocca.Criteria criteria = new Mocca.Criteria(voters, 30, 0);
+    final byte[] initMocca = Mocca.initialize(criteria, token, PROPOSAL_LIFETIME);
 
     mocca = blockchain.deployContract(voter1, MOCCA_CONTRACT, initMocca);
     moccaContract = new Mocca(getStateClient(), mocca);
@@ -92,8 +94,8 @@ public final class MoccaTest extends JunitContractTest {
   void deployWithEmptyListOfVoters() {
 
     List<Mocca.Voter> voters = List.of();
-    Mocca.Criteria criteria = new Mocca.Criteria(voters, 0);
-    final byte[] initMocca = Mocca.initialize(criteria, token);
+    Mocca.Criteria criteria = new Mocca.Criteria(voters, 0, 0);
+    final byte[] initMocca = Mocca.initialize(criteria, token, PROPOSAL_LIFETIME);
 
     assertThatThrownBy(() -> blockchain.deployContract(voter1, MOCCA_CONTRACT, initMocca))
         .hasMessageContaining("Cannot use a criteria without assigned voters.");
@@ -113,13 +115,100 @@ public final class MoccaTest extends JunitContractTest {
             new Mocca.Voter(voter3, 15),
             new Mocca.Voter(voter4, 30));
 
-    Mocca.Criteria criteria = new Mocca.Criteria(voters, 61);
-    final byte[] initMocca = Mocca.initialize(criteria, token);
+    Mocca.Criteria criteria = new Mocca.Criteria(voters, 61, 0);
+    final byte[] initMocca = Mocca.initialize(criteria, token, PROPOSAL_LIFETIME);
 
     assertThatThrownBy(() -> blockchain.deployContract(voter1, MOCCA_CONTRACT, initMocca))
         .hasMessageContaining("Threshold cannot be larger then the total weight of votes.");
   }
 
+  /** Deploying the MOCCA contract with a non-positive proposal lifetime is not allowed. */
+  @ContractTest(previous = "setup")
+  void deployWithNonPositiveProposalLifetime() {
+
+    List<Mocca.Voter> voters = List.of(new Mocca.Voter(voter1, 5));
+    Mocca.Criteria criteria = new Mocca.Criteria(voters, 5, 0);
+    final byte[] initMocca = Mocca.initialize(criteria, token, 0);
+
+    assertThatThrownBy(() -> blockchain.deployContract(voter1, MOCCA_CONTRACT, initMocca))
+        .hasMessageContaining("The lifetime of proposals must be positive.");
+  }
+
+  /** Deploying the MOCCA contract with a quorum larger than the total weight is not allowed. */
+  @ContractTest(previous = "setup")
+  void deployWithQuorumLargerThanTotalVoteWeight() {
+
+    List<Mocca.Voter> voters = List.of(new Mocca.Voter(voter1, 5), new Mocca.Voter(voter2, 10));
+    Mocca.Criteria criteria = new Mocca.Criteria(voters, 5, 16);
+    final byte[] initMocca = Mocca.initialize(criteria, token, PROPOSAL_LIFETIME);
+
+    assertThatThrownBy(() -> blockchain.deployContract(voter1, MOCCA_CONTRACT, initMocca))
+        .hasMessageContaining("Quorum cannot be larger then the total weight of votes.");
+  }
+
+  /** Deploy the MOCCA contract with a quorum, that is larger than the threshold. */
+  @ContractTest(previous = "setup")
+  void deployMoccaWithQuorum() {
+
+    List<Mocca.Voter> voters =
+        List.of(
+            new Mocca.Voter(voter1, 5),
+            new Mocca.Voter(voter2, 10),
+            new Mocca.Voter(voter3, 15),
+            new Mocca.Voter(voter4, 30));
+    Mocca.Criteria criteria = new Mocca.Criteria(voters, 30, 45);
+    final byte[] initMocca = Mocca.initialize(criteria, token, PROPOSAL_LIFETIME);
+
+    mocca = blockchain.deployContract(voter1, MOCCA_CONTRACT, initMocca);
+    moccaContract = new Mocca(getStateClient(), mocca);
+
+    List<Mocca.Voter> voterWeights = List.of(new Mocca.Voter(voter1, 5));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 5, 0);
+    blockchain.sendAction(
+        escrowUser, mocca, Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), ""));
+
+    assertThat(moccaContract.getState().criteria().quorum()).isEqualTo(45);
+  }
+
+  /** A proposal is not approved when the threshold is met, if the quorum is not reached. */
+  @ContractTest(previous = "deployMoccaWithQuorum")
+  void thresholdMetButQuorumNotReached() {
+
+    blockchain.sendAction(voter4, mocca, Mocca.vote(0, new Mocca.VoteYes()));
+
+    byte[] executeProposal = Mocca.execute(0);
+    assertThatThrownBy(() -> blockchain.sendAction(voter4, mocca, executeProposal))
+        .hasMessageContaining("Voting quorum not reached.");
+
+    assertThat(moccaContract.getState().proposal().get(0).result()).isNull();
+  }
+
+  /** A proposal is not denied when enough "No" votes are cast, if the quorum is not reached. */
+  @ContractTest(previous = "deployMoccaWithQuorum")
+  void denialThresholdMetButQuorumNotReached() {
+
+    blockchain.sendAction(voter4, mocca, Mocca.vote(0, new Mocca.VoteNo()));
+    blockchain.sendAction(voter1, mocca, Mocca.vote(0, new Mocca.VoteNo()));
+
+    byte[] executeProposal = Mocca.execute(0);
+    assertThatThrownBy(() -> blockchain.sendAction(voter4, mocca, executeProposal))
+        .hasMessageContaining("Voting quorum not reached.");
+  }
+
+  /** A proposal is approved when both the threshold is met and the quorum is reached. */
+  @ContractTest(previous = "deployMoccaWithQuorum")
+  void thresholdMetAndQuorumReached() {
+
+    blockchain.sendAction(voter4, mocca, Mocca.vote(0, new Mocca.VoteYes()));
+    blockchain.sendAction(voter3, mocca, Mocca.vote(0, new Mocca.VoteAbstain()));
+
+    blockchain.sendAction(voter4, mocca, Mocca.execute(0));
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+    assertThat(moccaState.proposal().get(0).result()).isInstanceOf(Mocca.VoteResultApproved.class);
+    assertThat(moccaState.criteria().voters()).hasSize(1);
+  }
+
   // Feature: Escrow tokens with MOCCA
 
   /** A user can escrow an amount of tokens to the Mocca contract. */
@@ -163,12 +252,14 @@ public final class MoccaTest extends JunitContractTest {
 
   // Feature: Transfer proposal.
 
-  /** A user can propose a transfer of funds. */
+  /** A user can propose a transfer of funds, with a description of the proposal. */
   @ContractTest(previous = "deployMocca")
   void proposeTransfer() {
 
     byte[] proposeTransfer =
-        Mocca.propose(new Mocca.ProposalTypeTransfer(BigInteger.valueOf(1000L), receivingUser));
+        Mocca.propose(
+            new Mocca.ProposalTypeTransfer(BigInteger.valueOf(1000L), receivingUser),
+            "Pay the receiving user for the audit.");
 
     blockchain.sendAction(escrowUser, mocca, proposeTransfer);
 
@@ -177,6 +268,22 @@ public final class MoccaTest extends JunitContractTest {
     assertThat(moccaState.proposal().getNextN(null, 100).size()).isEqualTo(1);
     assertThat(moccaState.proposal().get(0).proposalType())
         .isInstanceOf(Mocca.ProposalTypeTransfer.class);
+    assertThat(moccaState.proposal().get(0).description())
+        .isEqualTo("Pay the receiving user for the audit.");
+  }
+
+  /** A proposal cannot have a description longer than 1024 bytes. */
+  @ContractTest(previous = "deployMocca")
+  void proposeWithTooLongDescription() {
+    byte[] proposeTransfer =
+        Mocca.propose(
+            new Mocca.ProposalTypeTransfer(BigInteger.valueOf(1000L), receivingUser),
+            "a".repeat(1025));
+
+    assertThatThrownBy(() -> blockchain.sendAction(escrowUser, mocca, proposeTransfer))
+        .hasMessageContaining("The description cannot be longer than 1024 bytes.");
+
+    assertThat(moccaContract.getState().proposal().getNextN(null, 100)).isEmpty();
   }
 
   /**
@@ -194,7 +301,7 @@ public final class MoccaTest extends JunitContractTest {
     blockchain.sendAction(voter3, mocca, yesVote);
 
     Mocca.MoccaState moccaState = moccaContract.getState();
-    Token.TokenState tokenState = Token.TokenState.deserialize(blockchain.getContractState(token));
+    Token.TokenState tokenState = new Token(getStateClient(), token).getState();
 
     Mocca.Proposal proposal = moccaState.proposal().get(0);
 
@@ -209,7 +316,7 @@ public final class MoccaTest extends JunitContractTest {
     proposal = moccaState.proposal().get(0);
     assertThat(proposal.result()).isInstanceOf(Mocca.VoteResultApproved.class);
 
-    tokenState = Token.TokenState.deserialize(blockchain.getContractState(token));
+    tokenState = new Token(getStateClient(), token).getState();
     assertThat(tokenState.balances().get(receivingUser)).isEqualTo(1000);
   }
 
@@ -228,7 +335,7 @@ public final class MoccaTest extends JunitContractTest {
     blockchain.sendAction(voter4, mocca, noVote);
 
     moccaState = moccaContract.getState();
-    Token.TokenState tokenState = Token.TokenState.deserialize(blockchain.getContractState(token));
+    Token.TokenState tokenState = new Token(getStateClient(), token).getState();
 
     Mocca.Proposal proposal = moccaState.proposal().get(0);
 
@@ -241,7 +348,7 @@ public final class MoccaTest extends JunitContractTest {
     blockchain.sendAction(voter4, mocca, executeProposal);
 
     moccaState = moccaContract.getState();
-    tokenState = Token.TokenState.deserialize(blockchain.getContractState(token));
+    tokenState = new Token(getStateClient(), token).getState();
     proposal = moccaState.proposal().get(0);
 
     assertThat(proposal.result()).isInstanceOf(Mocca.VoteResultDenied.class);
@@ -259,11 +366,107 @@ public final class MoccaTest extends JunitContractTest {
     assertThat(moccaState.amountOfTokens()).isEqualTo(BigInteger.valueOf(10_000L));
 
     byte[] proposeTransfer =
-        Mocca.propose(new Mocca.ProposalTypeTransfer(BigInteger.valueOf(1000L), receivingUser));
+        Mocca.propose(new Mocca.ProposalTypeTransfer(BigInteger.valueOf(1000L), receivingUser), "");
 
     blockchain.sendAction(escrowUser, mocca, proposeTransfer);
   }
 
+  // Feature: Batch transfer proposal.
+
+  /** A batch transfer proposal pays out to all receivers, when approved and executed. */
+  @ContractTest(previous = "deployMocca")
+  void executeBatchTransfer() {
+    byte[] escrowTokens = Mocca.escrow(BigInteger.valueOf(10_000L));
+    blockchain.sendAction(escrowUser, mocca, escrowTokens);
+
+    blockchain.sendAction(escrowUser, mocca, Mocca.propose(batchTransferToThreeReceivers(), ""));
+
+    byte[] yesVote = Mocca.vote(0, new Mocca.VoteYes());
+    blockchain.sendAction(voter1, mocca, yesVote);
+    blockchain.sendAction(voter2, mocca, yesVote);
+    blockchain.sendAction(voter3, mocca, yesVote);
+
+    blockchain.sendAction(voter4, mocca, Mocca.execute(0));
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+    assertThat(moccaState.proposal().get(0).result()).isInstanceOf(Mocca.VoteResultApproved.class);
+    assertThat(moccaState.amountOfTokens()).isEqualTo(BigInteger.valueOf(4_000L));
+
+    Token.TokenState tokenState = new Token(getStateClient(), token).getState();
+    assertThat(tokenState.balances().get(receivingUser)).isEqualTo(1000);
+    assertThat(tokenState.balances().get(voter1)).isEqualTo(2000);
+    assertThat(tokenState.balances().get(voter2)).isEqualTo(3000);
+    assertThat(tokenState.balances().get(mocca)).isEqualTo(4000);
+  }
+
+  /**
+   * A batch transfer cannot be executed without escrowed tokens, and leaves the proposal and the
+   * amount of escrowed tokens unchanged.
+   */
+  @ContractTest(previous = "deployMocca")
+  void executeBatchTransferWithInsufficientFunds() {
+    blockchain.sendAction(escrowUser, mocca, Mocca.propose(batchTransferToThreeReceivers(), ""));
+
+    byte[] yesVote = Mocca.vote(0, new Mocca.VoteYes());
+    blockchain.sendAction(voter1, mocca, yesVote);
+    blockchain.sendAction(voter2, mocca, yesVote);
+    blockchain.sendAction(voter3, mocca, yesVote);
+
+    assertThatThrownBy(() -> blockchain.sendAction(voter4, mocca, Mocca.execute(0)))
+        .hasMessageContaining("The escrowed tokens (0) cannot cover the batch transfer of 6000");
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+    assertThat(moccaState.proposal().get(0).result()).isNull();
+    assertThat(moccaState.amountOfTokens()).isEqualTo(BigInteger.ZERO);
+  }
+
+  /**
+   * A batch transfer cannot be executed when the escrowed tokens only cover some of the payouts,
+   * such that no receiver is paid before the proposal is reset, and paid again on the next
+   * execution.
+   */
+  @ContractTest(previous = "deployMocca")
+  void executeBatchTransferWithPartialEscrow() {
+    byte[] escrowTokens = Mocca.escrow(BigInteger.valueOf(3_000L));
+    blockchain.sendAction(escrowUser, mocca, escrowTokens);
+
+    blockchain.sendAction(escrowUser, mocca, Mocca.propose(batchTransferToThreeReceivers(), ""));
+
+    byte[] yesVote = Mocca.vote(0, new Mocca.VoteYes());
+    blockchain.sendAction(voter1, mocca, yesVote);
+    blockchain.sendAction(voter2, mocca, yesVote);
+    blockchain.sendAction(voter3, mocca, yesVote);
+
+    assertThatThrownBy(() -> blockchain.sendAction(voter4, mocca, Mocca.execute(0)))
+        .hasMessageContaining("The escrowed tokens (3000) cannot cover the batch transfer of 6000");
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+    assertThat(moccaState.proposal().get(0).result()).isNull();
+    assertThat(moccaState.amountOfTokens()).isEqualTo(BigInteger.valueOf(3_000L));
+
+    Token.TokenState tokenState = new Token(getStateClient(), token).getState();
+    assertThat(tokenState.balances().get(receivingUser)).isNull();
+    assertThat(tokenState.balances().get(voter1)).isNull();
+    assertThat(tokenState.balances().get(mocca)).isEqualTo(3000);
+  }
+
+  /** A batch transfer proposal must contain at least one payout. */
+  @ContractTest(previous = "deployMocca")
+  void proposeBatchTransferWithoutPayouts() {
+    byte[] proposeBatchTransfer = Mocca.propose(new Mocca.ProposalTypeBatchTransfer(List.of()), "");
+
+    assertThatThrownBy(() -> blockchain.sendAction(escrowUser, mocca, proposeBatchTransfer))
+        .hasMessageContaining("Cannot propose a batch transfer without payouts.");
+  }
+
+  private Mocca.ProposalTypeBatchTransfer batchTransferToThreeReceivers() {
+    return new Mocca.ProposalTypeBatchTransfer(
+        List.of(
+            new Mocca.Payout(receivingUser, BigInteger.valueOf(1000L)),
+            new Mocca.Payout(voter1, BigInteger.valueOf(2000L)),
+            new Mocca.Payout(voter2, BigInteger.valueOf(3000L))));
+  }
+
   // Feature: New criteria proposal.
 
   /** A user can propose a new criteria to use. */
@@ -273,8 +476,8 @@ public final class MoccaTest extends JunitContractTest {
     List<Mocca.Voter> voterWeights =
         List.of(
             new Mocca.Voter(voter1, 5), new Mocca.Voter(voter2, 6), new Mocca.Voter(voter3, 10));
-    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11);
-    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11, 0);
+    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), "");
 
     blockchain.sendAction(escrowUser, mocca, proposeNewCriteria);
 
@@ -296,8 +499,8 @@ public final class MoccaTest extends JunitContractTest {
     List<Mocca.Voter> voterWeights =
         List.of(
             new Mocca.Voter(voter1, 5), new Mocca.Voter(voter2, 6), new Mocca.Voter(voter3, 10));
-    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11);
-    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11, 0);
+    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), "");
 
     blockchain.sendAction(escrowUser, mocca, proposeNewCriteria);
 
@@ -333,8 +536,8 @@ public final class MoccaTest extends JunitContractTest {
     List<Mocca.Voter> voterWeights =
         List.of(
             new Mocca.Voter(voter1, 5), new Mocca.Voter(voter2, 6), new Mocca.Voter(voter3, 10));
-    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11);
-    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11, 0);
+    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), "");
 
     blockchain.sendAction(escrowUser, mocca, proposeNewCriteria);
 
@@ -366,8 +569,8 @@ public final class MoccaTest extends JunitContractTest {
   @ContractTest(previous = "deployMocca")
   void newCriteriaWithEmptyListOfVoters() {
     List<Mocca.Voter> voterWeights = List.of();
-    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11);
-    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11, 0);
+    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), "");
 
     assertThatThrownBy(() -> blockchain.sendAction(escrowUser, mocca, proposeNewCriteria))
         .hasMessageContaining("Cannot use a criteria without assigned voters.");
@@ -382,13 +585,36 @@ public final class MoccaTest extends JunitContractTest {
     List<Mocca.Voter> voterWeights =
         List.of(
             new Mocca.Voter(voter1, 5), new Mocca.Voter(voter2, 6), new Mocca.Voter(voter3, 10));
-    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 22);
-    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 22, 0);
+    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), "");
 
     assertThatThrownBy(() -> blockchain.sendAction(escrowUser, mocca, proposeNewCriteria))
         .hasMessageContaining("Threshold cannot be larger then the total weight of votes.");
   }
 
+  /** A new criteria proposal, where a voter is registered more than once, is not allowed. */
+  @ContractTest(previous = "deployMocca")
+  void newCriteriaWithDuplicateVoter() {
+    List<Mocca.Voter> voterWeights =
+        List.of(new Mocca.Voter(voter1, 5), new Mocca.Voter(voter1, 6));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11, 0);
+    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), "");
+
+    assertThatThrownBy(() -> blockchain.sendAction(escrowUser, mocca, proposeNewCriteria))
+        .hasMessageContaining("is registered more than once.");
+  }
+
+  /** A new criteria proposal, where the threshold is zero, is not allowed. */
+  @ContractTest(previous = "deployMocca")
+  void newCriteriaWithZeroThreshold() {
+    List<Mocca.Voter> voterWeights = List.of(new Mocca.Voter(voter1, 5));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 0, 0);
+    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), "");
+
+    assertThatThrownBy(() -> blockchain.sendAction(escrowUser, mocca, proposeNewCriteria))
+        .hasMessageContaining("Threshold must be positive.");
+  }
+
   // Feature: Voting
 
   /** A voter can vote "Yes" to a new criteria proposal. */
@@ -527,19 +753,86 @@ public final class MoccaTest extends JunitContractTest {
     assertThat(moccaState.proposal().get(0).result()).isInstanceOf(Mocca.VoteResultDenied.class);
   }
 
+  /** The weight of "Abstain" votes does not count towards approving a proposal. */
+  @ContractTest(previous = "proposeTransfer")
+  void abstainWeightDoesNotCountTowardsApproval() {
+
+    blockchain.sendAction(voter1, mocca, Mocca.vote(0, new Mocca.VoteYes()));
+    blockchain.sendAction(voter4, mocca, Mocca.vote(0, new Mocca.VoteAbstain()));
+
+    byte[] executeProposal = Mocca.execute(0);
+    assertThatThrownBy(() -> blockchain.sendAction(voter4, mocca, executeProposal))
+        .hasMessageContaining("The voting was not conclusive.");
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+
+    assertThat(moccaState.proposal().get(0).votes().get(voter4).discriminant())
+        .isEqualTo(Mocca.VoteD.ABSTAIN);
+    assertThat(moccaState.proposal().get(0).result()).isNull();
+  }
+
+  /** The weight of "Abstain" votes does not count towards denying a proposal. */
+  @ContractTest(previous = "proposeTransfer")
+  void abstainWeightDoesNotCountTowardsDenial() {
+
+    byte[] noVote = Mocca.vote(0, new Mocca.VoteNo());
+    blockchain.sendAction(voter2, mocca, noVote);
+    blockchain.sendAction(voter3, mocca, noVote);
+    blockchain.sendAction(voter4, mocca, Mocca.vote(0, new Mocca.VoteAbstain()));
+
+    byte[] executeProposal = Mocca.execute(0);
+    assertThatThrownBy(() -> blockchain.sendAction(voter4, mocca, executeProposal))
+        .hasMessageContaining("The voting was not conclusive.");
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+
+    assertThat(moccaState.proposal().get(0).result()).isNull();
+  }
+
+  /** A proposal can be approved even though some voters abstain. */
+  @ContractTest(previous = "proposeNewCriteria")
+  void approvedWithAbstainVotes() {
+
+    byte[] yesVote = Mocca.vote(0, new Mocca.VoteYes());
+    blockchain.sendAction(voter1, mocca, Mocca.vote(0, new Mocca.VoteAbstain()));
+    blockchain.sendAction(voter2, mocca, Mocca.vote(0, new Mocca.VoteNo()));
+    blockchain.sendAction(voter4, mocca, yesVote);
+
+    byte[] executeProposal = Mocca.execute(0);
+    blockchain.sendAction(voter4, mocca, executeProposal);
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+
+    assertThat(moccaState.proposal().get(0).result()).isInstanceOf(Mocca.VoteResultApproved.class);
+  }
+
+  /** A voter can change its vote to "Abstain". */
+  @ContractTest(previous = "proposeTransfer")
+  void voterCanChangeVoteToAbstain() {
+
+    blockchain.sendAction(voter1, mocca, Mocca.vote(0, new Mocca.VoteYes()));
+    blockchain.sendAction(voter1, mocca, Mocca.vote(0, new Mocca.VoteAbstain()));
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+
+    assertThat(moccaState.proposal().get(0).votes().size()).isEqualTo(1);
+    assertThat(moccaState.proposal().get(0).votes().get(voter1).discriminant())
+        .isEqualTo(Mocca.VoteD.ABSTAIN);
+  }
+
   /** Votes from a prior voter list does not count, when a new criteria is instated. */
   @ContractTest(previous = "deployMocca")
   void oldVotesDoesNotCountWhenNewCriteriaIsSet() {
     List<Mocca.Voter> voterWeights =
         List.of(
             new Mocca.Voter(voter1, 5), new Mocca.Voter(voter2, 6), new Mocca.Voter(voter3, 10));
-    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11);
-    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria));
+    Mocca.Criteria newCriteria = new Mocca.Criteria(voterWeights, 11, 0);
+    byte[] proposeNewCriteria = Mocca.propose(new Mocca.ProposalTypeNewCriteria(newCriteria), "");
 
     blockchain.sendAction(escrowUser, mocca, proposeNewCriteria);
 
     byte[] proposeTransfer =
-        Mocca.propose(new Mocca.ProposalTypeTransfer(BigInteger.valueOf(1000L), receivingUser));
+        Mocca.propose(new Mocca.ProposalTypeTransfer(BigInteger.valueOf(1000L), receivingUser), "");
 
     blockchain.sendAction(escrowUser, mocca, proposeTransfer);
 
@@ -643,6 +936,50 @@ public final class MoccaTest extends JunitContractTest {
     assertThat(moccaState.amountOfTokens()).isEqualTo(10_000);
   }
 
+  /** A proposal can be executed right up until it expires. */
+  @ContractTest(previous = "deployMocca")
+  void executeProposalBeforeExpiry() {
+    setupTransferProposal();
+
+    byte[] yesVote = Mocca.vote(0, new Mocca.VoteYes());
+    blockchain.sendAction(voter1, mocca, yesVote);
+    blockchain.sendAction(voter2, mocca, yesVote);
+    blockchain.sendAction(voter3, mocca, yesVote);
+
+    long expiresAt = moccaContract.getState().proposal().get(0).expiresAtMillis();
+    assertThat(expiresAt)
+        .isLessThanOrEqualTo(blockchain.getBlockProductionTime() + PROPOSAL_LIFETIME);
+    blockchain.waitForBlockProductionTime(expiresAt);
+
+    byte[] executeProposal = Mocca.execute(0);
+    blockchain.sendAction(voter4, mocca, executeProposal);
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+    assertThat(moccaState.proposal().get(0).result()).isInstanceOf(Mocca.VoteResultApproved.class);
+  }
+
+  /** A proposal cannot be executed after it has expired, and is left without a result. */
+  @ContractTest(previous = "deployMocca")
+  void executeProposalAfterExpiry() {
+    setupTransferProposal();
+
+    byte[] yesVote = Mocca.vote(0, new Mocca.VoteYes());
+    blockchain.sendAction(voter1, mocca, yesVote);
+    blockchain.sendAction(voter2, mocca, yesVote);
+    blockchain.sendAction(voter3, mocca, yesVote);
+
+    long expiresAt = moccaContract.getState().proposal().get(0).expiresAtMillis();
+    blockchain.waitForBlockProductionTime(expiresAt + 1);
+
+    byte[] executeProposal = Mocca.execute(0);
+    assertThatThrownBy(() -> blockchain.sendAction(voter4, mocca, executeProposal))
+        .hasMessageContaining("The proposal has expired.");
+
+    Mocca.MoccaState moccaState = moccaContract.getState();
+    assertThat(moccaState.proposal().get(0).result()).isNull();
+    assertThat(moccaState.amountOfTokens()).isEqualTo(BigInteger.valueOf(10_000L));
+  }
+
   /** Cannot execute a proposol, if using the wrong id. */
   @ContractTest(previous = "proposeNewCriteria")
   void executeNonexistingProposal() {
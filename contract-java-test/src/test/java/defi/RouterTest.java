@@ -31,9 +31,14 @@ public final class RouterTest extends JunitContractTest {
 
     @Override
     public BigInteger getTokenBalance(BlockchainAddress tokenContract, BlockchainAddress key) {
-      final Token.TokenState state =
-          Token.TokenState.deserialize(blockchain.getContractState(tokenContract));
-      return state.balances().getOrDefault(key, BigInteger.ZERO);
+      final Token.TokenState state = new Token(getStateClient(), tokenContract).getState();
+      BigInteger val = state.balances().get(key);
+      return val != null ? val : BigInteger.ZERO;
+    }
+
+    @Override
+    public byte[] initializeTokenRpc(String name, String symbol, BigInteger totalSupply) {
+      return Token.initialize(name, symbol, (byte) 8, totalSupply, null);
     }
   }
 
@@ -54,5 +59,10 @@ public final class RouterTest extends JunitContractTest {
       BigInteger val = state.balances().get(key);
       return val != null ? val : BigInteger.ZERO;
     }
+
+    @Override
+    public byte[] initializeTokenRpc(String name, String symbol, BigInteger totalSupply) {
+      return TokenV2.initialize(name, symbol, (byte) 8, totalSupply);
+    }
   }
 }
@@ -2,14 +2,17 @@ package defi;
 
 import static org.assertj.core.api.Assertions.assertThat;
 import static org.assertj.core.api.Assertions.assertThatCode;
+import static org.assertj.core.api.Assertions.assertThatThrownBy;
 
 import com.partisiablockchain.BlockchainAddress;
 import com.partisiablockchain.language.abicodegen.DexSwapFactory;
 import com.partisiablockchain.language.abicodegen.LiquiditySwap;
+import com.partisiablockchain.language.abicodegen.LiquiditySwapLock;
 import com.partisiablockchain.language.abicodegen.Token;
 import com.partisiablockchain.language.junit.ContractBytes;
 import com.partisiablockchain.language.junit.ContractTest;
 import com.partisiablockchain.language.junit.JunitContractTest;
+import com.partisiablockchain.language.testenvironment.TxExecution;
 import defi.properties.LiquiditySwapBaseTest;
 import java.math.BigInteger;
 import java.nio.file.Path;
@@ -36,8 +39,10 @@ public final class SwapFactoryTest extends JunitContractTest {
 
   private BlockchainAddress token1;
   private BlockchainAddress token2;
+  private BlockchainAddress token3;
   private BlockchainAddress swapFactory;
   private BlockchainAddress swapAddress;
+  private BlockchainAddress failedDeploymentAddress;
   private DexSwapFactory swapFactoryContract;
 
   /** Setup for testing. Requires some token contracts to swap between. */
@@ -49,12 +54,18 @@ public final class SwapFactoryTest extends JunitContractTest {
     swapper = blockchain.newAccount(3);
 
     // Setup tokens
-    final byte[] initRpcEth = Token.initialize("Ethereum Ether", "ETH", (byte) 18, TOTAL_SUPPLY);
+    final byte[] initRpcEth =
+        Token.initialize("Ethereum Ether", "ETH", (byte) 18, TOTAL_SUPPLY, null);
     token1 = blockchain.deployContract(creator, TokenContractTest.CONTRACT_BYTES, initRpcEth);
 
-    final byte[] initRpcUsdCoin = Token.initialize("USD Coin", "USDC", (byte) 18, TOTAL_SUPPLY);
+    final byte[] initRpcUsdCoin =
+        Token.initialize("USD Coin", "USDC", (byte) 18, TOTAL_SUPPLY, null);
     token2 = blockchain.deployContract(creator, TokenContractTest.CONTRACT_BYTES, initRpcUsdCoin);
 
+    final byte[] initRpcTether =
+        Token.initialize("Tether USD", "USDT", (byte) 18, TOTAL_SUPPLY, null);
+    token3 = blockchain.deployContract(creator, TokenContractTest.CONTRACT_BYTES, initRpcTether);
+
     // Move some tokens to accounts
     transfer(token1, creator, liquidityProvider, INITIAL_ACCOUNT_TOKENS);
     transfer(token1, creator, swapper, INITIAL_ACCOUNT_TOKENS);
@@ -83,7 +94,9 @@ public final class SwapFactoryTest extends JunitContractTest {
     assertThat(state.permissionDeploySwap()).isEqualTo(permissionDeploySwap);
     assertThat(state.permissionDelistSwap()).isEqualTo(permissionDelistSwap);
     assertThat(state.swapContracts().getNextN(null, 100)).isEmpty();
+    assertThat(state.failedDeployments().getNextN(null, 100)).isEmpty();
     assertThat(state.swapContractBinary()).isNull();
+    assertThat(state.paused()).isFalse();
   }
 
   /** Cannot deploy a new swap before the contract code is uploaded. */
@@ -148,6 +161,7 @@ public final class SwapFactoryTest extends JunitContractTest {
     assertThat(info.contractVersion()).isEqualTo(1_0_0);
     assertThat(info.successfullyDeployed()).isTrue();
     assertThat(info.tokenPair()).isEqualTo(tokenPair);
+    assertThat(state.swapContractsByPair().get(tokenPair)).isEqualTo(swapAddress);
 
     // Check state of deployed contract
     final LiquiditySwap.LiquiditySwapContractState swapContractState =
@@ -233,6 +247,89 @@ public final class SwapFactoryTest extends JunitContractTest {
             "Versions must be increasing: Previous version 100 should be less than new version 10");
   }
 
+  /** Updating the binary with the same version as the current binary is rejected. */
+  @ContractTest(previous = "setContractCode")
+  void failWhenVersionIsUnchanged() {
+    assertThatCode(
+            () ->
+                updateSwapBinary(
+                    creator,
+                    1_0_0,
+                    LiquiditySwapTest.CONTRACT_BYTES.code(),
+                    LiquiditySwapTest.CONTRACT_BYTES.abi()))
+        .hasMessageContaining("Previous version 100 should be less than new version 100");
+  }
+
+  /**
+   * Users with permission_update_swap can force the binary back to an older version. The override
+   * is recorded in the state, and an event noting the rollback is emitted.
+   */
+  @ContractTest(previous = "setContractCode")
+  void forceSetSwapBinaryToLowerVersion() {
+    final byte[] rpc =
+        DexSwapFactory.forceSetSwapBinary(
+            LiquiditySwapTest.CONTRACT_BYTES.code(), LiquiditySwapTest.CONTRACT_BYTES.abi(), 1_0);
+    final TxExecution transaction = blockchain.sendActionAsync(creator, swapFactory, rpc);
+    final TxExecution action = blockchain.executeEventAsync(transaction.getContractInteraction());
+    assertThat(action.isSuccess()).isTrue();
+
+    final List<TxExecution> events = action.getSpawnedEvents();
+    assertThat(events).hasSize(1);
+    assertThat(events.get(0).getEvent().getEvent().getInner().target()).isEqualTo(swapFactory);
+    assertThat(blockchain.executeEventAsync(events.get(0)).isSuccess()).isTrue();
+
+    // Check state
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    assertThat(state.swapContractBinary().version()).isEqualTo(1_0);
+    assertThat(state.swapBinaryOverrides())
+        .containsExactly(new DexSwapFactory.SwapBinaryOverride(creator, 1_0_0L, 1_0L));
+
+    // Swap contracts can still be deployed
+    final byte[] rpcDeploy =
+        DexSwapFactory.deploySwapContract(tokenPair(token1, token2), (short) 3);
+    blockchain.sendAction(liquidityProvider, swapFactory, rpcDeploy);
+    final DexSwapFactory.SwapFactoryState stateAfterDeploy = swapFactoryContract.getState();
+    final BlockchainAddress deployedAddress =
+        stateAfterDeploy.swapContractsByPair().get(tokenPair(token1, token2));
+    assertThat(stateAfterDeploy.swapContracts().get(deployedAddress).contractVersion())
+        .isEqualTo(1_0);
+  }
+
+  /** Forcing the binary to a higher version does not emit a rollback event. */
+  @ContractTest(previous = "setContractCode")
+  void forceSetSwapBinaryToHigherVersion() {
+    final byte[] rpc =
+        DexSwapFactory.forceSetSwapBinary(
+            LiquiditySwapTest.CONTRACT_BYTES.code(), LiquiditySwapTest.CONTRACT_BYTES.abi(), 2_0_0);
+    final TxExecution transaction = blockchain.sendActionAsync(creator, swapFactory, rpc);
+    final TxExecution action = blockchain.executeEventAsync(transaction.getContractInteraction());
+    assertThat(action.isSuccess()).isTrue();
+    assertThat(action.getSpawnedEvents()).isEmpty();
+
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    assertThat(state.swapContractBinary().version()).isEqualTo(2_0_0);
+    assertThat(state.swapBinaryOverrides())
+        .containsExactly(new DexSwapFactory.SwapBinaryOverride(creator, 1_0_0L, 2_0_0L));
+  }
+
+  /** Users without permission_update_swap cannot force the binary. */
+  @ContractTest(previous = "setContractCode")
+  void forceSetSwapBinaryWithoutPermission() {
+    final byte[] rpc =
+        DexSwapFactory.forceSetSwapBinary(
+            LiquiditySwapTest.CONTRACT_BYTES.code(), LiquiditySwapTest.CONTRACT_BYTES.abi(), 1_0);
+    assertThatCode(() -> blockchain.sendAction(swapper, swapFactory, rpc))
+        .hasMessageContaining("did not have permission \"update swap\"");
+  }
+
+  /** Rollback events can only be sent by the swap factory itself. */
+  @ContractTest(previous = "setContractCode")
+  void rollbackEventCannotBeSentByUsers() {
+    final byte[] rpc = DexSwapFactory.swapBinaryRollbackEvent(1_0_0, 1_0);
+    assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpc))
+        .hasMessageContaining("Rollback events can only be sent by the swap factory itself.");
+  }
+
   /** Users with permission_deploy_swap can delist contracts. */
   @ContractTest(previous = "performSwap")
   void delistSwapContract() {
@@ -241,6 +338,22 @@ public final class SwapFactoryTest extends JunitContractTest {
 
     final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
     assertThat(state.swapContracts().getNextN(null, 100)).isEmpty();
+    assertThat(state.swapContractsByPair().getNextN(null, 100)).isEmpty();
+    assertThat(state.swapContractsByPair().get(tokenPair(token1, token2))).isNull();
+  }
+
+  /** A token pair can be deployed again, once the previous swap contract has been delisted. */
+  @ContractTest(previous = "delistSwapContract")
+  void redeploySwapContractAfterDelisting() {
+    final DexSwapFactory.TokenPair tokenPair = tokenPair(token1, token2);
+    final byte[] rpc = DexSwapFactory.deploySwapContract(tokenPair, (short) 3);
+    blockchain.sendAction(creator, swapFactory, rpc);
+
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    assertThat(state.swapContracts().getNextN(null, 100)).hasSize(1);
+    final BlockchainAddress redeployedAddress = state.swapContractsByPair().get(tokenPair);
+    assertThat(redeployedAddress).isNotNull().isNotEqualTo(swapAddress);
+    assertThat(state.swapContracts().get(redeployedAddress).successfullyDeployed()).isTrue();
   }
 
   /** Users without permission_deploy_swap is incapable of delisting contracts. */
@@ -295,17 +408,116 @@ public final class SwapFactoryTest extends JunitContractTest {
         .isGreaterThan(BigInteger.ONE);
   }
 
+  /** No swap contracts can be deployed while the factory is paused. */
+  @ContractTest(previous = "createFirstSwap")
+  void failWhenDeployingWhilePaused() {
+    blockchain.sendAction(creator, swapFactory, DexSwapFactory.pause());
+    assertThat(swapFactoryContract.getState().paused()).isTrue();
+
+    final DexSwapFactory.TokenPair tokenPair = tokenPair(token1, token3);
+    final byte[] rpc = DexSwapFactory.deploySwapContract(tokenPair, (short) 3);
+    assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpc))
+        .hasMessageContaining("Swap factory paused");
+
+    final byte[] rpcLock =
+        DexSwapFactory.deploySwapLockContract(
+            tokenPair, (short) 3, new DexSwapFactory.PermissionAnybody(), null);
+    assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpcLock))
+        .hasMessageContaining("Swap factory paused");
+
+    assertThat(swapFactoryContract.getState().swapContracts().getNextN(null, 100)).hasSize(1);
+  }
+
+  /** Swap contracts can be deployed again, once the factory has been unpaused. */
+  @ContractTest(previous = "failWhenDeployingWhilePaused")
+  void deployAfterUnpause() {
+    blockchain.sendAction(creator, swapFactory, DexSwapFactory.unpause());
+    assertThat(swapFactoryContract.getState().paused()).isFalse();
+
+    final byte[] rpc = DexSwapFactory.deploySwapContract(tokenPair(token1, token3), (short) 3);
+    blockchain.sendAction(creator, swapFactory, rpc);
+
+    assertThat(swapFactoryContract.getState().swapContracts().getNextN(null, 100)).hasSize(2);
+  }
+
+  /** Swap contracts can still be delisted while the factory is paused. */
+  @ContractTest(previous = "failWhenDeployingWhilePaused")
+  void delistWhilePaused() {
+    blockchain.sendAction(
+        liquidityProvider, swapFactory, DexSwapFactory.delistSwapContract(swapAddress));
+
+    assertThat(swapFactoryContract.getState().swapContracts().getNextN(null, 100)).isEmpty();
+  }
+
+  /** Users without permission_update_swap cannot pause or unpause the factory. */
+  @ContractTest(previous = "createFirstSwap")
+  void pauseWithoutPermission() {
+    assertThatCode(() -> blockchain.sendAction(swapper, swapFactory, DexSwapFactory.pause()))
+        .hasMessageContaining("did not have permission \"update swap\"");
+    assertThatCode(() -> blockchain.sendAction(swapper, swapFactory, DexSwapFactory.unpause()))
+        .hasMessageContaining("did not have permission \"update swap\"");
+    assertThat(swapFactoryContract.getState().paused()).isFalse();
+  }
+
   /** Multiple contracts can be deployed from the same factory. */
   @ContractTest(previous = "createFirstSwap")
   void createSecondSwapContract() {
-    // Deploy second swap contract with same token pair
-    final DexSwapFactory.TokenPair tokenPair = tokenPair(token1, token2);
+    // Deploy second swap contract with a different token pair
+    final DexSwapFactory.TokenPair tokenPair = tokenPair(token1, token3);
     final byte[] rpc = DexSwapFactory.deploySwapContract(tokenPair, (short) 3);
     blockchain.sendAction(creator, swapFactory, rpc);
 
     // Check state
     final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
     assertThat(state.swapContracts().getNextN(null, 100)).hasSize(2);
+    assertThat(state.swapContractsByPair().getNextN(null, 100)).hasSize(2);
+  }
+
+  /** Only a single listed swap contract can exist for each token pair. */
+  @ContractTest(previous = "createFirstSwap")
+  void failWhenDeployingDuplicatePair() {
+    final DexSwapFactory.TokenPair sortedPair = tokenPair(token1, token2);
+    final DexSwapFactory.TokenPair reversedPair =
+        new DexSwapFactory.TokenPair(sortedPair.tokenBAddress(), sortedPair.tokenAAddress());
+
+    for (final DexSwapFactory.TokenPair tokenPair : List.of(sortedPair, reversedPair)) {
+      final byte[] rpc = DexSwapFactory.deploySwapContract(tokenPair, (short) 3);
+      assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpc))
+          .hasMessageContaining("A swap contract already exists for the given token pair");
+
+      final byte[] rpcLock =
+          DexSwapFactory.deploySwapLockContract(
+              tokenPair, (short) 3, new DexSwapFactory.PermissionAnybody(), null);
+      assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpcLock))
+          .hasMessageContaining("A swap contract already exists for the given token pair");
+    }
+
+    // Check state
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    assertThat(state.swapContracts().getNextN(null, 100)).hasSize(1);
+    assertThat(state.swapContractsByPair().get(sortedPair)).isEqualTo(swapAddress);
+  }
+
+  /**
+   * Swap contracts are indexed by their token pair, independently of the order the tokens were
+   * given in when deploying.
+   */
+  @ContractTest(previous = "setContractCode")
+  void swapContractIsIndexedRegardlessOfTokenOrder() {
+    final DexSwapFactory.TokenPair sortedPair = tokenPair(token1, token2);
+    final DexSwapFactory.TokenPair reversedPair =
+        new DexSwapFactory.TokenPair(sortedPair.tokenBAddress(), sortedPair.tokenAAddress());
+
+    final byte[] rpc = DexSwapFactory.deploySwapContract(reversedPair, (short) 3);
+    blockchain.sendAction(creator, swapFactory, rpc);
+
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    final BlockchainAddress deployedAddress =
+        state.swapContracts().getNextN(null, 100).iterator().next().getKey();
+    assertThat(state.swapContracts().get(deployedAddress).tokenPair()).isEqualTo(reversedPair);
+    assertThat(state.swapContractsByPair().getNextN(null, 100)).hasSize(1);
+    assertThat(state.swapContractsByPair().get(sortedPair)).isEqualTo(deployedAddress);
+    assertThat(state.swapContractsByPair().get(reversedPair)).isNull();
   }
 
   /** Users are prevented from deployed a swap between identical tokens. */
@@ -324,6 +536,81 @@ public final class SwapFactoryTest extends JunitContractTest {
     assertThat(tokenPair(token1, token2)).isEqualTo(tokenPair(token2, token1));
   }
 
+  /**
+   * Swaps between identical tokens are rejected by the factory itself, before any contract is
+   * deployed, for both plain swap contracts and swap lock contracts.
+   */
+  @ContractTest(previous = "setContractCode")
+  void failWhenDeployingSelfSwapperBeforeDeployment() {
+    final DexSwapFactory.TokenPair tokenPair = new DexSwapFactory.TokenPair(token2, token2);
+
+    final byte[] rpc = DexSwapFactory.deploySwapContract(tokenPair, (short) 3);
+    assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpc))
+        .hasMessageContaining("Tokens A and B must not be the same contract");
+
+    final byte[] rpcLock =
+        DexSwapFactory.deploySwapLockContract(
+            tokenPair, (short) 3, new DexSwapFactory.PermissionAnybody(), null);
+    assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpcLock))
+        .hasMessageContaining("Tokens A and B must not be the same contract");
+
+    // Check state
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    assertThat(state.swapContracts().getNextN(null, 100)).isEmpty();
+    assertThat(state.swapContractsByPair().getNextN(null, 100)).isEmpty();
+
+    // Distinct tokens can still be deployed
+    final byte[] rpcDistinct =
+        DexSwapFactory.deploySwapContract(tokenPair(token2, token3), (short) 3);
+    blockchain.sendAction(creator, swapFactory, rpcDistinct);
+    assertThat(swapFactoryContract.getState().swapContracts().getNextN(null, 100)).hasSize(1);
+  }
+
+  /**
+   * Swap lock contracts deployed by the factory are initialized with the given lock duration, after
+   * which acquired locks are released automatically.
+   */
+  @ContractTest(previous = "setupFactory")
+  void deploySwapLockContractWithLockDuration() {
+    updateSwapBinary(
+        creator,
+        1_0_0,
+        LiquiditySwapLockTest.CONTRACT_BYTES.code(),
+        LiquiditySwapLockTest.CONTRACT_BYTES.abi());
+
+    final DexSwapFactory.TokenPair tokenPair = tokenPair(token1, token2);
+    final byte[] rpc =
+        DexSwapFactory.deploySwapLockContract(
+            tokenPair, (short) 3, new DexSwapFactory.PermissionAnybody(), 60_000L);
+    blockchain.sendAction(creator, swapFactory, rpc);
+
+    // Check state of dex
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    final BlockchainAddress swapLockAddress = state.swapContractsByPair().get(tokenPair);
+    final DexSwapFactory.SwapContractInfo info = state.swapContracts().get(swapLockAddress);
+    assertThat(info.successfullyDeployed()).isTrue();
+    assertThat(info.supportsLocks()).isTrue();
+
+    // Check state of deployed contract
+    final LiquiditySwapLock.LiquiditySwapContractState swapLockContractState =
+        LiquiditySwapLock.LiquiditySwapContractState.deserialize(
+            blockchain.getContractState(swapLockAddress));
+    assertThat(swapLockContractState.swapFeePerMille()).isEqualTo((short) 3);
+    assertThat(swapLockContractState.lockDurationMillis()).isEqualTo(60_000L);
+  }
+
+  /** Swap lock contracts cannot be deployed with a lock duration that is not positive. */
+  @ContractTest(previous = "setContractCode")
+  void failWhenDeployingSwapLockContractWithNonPositiveLockDuration() {
+    final byte[] rpc =
+        DexSwapFactory.deploySwapLockContract(
+            tokenPair(token1, token2), (short) 3, new DexSwapFactory.PermissionAnybody(), 0L);
+    assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpc))
+        .hasMessageContaining("Lock duration must be positive, but was 0 ms");
+
+    assertThat(swapFactoryContract.getState().swapContracts().getNextN(null, 100)).isEmpty();
+  }
+
   /**
    * Code updating checks that uploaded code contains WASM magic bytes, to prevent costly mistakes.
    */
@@ -348,6 +635,90 @@ public final class SwapFactoryTest extends JunitContractTest {
         .hasMessageContaining("ABI data invalid");
   }
 
+  /**
+   * When a swap contract fails to deploy, for example due to a faulty swap contract binary, the
+   * deployment is recorded as failed, and the swap contract is removed from the directory.
+   */
+  @ContractTest(previous = "setupFactory")
+  void failedDeploymentIsRecorded() {
+    // Upload a binary which cannot be initialized as a swap contract
+    updateSwapBinary(
+        creator,
+        1_0_0,
+        TokenContractTest.CONTRACT_BYTES.code(),
+        TokenContractTest.CONTRACT_BYTES.abi());
+
+    final DexSwapFactory.TokenPair tokenPair = tokenPair(token1, token2);
+    final byte[] rpc = DexSwapFactory.deploySwapContract(tokenPair, (short) 3);
+    assertThatThrownBy(() -> blockchain.sendAction(liquidityProvider, swapFactory, rpc))
+        .isInstanceOf(RuntimeException.class);
+
+    // Check state
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    assertThat(state.swapContracts().getNextN(null, 100)).isEmpty();
+    assertThat(state.swapContractsByPair().getNextN(null, 100)).isEmpty();
+    assertThat(state.failedDeployments().getNextN(null, 100)).hasSize(1);
+
+    failedDeploymentAddress =
+        state.failedDeployments().getNextN(null, 100).iterator().next().getKey();
+    final DexSwapFactory.FailedDeployment failedDeployment =
+        state.failedDeployments().get(failedDeploymentAddress);
+    assertThat(failedDeployment.tokenPair()).isEqualTo(tokenPair);
+    assertThat(failedDeployment.contractVersion()).isEqualTo(1_0_0);
+    assertThat(failedDeployment.swapFeePerMille()).isEqualTo((short) 3);
+    assertThat(failedDeployment.lockSettings()).isNull();
+  }
+
+  /**
+   * A failed deployment can be retried without specifying the parameters again. The retry uses the
+   * current swap contract binary.
+   */
+  @ContractTest(previous = "failedDeploymentIsRecorded")
+  void retryFailedDeployment() {
+    updateSwapBinary(
+        creator,
+        2_0_0,
+        LiquiditySwapTest.CONTRACT_BYTES.code(),
+        LiquiditySwapTest.CONTRACT_BYTES.abi());
+
+    final byte[] rpc = DexSwapFactory.retryDeploy(failedDeploymentAddress);
+    blockchain.sendAction(liquidityProvider, swapFactory, rpc);
+
+    // Check state
+    final DexSwapFactory.SwapFactoryState state = swapFactoryContract.getState();
+    assertThat(state.failedDeployments().getNextN(null, 100)).isEmpty();
+    assertThat(state.swapContracts().getNextN(null, 100)).hasSize(1);
+
+    final BlockchainAddress retriedAddress =
+        state.swapContractsByPair().get(tokenPair(token1, token2));
+    assertThat(retriedAddress).isNotNull().isNotEqualTo(failedDeploymentAddress);
+    final DexSwapFactory.SwapContractInfo info = state.swapContracts().get(retriedAddress);
+    assertThat(info.successfullyDeployed()).isTrue();
+    assertThat(info.contractVersion()).isEqualTo(2_0_0);
+    assertThat(info.supportsLocks()).isFalse();
+
+    final LiquiditySwap.LiquiditySwapContractState swapContractState =
+        LiquiditySwap.LiquiditySwapContractState.deserialize(
+            blockchain.getContractState(retriedAddress));
+    assertThat(swapContractState.swapFeePerMille()).isEqualTo((short) 3);
+  }
+
+  /** Users without permission_deploy_swap cannot retry failed deployments. */
+  @ContractTest(previous = "failedDeploymentIsRecorded")
+  void retryFailedDeploymentWithoutPermission() {
+    final byte[] rpc = DexSwapFactory.retryDeploy(failedDeploymentAddress);
+    assertThatCode(() -> blockchain.sendAction(swapper, swapFactory, rpc))
+        .hasMessageContaining("did not have permission \"deploy swap\"");
+  }
+
+  /** Only deployments that have failed can be retried. */
+  @ContractTest(previous = "createFirstSwap")
+  void retryUnknownDeployment() {
+    final byte[] rpc = DexSwapFactory.retryDeploy(swapAddress);
+    assertThatCode(() -> blockchain.sendAction(creator, swapFactory, rpc))
+        .hasMessageContaining("No failed deployment exists for the given address");
+  }
+
   /**
    * Swap contracts can be created with a fully open permission, if desired, allowing anyone to
    * deploy contracts.
@@ -371,11 +742,12 @@ public final class SwapFactoryTest extends JunitContractTest {
         LiquiditySwapTest.CONTRACT_BYTES.abi());
 
     // Deploy swaps
-    final DexSwapFactory.TokenPair tokenPair = tokenPair(token1, token2);
-    final byte[] rpc = DexSwapFactory.deploySwapContract(tokenPair, (short) 3);
-    blockchain.sendAction(creator, swapFactory, rpc);
-    blockchain.sendAction(liquidityProvider, swapFactory, rpc);
-    blockchain.sendAction(swapper, swapFactory, rpc);
+    final byte[] rpc12 = DexSwapFactory.deploySwapContract(tokenPair(token1, token2), (short) 3);
+    blockchain.sendAction(creator, swapFactory, rpc12);
+    final byte[] rpc13 = DexSwapFactory.deploySwapContract(tokenPair(token1, token3), (short) 3);
+    blockchain.sendAction(liquidityProvider, swapFactory, rpc13);
+    final byte[] rpc23 = DexSwapFactory.deploySwapContract(tokenPair(token2, token3), (short) 3);
+    blockchain.sendAction(swapper, swapFactory, rpc23);
   }
 
   private void transfer(
@@ -417,9 +789,9 @@ public final class SwapFactoryTest extends JunitContractTest {
   }
 
   private BigInteger tokenBalance(BlockchainAddress owner, BlockchainAddress tokenAddress) {
-    final Token.TokenState state =
-        Token.TokenState.deserialize(blockchain.getContractState(tokenAddress));
-    return state.balances().getOrDefault(owner, BigInteger.ZERO);
+    final Token.TokenState state = new Token(getStateClient(), tokenAddress).getState();
+    final BigInteger balance = state.balances().get(owner);
+    return balance != null ? balance : BigInteger.ZERO;
   }
 
   private void withdraw(BlockchainAddress owner, BlockchainAddress token, BigInteger amount) {
@@ -1,16 +1,28 @@
 package defi;
 
+import static org.assertj.core.api.Assertions.assertThat;
+import static org.assertj.core.api.Assertions.assertThatThrownBy;
+
 import com.partisiablockchain.BlockchainAddress;
+import com.partisiablockchain.crypto.Hash;
+import com.partisiablockchain.crypto.KeyPair;
+import com.partisiablockchain.crypto.Signature;
 import com.partisiablockchain.language.abicodegen.Token;
 import com.partisiablockchain.language.junit.ContractBytes;
+import com.partisiablockchain.language.junit.ContractTest;
+import com.partisiablockchain.language.junit.JunitContractTest;
 import com.partisiablockchain.language.junit.TestBlockchain;
+import com.partisiablockchain.language.junit.exceptions.ActionFailureException;
+import com.partisiablockchain.language.testenvironment.TxExecution;
 import defi.properties.Mpc20ExtensionApproveRelativeTest;
 import defi.properties.Mpc20ExtensionBulkTransferTest;
 import defi.properties.Mpc20StandardTest;
 import defi.util.Mpc20LikeState;
 import java.math.BigInteger;
 import java.nio.file.Path;
+import java.util.List;
 import java.util.Map;
+import java.util.stream.Collectors;
 import org.junit.jupiter.api.Nested;
 
 /** Test the {@link Token} contract. */
@@ -30,7 +42,8 @@ public final class TokenContractTest {
       byte decimals,
       BigInteger initialTokenSupply,
       ContractBytes contractBytes) {
-    final byte[] initRpc = Token.initialize(tokenName, tokenSymbol, decimals, initialTokenSupply);
+    final byte[] initRpc =
+        Token.initialize(tokenName, tokenSymbol, decimals, initialTokenSupply, null);
     return blockchain.deployContract(creator, contractBytes, initRpc);
   }
 
@@ -115,6 +128,634 @@ public final class TokenContractTest {
     }
   }
 
+  /** Minting and burning of tokens, changing the total supply. */
+  @Nested
+  final class MintAndBurn extends JunitContractTest {
+
+    private static final BigInteger INITIAL_SUPPLY = BigInteger.valueOf(1_000);
+
+    private BlockchainAddress owner;
+    private BlockchainAddress user;
+    private BlockchainAddress tokenContract;
+
+    /** The owner deploys a token contract, and transfers some tokens to a user. */
+    @ContractTest
+    void setup() {
+      owner = blockchain.newAccount(10);
+      user = blockchain.newAccount(11);
+
+      tokenContract =
+          deploy(
+              blockchain, owner, "My Cool Token", "COOL", (byte) 8, INITIAL_SUPPLY, CONTRACT_BYTES);
+      blockchain.sendAction(owner, tokenContract, Token.transfer(user, BigInteger.valueOf(100)));
+
+      assertThat(getState().totalSupply()).isEqualTo(INITIAL_SUPPLY);
+    }
+
+    /** The owner can mint tokens to an address that did not hold any tokens. */
+    @ContractTest(previous = "setup")
+    void mintToNewAddress() {
+      final BlockchainAddress receiver = blockchain.newAccount(12);
+      assertThat(getState().balances().get(receiver)).isNull();
+
+      blockchain.sendAction(owner, tokenContract, Token.mint(receiver, BigInteger.valueOf(500)));
+
+      final Token.TokenState state = getState();
+      assertThat(state.balances().get(receiver)).isEqualTo(BigInteger.valueOf(500));
+      assertThat(state.totalSupply()).isEqualTo(BigInteger.valueOf(1_500));
+    }
+
+    /** Users other than the owner cannot mint tokens. */
+    @ContractTest(previous = "setup")
+    void nonOwnerCannotMint() {
+      assertThatThrownBy(
+              () ->
+                  blockchain.sendAction(
+                      user, tokenContract, Token.mint(user, BigInteger.valueOf(500))))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Only the owner of the contract can mint tokens.");
+
+      final Token.TokenState state = getState();
+      assertThat(state.balances().get(user)).isEqualTo(BigInteger.valueOf(100));
+      assertThat(state.totalSupply()).isEqualTo(INITIAL_SUPPLY);
+    }
+
+    /** A user can burn all of their tokens, removing them from the balances. */
+    @ContractTest(previous = "setup")
+    void burnToZero() {
+      blockchain.sendAction(user, tokenContract, Token.burn(BigInteger.valueOf(100)));
+
+      final Token.TokenState state = getState();
+      assertThat(state.balances().get(user)).isNull();
+      assertThat(state.balances().get(owner)).isEqualTo(BigInteger.valueOf(900));
+      assertThat(state.totalSupply()).isEqualTo(BigInteger.valueOf(900));
+    }
+
+    /** A user cannot burn more tokens than they own. */
+    @ContractTest(previous = "setup")
+    void burnMoreThanOwned() {
+      assertThatThrownBy(
+              () -> blockchain.sendAction(user, tokenContract, Token.burn(BigInteger.valueOf(101))))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining(
+              "Insufficient COOL tokens for burn! Have 100, but trying to burn 101 (in minimal"
+                  + " units)");
+
+      assertThat(getState().totalSupply()).isEqualTo(INITIAL_SUPPLY);
+    }
+
+    /** An approved spender can burn tokens from the owner, spending the allowance. */
+    @ContractTest(previous = "setup")
+    void burnFromWithinAllowance() {
+      final BlockchainAddress spender = blockchain.newAccount(12);
+      blockchain.sendAction(user, tokenContract, Token.approve(spender, BigInteger.valueOf(60)));
+
+      blockchain.sendAction(spender, tokenContract, Token.burnFrom(user, BigInteger.valueOf(40)));
+
+      final Token.TokenState state = getState();
+      assertThat(state.balances().get(user)).isEqualTo(BigInteger.valueOf(60));
+      assertThat(state.balances().get(spender)).isNull();
+      assertThat(state.allowed().get(new Token.AllowedAddress(user, spender)))
+          .isEqualTo(BigInteger.valueOf(20));
+      assertThat(state.totalSupply()).isEqualTo(BigInteger.valueOf(960));
+    }
+
+    /** A spender cannot burn more tokens than it has been allowed to. */
+    @ContractTest(previous = "setup")
+    void burnFromExceedingAllowance() {
+      final BlockchainAddress spender = blockchain.newAccount(12);
+      blockchain.sendAction(user, tokenContract, Token.approve(spender, BigInteger.valueOf(30)));
+
+      assertThatThrownBy(
+              () ->
+                  blockchain.sendAction(
+                      spender, tokenContract, Token.burnFrom(user, BigInteger.valueOf(31))))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining(
+              "Insufficient COOL allowance for burn_from! Allowed 30, but trying to burn 31 (in"
+                  + " minimal units)");
+
+      final Token.TokenState state = getState();
+      assertThat(state.balances().get(user)).isEqualTo(BigInteger.valueOf(100));
+      assertThat(state.totalSupply()).isEqualTo(INITIAL_SUPPLY);
+    }
+
+    /** A spender cannot burn more tokens than the owner has, even when allowed to. */
+    @ContractTest(previous = "setup")
+    void burnFromMoreThanOwned() {
+      final BlockchainAddress spender = blockchain.newAccount(12);
+      blockchain.sendAction(user, tokenContract, Token.approve(spender, BigInteger.valueOf(500)));
+
+      assertThatThrownBy(
+              () ->
+                  blockchain.sendAction(
+                      spender, tokenContract, Token.burnFrom(user, BigInteger.valueOf(101))))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining(
+              "Insufficient COOL tokens for burn! Have 100, but trying to burn 101");
+
+      assertThat(getState().totalSupply()).isEqualTo(INITIAL_SUPPLY);
+    }
+
+    private Token.TokenState getState() {
+      return new Token(getStateClient(), tokenContract).getState();
+    }
+  }
+
+  /** Relative changes to allowances, using increase and decrease allowance. */
+  @Nested
+  final class IncreaseDecreaseAllowance extends JunitContractTest {
+
+    private BlockchainAddress owner;
+    private BlockchainAddress spender;
+    private BlockchainAddress tokenContract;
+
+    /** The owner deploys a token contract. */
+    @ContractTest
+    void setup() {
+      owner = blockchain.newAccount(10);
+      spender = blockchain.newAccount(11);
+
+      tokenContract =
+          deploy(
+              blockchain,
+              owner,
+              "My Cool Token",
+              "COOL",
+              (byte) 8,
+              BigInteger.valueOf(1_000),
+              CONTRACT_BYTES);
+
+      assertThat(allowance()).isNull();
+    }
+
+    /** Increasing an allowance, when nothing has been approved, approves the delta. */
+    @ContractTest(previous = "setup")
+    void increaseFromZero() {
+      blockchain.sendAction(
+          owner, tokenContract, Token.increaseAllowance(spender, BigInteger.valueOf(50)));
+      assertThat(allowance()).isEqualTo(BigInteger.valueOf(50));
+
+      blockchain.sendAction(
+          owner, tokenContract, Token.increaseAllowance(spender, BigInteger.valueOf(25)));
+      assertThat(allowance()).isEqualTo(BigInteger.valueOf(75));
+    }
+
+    /** Decreasing an allowance lowers the approved amount by the delta. */
+    @ContractTest(previous = "setup")
+    void decreaseAllowance() {
+      blockchain.sendAction(owner, tokenContract, Token.approve(spender, BigInteger.valueOf(50)));
+      blockchain.sendAction(
+          owner, tokenContract, Token.decreaseAllowance(spender, BigInteger.valueOf(20)));
+
+      assertThat(allowance()).isEqualTo(BigInteger.valueOf(30));
+    }
+
+    /** Decreasing an allowance below zero clamps it to zero, removing the allowance. */
+    @ContractTest(previous = "setup")
+    void decreaseBelowZeroClampsToZero() {
+      blockchain.sendAction(owner, tokenContract, Token.approve(spender, BigInteger.valueOf(50)));
+      blockchain.sendAction(
+          owner, tokenContract, Token.decreaseAllowance(spender, BigInteger.valueOf(51)));
+
+      assertThat(allowance()).isNull();
+    }
+
+    /** Approve still overwrites an allowance, that was increased. */
+    @ContractTest(previous = "setup")
+    void approveOverwritesIncreasedAllowance() {
+      blockchain.sendAction(
+          owner, tokenContract, Token.increaseAllowance(spender, BigInteger.valueOf(50)));
+      blockchain.sendAction(owner, tokenContract, Token.approve(spender, BigInteger.valueOf(10)));
+
+      assertThat(allowance()).isEqualTo(BigInteger.TEN);
+    }
+
+    private BigInteger allowance() {
+      final Token.TokenState state = new Token(getStateClient(), tokenContract).getState();
+      return new Mpc20State(state).allowance(owner, spender);
+    }
+  }
+
+  /** Allowance changes signed off-chain by the owner, submitted by another user. */
+  @Nested
+  final class Permits extends JunitContractTest {
+
+    private static final long DEADLINE = 10_000;
+
+    private final KeyPair ownerKey = new KeyPair(BigInteger.valueOf(10));
+    private BlockchainAddress owner;
+    private BlockchainAddress spender;
+    private BlockchainAddress relayer;
+    private BlockchainAddress tokenContract;
+
+    /** The owner deploys a token contract. */
+    @ContractTest
+    void setup() {
+      owner = blockchain.newAccount(10);
+      spender = blockchain.newAccount(11);
+      relayer = blockchain.newAccount(12);
+      assertThat(ownerKey.getPublic().createAddress()).isEqualTo(owner);
+
+      tokenContract =
+          deploy(
+              blockchain,
+              owner,
+              "My Cool Token",
+              "COOL",
+              (byte) 8,
+              BigInteger.valueOf(1_000),
+              CONTRACT_BYTES);
+    }
+
+    /** A permit signed by the owner sets the allowance, and can be spent by the spender. */
+    @ContractTest(previous = "setup")
+    void validPermit() {
+      final Token.Permit permit = permit(BigInteger.valueOf(50), 0, DEADLINE);
+      blockchain.sendAction(relayer, tokenContract, Token.permit(permit, sign(permit)));
+
+      assertThat(allowance()).isEqualTo(BigInteger.valueOf(50));
+      assertThat(getState().permitNonces().get(owner)).isEqualTo(1L);
+
+      blockchain.sendAction(
+          spender, tokenContract, Token.transferFrom(owner, spender, BigInteger.valueOf(50)));
+      assertThat(getState().balances().get(spender)).isEqualTo(BigInteger.valueOf(50));
+    }
+
+    /** A permit cannot be used twice, as its nonce has been used. */
+    @ContractTest(previous = "setup")
+    void replayedPermitIsRejected() {
+      final Token.Permit permit = permit(BigInteger.valueOf(50), 0, DEADLINE);
+      final byte[] rpc = Token.permit(permit, sign(permit));
+      blockchain.sendAction(relayer, tokenContract, rpc);
+      blockchain.sendAction(
+          spender, tokenContract, Token.transferFrom(owner, spender, BigInteger.valueOf(50)));
+
+      assertThatThrownBy(() -> blockchain.sendAction(relayer, tokenContract, rpc))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Invalid permit nonce. Expected 1, but got 0.");
+      assertThat(allowance()).isNull();
+    }
+
+    /** A permit cannot be used after its deadline. */
+    @ContractTest(previous = "setup")
+    void expiredPermitIsRejected() {
+      final Token.Permit permit = permit(BigInteger.valueOf(50), 0, DEADLINE);
+      final byte[] rpc = Token.permit(permit, sign(permit));
+      blockchain.waitForBlockProductionTime(DEADLINE + 1);
+
+      assertThatThrownBy(() -> blockchain.sendAction(relayer, tokenContract, rpc))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Permit expired at 10000");
+      assertThat(allowance()).isNull();
+    }
+
+    /** A permit signed by someone other than the owner is rejected. */
+    @ContractTest(previous = "setup")
+    void permitSignedByOtherUserIsRejected() {
+      final Token.Permit permit = permit(BigInteger.valueOf(50), 0, DEADLINE);
+      final Signature signature = new KeyPair(BigInteger.valueOf(11)).sign(messageHash(permit));
+
+      assertThatThrownBy(
+              () -> blockchain.sendAction(relayer, tokenContract, Token.permit(permit, signature)))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Permit is not signed by the owner");
+      assertThat(allowance()).isNull();
+    }
+
+    /** A frozen owner cannot grant allowances with a permit. */
+    @ContractTest(previous = "setup")
+    void permitOfFrozenOwnerIsRejected() {
+      blockchain.sendAction(owner, tokenContract, Token.freeze(owner));
+      final Token.Permit permit = permit(BigInteger.valueOf(50), 0, DEADLINE);
+      final byte[] rpc = Token.permit(permit, sign(permit));
+
+      assertThatThrownBy(() -> blockchain.sendAction(relayer, tokenContract, rpc))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("is frozen, and cannot approve spenders by permit.");
+      assertThat(allowance()).isNull();
+      assertThat(getState().permitNonces().get(owner)).isNull();
+    }
+
+    private Token.Permit permit(BigInteger amount, long nonce, long deadline) {
+      return new Token.Permit(owner, spender, amount, nonce, deadline);
+    }
+
+    private Signature sign(Token.Permit permit) {
+      return ownerKey.sign(messageHash(permit));
+    }
+
+    private Hash messageHash(Token.Permit permit) {
+      return Hash.create(
+          stream -> {
+            tokenContract.write(stream);
+            permit.owner().write(stream);
+            permit.spender().write(stream);
+            stream.writeUnsignedBigInteger(permit.amount(), 16);
+            stream.writeLong(permit.nonce());
+            stream.writeLong(permit.deadlineMillis());
+          });
+    }
+
+    private BigInteger allowance() {
+      return new Mpc20State(getState()).allowance(owner, spender);
+    }
+
+    private Token.TokenState getState() {
+      return new Token(getStateClient(), tokenContract).getState();
+    }
+  }
+
+  /** Freezing of individual accounts by the owner. */
+  @Nested
+  final class Freeze extends JunitContractTest {
+
+    private BlockchainAddress owner;
+    private BlockchainAddress user;
+    private BlockchainAddress spender;
+    private BlockchainAddress tokenContract;
+
+    /** The owner deploys a token contract, and transfers some tokens to a user. */
+    @ContractTest
+    void setup() {
+      owner = blockchain.newAccount(10);
+      user = blockchain.newAccount(11);
+      spender = blockchain.newAccount(12);
+
+      tokenContract =
+          deploy(
+              blockchain,
+              owner,
+              "My Cool Token",
+              "COOL",
+              (byte) 8,
+              BigInteger.valueOf(1_000),
+              CONTRACT_BYTES);
+      blockchain.sendAction(owner, tokenContract, Token.transfer(user, BigInteger.valueOf(100)));
+      blockchain.sendAction(user, tokenContract, Token.approve(spender, BigInteger.TEN));
+    }
+
+    /** A frozen account cannot send tokens, but keeps its balance. */
+    @ContractTest(previous = "setup")
+    void frozenAccountCannotSend() {
+      blockchain.sendAction(owner, tokenContract, Token.freeze(user));
+
+      assertThatThrownBy(
+              () ->
+                  blockchain.sendAction(user, tokenContract, Token.transfer(owner, BigInteger.TEN)))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("is frozen, and cannot send tokens.");
+      assertThat(getState().balances().get(user)).isEqualTo(BigInteger.valueOf(100));
+    }
+
+    /** Approved spenders cannot transfer tokens from a frozen account. */
+    @ContractTest(previous = "setup")
+    void frozenAccountCannotBeTransferredFrom() {
+      blockchain.sendAction(owner, tokenContract, Token.freeze(user));
+
+      assertThatThrownBy(
+              () ->
+                  blockchain.sendAction(
+                      spender, tokenContract, Token.transferFrom(user, spender, BigInteger.TEN)))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("is frozen, and cannot send tokens.");
+    }
+
+    /** A frozen account cannot be sent tokens. */
+    @ContractTest(previous = "setup")
+    void frozenAccountCannotReceive() {
+      blockchain.sendAction(owner, tokenContract, Token.freeze(user));
+
+      assertThatThrownBy(
+              () ->
+                  blockchain.sendAction(owner, tokenContract, Token.transfer(user, BigInteger.TEN)))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("is frozen, and cannot receive tokens.");
+      assertThatThrownBy(
+              () ->
+                  blockchain.sendAction(
+                      owner,
+                      tokenContract,
+                      Token.bulkTransfer(List.of(new Token.Transfer(user, BigInteger.TEN)))))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("is frozen, and cannot receive tokens.");
+    }
+
+    /** An unfrozen account can send and receive tokens again. */
+    @ContractTest(previous = "setup")
+    void unfrozenAccountCanTransferAgain() {
+      blockchain.sendAction(owner, tokenContract, Token.freeze(user));
+      blockchain.sendAction(owner, tokenContract, Token.unfreeze(user));
+
+      blockchain.sendAction(user, tokenContract, Token.transfer(owner, BigInteger.TEN));
+      blockchain.sendAction(owner, tokenContract, Token.transfer(user, BigInteger.ONE));
+
+      assertThat(getState().balances().get(user)).isEqualTo(BigInteger.valueOf(91));
+      assertThat(getState().frozenAccounts().get(user)).isNull();
+    }
+
+    /** Users other than the owner cannot freeze or unfreeze accounts. */
+    @ContractTest(previous = "setup")
+    void nonOwnerCannotFreeze() {
+      assertThatThrownBy(() -> blockchain.sendAction(user, tokenContract, Token.freeze(owner)))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Only the owner of the contract can freeze accounts.");
+      assertThatThrownBy(() -> blockchain.sendAction(user, tokenContract, Token.unfreeze(user)))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Only the owner of the contract can unfreeze accounts.");
+    }
+
+    private Token.TokenState getState() {
+      return new Token(getStateClient(), tokenContract).getState();
+    }
+  }
+
+  /** Upgrading the token contract. */
+  @Nested
+  final class Upgrade extends JunitContractTest {
+
+    private BlockchainAddress owner;
+    private BlockchainAddress user;
+    private BlockchainAddress tokenContract;
+
+    /** The owner deploys a token contract. */
+    @ContractTest
+    void setup() {
+      owner = blockchain.newAccount(10);
+      user = blockchain.newAccount(11);
+
+      tokenContract =
+          deploy(
+              blockchain,
+              owner,
+              "My Cool Token",
+              "COOL",
+              (byte) 8,
+              BigInteger.valueOf(1_000),
+              CONTRACT_BYTES);
+      blockchain.sendAction(owner, tokenContract, Token.transfer(user, BigInteger.TEN));
+    }
+
+    /** Users other than the owner cannot upgrade the contract, and balances are unchanged. */
+    @ContractTest(previous = "setup")
+    void userCannotUpgrade() {
+      assertThatThrownBy(
+              () -> blockchain.upgradeContract(user, tokenContract, CONTRACT_BYTES, new byte[0]))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Contract did not allow this upgrade");
+
+      final Token.TokenState state = new Token(getStateClient(), tokenContract).getState();
+      assertThat(state.balances().get(owner)).isEqualTo(BigInteger.valueOf(990));
+      assertThat(state.balances().get(user)).isEqualTo(BigInteger.TEN);
+    }
+
+    /** The owner can upgrade the contract, and balances are unchanged. */
+    @ContractTest(previous = "setup")
+    void ownerCanUpgrade() {
+      blockchain.upgradeContract(owner, tokenContract, CONTRACT_BYTES, new byte[0]);
+
+      final Token.TokenState state = new Token(getStateClient(), tokenContract).getState();
+      assertThat(state.balances().get(owner)).isEqualTo(BigInteger.valueOf(990));
+      assertThat(state.balances().get(user)).isEqualTo(BigInteger.TEN);
+      assertThat(state.totalSupply()).isEqualTo(BigInteger.valueOf(1_000));
+    }
+  }
+
+  /** Transfer events, emitted for every transfer, and the owner configurable transfer hook. */
+  @Nested
+  final class TransferEvents extends JunitContractTest {
+
+    private BlockchainAddress owner;
+    private BlockchainAddress user;
+    private BlockchainAddress tokenContract;
+    private BlockchainAddress hookContract;
+
+    /** The owner deploys a token contract, and a contract to use as transfer hook. */
+    @ContractTest
+    void setup() {
+      owner = blockchain.newAccount(10);
+      user = blockchain.newAccount(11);
+
+      tokenContract =
+          deploy(
+              blockchain,
+              owner,
+              "My Cool Token",
+              "COOL",
+              (byte) 8,
+              BigInteger.valueOf(1_000),
+              CONTRACT_BYTES);
+      hookContract =
+          deploy(blockchain, owner, "Hook", "HOOK", (byte) 8, BigInteger.ONE, CONTRACT_BYTES);
+
+      assertThat(getState().transferHook()).isNull();
+    }
+
+    /** A single transfer emits exactly one transfer event, sent to the token contract itself. */
+    @ContractTest(previous = "setup")
+    void singleTransferEmitsOneEvent() {
+      final List<TxExecution> events =
+          executeAndGetEvents(owner, Token.transfer(user, BigInteger.TEN));
+
+      assertThat(events).hasSize(1);
+      assertThat(target(events.get(0))).isEqualTo(tokenContract);
+      assertThat(events.get(0).isSuccess()).isTrue();
+    }
+
+    /** A transfer from emits exactly one transfer event. */
+    @ContractTest(previous = "setup")
+    void transferFromEmitsOneEvent() {
+      blockchain.sendAction(owner, tokenContract, Token.approve(user, BigInteger.TEN));
+
+      final List<TxExecution> events =
+          executeAndGetEvents(user, Token.transferFrom(owner, user, BigInteger.TEN));
+
+      assertThat(events).hasSize(1);
+      assertThat(events.get(0).isSuccess()).isTrue();
+    }
+
+    /** A bulk transfer of three transfers emits exactly three transfer events. */
+    @ContractTest(previous = "setup")
+    void bulkTransferEmitsEventPerTransfer() {
+      final List<Token.Transfer> transfers =
+          List.of(
+              new Token.Transfer(user, BigInteger.ONE),
+              new Token.Transfer(blockchain.newAccount(12), BigInteger.TWO),
+              new Token.Transfer(blockchain.newAccount(13), BigInteger.TEN));
+
+      final List<TxExecution> events = executeAndGetEvents(owner, Token.bulkTransfer(transfers));
+
+      assertThat(events).hasSize(3);
+      assertThat(events).allMatch(TxExecution::isSuccess);
+    }
+
+    /** When a transfer hook is set, transfer events are sent to the hook. */
+    @ContractTest(previous = "setup")
+    void transferEventsAreSentToHook() {
+      blockchain.sendAction(owner, tokenContract, Token.setTransferHook(hookContract));
+      assertThat(getState().transferHook()).isEqualTo(hookContract);
+
+      final List<TxExecution> events =
+          executeAndGetEvents(owner, Token.transfer(user, BigInteger.TEN));
+
+      assertThat(events).hasSize(1);
+      assertThat(target(events.get(0))).isEqualTo(hookContract);
+    }
+
+    /** A failing transfer hook does not revert the transfer it was notified of. */
+    @ContractTest(previous = "setup")
+    void failingHookDoesNotRevertTransfer() {
+      // The hook is another token contract, which rejects transfer events not sent by itself.
+      blockchain.sendAction(owner, tokenContract, Token.setTransferHook(hookContract));
+
+      final List<TxExecution> events =
+          executeAndGetEvents(owner, Token.transfer(user, BigInteger.TEN));
+
+      assertThat(events.get(0).isSuccess()).isFalse();
+      assertThat(getState().balances().get(user)).isEqualTo(BigInteger.TEN);
+    }
+
+    /** Users other than the owner cannot set the transfer hook. */
+    @ContractTest(previous = "setup")
+    void nonOwnerCannotSetTransferHook() {
+      assertThatThrownBy(
+              () -> blockchain.sendAction(user, tokenContract, Token.setTransferHook(user)))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Only the owner of the contract can set the transfer hook.");
+    }
+
+    /** Transfer events cannot be sent by users. */
+    @ContractTest(previous = "setup")
+    void userCannotSendTransferEvent() {
+      assertThatThrownBy(
+              () ->
+                  blockchain.sendAction(
+                      user, tokenContract, Token.transferEvent(owner, user, BigInteger.TEN)))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining("Transfer events can only be sent by the token contract itself.");
+    }
+
+    private List<TxExecution> executeAndGetEvents(BlockchainAddress sender, byte[] rpc) {
+      final TxExecution transaction = blockchain.sendActionAsync(sender, tokenContract, rpc);
+      final TxExecution action = blockchain.executeEventAsync(transaction.getContractInteraction());
+      assertThat(action.isSuccess()).isTrue();
+
+      final List<TxExecution> events = action.getSpawnedEvents();
+      for (final TxExecution event : events) {
+        blockchain.executeEventAsync(event);
+      }
+      return events;
+    }
+
+    private BlockchainAddress target(TxExecution event) {
+      return event.getEvent().getEvent().getInner().target();
+    }
+
+    private Token.TokenState getState() {
+      return new Token(getStateClient(), tokenContract).getState();
+    }
+  }
+
   private static final class Mpc20State implements Mpc20LikeState {
     Token.TokenState state;
 
@@ -144,13 +785,13 @@ public final class TokenContractTest {
 
     @Override
     public Map<BlockchainAddress, BigInteger> balances() {
-      return state.balances();
+      return state.balances().getNextN(null, 1000).stream()
+          .collect(Collectors.toMap(Map.Entry::getKey, Map.Entry::getValue));
     }
 
     @Override
     public BigInteger allowance(final BlockchainAddress owner, final BlockchainAddress spender) {
-      final var ownerAllowances = state.allowed().get(owner);
-      return ownerAllowances == null ? null : ownerAllowances.get(spender);
+      return state.allowed().get(new Token.AllowedAddress(owner, spender));
     }
   }
 }
@@ -31,6 +31,8 @@ public final class TokenSplitterTest extends JunitContractTest {
   private static final BigInteger INITIAL_TOKEN_SPLITTER_TOKENS =
       BigInteger.valueOf(100).multiply(BigInteger.TEN.pow(18));
 
+  private static final long SETTLEMENT_DEADLINE = 1000000L;
+
   private BlockchainAddress originalTokenAddress;
   private BlockchainAddress trueTokenAddress;
   private BlockchainAddress falseTokenAddress;
@@ -48,17 +50,18 @@ public final class TokenSplitterTest extends JunitContractTest {
     admin = blockchain.newAccount(2);
     arbitratorAddress = blockchain.newAccount(3);
 
-    final byte[] initOriginalToken = Token.initialize("USD Coin", "USDC", (byte) 18, TOTAL_SUPPLY);
+    final byte[] initOriginalToken =
+        Token.initialize("USD Coin", "USDC", (byte) 18, TOTAL_SUPPLY, null);
     originalTokenAddress =
         blockchain.deployContract(admin, TokenContractTest.CONTRACT_BYTES, initOriginalToken);
 
     final byte[] initTrueToken =
-        Token.initialize("USDC If True", "USDCIT", (byte) 18, TOTAL_SUPPLY);
+        Token.initialize("USDC If True", "USDCIT", (byte) 18, TOTAL_SUPPLY, null);
     trueTokenAddress =
         blockchain.deployContract(admin, TokenContractTest.CONTRACT_BYTES, initTrueToken);
 
     final byte[] initFalseToken =
-        Token.initialize("USDC If False", "USDCIF", (byte) 18, TOTAL_SUPPLY);
+        Token.initialize("USDC If False", "USDCIF", (byte) 18, TOTAL_SUPPLY, null);
     falseTokenAddress =
         blockchain.deployContract(admin, TokenContractTest.CONTRACT_BYTES, initFalseToken);
 
@@ -69,7 +72,11 @@ public final class TokenSplitterTest extends JunitContractTest {
             originalTokenAddress,
             trueTokenAddress,
             falseTokenAddress,
-            arbitratorAddress);
+            arbitratorAddress,
+            SETTLEMENT_DEADLINE,
+            BigInteger.ZERO,
+            0,
+            0L);
     tokenSplitterAddress =
         blockchain.deployContract(admin, TOKEN_SPLITTER_CONTRACT_BYTES, initTokenSplitter);
 
@@ -86,7 +93,16 @@ public final class TokenSplitterTest extends JunitContractTest {
     @Override
     protected byte[] initContractUnderTestRpc(BlockchainAddress token1, BlockchainAddress token2) {
       return PredictionMarketTokenSplitter.initialize(
-          "eventDesc", "eventSymbol", token1, token2, falseTokenAddress, arbitratorAddress);
+          "eventDesc",
+          "eventSymbol",
+          token1,
+          token2,
+          falseTokenAddress,
+          arbitratorAddress,
+          SETTLEMENT_DEADLINE,
+          BigInteger.ZERO,
+          0,
+          0L);
     }
 
     @Override
@@ -160,6 +176,53 @@ public final class TokenSplitterTest extends JunitContractTest {
     assertOriginalTokenBalance(client, BigInteger.ZERO);
   }
 
+  /**
+   * An account can split some amount of the original token from its balance, and have the
+   * resulting true and false tokens transferred directly to its wallet.
+   */
+  @ContractTest(previous = "setUp")
+  void canSplitAndWithdraw() {
+    depositInitialTokensAndPrepare();
+
+    transfer(originalTokenAddress, admin, client, BigInteger.TEN);
+    approve(client, originalTokenAddress, tokenSplitterAddress, BigInteger.TEN);
+    deposit(client, originalTokenAddress, BigInteger.TEN);
+
+    splitAndWithdraw(client, BigInteger.TEN);
+
+    assertThat(walletBalance(trueTokenAddress, client)).isEqualTo(BigInteger.TEN);
+    assertThat(walletBalance(falseTokenAddress, client)).isEqualTo(BigInteger.TEN);
+
+    assertTrueTokenBalance(client, BigInteger.ZERO);
+    assertFalseTokenBalance(client, BigInteger.ZERO);
+    assertOriginalTokenBalance(client, BigInteger.ZERO);
+
+    final BigInteger remaining = INITIAL_TOKEN_SPLITTER_TOKENS.subtract(BigInteger.TEN);
+    assertTrueTokenBalance(tokenSplitterAddress, remaining);
+    assertFalseTokenBalance(tokenSplitterAddress, remaining);
+    assertOriginalTokenBalance(tokenSplitterAddress, BigInteger.TEN);
+    assertThat(walletBalance(trueTokenAddress, tokenSplitterAddress)).isEqualTo(remaining);
+    assertThat(walletBalance(falseTokenAddress, tokenSplitterAddress)).isEqualTo(remaining);
+    assertThat(walletBalance(originalTokenAddress, tokenSplitterAddress))
+        .isEqualTo(BigInteger.TEN);
+  }
+
+  /** An account cannot split and withdraw more original tokens than it has deposited. */
+  @ContractTest(previous = "setUp")
+  void splitAndWithdrawInsufficientBalance() {
+    depositInitialTokensAndPrepare();
+
+    transfer(originalTokenAddress, admin, client, BigInteger.TEN);
+    approve(client, originalTokenAddress, tokenSplitterAddress, BigInteger.TEN);
+    deposit(client, originalTokenAddress, BigInteger.TEN);
+
+    assertThatThrownBy(() -> splitAndWithdraw(client, BigInteger.valueOf(11)))
+        .isInstanceOf(ActionFailureException.class);
+
+    assertOriginalTokenBalance(client, BigInteger.TEN);
+    assertThat(walletBalance(trueTokenAddress, client)).isNull();
+  }
+
   /** An account cannot split its tokens before the token splitter has been prepared. */
   @ContractTest(previous = "setUp")
   void splitBeforePrepared() {
@@ -248,6 +311,34 @@ public final class TokenSplitterTest extends JunitContractTest {
     assertThat(((PredictionMarketTokenSplitter.LifeStageSETTLED) lifeStage).outcome()).isTrue();
   }
 
+  /** Activation, settlement and cancellation events can only be sent by the contract itself. */
+  @ContractTest(previous = "setUp")
+  void userCannotSendEvents() {
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    client,
+                    tokenSplitterAddress,
+                    PredictionMarketTokenSplitter.activatedEvent(BigInteger.TEN)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Events can only be sent by the token splitter contract itself.");
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    client, tokenSplitterAddress, PredictionMarketTokenSplitter.settledEvent(true)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Events can only be sent by the token splitter contract itself.");
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    client, tokenSplitterAddress, PredictionMarketTokenSplitter.cancelledEvent()))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Events can only be sent by the token splitter contract itself.");
+
+    assertThat(tokenSplitter.getState().lifeStage())
+        .isInstanceOf(PredictionMarketTokenSplitter.LifeStagePREPARING.class);
+  }
+
   /** An address other than that of the arbitrator cannot settle the outcome of the event. */
   @ContractTest(previous = "setUp")
   void settleByNonArbitrator() {
@@ -344,17 +435,185 @@ public final class TokenSplitterTest extends JunitContractTest {
         .hasMessageContaining("Can only redeem if life stage is Settled");
   }
 
+  /** The arbitrator can settle the event right up to the settlement deadline. */
+  @ContractTest(previous = "setUp")
+  void settleAtDeadline() {
+    depositInitialTokensAndPrepare();
+
+    blockchain.waitForBlockProductionTime(SETTLEMENT_DEADLINE);
+
+    settle(arbitratorAddress, false);
+
+    assertThat(tokenSplitter.getState().lifeStage())
+        .isInstanceOf(PredictionMarketTokenSplitter.LifeStageSETTLED.class);
+  }
+
+  /** The event cannot be cancelled before the settlement deadline has passed. */
+  @ContractTest(previous = "setUp")
+  void cancelBeforeDeadline() {
+    depositInitialTokensAndPrepare();
+
+    blockchain.waitForBlockProductionTime(SETTLEMENT_DEADLINE);
+
+    assertThatThrownBy(() -> cancelUnsettled(client))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Cannot cancel the event before the settlement deadline has passed.");
+
+    assertThat(tokenSplitter.getState().lifeStage())
+        .isInstanceOf(PredictionMarketTokenSplitter.LifeStageACTIVE.class);
+  }
+
+  /** Anyone can cancel the event when the settlement deadline has passed. */
+  @ContractTest(previous = "setUp")
+  void cancelAfterDeadline() {
+    depositInitialTokensAndPrepare();
+
+    blockchain.waitForBlockProductionTime(SETTLEMENT_DEADLINE + 1);
+
+    cancelUnsettled(client);
+
+    assertThat(tokenSplitter.getState().lifeStage())
+        .isInstanceOf(PredictionMarketTokenSplitter.LifeStageCANCELLED.class);
+  }
+
+  /** The event cannot be cancelled when it has already been settled. */
+  @ContractTest(previous = "setUp")
+  void cancelAlreadySettled() {
+    depositInitialTokensAndPrepare();
+
+    settle(arbitratorAddress, true);
+
+    blockchain.waitForBlockProductionTime(SETTLEMENT_DEADLINE + 1);
+
+    assertThatThrownBy(() -> cancelUnsettled(client))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Can only cancel if life stage is Active");
+  }
+
+  /** The arbitrator cannot settle the event when it has been cancelled. */
+  @ContractTest(previous = "setUp")
+  void settleAfterCancelled() {
+    depositInitialTokensAndPrepare();
+
+    blockchain.waitForBlockProductionTime(SETTLEMENT_DEADLINE + 1);
+    cancelUnsettled(client);
+
+    assertThatThrownBy(() -> settle(arbitratorAddress, true))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Can only settle if life stage is Active");
+  }
+
+  /**
+   * When the event has been cancelled, an account can redeem its true and false tokens into
+   * original tokens, at half an original token each.
+   */
+  @ContractTest(previous = "setUp")
+  void redeemCancelled() {
+    depositInitialTokensAndPrepare();
+
+    transfer(originalTokenAddress, admin, client, BigInteger.TEN);
+    approve(client, originalTokenAddress, tokenSplitterAddress, BigInteger.TEN);
+    deposit(client, originalTokenAddress, BigInteger.TEN);
+
+    split(client, BigInteger.TEN);
+
+    blockchain.waitForBlockProductionTime(SETTLEMENT_DEADLINE + 1);
+    cancelUnsettled(client);
+
+    redeemCancelled(client, trueTokenAddress, BigInteger.TEN);
+    redeemCancelled(client, falseTokenAddress, BigInteger.TEN);
+
+    assertOriginalTokenBalance(client, BigInteger.TEN);
+    assertTrueTokenBalance(client, BigInteger.ZERO);
+    assertFalseTokenBalance(client, BigInteger.ZERO);
+  }
+
+  /**
+   * When the event has been cancelled, an account holding only true tokens can redeem them on their
+   * own, for example after having sold its false tokens elsewhere.
+   */
+  @ContractTest(previous = "setUp")
+  void redeemCancelledOneSided() {
+    depositInitialTokensAndPrepare();
+
+    transfer(originalTokenAddress, admin, client, BigInteger.TEN);
+    approve(client, originalTokenAddress, tokenSplitterAddress, BigInteger.TEN);
+    deposit(client, originalTokenAddress, BigInteger.TEN);
+
+    split(client, BigInteger.TEN);
+    withdraw(client, falseTokenAddress, BigInteger.TEN);
+
+    blockchain.waitForBlockProductionTime(SETTLEMENT_DEADLINE + 1);
+    cancelUnsettled(client);
+
+    redeemCancelled(client, trueTokenAddress, BigInteger.TEN);
+
+    assertOriginalTokenBalance(client, BigInteger.valueOf(5));
+    assertTrueTokenBalance(client, BigInteger.ZERO);
+    assertFalseTokenBalance(client, BigInteger.ZERO);
+  }
+
+  /** An account cannot use redeem when the event has been cancelled. */
+  @ContractTest(previous = "setUp")
+  void redeemWhenCancelled() {
+    depositInitialTokensAndPrepare();
+
+    blockchain.waitForBlockProductionTime(SETTLEMENT_DEADLINE + 1);
+    cancelUnsettled(client);
+
+    assertThatThrownBy(() -> redeem(client, BigInteger.TEN))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Can only redeem if life stage is Settled.");
+  }
+
+  /** Initializing with a settlement deadline in the past fails. */
+  @ContractTest(previous = "setUp")
+  void deployInvalidSettlementDeadline() {
+    byte[] initTokenSplitter =
+        PredictionMarketTokenSplitter.initialize(
+            "eventDesc",
+            "eventSymbol",
+            originalTokenAddress,
+            trueTokenAddress,
+            falseTokenAddress,
+            arbitratorAddress,
+            blockchain.getBlockProductionTime(),
+            BigInteger.ZERO,
+            0,
+            0L);
+
+    assertThatThrownBy(
+            () ->
+                blockchain.deployContract(admin, TOKEN_SPLITTER_CONTRACT_BYTES, initTokenSplitter))
+        .hasMessageContaining("Settlement deadline has to be in the future.");
+  }
+
   private void deposit(
       BlockchainAddress executor, BlockchainAddress tokenAddress, BigInteger amount) {
     final byte[] rpc = PredictionMarketTokenSplitter.deposit(tokenAddress, amount);
     blockchain.sendAction(executor, tokenSplitterAddress, rpc);
   }
 
+  private void withdraw(
+      BlockchainAddress executor, BlockchainAddress tokenAddress, BigInteger amount) {
+    final byte[] rpc = PredictionMarketTokenSplitter.withdraw(tokenAddress, amount, false);
+    blockchain.sendAction(executor, tokenSplitterAddress, rpc);
+  }
+
   private void split(BlockchainAddress executor, BigInteger amount) {
     final byte[] rpc = PredictionMarketTokenSplitter.split(amount);
     blockchain.sendAction(executor, tokenSplitterAddress, rpc);
   }
 
+  private void splitAndWithdraw(BlockchainAddress executor, BigInteger amount) {
+    final byte[] rpc = PredictionMarketTokenSplitter.splitAndWithdraw(amount, true);
+    blockchain.sendAction(executor, tokenSplitterAddress, rpc);
+  }
+
+  private BigInteger walletBalance(BlockchainAddress token, BlockchainAddress owner) {
+    return new Token(getStateClient(), token).getState().balances().get(owner);
+  }
+
   private void join(BlockchainAddress executor, BigInteger amount) {
     final byte[] rpc = PredictionMarketTokenSplitter.join(amount);
     blockchain.sendAction(executor, tokenSplitterAddress, rpc);
@@ -370,11 +629,22 @@ public final class TokenSplitterTest extends JunitContractTest {
     blockchain.sendAction(executor, tokenSplitterAddress, rpc);
   }
 
+  private void cancelUnsettled(BlockchainAddress executor) {
+    final byte[] rpc = PredictionMarketTokenSplitter.cancelUnsettled();
+    blockchain.sendAction(executor, tokenSplitterAddress, rpc);
+  }
+
   private void redeem(BlockchainAddress executor, BigInteger amount) {
     final byte[] rpc = PredictionMarketTokenSplitter.redeem(amount);
     blockchain.sendAction(executor, tokenSplitterAddress, rpc);
   }
 
+  private void redeemCancelled(
+      BlockchainAddress executor, BlockchainAddress tokenAddress, BigInteger amount) {
+    final byte[] rpc = PredictionMarketTokenSplitter.redeemCancelled(tokenAddress, amount);
+    blockchain.sendAction(executor, tokenSplitterAddress, rpc);
+  }
+
   private void assertOriginalTokenBalance(BlockchainAddress account, BigInteger balance) {
     assertThat(getTokenBalance(account).liquidityTokens()).isEqualTo(balance);
   }
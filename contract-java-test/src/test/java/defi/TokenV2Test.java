@@ -1,7 +1,6 @@
 package defi;
 
 import com.partisiablockchain.BlockchainAddress;
-import com.partisiablockchain.language.abicodegen.Token;
 import com.partisiablockchain.language.abicodegen.TokenV2;
 import com.partisiablockchain.language.junit.ContractBytes;
 import com.partisiablockchain.language.junit.TestBlockchain;
@@ -33,7 +32,7 @@ public final class TokenV2Test {
       byte decimals,
       BigInteger initialTokenSupply,
       ContractBytes contractBytes) {
-    final byte[] initRpc = Token.initialize(tokenName, tokenSymbol, decimals, initialTokenSupply);
+    final byte[] initRpc = TokenV2.initialize(tokenName, tokenSymbol, decimals, initialTokenSupply);
     return blockchain.deployContract(creator, contractBytes, initRpc);
   }
 
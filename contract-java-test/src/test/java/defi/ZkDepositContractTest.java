@@ -10,6 +10,7 @@ import defi.properties.DepositWithdrawTest;
 import defi.properties.ZkDepositTest;
 import java.math.BigInteger;
 import java.nio.file.Path;
+import java.util.List;
 import org.junit.jupiter.api.Nested;
 
 /** {@link ZkDeposit} testing. */
@@ -39,7 +40,8 @@ public final class ZkDepositContractTest {
 
     @Override
     protected byte[] initContractUnderTestRpc(BlockchainAddress token1, BlockchainAddress token2) {
-      return ZkDeposit.initialize(approver, token1);
+      return ZkDeposit.initialize(
+          List.of(approver), 1, token1, ZkDepositTest.MAX_QUEUE_LENGTH);
     }
 
     @Override
@@ -34,7 +34,7 @@ public final class ZkLiquiditySwapContractTest {
 
     @Override
     protected byte[] initContractUnderTestRpc(BlockchainAddress token1, BlockchainAddress token2) {
-      return ZkLiquiditySwap.initialize(token1, token2, (short) 0);
+      return ZkLiquiditySwap.initialize(token1, token2, (short) 0, (short) 1000);
     }
 
     @Override
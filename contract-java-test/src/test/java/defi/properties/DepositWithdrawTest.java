@@ -100,10 +100,10 @@ public abstract class DepositWithdrawTest extends JunitContractTest {
     users = List.of(creatorAddress, blockchain.newAccount(2));
 
     // Setup tokens
-    final byte[] initTokenA = Token.initialize("Token A", "AAA", (byte) 0, TOTAL_SUPPLY);
+    final byte[] initTokenA = Token.initialize("Token A", "AAA", (byte) 0, TOTAL_SUPPLY, null);
     contractTokenA = blockchain.deployContract(creatorAddress, contractBytesToken, initTokenA);
 
-    final byte[] initTokenB = Token.initialize("Token B", "BBB", (byte) 0, BigInteger.ZERO);
+    final byte[] initTokenB = Token.initialize("Token B", "BBB", (byte) 0, BigInteger.ZERO, null);
     contractTokenB = blockchain.deployContract(creatorAddress, contractBytesToken, initTokenB);
 
     // Setup swap
@@ -430,10 +430,9 @@ public abstract class DepositWithdrawTest extends JunitContractTest {
   }
 
   private void assertTokenAaaBalance(BlockchainAddress owner, BigInteger amount) {
-    final Token.TokenState state =
-        Token.TokenState.deserialize(blockchain.getContractState(contractTokenA));
-    final BigInteger balance = state.balances().getOrDefault(owner, BigInteger.ZERO);
-    Assertions.assertThat(balance).isEqualTo(amount);
+    final Token.TokenState state = new Token(getStateClient(), contractTokenA).getState();
+    final BigInteger balance = state.balances().get(owner);
+    Assertions.assertThat(balance != null ? balance : BigInteger.ZERO).isEqualTo(amount);
   }
 
   private void assertDepositAmount(BlockchainAddress owner, long amount) {
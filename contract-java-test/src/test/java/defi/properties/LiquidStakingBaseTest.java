@@ -19,6 +19,8 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
   public static final int STAKE_TOKEN_SUPPLY = 100000000;
   public static final long LENGTH_OF_COOLDOWN_PERIOD = 100;
   public static final long LENGTH_OF_REDEEM_PERIOD = 100;
+  public static final short INSTANT_UNSTAKE_PENALTY_PER_MILLE = 100;
+  public static final int MINIMUM_SUBMIT_AMOUNT = 5;
 
   protected static final int USER_1_FUNDS = 500;
   protected static final int USER_2_FUNDS = 1000;
@@ -32,7 +34,8 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
   public BlockchainAddress user2;
   public BlockchainAddress user3;
   public BlockchainAddress stakingResponsible;
-  public BlockchainAddress liquidStakingAdministrator;
+  public BlockchainAddress liquidStakingFeeAdministrator;
+  public BlockchainAddress liquidStakingOperationsAdministrator;
 
   /**
    * Helper function to wait for the redeem period to start. Redeem period: from
@@ -62,7 +65,18 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
    * @param amount The amount of stake tokens to submit.
    */
   protected final void submit(BlockchainAddress account, int amount) {
-    byte[] rpc = LiquidStaking.submit(BigInteger.valueOf(amount));
+    submit(account, amount, 0);
+  }
+
+  /**
+   * Helper function for making submit RPC with a slippage limit and invoking the submit action.
+   *
+   * @param account The account that invokes the action.
+   * @param amount The amount of stake tokens to submit.
+   * @param minLiquidOut The smallest amount of liquid tokens the account accepts to receive.
+   */
+  protected final void submit(BlockchainAddress account, int amount, int minLiquidOut) {
+    byte[] rpc = LiquidStaking.submit(BigInteger.valueOf(amount), BigInteger.valueOf(minLiquidOut));
     blockchain.sendAction(account, liquidStakingAddress, rpc);
   }
 
@@ -167,6 +181,91 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
     blockchain.sendAction(account, liquidStakingAddress, cancelRpc);
   }
 
+  /**
+   * Helper function for making instant unstake RPC and invoking the instant unstake action.
+   *
+   * @param account The account that invokes the action.
+   * @param amount The amount of liquid tokens to unstake.
+   */
+  protected final void instantUnstake(BlockchainAddress account, int amount) {
+    byte[] rpc = LiquidStaking.instantUnstake(BigInteger.valueOf(amount));
+    blockchain.sendAction(account, liquidStakingAddress, rpc);
+  }
+
+  /**
+   * Helper function for making change instant unstake penalty RPC and invoking the action.
+   *
+   * @param account The account that invokes the action.
+   * @param penaltyPerMille The new penalty in per mille.
+   */
+  protected final void changeInstantUnstakePenalty(BlockchainAddress account, int penaltyPerMille) {
+    byte[] rpc = LiquidStaking.changeInstantUnstakePenalty((short) penaltyPerMille);
+    blockchain.sendAction(account, liquidStakingAddress, rpc);
+  }
+
+  /**
+   * Helper function for making add to denylist RPC and invoking the add to denylist action.
+   *
+   * @param account The account that invokes the action.
+   * @param deniedAddress The address to deny.
+   */
+  protected final void addToDenylist(BlockchainAddress account, BlockchainAddress deniedAddress) {
+    byte[] rpc = LiquidStaking.addToDenylist(deniedAddress);
+    blockchain.sendAction(account, liquidStakingAddress, rpc);
+  }
+
+  /**
+   * Helper function for making remove from denylist RPC and invoking the remove from denylist
+   * action.
+   *
+   * @param account The account that invokes the action.
+   * @param deniedAddress The address to allow again.
+   */
+  protected final void removeFromDenylist(
+      BlockchainAddress account, BlockchainAddress deniedAddress) {
+    byte[] rpc = LiquidStaking.removeFromDenylist(deniedAddress);
+    blockchain.sendAction(account, liquidStakingAddress, rpc);
+  }
+
+  /**
+   * Helper function for making transfer RPC and invoking the transfer action.
+   *
+   * @param account The account that invokes the action.
+   * @param to The account receiving the liquid tokens.
+   * @param amount The amount of liquid tokens to transfer.
+   */
+  protected final void transferLiquid(BlockchainAddress account, BlockchainAddress to, int amount) {
+    byte[] rpc = LiquidStaking.transfer(to, BigInteger.valueOf(amount));
+    blockchain.sendAction(account, liquidStakingAddress, rpc);
+  }
+
+  /**
+   * Helper function for making approve RPC and invoking the approve action.
+   *
+   * @param account The account that invokes the action.
+   * @param spender The account allowed to transfer the liquid tokens.
+   * @param amount The amount of liquid tokens to approve.
+   */
+  protected final void approveLiquid(
+      BlockchainAddress account, BlockchainAddress spender, int amount) {
+    byte[] rpc = LiquidStaking.approve(spender, BigInteger.valueOf(amount));
+    blockchain.sendAction(account, liquidStakingAddress, rpc);
+  }
+
+  /**
+   * Helper function for making transfer from RPC and invoking the transfer from action.
+   *
+   * @param account The account that invokes the action.
+   * @param from The account sending the liquid tokens.
+   * @param to The account receiving the liquid tokens.
+   * @param amount The amount of liquid tokens to transfer.
+   */
+  protected final void transferLiquidFrom(
+      BlockchainAddress account, BlockchainAddress from, BlockchainAddress to, int amount) {
+    byte[] rpc = LiquidStaking.transferFrom(from, to, BigInteger.valueOf(amount));
+    blockchain.sendAction(account, liquidStakingAddress, rpc);
+  }
+
   /**
    * Retrieve the liquid token balance for a user.
    *
@@ -309,10 +408,12 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
     Token.TokenState tokenState = getTokenContractState();
     assertThat(tokenState.balances().get(user)).isEqualTo(balance);
 
+    final Token.AllowedAddress allowedAddress =
+        new Token.AllowedAddress(user, liquidStakingAddress);
     if (allowedAmount == 0) {
-      assertThat(tokenState.allowed().containsKey(user)).isFalse();
+      assertThat(tokenState.allowed().get(allowedAddress)).isNull();
     } else {
-      assertThat(tokenState.allowed().get(user).get(liquidStakingAddress)).isEqualTo(allowedAmount);
+      assertThat(tokenState.allowed().get(allowedAddress)).isEqualTo(allowedAmount);
     }
   }
 
@@ -389,7 +490,7 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
     }
 
     if (buyInPercentageAfterInitialSubmit != 0) {
-      changeBuyIn(liquidStakingAdministrator, buyInPercentageAfterInitialSubmit);
+      changeBuyIn(liquidStakingFeeAdministrator, buyInPercentageAfterInitialSubmit);
     }
   }
 
@@ -412,7 +513,8 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
     String tokenSymbol = "Test Coin";
 
     byte[] tokenInitRpc =
-        Token.initialize(tokenName, tokenSymbol, (byte) 18, BigInteger.valueOf(STAKE_TOKEN_SUPPLY));
+        Token.initialize(
+            tokenName, tokenSymbol, (byte) 18, BigInteger.valueOf(STAKE_TOKEN_SUPPLY), null);
     this.stakeTokenAddress =
         blockchain.deployContract(stakeTokenOwner, contractBytesToken, tokenInitRpc);
 
@@ -434,10 +536,13 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
         LiquidStaking.initialize(
             stakeTokenAddress,
             stakingResponsible,
-            liquidStakingAdministrator,
+            liquidStakingFeeAdministrator,
+            liquidStakingOperationsAdministrator,
             LENGTH_OF_COOLDOWN_PERIOD,
             LENGTH_OF_REDEEM_PERIOD,
             BigInteger.ZERO,
+            INSTANT_UNSTAKE_PENALTY_PER_MILLE,
+            BigInteger.valueOf(MINIMUM_SUBMIT_AMOUNT),
             "Liquid Staking Token",
             "LST",
             (byte) 4);
@@ -485,7 +590,8 @@ public abstract class LiquidStakingBaseTest extends JunitContractTest {
     user2 = blockchain.newAccount(5);
     user3 = blockchain.newAccount(6);
     stakingResponsible = blockchain.newAccount(7);
-    liquidStakingAdministrator = blockchain.newAccount(8);
+    liquidStakingFeeAdministrator = blockchain.newAccount(8);
+    liquidStakingOperationsAdministrator = blockchain.newAccount(9);
 
     stakeTokenAddress = deployStakeTokenContract(contractBytesToken);
 
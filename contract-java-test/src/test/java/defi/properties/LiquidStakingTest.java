@@ -9,6 +9,7 @@ import com.partisiablockchain.language.abicodegen.Token;
 import com.partisiablockchain.language.junit.ContractBytes;
 import com.partisiablockchain.language.junit.ContractTest;
 import com.partisiablockchain.language.junit.exceptions.ActionFailureException;
+import com.partisiablockchain.language.testenvironment.TxExecution;
 import java.math.BigInteger;
 import java.util.Objects;
 
@@ -81,6 +82,42 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertLiquidStakingStateInvariant();
   }
 
+  /** A user cannot submit less than the minimum submit amount for liquid staking. */
+  @ContractTest(previous = "setup")
+  void cannotSubmitBelowMinimumAmount() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+
+    assertThatThrownBy(() -> submit(user1, MINIMUM_SUBMIT_AMOUNT - 1))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot submit 4 tokens, which is below the minimum submit amount of 5.");
+
+    submit(user1, MINIMUM_SUBMIT_AMOUNT);
+
+    assertThat(totalPoolStakeToken()).isEqualTo(105);
+    assertThat(totalPoolLiquidToken()).isEqualTo(105);
+    assertThat(getLiquidBalance(user1)).isEqualTo(105);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /**
+   * A submit that would mint zero liquid tokens fails, instead of donating the stake tokens to the
+   * pool.
+   */
+  @ContractTest(previous = "setup")
+  void cannotSubmitForZeroLiquidTokens() {
+    initialSetupWithAsserts(10, 100, 0, 0);
+
+    assertThatThrownBy(() -> submit(user2, 10))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Submitting 10 stake tokens would mint zero liquid tokens.");
+
+    assertThat(totalPoolStakeToken()).isEqualTo(110);
+    assertThat(totalPoolLiquidToken()).isEqualTo(10);
+    assertThat(getLiquidBalance(user2)).isEqualTo(0);
+    assertLiquidStakingStateInvariant();
+  }
+
   /** A user can increase the amount of liquid staking by submitting tokens multiple times. */
   @ContractTest(previous = "setup")
   void userSubmitsSeveralTimes() {
@@ -154,12 +191,71 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertInitialLiquidStakingState();
   }
 
+  /** A user can submit with a slippage limit, which is met at the current exchange rate. */
+  @ContractTest(previous = "setup")
+  void submitWithinSlippageLimit() {
+    initialSetupWithAsserts(100, 100, 0, 0);
+
+    submit(user2, 100, 50);
+
+    assertPoolAmounts(300, 150);
+    assertThat(getLiquidBalance(user2)).isEqualTo(50);
+    assertTokenState(user2, USER_2_FUNDS - 100, USER_2_FUNDS - 100);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** A user cannot submit, if fewer liquid tokens than the slippage limit would be minted. */
+  @ContractTest(previous = "setup")
+  void cannotSubmitBelowSlippageLimit() {
+    initialSetupWithAsserts(100, 100, 0, 0);
+
+    assertThatThrownBy(() -> submit(user2, 100, 51))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Submitting 100 stake tokens would mint 50 liquid tokens, which is below the minimum"
+                + " of 51.");
+
+    assertPoolAmounts(200, 100);
+    assertThat(getLiquidBalance(user2)).isNull();
+    assertTokenState(user2, USER_2_FUNDS, USER_2_FUNDS);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /**
+   * If the exchange rate changes while the stake tokens are being transferred, such that fewer
+   * liquid tokens than the slippage limit would be minted, the transferred stake tokens are
+   * refunded to the user and no liquid tokens are minted.
+   */
+  @ContractTest(previous = "setup")
+  void submitRefundedWhenExchangeRateChangesBeforeMinting() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+
+    byte[] rpc = LiquidStaking.submit(BigInteger.valueOf(100), BigInteger.valueOf(100));
+    TxExecution submitAction = blockchain.sendActionAsync(user2, liquidStakingAddress, rpc);
+    TxExecution submitEvent = blockchain.executeEventAsync(submitAction.getContractInteraction());
+    TxExecution transferEvent = blockchain.executeEventAsync(submitEvent.getContractInteraction());
+    assertThat(transferEvent.isSuccess()).isTrue();
+
+    // Rewards are accrued before the submit callback, halving the amount of liquid tokens minted.
+    accrueRewards(stakingResponsible, 100);
+
+    TxExecution systemCallback = blockchain.executeEventAsync(transferEvent.getSystemCallback());
+    TxExecution submitCallback = blockchain.executeEvent(systemCallback.getContractCallback());
+    assertThat(submitCallback.isSuccess()).isTrue();
+
+    assertPoolAmounts(200, 100);
+    assertThat(getLiquidBalance(user2)).isNull();
+    assertTokenState(user2, USER_2_FUNDS, USER_2_FUNDS - 100);
+    assertTokenStateForLiquidStakingContract(100);
+    assertLiquidStakingStateInvariant();
+  }
+
   /** A user cannot submit if action does not contain enough gas to execute the events. */
   @ContractTest(previous = "setup")
   void submitNeedsEnoughGasForEvents() {
     initialSetupWithAsserts(50, 0, 0, 0);
 
-    byte[] rpc = LiquidStaking.submit(BigInteger.valueOf(20));
+    byte[] rpc = LiquidStaking.submit(BigInteger.valueOf(20), BigInteger.ZERO);
     assertThatThrownBy(() -> blockchain.sendAction(user1, liquidStakingAddress, rpc, 16988))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining("Cannot allocate gas for events");
@@ -255,7 +351,7 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
   void adminCannotWithdrawTokens() {
     initialSetupWithAsserts(100, 0, 0, 0);
 
-    assertThatThrownBy(() -> withdraw(liquidStakingAdministrator, 10))
+    assertThatThrownBy(() -> withdraw(liquidStakingFeeAdministrator, 10))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
             "Unauthorized to withdraw tokens. Only the registered staking responsible (at address:"
@@ -322,7 +418,7 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
   void adminCannotAccrueRewards() {
     initialSetupWithAsserts(50, 0, 50, 0);
 
-    assertThatThrownBy(() -> accrueRewards(liquidStakingAdministrator, 10))
+    assertThatThrownBy(() -> accrueRewards(liquidStakingFeeAdministrator, 10))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
             "Unauthorized to accrue rewards. Only the registered staking responsible (at address:"
@@ -631,7 +727,7 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
   void adminCannotDepositTokens() {
     initialSetupWithAsserts(100, 0, 50, 0);
 
-    assertThatThrownBy(() -> deposit(liquidStakingAdministrator, 10))
+    assertThatThrownBy(() -> deposit(liquidStakingFeeAdministrator, 10))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
             "Unauthorized to deposit tokens. Only the registered staking responsible (at address:"
@@ -881,20 +977,20 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertLiquidStakingStateInvariant();
   }
 
-  /** The administrator can change the buy in percentage. */
+  /** The fee administrator can change the buy in percentage. */
   @ContractTest(previous = "setup")
   void adminCanChangeBuyInPercentage() {
     assertInitialLiquidStakingState();
 
-    changeBuyIn(liquidStakingAdministrator, 10);
+    changeBuyIn(liquidStakingFeeAdministrator, 10);
 
     assertBuyInPercentage(true, 10);
     assertLiquidStakingStateInvariant();
   }
 
   /**
-   * A user cannot change the buy in percentage. Only the administrator has access to changing the
-   * buy in percentage.
+   * A user cannot change the buy in percentage. Only the fee administrator has access to changing
+   * the buy in percentage.
    */
   @ContractTest(previous = "setup")
   void userCannotChangeBuyIn() {
@@ -903,16 +999,17 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertThatThrownBy(() -> changeBuyIn(user1, 0))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
-            "Cannot change the buy-in percentage. Only the registered administrator (at address:"
-                + " 00B4D7BC4690C2FC52A27BA8734E8633B5613DD0ED) can change the buy-in percentage.");
+            "Cannot change the buy-in percentage. Only the registered fee administrator (at"
+                + " address: 00B4D7BC4690C2FC52A27BA8734E8633B5613DD0ED) can change the buy-in"
+                + " percentage.");
 
     assertBuyInPercentage(true, 10);
     assertLiquidStakingStateInvariant();
   }
 
   /**
-   * The staking responsible cannot change the buy in percentage. Only the administrator has access
-   * to changing the buy in percentage.
+   * The staking responsible cannot change the buy in percentage. Only the fee administrator has
+   * access to changing the buy in percentage.
    */
   @ContractTest(previous = "setup")
   void stakingResponsibleCannotChangeBuyIn() {
@@ -921,8 +1018,9 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertThatThrownBy(() -> changeBuyIn(stakingResponsible, 13))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
-            "Cannot change the buy-in percentage. Only the registered administrator (at address:"
-                + " 00B4D7BC4690C2FC52A27BA8734E8633B5613DD0ED) can change the buy-in percentage.");
+            "Cannot change the buy-in percentage. Only the registered fee administrator (at"
+                + " address: 00B4D7BC4690C2FC52A27BA8734E8633B5613DD0ED) can change the buy-in"
+                + " percentage.");
 
     assertBuyInPercentage(true, 5);
     assertLiquidStakingStateInvariant();
@@ -964,12 +1062,12 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertLiquidStakingStateInvariant();
   }
 
-  /** The administrator can disable the buy in and release all locked buy in tokens. */
+  /** The fee administrator can disable the buy in and release all locked buy in tokens. */
   @ContractTest(previous = "setup")
   void adminCanDisableBuyIn() {
     assertInitialLiquidStakingState();
 
-    changeBuyIn(liquidStakingAdministrator, 10);
+    changeBuyIn(liquidStakingFeeAdministrator, 10);
     submit(user1, 100);
 
     assertBuyInPercentage(true, 10);
@@ -979,7 +1077,7 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertTokenStateForLiquidStakingContract(100);
     assertLiquidStakingStateInvariant();
 
-    disableBuyIn(liquidStakingAdministrator);
+    disableBuyIn(liquidStakingFeeAdministrator);
 
     assertBuyInPercentage(false, 0);
     assertThat(getLiquidBalance(user1)).isEqualTo(100);
@@ -990,8 +1088,8 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
   }
 
   /**
-   * A user cannot disable the buy in. Only the administrator has access to disabling the buy in and
-   * releasing the locked buy in tokens.
+   * A user cannot disable the buy in. Only the fee administrator has access to disabling the buy
+   * in and releasing the locked buy in tokens.
    */
   @ContractTest(previous = "setup")
   void userCannotDisableBuyIn() {
@@ -1000,7 +1098,7 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertThatThrownBy(() -> disableBuyIn(user1))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
-            "Cannot disable buy-in. Only the registered administrator (at address:"
+            "Cannot disable buy-in. Only the registered fee administrator (at address:"
                 + " 00B4D7BC4690C2FC52A27BA8734E8633B5613DD0ED) can disable buy-in.");
 
     assertBuyInPercentage(true, 10);
@@ -1008,8 +1106,8 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
   }
 
   /**
-   * The staking responsible cannot disable the buy in. Only the administrator has access to
-   * disabling the buy in and releasing the locked buy in tokens.
+   * The staking responsible cannot disable the buy in. Only the fee administrator has access
+   * to disabling the buy in and releasing the locked buy in tokens.
    */
   @ContractTest(previous = "setup")
   void stakingResponsibleCannotDisableBuyIn() {
@@ -1018,22 +1116,22 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertThatThrownBy(() -> disableBuyIn(stakingResponsible))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
-            "Cannot disable buy-in. Only the registered administrator (at address:"
+            "Cannot disable buy-in. Only the registered fee administrator (at address:"
                 + " 00B4D7BC4690C2FC52A27BA8734E8633B5613DD0ED) can disable buy-in.");
 
     assertBuyInPercentage(true, 5);
     assertLiquidStakingStateInvariant();
   }
 
-  /** The liquidStakingAdministrator cannot disable buy-in, when it is already disabled. */
+  /** The liquidStakingFeeAdministrator cannot disable buy-in, when it is already disabled. */
   @ContractTest(previous = "setup")
   void adminCannotDisableBuyInWhenDisabled() {
     initialSetupWithAsserts(0, 0, 0, 5);
 
-    disableBuyIn(liquidStakingAdministrator);
+    disableBuyIn(liquidStakingFeeAdministrator);
     assertBuyInPercentage(false, 0);
 
-    assertThatThrownBy(() -> disableBuyIn(liquidStakingAdministrator))
+    assertThatThrownBy(() -> disableBuyIn(liquidStakingFeeAdministrator))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining("Cannot disable buy-in, when it is already disabled.");
 
@@ -1041,16 +1139,16 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertLiquidStakingStateInvariant();
   }
 
-  /** The liquidStakingAdministrator can enable the buy in by changing the buy in percentage. */
+  /** The liquidStakingFeeAdministrator can enable the buy in by changing the buy in percentage. */
   @ContractTest(previous = "setup")
   void adminEnablesBuyIn() {
     assertInitialLiquidStakingState();
 
-    disableBuyIn(liquidStakingAdministrator);
+    disableBuyIn(liquidStakingFeeAdministrator);
     assertBuyInPercentage(false, 0);
     assertLiquidStakingStateInvariant();
 
-    changeBuyIn(liquidStakingAdministrator, 13);
+    changeBuyIn(liquidStakingFeeAdministrator, 13);
 
     assertBuyInPercentage(true, 13);
     assertLiquidStakingStateInvariant();
@@ -1070,6 +1168,46 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertThat(getPendingUnlocks(user1)).isNull();
   }
 
+  /**
+   * Cancelling a pending unlock restores the amount of liquid tokens available for unlocking,
+   * without moving any tokens.
+   */
+  @ContractTest(previous = "setup")
+  void cancellingPendingUnlockRestoresAvailableLiquidity() {
+    initialSetupWithAsserts(60, 0, 0, 0);
+
+    requestUnlock(user1, 50);
+    assertThatThrownBy(() -> requestUnlock(user1, 20))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Unlock amount too large");
+
+    cancelPendingUnlock(user1, getPendingUnlocks(user1).get(0).id());
+
+    assertPoolAmounts(60, 60);
+    assertThat(getLiquidBalance(user1)).isEqualTo(60);
+    assertTokenStateForLiquidStakingContract(60);
+
+    requestUnlock(user1, 60);
+    assertThat(getPendingUnlocks(user1)).hasSize(1);
+    assertThat(getPendingUnlocks(user1).get(0).liquidAmount()).isEqualTo(60);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** The user cannot cancel a pending unlock with an unknown id. */
+  @ContractTest(previous = "setup")
+  void userCannotCancelUnknownPendingUnlock() {
+    initialSetupWithAsserts(60, 0, 0, 0);
+
+    requestUnlock(user1, 10);
+
+    assertThatThrownBy(() -> cancelPendingUnlock(user1, 99))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("User does not possess pending unlock with id: 99");
+
+    assertThat(getPendingUnlocks(user1)).hasSize(1);
+    assertLiquidStakingStateInvariant();
+  }
+
   /** The user can cancel their own pending expired unlocks. */
   @ContractTest(previous = "setup")
   void userCanCancelTheirOwnExpiredPendingUnlocks() {
@@ -1120,7 +1258,7 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertThat(getPendingUnlocks(user2)).isNull();
   }
 
-  /** The administrator can clean up pending unlocks. */
+  /** The operations administrator can clean up pending unlocks. */
   @ContractTest(previous = "setup")
   void adminCanCleanUpPendingUnlocks() {
     initialSetupWithAsserts(60, 0, 0, 0);
@@ -1150,7 +1288,7 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
 
     assertLiquidStakingStateInvariant();
 
-    cleanUpPendingUnlocks(liquidStakingAdministrator);
+    cleanUpPendingUnlocks(liquidStakingOperationsAdministrator);
 
     assertThat(getPendingUnlocks(user1)).isNull();
 
@@ -1205,8 +1343,8 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
   }
 
   /**
-   * A user cannot clean up pending unlocks. Only the administrator has access to clean up pending
-   * unlocks.
+   * A user cannot clean up pending unlocks. Only the operations administrator has access to
+   * clean up pending unlocks.
    */
   @ContractTest(previous = "setup")
   void userCannotCleanUpPendingUnlocks() {
@@ -1218,13 +1356,317 @@ public abstract class LiquidStakingTest extends LiquidStakingBaseTest {
     assertThatThrownBy(() -> cleanUpPendingUnlocks(user1))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
-            "Cannot clean up pending unlocks. Only the registered administrator (at address:"
-                + " 00B4D7BC4690C2FC52A27BA8734E8633B5613DD0ED) or staking responsible (at address:"
-                + " 00F96DB08DBEB7B777E48D993CAF474A410A9B629B) can clean up pending unlocks.");
+            "Cannot clean up pending unlocks. Only the registered operations administrator (at"
+                + " address: 0023778983E83ADBCC05A7BC46929F945C2E30645A) or staking responsible (at"
+                + " address: 00F96DB08DBEB7B777E48D993CAF474A410A9B629B) can clean up pending"
+                + " unlocks.");
 
     assertThat(getPendingUnlocks(user1)).isNotNull();
     assertThat(getPendingUnlocks(user1)).hasSize(1);
     assertThat(getPendingUnlocks(user1).get(0).liquidAmount()).isEqualTo(50);
     assertLiquidStakingStateInvariant();
   }
+
+  /**
+   * A user can instantly unstake liquid tokens. The stake tokens minus the penalty are paid out,
+   * while the penalty is retained in the stake token pool.
+   */
+  @ContractTest(previous = "setup")
+  void instantUnstakeRetainsPenaltyInPool() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+
+    instantUnstake(user1, 50);
+
+    // 50 stake tokens, minus 10% penalty.
+    assertPoolAmounts(55, 50);
+    assertThat(getLiquidBalance(user1)).isEqualTo(50);
+    assertTokenState(user1, USER_1_FUNDS - 100 + 45, USER_1_FUNDS - 100);
+    assertTokenStateForLiquidStakingContract(55);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** The penalty retained by instant unstaking benefits the remaining stakers. */
+  @ContractTest(previous = "setup")
+  void instantUnstakePenaltyBenefitsRemainingStakers() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+    submit(user2, 100);
+
+    instantUnstake(user1, 100);
+
+    assertPoolAmounts(110, 100);
+    requestUnlock(user2, 100);
+    assertThat(getPendingUnlocks(user2).get(0).stakeTokenAmount()).isEqualTo(110);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** Instant unstaking fails when the contract does not hold enough stake tokens. */
+  @ContractTest(previous = "setup")
+  void instantUnstakeFailsWithoutStakeTokensOnContract() {
+    initialSetupWithAsserts(100, 0, 100, 0);
+
+    assertThatThrownBy(() -> instantUnstake(user1, 10))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot instantly unstake. Requires 9 stake tokens, but only 0 are available on the"
+                + " contract.");
+
+    assertPoolAmounts(100, 100);
+    assertThat(getLiquidBalance(user1)).isEqualTo(100);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** A user cannot instantly unstake more liquid tokens than they own. */
+  @ContractTest(previous = "setup")
+  void cannotInstantUnstakeMoreThanBalance() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+
+    assertThatThrownBy(() -> instantUnstake(user1, 101))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Unlock amount too large");
+
+    assertPoolAmounts(100, 100);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** A user cannot instantly unstake zero tokens. */
+  @ContractTest(previous = "setup")
+  void cannotInstantUnstakeZeroTokens() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+
+    assertThatThrownBy(() -> instantUnstake(user1, 0))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Cannot instantly unstake zero tokens.");
+  }
+
+  /** The fee administrator can change the instant unstake penalty. */
+  @ContractTest(previous = "setup")
+  void adminCanChangeInstantUnstakePenalty() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+
+    changeInstantUnstakePenalty(liquidStakingFeeAdministrator, 0);
+    assertThat(getLiquidStakingState().instantUnstakePenaltyPerMille()).isEqualTo((short) 0);
+
+    instantUnstake(user1, 50);
+    assertPoolAmounts(50, 50);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** A user cannot change the instant unstake penalty. */
+  @ContractTest(previous = "setup")
+  void userCannotChangeInstantUnstakePenalty() {
+    assertThatThrownBy(() -> changeInstantUnstakePenalty(user1, 0))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot change the instant unstake penalty. Only the registered fee administrator");
+
+    assertThat(getLiquidStakingState().instantUnstakePenaltyPerMille())
+        .isEqualTo(INSTANT_UNSTAKE_PENALTY_PER_MILLE);
+  }
+
+  /** Rewards cannot be accrued while there are no liquid tokens to receive them. */
+  @ContractTest(previous = "setup")
+  void cannotAccrueRewardsWithoutLiquidTokens() {
+    assertInitialLiquidStakingState();
+
+    assertThatThrownBy(() -> accrueRewards(stakingResponsible, 50))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot accrue rewards when there are no liquid tokens to receive them.");
+
+    assertInitialLiquidStakingState();
+    submit(user1, 100);
+    assertThat(getLiquidBalance(user1)).isEqualTo(100);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /**
+   * When stake tokens are left in an otherwise empty pool, the next submission is exchanged at
+   * 1:1, and the leftover stake tokens are assigned to the fee administrator.
+   */
+  @ContractTest(previous = "setup")
+  void submitToEmptyPoolWithLeftoverStakeTokens() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+    instantUnstake(user1, 100);
+    assertPoolAmounts(10, 0);
+
+    submit(user2, 50);
+
+    assertThat(getLiquidBalance(user2)).isEqualTo(50);
+    assertThat(getLiquidBalance(liquidStakingFeeAdministrator)).isEqualTo(10);
+    assertPoolAmounts(60, 60);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** A denied user cannot submit, but can submit again once removed from the denylist. */
+  @ContractTest(previous = "setup")
+  void deniedUserCannotSubmit() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+    addToDenylist(liquidStakingOperationsAdministrator, user1);
+
+    assertThatThrownBy(() -> submit(user1, 50))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("is on the denylist, and cannot interact with the contract");
+    assertThat(getLiquidBalance(user1)).isEqualTo(100);
+
+    removeFromDenylist(liquidStakingOperationsAdministrator, user1);
+    submit(user1, 50);
+
+    assertThat(getLiquidBalance(user1)).isEqualTo(150);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** A denied user cannot transfer, but can transfer again once removed from the denylist. */
+  @ContractTest(previous = "setup")
+  void deniedUserCannotTransfer() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+    addToDenylist(liquidStakingOperationsAdministrator, user1);
+
+    assertThatThrownBy(() -> transferLiquid(user1, user2, 50))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("is on the denylist, and cannot interact with the contract");
+    assertThat(getLiquidBalance(user1)).isEqualTo(100);
+
+    removeFromDenylist(liquidStakingOperationsAdministrator, user1);
+    transferLiquid(user1, user2, 50);
+
+    assertThat(getLiquidBalance(user1)).isEqualTo(50);
+    assertThat(getLiquidBalance(user2)).isEqualTo(50);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /**
+   * Neither a denied spender nor a denied owner can transfer using transfer from, but can once
+   * removed from the denylist.
+   */
+  @ContractTest(previous = "setup")
+  void deniedUserCannotTransferFrom() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+    approveLiquid(user1, user2, 100);
+
+    addToDenylist(liquidStakingOperationsAdministrator, user2);
+    assertThatThrownBy(() -> transferLiquidFrom(user2, user1, user3, 50))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("is on the denylist, and cannot interact with the contract");
+    removeFromDenylist(liquidStakingOperationsAdministrator, user2);
+
+    addToDenylist(liquidStakingOperationsAdministrator, user1);
+    assertThatThrownBy(() -> transferLiquidFrom(user2, user1, user3, 50))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("is on the denylist, and cannot interact with the contract");
+    assertThat(getLiquidBalance(user1)).isEqualTo(100);
+
+    removeFromDenylist(liquidStakingOperationsAdministrator, user1);
+    transferLiquidFrom(user2, user1, user3, 50);
+
+    assertThat(getLiquidBalance(user1)).isEqualTo(50);
+    assertThat(getLiquidBalance(user3)).isEqualTo(50);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** A denied user cannot request unlocks, but can once removed from the denylist. */
+  @ContractTest(previous = "setup")
+  void deniedUserCannotRequestUnlock() {
+    initialSetupWithAsserts(100, 0, 0, 0);
+    addToDenylist(liquidStakingOperationsAdministrator, user1);
+
+    assertThatThrownBy(() -> requestUnlock(user1, 50))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("is on the denylist, and cannot interact with the contract");
+    assertThat(getPendingUnlocks(user1)).isNull();
+
+    removeFromDenylist(liquidStakingOperationsAdministrator, user1);
+    requestUnlock(user1, 50);
+
+    assertThat(getPendingUnlocks(user1)).hasSize(1);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** Only the operations administrator can change the denylist. */
+  @ContractTest(previous = "setup")
+  void userCannotChangeDenylist() {
+    assertThatThrownBy(() -> addToDenylist(user1, user2))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot change the denylist. Only the registered operations administrator");
+    assertThat(getLiquidStakingState().denylist().get(user2)).isNull();
+
+    addToDenylist(liquidStakingOperationsAdministrator, user2);
+    assertThatThrownBy(() -> removeFromDenylist(stakingResponsible, user2))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot change the denylist. Only the registered operations administrator");
+    assertThat(getLiquidStakingState().denylist().get(user2)).isTrue();
+  }
+
+  /** The fee administrator cannot change the denylist or clean up pending unlocks. */
+  @ContractTest(previous = "setup")
+  void feeAdministratorCannotPerformOperations() {
+    initialSetupWithAsserts(60, 0, 0, 0);
+
+    requestUnlock(user1, 50);
+    waitForRedeemPeriodToExpire();
+
+    assertThatThrownBy(() -> addToDenylist(liquidStakingFeeAdministrator, user2))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot change the denylist. Only the registered operations administrator");
+    assertThat(getLiquidStakingState().denylist().get(user2)).isNull();
+
+    assertThatThrownBy(() -> cleanUpPendingUnlocks(liquidStakingFeeAdministrator))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot clean up pending unlocks. Only the registered operations administrator");
+    assertThat(getPendingUnlocks(user1)).hasSize(1);
+  }
+
+  /** The operations administrator cannot change the buy in or the instant unstake penalty. */
+  @ContractTest(previous = "setup")
+  void operationsAdministratorCannotChangeFees() {
+    initialSetupWithAsserts(0, 0, 0, 10);
+
+    assertThatThrownBy(() -> changeBuyIn(liquidStakingOperationsAdministrator, 0))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot change the buy-in percentage. Only the registered fee administrator");
+    assertThatThrownBy(() -> disableBuyIn(liquidStakingOperationsAdministrator))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Cannot disable buy-in. Only the registered fee administrator");
+    assertThatThrownBy(() -> changeInstantUnstakePenalty(liquidStakingOperationsAdministrator, 0))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Cannot change the instant unstake penalty. Only the registered fee administrator");
+
+    assertBuyInPercentage(true, 10);
+    assertThat(getLiquidStakingState().instantUnstakePenaltyPerMille())
+        .isEqualTo(INSTANT_UNSTAKE_PENALTY_PER_MILLE);
+    assertLiquidStakingStateInvariant();
+  }
+
+  /** Submit, unlock and redeem events can only be sent by the contract itself. */
+  @ContractTest(previous = "setup")
+  void userCannotSendEvents() {
+    final BigInteger amount = BigInteger.TEN;
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    user1,
+                    liquidStakingAddress,
+                    LiquidStaking.submittedEvent(user1, amount, amount)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Events can only be sent by the liquid staking contract itself.");
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    user1,
+                    liquidStakingAddress,
+                    LiquidStaking.unlockRequestedEvent(user1, amount, 100L, 200L)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Events can only be sent by the liquid staking contract itself.");
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    user1, liquidStakingAddress, LiquidStaking.redeemedEvent(user1, amount)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Events can only be sent by the liquid staking contract itself.");
+  }
 }
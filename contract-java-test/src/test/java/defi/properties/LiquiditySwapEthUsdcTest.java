@@ -255,11 +255,11 @@ public abstract class LiquiditySwapEthUsdcTest extends LiquiditySwapBaseTest {
 
   private void initializeTokenContracts() {
     final byte[] initRpcEth =
-        Token.initialize("Ethereum Ether", "ETH", (byte) DECIMALS_ETH, TOTAL_SUPPLY_ETH);
+        Token.initialize("Ethereum Ether", "ETH", (byte) DECIMALS_ETH, TOTAL_SUPPLY_ETH, null);
     contractEth = blockchain.deployContract(creatorAddress, contractBytesToken, initRpcEth);
 
     final byte[] initRpcUsdCoin =
-        Token.initialize("USD Coin", "USDC", (byte) DECIMALS_USDC, TOTAL_SUPPLY_USDC);
+        Token.initialize("USD Coin", "USDC", (byte) DECIMALS_USDC, TOTAL_SUPPLY_USDC, null);
     contractUsdCoin = blockchain.deployContract(creatorAddress, contractBytesToken, initRpcUsdCoin);
   }
 }
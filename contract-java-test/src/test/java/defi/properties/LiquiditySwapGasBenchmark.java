@@ -3,6 +3,7 @@ package defi.properties;
 import com.partisiablockchain.BlockchainAddress;
 import com.partisiablockchain.language.abicodegen.LiquiditySwap;
 import com.partisiablockchain.language.abicodegen.Token;
+import com.partisiablockchain.language.abicodegen.TokenV2;
 import com.partisiablockchain.language.junit.ContractBytes;
 import com.partisiablockchain.language.junit.ContractTest;
 import defi.util.GasBenchmark;
@@ -56,10 +57,10 @@ public abstract class LiquiditySwapGasBenchmark extends LiquiditySwapBaseTest {
   void contractInit() {
     creatorAddress = blockchain.newAccount(1);
     // Setup tokens
-    final byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A);
+    final byte[] initRpcA = TokenV2.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A);
     contractTokenA = blockchain.deployContract(creatorAddress, contractBytesToken, initRpcA);
 
-    final byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B);
+    final byte[] initRpcB = TokenV2.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B);
     contractTokenB = blockchain.deployContract(creatorAddress, contractBytesToken, initRpcB);
 
     // Setup swap
@@ -30,11 +30,21 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
   private static final BigInteger INITIAL_LIQUIDITY_A = BigInteger.ONE.shiftLeft(60);
   private static final BigInteger INITIAL_LIQUIDITY_B = BigInteger.ONE.shiftLeft(59);
 
-  private static final BigInteger SWAP_CONSTANT = INITIAL_LIQUIDITY_A.multiply(INITIAL_LIQUIDITY_B);
+  private static final BigInteger INITIAL_LIQUIDITY_TOKENS =
+      INITIAL_LIQUIDITY_A.multiply(INITIAL_LIQUIDITY_B).sqrt();
+
+  /** Liquidity tokens locked in the pools when they are initialized. */
+  private static final BigInteger MINIMUM_LIQUIDITY = BigInteger.valueOf(100);
+
+  private static final BigInteger OWNER_LIQUIDITY_TOKENS =
+      INITIAL_LIQUIDITY_TOKENS.subtract(MINIMUM_LIQUIDITY);
 
   private static final BigInteger NON_OWNER_TOKEN_AMOUNT_A = BigInteger.ONE.shiftLeft(15);
   private static final BigInteger NON_OWNER_TOKEN_AMOUNT_B = BigInteger.ONE.shiftLeft(14);
 
+  private static final short MAX_SWAP_OUTPUT_PER_MILLE = 900;
+  private static final BigInteger SMALL_LIQUIDITY = BigInteger.valueOf(1000);
+
   public BlockchainAddress contractOwnerAddress;
   public BlockchainAddress nonOwnerAddress1;
   public BlockchainAddress nonOwnerAddress2;
@@ -66,10 +76,10 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     nonOwnerAddress2 = blockchain.newAccount(7);
 
     // Setup tokens.
-    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A);
+    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A, null);
     contractTokenA = blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcA);
 
-    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B);
+    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B, null);
     contractTokenB = blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcB);
 
     // Give our non owners some tokens to work with.
@@ -91,7 +101,9 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
         Token.transfer(nonOwnerAddress2, NON_OWNER_TOKEN_AMOUNT_B));
 
     // Setup swap contract.
-    byte[] initRpcSwap = ZkLiquiditySwap.initialize(contractTokenA, contractTokenB, (short) 0);
+    byte[] initRpcSwap =
+        ZkLiquiditySwap.initialize(
+            contractTokenA, contractTokenB, (short) 0, MAX_SWAP_OUTPUT_PER_MILLE);
     swapContractAddress =
         blockchain.deployZkContract(contractOwnerAddress, contractBytesSwap, initRpcSwap);
 
@@ -104,6 +116,7 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     Assertions.assertThat(state.swapConstant()).isEqualTo(0);
     assertHasLiquidity(state, false);
     Assertions.assertThat(state.worklist()).isEmpty();
+    Assertions.assertThat(state.maxSwapOutputPerMille()).isEqualTo(MAX_SWAP_OUTPUT_PER_MILLE);
 
     // Check initial value of balances in state.
     ZkLiquiditySwap.TokenBalances b = state.tokenBalances();
@@ -112,6 +125,24 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     Assertions.assertThat(b.balances().getNextN(null, 100)).isEmpty();
   }
 
+  /** The maximum swap output must be a positive part of the pool, of at most 1000 per mille. */
+  @ContractTest(previous = "contractInit")
+  void initWithInvalidMaximumSwapOutput() {
+    for (short maxSwapOutputPerMille : new short[] {0, 1001}) {
+      byte[] initRpcSwap =
+          ZkLiquiditySwap.initialize(
+              contractTokenA, contractTokenB, (short) 0, maxSwapOutputPerMille);
+      Assertions.assertThatCode(
+              () ->
+                  blockchain.deployZkContract(
+                      contractOwnerAddress, contractBytesSwap, initRpcSwap))
+          .isInstanceOf(RuntimeException.class)
+          .hasMessageContaining(
+              "Maximum swap output must be between 1 and 1000 per mille, but was "
+                  + maxSwapOutputPerMille);
+    }
+  }
+
   /** Tests that contract owner can deposit, and a new account is created. */
   @ContractTest(previous = "contractInit")
   void initialDepositFromOwner() {
@@ -141,12 +172,19 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
         swapContractAddress,
         ZkLiquiditySwap.provideInitialLiquidity(INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B));
 
-    // Tokens have been moved from owner to liquidity pool.
+    // Tokens have been moved from owner to liquidity pool, and liquidity tokens minted, except
+    // for the minimum liquidity locked in the pools.
     Assertions.assertThat(getDepositBalances())
-        .containsExactly(
+        .containsOnly(
             entry(
                 swapContractAddress,
-                new ZkLiquiditySwap.TokenBalance(INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B, ZERO)));
+                new ZkLiquiditySwap.TokenBalance(
+                    INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B, INITIAL_LIQUIDITY_TOKENS)),
+            entry(
+                contractOwnerAddress,
+                new ZkLiquiditySwap.TokenBalance(ZERO, ZERO, OWNER_LIQUIDITY_TOKENS)));
+    Assertions.assertThat(getSwapState().swapConstant())
+        .isEqualTo(INITIAL_LIQUIDITY_A.multiply(INITIAL_LIQUIDITY_B));
 
     // Contract state is now open.
     assertHasLiquidity(getSwapState(), true);
@@ -164,7 +202,7 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
                     ZkLiquiditySwap.provideInitialLiquidity(INITIAL_LIQUIDITY_A, BigInteger.ZERO)))
         .isInstanceOf(ActionFailureException.class)
         .hasMessageContaining(
-            "Contract pools should have been initialized after calling provide_initial_liquidity");
+            "Initial liquidity must mint more than 100 liquidity tokens, but would mint 0");
 
     // Contract state is still closed.
     assertHasLiquidity(getSwapState(), false);
@@ -185,7 +223,8 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     Assertions.assertThat(getDepositBalances())
         .containsEntry(
             contractOwnerAddress,
-            new ZkLiquiditySwap.TokenBalance(INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B, ZERO));
+            new ZkLiquiditySwap.TokenBalance(
+                INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B, OWNER_LIQUIDITY_TOKENS));
   }
 
   /** Non owner cannot close pool. */
@@ -200,6 +239,190 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
         .hasMessageContaining("Only the contract owner can close the pools");
   }
 
+  /** Non-owners can provide liquidity at the current pool ratio, and receive liquidity tokens. */
+  @ContractTest(previous = "initializePool")
+  void provideLiquidityProportional() {
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    depositIntoSwap(nonOwnerAddress1, contractTokenB, NON_OWNER_TOKEN_AMOUNT_B);
+
+    blockchain.sendAction(
+        nonOwnerAddress1,
+        swapContractAddress,
+        ZkLiquiditySwap.provideLiquidity(NON_OWNER_TOKEN_AMOUNT_A, NON_OWNER_TOKEN_AMOUNT_B));
+
+    final BigInteger mintedLiquidityTokens =
+        NON_OWNER_TOKEN_AMOUNT_A.multiply(INITIAL_LIQUIDITY_TOKENS).divide(INITIAL_LIQUIDITY_A);
+    final BigInteger poolA = INITIAL_LIQUIDITY_A.add(NON_OWNER_TOKEN_AMOUNT_A);
+    final BigInteger poolB = INITIAL_LIQUIDITY_B.add(NON_OWNER_TOKEN_AMOUNT_B);
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(
+            nonOwnerAddress1, new ZkLiquiditySwap.TokenBalance(ZERO, ZERO, mintedLiquidityTokens))
+        .containsEntry(
+            swapContractAddress,
+            new ZkLiquiditySwap.TokenBalance(
+                poolA, poolB, INITIAL_LIQUIDITY_TOKENS.add(mintedLiquidityTokens)));
+    Assertions.assertThat(getSwapState().swapConstant()).isEqualTo(poolA.multiply(poolB));
+
+    // Swaps use the updated pools.
+    depositIntoSwap(nonOwnerAddress2, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    final BigInteger receiving = calculateReceivingAmount(contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    swap(nonOwnerAddress2, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, false);
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(nonOwnerAddress2, createBalance(ZERO, receiving));
+  }
+
+  /** Removing half of the liquidity tokens returns half of the pools. */
+  @ContractTest(previous = "initializePool")
+  void removeHalfOfLiquidity() {
+    final BigInteger halfLiquidityTokens = INITIAL_LIQUIDITY_TOKENS.divide(BigInteger.TWO);
+    blockchain.sendAction(
+        contractOwnerAddress,
+        swapContractAddress,
+        ZkLiquiditySwap.removeLiquidity(halfLiquidityTokens));
+
+    final BigInteger outputA =
+        INITIAL_LIQUIDITY_A.multiply(halfLiquidityTokens).divide(INITIAL_LIQUIDITY_TOKENS);
+    final BigInteger outputB =
+        INITIAL_LIQUIDITY_B.multiply(halfLiquidityTokens).divide(INITIAL_LIQUIDITY_TOKENS);
+    Assertions.assertThat(outputA)
+        .isCloseTo(INITIAL_LIQUIDITY_A.divide(BigInteger.TWO), Assertions.within(BigInteger.ONE));
+    Assertions.assertThat(outputB)
+        .isCloseTo(INITIAL_LIQUIDITY_B.divide(BigInteger.TWO), Assertions.within(BigInteger.ONE));
+
+    final BigInteger poolA = INITIAL_LIQUIDITY_A.subtract(outputA);
+    final BigInteger poolB = INITIAL_LIQUIDITY_B.subtract(outputB);
+    Assertions.assertThat(getDepositBalances())
+        .containsOnly(
+            entry(
+                contractOwnerAddress,
+                new ZkLiquiditySwap.TokenBalance(
+                    outputA, outputB, OWNER_LIQUIDITY_TOKENS.subtract(halfLiquidityTokens))),
+            entry(
+                swapContractAddress,
+                new ZkLiquiditySwap.TokenBalance(
+                    poolA, poolB, INITIAL_LIQUIDITY_TOKENS.subtract(halfLiquidityTokens))));
+    Assertions.assertThat(getSwapState().swapConstant()).isEqualTo(poolA.multiply(poolB));
+    assertHasLiquidity(getSwapState(), true);
+  }
+
+  /**
+   * Removing all liquidity tokens of the last liquidity provider returns all of the pools, and
+   * burns the locked minimum liquidity.
+   */
+  @ContractTest(previous = "initializePool")
+  void removeAllLiquidity() {
+    blockchain.sendAction(
+        contractOwnerAddress,
+        swapContractAddress,
+        ZkLiquiditySwap.removeLiquidity(OWNER_LIQUIDITY_TOKENS));
+
+    Assertions.assertThat(getDepositBalances())
+        .containsOnly(
+            entry(contractOwnerAddress, createBalance(INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B)));
+    Assertions.assertThat(getSwapState().swapConstant()).isZero();
+    assertHasLiquidity(getSwapState(), false);
+  }
+
+  /**
+   * Removing all liquidity tokens of the owner while another liquidity provider remains returns
+   * all but the share of the locked minimum liquidity, which is retained in the pools until the
+   * last liquidity provider removes their liquidity.
+   */
+  @ContractTest(previous = "initializePool")
+  void removeAllLiquidityWhileOtherProviderRemains() {
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    depositIntoSwap(nonOwnerAddress1, contractTokenB, NON_OWNER_TOKEN_AMOUNT_B);
+    blockchain.sendAction(
+        nonOwnerAddress1,
+        swapContractAddress,
+        ZkLiquiditySwap.provideLiquidity(NON_OWNER_TOKEN_AMOUNT_A, NON_OWNER_TOKEN_AMOUNT_B));
+    final BigInteger minted =
+        NON_OWNER_TOKEN_AMOUNT_A.multiply(INITIAL_LIQUIDITY_TOKENS).divide(INITIAL_LIQUIDITY_A);
+
+    blockchain.sendAction(
+        contractOwnerAddress,
+        swapContractAddress,
+        ZkLiquiditySwap.removeLiquidity(OWNER_LIQUIDITY_TOKENS));
+
+    final BigInteger totalLiquidityTokens = INITIAL_LIQUIDITY_TOKENS.add(minted);
+    final BigInteger poolA = INITIAL_LIQUIDITY_A.add(NON_OWNER_TOKEN_AMOUNT_A);
+    final BigInteger poolB = INITIAL_LIQUIDITY_B.add(NON_OWNER_TOKEN_AMOUNT_B);
+    final BigInteger outputA = poolA.multiply(OWNER_LIQUIDITY_TOKENS).divide(totalLiquidityTokens);
+    final BigInteger outputB = poolB.multiply(OWNER_LIQUIDITY_TOKENS).divide(totalLiquidityTokens);
+    Assertions.assertThat(getDepositBalances())
+        .containsOnly(
+            entry(contractOwnerAddress, createBalance(outputA, outputB)),
+            entry(nonOwnerAddress1, new ZkLiquiditySwap.TokenBalance(ZERO, ZERO, minted)),
+            entry(
+                swapContractAddress,
+                new ZkLiquiditySwap.TokenBalance(
+                    poolA.subtract(outputA),
+                    poolB.subtract(outputB),
+                    minted.add(MINIMUM_LIQUIDITY))));
+    assertHasLiquidity(getSwapState(), true);
+
+    blockchain.sendAction(
+        nonOwnerAddress1, swapContractAddress, ZkLiquiditySwap.removeLiquidity(minted));
+
+    Assertions.assertThat(getDepositBalances())
+        .containsOnly(
+            entry(contractOwnerAddress, createBalance(outputA, outputB)),
+            entry(
+                nonOwnerAddress1, createBalance(poolA.subtract(outputA), poolB.subtract(outputB))));
+    assertHasLiquidity(getSwapState(), false);
+  }
+
+  /** Users cannot remove more liquidity tokens than they own. */
+  @ContractTest(previous = "initializePool")
+  void removeMoreLiquidityThanOwned() {
+    Assertions.assertThatCode(
+            () ->
+                blockchain.sendAction(
+                    nonOwnerAddress1,
+                    swapContractAddress,
+                    ZkLiquiditySwap.removeLiquidity(BigInteger.ONE)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Insufficient LiquidityToken deposit: 0/1");
+  }
+
+  /** Liquidity cannot be provided at a ratio that differs from the pools. */
+  @ContractTest(previous = "initializePool")
+  void provideLiquidityWithMismatchedRatio() {
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    depositIntoSwap(nonOwnerAddress1, contractTokenB, NON_OWNER_TOKEN_AMOUNT_B);
+
+    Assertions.assertThatCode(
+            () ->
+                blockchain.sendAction(
+                    nonOwnerAddress1,
+                    swapContractAddress,
+                    ZkLiquiditySwap.provideLiquidity(
+                        NON_OWNER_TOKEN_AMOUNT_A, NON_OWNER_TOKEN_AMOUNT_B.divide(BigInteger.TWO))))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Provided amounts do not match the pool ratio");
+
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(
+            nonOwnerAddress1, createBalance(NON_OWNER_TOKEN_AMOUNT_A, NON_OWNER_TOKEN_AMOUNT_B))
+        .containsEntry(
+            swapContractAddress,
+            new ZkLiquiditySwap.TokenBalance(
+                INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B, INITIAL_LIQUIDITY_TOKENS));
+  }
+
+  /** Liquidity cannot be provided before the pools have been initialized. */
+  @ContractTest(previous = "initialDepositFromOwner")
+  void provideLiquidityToClosedPools() {
+    Assertions.assertThatCode(
+            () ->
+                blockchain.sendAction(
+                    contractOwnerAddress,
+                    swapContractAddress,
+                    ZkLiquiditySwap.provideLiquidity(INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("The contract is closed");
+  }
+
   /** Test non owner deposit and withdraw without swapping. */
   @ContractTest(previous = "initializePool")
   void withdrawInitial() {
@@ -211,18 +434,18 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
         .containsEntry(
             nonOwnerAddress1,
             new ZkLiquiditySwap.TokenBalance(NON_OWNER_TOKEN_AMOUNT_A, ZERO, ZERO));
-    Assertions.assertThat(getTokenState(contractTokenA).balances())
-        .containsEntry(swapContractAddress, INITIAL_LIQUIDITY_A.add(NON_OWNER_TOKEN_AMOUNT_A));
+    Assertions.assertThat(getTokenState(contractTokenA).balances().get(swapContractAddress))
+        .isEqualTo(INITIAL_LIQUIDITY_A.add(NON_OWNER_TOKEN_AMOUNT_A));
 
     // Withdraw from contract.
     withdrawFromSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
 
     // Assert that tokens are back to original account.
     Assertions.assertThat(getDepositBalances()).doesNotContainKey(nonOwnerAddress1);
-    Assertions.assertThat(getTokenState(contractTokenA).balances())
-        .containsEntry(nonOwnerAddress1, NON_OWNER_TOKEN_AMOUNT_A);
-    Assertions.assertThat(getTokenState(contractTokenA).balances())
-        .containsEntry(swapContractAddress, INITIAL_LIQUIDITY_A);
+    Assertions.assertThat(getTokenState(contractTokenA).balances().get(nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
+    Assertions.assertThat(getTokenState(contractTokenA).balances().get(swapContractAddress))
+        .isEqualTo(INITIAL_LIQUIDITY_A);
   }
 
   /** Test non owner deposit, swap and withdraw. */
@@ -248,8 +471,95 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     Assertions.assertThat(getDepositBalances()).doesNotContainKey(nonOwnerAddress1);
 
     // Check that the tokens have been transferred.
-    Assertions.assertThat(getTokenState(contractTokenB).balances())
-        .containsEntry(nonOwnerAddress1, NON_OWNER_TOKEN_AMOUNT_B.add(receiving));
+    Assertions.assertThat(getTokenState(contractTokenB).balances().get(nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_B.add(receiving));
+  }
+
+  /** Swaps producing at least the minimum output are performed. */
+  @ContractTest(previous = "initializePool")
+  void swapMeetingMinimumOutput() {
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+
+    BigInteger receiving = calculateReceivingAmount(contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    swap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, false, receiving);
+
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(nonOwnerAddress1, createBalance(ZERO, receiving));
+  }
+
+  /** Swaps producing less than the minimum output fail, and the input stays with the user. */
+  @ContractTest(previous = "initializePool")
+  void swapViolatingMinimumOutput() {
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+
+    BigInteger receiving = calculateReceivingAmount(contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    BigInteger minOut = receiving.add(BigInteger.ONE);
+    Assertions.assertThatCode(
+            () -> swap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, false, minOut))
+        .isInstanceOf(RuntimeException.class)
+        .hasMessageContaining(
+            "Swap output " + receiving + " is below the minimum output " + minOut);
+
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(nonOwnerAddress1, createBalance(NON_OWNER_TOKEN_AMOUNT_A, ZERO))
+        .containsEntry(
+            swapContractAddress,
+            new ZkLiquiditySwap.TokenBalance(
+                INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B, INITIAL_LIQUIDITY_TOKENS));
+    Assertions.assertThat(getSwapState().worklist()).isEmpty();
+  }
+
+  /** The owner initializes pools with very little liquidity. */
+  @ContractTest(previous = "contractInit")
+  void initializeSmallPool() {
+    depositIntoSwap(contractOwnerAddress, contractTokenA, SMALL_LIQUIDITY);
+    depositIntoSwap(contractOwnerAddress, contractTokenB, SMALL_LIQUIDITY);
+    blockchain.sendAction(
+        contractOwnerAddress,
+        swapContractAddress,
+        ZkLiquiditySwap.provideInitialLiquidity(SMALL_LIQUIDITY, SMALL_LIQUIDITY));
+
+    assertHasLiquidity(getSwapState(), true);
+  }
+
+  /**
+   * Swaps that would output more than the maximum part of the output pool fail, and the input
+   * stays with the user.
+   */
+  @ContractTest(previous = "initializeSmallPool")
+  void swapDrainingSmallPool() {
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+
+    BigInteger receiving = calculateReceivingAmount(contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    Assertions.assertThat(receiving).isEqualTo(970);
+    Assertions.assertThatCode(
+            () -> swap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, false))
+        .isInstanceOf(RuntimeException.class)
+        .hasMessageContaining("Swap output 970 exceeds the maximum output 900 of the pool");
+
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(nonOwnerAddress1, createBalance(NON_OWNER_TOKEN_AMOUNT_A, ZERO))
+        .containsEntry(
+            swapContractAddress,
+            new ZkLiquiditySwap.TokenBalance(SMALL_LIQUIDITY, SMALL_LIQUIDITY, SMALL_LIQUIDITY));
+    Assertions.assertThat(getSwapState().worklist()).isEmpty();
+  }
+
+  /** Swaps outputting less than the maximum part of the output pool are performed. */
+  @ContractTest(previous = "initializeSmallPool")
+  void swapAgainstSmallPool() {
+    BigInteger amount = BigInteger.valueOf(100);
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, amount);
+
+    BigInteger receiving = calculateReceivingAmount(contractTokenA, amount);
+    swap(nonOwnerAddress1, contractTokenA, amount, false);
+
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(nonOwnerAddress1, createBalance(ZERO, receiving))
+        .containsEntry(
+            swapContractAddress,
+            new ZkLiquiditySwap.TokenBalance(
+                SMALL_LIQUIDITY.add(amount), SMALL_LIQUIDITY.subtract(receiving), SMALL_LIQUIDITY));
   }
 
   /** Tests non owner swaps back and forth. */
@@ -405,7 +715,71 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
                 nonOwnerAddress2,
                 new ZkLiquiditySwap.TokenBalance(ZERO, NON_OWNER_TOKEN_AMOUNT_B, ZERO),
                 swapContractAddress,
-                new ZkLiquiditySwap.TokenBalance(INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B, ZERO)));
+                new ZkLiquiditySwap.TokenBalance(
+                    INITIAL_LIQUIDITY_A, INITIAL_LIQUIDITY_B, INITIAL_LIQUIDITY_TOKENS),
+                contractOwnerAddress,
+                new ZkLiquiditySwap.TokenBalance(ZERO, ZERO, OWNER_LIQUIDITY_TOKENS)));
+  }
+
+  /**
+   * A front-only swap whose input is completed after another swap is dropped, and the user keeps
+   * their input tokens.
+   */
+  @ContractTest(previous = "initializePool")
+  void frontOnlySwapDroppedWhenNotFirst() {
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    depositIntoSwap(nonOwnerAddress2, contractTokenB, NON_OWNER_TOKEN_AMOUNT_B);
+
+    zkNodes.stop();
+
+    VariableId id1 = swap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, false);
+    VariableId id2 = swap(nonOwnerAddress2, contractTokenB, NON_OWNER_TOKEN_AMOUNT_B, true);
+
+    BigInteger receiving1B = calculateReceivingAmount(contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    zkNodes.confirmInput(id1);
+    zkNodes.confirmInput(id2);
+
+    // Only the first swap is queued.
+    Assertions.assertThat(getSwapState().worklist()).hasSize(1);
+
+    performSwapsInQueue(1);
+    Assertions.assertThat(getSwapState().worklist()).isEmpty();
+    Assertions.assertThat(zkNodes.getPendingOpens(swapContractAddress)).isEmpty();
+
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(nonOwnerAddress1, createBalance(ZERO, receiving1B))
+        .containsEntry(nonOwnerAddress2, createBalance(ZERO, NON_OWNER_TOKEN_AMOUNT_B));
+  }
+
+  /**
+   * The queue is ordered by completion of inputs, so a front-only swap that is invoked last but
+   * completed first is performed before the other swap.
+   */
+  @ContractTest(previous = "initializePool")
+  void frontOnlySwapPerformedWhenCompletedFirst() {
+    depositIntoSwap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    depositIntoSwap(nonOwnerAddress2, contractTokenB, NON_OWNER_TOKEN_AMOUNT_B);
+
+    zkNodes.stop();
+
+    VariableId id1 = swap(nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A, false);
+    VariableId id2 = swap(nonOwnerAddress2, contractTokenB, NON_OWNER_TOKEN_AMOUNT_B, true);
+
+    BigInteger receiving2A = calculateReceivingAmount(contractTokenB, NON_OWNER_TOKEN_AMOUNT_B);
+    zkNodes.confirmInput(id2);
+    zkNodes.confirmInput(id1);
+
+    Assertions.assertThat(getSwapState().worklist()).hasSize(2);
+
+    performSwapsInQueue(1);
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(nonOwnerAddress2, createBalance(receiving2A, ZERO))
+        .containsEntry(nonOwnerAddress1, createBalance(NON_OWNER_TOKEN_AMOUNT_A, ZERO));
+
+    BigInteger receiving1B = calculateReceivingAmount(contractTokenA, NON_OWNER_TOKEN_AMOUNT_A);
+    performSwapsInQueue(1);
+    Assertions.assertThat(getDepositBalances())
+        .containsEntry(nonOwnerAddress1, createBalance(ZERO, receiving1B));
   }
 
   /** Swap in queue happens even if user withdraws, if there is still enough tokens. */
@@ -601,8 +975,8 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     // User deposits.
     depositIntoSwap(
         nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A.divide(BigInteger.TWO));
-    Assertions.assertThat(getTokenState(contractTokenA).balances())
-        .containsEntry(nonOwnerAddress1, NON_OWNER_TOKEN_AMOUNT_A.divide(BigInteger.TWO));
+    Assertions.assertThat(getTokenState(contractTokenA).balances().get(nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A.divide(BigInteger.TWO));
 
     // Owner closes pools.
     blockchain.sendAction(contractOwnerAddress, swapContractAddress, ZkLiquiditySwap.closePools());
@@ -612,8 +986,8 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
         nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A.divide(BigInteger.TWO));
 
     // Assert that tokens are back to original account.
-    Assertions.assertThat(getTokenState(contractTokenA).balances())
-        .containsEntry(nonOwnerAddress1, NON_OWNER_TOKEN_AMOUNT_A);
+    Assertions.assertThat(getTokenState(contractTokenA).balances().get(nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
   }
 
   /** User can withdraw after failed swap. */
@@ -622,8 +996,8 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     // User deposits.
     depositIntoSwap(
         nonOwnerAddress1, contractTokenA, NON_OWNER_TOKEN_AMOUNT_A.divide(BigInteger.TWO));
-    Assertions.assertThat(getTokenState(contractTokenA).balances())
-        .containsEntry(nonOwnerAddress1, NON_OWNER_TOKEN_AMOUNT_A.divide(BigInteger.TWO));
+    Assertions.assertThat(getTokenState(contractTokenA).balances().get(nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A.divide(BigInteger.TWO));
 
     // Try to swap too much.
     Assertions.assertThatCode(
@@ -644,8 +1018,8 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
 
     // Assert that tokens are back to original account.
     Assertions.assertThat(getDepositBalances()).doesNotContainKey(nonOwnerAddress1);
-    Assertions.assertThat(getTokenState(contractTokenA).balances())
-        .containsEntry(nonOwnerAddress1, NON_OWNER_TOKEN_AMOUNT_A);
+    Assertions.assertThat(getTokenState(contractTokenA).balances().get(nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
   }
 
   private ZkLiquiditySwap.ContractState getSwapState() {
@@ -653,7 +1027,7 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
   }
 
   private Token.TokenState getTokenState(BlockchainAddress tokenAddress) {
-    return Token.TokenState.deserialize(blockchain.getContractState(tokenAddress));
+    return new Token(getStateClient(), tokenAddress).getState();
   }
 
   private CompactBitArray swapSecretSharedInput(BigInteger amount, boolean direction) {
@@ -668,10 +1042,11 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     return builder.getBits();
   }
 
-  private byte[] swapPublicRpc(boolean onlyIfAtFront) {
+  private byte[] swapPublicRpc(boolean onlyIfAtFront, BigInteger minOut) {
     FileAbi fileAbi = new AbiParser(contractBytesSwap.abi()).parseAbi();
     final RpcContractBuilder builder = new RpcContractBuilder(fileAbi.contract(), "swap");
     builder.addBool(onlyIfAtFront);
+    builder.addU128(minOut);
     return builder.getBytes();
   }
 
@@ -680,12 +1055,21 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
       BlockchainAddress token,
       BigInteger amount,
       boolean onlyIfAtFront) {
+    return swap(swapper, token, amount, onlyIfAtFront, ZERO);
+  }
+
+  private VariableId swap(
+      BlockchainAddress swapper,
+      BlockchainAddress token,
+      BigInteger amount,
+      boolean onlyIfAtFront,
+      BigInteger minOut) {
     boolean direction = token.equals(contractTokenA);
     return blockchain.sendSecretInput(
         swapContractAddress,
         swapper,
         swapSecretSharedInput(amount, direction),
-        swapPublicRpc(onlyIfAtFront));
+        swapPublicRpc(onlyIfAtFront, minOut));
   }
 
   private BigInteger calculateReceivingAmount(BlockchainAddress fromToken, BigInteger amount) {
@@ -694,7 +1078,7 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
         getPoolAmountForToken(fromToken.equals(contractTokenA) ? contractTokenB : contractTokenA);
 
     BigInteger newFromAmount = oldFromAmount.add(amount);
-    BigInteger[] divrem = SWAP_CONSTANT.divideAndRemainder(newFromAmount);
+    BigInteger[] divrem = getSwapState().swapConstant().divideAndRemainder(newFromAmount);
     BigInteger newToAmount = divrem[0];
     if (!divrem[1].equals(ZERO)) {
       newToAmount = newToAmount.add(BigInteger.ONE);
@@ -733,8 +1117,9 @@ public abstract class LiquiditySwapZkTest extends JunitContractTest {
     if (expected) {
       Assertions.assertThat(liquidityPoolBalances.aTokens()).as("Token A liquidity").isPositive();
       Assertions.assertThat(liquidityPoolBalances.bTokens()).as("Token B liquidity").isPositive();
-    } else {
-      Assertions.assertThat(liquidityPoolBalances).as("Token liquidity").isNull();
+    } else if (liquidityPoolBalances != null) {
+      Assertions.assertThat(liquidityPoolBalances.aTokens()).as("Token A liquidity").isZero();
+      Assertions.assertThat(liquidityPoolBalances.bTokens()).as("Token B liquidity").isZero();
     }
   }
 
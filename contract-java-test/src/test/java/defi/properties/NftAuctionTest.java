@@ -13,6 +13,7 @@ import com.partisiablockchain.language.junit.JunitContractTest;
 import com.partisiablockchain.language.junit.exceptions.ActionFailureException;
 import com.partisiablockchain.language.testenvironment.TxExecution;
 import java.math.BigInteger;
+import java.util.List;
 
 /** Test suite for the {@link NftAuction} smart contract. */
 public abstract class NftAuctionTest extends JunitContractTest {
@@ -75,7 +76,7 @@ public abstract class NftAuctionTest extends JunitContractTest {
 
     // deploy token contracts
     byte[] dogeInitRpc =
-        Token.initialize("Doge Coin", "DOGE", (byte) 18, BigInteger.valueOf(DOGE_SUPPLY));
+        Token.initialize("Doge Coin", "DOGE", (byte) 18, BigInteger.valueOf(DOGE_SUPPLY), null);
     doge = blockchain.deployContract(ownerDoge, contractBytesToken, dogeInitRpc);
     byte[] nftInitRpc = NftContract.initialize("Disinterested Monkey Boat Association", "DMBA", "");
     nft = blockchain.deployContract(auctionOwner, contractBytesNft, nftInitRpc);
@@ -90,7 +91,7 @@ public abstract class NftAuctionTest extends JunitContractTest {
     blockchain.sendAction(auctionOwner, nft, transferFour);
 
     // assert that the transfers were successful
-    Token.TokenState dogeState = Token.TokenState.deserialize(blockchain.getContractState(doge));
+    Token.TokenState dogeState = new Token(getStateClient(), doge).getState();
     assertThat(dogeState.balances().get(bidder1)).isEqualTo(500);
     assertThat(dogeState.balances().get(bidder2)).isEqualTo(1000);
     assertThat(dogeState.balances().get(bidder3)).isEqualTo(1500);
@@ -108,7 +109,13 @@ public abstract class NftAuctionTest extends JunitContractTest {
 
     // deploy the auction contract
     byte[] auctionInitRpc =
-        NftAuction.initialize(nft, nftId, doge, BigInteger.valueOf(20), BigInteger.valueOf(5), 2);
+        NftAuction.initialize(
+            List.of(new NftAuction.NftForSale(nft, nftId)),
+            new NftAuction.BiddingAssetToken(doge),
+            BigInteger.valueOf(20),
+            BigInteger.valueOf(5),
+            2,
+            List.of());
 
     auction = blockchain.deployContract(auctionOwner, contractBytesAuction, auctionInitRpc);
     auctionContract = new NftAuction(getStateClient(), auction);
@@ -130,10 +137,13 @@ public abstract class NftAuctionTest extends JunitContractTest {
     blockchain.sendAction(bidder2, doge, approveForAuctionBidderTwoRpc);
     blockchain.sendAction(bidder3, doge, approveForAuctionBidderThreeRpc);
 
-    dogeState = Token.TokenState.deserialize(blockchain.getContractState(doge));
-    assertThat(dogeState.allowed().get(bidder1).get(auction)).isEqualTo(BigInteger.valueOf(500));
-    assertThat(dogeState.allowed().get(bidder2).get(auction)).isEqualTo(BigInteger.valueOf(1000));
-    assertThat(dogeState.allowed().get(bidder3).get(auction)).isEqualTo(BigInteger.valueOf(1500));
+    dogeState = new Token(getStateClient(), doge).getState();
+    assertThat(dogeState.allowed().get(new Token.AllowedAddress(bidder1, auction)))
+        .isEqualTo(BigInteger.valueOf(500));
+    assertThat(dogeState.allowed().get(new Token.AllowedAddress(bidder2, auction)))
+        .isEqualTo(BigInteger.valueOf(1000));
+    assertThat(dogeState.allowed().get(new Token.AllowedAddress(bidder3, auction)))
+        .isEqualTo(BigInteger.valueOf(1500));
 
     // start the auction
     byte[] startRpc = NftAuction.start();
@@ -160,7 +170,7 @@ public abstract class NftAuctionTest extends JunitContractTest {
 
     // deploy token contracts
     byte[] dogeInitRpc =
-        Token.initialize("Doge Coin", "DOGE", (byte) 18, BigInteger.valueOf(DOGE_SUPPLY));
+        Token.initialize("Doge Coin", "DOGE", (byte) 18, BigInteger.valueOf(DOGE_SUPPLY), null);
     doge = blockchain.deployContract(ownerDoge, contractBytesToken, dogeInitRpc);
 
     byte[] nftInitRpc = NftContract.initialize("Disinterested Monkey Boat Association", "DMBA", "");
@@ -172,7 +182,13 @@ public abstract class NftAuctionTest extends JunitContractTest {
 
     // deploy the auction contract
     byte[] auctionInitRpc =
-        NftAuction.initialize(nft, nftId, doge, BigInteger.valueOf(20), BigInteger.valueOf(5), 2);
+        NftAuction.initialize(
+            List.of(new NftAuction.NftForSale(nft, nftId)),
+            new NftAuction.BiddingAssetToken(doge),
+            BigInteger.valueOf(20),
+            BigInteger.valueOf(5),
+            2,
+            List.of());
 
     auction = blockchain.deployContract(auctionOwner, contractBytesAuction, auctionInitRpc);
 
@@ -267,8 +283,9 @@ public abstract class NftAuctionTest extends JunitContractTest {
     assertThat(auctionState.status()).isEqualTo(ENDED); // status should be ENDED
     assertThat(auctionState.claimMap().getNextN(null, 100).size())
         .isEqualTo(2); // one claim for bidder and one for seller
-    assertThat(auctionState.claimMap().get(bidder3).nftForSale())
-        .isEqualTo(nft); // bidder's claim should be equal to the nft for sale
+    assertThat(auctionState.claimMap().get(bidder3).nftsForSale())
+        .containsExactly(
+            new NftAuction.NftForSale(nft, nftId)); // bidder's claim should be the nft for sale
     assertThat(auctionState.claimMap().get(auctionOwner).tokensForBidding())
         .isEqualTo(BigInteger.valueOf(30)); // seller's claim should be equal to bid
 
@@ -281,8 +298,8 @@ public abstract class NftAuctionTest extends JunitContractTest {
 
     assertThat(auctionState.claimMap().getNextN(null, 100).size())
         .isEqualTo(2); // size of claim map should remain the same
-    assertThat(auctionState.claimMap().get(bidder3).nftForSale())
-        .isEqualTo(null); // tokens should now be claimed
+    assertThat(auctionState.claimMap().get(bidder3).nftsForSale())
+        .isEmpty(); // nft should now be claimed
     assertThat(auctionState.claimMap().get(auctionOwner).tokensForBidding())
         .isEqualTo(BigInteger.valueOf(0)); // tokens should now be claimed
   }
@@ -316,7 +333,8 @@ public abstract class NftAuctionTest extends JunitContractTest {
     assertThat(auctionState.claimMap().get(bidder1).tokensForBidding())
         .isEqualTo(BigInteger.valueOf(20));
     // the highest bidder (auction winner) should be able to claim the prize of the auction
-    assertThat(auctionState.claimMap().get(bidder2).nftForSale()).isEqualTo(nft);
+    assertThat(auctionState.claimMap().get(bidder2).nftsForSale())
+        .containsExactly(new NftAuction.NftForSale(nft, nftId));
     // the auction owner should be able to claim the highest bid
     assertThat(auctionState.claimMap().get(auctionOwner).tokensForBidding())
         .isEqualTo(BigInteger.valueOf(30));
@@ -364,7 +382,8 @@ public abstract class NftAuctionTest extends JunitContractTest {
     assertThat(auctionState.claimMap().getNextN(null, 100).size()).isEqualTo(3);
     assertThat(auctionState.claimMap().get(bidder2).tokensForBidding())
         .isEqualTo(BigInteger.valueOf(10));
-    assertThat(auctionState.claimMap().get(bidder1).nftForSale()).isEqualTo(nft);
+    assertThat(auctionState.claimMap().get(bidder1).nftsForSale())
+        .containsExactly(new NftAuction.NftForSale(nft, nftId));
   }
 
   /** The first of two bids with the same amount bid is registered as the highest. */
@@ -394,7 +413,8 @@ public abstract class NftAuctionTest extends JunitContractTest {
     assertThat(auctionState.claimMap().get(bidder2).tokensForBidding())
         .isEqualTo(BigInteger.valueOf(30));
     // The first one to bid the highest bid should be able to claim the auction prize.
-    assertThat(auctionState.claimMap().get(bidder1).nftForSale()).isEqualTo(nft);
+    assertThat(auctionState.claimMap().get(bidder1).nftsForSale())
+        .containsExactly(new NftAuction.NftForSale(nft, nftId));
   }
 
   /**
@@ -426,12 +446,77 @@ public abstract class NftAuctionTest extends JunitContractTest {
     assertThat(auctionState.claimMap().get(bidder1).tokensForBidding())
         .isEqualTo(BigInteger.valueOf(20));
     // The bidder should be able to claim the auction prize.
-    assertThat(auctionState.claimMap().get(bidder1).nftForSale()).isEqualTo(nft);
+    assertThat(auctionState.claimMap().get(bidder1).nftsForSale())
+        .containsExactly(new NftAuction.NftForSale(nft, nftId));
     // The auction owner should be able to claim the highest of the two bids.
     assertThat(auctionState.claimMap().get(auctionOwner).tokensForBidding())
         .isEqualTo(BigInteger.valueOf(30));
   }
 
+  /**
+   * A bidder who is outbid is notified by exactly one refund-available event, and can claim the
+   * refunded amount.
+   */
+  @ContractTest(previous = "setup")
+  void outbidBidderTriggersRefundAvailableEvent() {
+    blockchain.sendAction(bidder1, auction, NftAuction.bid(BigInteger.valueOf(30)));
+
+    // Outbid bidder1. The bid callback emits exactly one refund-available event, sent to the
+    // auction itself.
+    TxExecution bidCallback = executeBidUntilCallback(bidder2, BigInteger.valueOf(40));
+    List<TxExecution> events = bidCallback.getSpawnedEvents();
+    assertThat(events).hasSize(1);
+    assertThat(events.get(0).getEvent().getEvent().getInner().target()).isEqualTo(auction);
+    assertThat(blockchain.executeEventAsync(events.get(0)).isSuccess()).isTrue();
+
+    NftAuction.NftAuctionContractState auctionState = auctionContract.getState();
+    assertThat(auctionState.highestBidder().bidder()).isEqualTo(bidder2);
+    assertThat(auctionState.claimMap().get(bidder1).tokensForBidding())
+        .isEqualTo(BigInteger.valueOf(30));
+  }
+
+  /** The first bid does not refund anyone, so the bid callback emits no events. */
+  @ContractTest(previous = "setup")
+  void firstBidEmitsNoRefundAvailableEvent() {
+    TxExecution bidCallback = executeBidUntilCallback(bidder1, BigInteger.valueOf(30));
+    assertThat(bidCallback.getSpawnedEvents()).isEmpty();
+
+    NftAuction.NftAuctionContractState auctionState = auctionContract.getState();
+    assertThat(auctionState.highestBidder().bidder()).isEqualTo(bidder1);
+  }
+
+  /**
+   * Sends a bid, and executes the event tree one step at a time until the bid callback has been
+   * executed.
+   *
+   * @param bidder the account placing the bid
+   * @param amount the amount bid
+   * @return the executed bid callback
+   */
+  private TxExecution executeBidUntilCallback(BlockchainAddress bidder, BigInteger amount) {
+    TxExecution bidTransaction =
+        blockchain.sendActionAsync(bidder, auction, NftAuction.bid(amount));
+    TxExecution bidAction = bidTransaction.getContractInteraction();
+    blockchain.executeEventAsync(bidAction);
+    TxExecution transferFrom = bidAction.getContractInteraction();
+    blockchain.executeEventAsync(transferFrom);
+    TxExecution systemCallback = transferFrom.getSystemCallback();
+    blockchain.executeEventAsync(systemCallback);
+    TxExecution bidCallback = systemCallback.getContractCallback();
+    blockchain.executeEventAsync(bidCallback);
+    assertThat(bidCallback.isSuccess()).isTrue();
+    return bidCallback;
+  }
+
+  /** Refund-available events can only be sent by the auction contract itself. */
+  @ContractTest(previous = "setup")
+  void userCannotSendRefundAvailableEvent() {
+    byte[] eventRpc = NftAuction.refundAvailableEvent(bidder1, BigInteger.valueOf(30));
+    assertThatThrownBy(() -> blockchain.sendAction(bidder1, auction, eventRpc))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Refund events can only be sent by the auction contract itself");
+  }
+
   /** Non-owner cannot start the auction. */
   @ContractTest(previous = "setup")
   void startCalledByNonOwner() {
@@ -489,12 +574,12 @@ public abstract class NftAuctionTest extends JunitContractTest {
     blockchain.sendAction(bidder2, auction, bidTwentyRpc);
     blockchain.sendAction(bidder3, auction, bidThirtyRpc);
     auctionState = auctionContract.getState();
-    assertThat(auctionState.claimMap().get(auctionOwner).nftForSale()).isEqualTo(null);
+    assertThat(auctionState.claimMap().get(auctionOwner).nftsForSale()).isEmpty();
 
     byte[] claimRpc = NftAuction.claim();
     blockchain.sendAction(auctionOwner, auction, claimRpc);
     auctionState = auctionContract.getState();
-    assertThat(auctionState.claimMap().get(auctionOwner).nftForSale()).isEqualTo(null);
+    assertThat(auctionState.claimMap().get(auctionOwner).nftsForSale()).isEmpty();
     assertThat(auctionState.highestBidder().bidder()).isEqualTo(bidder3);
   }
 
@@ -555,12 +640,12 @@ public abstract class NftAuctionTest extends JunitContractTest {
   void nonPublicBidToken() {
     byte[] auctionInitRpcBidIllegal =
         NftAuction.initialize(
-            nft,
-            nftId,
-            blockchain.newAccount(12),
+            List.of(new NftAuction.NftForSale(nft, nftId)),
+            new NftAuction.BiddingAssetToken(blockchain.newAccount(12)),
             BigInteger.valueOf(20),
             BigInteger.valueOf(5),
-            2);
+            2,
+            List.of());
 
     assertThatThrownBy(
             () ->
@@ -570,17 +655,83 @@ public abstract class NftAuctionTest extends JunitContractTest {
         .hasMessageContaining("Tried to create a contract buying a non publicContract token");
   }
 
+  /**
+   * An auction cannot be deployed with an account or a public contract as the BYOC contract of the
+   * native coin, as BYOC contracts are system contracts.
+   */
+  @ContractTest(previous = "setup")
+  void nativeCoinThroughNonSystemContract() {
+    for (BlockchainAddress byoc : List.of(blockchain.newAccount(12), doge)) {
+      byte[] auctionInitRpcBidIllegal =
+          NftAuction.initialize(
+              List.of(new NftAuction.NftForSale(nft, nftId)),
+              new NftAuction.BiddingAssetNative(byoc),
+              BigInteger.valueOf(20),
+              BigInteger.valueOf(5),
+              2,
+              List.of());
+
+      assertThatThrownBy(
+              () ->
+                  blockchain.deployContract(
+                      auctionOwner, contractBytesAuction, auctionInitRpcBidIllegal))
+          .isInstanceOf(ActionFailureException.class)
+          .hasMessageContaining(
+              "Tried to create a contract buying the native coin through a non systemContract");
+    }
+  }
+
+  /** An auction cannot be deployed with a minimum increment of zero. */
+  @ContractTest(previous = "setup")
+  void zeroMinIncrement() {
+    byte[] auctionInitRpcIllegal =
+        NftAuction.initialize(
+            List.of(new NftAuction.NftForSale(nft, nftId)),
+            new NftAuction.BiddingAssetToken(doge),
+            BigInteger.valueOf(20),
+            BigInteger.ZERO,
+            2,
+            List.of());
+
+    assertThatThrownBy(
+            () ->
+                blockchain.deployContract(
+                    auctionOwner, contractBytesAuction, auctionInitRpcIllegal))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Tried to create a contract with a minimum increment of zero");
+  }
+
+  /** An auction cannot be deployed with a duration of zero hours. */
+  @ContractTest(previous = "setup")
+  void zeroAuctionDuration() {
+    byte[] auctionInitRpcIllegal =
+        NftAuction.initialize(
+            List.of(new NftAuction.NftForSale(nft, nftId)),
+            new NftAuction.BiddingAssetToken(doge),
+            BigInteger.valueOf(20),
+            BigInteger.valueOf(5),
+            0,
+            List.of());
+
+    assertThatThrownBy(
+            () ->
+                blockchain.deployContract(
+                    auctionOwner, contractBytesAuction, auctionInitRpcIllegal))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Tried to create a contract with an auction duration of zero hours");
+  }
+
   /** An auction cannot be selling a non-public NFT. */
   @ContractTest(previous = "setup")
   void nonPublicSaleAuction() {
     byte[] auctionInitRpcSaleIllegal =
         NftAuction.initialize(
-            blockchain.newAccount(10),
-            BigInteger.valueOf(1),
-            doge,
+            List.of(new NftAuction.NftForSale(blockchain.newAccount(10), BigInteger.ONE)),
+            new NftAuction.BiddingAssetToken(doge),
             BigInteger.valueOf(20),
             BigInteger.valueOf(5),
-            2);
+            2,
+            List.of());
 
     assertThatThrownBy(
             () ->
@@ -636,4 +787,233 @@ public abstract class NftAuctionTest extends JunitContractTest {
             "Insufficient DOGE allowance for transfer_from! Allowed 500, but trying to transfer"
                 + " 10000");
   }
+
+  /** A bidder that isn't on the allow-list of a private auction cannot bid. */
+  @ContractTest(previous = "setup")
+  void nonAllowedBidderIsRejected() {
+    BlockchainAddress privateAuction = deployAndStartPrivateAuction(List.of(bidder1));
+
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    bidder2, privateAuction, NftAuction.bid(BigInteger.valueOf(50))))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Only allowed bidders can bid on the auction");
+
+    Token.TokenState dogeState = new Token(getStateClient(), doge).getState();
+    assertThat(dogeState.balances().get(bidder2)).isEqualTo(1000);
+  }
+
+  /** A bidder on the allow-list of a private auction can bid. */
+  @ContractTest(previous = "setup")
+  void allowedBidderCanBid() {
+    BlockchainAddress privateAuction = deployAndStartPrivateAuction(List.of(bidder1));
+
+    blockchain.sendAction(bidder1, privateAuction, NftAuction.bid(BigInteger.valueOf(50)));
+
+    NftAuction.NftAuctionContractState auctionState =
+        new NftAuction(getStateClient(), privateAuction).getState();
+    assertThat(auctionState.highestBidder().bidder()).isEqualTo(bidder1);
+    assertThat(auctionState.highestBidder().amount()).isEqualTo(BigInteger.valueOf(50));
+  }
+
+  /** The contract owner can add bidders to the allow-list during the bidding phase. */
+  @ContractTest(previous = "setup")
+  void ownerCanAddAllowedBidder() {
+    BlockchainAddress privateAuction = deployAndStartPrivateAuction(List.of(bidder1));
+
+    blockchain.sendAction(auctionOwner, privateAuction, NftAuction.addAllowedBidder(bidder2));
+    blockchain.sendAction(bidder2, privateAuction, NftAuction.bid(BigInteger.valueOf(50)));
+
+    NftAuction.NftAuctionContractState auctionState =
+        new NftAuction(getStateClient(), privateAuction).getState();
+    assertThat(auctionState.allowedBidders().get(bidder2)).isTrue();
+    assertThat(auctionState.highestBidder().bidder()).isEqualTo(bidder2);
+  }
+
+  /** A bidder removed from the allow-list can no longer bid. */
+  @ContractTest(previous = "setup")
+  void removedBidderIsRejected() {
+    BlockchainAddress privateAuction = deployAndStartPrivateAuction(List.of(bidder1, bidder2));
+
+    blockchain.sendAction(auctionOwner, privateAuction, NftAuction.removeAllowedBidder(bidder2));
+
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    bidder2, privateAuction, NftAuction.bid(BigInteger.valueOf(50))))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Only allowed bidders can bid on the auction");
+  }
+
+  /** The last allowed bidder cannot be removed, since that would open the auction to anyone. */
+  @ContractTest(previous = "setup")
+  void cannotRemoveLastAllowedBidder() {
+    BlockchainAddress privateAuction = deployAndStartPrivateAuction(List.of(bidder1));
+
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    auctionOwner, privateAuction, NftAuction.removeAllowedBidder(bidder1)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Cannot remove the last allowed bidder");
+  }
+
+  /** Only the contract owner can change the allowed bidders. */
+  @ContractTest(previous = "setup")
+  void nonOwnerCannotChangeAllowedBidders() {
+    BlockchainAddress privateAuction = deployAndStartPrivateAuction(List.of(bidder1));
+
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    bidder1, privateAuction, NftAuction.addAllowedBidder(bidder2)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Only the contract owner can change the allowed bidders");
+  }
+
+  /** The allowed bidders cannot be changed after the auction has ended. */
+  @ContractTest(previous = "setup")
+  void allowedBiddersCannotChangeAfterEnd() {
+    BlockchainAddress privateAuction = deployAndStartPrivateAuction(List.of(bidder1));
+    blockchain.waitForBlockProductionTime(auctionEndTime);
+    blockchain.sendAction(auctionOwner, privateAuction, NftAuction.execute());
+
+    assertThatThrownBy(
+            () ->
+                blockchain.sendAction(
+                    auctionOwner, privateAuction, NftAuction.addAllowedBidder(bidder2)))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining(
+            "Tried to change the allowed bidders when the status isn't Creation or Bidding");
+  }
+
+  /** Both NFTs of a bundle are escrowed on start, and delivered together to the winner on claim. */
+  @ContractTest(previous = "setup")
+  void bundleIsEscrowedAndDeliveredToWinner() {
+    List<BigInteger> bundleIds = List.of(BigInteger.valueOf(4203), BigInteger.valueOf(4204));
+    BlockchainAddress bundleAuction = deployAndStartBundleAuction(bundleIds);
+    assertNftOwners(bundleIds, bundleAuction);
+
+    blockchain.sendAction(bidder1, doge, Token.approve(bundleAuction, BigInteger.valueOf(500)));
+    blockchain.sendAction(bidder1, bundleAuction, NftAuction.bid(BigInteger.valueOf(50)));
+    blockchain.waitForBlockProductionTime(auctionEndTime);
+    blockchain.sendAction(auctionOwner, bundleAuction, NftAuction.execute());
+
+    NftAuction.NftAuctionContractState auctionState =
+        new NftAuction(getStateClient(), bundleAuction).getState();
+    assertThat(auctionState.claimMap().get(bidder1).nftsForSale())
+        .containsExactly(
+            new NftAuction.NftForSale(nft, bundleIds.get(0)),
+            new NftAuction.NftForSale(nft, bundleIds.get(1)));
+
+    blockchain.sendAction(bidder1, bundleAuction, NftAuction.claim());
+    assertNftOwners(bundleIds, bidder1);
+  }
+
+  /** Both NFTs of a bundle are returned to the owner when the auction is cancelled. */
+  @ContractTest(previous = "setup")
+  void bundleIsReturnedToOwnerOnCancel() {
+    List<BigInteger> bundleIds = List.of(BigInteger.valueOf(4203), BigInteger.valueOf(4204));
+    BlockchainAddress bundleAuction = deployAndStartBundleAuction(bundleIds);
+
+    blockchain.sendAction(auctionOwner, bundleAuction, NftAuction.cancel());
+    blockchain.sendAction(auctionOwner, bundleAuction, NftAuction.claim());
+
+    assertNftOwners(bundleIds, auctionOwner);
+  }
+
+  /** An auction cannot be deployed without any NFTs for sale. */
+  @ContractTest(previous = "setup")
+  void emptyBundle() {
+    byte[] auctionInitRpcIllegal =
+        NftAuction.initialize(
+            List.of(),
+            new NftAuction.BiddingAssetToken(doge),
+            BigInteger.valueOf(20),
+            BigInteger.valueOf(5),
+            2,
+            List.of());
+
+    assertThatThrownBy(
+            () ->
+                blockchain.deployContract(
+                    auctionOwner, contractBytesAuction, auctionInitRpcIllegal))
+        .isInstanceOf(ActionFailureException.class)
+        .hasMessageContaining("Tried to create a contract selling no NFTs");
+  }
+
+  /**
+   * Deploys and starts an auction accepting bids in DOGE, selling a bundle of newly minted NFTs.
+   *
+   * @param nftIds Ids of the NFTs to mint and sell as a bundle. Not nullable.
+   * @return Address of the deployed auction.
+   */
+  private BlockchainAddress deployAndStartBundleAuction(List<BigInteger> nftIds) {
+    for (BigInteger id : nftIds) {
+      blockchain.sendAction(auctionOwner, nft, NftContract.mint(auctionOwner, id, new byte[16]));
+    }
+
+    byte[] auctionInitRpc =
+        NftAuction.initialize(
+            nftIds.stream().map(id -> new NftAuction.NftForSale(nft, id)).toList(),
+            new NftAuction.BiddingAssetToken(doge),
+            BigInteger.valueOf(20),
+            BigInteger.valueOf(5),
+            2,
+            List.of());
+    BlockchainAddress bundleAuction =
+        blockchain.deployContract(auctionOwner, contractBytesAuction, auctionInitRpc);
+
+    for (BigInteger id : nftIds) {
+      blockchain.sendAction(auctionOwner, nft, NftContract.approve(bundleAuction, id));
+    }
+    blockchain.sendAction(auctionOwner, bundleAuction, NftAuction.start());
+    return bundleAuction;
+  }
+
+  /**
+   * Asserts that all the given NFTs are owned by the given owner.
+   *
+   * @param nftIds Ids of the NFTs. Not nullable.
+   * @param owner Expected owner of the NFTs. Not nullable.
+   */
+  private void assertNftOwners(List<BigInteger> nftIds, BlockchainAddress owner) {
+    NftContract.NFTContractState nftState =
+        NftContract.NFTContractState.deserialize(blockchain.getContractState(nft));
+    for (BigInteger id : nftIds) {
+      assertThat(nftState.owners().get(id)).isEqualTo(owner);
+    }
+  }
+
+  /**
+   * Deploys and starts a private auction accepting bids in DOGE from the given bidders, selling a
+   * newly minted NFT. The first two bidders approve the auction to transfer their DOGE.
+   *
+   * @param allowedBidders Bidders allowed to bid on the auction. Not nullable.
+   * @return Address of the deployed auction.
+   */
+  private BlockchainAddress deployAndStartPrivateAuction(List<BlockchainAddress> allowedBidders) {
+    BigInteger privateNftId = BigInteger.valueOf(4202);
+    byte[] mintRpc = NftContract.mint(auctionOwner, privateNftId, new byte[16]);
+    blockchain.sendAction(auctionOwner, nft, mintRpc);
+
+    byte[] auctionInitRpc =
+        NftAuction.initialize(
+            List.of(new NftAuction.NftForSale(nft, privateNftId)),
+            new NftAuction.BiddingAssetToken(doge),
+            BigInteger.valueOf(20),
+            BigInteger.valueOf(5),
+            2,
+            allowedBidders);
+    BlockchainAddress privateAuction =
+        blockchain.deployContract(auctionOwner, contractBytesAuction, auctionInitRpc);
+
+    blockchain.sendAction(auctionOwner, nft, NftContract.approve(privateAuction, privateNftId));
+    blockchain.sendAction(auctionOwner, privateAuction, NftAuction.start());
+
+    blockchain.sendAction(bidder1, doge, Token.approve(privateAuction, BigInteger.valueOf(500)));
+    blockchain.sendAction(bidder2, doge, Token.approve(privateAuction, BigInteger.valueOf(1000)));
+    return privateAuction;
+  }
 }
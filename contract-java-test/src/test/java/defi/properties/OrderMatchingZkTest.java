@@ -72,10 +72,10 @@ public abstract class OrderMatchingZkTest extends JunitContractTest {
     users = IntStream.range(0, 10).map(x -> x + 1000).mapToObj(blockchain::newAccount).toList();
 
     // Setup tokens
-    final byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A);
+    final byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A, null);
     contractTokenA = blockchain.deployContract(creatorAddress, contractBytesToken, initRpcA);
 
-    final byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B);
+    final byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B, null);
     contractTokenB = blockchain.deployContract(creatorAddress, contractBytesToken, initRpcB);
 
     // Setup swap
@@ -48,6 +48,8 @@ import org.junit.jupiter.api.RepetitionInfo;
 @CheckReturnValue
 public abstract class RoutingTest extends JunitContractTest {
   private static final int MAX_ROUTE_LENGTH = 5;
+  private static final int MAX_ROUTE_LENGTH_CEILING = 8;
+  private static final int MAX_ACTIVE_ROUTES_PER_USER = 5;
   private static final BigInteger ZERO = BigInteger.ZERO;
 
   private static final BigInteger TOTAL_SUPPLY_A = BigInteger.ONE.shiftLeft(30);
@@ -138,9 +140,13 @@ public abstract class RoutingTest extends JunitContractTest {
 
     initializeNonOwners();
 
-    // Deploy the router
+    // Deploy the router, with the default maximum route length.
     byte[] initRpcRouter =
-        SwapRouter.initialize(new SwapRouter.PermissionAnybody(), swapContractInfoList);
+        SwapRouter.initialize(
+            new SwapRouter.PermissionAnybody(),
+            swapContractInfoList,
+            MAX_ACTIVE_ROUTES_PER_USER,
+            null);
     routerContract =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwapRouter, initRpcRouter);
 
@@ -151,6 +157,41 @@ public abstract class RoutingTest extends JunitContractTest {
     Assertions.assertThat(state.routeTracker()).isNotNull();
     Assertions.assertThat(state.routeTracker().nextRouteId()).isZero();
     Assertions.assertThat(state.routeTracker().activeRoutes().getNextN(null, 100)).isEmpty();
+    Assertions.assertThat(state.routeTracker().activeRoutesPerUser().getNextN(null, 100))
+        .isEmpty();
+    Assertions.assertThat(state.maxActiveRoutesPerUser()).isEqualTo(MAX_ACTIVE_ROUTES_PER_USER);
+    Assertions.assertThat(state.maxRouteLength()).isEqualTo(MAX_ROUTE_LENGTH);
+  }
+
+  /** Router contract cannot be deployed without allowing users any active routes. */
+  @ContractTest(previous = "contractInit")
+  void contractInitWithoutActiveRoutes() {
+    byte[] initRpcRouter =
+        SwapRouter.initialize(new SwapRouter.PermissionAnybody(), List.of(), 0, MAX_ROUTE_LENGTH);
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.deployContract(
+                    contractOwnerAddress, contractBytesSwapRouter, initRpcRouter))
+        .hasMessageContaining("Maximum number of active routes per user must be positive.");
+  }
+
+  /**
+   * Router contract cannot be deployed with a maximum route length exceeding the ceiling, as routes
+   * would hit the limit of chained events.
+   */
+  @ContractTest(previous = "contractInit")
+  void contractInitWithTooLongMaximumRouteLength() {
+    byte[] initRpcRouter =
+        SwapRouter.initialize(
+            new SwapRouter.PermissionAnybody(),
+            List.of(),
+            MAX_ACTIVE_ROUTES_PER_USER,
+            MAX_ROUTE_LENGTH_CEILING + 1);
+    Assertions.assertThatThrownBy(
+            () ->
+                blockchain.deployContract(
+                    contractOwnerAddress, contractBytesSwapRouter, initRpcRouter))
+        .hasMessageContaining("Maximum route length (9) must be between 1 and 8.");
   }
 
   /**
@@ -173,7 +214,7 @@ public abstract class RoutingTest extends JunitContractTest {
             nonOwnerAddress1,
             routerContract,
             SwapRouter.routeSwap(
-                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
             swapRouteGasAmount);
     executeTxExecutionInUnpredictableOrder(repetitionInfo, List.of(s1));
 
@@ -206,7 +247,8 @@ public abstract class RoutingTest extends JunitContractTest {
                 contractTokenA,
                 contractTokenD,
                 NON_OWNER_TOKEN_AMOUNT_A,
-                BigInteger.ONE),
+                BigInteger.ONE,
+                null),
             swapRouteGasAmount);
     final TxExecution s2 =
         blockchain.sendActionAsync(
@@ -217,7 +259,8 @@ public abstract class RoutingTest extends JunitContractTest {
                 contractTokenA,
                 contractTokenD,
                 NON_OWNER_TOKEN_AMOUNT_A,
-                BigInteger.ONE),
+                BigInteger.ONE,
+                null),
             swapRouteGasAmount);
 
     executeTxExecutionInUnpredictableOrder(repetitionInfo, List.of(s1, s2));
@@ -255,7 +298,8 @@ public abstract class RoutingTest extends JunitContractTest {
                   contractTokenA,
                   contractTokenD,
                   NON_OWNER_TOKEN_AMOUNT_A,
-                  BigInteger.ONE),
+                  BigInteger.ONE,
+                  null),
               swapRouteGasAmount));
     }
     executeTxExecutionInUnpredictableOrder(repetitionInfo, spawns);
@@ -307,7 +351,8 @@ public abstract class RoutingTest extends JunitContractTest {
                   info.initialToken,
                   info.finalToken,
                   info.initialAmount,
-                  BigInteger.ONE),
+                  BigInteger.ONE,
+                  null),
               swapRouteGasAmount));
     }
 
@@ -368,7 +413,8 @@ public abstract class RoutingTest extends JunitContractTest {
                   info.initialToken,
                   info.finalToken,
                   info.initialAmount,
-                  BigInteger.ONE),
+                  BigInteger.ONE,
+                  null),
               guaranteedGasCost));
     }
 
@@ -429,7 +475,8 @@ public abstract class RoutingTest extends JunitContractTest {
                 contractTokenA,
                 contractTokenC,
                 NON_OWNER_TOKEN_AMOUNT_A,
-                BigInteger.ONE),
+                BigInteger.ONE,
+                null),
             swapRouteGasAmount);
     final TxExecution s2 =
         blockchain.sendActionAsync(
@@ -440,7 +487,8 @@ public abstract class RoutingTest extends JunitContractTest {
                 contractTokenC,
                 contractTokenA,
                 NON_OWNER_TOKEN_AMOUNT_C,
-                BigInteger.ONE),
+                BigInteger.ONE,
+                null),
             swapRouteGasAmount);
 
     executeTxExecutionInUnpredictableOrder(repetitionInfo, List.of(s1, s2));
@@ -476,7 +524,7 @@ public abstract class RoutingTest extends JunitContractTest {
         nonOwnerAddress1,
         routerContract,
         SwapRouter.routeSwap(
-            swapRoute, contractTokenA, contractTokenB, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+            swapRoute, contractTokenA, contractTokenB, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
         swapRouteGasAmount);
 
     // User gets the desired tokens.
@@ -509,7 +557,7 @@ public abstract class RoutingTest extends JunitContractTest {
             nonOwnerAddress1,
             routerContract,
             SwapRouter.routeSwap(
-                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
             swapRouteGasAmount);
 
     // Execute user -> router interaction.
@@ -652,14 +700,18 @@ public abstract class RoutingTest extends JunitContractTest {
     final BigInteger cLiquidity = BigInteger.ONE.shiftLeft(27);
 
     // Deploy a new token: Z
-    byte[] initRpcZ = Token.initialize("Token Z", "Z", (byte) 8, zSupply);
+    byte[] initRpcZ = initializeTokenRpc("Token Z", "Z", zSupply);
     BlockchainAddress contractTokenZ =
         blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcZ);
 
     // Deploy new swap between C and Z.
     byte[] initRpcSwapCandZ =
         LiquiditySwapLock.initialize(
-            contractTokenC, contractTokenZ, (short) 0, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenC,
+            contractTokenZ,
+            (short) 0,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     BlockchainAddress swapLockContractAddressCandZ =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwap, initRpcSwapCandZ);
 
@@ -675,13 +727,14 @@ public abstract class RoutingTest extends JunitContractTest {
     blockchain.sendAction(
         contractOwnerAddress,
         routerContract,
-        SwapRouter.addSwapContract(swapLockContractAddressCandZ, contractTokenC, contractTokenZ));
+        SwapRouter.addSwapContract(
+            swapLockContractAddressCandZ, contractTokenC, contractTokenZ, true, true));
 
     SwapRouter.RouterState routerState = getRouterState();
     Assertions.assertThat(routerState.swapContracts().get(routerState.swapContracts().size() - 1))
         .isEqualTo(
             new SwapRouter.SwapContractInfo(
-                swapLockContractAddressCandZ, contractTokenC, contractTokenZ));
+                swapLockContractAddressCandZ, contractTokenC, contractTokenZ, true, true));
 
     // Approve the router at the original token.
     blockchain.sendAction(
@@ -700,7 +753,8 @@ public abstract class RoutingTest extends JunitContractTest {
                 contractTokenA,
                 contractTokenZ,
                 NON_OWNER_TOKEN_AMOUNT_A,
-                BigInteger.ONE),
+                BigInteger.ONE,
+                null),
             swapRouteGasAmount);
     executeTxExecutionInUnpredictableOrder(repetitionInfo, List.of(s1));
 
@@ -724,17 +778,18 @@ public abstract class RoutingTest extends JunitContractTest {
             nonOwnerAddress1,
             routerContract,
             SwapRouter.routeSwap(
-                swapRoute, contractTokenA, contractTokenE, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+                swapRoute, contractTokenA, contractTokenE, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
             swapRouteGasAmount);
     TxExecution s2 = executeEventAsync(s1.getContractInteraction());
 
     // We get the correct error message.
     Assertions.assertThat(s1.getContractInteraction().getFailureCause().getErrorMessage())
         .contains(
-            "Unknown swap address: %s."
+            "Swap contract %s at swap number %s is not routable."
                 .formatted(
                     LiquiditySwapLockPermissionTest.addressAsFormattedByteString(
-                        swapLockContractUnknown)));
+                        swapLockContractUnknown),
+                    3));
 
     // The interaction didn't spawn further events -> No calls to swap contracts -> no locks
     // acquired.
@@ -745,6 +800,47 @@ public abstract class RoutingTest extends JunitContractTest {
         .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
   }
 
+  /**
+   * A swap contract removed from the router can no longer be routed through. Routing fails with an
+   * error naming the removed swap contract, and no locks are acquired.
+   */
+  @ContractTest(previous = "contractInit")
+  void routeThroughRemovedSwapContract() {
+    blockchain.sendAction(
+        contractOwnerAddress,
+        routerContract,
+        SwapRouter.removeSwapContract(swapLockContractAddressCandD));
+    Assertions.assertThat(getRouterState().swapContracts())
+        .extracting(SwapRouter.SwapContractInfo::swapAddress)
+        .doesNotContain(swapLockContractAddressCandD);
+
+    // Route swap A -> C -> D.
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandC, swapLockContractAddressCandD);
+
+    TxExecution s1 =
+        blockchain.sendActionAsync(
+            nonOwnerAddress1,
+            routerContract,
+            SwapRouter.routeSwap(
+                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
+            swapRouteGasAmount);
+    TxExecution s2 = executeEventAsync(s1.getContractInteraction());
+
+    Assertions.assertThat(s1.getContractInteraction().getFailureCause().getErrorMessage())
+        .contains(
+            "Swap contract %s at swap number %s is not routable. It may have been removed."
+                .formatted(
+                    LiquiditySwapLockPermissionTest.addressAsFormattedByteString(
+                        swapLockContractAddressCandD),
+                    2));
+
+    // No calls to swap contracts -> no locks acquired.
+    Assertions.assertThat(s2.getSpawnedEvents()).isEmpty();
+    Assertions.assertThat(getTokenBalance(contractTokenA, nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
+  }
+
   /**
    * If a user provides a route where tokens doesn't match, the route is rejected, and no locks are
    * acquired.
@@ -764,7 +860,7 @@ public abstract class RoutingTest extends JunitContractTest {
             nonOwnerAddress1,
             routerContract,
             SwapRouter.routeSwap(
-                swapRoute, contractTokenA, contractTokenB, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+                swapRoute, contractTokenA, contractTokenB, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
             swapRouteGasAmount);
     TxExecution s2 = executeEventAsync(s1.getContractInteraction());
 
@@ -803,7 +899,7 @@ public abstract class RoutingTest extends JunitContractTest {
             nonOwnerAddress1,
             routerContract,
             SwapRouter.routeSwap(
-                swapRoute, contractTokenA, contractTokenE, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+                swapRoute, contractTokenA, contractTokenE, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
             swapRouteGasAmount);
     TxExecution s2 = executeEventAsync(s1.getContractInteraction());
 
@@ -839,7 +935,12 @@ public abstract class RoutingTest extends JunitContractTest {
             nonOwnerAddress1,
             routerContract,
             SwapRouter.routeSwap(
-                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, minimumOutput),
+                swapRoute,
+                contractTokenA,
+                contractTokenD,
+                NON_OWNER_TOKEN_AMOUNT_A,
+                minimumOutput,
+                null),
             swapRouteGasAmount);
 
     ExecutionUtil.executeTxExecutionInUnpredictableOrder(this, repetitionInfo, List.of(s1))
@@ -860,6 +961,109 @@ public abstract class RoutingTest extends JunitContractTest {
         .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
   }
 
+  /**
+   * If an intermediate swap on the route produces less than its hop minimum output amount, lock
+   * acquisition fails and the route is cancelled, even though the final minimum would be met.
+   */
+  @Previous("contractInit")
+  @RepeatedTest(5)
+  void routeHopMinimumOutputTooHigh(RepetitionInfo repetitionInfo) {
+    // Route swap A -> C -> D, where A -> C produces 2047 C tokens.
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandC, swapLockContractAddressCandD);
+
+    // Approve the router at the original token.
+    blockchain.sendAction(
+        nonOwnerAddress1, contractTokenA, Token.approve(routerContract, NON_OWNER_TOKEN_AMOUNT_A));
+
+    final List<BigInteger> hopMinimums = List.of(BigInteger.valueOf(2048), ZERO);
+
+    TxExecution s1 =
+        blockchain.sendActionAsync(
+            nonOwnerAddress1,
+            routerContract,
+            SwapRouter.routeSwapWithHopMinimums(
+                swapRoute,
+                contractTokenA,
+                contractTokenD,
+                NON_OWNER_TOKEN_AMOUNT_A,
+                BigInteger.ONE,
+                hopMinimums),
+            swapRouteGasAmount);
+
+    ExecutionUtil.executeTxExecutionInUnpredictableOrder(this, repetitionInfo, List.of(s1))
+        .assertFailures(
+            swapLockContractAddressAandC,
+            "Swap would produce 2047 output tokens, but minimum was set to 2048",
+            routerContract,
+            "Could not acquire all locks in route.");
+
+    // Check for no locks, and no tokens taken from the user
+    Assertions.assertThat(
+            getSwapState(swapLockContractAddressAandC).virtualState().locks().getNextN(null, 100))
+        .hasSize(0);
+    Assertions.assertThat(
+            getSwapState(swapLockContractAddressCandD).virtualState().locks().getNextN(null, 100))
+        .hasSize(0);
+    Assertions.assertThat(getTokenBalance(contractTokenA, nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
+    Assertions.assertThat(getTokenBalance(contractTokenD, nonOwnerAddress1)).isEqualTo(ZERO);
+  }
+
+  /** A route where every swap meets its hop minimum output amount is executed. */
+  @ContractTest(previous = "contractInit")
+  void routeHopMinimumOutputMet() {
+    // Route swap A -> C -> D, producing 2047 C tokens and then 8184 D tokens.
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandC, swapLockContractAddressCandD);
+
+    // Approve the router at the original token.
+    blockchain.sendAction(
+        nonOwnerAddress1, contractTokenA, Token.approve(routerContract, NON_OWNER_TOKEN_AMOUNT_A));
+
+    final List<BigInteger> hopMinimums =
+        List.of(BigInteger.valueOf(2047), BigInteger.valueOf(8184));
+
+    blockchain.sendAction(
+        nonOwnerAddress1,
+        routerContract,
+        SwapRouter.routeSwapWithHopMinimums(
+            swapRoute,
+            contractTokenA,
+            contractTokenD,
+            NON_OWNER_TOKEN_AMOUNT_A,
+            BigInteger.ONE,
+            hopMinimums),
+        swapRouteGasAmount);
+
+    Assertions.assertThat(getTokenBalance(contractTokenD, nonOwnerAddress1))
+        .isEqualTo(BigInteger.valueOf(8184));
+  }
+
+  /** If the number of hop minimums doesn't match the route length, the route is rejected. */
+  @ContractTest(previous = "contractInit")
+  void routeHopMinimumsWrongLength() {
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandC, swapLockContractAddressCandD);
+
+    Assertions.assertThatCode(
+            () ->
+                blockchain.sendAction(
+                    nonOwnerAddress1,
+                    routerContract,
+                    SwapRouter.routeSwapWithHopMinimums(
+                        swapRoute,
+                        contractTokenA,
+                        contractTokenD,
+                        NON_OWNER_TOKEN_AMOUNT_A,
+                        ZERO,
+                        List.of(ZERO)),
+                    swapRouteGasAmount))
+        .isInstanceOf(RuntimeException.class)
+        .hasMessageContaining(
+            "Number of hop minimum output amounts (1) doesn't match the route length (2).");
+  }
+
   /**
    * If a user provides a route which includes a swap contract without liquidity, lock acquisition
    * fails, and all acquired locks are cancelled.
@@ -883,7 +1087,7 @@ public abstract class RoutingTest extends JunitContractTest {
             nonOwnerAddress1,
             routerContract,
             SwapRouter.routeSwap(
-                swapRoute, contractTokenA, contractTokenF, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+                swapRoute, contractTokenA, contractTokenF, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
             swapRouteGasAmount);
 
     ExecutionUtil.executeTxExecutionInUnpredictableOrder(this, repetitionInfo, List.of(s1))
@@ -916,15 +1120,20 @@ public abstract class RoutingTest extends JunitContractTest {
                     nonOwnerAddress1,
                     routerContract,
                     SwapRouter.routeSwap(
-                        swapRoute, contractTokenA, contractTokenB, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+                        swapRoute,
+                        contractTokenA,
+                        contractTokenB,
+                        NON_OWNER_TOKEN_AMOUNT_A,
+                        ZERO,
+                        null),
                     swapRouteGasAmount))
         .isInstanceOf(RuntimeException.class)
         .hasMessageContaining("The given route is empty.");
   }
 
   /**
-   * If a user doesn't approve the router before requesting a lock swap, routing fails and no locks
-   * are acquired.
+   * If a user doesn't approve the router before requesting a lock swap, routing fails, no locks are
+   * acquired and the route is dropped.
    */
   @ContractTest(previous = "contractInit")
   void userDoesntApproveRouter() {
@@ -937,7 +1146,7 @@ public abstract class RoutingTest extends JunitContractTest {
             nonOwnerAddress1,
             routerContract,
             SwapRouter.routeSwap(
-                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO),
+                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
             swapRouteGasAmount);
 
     // Execute user -> router interaction.
@@ -951,11 +1160,15 @@ public abstract class RoutingTest extends JunitContractTest {
                 "Insufficient A allowance for transfer_from! Allowed 0, but trying to transfer %s",
                 NON_OWNER_TOKEN_AMOUNT_A));
 
-    // Handle callback to allow router to throw error.
+    // Handle callback, which drops the route.
     TxExecution s4 = executeEventAsync(s3.getSystemCallback());
     blockchain.executeEvent(s4.getContractCallback());
-    Assertions.assertThat(s4.getContractCallback().getFailureCause().getErrorMessage())
-        .contains("Could not take control of tokens.");
+    Assertions.assertThat(s4.getContractCallback().isSuccess()).isTrue();
+    Assertions.assertThat(getRouterState().routeTracker().activeRoutes().getNextN(null, 100))
+        .isEmpty();
+    Assertions.assertThat(
+            getRouterState().routeTracker().activeRoutesPerUser().get(nonOwnerAddress1))
+        .isNull();
   }
 
   /**
@@ -981,7 +1194,8 @@ public abstract class RoutingTest extends JunitContractTest {
                 contractTokenA,
                 contractTokenD,
                 NON_OWNER_TOKEN_AMOUNT_A.add(BigInteger.ONE), // 1 more token than owned.
-                ZERO),
+                ZERO,
+                null),
             swapRouteGasAmount);
 
     // Execute user -> router interaction.
@@ -995,11 +1209,15 @@ public abstract class RoutingTest extends JunitContractTest {
                 "Insufficient A allowance for transfer_from! Allowed %s, but trying to transfer %s",
                 NON_OWNER_TOKEN_AMOUNT_A, NON_OWNER_TOKEN_AMOUNT_A.add(BigInteger.ONE)));
 
-    // Handle callback to allow router to throw error.
+    // Handle callback, which drops the route.
     TxExecution s4 = executeEventAsync(s3.getSystemCallback());
     blockchain.executeEvent(s4.getContractCallback());
-    Assertions.assertThat(s4.getContractCallback().getFailureCause().getErrorMessage())
-        .contains("Could not take control of tokens.");
+    Assertions.assertThat(s4.getContractCallback().isSuccess()).isTrue();
+    Assertions.assertThat(getRouterState().routeTracker().activeRoutes().getNextN(null, 100))
+        .isEmpty();
+    Assertions.assertThat(
+            getRouterState().routeTracker().activeRoutesPerUser().get(nonOwnerAddress1))
+        .isNull();
   }
 
   /**
@@ -1035,7 +1253,8 @@ public abstract class RoutingTest extends JunitContractTest {
                         randomRoute.initialToken,
                         randomRoute.finalToken,
                         randomRoute.initialAmount,
-                        BigInteger.ONE),
+                        BigInteger.ONE,
+                        null),
                     tooLittleGas))
         .isInstanceOf(RuntimeException.class)
         .hasMessageContaining("Cannot allocate gas for events.");
@@ -1059,7 +1278,8 @@ public abstract class RoutingTest extends JunitContractTest {
             randomRoute.initialToken,
             randomRoute.finalToken,
             randomRoute.initialAmount,
-            BigInteger.ONE),
+            BigInteger.ONE,
+            null),
         tooLittleGas + 250);
 
     assertTokenBalanceNotZero(randomRoute.finalToken, routeUser);
@@ -1094,7 +1314,8 @@ public abstract class RoutingTest extends JunitContractTest {
                         randomRoute.initialToken,
                         randomRoute.finalToken,
                         randomRoute.initialAmount,
-                        BigInteger.ONE),
+                        BigInteger.ONE,
+                        null),
                     swapRouteGasAmount))
         .isInstanceOf(RuntimeException.class)
         .hasMessageContaining(
@@ -1143,13 +1364,72 @@ public abstract class RoutingTest extends JunitContractTest {
                 randomRoute.initialToken,
                 randomRoute.finalToken,
                 randomRoute.initialAmount,
-                BigInteger.ONE),
+                BigInteger.ONE,
+                null),
             285_000L);
     executeTxExecutionInUnpredictableOrder(repetitionInfo, List.of(s1));
 
     assertTokenBalanceNotZero(randomRoute.finalToken, routeUser);
   }
 
+  /**
+   * A router initialized with a raised maximum route length accepts routes longer than the default
+   * maximum, given enough gas, while the default router rejects them.
+   */
+  @RepeatedTest(1)
+  @Previous("contractInit")
+  void swapRouteRaisedMaxLength(RepetitionInfo repetitionInfo) {
+    initializeTestMappings();
+    final BlockchainAddress routeUser = blockchain.newAccount(89);
+    RandomRouteInfo randomRoute =
+        generateRandomRoutes(repetitionInfo, 1, MAX_ROUTE_LENGTH + 1).get(0);
+
+    byte[] initRpcRouter =
+        SwapRouter.initialize(
+            new SwapRouter.PermissionAnybody(),
+            getRouterState().swapContracts(),
+            MAX_ACTIVE_ROUTES_PER_USER,
+            MAX_ROUTE_LENGTH + 1);
+    final BlockchainAddress longRouteRouter =
+        blockchain.deployContract(contractOwnerAddress, contractBytesSwapRouter, initRpcRouter);
+
+    blockchain.sendAction(
+        contractOwnerAddress,
+        randomRoute.initialToken,
+        Token.transfer(routeUser, randomRoute.initialAmount));
+
+    final byte[] routeSwapRpc =
+        SwapRouter.routeSwap(
+            randomRoute.swapRoute,
+            randomRoute.initialToken,
+            randomRoute.finalToken,
+            randomRoute.initialAmount,
+            BigInteger.ONE,
+            false);
+
+    // The default router rejects the route.
+    blockchain.sendAction(
+        routeUser,
+        randomRoute.initialToken,
+        Token.approve(routerContract, randomRoute.initialAmount));
+    Assertions.assertThatCode(
+            () -> blockchain.sendAction(routeUser, routerContract, routeSwapRpc, 345_000L))
+        .hasMessageContaining(
+            "Swap route length (%s) is greater than maximum allowed (%s).",
+            MAX_ROUTE_LENGTH + 1, MAX_ROUTE_LENGTH);
+
+    // The router with the raised maximum accepts the route.
+    blockchain.sendAction(
+        routeUser,
+        randomRoute.initialToken,
+        Token.approve(longRouteRouter, randomRoute.initialAmount));
+    final TxExecution s1 =
+        blockchain.sendActionAsync(routeUser, longRouteRouter, routeSwapRpc, 345_000L);
+    executeTxExecutionInUnpredictableOrder(repetitionInfo, List.of(s1));
+
+    assertTokenBalanceNotZero(randomRoute.finalToken, routeUser);
+  }
+
   private void approveExtraNonOwnersA() {
     for (BlockchainAddress nonOwner : extraNonOwnerAddresses) {
       blockchain.sendAction(
@@ -1223,82 +1503,110 @@ public abstract class RoutingTest extends JunitContractTest {
   }
 
   private void deployTokenContracts() {
-    byte[] initRpcA = Token.initialize("Token A", "A", (byte) 8, TOTAL_SUPPLY_A);
+    byte[] initRpcA = initializeTokenRpc("Token A", "A", TOTAL_SUPPLY_A);
     contractTokenA = blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcA);
 
-    byte[] initRpcB = Token.initialize("Token B", "B", (byte) 8, TOTAL_SUPPLY_B);
+    byte[] initRpcB = initializeTokenRpc("Token B", "B", TOTAL_SUPPLY_B);
     contractTokenB = blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcB);
 
-    byte[] initRpcC = Token.initialize("Token C", "C", (byte) 8, TOTAL_SUPPLY_C);
+    byte[] initRpcC = initializeTokenRpc("Token C", "C", TOTAL_SUPPLY_C);
     contractTokenC = blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcC);
 
-    byte[] initRpcD = Token.initialize("Token D", "D", (byte) 8, TOTAL_SUPPLY_D);
+    byte[] initRpcD = initializeTokenRpc("Token D", "D", TOTAL_SUPPLY_D);
     contractTokenD = blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcD);
 
-    byte[] initRpcE = Token.initialize("Token E", "E", (byte) 8, TOTAL_SUPPLY_E);
+    byte[] initRpcE = initializeTokenRpc("Token E", "E", TOTAL_SUPPLY_E);
     contractTokenE = blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcE);
 
-    byte[] initRpcF = Token.initialize("Token F", "F", (byte) 8, TOTAL_SUPPLY_F);
+    byte[] initRpcF = initializeTokenRpc("Token F", "F", TOTAL_SUPPLY_F);
     contractTokenF = blockchain.deployContract(contractOwnerAddress, contractBytesToken, initRpcF);
   }
 
   private List<SwapRouter.SwapContractInfo> deploySwapContracts() {
     byte[] initRpcSwapAandB =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenB, FEE_AB, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA,
+            contractTokenB,
+            FEE_AB,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     swapLockContractAddressAandB =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwap, initRpcSwapAandB);
 
     byte[] initRpcSwapAandC =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenC, FEE_AC, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA,
+            contractTokenC,
+            FEE_AC,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     swapLockContractAddressAandC =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwap, initRpcSwapAandC);
 
     byte[] initRpcSwapBandD =
         LiquiditySwapLock.initialize(
-            contractTokenB, contractTokenD, FEE_BD, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenB,
+            contractTokenD,
+            FEE_BD,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     swapLockContractAddressBandD =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwap, initRpcSwapBandD);
 
     byte[] initRpcSwapCandD =
         LiquiditySwapLock.initialize(
-            contractTokenC, contractTokenD, FEE_CD, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenC,
+            contractTokenD,
+            FEE_CD,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     swapLockContractAddressCandD =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwap, initRpcSwapCandD);
 
     byte[] initRpcSwapDandE =
         LiquiditySwapLock.initialize(
-            contractTokenD, contractTokenE, FEE_CD, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenD,
+            contractTokenE,
+            FEE_CD,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     swapLockContractUnknown =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwap, initRpcSwapDandE);
 
     byte[] initRpcSwapAandF =
         LiquiditySwapLock.initialize(
-            contractTokenA, contractTokenF, FEE_AF, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenA,
+            contractTokenF,
+            FEE_AF,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     swapLockContractAddressAandF =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwap, initRpcSwapAandF);
 
     byte[] initRpcSwapDandF =
         LiquiditySwapLock.initialize(
-            contractTokenD, contractTokenF, FEE_DF, new LiquiditySwapLock.PermissionAnybody());
+            contractTokenD,
+            contractTokenF,
+            FEE_DF,
+            new LiquiditySwapLock.PermissionAnybody(),
+            null);
     swapLockContractAddressDandF =
         blockchain.deployContract(contractOwnerAddress, contractBytesSwap, initRpcSwapDandF);
 
     // Don't include D -> E, as it's supposed to be unknown to the router.
     return List.of(
         new SwapRouter.SwapContractInfo(
-            swapLockContractAddressAandB, contractTokenA, contractTokenB),
+            swapLockContractAddressAandB, contractTokenA, contractTokenB, true, true),
         new SwapRouter.SwapContractInfo(
-            swapLockContractAddressAandC, contractTokenA, contractTokenC),
+            swapLockContractAddressAandC, contractTokenA, contractTokenC, true, true),
         new SwapRouter.SwapContractInfo(
-            swapLockContractAddressBandD, contractTokenB, contractTokenD),
+            swapLockContractAddressBandD, contractTokenB, contractTokenD, true, true),
         new SwapRouter.SwapContractInfo(
-            swapLockContractAddressCandD, contractTokenC, contractTokenD),
+            swapLockContractAddressCandD, contractTokenC, contractTokenD, true, true),
         new SwapRouter.SwapContractInfo(
-            swapLockContractAddressAandF, contractTokenA, contractTokenF),
+            swapLockContractAddressAandF, contractTokenA, contractTokenF, true, true),
         new SwapRouter.SwapContractInfo(
-            swapLockContractAddressDandF, contractTokenD, contractTokenF));
+            swapLockContractAddressDandF, contractTokenD, contractTokenF, true, true));
   }
 
   private void depositIntoSwap(
@@ -1310,6 +1618,222 @@ public abstract class RoutingTest extends JunitContractTest {
     blockchain.sendAction(sender, swapContract, LiquiditySwapLock.deposit(contractToken, amount));
   }
 
+  /** A completed route increments the completed routes counter and the input token volume. */
+  @ContractTest(previous = "contractInit")
+  void completedRouteIsRecordedInStatistics() {
+    blockchain.sendAction(
+        nonOwnerAddress1, contractTokenA, Token.approve(routerContract, NON_OWNER_TOKEN_AMOUNT_A));
+
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandC, swapLockContractAddressCandD);
+    blockchain.sendAction(
+        nonOwnerAddress1,
+        routerContract,
+        SwapRouter.routeSwap(
+            swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO, null),
+        swapRouteGasAmount);
+
+    SwapRouter.RouteStatistics statistics = getRouterState().routeStatistics();
+    Assertions.assertThat(statistics.completedRoutes()).isEqualTo(1);
+    Assertions.assertThat(statistics.cancelledRoutes()).isEqualTo(0);
+    Assertions.assertThat(statistics.volumePerToken().get(contractTokenA))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
+    Assertions.assertThat(statistics.volumePerToken().get(contractTokenC)).isNull();
+    Assertions.assertThat(
+            getRouterState().routeTracker().activeRoutesPerUser().get(nonOwnerAddress1))
+        .isNull();
+  }
+
+  /** A cancelled route increments the cancelled routes counter, but not the volume. */
+  @Previous("contractInit")
+  @RepeatedTest(5)
+  void cancelledRouteIsRecordedInStatistics(RepetitionInfo repetitionInfo) {
+    blockchain.sendAction(
+        nonOwnerAddress1, contractTokenA, Token.approve(routerContract, NON_OWNER_TOKEN_AMOUNT_A));
+
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandC, swapLockContractAddressCandD);
+    TxExecution s1 =
+        blockchain.sendActionAsync(
+            nonOwnerAddress1,
+            routerContract,
+            SwapRouter.routeSwap(
+                swapRoute,
+                contractTokenA,
+                contractTokenD,
+                NON_OWNER_TOKEN_AMOUNT_A,
+                new BigInteger("10000"),
+                null),
+            swapRouteGasAmount);
+
+    ExecutionUtil.executeTxExecutionInUnpredictableOrder(this, repetitionInfo, List.of(s1))
+        .assertFailures(
+            swapLockContractAddressCandD,
+            "Swap would produce 8184 output tokens, but minimum was set to 10000",
+            routerContract,
+            "Could not acquire all locks in route.");
+
+    SwapRouter.RouteStatistics statistics = getRouterState().routeStatistics();
+    Assertions.assertThat(statistics.completedRoutes()).isEqualTo(0);
+    Assertions.assertThat(statistics.cancelledRoutes()).isEqualTo(1);
+    Assertions.assertThat(statistics.volumePerToken().get(contractTokenA)).isNull();
+    Assertions.assertThat(
+            getRouterState().routeTracker().activeRoutesPerUser().get(nonOwnerAddress1))
+        .isNull();
+  }
+
+  /**
+   * If a lock cannot be acquired on a route allowing partial execution, the locks acquired so far
+   * are executed if they meet the partial minimum output, and the user receives the intermediate
+   * token of the last acquired lock.
+   */
+  @Previous("contractInit")
+  @RepeatedTest(5)
+  void partialRouteReturnsIntermediateToken(RepetitionInfo repetitionInfo) {
+    // Route swap A -> C -> D, where A -> C produces 2047 C tokens, but C -> D cannot meet the
+    // minimum output.
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandC, swapLockContractAddressCandD);
+
+    // Approve the router at the original token.
+    blockchain.sendAction(
+        nonOwnerAddress1, contractTokenA, Token.approve(routerContract, NON_OWNER_TOKEN_AMOUNT_A));
+
+    TxExecution s1 =
+        blockchain.sendActionAsync(
+            nonOwnerAddress1,
+            routerContract,
+            SwapRouter.routeSwap(
+                swapRoute,
+                contractTokenA,
+                contractTokenD,
+                NON_OWNER_TOKEN_AMOUNT_A,
+                new BigInteger("10000"),
+                BigInteger.valueOf(2047)),
+            swapRouteGasAmount);
+
+    ExecutionUtil.executeTxExecutionInUnpredictableOrder(this, repetitionInfo, List.of(s1))
+        .assertFailure(
+            swapLockContractAddressCandD,
+            "Swap would produce 8184 output tokens, but minimum was set to 10000");
+
+    // The user ends up with the intermediate C tokens, rather than A or D tokens.
+    Assertions.assertThat(getTokenBalance(contractTokenA, nonOwnerAddress1)).isEqualTo(ZERO);
+    Assertions.assertThat(getTokenBalance(contractTokenC, nonOwnerAddress1))
+        .isEqualTo(BigInteger.valueOf(2047));
+    Assertions.assertThat(getTokenBalance(contractTokenD, nonOwnerAddress1)).isEqualTo(ZERO);
+    Assertions.assertThat(getTokenBalance(contractTokenC, routerContract)).isEqualTo(ZERO);
+
+    // No locks are left behind.
+    Assertions.assertThat(
+            getSwapState(swapLockContractAddressAandC).virtualState().locks().getNextN(null, 100))
+        .hasSize(0);
+    Assertions.assertThat(
+            getSwapState(swapLockContractAddressCandD).virtualState().locks().getNextN(null, 100))
+        .hasSize(0);
+
+    SwapRouter.RouteStatistics statistics = getRouterState().routeStatistics();
+    Assertions.assertThat(statistics.completedRoutes()).isEqualTo(1);
+    Assertions.assertThat(statistics.cancelledRoutes()).isEqualTo(0);
+  }
+
+  /**
+   * If the first lock cannot be acquired on a route allowing partial execution, there is nothing to
+   * execute, and the route is cancelled with the input tokens returned to the user.
+   */
+  @Previous("contractInit")
+  @RepeatedTest(5)
+  void partialRouteWithoutAcquiredLocksIsCancelled(RepetitionInfo repetitionInfo) {
+    // Route swap A -> F -> D, where A -> F has no liquidity.
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandF, swapLockContractAddressDandF);
+
+    // Approve the router at the original token.
+    blockchain.sendAction(
+        nonOwnerAddress1, contractTokenA, Token.approve(routerContract, NON_OWNER_TOKEN_AMOUNT_A));
+
+    TxExecution s1 =
+        blockchain.sendActionAsync(
+            nonOwnerAddress1,
+            routerContract,
+            SwapRouter.routeSwap(
+                swapRoute, contractTokenA, contractTokenD, NON_OWNER_TOKEN_AMOUNT_A, ZERO, ZERO),
+            swapRouteGasAmount);
+
+    ExecutionUtil.executeTxExecutionInUnpredictableOrder(this, repetitionInfo, List.of(s1))
+        .assertFailures(
+            swapLockContractAddressAandF,
+            "Pools must have existing liquidity to acquire a lock",
+            routerContract,
+            "Could not acquire all locks in route.");
+
+    // Check for no locks, and no tokens taken from the user
+    Assertions.assertThat(
+            getSwapState(swapLockContractAddressAandF).virtualState().locks().getNextN(null, 100))
+        .hasSize(0);
+    Assertions.assertThat(getTokenBalance(contractTokenA, nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
+
+    SwapRouter.RouteStatistics statistics = getRouterState().routeStatistics();
+    Assertions.assertThat(statistics.completedRoutes()).isEqualTo(0);
+    Assertions.assertThat(statistics.cancelledRoutes()).isEqualTo(1);
+  }
+
+  /**
+   * If the locks acquired on a route allowing partial execution produce less than the partial
+   * minimum output, the route is cancelled with the input tokens returned to the user.
+   */
+  @Previous("contractInit")
+  @RepeatedTest(5)
+  void partialRouteBelowPartialMinimumIsCancelled(RepetitionInfo repetitionInfo) {
+    // Route swap A -> C -> D, where A -> C produces 2047 C tokens, one less than the partial
+    // minimum, and C -> D cannot meet the minimum output.
+    List<BlockchainAddress> swapRoute =
+        List.of(swapLockContractAddressAandC, swapLockContractAddressCandD);
+
+    // Approve the router at the original token.
+    blockchain.sendAction(
+        nonOwnerAddress1, contractTokenA, Token.approve(routerContract, NON_OWNER_TOKEN_AMOUNT_A));
+
+    TxExecution s1 =
+        blockchain.sendActionAsync(
+            nonOwnerAddress1,
+            routerContract,
+            SwapRouter.routeSwap(
+                swapRoute,
+                contractTokenA,
+                contractTokenD,
+                NON_OWNER_TOKEN_AMOUNT_A,
+                new BigInteger("10000"),
+                BigInteger.valueOf(2048)),
+            swapRouteGasAmount);
+
+    ExecutionUtil.executeTxExecutionInUnpredictableOrder(this, repetitionInfo, List.of(s1))
+        .assertFailures(
+            swapLockContractAddressCandD,
+            "Swap would produce 8184 output tokens, but minimum was set to 10000",
+            routerContract,
+            "Could not acquire all locks in route.");
+
+    // The user keeps the A tokens, and receives no C or D tokens.
+    Assertions.assertThat(getTokenBalance(contractTokenA, nonOwnerAddress1))
+        .isEqualTo(NON_OWNER_TOKEN_AMOUNT_A);
+    Assertions.assertThat(getTokenBalance(contractTokenC, nonOwnerAddress1)).isEqualTo(ZERO);
+    Assertions.assertThat(getTokenBalance(contractTokenD, nonOwnerAddress1)).isEqualTo(ZERO);
+
+    // No locks are left behind.
+    Assertions.assertThat(
+            getSwapState(swapLockContractAddressAandC).virtualState().locks().getNextN(null, 100))
+        .hasSize(0);
+    Assertions.assertThat(
+            getSwapState(swapLockContractAddressCandD).virtualState().locks().getNextN(null, 100))
+        .hasSize(0);
+
+    SwapRouter.RouteStatistics statistics = getRouterState().routeStatistics();
+    Assertions.assertThat(statistics.completedRoutes()).isEqualTo(0);
+    Assertions.assertThat(statistics.cancelledRoutes()).isEqualTo(1);
+  }
+
   private SwapRouter.RouterState getRouterState() {
     return new SwapRouter(getStateClient(), routerContract).getState();
   }
@@ -1328,6 +1852,15 @@ public abstract class RoutingTest extends JunitContractTest {
   protected abstract BigInteger getTokenBalance(
       BlockchainAddress tokenContract, BlockchainAddress account);
 
+  /**
+   * Creates the RPC for initializing a token contract with 8 decimals.
+   *
+   * @param name Name of the token. Not nullable.
+   * @param symbol Symbol of the token. Not nullable.
+   * @param totalSupply Initial supply of the token. Not nullable.
+   */
+  protected abstract byte[] initializeTokenRpc(String name, String symbol, BigInteger totalSupply);
+
   private BlockchainAddress getMatchingToken(
       BlockchainAddress swapContract, BlockchainAddress token) {
     if (swapContract.equals(swapLockContractAddressAandB)) {
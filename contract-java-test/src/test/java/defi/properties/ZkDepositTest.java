@@ -8,6 +8,7 @@ import com.partisiablockchain.language.codegenlib.BlockchainStateClient;
 import com.partisiablockchain.language.junit.ContractBytes;
 import com.partisiablockchain.language.junit.JunitContractTest;
 import com.partisiablockchain.language.junit.Previous;
+import com.partisiablockchain.language.testenvironment.TxExecution;
 import com.partisiablockchain.language.testenvironment.zk.node.RealV1FakeNodes;
 import com.secata.stream.BitInput;
 import com.secata.stream.BitOutput;
@@ -17,8 +18,10 @@ import java.math.BigInteger;
 import java.util.Base64;
 import java.util.HashSet;
 import java.util.LinkedHashMap;
+import java.util.List;
 import java.util.Map;
 import java.util.Set;
+import java.util.concurrent.atomic.AtomicLong;
 import org.assertj.core.api.Assertions;
 import org.junit.jupiter.api.Test;
 
@@ -38,9 +41,16 @@ public abstract class ZkDepositTest extends JunitContractTest {
   public static final int TOKEN_BIT_SIZE = 128;
   public static final int RECIPIENT_KEY_BIT_SIZE = 128;
 
+  public static final int MAX_QUEUE_LENGTH = 10;
+
+  /** Nonces for transfer requests that do not specify a nonce, unique across all tests. */
+  private static final AtomicLong NEXT_TRANSFER_NONCE = new AtomicLong(1_000);
+
   public BlockchainAddress accountCreator;
   public BlockchainAddress accountSender;
   public BlockchainAddress accountApprover;
+  public BlockchainAddress accountApprover2;
+  public BlockchainAddress accountApprover3;
   public BlockchainAddress accountRecipient;
   public BlockchainAddress accountNoAccount;
   public BlockchainAddress contractToken;
@@ -77,18 +87,31 @@ public abstract class ZkDepositTest extends JunitContractTest {
     accountNoAccount = blockchain.newAccount(5);
 
     // Setup tokens
-    final byte[] initToken = Token.initialize("Token A", "AAA", (byte) 0, TOTAL_SUPPLY);
+    final byte[] initToken = Token.initialize("Token A", "AAA", (byte) 0, TOTAL_SUPPLY, null);
     contractToken = blockchain.deployContract(accountCreator, contractBytesToken, initToken);
 
     // Setup swap
-    final byte[] initRpcDeposit = ZkDeposit.initialize(accountApprover, contractToken);
-    if (contractBytesDeposit.codeFormat() == ContractBytes.CodeFormat.ZKWA) {
-      contractDeposit =
-          blockchain.deployZkContract(accountCreator, contractBytesDeposit, initRpcDeposit);
-    } else {
-      contractDeposit =
-          blockchain.deployContract(accountCreator, contractBytesDeposit, initRpcDeposit);
-    }
+    contractDeposit = deployDeposit(List.of(accountApprover), 1);
+  }
+
+  /** Setup for tests where transfers must be approved by two out of three approvers. */
+  @Test
+  @Previous("initializeContracts")
+  void initializeContractsWithMultipleApprovers() {
+    accountApprover2 = blockchain.newAccount(6);
+    accountApprover3 = blockchain.newAccount(7);
+    contractDeposit =
+        deployDeposit(List.of(accountApprover, accountApprover2, accountApprover3), 2);
+
+    createAccount(accountSender, RECIPIENT_KEY_SENDER);
+    createAccount(accountRecipient, RECIPIENT_KEY_RECIPIENT);
+    blockchain.sendAction(
+        accountCreator, contractToken, Token.transfer(accountSender, BigInteger.valueOf(3_000L)));
+    approveDeposit(accountSender, BigInteger.valueOf(1_000));
+    deposit(accountSender, BigInteger.valueOf(1_000));
+
+    assertDepositBalance(accountSender, 1_000);
+    assertInvariantsAtIdle();
   }
 
   /** Users must be able to instantiate accounts in the zk-deposit contract. */
@@ -112,6 +135,29 @@ public abstract class ZkDepositTest extends JunitContractTest {
     assertInvariantsAtIdle();
   }
 
+  /** Account existence is public, and is only set once the account creation has completed. */
+  @Test
+  @Previous("initializeContracts")
+  void accountExistsAfterAccountCreationCompletes() {
+    Assertions.assertThat(accountExists(accountSender)).isFalse();
+
+    zkNodes.stop();
+    final byte[] publicRpc =
+        SafeDataOutputStream.serialize(s -> s.writeByte(SHORTNAME_CREATE_ACCOUNT));
+    final CompactBitArray secretRpc =
+        BitOutput.serializeBits(
+            s -> s.writeUnsignedBigInteger(RECIPIENT_KEY_SENDER, RECIPIENT_KEY_BIT_SIZE));
+    blockchain.sendSecretInput(contractDeposit, accountSender, secretRpc, publicRpc, 40_000);
+
+    Assertions.assertThat(accountExists(accountSender)).isFalse();
+
+    zkNodes.finishTasks();
+
+    Assertions.assertThat(accountExists(accountSender)).isTrue();
+    Assertions.assertThat(accountExists(accountRecipient)).isFalse();
+    assertInvariantsAtIdle();
+  }
+
   /**
    * Users can deposit amounts, which result in secret-shared balances, which can be read by the
    * owner.
@@ -162,6 +208,41 @@ public abstract class ZkDepositTest extends JunitContractTest {
     assertInvariantsAtIdle();
   }
 
+  /** A sender cannot request a transfer with the nonce of one of their pending transfers. */
+  @Test
+  @Previous("senderDepositToken")
+  void duplicateNonceIsRejectedWhilePending() {
+    requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(400), 7);
+
+    Assertions.assertThatCode(
+            () ->
+                requestTransfer(
+                    accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(400), 7))
+        .hasStackTraceContaining("already has a pending transfer with nonce 7");
+    Assertions.assertThat(getPendingTransferCount()).isEqualTo(1);
+
+    // Nonces are per sender
+    requestTransfer(accountRecipient, RECIPIENT_KEY_SENDER, BigInteger.valueOf(0), 7);
+    Assertions.assertThat(getPendingTransferCount()).isEqualTo(2);
+  }
+
+  /** A nonce can be reused once the transfer using it has been approved and completed. */
+  @Test
+  @Previous("senderDepositToken")
+  void nonceCanBeReusedAfterCompletion() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(400), 7);
+    approveTransfer(accountApprover, transferId);
+
+    final int secondTransferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(100), 7);
+    approveTransfer(accountApprover, secondTransferId);
+
+    assertDepositBalance(accountSender, 500);
+    assertDepositBalance(accountRecipient, 500);
+    assertInvariantsAtIdle();
+  }
+
   /** Users can withdraw tokens from their accounts if they have enough. */
   @Test
   @Previous("requestTransferApproveAndExecute")
@@ -174,9 +255,167 @@ public abstract class ZkDepositTest extends JunitContractTest {
     assertInvariantsAtIdle();
   }
 
+  /** Transfers are executed once two out of three approvers have approved them. */
+  @Test
+  @Previous("initializeContractsWithMultipleApprovers")
+  void twoOfThreeApproversCanApproveTransfer() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(400));
+
+    sendActionToCut(accountApprover3, ZkDeposit.approveTransfer(transferId), 10_000);
+    approveTransfer(accountApprover2, transferId);
+
+    Assertions.assertThat(getPendingTransferCount()).isZero();
+    assertDepositBalance(accountSender, 600);
+    assertDepositBalance(accountRecipient, 400);
+    assertInvariantsAtIdle();
+  }
+
+  /** The first approver can migrate the contract to a new token while the contract is idle. */
+  @Test
+  @Previous("createUserAccounts")
+  void changeTokenAddressWhenIdle() {
+    final byte[] initToken = Token.initialize("Token B", "BBB", (byte) 0, TOTAL_SUPPLY, null);
+    final BlockchainAddress newToken =
+        blockchain.deployContract(accountCreator, contractBytesToken, initToken);
+
+    sendActionToCut(accountApprover, ZkDeposit.setTokenAddress(newToken), 10_000);
+    Assertions.assertThat(
+            new ZkDeposit(getStateClient(), contractDeposit).getState().openState().tokenAddress())
+        .isEqualTo(newToken);
+
+    // Deposits with the old token are rejected
+    Assertions.assertThatCode(() -> deposit(accountSender, BigInteger.valueOf(100)))
+        .hasStackTraceContaining("Unknown token");
+
+    // Subsequent deposits use the new token
+    contractToken = newToken;
+    blockchain.sendAction(
+        accountCreator, contractToken, Token.transfer(accountSender, BigInteger.valueOf(1_000L)));
+    approveDeposit(accountSender, BigInteger.valueOf(1_000));
+    deposit(accountSender, BigInteger.valueOf(1_000));
+
+    assertDepositBalance(accountSender, 1_000);
+    assertTokenBalance(accountSender, 0);
+    assertInvariantsAtIdle();
+  }
+
   ////////////////////////////////////////////////////////////////////////////////
   // Failing cases
 
+  /** The token address cannot be changed while work is queued or transfers are pending. */
+  @Test
+  @Previous("senderDepositToken")
+  void failToChangeTokenAddressWhileNotIdle() {
+    final BlockchainAddress newToken = blockchain.newAccount(8);
+
+    // Pending transfers
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(100));
+    Assertions.assertThatCode(
+            () -> sendActionToCut(accountApprover, ZkDeposit.setTokenAddress(newToken), 10_000))
+        .hasStackTraceContaining(
+            "Cannot change the token address while transfers are pending approval");
+    approveTransfer(accountApprover, transferId);
+
+    // Queued work
+    zkNodes.stop();
+    deposit(accountSender, BigInteger.valueOf(100));
+    deposit(accountSender, BigInteger.valueOf(100));
+    Assertions.assertThatCode(
+            () -> sendActionToCut(accountApprover, ZkDeposit.setTokenAddress(newToken), 10_000))
+        .hasStackTraceContaining("Cannot change the token address while work is queued");
+    zkNodes.finishTasks();
+
+    Assertions.assertThat(
+            new ZkDeposit(getStateClient(), contractDeposit).getState().openState().tokenAddress())
+        .isEqualTo(contractToken);
+    assertDepositBalance(accountSender, 1_100);
+    assertInvariantsAtIdle();
+  }
+
+  /** The token address cannot be changed while the tokens of a deposit are being transferred. */
+  @Test
+  @Previous("senderDepositToken")
+  void failToChangeTokenAddressWhileDepositIsInFlight() {
+    final BlockchainAddress newToken = blockchain.newAccount(8);
+    approveDeposit(accountSender, BigInteger.valueOf(100));
+
+    final TxExecution transferEvent = startDeposit(accountSender, BigInteger.valueOf(100));
+    Assertions.assertThatCode(
+            () -> sendActionToCut(accountApprover, ZkDeposit.setTokenAddress(newToken), 10_000))
+        .hasStackTraceContaining("Cannot change the token address while deposits are in flight");
+
+    blockchain.executeEvent(transferEvent);
+    Assertions.assertThat(
+            new ZkDeposit(getStateClient(), contractDeposit).getState().openState().tokenAddress())
+        .isEqualTo(contractToken);
+    assertDepositBalance(accountSender, 1_100);
+    assertInvariantsAtIdle();
+  }
+
+  /** Only the first approver can change the token address. */
+  @Test
+  @Previous("initializeContractsWithMultipleApprovers")
+  void failToChangeTokenAddressAsOtherUser() {
+    for (final BlockchainAddress user : List.of(accountSender, accountApprover2)) {
+      Assertions.assertThatCode(
+              () -> sendActionToCut(user, ZkDeposit.setTokenAddress(user), 10_000))
+          .hasStackTraceContaining(
+              "Only the first transfer approver can change the token address");
+    }
+  }
+
+  /** The approval threshold must be reachable by the given approvers. */
+  @Test
+  @Previous("initializeContracts")
+  void failToDeployWithUnreachableApprovalThreshold() {
+    Assertions.assertThatCode(() -> deployDeposit(List.of(accountApprover), 2))
+        .hasStackTraceContaining(
+            "Approval threshold must be between 1 and the number of approvers");
+    Assertions.assertThatCode(() -> deployDeposit(List.of(accountApprover), 0))
+        .hasStackTraceContaining(
+            "Approval threshold must be between 1 and the number of approvers");
+  }
+
+  /** A single approval is not enough when the threshold is two. */
+  @Test
+  @Previous("initializeContractsWithMultipleApprovers")
+  void singleApprovalIsNotEnough() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(400));
+
+    sendActionToCut(accountApprover, ZkDeposit.approveTransfer(transferId), 10_000);
+
+    Assertions.assertThat(getQueueSize()).isZero();
+    Assertions.assertThat(getPendingTransferCount()).isEqualTo(1);
+    assertDepositBalance(accountSender, 1_000);
+    assertDepositBalance(accountRecipient, 0);
+    assertInvariantsAtIdle();
+  }
+
+  /** Approving the same transfer twice does not count as two approvals. */
+  @Test
+  @Previous("initializeContractsWithMultipleApprovers")
+  void duplicateApprovalsDoNotCountTwice() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(400));
+
+    sendActionToCut(accountApprover, ZkDeposit.approveTransfer(transferId), 10_000);
+    Assertions.assertThatCode(
+            () -> sendActionToCut(accountApprover, ZkDeposit.approveTransfer(transferId), 10_000))
+        .hasStackTraceContaining("has already approved the pending request with id " + transferId);
+
+    Assertions.assertThat(getPendingTransferCount()).isEqualTo(1);
+    assertDepositBalance(accountSender, 1_000);
+    assertDepositBalance(accountRecipient, 0);
+
+    approveTransfer(accountApprover2, transferId);
+    assertDepositBalance(accountSender, 600);
+    assertDepositBalance(accountRecipient, 400);
+    assertInvariantsAtIdle();
+  }
+
   /** Users cannot create two accounts for the same blockchain address. */
   @Test
   @Previous("initializeContracts")
@@ -241,7 +480,7 @@ public abstract class ZkDepositTest extends JunitContractTest {
 
     // Approve transfer
     Assertions.assertThatCode(() -> approveTransfer(accountSender, transferId))
-        .hasStackTraceContaining("Approver is the only user that can approve transfers");
+        .hasStackTraceContaining("Only transfer approvers can approve transfers");
 
     assertDepositBalance(accountSender, 1_000);
     assertDepositBalance(accountRecipient, 0);
@@ -249,6 +488,88 @@ public abstract class ZkDepositTest extends JunitContractTest {
     assertInvariantsAtIdle();
   }
 
+  /** Senders can cancel their own transfers before they have been approved. */
+  @Test
+  @Previous("senderDepositToken")
+  void senderCanCancelTransferRequest() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(400));
+
+    sendActionToCut(accountSender, ZkDeposit.cancelTransferRequest(transferId), 10_000);
+
+    Assertions.assertThat(getPendingTransferCount()).isZero();
+    Assertions.assertThat(variableWithId(transferId)).isNull();
+
+    Assertions.assertThatCode(() -> approveTransfer(accountApprover, transferId))
+        .hasStackTraceContaining("Could not find a pending request with id " + transferId);
+
+    assertDepositBalance(accountSender, 1_000);
+    assertDepositBalance(accountRecipient, 0);
+    assertInvariantsAtIdle();
+  }
+
+  /** The pending transfer count tracks requested, approved and cancelled transfers. */
+  @Test
+  @Previous("initializeContractsWithMultipleApprovers")
+  void pendingTransferCountTracksRequestsApprovalsAndCancellations() {
+    Assertions.assertThat(getPendingTransferCountFromState()).isZero();
+
+    final int firstId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(100));
+    final int secondId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(200));
+    final int thirdId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(300));
+    Assertions.assertThat(getPendingTransferCountFromState()).isEqualTo(3);
+
+    // A single approval does not complete the approval, so the transfer is still pending.
+    sendActionToCut(accountApprover, ZkDeposit.approveTransfer(firstId), 10_000);
+    Assertions.assertThat(getPendingTransferCountFromState()).isEqualTo(3);
+
+    approveTransfer(accountApprover2, firstId);
+    Assertions.assertThat(getPendingTransferCountFromState()).isEqualTo(2);
+
+    sendActionToCut(accountSender, ZkDeposit.cancelTransferRequest(secondId), 10_000);
+    Assertions.assertThat(getPendingTransferCountFromState()).isEqualTo(1);
+
+    Assertions.assertThat(getPendingTransferCount()).isEqualTo(1);
+    Assertions.assertThat(variableWithId(thirdId)).isNotNull();
+    assertInvariantsAtIdle();
+  }
+
+  /** Only the sender of a transfer can cancel it. */
+  @Test
+  @Previous("senderDepositToken")
+  void nonSenderCannotCancelTransferRequest() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(400));
+
+    for (final BlockchainAddress user : List.of(accountRecipient, accountApprover)) {
+      Assertions.assertThatCode(
+              () -> sendActionToCut(user, ZkDeposit.cancelTransferRequest(transferId), 10_000))
+          .hasStackTraceContaining("Only the sender of the transfer can cancel it");
+    }
+
+    Assertions.assertThat(getPendingTransferCount()).isEqualTo(1);
+
+    approveTransfer(accountApprover, transferId);
+    assertDepositBalance(accountSender, 600);
+    assertDepositBalance(accountRecipient, 400);
+    assertInvariantsAtIdle();
+  }
+
+  /** Transfers that does not exist cannot be cancelled. */
+  @Test
+  @Previous("senderDepositToken")
+  void failToCancelNonExistingTransfer() {
+    Assertions.assertThatCode(
+            () -> sendActionToCut(accountSender, ZkDeposit.cancelTransferRequest(999), 10_000))
+        .hasStackTraceContaining("Could not find a pending request with id 999");
+
+    assertDepositBalance(accountSender, 1_000);
+    assertInvariantsAtIdle();
+  }
+
   /** Approvers cannot approve transfers that does not exist. */
   @Test
   @Previous("senderDepositToken")
@@ -410,6 +731,41 @@ public abstract class ZkDepositTest extends JunitContractTest {
     assertInvariantsAtIdle();
   }
 
+  /**
+   * Withdrawing more than the deposited balance fails with a clear insufficient balance signal,
+   * and no tokens are transferred.
+   */
+  @Test
+  @Previous("requestTransferApproveAndExecute")
+  void failToWithdrawMoreThanBalance() {
+    Assertions.assertThatCode(() -> withdraw(accountRecipient, BigInteger.valueOf(401)))
+        .hasStackTraceContaining(
+            "Insufficient deposit balance! Could not withdraw 401 tokens, as user do not have"
+                + " that amount deposited");
+
+    assertDepositBalance(accountRecipient, 400);
+    assertTokenBalance(accountRecipient, 0);
+    assertInvariantsAtIdle();
+
+    // Queue is still live
+    withdraw(accountRecipient, BigInteger.valueOf(400));
+    assertTokenBalance(accountRecipient, 400);
+  }
+
+  /** The insufficient balance event can only be emitted by the contract itself. */
+  @Test
+  @Previous("senderDepositToken")
+  void insufficientBalanceEventCannotBeCalledDirectlyByUsers() {
+    Assertions.assertThatCode(
+            () ->
+                sendActionToCut(
+                    accountSender,
+                    ZkDeposit.insufficientBalanceEvent(accountSender, BigInteger.valueOf(100)),
+                    100_000))
+        .hasStackTraceContaining(
+            "This is an internal invocation. Must not be invoked by outside users.");
+  }
+
   /** Fail to send some very large amounts that user does not own. */
   @Test
   @Previous("senderDepositToken")
@@ -490,6 +846,248 @@ public abstract class ZkDepositTest extends JunitContractTest {
     assertTokenBalance(accountSender, 2_000);
   }
 
+  /**
+   * Users can deposit multiple amounts at once, which schedules a single work item for the sum of
+   * the amounts.
+   */
+  @Test
+  @Previous("createUserAccounts")
+  void depositManyAmounts() {
+    approveDeposit(accountSender, BigInteger.valueOf(1_000));
+
+    zkNodes.stop();
+
+    // The first deposit starts computing immediately, and is therefore not in the queue.
+    deposit(accountSender, BigInteger.valueOf(100));
+    Assertions.assertThat(getQueueSize()).isEqualTo(0);
+
+    depositMany(
+        accountSender,
+        List.of(BigInteger.valueOf(100), BigInteger.valueOf(200), BigInteger.valueOf(300)));
+    Assertions.assertThat(getQueueSize()).isEqualTo(1);
+    assertTokenBalance(accountSender, 2_300);
+
+    zkNodes.finishTasks();
+
+    assertDepositBalance(accountSender, 700);
+    assertTokenBalance(accountSender, 2_300);
+    assertInvariantsAtIdle();
+  }
+
+  /** Users cannot deposit multiple amounts if they do not have an account. */
+  @Test
+  @Previous("createUserAccounts")
+  void failToDepositManyWithoutCreatingAccount() {
+    blockchain.sendAction(
+        accountCreator,
+        contractToken,
+        Token.transfer(accountNoAccount, BigInteger.valueOf(1_000L)));
+
+    approveDeposit(accountNoAccount, BigInteger.valueOf(1_000));
+    Assertions.assertThatCode(
+            () ->
+                depositMany(
+                    accountNoAccount, List.of(BigInteger.valueOf(100), BigInteger.valueOf(200))))
+        .hasStackTraceContaining(
+            "User does not possess an account: 00C5DCB3BCF6F048B0A765184B55B3F8D89DEA7377");
+
+    Assertions.assertThat(getTokenBalance(accountNoAccount)).isEqualTo(BigInteger.valueOf(1_000));
+    assertInvariantsAtIdle();
+  }
+
+  /**
+   * Deposits, withdrawals and transfer approvals are rejected once the queue is full, and are
+   * accepted again once the queue has been worked through.
+   */
+  @Test
+  @Previous("senderDepositToken")
+  void queueLengthIsCapped() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(100));
+    approveDeposit(accountSender, BigInteger.valueOf(2_000));
+
+    zkNodes.stop();
+
+    // The first deposit starts computing immediately, and is therefore not in the queue.
+    for (int i = 0; i <= MAX_QUEUE_LENGTH; i++) {
+      deposit(accountSender, BigInteger.valueOf(100));
+    }
+    Assertions.assertThat(getQueueSize()).isEqualTo(MAX_QUEUE_LENGTH);
+
+    Assertions.assertThatCode(() -> deposit(accountSender, BigInteger.valueOf(100)))
+        .hasStackTraceContaining("The work queue is full");
+    Assertions.assertThatCode(() -> withdraw(accountSender, BigInteger.valueOf(100)))
+        .hasStackTraceContaining("The work queue is full");
+    Assertions.assertThatCode(() -> approveTransfer(accountApprover, transferId))
+        .hasStackTraceContaining("The work queue is full");
+
+    Assertions.assertThat(getQueueSize()).isEqualTo(MAX_QUEUE_LENGTH);
+    Assertions.assertThat(getPendingTransferCount()).isEqualTo(1);
+    assertTokenBalance(accountSender, 2_000 - 100 * (MAX_QUEUE_LENGTH + 1));
+
+    zkNodes.finishTasks();
+    Assertions.assertThat(getQueueSize()).isZero();
+
+    // Slots are freed once the queued items have been completed.
+    approveTransfer(accountApprover, transferId);
+    deposit(accountSender, BigInteger.valueOf(100));
+
+    assertDepositBalance(accountSender, 1_000 + 100 * (MAX_QUEUE_LENGTH + 2) - 100);
+    assertDepositBalance(accountRecipient, 100);
+    assertTokenBalance(accountSender, 2_000 - 100 * (MAX_QUEUE_LENGTH + 2));
+    assertInvariantsAtIdle();
+  }
+
+  /**
+   * Deposits whose tokens are still being transferred count against the queue cap, as they are
+   * added to the queue once the transfer completes.
+   */
+  @Test
+  @Previous("senderDepositToken")
+  void depositsInFlightCountAgainstQueueCap() {
+    approveDeposit(accountSender, BigInteger.valueOf(2_000));
+
+    zkNodes.stop();
+
+    // The first deposit starts computing immediately, and is therefore not in the queue.
+    for (int i = 0; i < MAX_QUEUE_LENGTH; i++) {
+      deposit(accountSender, BigInteger.valueOf(100));
+    }
+    Assertions.assertThat(getQueueSize()).isEqualTo(MAX_QUEUE_LENGTH - 1);
+
+    final TxExecution transferEvent = startDeposit(accountSender, BigInteger.valueOf(100));
+    Assertions.assertThat(getDepositsInFlight()).isEqualTo(1);
+    Assertions.assertThatCode(() -> deposit(accountSender, BigInteger.valueOf(100)))
+        .hasStackTraceContaining("The work queue is full");
+
+    blockchain.executeEvent(transferEvent);
+    Assertions.assertThat(getDepositsInFlight()).isZero();
+    Assertions.assertThat(getQueueSize()).isEqualTo(MAX_QUEUE_LENGTH);
+
+    zkNodes.finishTasks();
+    assertDepositBalance(accountSender, 1_000 + 100 * (MAX_QUEUE_LENGTH + 1));
+    assertInvariantsAtIdle();
+  }
+
+  /** A deposit whose transfer fails is no longer counted as in flight. */
+  @Test
+  @Previous("senderDepositToken")
+  void failedDepositTransferIsNoLongerInFlight() {
+    approveDeposit(accountSender, BigInteger.valueOf(100));
+
+    final TxExecution transferEvent = startDeposit(accountSender, BigInteger.valueOf(200));
+    Assertions.assertThat(getDepositsInFlight()).isEqualTo(1);
+    final TxExecution transfer = blockchain.executeEventAsync(transferEvent);
+    Assertions.assertThat(transfer.isSuccess()).isFalse();
+    final TxExecution systemCallback = blockchain.executeEventAsync(transfer.getSystemCallback());
+    final TxExecution callback =
+        blockchain.executeEventAsync(systemCallback.getContractCallback());
+    Assertions.assertThat(callback.isSuccess()).isTrue();
+
+    Assertions.assertThat(getDepositsInFlight()).isZero();
+    assertDepositBalance(accountSender, 1_000);
+    assertInvariantsAtIdle();
+  }
+
+  /** The contract cannot be deployed with a queue that cannot hold any work items. */
+  @Test
+  @Previous("initializeContracts")
+  void failToDeployWithEmptyQueue() {
+    final byte[] initRpcDeposit =
+        ZkDeposit.initialize(List.of(accountApprover), 1, contractToken, 0);
+    Assertions.assertThatCode(
+            () -> blockchain.deployZkContract(accountCreator, contractBytesDeposit, initRpcDeposit))
+        .hasStackTraceContaining("Max queue length must be at least 1");
+  }
+
+  /**
+   * Deposits, withdrawals, account creations and transfer approvals are rejected while the contract
+   * is paused.
+   */
+  @Test
+  @Previous("senderDepositToken")
+  void submissionsAreRejectedWhilePaused() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(100));
+    setPaused(true);
+
+    Assertions.assertThatCode(() -> deposit(accountSender, BigInteger.valueOf(100)))
+        .hasStackTraceContaining("The contract is paused");
+    Assertions.assertThatCode(
+            () -> depositMany(accountSender, List.of(BigInteger.ONE, BigInteger.TWO)))
+        .hasStackTraceContaining("The contract is paused");
+    Assertions.assertThatCode(() -> withdraw(accountSender, BigInteger.valueOf(100)))
+        .hasStackTraceContaining("The contract is paused");
+    Assertions.assertThatCode(() -> approveTransfer(accountApprover, transferId))
+        .hasStackTraceContaining("The contract is paused");
+    Assertions.assertThatCode(() -> createAccount(accountNoAccount, RECIPIENT_KEY_UNUSED))
+        .hasStackTraceContaining("The contract is paused");
+
+    Assertions.assertThat(accountExists(accountNoAccount)).isFalse();
+    Assertions.assertThat(getPendingTransferCount()).isEqualTo(1);
+    assertDepositBalance(accountSender, 1_000);
+    assertDepositBalance(accountRecipient, 0);
+    assertTokenBalance(accountSender, 2_000);
+    assertInvariantsAtIdle();
+  }
+
+  /** Work items queued before the contract was paused are still worked through. */
+  @Test
+  @Previous("senderDepositToken")
+  void queueIsDrainedWhilePaused() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(100));
+
+    zkNodes.stop();
+    deposit(accountSender, BigInteger.valueOf(100));
+    approveTransfer(accountApprover, transferId);
+    withdraw(accountSender, BigInteger.valueOf(200));
+    Assertions.assertThat(getQueueSize()).isEqualTo(2);
+
+    setPaused(true);
+    zkNodes.finishTasks();
+
+    Assertions.assertThat(getQueueSize()).isZero();
+    assertDepositBalance(accountSender, 800);
+    assertDepositBalance(accountRecipient, 100);
+    assertTokenBalance(accountSender, 2_100);
+    assertInvariantsAtIdle();
+  }
+
+  /** Unpausing the contract accepts submissions again. */
+  @Test
+  @Previous("senderDepositToken")
+  void unpausingRestoresSubmissions() {
+    final int transferId =
+        requestTransfer(accountSender, RECIPIENT_KEY_RECIPIENT, BigInteger.valueOf(100));
+    setPaused(true);
+    setPaused(false);
+
+    deposit(accountSender, BigInteger.valueOf(100));
+    approveTransfer(accountApprover, transferId);
+    withdraw(accountSender, BigInteger.valueOf(200));
+    createAccount(accountNoAccount, RECIPIENT_KEY_UNUSED);
+
+    Assertions.assertThat(accountExists(accountNoAccount)).isTrue();
+    assertDepositBalance(accountSender, 800);
+    assertDepositBalance(accountRecipient, 100);
+    assertTokenBalance(accountSender, 2_100);
+    assertInvariantsAtIdle();
+  }
+
+  /** Only the first approver can pause the contract. */
+  @Test
+  @Previous("initializeContractsWithMultipleApprovers")
+  void failToPauseAsOtherUser() {
+    for (final BlockchainAddress user : List.of(accountSender, accountApprover2)) {
+      Assertions.assertThatCode(() -> sendActionToCut(user, ZkDeposit.setPaused(true), 10_000))
+          .hasStackTraceContaining("Only the first transfer approver can pause the contract");
+    }
+    Assertions.assertThat(
+            new ZkDeposit(getStateClient(), contractDeposit).getState().openState().paused())
+        .isFalse();
+  }
+
   /**
    * Users can create a large queue of transfers that is executed once the nodes get around to it.
    */
@@ -593,6 +1191,16 @@ public abstract class ZkDepositTest extends JunitContractTest {
   ////////////////////////////////////////////////////////////////////////////////
   // Utility
 
+  private BlockchainAddress deployDeposit(List<BlockchainAddress> approvers, int threshold) {
+    final byte[] initRpcDeposit =
+        ZkDeposit.initialize(approvers, threshold, contractToken, MAX_QUEUE_LENGTH);
+    if (contractBytesDeposit.codeFormat() == ContractBytes.CodeFormat.ZKWA) {
+      return blockchain.deployZkContract(accountCreator, contractBytesDeposit, initRpcDeposit);
+    } else {
+      return blockchain.deployContract(accountCreator, contractBytesDeposit, initRpcDeposit);
+    }
+  }
+
   private void createAccount(BlockchainAddress user, BigInteger recipientKey) {
     final byte[] publicRpc =
         SafeDataOutputStream.serialize(s -> s.writeByte(SHORTNAME_CREATE_ACCOUNT));
@@ -606,6 +1214,10 @@ public abstract class ZkDepositTest extends JunitContractTest {
     // Skip checking of compute complexity as it varies too much.
   }
 
+  private void setPaused(boolean paused) {
+    sendActionToCut(accountApprover, ZkDeposit.setPaused(paused), 10_000);
+  }
+
   private void approveDeposit(BlockchainAddress owner, BigInteger amount) {
     blockchain.sendAction(owner, contractToken, Token.approve(contractDeposit, amount), 3_000);
   }
@@ -617,6 +1229,30 @@ public abstract class ZkDepositTest extends JunitContractTest {
     assertComputeComplexity(TOKEN_BIT_SIZE, TOKEN_BIT_SIZE);
   }
 
+  /**
+   * Starts a deposit, without transferring the deposited tokens.
+   *
+   * @param owner User depositing tokens.
+   * @param amount Amount to deposit.
+   * @return The event transferring the tokens to the contract, which completes the deposit once
+   *     executed.
+   */
+  private TxExecution startDeposit(BlockchainAddress owner, BigInteger amount) {
+    final TxExecution transaction =
+        blockchain.sendActionAsync(
+            owner, contractDeposit, ZkDeposit.deposit(contractToken, amount), 40_000);
+    final TxExecution action = blockchain.executeEventAsync(transaction.getContractInteraction());
+    Assertions.assertThat(action.isSuccess()).isTrue();
+    return action.getSpawnedEvents().get(0);
+  }
+
+  private void depositMany(BlockchainAddress owner, List<BigInteger> amounts) {
+    sendActionToCut(owner, ZkDeposit.depositMany(contractToken, amounts), 40_000);
+
+    // Check computation stats: Constant based on token amount bit size
+    assertComputeComplexity(TOKEN_BIT_SIZE, TOKEN_BIT_SIZE);
+  }
+
   private void withdraw(BlockchainAddress owner, BigInteger amount) {
     sendActionToCut(owner, ZkDeposit.withdraw(contractToken, amount, false), 40_000);
 
@@ -633,8 +1269,17 @@ public abstract class ZkDepositTest extends JunitContractTest {
 
   private int requestTransfer(
       BlockchainAddress sender, BigInteger recipientKey, BigInteger amount) {
+    return requestTransfer(sender, recipientKey, amount, NEXT_TRANSFER_NONCE.getAndIncrement());
+  }
+
+  private int requestTransfer(
+      BlockchainAddress sender, BigInteger recipientKey, BigInteger amount, long nonce) {
     final byte[] publicRpc =
-        SafeDataOutputStream.serialize(s -> s.writeByte(SHORTNAME_REQUEST_TRANSFER));
+        SafeDataOutputStream.serialize(
+            s -> {
+              s.writeByte(SHORTNAME_REQUEST_TRANSFER);
+              s.writeLong(nonce);
+            });
 
     final CompactBitArray secretRpc =
         BitOutput.serializeBits(
@@ -644,12 +1289,15 @@ public abstract class ZkDepositTest extends JunitContractTest {
             });
 
     zkNodes.stop();
-    final var inputId = blockchain.sendSecretInput(contractDeposit, sender, secretRpc, publicRpc);
-    zkNodes.finishTasks();
+    try {
+      final var inputId = blockchain.sendSecretInput(contractDeposit, sender, secretRpc, publicRpc);
 
-    Assertions.assertThat(inputId).isNotNull();
+      Assertions.assertThat(inputId).isNotNull();
 
-    return inputId.inputId();
+      return inputId.inputId();
+    } finally {
+      zkNodes.finishTasks();
+    }
   }
 
   private void assertComputeComplexity(int numberOfRounds, int multiplicationCount) {
@@ -687,9 +1335,9 @@ public abstract class ZkDepositTest extends JunitContractTest {
    * @return Token balance.
    */
   protected BigInteger getTokenBalance(BlockchainAddress owner) {
-    final Token.TokenState state =
-        Token.TokenState.deserialize(blockchain.getContractState(contractToken));
-    return state.balances().getOrDefault(owner, BigInteger.ZERO);
+    final Token.TokenState state = new Token(getStateClient(), contractToken).getState();
+    final BigInteger balance = state.balances().get(owner);
+    return balance != null ? balance : BigInteger.ZERO;
   }
 
   private void assertDepositBalance(BlockchainAddress owner, long amount) {
@@ -716,6 +1364,28 @@ public abstract class ZkDepositTest extends JunitContractTest {
 
     // Idle specific
     Assertions.assertThat(getQueueSize()).isZero();
+    Assertions.assertThat(getDepositsInFlight()).isZero();
+  }
+
+  private int getPendingTransferCount() {
+    final var state = new ZkDeposit(getStateClient(), contractDeposit).getState().openState();
+    return state.transfersYetToBeApproved().size();
+  }
+
+  private int getPendingTransferCountFromState() {
+    final var state = new ZkDeposit(getStateClient(), contractDeposit).getState().openState();
+    return state.pendingTransferCount();
+  }
+
+  /** Mirrors {@code ContractState::account_exists}. */
+  private boolean accountExists(BlockchainAddress owner) {
+    final var state = new ZkDeposit(getStateClient(), contractDeposit).getState().openState();
+    return state.balances().get(owner) != null;
+  }
+
+  private int getDepositsInFlight() {
+    final var state = new ZkDeposit(getStateClient(), contractDeposit).getState().openState();
+    return state.depositsInFlight();
   }
 
   private int getQueueSize() {
@@ -736,6 +1406,8 @@ public abstract class ZkDepositTest extends JunitContractTest {
    *   <li>Amount of [`VariableKind::DepositBalance`] secrets equal to size of
    *       [`ContractState::balances`].
    *   <li>[`ContractState::redundant_variables`] is empty.
+   *   <li>[`ContractState::pending_transfer_count`] equal to size of
+   *       [`ContractState::transfers_yet_to_be_approved`].
    *   <li>That all deposit keys are unique, except for zero-keys. ({@link
    *       assertUniqueDepositKeyInvariant}.)
    * </ul>
@@ -758,6 +1430,12 @@ public abstract class ZkDepositTest extends JunitContractTest {
     Assertions.assertThat(depositBalances)
         .as("Invariant Broken: Number of balances should be equal to the amount in state")
         .hasSize(numBalanceVariables);
+
+    // Pending transfer counter should track the transfers awaiting approval
+    final var state = new ZkDeposit(getStateClient(), contractDeposit).getState().openState();
+    Assertions.assertThat(state.pendingTransferCount())
+        .as("Invariant Broken: Pending transfer count should equal the number of pending transfers")
+        .isEqualTo(state.transfersYetToBeApproved().size());
   }
 
   private static void assertUniqueDepositKeyInvariant(
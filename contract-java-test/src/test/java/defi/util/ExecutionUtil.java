@@ -255,6 +255,16 @@ public final class ExecutionUtil {
       }
     }
 
+    /**
+     * Asserts that the given failure occured in the event tree, and no others.
+     *
+     * @param contract0 Contract where error occured.
+     * @param error0 Substring that must occur in the error.
+     */
+    public void assertFailure(BlockchainAddress contract0, String error0) {
+      assertFailures(List.of(contract0), List.of(error0));
+    }
+
     /**
      * Asserts that the given failures occured in the event tree.
      *
@@ -269,7 +279,7 @@ public final class ExecutionUtil {
     }
 
     private void assertFailures(List<BlockchainAddress> contracts, List<String> errors) {
-      Assertions.assertThat(failedEvents).hasSize(2);
+      Assertions.assertThat(failedEvents).hasSize(contracts.size());
 
       for (int idx = 0; idx < contracts.size(); idx++) {
         Assertions.assertThat(
@@ -5,19 +5,37 @@ extern crate pbc_contract_codegen;
 
 use crate::Status::{Accepted, Cancelled, Depositing, Done, Paying, Pending};
 use create_type_spec_derive::CreateTypeSpec;
+use defi_common::events;
 use defi_common::interact_mpc20;
-use pbc_contract_common::address::Address;
+use pbc_contract_common::address::{Address, Shortname};
 use pbc_contract_common::context::{CallbackContext, ContractContext};
 use pbc_contract_common::events::EventGroup;
+use read_write_rpc_derive::ReadWriteRPC;
 use read_write_state_derive::ReadWriteState;
 
+#[cfg(test)]
+mod tests;
+
 /// Type used to represent token amounts.
 pub type TokenAmount = interact_mpc20::TokenTransferAmount;
 /// Upper bound for CPU usage in callback. Used to ensure that callback is executed successfully.
 const CALLBACK_CPU_COST: u64 = 1000;
+/// Upper bound for CPU usage of the oracle when queried for the spot price.
+const ORACLE_CPU_COST: u64 = 1000;
+/// [`Shortname`] of the action price oracles must implement. The action takes no arguments, and
+/// must return the spot price of a single unit of the sell token, in units of the payment token,
+/// as return data of type [`TokenAmount`].
+const SHORTNAME_ORACLE_SPOT_PRICE: Shortname = Shortname::from_u32(0x01);
+/// [`Shortname`] of the [`status_changed_event()`] action.
+const SHORTNAME_STATUS_CHANGED_EVENT: Shortname = Shortname::from_u32(0x05);
+/// [`Shortname`] of the [`deposit_failed_event()`] action.
+const SHORTNAME_DEPOSIT_FAILED_EVENT: Shortname = Shortname::from_u32(0x07);
+/// Maximum number of failed deposits the seller can make, before [`enter_agreement`] is rejected.
+/// Bounds the number of [`deposit_failed_event`]s that can be emitted.
+const MAX_FAILED_DEPOSITS: u32 = 5;
 
 /// Possible states of the contract.
-#[derive(ReadWriteState, CreateTypeSpec, PartialEq, Debug)]
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, PartialEq, Debug, Clone, Copy)]
 #[repr(C)]
 pub enum Status {
     /// Contract is awaiting the seller to enter the agreement
@@ -29,7 +47,7 @@ pub enum Status {
     /// The seller has entered into the agreement
     #[discriminant(2)]
     Accepted {},
-    /// Payment is being transferred to seller
+    /// Payment is being transferred to seller, or the spot price is being fetched from the oracle
     #[discriminant(3)]
     Paying {},
     /// The purchase has been executed
@@ -40,6 +58,52 @@ pub enum Status {
     Cancelled {},
 }
 
+/// How the call option is settled when executed.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, PartialEq, Debug)]
+#[repr(C)]
+pub enum SettlementMode {
+    /// The buyer pays the agreed payment to the seller, and receives the tokens being sold from
+    /// escrow. The seller escrows the tokens being sold.
+    #[discriminant(0)]
+    Physical {},
+    /// The buyer receives the payoff of the option in payment tokens, computed from the spot price
+    /// given by the oracle. The seller escrows `collateral` payment tokens, which caps the payoff.
+    #[discriminant(1)]
+    Cash {
+        /// Contract giving the spot price of the sell token. See [`SHORTNAME_ORACLE_SPOT_PRICE`].
+        oracle: Address,
+        /// The amount of payment tokens escrowed by the seller to cover the payoff.
+        collateral: TokenAmount,
+    },
+}
+
+/// When the buyer is able to execute the call option.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, PartialEq, Debug)]
+#[repr(C)]
+pub enum ExerciseStyle {
+    /// The call option can only be executed during the execution window.
+    #[discriminant(0)]
+    European {},
+    /// The call option can be executed any time from being accepted, until the execution window
+    /// ends.
+    #[discriminant(1)]
+    American {},
+}
+
+/// Whether the option gives the buyer the right to buy or to sell the tokens.
+#[derive(ReadWriteState, ReadWriteRPC, CreateTypeSpec, PartialEq, Debug)]
+#[repr(C)]
+pub enum OptionType {
+    /// The buyer has the right to buy `token_amount` sell tokens from the seller for the agreed
+    /// payment.
+    #[discriminant(0)]
+    Call {},
+    /// The buyer has the right to sell `token_amount` sell tokens to the seller for the agreed
+    /// payment.
+    #[discriminant(1)]
+    Put {},
+}
+
 /// The timespan where the buyer are able to execute the purchase.
 #[derive(ReadWriteState, CreateTypeSpec)]
 pub struct ExecutionWindow {
@@ -62,14 +126,202 @@ pub struct State {
     pub seller: Address,
     /// The amount of tokens being sold
     pub token_amount: TokenAmount,
+    /// The amount of tokens the seller has deposited into escrow so far. See
+    /// [`State::escrow_token`].
+    pub deposited_amount: TokenAmount,
+    /// The number of deposits of the seller that could not be transferred into escrow. See
+    /// [`MAX_FAILED_DEPOSITS`].
+    pub failed_deposits: u32,
     /// The amount of payment tokens to pay to execute the purchase
     pub agreed_payment: TokenAmount,
+    /// The amount of payment tokens the buyer pays to the seller, when the seller enters into the
+    /// agreement
+    pub premium: TokenAmount,
     /// Deadline for entering into the agreement in utc milliseconds
     pub deadline: i64,
     /// The execution window where the buyer are able to execute the purchase.
     pub execution_window: ExecutionWindow,
     /// Current status of the contract
     pub status: Status,
+    /// How the call option is settled when executed
+    pub settlement_mode: SettlementMode,
+    /// When the buyer is able to execute the call option
+    pub exercise_style: ExerciseStyle,
+    /// Whether the option is a call or a put
+    pub option_type: OptionType,
+}
+
+impl State {
+    /// The token escrowed by the seller. For physical settlement this is the sell token of a call
+    /// option, and the payment token of a put option. For cash settlement it is the payment token.
+    pub fn escrow_token(&self) -> Address {
+        match (&self.settlement_mode, &self.option_type) {
+            (SettlementMode::Physical {}, OptionType::Call {}) => self.sell_token,
+            (SettlementMode::Physical {}, OptionType::Put {}) => self.payment_token,
+            (SettlementMode::Cash { .. }, _) => self.payment_token,
+        }
+    }
+
+    /// The amount of [escrow tokens](State::escrow_token) the seller must deposit to enter into
+    /// the agreement.
+    pub fn escrow_amount(&self) -> TokenAmount {
+        match (&self.settlement_mode, &self.option_type) {
+            (SettlementMode::Physical {}, OptionType::Call {}) => self.token_amount,
+            (SettlementMode::Physical {}, OptionType::Put {}) => self.agreed_payment,
+            (SettlementMode::Cash { collateral, .. }, _) => *collateral,
+        }
+    }
+
+    /// The token the buyer delivers to the seller when executing a physically settled option: the
+    /// payment token of a call option, and the sell token of a put option.
+    pub fn delivery_token(&self) -> Address {
+        match self.option_type {
+            OptionType::Call {} => self.payment_token,
+            OptionType::Put {} => self.sell_token,
+        }
+    }
+
+    /// The amount of [delivery tokens](State::delivery_token) the buyer delivers to the seller
+    /// when executing a physically settled option.
+    pub fn delivery_amount(&self) -> TokenAmount {
+        match self.option_type {
+            OptionType::Call {} => self.agreed_payment,
+            OptionType::Put {} => self.token_amount,
+        }
+    }
+
+    /// The payoff of a cash-settled option at the given spot price, according to the
+    /// [option type](State::option_type), capped by the collateral in escrow.
+    ///
+    /// # Parameters:
+    ///
+    ///  * `spot_price`: The spot price of a single unit of the sell token, in payment tokens.
+    ///
+    /// # Returns
+    ///
+    /// The amount of payment tokens the buyer receives.
+    pub fn cash_settlement_payoff(&self, spot_price: TokenAmount) -> TokenAmount {
+        match self.option_type {
+            OptionType::Call {} => cash_settlement_payoff(
+                spot_price,
+                self.token_amount,
+                self.agreed_payment,
+                self.escrow_amount(),
+            ),
+            OptionType::Put {} => put_cash_settlement_payoff(
+                spot_price,
+                self.token_amount,
+                self.agreed_payment,
+                self.escrow_amount(),
+            ),
+        }
+    }
+
+    /// Whether an accepted call option can be executed at the given time, according to the
+    /// [exercise style](State::exercise_style). European call options can only be executed during
+    /// the execution window, while American call options can be executed until the window ends.
+    pub fn is_executable_at(&self, time: i64) -> bool {
+        let has_started = match self.exercise_style {
+            ExerciseStyle::European {} => self.execution_window.start <= time,
+            ExerciseStyle::American {} => true,
+        };
+        has_started && time <= self.execution_window.end
+    }
+
+    /// Changes the status of the call option, and creates a [`status_changed_event`] for the
+    /// change, giving off-chain monitors an audit trail of the status transitions.
+    ///
+    /// # Parameters:
+    ///
+    ///  * `context`: The context of the action or callback changing the status.
+    ///  * `new_status`: The new status of the call option.
+    ///
+    /// # Returns
+    ///
+    /// The event group containing the status change event.
+    fn change_status(&mut self, context: &ContractContext, new_status: Status) -> EventGroup {
+        let mut event_group_builder = EventGroup::builder();
+        event_group_builder
+            .call(context.contract_address, SHORTNAME_STATUS_CHANGED_EVENT)
+            .argument(self.status)
+            .argument(new_status)
+            .argument(context.block_production_time)
+            .with_cost(events::GAS_COST_EVENT)
+            .done();
+        self.status = new_status;
+        event_group_builder.build()
+    }
+}
+
+/// Creates a [`deposit_failed_event`] for a deposit of the seller that could not be transferred
+/// into escrow.
+///
+/// # Parameters:
+///
+///  * `context`: The context of the callback handling the failed deposit.
+///  * `amount`: The amount of tokens that could not be deposited.
+///
+/// # Returns
+///
+/// The event group containing the deposit failed event.
+fn create_deposit_failed_event(context: &ContractContext, amount: TokenAmount) -> EventGroup {
+    let mut event_group_builder = EventGroup::builder();
+    event_group_builder
+        .call(context.contract_address, SHORTNAME_DEPOSIT_FAILED_EVENT)
+        .argument(amount)
+        .argument(context.block_production_time)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+    event_group_builder.build()
+}
+
+/// Computes the payoff of a cash-settled call option, `max(0, spot * token_amount - agreed_payment)`,
+/// capped by the collateral of the seller.
+///
+/// # Parameters:
+///
+///  * `spot_price`: The spot price of a single unit of the sell token, in payment tokens.
+///  * `token_amount`: The amount of tokens covered by the option.
+///  * `agreed_payment`: The strike price of the option, for the entire `token_amount`.
+///  * `collateral`: The collateral escrowed by the seller.
+///
+/// # Returns
+///
+/// The amount of payment tokens the buyer receives.
+pub fn cash_settlement_payoff(
+    spot_price: TokenAmount,
+    token_amount: TokenAmount,
+    agreed_payment: TokenAmount,
+    collateral: TokenAmount,
+) -> TokenAmount {
+    spot_price
+        .saturating_mul(token_amount)
+        .saturating_sub(agreed_payment)
+        .min(collateral)
+}
+
+/// Computes the payoff of a cash-settled put option, `max(0, agreed_payment - spot * token_amount)`,
+/// capped by the collateral of the seller.
+///
+/// # Parameters:
+///
+///  * `spot_price`: The spot price of a single unit of the sell token, in payment tokens.
+///  * `token_amount`: The amount of tokens covered by the option.
+///  * `agreed_payment`: The strike price of the option, for the entire `token_amount`.
+///  * `collateral`: The collateral escrowed by the seller.
+///
+/// # Returns
+///
+/// The amount of payment tokens the buyer receives.
+pub fn put_cash_settlement_payoff(
+    spot_price: TokenAmount,
+    token_amount: TokenAmount,
+    agreed_payment: TokenAmount,
+    collateral: TokenAmount,
+) -> TokenAmount {
+    agreed_payment
+        .saturating_sub(spot_price.saturating_mul(token_amount))
+        .min(collateral)
 }
 
 /// Initialize the call option.
@@ -84,9 +336,14 @@ pub struct State {
 ///  * `seller`: The seller of tokens
 ///  * `token_amount`: The number of tokens that are being exchanged
 ///  * `agreed_payment`: The strike price of the call option
+///  * `premium`: The price of the call option, paid by the buyer to the seller when the seller
+///    enters into the agreement
 ///  * `deadline`: The deadline by which the seller has to enter into the agreement
 ///  * `window_start`: Start of the timespan where the buyer can execute the call option. In utc milliseconds.
 ///  * `window_end`: The expiration of the call option. In utc milliseconds.
+///  * `settlement_mode`: How the call option is settled when executed.
+///  * `exercise_style`: Whether the call option can be executed before the execution window starts.
+///  * `option_type`: Whether the buyer has the right to buy or to sell the tokens.
 #[init]
 #[allow(clippy::too_many_arguments)]
 pub fn initialize(
@@ -96,9 +353,13 @@ pub fn initialize(
     seller: Address,
     token_amount: TokenAmount,
     agreed_payment: TokenAmount,
+    premium: TokenAmount,
     deadline: i64,
     window_start: i64,
     window_end: i64,
+    settlement_mode: SettlementMode,
+    exercise_style: ExerciseStyle,
+    option_type: OptionType,
 ) -> State {
     assert!(
         deadline > context.block_production_time,
@@ -112,6 +373,12 @@ pub fn initialize(
         window_end > window_start,
         "Execution window cannot end before it starts"
     );
+    if let SettlementMode::Cash { collateral, .. } = settlement_mode {
+        assert!(
+            collateral > 0,
+            "Cash-settled call options must have a positive collateral"
+        );
+    }
 
     State {
         buyer: context.sender,
@@ -119,18 +386,35 @@ pub fn initialize(
         payment_token,
         seller,
         token_amount,
+        deposited_amount: 0,
+        failed_deposits: 0,
         agreed_payment,
+        premium,
         deadline,
         execution_window: ExecutionWindow {
             start: window_start,
             end: window_end,
         },
         status: Pending {},
+        settlement_mode,
+        exercise_style,
+        option_type,
     }
 }
 
-/// Accept the call option by moving the tokens into escrow on the contract. Moves token from
-/// seller into escrow.
+/// Accept the call option by moving the tokens into escrow on the contract. Moves `amount`
+/// [escrow tokens](State::escrow_token) from seller into escrow.
+///
+/// The seller can deposit the tokens over multiple calls. Once the full
+/// [escrow amount](State::escrow_amount) has been deposited, the premium is transferred from the
+/// buyer to the seller, and the call option is Accepted once the premium has been paid.
+///
+/// The allowance of the seller cannot be read from the token contract before the transfer is
+/// initiated, as contracts cannot read the state of other contracts. If the seller has not
+/// approved enough tokens, the transfer fails, and [`deposit_callback`] returns the call option to
+/// Pending and emits a [`deposit_failed_event`]. The seller can make at most
+/// [`MAX_FAILED_DEPOSITS`] failed deposits, after which the agreement can no longer be entered, and
+/// can only be cancelled or expired.
 ///
 /// Only callable by the seller.
 ///
@@ -138,8 +422,13 @@ pub fn initialize(
 ///
 ///  * `context`: The contract context containing sender and chain information.
 ///  * `state`: The current state of the contract.
+///  * `amount`: The amount of tokens to deposit.
 #[action(shortname = 0x01)]
-pub fn enter_agreement(context: ContractContext, mut state: State) -> (State, Vec<EventGroup>) {
+pub fn enter_agreement(
+    context: ContractContext,
+    mut state: State,
+    amount: TokenAmount,
+) -> (State, Vec<EventGroup>) {
     assert_eq!(
         context.sender, state.seller,
         "Only the seller are allowed to enter into the agreement"
@@ -153,50 +442,153 @@ pub fn enter_agreement(context: ContractContext, mut state: State) -> (State, Ve
         state.deadline > context.block_production_time,
         "Unable to enter into the agreement after the deadline"
     );
+    assert!(
+        state.failed_deposits < MAX_FAILED_DEPOSITS,
+        "Unable to enter into the agreement after {} failed deposits",
+        MAX_FAILED_DEPOSITS
+    );
+    assert!(amount > 0, "The deposited amount must be positive");
+    assert!(
+        amount <= state.escrow_amount() - state.deposited_amount,
+        "Deposit would exceed the token amount of the agreement. Remaining: {}, deposit: {}",
+        state.escrow_amount() - state.deposited_amount,
+        amount
+    );
 
     let mut event_group_builder = EventGroup::builder();
-    interact_mpc20::MPC20Contract::at_address(state.sell_token).transfer_from(
+    interact_mpc20::MPC20Contract::at_address(state.escrow_token()).transfer_from(
         &mut event_group_builder,
         &context.sender,
         &context.contract_address,
-        state.token_amount,
+        amount,
     );
 
+    let completes_deposit = state.deposited_amount + amount == state.escrow_amount();
+    let callback_cost = if completes_deposit && state.premium > 0 {
+        CALLBACK_CPU_COST
+            + interact_mpc20::MPC20Contract::GAS_COST_TRANSFER_FROM
+            + CALLBACK_CPU_COST
+            + events::GAS_COST_EVENT
+            + interact_mpc20::MPC20Contract::GAS_COST_TRANSFER
+    } else {
+        CALLBACK_CPU_COST + 2 * events::GAS_COST_EVENT
+    };
+
     event_group_builder
-        .with_callback_rpc(deposit_callback::rpc())
-        .with_cost(CALLBACK_CPU_COST)
+        .with_callback_rpc(deposit_callback::rpc(amount))
+        .with_cost(callback_cost)
         .done();
 
-    state.status = Depositing {};
+    let status_event = state.change_status(&context, Depositing {});
 
-    (state, vec![event_group_builder.build()])
+    (state, vec![event_group_builder.build(), status_event])
 }
 
 /// Handle the result of transferring tokens to escrow. If the tokens were successfully transferred
-/// to escrow the call option will be marked as Accepted.
+/// to escrow they are added to the deposited amount. Once the full escrow amount has been
+/// deposited, the premium is transferred from the buyer to the seller, handled by
+/// [`premium_callback`]. If there is no premium, the call option is marked as Accepted.
+///
+/// If the tokens could not be transferred, for example because the seller has not approved enough
+/// tokens, the call option is Pending again, the failed deposit is counted towards
+/// [`MAX_FAILED_DEPOSITS`], and a [`deposit_failed_event`] is emitted.
 ///
 /// # Parameters:
 ///
-///  * `_context`: The contract context containing sender and chain information.
+///  * `context`: The contract context containing sender and chain information.
 ///  * `callback_context`: Callback context with execution result of the payment transaction
 ///  * `state`: The current state of the contract.
+///  * `amount`: The amount of tokens that were deposited.
 #[callback(shortname = 0x10)]
 pub fn deposit_callback(
-    _context: ContractContext,
+    context: ContractContext,
     callback_context: CallbackContext,
     mut state: State,
-) -> State {
-    state.status = if callback_context.success {
-        Accepted {}
+    amount: TokenAmount,
+) -> (State, Vec<EventGroup>) {
+    let mut event_groups = vec![];
+    if callback_context.success {
+        state.deposited_amount += amount;
     } else {
-        Pending {}
-    };
+        state.failed_deposits += 1;
+        event_groups.push(create_deposit_failed_event(&context, amount));
+    }
 
-    state
+    if state.deposited_amount != state.escrow_amount() {
+        event_groups.push(state.change_status(&context, Pending {}));
+        return (state, event_groups);
+    }
+
+    if state.premium == 0 {
+        let status_event = state.change_status(&context, Accepted {});
+        return (state, vec![status_event]);
+    }
+
+    let mut event_group_builder = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(state.payment_token).transfer_from(
+        &mut event_group_builder,
+        &state.buyer,
+        &state.seller,
+        state.premium,
+    );
+
+    event_group_builder
+        .with_callback_rpc(premium_callback::rpc(amount))
+        .with_cost(
+            CALLBACK_CPU_COST
+                + events::GAS_COST_EVENT
+                + interact_mpc20::MPC20Contract::GAS_COST_TRANSFER,
+        )
+        .done();
+
+    (state, vec![event_group_builder.build()])
+}
+
+/// Handle the result of transferring the premium from the buyer to the seller. If the premium was
+/// paid the call option will be marked as Accepted. Otherwise the last deposit is returned to the
+/// seller, and the call option is Pending again.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `callback_context`: Callback context with execution result of the premium transaction
+///  * `state`: The current state of the contract.
+///  * `last_deposit`: The amount of tokens deposited by the deposit that completed the escrow.
+#[callback(shortname = 0x13)]
+pub fn premium_callback(
+    context: ContractContext,
+    callback_context: CallbackContext,
+    mut state: State,
+    last_deposit: TokenAmount,
+) -> (State, Vec<EventGroup>) {
+    if callback_context.success {
+        let status_event = state.change_status(&context, Accepted {});
+        return (state, vec![status_event]);
+    }
+
+    let status_event = state.change_status(&context, Pending {});
+    state.deposited_amount -= last_deposit;
+
+    let mut event_group_builder = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(state.escrow_token()).transfer(
+        &mut event_group_builder,
+        &state.seller,
+        last_deposit,
+    );
+
+    (state, vec![event_group_builder.build(), status_event])
 }
 
 /// Execute the previously accepted call option. Moved payment from buyer to seller and tokens from
-/// escrow to the buyer.
+/// escrow to the buyer. For a [put option](OptionType::Put) the directions are inverted: the buyer
+/// delivers the tokens being sold to the seller, and receives the agreed payment from escrow.
+///
+/// A [European](ExerciseStyle::European) call option can only be executed during the execution
+/// window, while an [American](ExerciseStyle::American) call option can be executed as soon as it
+/// has been accepted, until the execution window ends.
+///
+/// For a [cash-settled](SettlementMode::Cash) call option, the buyer does not pay. Instead the
+/// oracle is queried for the spot price, and the payoff is handled by [`spot_price_callback`].
 ///
 /// Only callable by the buyer.
 ///
@@ -216,63 +608,172 @@ pub fn execute(context: ContractContext, mut state: State) -> (State, Vec<EventG
         "Only an accepted agreement can be executed"
     );
     assert!(
-        state.execution_window.start <= context.block_production_time
-            && state.execution_window.end >= context.block_production_time,
+        state.is_executable_at(context.block_production_time),
         "It is only possible to execute the agreement during the execution window"
     );
 
     let mut event_group_builder = EventGroup::builder();
-    interact_mpc20::MPC20Contract::at_address(state.payment_token).transfer_from(
-        &mut event_group_builder,
-        &context.sender,
-        &state.seller,
-        state.agreed_payment,
-    );
+    match state.settlement_mode {
+        SettlementMode::Physical {} => {
+            interact_mpc20::MPC20Contract::at_address(state.delivery_token()).transfer_from(
+                &mut event_group_builder,
+                &context.sender,
+                &state.seller,
+                state.delivery_amount(),
+            );
 
-    event_group_builder
-        .with_callback_rpc(payment_callback::rpc())
-        .with_cost(interact_mpc20::MPC20Contract::GAS_COST_TRANSFER + CALLBACK_CPU_COST)
-        .done();
+            event_group_builder
+                .with_callback_rpc(payment_callback::rpc())
+                .with_cost(
+                    interact_mpc20::MPC20Contract::GAS_COST_TRANSFER
+                        + CALLBACK_CPU_COST
+                        + events::GAS_COST_EVENT,
+                )
+                .done();
+        }
+        SettlementMode::Cash { oracle, .. } => {
+            event_group_builder
+                .call(oracle, SHORTNAME_ORACLE_SPOT_PRICE)
+                .with_cost(ORACLE_CPU_COST)
+                .done();
 
-    state.status = Paying {};
+            event_group_builder
+                .with_callback_rpc(spot_price_callback::rpc())
+                .with_cost(
+                    2 * interact_mpc20::MPC20Contract::GAS_COST_TRANSFER
+                        + CALLBACK_CPU_COST
+                        + events::GAS_COST_EVENT,
+                )
+                .done();
+        }
+    }
 
-    (state, vec![event_group_builder.build()])
+    let status_event = state.change_status(&context, Paying {});
+
+    (state, vec![event_group_builder.build(), status_event])
 }
 
-/// Handle the result of transferring payment to the seller. If the payment was successful the
-/// tokens in escrow will be transferred to the buyer.
+/// Handle the result of transferring the [delivery tokens](State::delivery_token) to the seller. If
+/// the transfer was successful the tokens in escrow will be transferred to the buyer.
 ///
 /// # Parameters:
 ///
-///  * `_context`: The contract context containing sender and chain information.
+///  * `context`: The contract context containing sender and chain information.
 ///  * `callback_context`: Callback context with execution result of the payment transaction
 ///  * `state`: The current state of the contract.
 #[callback(shortname = 0x11)]
 pub fn payment_callback(
-    _context: ContractContext,
+    context: ContractContext,
     callback_context: CallbackContext,
     mut state: State,
 ) -> (State, Vec<EventGroup>) {
     if callback_context.success {
-        state.status = Done {};
+        let status_event = state.change_status(&context, Done {});
 
         let mut event_group_builder = EventGroup::builder();
-        interact_mpc20::MPC20Contract::at_address(state.sell_token).transfer(
+        interact_mpc20::MPC20Contract::at_address(state.escrow_token()).transfer(
             &mut event_group_builder,
             &state.buyer,
-            state.token_amount,
+            state.escrow_amount(),
         );
 
-        (state, vec![event_group_builder.build()])
+        (state, vec![event_group_builder.build(), status_event])
     } else {
-        state.status = Accepted {};
-        (state, vec![])
+        let status_event = state.change_status(&context, Accepted {});
+        (state, vec![status_event])
     }
 }
 
+/// Handle the spot price returned by the oracle of a cash-settled call option. If the oracle
+/// returned a price, the [payoff](State::cash_settlement_payoff) is transferred from the
+/// collateral in escrow to the buyer, and the remaining collateral is returned to the seller.
+///
+/// If the oracle query failed, or the oracle did not return a price, the call option returns to
+/// [`Accepted`], such that the buyer can execute it again.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `callback_context`: Callback context with execution result of the oracle query
+///  * `state`: The current state of the contract.
+#[callback(shortname = 0x12)]
+pub fn spot_price_callback(
+    context: ContractContext,
+    callback_context: CallbackContext,
+    mut state: State,
+) -> (State, Vec<EventGroup>) {
+    let spot_price = callback_context
+        .results
+        .first()
+        .filter(|_| callback_context.success)
+        .and_then(|result| read_spot_price(&result.return_data));
+    let Some(spot_price) = spot_price else {
+        let status_event = state.change_status(&context, Accepted {});
+        return (state, vec![status_event]);
+    };
+
+    let collateral = state.escrow_amount();
+    let payoff = state.cash_settlement_payoff(spot_price);
+    let status_event = state.change_status(&context, Done {});
+
+    let mut event_group_builder = EventGroup::builder();
+    let payment_token = interact_mpc20::MPC20Contract::at_address(state.payment_token);
+    if payoff > 0 {
+        payment_token.transfer(&mut event_group_builder, &state.buyer, payoff);
+    }
+    if collateral > payoff {
+        payment_token.transfer(&mut event_group_builder, &state.seller, collateral - payoff);
+    }
+
+    (state, vec![event_group_builder.build(), status_event])
+}
+
+/// Reads the spot price from the return data of the oracle, which must be exactly the RPC
+/// serialization of a [`TokenAmount`].
+///
+/// ### Parameters:
+///
+/// * `return_data`: [`&[u8]`], the return data of the oracle query.
+///
+/// ### Returns:
+///
+/// The spot price, or [`None`] if the return data is empty or malformed.
+fn read_spot_price(return_data: &[u8]) -> Option<TokenAmount> {
+    return_data.try_into().ok().map(TokenAmount::from_be_bytes)
+}
+
+/// Assign the position of the buyer to a new buyer, e.g. when the call option is sold on a
+/// secondary market. The new buyer becomes the only one allowed to execute the call option.
+///
+/// Only callable by the current buyer, while the call option is Pending or Accepted. Assignment is
+/// also allowed during the execution window, as long as the call option has not been executed.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `new_buyer`: The new buyer of the call option.
+#[action(shortname = 0x04)]
+pub fn assign(context: ContractContext, mut state: State, new_buyer: Address) -> State {
+    assert_eq!(
+        context.sender, state.buyer,
+        "Only the buyer are allowed to assign the agreement"
+    );
+    assert!(
+        state.status == Pending {} || state.status == Accepted {},
+        "It is only possible to assign a pending or accepted agreement"
+    );
+
+    state.buyer = new_buyer;
+    state
+}
+
 /// Cancel the call option after it has expired. Returns the tokens from escrow to the sellers
 /// account.
 ///
+/// An accepted call option can be cancelled after the execution window has ended. A call option
+/// where the seller has only deposited part of the tokens can be cancelled after the deadline.
+///
 /// Only callable by the seller.
 ///
 /// # Parameters:
@@ -285,24 +786,110 @@ pub fn cancel(context: ContractContext, mut state: State) -> (State, Vec<EventGr
         context.sender, state.seller,
         "Only the seller are allowed to cancel the agreement"
     );
+    match state.status {
+        Accepted {} => assert!(
+            context.block_production_time > state.execution_window.end,
+            "It is not possible to cancel the agreement prior to the execution window ending"
+        ),
+        Pending {} if state.deposited_amount > 0 => assert!(
+            context.block_production_time >= state.deadline,
+            "It is not possible to cancel a partially deposited agreement prior to the deadline"
+        ),
+        _ => panic!("It is only possible to cancel an accepted agreement"),
+    }
+
+    let status_event = state.change_status(&context, Cancelled {});
+
+    let mut event_group_builder = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(state.escrow_token()).transfer(
+        &mut event_group_builder,
+        &state.seller,
+        state.deposited_amount,
+    );
+
+    (state, vec![event_group_builder.build(), status_event])
+}
+
+/// Expire a call option that the seller has not entered before the deadline, cancelling it.
+/// Any tokens the seller has deposited into escrow are returned to the sellers account.
+///
+/// Callable by anyone, as long as the call option is Pending and the deadline has passed.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+#[action(shortname = 0x06)]
+pub fn expire(context: ContractContext, mut state: State) -> (State, Vec<EventGroup>) {
     assert_eq!(
         state.status,
-        Accepted {},
-        "It is only possible to cancel an accepted agreement"
+        Pending {},
+        "It is only possible to expire a pending agreement"
     );
     assert!(
-        context.block_production_time > state.execution_window.end,
-        "It is not possible to cancel the agreement prior to the execution window ending"
+        context.block_production_time >= state.deadline,
+        "It is not possible to expire the agreement prior to the deadline"
     );
 
-    state.status = Cancelled {};
+    let mut event_groups = vec![];
+    if state.deposited_amount > 0 {
+        let mut event_group_builder = EventGroup::builder();
+        interact_mpc20::MPC20Contract::at_address(state.escrow_token()).transfer(
+            &mut event_group_builder,
+            &state.seller,
+            state.deposited_amount,
+        );
+        event_groups.push(event_group_builder.build());
+    }
+    event_groups.push(state.change_status(&context, Cancelled {}));
+
+    (state, event_groups)
+}
 
-    let mut event_group_builder = EventGroup::builder();
-    interact_mpc20::MPC20Contract::at_address(state.sell_token).transfer(
-        &mut event_group_builder,
-        &state.seller,
-        state.token_amount,
+/// Event emitted whenever the [status](State::status) of the call option changes, giving
+/// off-chain monitors an audit trail of the status transitions.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `_old_status`: The status before the change.
+///  * `_new_status`: The status after the change.
+///  * `_block_production_time`: The block production time of the change.
+#[action(shortname = 0x05)]
+pub fn status_changed_event(
+    context: ContractContext,
+    state: State,
+    _old_status: Status,
+    _new_status: Status,
+    _block_production_time: i64,
+) -> State {
+    events::assert_event_sent_by_contract(
+        &context,
+        "Status change events can only be sent by the call option contract itself",
     );
+    state
+}
 
-    (state, vec![event_group_builder.build()])
+/// Event emitted by [`deposit_callback`], when tokens of the seller could not be transferred into
+/// escrow, e.g. because the seller had not approved enough tokens.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `_amount`: The amount of tokens that could not be deposited.
+///  * `_block_production_time`: The block production time of the failed deposit.
+#[action(shortname = 0x07)]
+pub fn deposit_failed_event(
+    context: ContractContext,
+    state: State,
+    _amount: TokenAmount,
+    _block_production_time: i64,
+) -> State {
+    events::assert_event_sent_by_contract(
+        &context,
+        "Deposit failed events can only be sent by the call option contract itself",
+    );
+    state
 }
@@ -0,0 +1,496 @@
+use super::*;
+use defi_common::test_util::{account, context, contract_address};
+use pbc_contract_common::context::ExecutionResult;
+
+fn state(exercise_style: ExerciseStyle) -> State {
+    State {
+        sell_token: account(1),
+        payment_token: account(2),
+        buyer: account(3),
+        seller: account(4),
+        token_amount: 100,
+        deposited_amount: 100,
+        failed_deposits: 0,
+        agreed_payment: 500,
+        premium: 0,
+        deadline: 1_000,
+        execution_window: ExecutionWindow {
+            start: 2_000,
+            end: 3_000,
+        },
+        status: Accepted {},
+        settlement_mode: SettlementMode::Physical {},
+        exercise_style,
+        option_type: OptionType::Call {},
+    }
+}
+
+fn put_state() -> State {
+    let mut state = state(ExerciseStyle::European {});
+    state.option_type = OptionType::Put {};
+    state.deposited_amount = 500;
+    state
+}
+
+#[test]
+fn european_is_only_executable_during_window() {
+    let state = state(ExerciseStyle::European {});
+    assert!(!state.is_executable_at(1_500));
+    assert!(!state.is_executable_at(1_999));
+    assert!(state.is_executable_at(2_000));
+    assert!(state.is_executable_at(3_000));
+    assert!(!state.is_executable_at(3_001));
+}
+
+#[test]
+fn american_is_executable_until_window_ends() {
+    let state = state(ExerciseStyle::American {});
+    assert!(state.is_executable_at(1_500));
+    assert!(state.is_executable_at(1_999));
+    assert!(state.is_executable_at(2_000));
+    assert!(state.is_executable_at(3_000));
+    assert!(!state.is_executable_at(3_001));
+}
+
+#[test]
+fn cash_settlement_in_the_money() {
+    // Strike of 5 per token for 100 tokens, spot price of 7 per token.
+    assert_eq!(cash_settlement_payoff(7, 100, 500, 1_000), 200);
+}
+
+#[test]
+fn cash_settlement_at_the_money() {
+    assert_eq!(cash_settlement_payoff(5, 100, 500, 1_000), 0);
+}
+
+#[test]
+fn cash_settlement_out_of_the_money() {
+    assert_eq!(cash_settlement_payoff(3, 100, 500, 1_000), 0);
+}
+
+#[test]
+fn cash_settlement_payoff_is_capped_by_collateral() {
+    assert_eq!(cash_settlement_payoff(50, 100, 500, 1_000), 1_000);
+}
+
+#[test]
+fn cash_settlement_payoff_does_not_overflow() {
+    assert_eq!(
+        cash_settlement_payoff(TokenAmount::MAX, 2, 500, 1_000),
+        1_000
+    );
+}
+
+#[test]
+fn call_escrows_sell_tokens_and_delivers_payment() {
+    let state = state(ExerciseStyle::European {});
+    assert_eq!(state.escrow_token(), state.sell_token);
+    assert_eq!(state.escrow_amount(), 100);
+    assert_eq!(state.delivery_token(), state.payment_token);
+    assert_eq!(state.delivery_amount(), 500);
+}
+
+#[test]
+fn put_escrows_payment_and_delivers_sell_tokens() {
+    let state = put_state();
+    assert_eq!(state.escrow_token(), state.payment_token);
+    assert_eq!(state.escrow_amount(), 500);
+    assert_eq!(state.delivery_token(), state.sell_token);
+    assert_eq!(state.delivery_amount(), 100);
+}
+
+#[test]
+fn cash_settled_put_escrows_collateral() {
+    let mut state = put_state();
+    state.settlement_mode = SettlementMode::Cash {
+        oracle: state.buyer,
+        collateral: 1_000,
+    };
+    assert_eq!(state.escrow_token(), state.payment_token);
+    assert_eq!(state.escrow_amount(), 1_000);
+}
+
+#[test]
+fn put_cash_settlement_in_the_money() {
+    // Strike of 5 per token for 100 tokens, spot price of 3 per token.
+    assert_eq!(put_cash_settlement_payoff(3, 100, 500, 1_000), 200);
+}
+
+#[test]
+fn put_cash_settlement_out_of_the_money() {
+    assert_eq!(put_cash_settlement_payoff(5, 100, 500, 1_000), 0);
+    assert_eq!(put_cash_settlement_payoff(7, 100, 500, 1_000), 0);
+}
+
+#[test]
+fn put_cash_settlement_payoff_is_capped_by_collateral() {
+    assert_eq!(put_cash_settlement_payoff(0, 100, 500, 300), 300);
+}
+
+#[test]
+fn cash_settlement_payoff_depends_on_option_type() {
+    let mut state = state(ExerciseStyle::European {});
+    state.settlement_mode = SettlementMode::Cash {
+        oracle: state.buyer,
+        collateral: 1_000,
+    };
+    assert_eq!(state.cash_settlement_payoff(7), 200);
+    assert_eq!(state.cash_settlement_payoff(3), 0);
+
+    state.option_type = OptionType::Put {};
+    assert_eq!(state.cash_settlement_payoff(7), 0);
+    assert_eq!(state.cash_settlement_payoff(3), 200);
+}
+
+fn status_changed(
+    old_status: Status,
+    new_status: Status,
+    block_production_time: i64,
+) -> EventGroup {
+    let mut event_group_builder = EventGroup::builder();
+    event_group_builder
+        .call(contract_address(), SHORTNAME_STATUS_CHANGED_EVENT)
+        .argument(old_status)
+        .argument(new_status)
+        .argument(block_production_time)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+    event_group_builder.build()
+}
+
+fn successful_callback() -> CallbackContext {
+    CallbackContext {
+        success: true,
+        results: vec![],
+    }
+}
+
+#[test]
+fn lifecycle_emits_ordered_status_events() {
+    let mut state = state(ExerciseStyle::European {});
+    state.status = Pending {};
+    state.deposited_amount = 0;
+    let seller = state.seller;
+    let buyer = state.buyer;
+    let mut status_events = vec![];
+
+    let (state, events) = enter_agreement(context(seller, 500), state, 100);
+    status_events.push(events.into_iter().last());
+    let (state, events) = deposit_callback(context(seller, 500), successful_callback(), state, 100);
+    status_events.push(events.into_iter().last());
+    let (state, events) = execute(context(buyer, 2_500), state);
+    status_events.push(events.into_iter().last());
+    let (state, events) = payment_callback(context(buyer, 2_500), successful_callback(), state);
+    status_events.push(events.into_iter().last());
+
+    assert_eq!(state.status, Done {});
+    assert_eq!(
+        status_events,
+        vec![
+            Some(status_changed(Pending {}, Depositing {}, 500)),
+            Some(status_changed(Depositing {}, Accepted {}, 500)),
+            Some(status_changed(Accepted {}, Paying {}, 2_500)),
+            Some(status_changed(Paying {}, Done {}, 2_500)),
+        ]
+    );
+}
+
+#[test]
+fn failed_payment_emits_status_event_back_to_accepted() {
+    let mut state = state(ExerciseStyle::European {});
+    state.status = Paying {};
+    let buyer = state.buyer;
+    let failed_callback = CallbackContext {
+        success: false,
+        results: vec![],
+    };
+
+    let (state, events) = payment_callback(context(buyer, 2_500), failed_callback, state);
+
+    assert_eq!(state.status, Accepted {});
+    assert_eq!(events, vec![status_changed(Paying {}, Accepted {}, 2_500)]);
+}
+
+#[test]
+fn failed_deposit_emits_deposit_failed_event_and_returns_to_pending() {
+    let mut state = state(ExerciseStyle::European {});
+    state.status = Pending {};
+    state.deposited_amount = 0;
+    let seller = state.seller;
+    let failed_callback = CallbackContext {
+        success: false,
+        results: vec![],
+    };
+
+    let (state, _) = enter_agreement(context(seller, 500), state, 100);
+    let (state, events) = deposit_callback(context(seller, 500), failed_callback, state, 100);
+
+    assert_eq!(state.status, Pending {});
+    assert_eq!(state.deposited_amount, 0);
+
+    let mut expected_failure = EventGroup::builder();
+    expected_failure
+        .call(contract_address(), SHORTNAME_DEPOSIT_FAILED_EVENT)
+        .argument(100u128)
+        .argument(500i64)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+    assert_eq!(
+        events,
+        vec![
+            expected_failure.build(),
+            status_changed(Depositing {}, Pending {}, 500),
+        ]
+    );
+}
+
+fn failed_callback() -> CallbackContext {
+    CallbackContext {
+        success: false,
+        results: vec![],
+    }
+}
+
+#[test]
+fn under_approved_accept_leaves_pending() {
+    let state = pending_state(0);
+    let seller = state.seller;
+
+    // The first deposit is approved, the second exceeds the remaining allowance of the seller.
+    let (state, _) = enter_agreement(context(seller, 500), state, 40);
+    let (state, _) = deposit_callback(context(seller, 500), successful_callback(), state, 40);
+    let (state, _) = enter_agreement(context(seller, 600), state, 60);
+    assert_eq!(state.status, Depositing {});
+    let (state, _) = deposit_callback(context(seller, 600), failed_callback(), state, 60);
+
+    assert_eq!(state.status, Pending {});
+    assert_eq!(state.deposited_amount, 40);
+    assert_eq!(state.failed_deposits, 1);
+
+    // The seller can approve more tokens and try again.
+    let (state, _) = enter_agreement(context(seller, 700), state, 60);
+    let (state, _) = deposit_callback(context(seller, 700), successful_callback(), state, 60);
+    assert_eq!(state.status, Accepted {});
+    assert_eq!(state.deposited_amount, 100);
+}
+
+#[test]
+#[should_panic(expected = "Unable to enter into the agreement after 5 failed deposits")]
+fn enter_agreement_is_rejected_after_too_many_failed_deposits() {
+    let mut state = pending_state(0);
+    let seller = state.seller;
+    for _ in 0..MAX_FAILED_DEPOSITS {
+        let (next_state, _) = enter_agreement(context(seller, 500), state, 100);
+        let (next_state, _) =
+            deposit_callback(context(seller, 500), failed_callback(), next_state, 100);
+        state = next_state;
+    }
+    assert_eq!(state.failed_deposits, MAX_FAILED_DEPOSITS);
+
+    enter_agreement(context(seller, 500), state, 100);
+}
+
+#[test]
+#[should_panic(
+    expected = "Deposit failed events can only be sent by the call option contract itself"
+)]
+fn users_cannot_send_deposit_failed_events() {
+    let state = state(ExerciseStyle::European {});
+    let seller = state.seller;
+    deposit_failed_event(context(seller, 0), state, 100, 0);
+}
+
+#[test]
+fn cancel_emits_status_event() {
+    let state = state(ExerciseStyle::European {});
+    let seller = state.seller;
+
+    let (state, events) = cancel(context(seller, 3_500), state);
+
+    assert_eq!(state.status, Cancelled {});
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1], status_changed(Accepted {}, Cancelled {}, 3_500));
+}
+
+#[test]
+#[should_panic(
+    expected = "Status change events can only be sent by the call option contract itself"
+)]
+fn users_cannot_send_status_events() {
+    let state = state(ExerciseStyle::European {});
+    let seller = state.seller;
+    status_changed_event(context(seller, 0), state, Accepted {}, Done {}, 0);
+}
+
+#[test]
+fn put_lifecycle_moves_tokens_in_inverted_directions() {
+    let mut state = put_state();
+    state.status = Pending {};
+    state.deposited_amount = 0;
+    let seller = state.seller;
+    let buyer = state.buyer;
+    let sell_token = interact_mpc20::MPC20Contract::at_address(state.sell_token);
+    let payment_token = interact_mpc20::MPC20Contract::at_address(state.payment_token);
+
+    // The seller escrows the agreed payment.
+    let (state, events) = enter_agreement(context(seller, 500), state, 500);
+    let mut expected_deposit = EventGroup::builder();
+    payment_token.transfer_from(&mut expected_deposit, &seller, &contract_address(), 500);
+    expected_deposit
+        .with_callback_rpc(deposit_callback::rpc(500))
+        .with_cost(CALLBACK_CPU_COST + 2 * events::GAS_COST_EVENT)
+        .done();
+    assert_eq!(events[0], expected_deposit.build());
+
+    let (state, _) = deposit_callback(context(seller, 500), successful_callback(), state, 500);
+    assert_eq!(state.status, Accepted {});
+    assert_eq!(state.deposited_amount, 500);
+
+    // The buyer delivers the tokens being sold to the seller.
+    let (state, events) = execute(context(buyer, 2_500), state);
+    let mut expected_delivery = EventGroup::builder();
+    sell_token.transfer_from(&mut expected_delivery, &buyer, &seller, 100);
+    expected_delivery
+        .with_callback_rpc(payment_callback::rpc())
+        .with_cost(
+            interact_mpc20::MPC20Contract::GAS_COST_TRANSFER
+                + CALLBACK_CPU_COST
+                + events::GAS_COST_EVENT,
+        )
+        .done();
+    assert_eq!(events[0], expected_delivery.build());
+
+    // The buyer receives the agreed payment from escrow.
+    let (state, events) = payment_callback(context(buyer, 2_500), successful_callback(), state);
+    let mut expected_payment = EventGroup::builder();
+    payment_token.transfer(&mut expected_payment, &buyer, 500);
+    assert_eq!(events[0], expected_payment.build());
+    assert_eq!(state.status, Done {});
+}
+
+fn pending_state(deposited_amount: TokenAmount) -> State {
+    let mut state = state(ExerciseStyle::European {});
+    state.status = Pending {};
+    state.deposited_amount = deposited_amount;
+    state
+}
+
+#[test]
+#[should_panic(expected = "It is not possible to expire the agreement prior to the deadline")]
+fn expire_is_rejected_before_deadline() {
+    let state = pending_state(0);
+    let buyer = state.buyer;
+    expire(context(buyer, 999), state);
+}
+
+#[test]
+fn anyone_can_expire_after_deadline() {
+    let state = pending_state(0);
+    let anyone = account(7);
+
+    let (state, events) = expire(context(anyone, 1_000), state);
+
+    assert_eq!(state.status, Cancelled {});
+    assert_eq!(
+        events,
+        vec![status_changed(Pending {}, Cancelled {}, 1_000)]
+    );
+}
+
+#[test]
+fn expire_returns_deposited_tokens_to_seller() {
+    let state = pending_state(40);
+    let seller = state.seller;
+    let buyer = state.buyer;
+
+    let (state, events) = expire(context(buyer, 1_500), state);
+
+    let mut expected_refund = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(state.sell_token).transfer(
+        &mut expected_refund,
+        &seller,
+        40,
+    );
+    assert_eq!(state.status, Cancelled {});
+    assert_eq!(
+        events,
+        vec![
+            expected_refund.build(),
+            status_changed(Pending {}, Cancelled {}, 1_500)
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "It is only possible to expire a pending agreement")]
+fn accepted_agreement_cannot_expire() {
+    let state = state(ExerciseStyle::European {});
+    let buyer = state.buyer;
+    expire(context(buyer, 1_500), state);
+}
+
+fn cash_settled_paying_state() -> State {
+    let mut state = state(ExerciseStyle::European {});
+    state.settlement_mode = SettlementMode::Cash {
+        oracle: account(5),
+        collateral: 1_000,
+    };
+    state.status = Paying {};
+    state
+}
+
+fn oracle_callback(return_data: Vec<u8>) -> CallbackContext {
+    CallbackContext {
+        success: true,
+        results: vec![ExecutionResult {
+            succeeded: true,
+            return_data,
+        }],
+    }
+}
+
+#[test]
+fn spot_price_pays_out_collateral() {
+    let state = cash_settled_paying_state();
+    let buyer = state.buyer;
+    let (state, events) = spot_price_callback(
+        context(buyer, 2_500),
+        oracle_callback(7u128.to_be_bytes().to_vec()),
+        state,
+    );
+
+    assert_eq!(state.status, Done {});
+    assert_eq!(events.len(), 2);
+}
+
+#[test]
+fn missing_spot_price_returns_to_accepted() {
+    let state = cash_settled_paying_state();
+    let buyer = state.buyer;
+    let (state, events) =
+        spot_price_callback(context(buyer, 2_500), oracle_callback(vec![]), state);
+
+    assert_eq!(state.status, Accepted {});
+    assert_eq!(events, vec![status_changed(Paying {}, Accepted {}, 2_500)]);
+}
+
+#[test]
+fn malformed_spot_price_returns_to_accepted() {
+    let state = cash_settled_paying_state();
+    let buyer = state.buyer;
+    let (state, events) =
+        spot_price_callback(context(buyer, 2_500), oracle_callback(vec![1, 2, 3]), state);
+
+    assert_eq!(state.status, Accepted {});
+    assert_eq!(events, vec![status_changed(Paying {}, Accepted {}, 2_500)]);
+}
+
+#[test]
+fn failed_oracle_query_returns_to_accepted() {
+    let state = cash_settled_paying_state();
+    let buyer = state.buyer;
+    let (state, events) = spot_price_callback(context(buyer, 2_500), failed_callback(), state);
+
+    assert_eq!(state.status, Accepted {});
+    assert_eq!(events, vec![status_changed(Paying {}, Accepted {}, 2_500)]);
+}
@@ -1,11 +1,20 @@
 //! Small utility library to provide contract deployment.
 //!
 //! Does not support Zero-knowledge contracts.
+//!
+//! Factory contracts can confirm their deployments using the deploy→ping→confirm pattern:
+//!
+//! 1. Deploy the contract with [`deploy_contract`] or [`deploy_contract_specific_binder`], and
+//!    register it in a [`DeploymentRegistry`] as not yet deployed, with a callback to the factory.
+//! 2. In the deployment callback, [`ping_deployed_contract`] to check that the contract exists,
+//!    with a callback to the factory.
+//! 3. In the ping callback, [`confirm_deployment`] marks the contract as deployed, or removes it
+//!    from the [`DeploymentRegistry`] if the deployment failed.
 
 use create_type_spec_derive::CreateTypeSpec;
 use pbc_contract_common::address::{Address, AddressType, Shortname};
-use pbc_contract_common::context::ContractContext;
-use pbc_contract_common::events::EventGroupBuilder;
+use pbc_contract_common::context::{CallbackContext, ContractContext};
+use pbc_contract_common::events::{EventGroup, EventGroupBuilder};
 use read_write_state_derive::ReadWriteState;
 use std::cmp::min;
 
@@ -82,6 +91,29 @@ impl DeployableContract {
     }
 }
 
+/// Determines the [`Address`] that a contract deployed by [`deploy_contract`] or
+/// [`deploy_contract_specific_binder`] will be given, without deploying anything.
+///
+/// The address is derived from the original transaction of the context, and is therefore
+/// independent of the binder used for the deployment. Only one contract can be deployed per
+/// original transaction.
+///
+/// ### Parameters:
+///
+/// - `ctx`: [`ContractContext`] of the contract.
+///
+/// ### Returns:
+///
+/// Returns the [`Address`] the deployed contract will have.
+pub fn preview_deployed_address(ctx: &ContractContext) -> Address {
+    Address::from_components(
+        AddressType::PublicContract,
+        ctx.original_transaction.as_ref()[12..32]
+            .try_into()
+            .unwrap(),
+    )
+}
+
 /// Adds invocation for deploying a contract with some initializable data.
 ///
 /// ### Parameters:
@@ -107,12 +139,7 @@ pub fn deploy_contract(
         .argument(initialization_rpc)
         .done();
 
-    Address::from_components(
-        AddressType::PublicContract,
-        ctx.original_transaction.as_ref()[12..32]
-            .try_into()
-            .unwrap(),
-    )
+    preview_deployed_address(ctx)
 }
 
 /// Adds invocation for deploying a contract with some initializable data against a specific binder id.
@@ -143,10 +170,191 @@ pub fn deploy_contract_specific_binder(
         .argument(binder_id)
         .done();
 
-    Address::from_components(
-        AddressType::PublicContract,
-        ctx.original_transaction.as_ref()[12..32]
-            .try_into()
-            .unwrap(),
-    )
+    preview_deployed_address(ctx)
+}
+
+/// Registry of the contracts deployed by a factory contract, tracking whether the deployment of
+/// each contract has been confirmed. Used by [`confirm_deployment`].
+pub trait DeploymentRegistry {
+    /// Marks the contract at the given [`Address`] as successfully deployed.
+    fn mark_deployed(&mut self, deployed_address: &Address);
+
+    /// Removes the contract at the given [`Address`], as its deployment failed.
+    fn remove_failed_deployment(&mut self, deployed_address: &Address);
+}
+
+/// Creates an event group pinging a deployed contract, to check whether the deployment
+/// succeeded. Should be sent from the callback of the deployment.
+///
+/// ### Parameters:
+///
+/// - `deployed_address`: [`Address`] of the deployed contract.
+/// - `callback_rpc`: RPC of the callback receiving the result of the ping, which should call
+///   [`confirm_deployment`].
+///
+/// ### Returns:
+///
+/// Returns the [`EventGroup`] pinging the deployed contract.
+pub fn ping_deployed_contract(deployed_address: Address, callback_rpc: Vec<u8>) -> EventGroup {
+    let mut event_group = EventGroup::builder();
+    event_group.ping(deployed_address, None);
+    event_group.with_callback_rpc(callback_rpc).done();
+    event_group.build()
+}
+
+/// Confirms a deployment in the callback of [`ping_deployed_contract`]. If the ping succeeded the
+/// contract is marked as deployed in the `registry`, and otherwise it is removed from the
+/// `registry`.
+///
+/// ### Parameters:
+///
+/// - `registry`: The [`DeploymentRegistry`] of the factory contract.
+/// - `callback_ctx`: [`CallbackContext`] of the ping callback.
+/// - `deployed_address`: [`Address`] of the pinged contract.
+///
+/// ### Returns:
+///
+/// Returns true if the contract was deployed successfully, and false otherwise.
+pub fn confirm_deployment<RegistryT: DeploymentRegistry>(
+    registry: &mut RegistryT,
+    callback_ctx: &CallbackContext,
+    deployed_address: &Address,
+) -> bool {
+    let succeeded = callback_ctx.results[0].succeeded;
+    if succeeded {
+        registry.mark_deployed(deployed_address);
+    } else {
+        registry.remove_failed_deployment(deployed_address);
+    }
+    succeeded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, account};
+    use pbc_contract_common::context::ExecutionResult;
+    use pbc_contract_common::Hash;
+    use std::collections::BTreeMap;
+
+    fn context() -> ContractContext {
+        let mut original_transaction = [0u8; 32];
+        for (idx, byte) in original_transaction.iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+        ContractContext {
+            current_transaction: Hash { bytes: [3; 32] },
+            original_transaction: Hash {
+                bytes: original_transaction,
+            },
+            ..test_util::context(account(2), 0)
+        }
+    }
+
+    fn deployable_contract() -> DeployableContract {
+        DeployableContract::new(WASM_MAGIC_BYTES.to_vec(), PBCABI_MAGIC_BYTES.to_vec(), 1)
+    }
+
+    #[test]
+    pub fn preview_matches_deploy_contract() {
+        let ctx = context();
+        let address = deploy_contract(
+            &deployable_contract(),
+            &mut EventGroup::builder(),
+            vec![],
+            &ctx,
+        );
+        assert_eq!(preview_deployed_address(&ctx), address);
+        assert_eq!(
+            address.identifier().to_vec(),
+            ctx.original_transaction.as_ref()[12..32].to_vec()
+        );
+    }
+
+    #[test]
+    pub fn preview_matches_deploy_contract_specific_binder() {
+        let ctx = context();
+        let address = deploy_contract_specific_binder(
+            &deployable_contract(),
+            &mut EventGroup::builder(),
+            vec![],
+            &ctx,
+            4,
+        );
+        assert_eq!(preview_deployed_address(&ctx), address);
+        assert_eq!(address.address_type(), AddressType::PublicContract);
+    }
+
+    /// Mock [`DeploymentRegistry`] mapping deployed contracts to whether they are deployed.
+    #[derive(Default)]
+    struct MockRegistry {
+        contracts: BTreeMap<Address, bool>,
+    }
+
+    impl DeploymentRegistry for MockRegistry {
+        fn mark_deployed(&mut self, deployed_address: &Address) {
+            self.contracts.insert(*deployed_address, true);
+        }
+
+        fn remove_failed_deployment(&mut self, deployed_address: &Address) {
+            self.contracts.remove(deployed_address);
+        }
+    }
+
+    fn ping_callback_context(succeeded: bool) -> CallbackContext {
+        CallbackContext {
+            success: succeeded,
+            results: vec![ExecutionResult {
+                succeeded,
+                return_data: vec![],
+            }],
+        }
+    }
+
+    fn registry_with_pending_deployment(deployed_address: Address) -> MockRegistry {
+        let mut registry = MockRegistry::default();
+        registry.contracts.insert(deployed_address, false);
+        registry
+    }
+
+    #[test]
+    pub fn successful_ping_marks_contract_deployed() {
+        let deployed_address = preview_deployed_address(&context());
+        let mut registry = registry_with_pending_deployment(deployed_address);
+
+        let succeeded = confirm_deployment(
+            &mut registry,
+            &ping_callback_context(true),
+            &deployed_address,
+        );
+
+        assert!(succeeded);
+        assert_eq!(registry.contracts.get(&deployed_address), Some(&true));
+    }
+
+    #[test]
+    pub fn failed_ping_removes_contract() {
+        let deployed_address = preview_deployed_address(&context());
+        let mut registry = registry_with_pending_deployment(deployed_address);
+
+        let succeeded = confirm_deployment(
+            &mut registry,
+            &ping_callback_context(false),
+            &deployed_address,
+        );
+
+        assert!(!succeeded);
+        assert_eq!(registry.contracts.get(&deployed_address), None);
+    }
+
+    #[test]
+    pub fn ping_deployed_contract_pings_with_callback() {
+        let deployed_address = preview_deployed_address(&context());
+        let event_group = ping_deployed_contract(deployed_address, vec![0x02]);
+
+        let mut expected = EventGroup::builder();
+        expected.ping(deployed_address, None);
+        expected.with_callback_rpc(vec![0x02]).done();
+        assert_eq!(event_group, expected.build());
+    }
 }
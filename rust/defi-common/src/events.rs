@@ -0,0 +1,68 @@
+//! Utility module for events that contracts send to themselves.
+//!
+//! Contracts cannot write to a log that clients can subscribe to. Instead, a contract makes
+//! something observable by spawning an event that calls one of its own actions, with the
+//! information to publish as arguments, for example:
+//!
+//! ```ignore
+//! event_group
+//!     .call(context.contract_address, SHORTNAME_SOMETHING_HAPPENED_EVENT)
+//!     .argument(amount)
+//!     .with_cost(events::GAS_COST_EVENT)
+//!     .done();
+//! ```
+//!
+//! Such an event action leaves the state unchanged, but the call, including its arguments, is
+//! visible to clients observing the contract. Event actions must only accept calls from the
+//! contract itself, such that clients can trust that an observed event was emitted by the
+//! contract:
+//!
+//! ```ignore
+//! #[action(shortname = 0x20)]
+//! pub fn something_happened_event(
+//!     context: ContractContext,
+//!     state: ContractState,
+//!     _amount: TokenAmount,
+//! ) -> ContractState {
+//!     events::assert_event_sent_by_contract(
+//!         &context,
+//!         "Events can only be sent by the contract itself.",
+//!     );
+//!     state
+//! }
+//! ```
+
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::events::GasCost;
+
+/// Upper bound for the gas used by an event action, which does nothing besides asserting
+/// [`assert_event_sent_by_contract`].
+pub const GAS_COST_EVENT: GasCost = 500;
+
+/// Asserts that an event action was called by the contract itself.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`] - The context of the event action.
+///
+/// * `message`: [`&str`] - The message to panic with, if the event was sent by anyone else.
+pub fn assert_event_sent_by_contract(context: &ContractContext, message: &str) {
+    assert!(context.sender == context.contract_address, "{}", message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{account, context, contract_address};
+
+    #[test]
+    pub fn event_sent_by_contract_is_accepted() {
+        assert_event_sent_by_contract(&context(contract_address(), 0), "Not sent by the contract.");
+    }
+
+    #[test]
+    #[should_panic(expected = "Not sent by the contract.")]
+    pub fn event_sent_by_others_is_rejected() {
+        assert_event_sent_by_contract(&context(account(1), 0), "Not sent by the contract.");
+    }
+}
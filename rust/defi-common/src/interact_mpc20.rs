@@ -14,6 +14,7 @@
 //! #[action(shortname=0x01)] transfer(to: Address, amount: u128);
 //! #[action(shortname=0x03)] transfer_from(from: Address, to: Address, amount: u128);
 //! #[action(shortname=0x05)] approve(spender: Address, amount: u128);
+//! #[action(shortname=0x07)] approve_relative(spender: Address, delta: i128);
 //! ```
 //!
 //! The root state struct is named TokenState and each of the following state fields exist in the
@@ -50,16 +51,21 @@ impl MPC20Contract {
     /// Shortname of the [`MPC20Contract::approve`] invocation
     const SHORTNAME_APPROVE: Shortname = Shortname::from_u32(0x05);
 
+    /// Shortname of the [`MPC20Contract::approve_relative`] invocation
+    const SHORTNAME_APPROVE_RELATIVE: Shortname = Shortname::from_u32(0x07);
+
     /// Gas amount sufficient for [`MPC20Contract::transfer`] invocation
     /// for the following contracts: token-v1, token-v2, and MPC MPC20 token.
     ///
-    /// Guarantees that the invocation does not fail due to insufficient gas.
+    /// Guarantees that the invocation does not fail due to insufficient gas, including the
+    /// [`crate::events::GAS_COST_EVENT`] of the transfer event spawned by token-v1.
     pub const GAS_COST_TRANSFER: GasCost = 15500;
 
     /// Gas amount sufficient for MPC20 [`MPC20Contract::transfer_from`] invocation
     /// for the following contracts: token-v1, token-v2, and MPC MPC20 token.
     ///
-    /// Guarantees that the invocation does not fail due to insufficient gas.
+    /// Guarantees that the invocation does not fail due to insufficient gas, including the
+    /// [`crate::events::GAS_COST_EVENT`] of the transfer event spawned by token-v1.
     pub const GAS_COST_TRANSFER_FROM: GasCost = 15500;
 
     /// Gas amount sufficient for MPC20 [`MPC20Contract::approve`] invocation
@@ -78,30 +84,35 @@ impl MPC20Contract {
 
     /// Create an interaction with the `self` token contract, for transferring an `amount` of
     /// tokens from calling contract to `receiver`.
+    ///
+    /// Returns the gas attached to the interaction, which is [`Self::GAS_COST_TRANSFER`].
     pub fn transfer(
         &self,
         event_group_builder: &mut EventGroupBuilder,
         receiver: &Address,
         amount: TokenTransferAmount,
-    ) {
+    ) -> GasCost {
         event_group_builder
             .call(self.contract_address, Self::SHORTNAME_TRANSFER)
             .argument(*receiver)
             .argument(amount)
             .with_cost(Self::GAS_COST_TRANSFER)
             .done();
+        Self::GAS_COST_TRANSFER
     }
 
     /// Create an interaction with the `self` token contract, for transferring an `amount` of
     /// tokens from `sender` to `receiver`. Requires that calling contract have been given an
     /// allowance by `sender`, by using [`Self::approve`].
+    ///
+    /// Returns the gas attached to the interaction, which is [`Self::GAS_COST_TRANSFER_FROM`].
     pub fn transfer_from(
         &self,
         event_group_builder: &mut EventGroupBuilder,
         sender: &Address,
         receiver: &Address,
         amount: TokenTransferAmount,
-    ) {
+    ) -> GasCost {
         event_group_builder
             .call(self.contract_address, Self::SHORTNAME_TRANSFER_FROM)
             .argument(*sender)
@@ -109,21 +120,134 @@ impl MPC20Contract {
             .argument(amount)
             .with_cost(Self::GAS_COST_TRANSFER_FROM)
             .done();
+        Self::GAS_COST_TRANSFER_FROM
     }
 
     /// Create an interaction with the `self` token contract, for approving an `approval_amount` of
     /// tokens owned by the sender of the interaction, to be handled by the `approved` contract.
+    ///
+    /// Returns the gas attached to the interaction, which is [`Self::GAS_COST_APPROVE`].
     pub fn approve(
         &self,
         event_group_builder: &mut EventGroupBuilder,
         approved: &Address,
         approval_amount: TokenAmount,
-    ) {
+    ) -> GasCost {
         event_group_builder
             .call(self.contract_address, Self::SHORTNAME_APPROVE)
             .argument(*approved)
             .argument(approval_amount)
             .with_cost(Self::GAS_COST_APPROVE)
             .done();
+        Self::GAS_COST_APPROVE
+    }
+
+    /// Create an interaction with the `self` token contract, for changing the approval of the
+    /// `approved` contract by `delta`, relative to any existing allowance.
+    ///
+    /// Unlike [`MPC20Contract::approve`], concurrent approvals of the same contract do not
+    /// override each other. Not all token contracts implement relative approval, like the BYOC
+    /// contracts.
+    ///
+    /// Returns the gas attached to the interaction, which is [`Self::GAS_COST_APPROVE`].
+    pub fn approve_relative(
+        &self,
+        event_group_builder: &mut EventGroupBuilder,
+        approved: &Address,
+        delta: i128,
+    ) -> GasCost {
+        event_group_builder
+            .call(self.contract_address, Self::SHORTNAME_APPROVE_RELATIVE)
+            .argument(*approved)
+            .argument(delta)
+            .with_cost(Self::GAS_COST_APPROVE)
+            .done();
+        Self::GAS_COST_APPROVE
+    }
+
+    /// Create interactions with the `self` token contract, that first resets the approval of the
+    /// `approved` contract to zero, and then approves `approval_amount`.
+    ///
+    /// This does not defend against the approve-race, where the `approved` contract spends both
+    /// the old and the new allowance. Both approvals are added to the same event group, and are
+    /// executed right after each other, so the `approved` contract can still spend the old
+    /// allowance before the event group is executed, and the new allowance afterwards. Use
+    /// [`MPC20Contract::approve_relative`] when the token contract supports it. The reset is only
+    /// useful for token contracts that reject changing a non-zero allowance to another non-zero
+    /// allowance.
+    ///
+    /// Returns the gas attached to the interactions, which is twice [`Self::GAS_COST_APPROVE`].
+    pub fn safe_approve(
+        &self,
+        event_group_builder: &mut EventGroupBuilder,
+        approved: &Address,
+        approval_amount: TokenAmount,
+    ) -> GasCost {
+        self.approve(event_group_builder, approved, 0)
+            + self.approve(event_group_builder, approved, approval_amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::contract;
+    use pbc_contract_common::events::EventGroup;
+
+    #[test]
+    pub fn gas_costs_are_non_zero() {
+        assert_ne!(MPC20Contract::GAS_COST_TRANSFER, 0);
+        assert_ne!(MPC20Contract::GAS_COST_TRANSFER_FROM, 0);
+        assert_ne!(MPC20Contract::GAS_COST_APPROVE, 0);
+    }
+
+    #[test]
+    pub fn builder_helpers_attach_gas_costs() {
+        let token = MPC20Contract::at_address(contract(1));
+        let mut event_group = EventGroup::builder();
+        assert_eq!(
+            token.transfer(&mut event_group, &contract(2), 10),
+            MPC20Contract::GAS_COST_TRANSFER
+        );
+        assert_eq!(
+            token.transfer_from(&mut event_group, &contract(2), &contract(3), 10),
+            MPC20Contract::GAS_COST_TRANSFER_FROM
+        );
+        assert_eq!(
+            token.approve(&mut event_group, &contract(3), 10),
+            MPC20Contract::GAS_COST_APPROVE
+        );
+        assert_eq!(
+            token.approve_relative(&mut event_group, &contract(3), 10),
+            MPC20Contract::GAS_COST_APPROVE
+        );
+    }
+
+    #[test]
+    pub fn safe_approve_attaches_gas_for_two_approvals() {
+        let token = MPC20Contract::at_address(contract(1));
+        let mut event_group = EventGroup::builder();
+        assert_eq!(
+            token.safe_approve(&mut event_group, &contract(3), 10),
+            2 * MPC20Contract::GAS_COST_APPROVE
+        );
+    }
+
+    #[test]
+    pub fn safe_approve_resets_before_approving() {
+        let token = MPC20Contract::at_address(contract(1));
+        let mut event_group = EventGroup::builder();
+        token.safe_approve(&mut event_group, &contract(3), 10);
+
+        let mut expected = EventGroup::builder();
+        for amount in [0, 10] {
+            expected
+                .call(contract(1), MPC20Contract::SHORTNAME_APPROVE)
+                .argument(contract(3))
+                .argument(amount as TokenAmount)
+                .with_cost(MPC20Contract::GAS_COST_APPROVE)
+                .done();
+        }
+        assert_eq!(event_group.build(), expected.build());
     }
 }
@@ -8,13 +8,31 @@
 //! ```ignore
 //! #[action(shortname=0x01)] deposit(token_address: Address, amount: TokenAmount);
 //! #[action(shortname=0x03)] withdraw(token_address: Address, amount: TokenAmount, wait_for_callback: bool);
+//! #[action(shortname=0x0A)] get_reserves();
 //! ```
+//!
+//! The `get_reserves` action must return the [`SwapReserves`] of the swap contract as return data,
+//! such that they can be read by a callback using
+//! [`get_return_data`](pbc_contract_common::context::ExecutionResult::get_return_data).
 
 use crate::interact_mpc20::MPC20Contract;
 use crate::token_balances::TokenAmount;
 use pbc_contract_common::address::Address;
 use pbc_contract_common::events::{EventGroupBuilder, GasCost};
 use pbc_contract_common::shortname::Shortname;
+use read_write_rpc_derive::ReadWriteRPC;
+
+/// Reserves of the pools of a swap contract, and the fee for making swaps, as returned by the
+/// [`SwapContract::get_reserves`] invocation.
+#[derive(ReadWriteRPC, PartialEq, Eq, Debug, Clone, Copy)]
+pub struct SwapReserves {
+    /// The amount of token A in the pool.
+    pub reserve_a: TokenAmount,
+    /// The amount of token B in the pool.
+    pub reserve_b: TokenAmount,
+    /// The fee for making swaps per mille.
+    pub fee_per_mille: u16,
+}
 
 /// Represents an individual swap contract on the blockchain.
 pub struct SwapContract {
@@ -28,6 +46,9 @@ impl SwapContract {
     /// Shortname of the [`SwapContract::withdraw`] invocation
     const SHORTNAME_WITHDRAW_SWAP_LOCK: Shortname = Shortname::from_u32(0x03);
 
+    /// Shortname of the [`SwapContract::get_reserves`] invocation
+    const SHORTNAME_GET_RESERVES: Shortname = Shortname::from_u32(0x0A);
+
     const DEPOSIT_BASE_COST: GasCost = 1770;
 
     /// Gas amount sufficient for [`SwapContract::deposit`] invocation.
@@ -44,6 +65,11 @@ impl SwapContract {
     pub const GAS_COST_WITHDRAW: GasCost =
         Self::WITHDRAW_BASE_COST + MPC20Contract::GAS_COST_TRANSFER_FROM;
 
+    /// Gas amount sufficient for [`SwapContract::get_reserves`] invocation.
+    ///
+    /// Guarantees that the invocation does not fail due to insufficient gas.
+    pub const GAS_COST_GET_RESERVES: GasCost = 500;
+
     /// Create a new swap contract representation at `contract_address`.
     pub fn at_address(contract_address: Address) -> Self {
         Self { contract_address }
@@ -88,4 +114,61 @@ impl SwapContract {
             .with_cost(Self::GAS_COST_WITHDRAW)
             .done();
     }
+
+    /// Create an interaction with the `self` swap contract, for reading the reserves of its pools
+    /// and its swap fee.
+    ///
+    /// The [`SwapReserves`] are returned to the callback added to `event_group_builder`, and can be
+    /// read from the result of the interaction with
+    /// [`get_return_data`](pbc_contract_common::context::ExecutionResult::get_return_data).
+    pub fn get_reserves(&self, event_group_builder: &mut EventGroupBuilder) {
+        event_group_builder
+            .call(self.contract_address, Self::SHORTNAME_GET_RESERVES)
+            .with_cost(Self::GAS_COST_GET_RESERVES)
+            .done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::contract;
+    use pbc_contract_common::events::EventGroup;
+    use pbc_traits::ReadWriteRPC;
+
+    #[test]
+    pub fn get_reserves_calls_get_reserves_action() {
+        let mut event_group = EventGroup::builder();
+        SwapContract::at_address(contract(1)).get_reserves(&mut event_group);
+
+        let mut expected = EventGroup::builder();
+        expected
+            .call(contract(1), Shortname::from_u32(0x0A))
+            .with_cost(SwapContract::GAS_COST_GET_RESERVES)
+            .done();
+        assert_eq!(event_group.build(), expected.build());
+    }
+
+    #[test]
+    pub fn swap_reserves_are_read_from_return_data() {
+        // Return data of a swap contract with reserves 1000 and 2^100, and a fee of 3 per mille.
+        let mut return_data = vec![];
+        return_data.extend(1000u128.to_be_bytes());
+        return_data.extend((1u128 << 100).to_be_bytes());
+        return_data.extend(3u16.to_be_bytes());
+
+        let reserves = SwapReserves::rpc_read_from(&mut return_data.as_slice());
+        assert_eq!(
+            reserves,
+            SwapReserves {
+                reserve_a: 1000,
+                reserve_b: 1 << 100,
+                fee_per_mille: 3,
+            }
+        );
+
+        let mut written = vec![];
+        reserves.rpc_write_to(&mut written).unwrap();
+        assert_eq!(written, return_data);
+    }
 }
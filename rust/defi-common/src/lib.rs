@@ -1,11 +1,14 @@
 #![doc = include_str!("../README.md")]
 
 pub mod deploy;
+pub mod events;
 pub mod interact_mpc20;
 pub mod interact_swap;
 pub mod interact_swap_lock_partial;
 pub mod liquidity_util;
 pub mod math;
 pub mod permission;
+#[cfg(any(test, feature = "test_util"))]
+pub mod test_util;
 pub mod token_balances;
 pub mod token_state;
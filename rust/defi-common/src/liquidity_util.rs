@@ -6,7 +6,7 @@ use read_write_rpc_derive::ReadWriteRPC;
 use read_write_state_derive::ReadWriteState;
 
 /// Id of a liquidity-lock.
-#[derive(Copy, Clone, Debug, ReadWriteRPC, ReadWriteState, CreateTypeSpec)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ReadWriteRPC, ReadWriteState, CreateTypeSpec)]
 pub struct LiquidityLockId {
     raw_id: u128,
 }
@@ -37,6 +37,36 @@ pub struct AcquiredLiquidityLockInformation {
     pub amount_out: TokenAmount,
 }
 
+/// Determines when a lock acquired at `acquired_at` expires, given the lock duration of the
+/// swap-contract.
+///
+/// ### Parameters:
+///
+/// * `acquired_at`: [`i64`] - Block production time at which the lock is acquired, in milliseconds.
+///
+/// * `lock_duration_millis`: [`Option<i64>`] - How long locks are held before they are released. `None` if locks never expire.
+///
+/// # Returns
+/// The expiry timestamp of the lock, or `None` if the lock never expires. [`Option<i64>`]
+pub fn lock_expiry(acquired_at: i64, lock_duration_millis: Option<i64>) -> Option<i64> {
+    lock_duration_millis.map(|duration| acquired_at.saturating_add(duration))
+}
+
+/// Checks whether a lock with the given expiry has expired at `now`, in which case the lock is
+/// considered released, and must no longer block liquidity.
+///
+/// ### Parameters:
+///
+/// * `expires_at`: [`Option<i64>`] - Expiry timestamp of the lock, in milliseconds. `None` if the lock never expires.
+///
+/// * `now`: [`i64`] - The current block production time, in milliseconds.
+///
+/// # Returns
+/// True if the lock has expired, false otherwise. [`bool`]
+pub fn is_lock_expired(expires_at: Option<i64>, now: i64) -> bool {
+    matches!(expires_at, Some(expires_at) if expires_at <= now)
+}
+
 /// Calculates how many of the output token you can get for `swap_amount_in` given an exchange fee in per mille. <br>
 /// In other words, calculates how much the input token amount, minus the fee, is worth in the output token currency. <br>
 /// This calculation is derived from section 3.1.2 of [UniSwap v1 whitepaper](https://github.com/runtimeverification/verified-smart-contracts/blob/uniswap/uniswap/x-y-k.pdf)
@@ -63,3 +93,29 @@ pub fn calculate_swap_to_amount(
     (remainder_ratio * swap_amount_in * pool_token_out)
         / (1000 * pool_token_in + remainder_ratio * swap_amount_in)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_without_duration_never_expire() {
+        let expires_at = lock_expiry(1000, None);
+        assert_eq!(expires_at, None);
+        assert!(!is_lock_expired(expires_at, i64::MAX));
+    }
+
+    #[test]
+    fn locks_expire_after_duration() {
+        let expires_at = lock_expiry(1000, Some(500));
+        assert_eq!(expires_at, Some(1500));
+        assert!(!is_lock_expired(expires_at, 1499));
+        assert!(is_lock_expired(expires_at, 1500));
+        assert!(is_lock_expired(expires_at, 1501));
+    }
+
+    #[test]
+    fn lock_expiry_saturates() {
+        assert_eq!(lock_expiry(i64::MAX - 1, Some(10)), Some(i64::MAX));
+    }
+}
@@ -54,6 +54,70 @@ pub fn u128_division_ceil(numerator: u128, denominator: u128) -> Result<u128, &'
     Ok(div_floor + u128::from(rem != 0))
 }
 
+/// Computes `a * b / c` rounding down, without overflowing in the intermediate product.
+///
+/// Uses checked [`u128`] multiplication when the product fits, and otherwise falls back to a
+/// 256-bit intermediate product, such that the result is correct whenever it fits in a [`u128`].
+///
+/// ### Parameters:
+///
+/// * `a`: [`u128`] - The first factor.
+///
+/// * `b`: [`u128`] - The second factor.
+///
+/// * `c`: [`u128`] - The divisor. Must be non-zero.
+///
+/// ### Returns:
+/// `a * b / c` of type [`u128`]. Panics if `c` is zero, or if the result does not fit in a
+/// [`u128`].
+pub fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    assert_ne!(c, 0, "Division by zero");
+    if let Some(product) = a.checked_mul(b) {
+        return product / c;
+    }
+
+    let (product_high, product_low) = u128_full_mul(a, b);
+    assert!(
+        product_high < c,
+        "Result of {a} * {b} / {c} does not fit in u128"
+    );
+
+    // Long division of the 256-bit product by `c`, one bit at a time. The remainder is always
+    // smaller than `c`, so the quotient fits in a u128.
+    let mut remainder = product_high;
+    let mut quotient: u128 = 0;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((product_low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
+/// Computes the full 256-bit product of `a` and `b`, as the high and low 128 bits.
+fn u128_full_mul(a: u128, b: u128) -> (u128, u128) {
+    const LOW_MASK: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW_MASK);
+    let (b_high, b_low) = (b >> 64, b & LOW_MASK);
+
+    // Each partial product of 64-bit halves fits in a u128.
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let high_high = a_high * b_high;
+
+    // Cannot overflow, as it is the sum of three values below 2^64.
+    let middle = (low_low >> 64) + (high_low & LOW_MASK) + (low_high & LOW_MASK);
+
+    let low = (middle << 64) | (low_low & LOW_MASK);
+    let high = high_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    (high, low)
+}
+
 /// The allowed range of per mille values.
 pub const ALLOWED_RANGE_PER_MILLE: RangeInclusive<u16> = 0..=1000;
 
@@ -64,6 +128,40 @@ pub fn assert_is_per_mille(per_mille: u16) {
     }
 }
 
+/// The number of basis points in a whole.
+pub const BASIS_POINTS_PER_WHOLE: u16 = 10000;
+
+/// The allowed range of basis points values.
+pub const ALLOWED_RANGE_BASIS_POINTS: RangeInclusive<u16> = 0..=BASIS_POINTS_PER_WHOLE;
+
+/// Asserts that given value was within the range of basis points values (between 0 and 10000.)
+pub fn assert_is_basis_points(bps: u16) {
+    if !ALLOWED_RANGE_BASIS_POINTS.contains(&bps) {
+        panic!("basis points value was {bps} bps, but must be between 0 bps and 10000 bps");
+    }
+}
+
+/// Computes the given fraction of `amount`, in basis points, rounding down.
+///
+/// ### Parameters:
+///
+/// * `amount`: [`u128`] - The amount to take a fraction of.
+///
+/// * `bps`: [`u16`] - The fraction in basis points. Must be between 0 and 10000.
+///
+/// ### Returns:
+/// `amount * bps / 10000` of type [`u128`]. Never overflows, even for large amounts.
+pub fn apply_basis_points(amount: u128, bps: u16) -> u128 {
+    assert_is_basis_points(bps);
+    let bps = u128::from(bps);
+    let whole = u128::from(BASIS_POINTS_PER_WHOLE);
+    match amount.checked_mul(bps) {
+        Some(product) => product / whole,
+        // Split into quotient and remainder, which cannot overflow as bps <= whole.
+        None => (amount / whole) * bps + (amount % whole) * bps / whole,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,4 +257,88 @@ mod tests {
         assert_eq!(u128_division_ceil(k, a), Ok(b));
         assert_eq!(u128_division_ceil(k, b), Ok(a));
     }
+
+    #[test]
+    pub fn apply_basis_points_boundaries() {
+        assert_eq!(apply_basis_points(12345, 0), 0);
+        assert_eq!(apply_basis_points(12345, 10000), 12345);
+        assert_eq!(apply_basis_points(12345, 30), 37);
+        assert_eq!(apply_basis_points(0, 10000), 0);
+    }
+
+    #[test]
+    pub fn apply_basis_points_large_amounts() {
+        assert_eq!(apply_basis_points(u128::MAX, 10000), u128::MAX);
+        assert_eq!(apply_basis_points(u128::MAX, 5000), u128::MAX / 2);
+        assert_eq!(apply_basis_points(u128::MAX, 1), u128::MAX / 10000);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "basis points value was 10001 bps, but must be between 0 bps and 10000 bps"
+    )]
+    pub fn basis_points_out_of_range() {
+        assert_is_basis_points(10001);
+    }
+
+    #[test]
+    pub fn mul_div_small_operands() {
+        assert_eq!(mul_div(6, 7, 2), 21);
+        assert_eq!(mul_div(10, 10, 3), 33);
+        assert_eq!(mul_div(0, u128::MAX, 1), 0);
+    }
+
+    #[test]
+    pub fn mul_div_near_u128_max() {
+        // The product of each of these overflows a u128.
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, 3, 3), u128::MAX);
+        assert_eq!(mul_div(u128::MAX, u128::MAX - 1, u128::MAX), u128::MAX - 1);
+        assert_eq!(mul_div(u128::MAX - 1, u128::MAX, u128::MAX - 1), u128::MAX);
+        assert_eq!(mul_div(1 << 127, 4, 8), 1 << 126);
+        assert_eq!(mul_div(u128::MAX, 1 << 64, 1 << 65), u128::MAX / 2);
+        assert_eq!(mul_div(u128::MAX, 2, 3), u128::MAX / 3 * 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in u128")]
+    pub fn mul_div_result_overflow() {
+        mul_div(u128::MAX, 2, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero")]
+    pub fn mul_div_division_by_zero() {
+        mul_div(1, 1, 0);
+    }
+
+    #[test]
+    pub fn u128_full_mul_boundaries() {
+        assert_eq!(u128_full_mul(0, u128::MAX), (0, 0));
+        assert_eq!(u128_full_mul(u128::MAX, 1), (0, u128::MAX));
+        assert_eq!(u128_full_mul(1 << 64, 1 << 64), (1, 0));
+        assert_eq!(u128_full_mul(u128::MAX, u128::MAX), (u128::MAX - 1, 1));
+    }
+
+    proptest! {
+        #[test]
+        fn mul_div_matches_naive_without_overflow(a in any::<u64>(), b in any::<u64>(), c in 1..=u128::MAX) {
+            let (a, b) = (u128::from(a), u128::from(b));
+            prop_assert_eq!(mul_div(a, b, c), a * b / c);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn mul_div_cancels_common_factor(a in any::<u128>(), b in 1..=u128::MAX) {
+            prop_assert_eq!(mul_div(a, b, b), a);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn apply_basis_points_never_exceeds_amount(amount in any::<u128>(), bps in 0..=10000u16) {
+            prop_assert!(apply_basis_points(amount, bps) <= amount);
+        }
+    }
 }
@@ -8,8 +8,28 @@ use read_write_state_derive::ReadWriteState;
 /// Permission enum for modelling permission systems at runtime.
 ///
 /// Intention is to allow contracts creators to specify which [`Address`]es are allowed to call
-/// specific invocations at initialization.
-#[derive(ReadWriteRPC, ReadWriteState, CreateTypeSpec)]
+/// specific invocations at initialization. A [`Permission::Specific`] allow-list can be
+/// administered at runtime using [`Permission::grant`], [`Permission::revoke`] or
+/// [`Permission::apply_update`].
+///
+/// Contracts can expose administration of a permission through an action taking a
+/// [`PermissionUpdate`], gated by a separate administration permission:
+///
+/// ```ignore
+/// #[action(shortname = 0x10)]
+/// pub fn update_permission_foo(
+///     context: ContractContext,
+///     mut state: ContractState,
+///     update: PermissionUpdate,
+/// ) -> ContractState {
+///     state
+///         .permission_admin
+///         .assert_permission_for(&context.sender, "administer foo");
+///     state.permission_foo.apply_update(update);
+///     state
+/// }
+/// ```
+#[derive(ReadWriteRPC, ReadWriteState, CreateTypeSpec, Debug, PartialEq, Eq)]
 #[repr(C)]
 pub enum Permission {
     /// Permission where everybody have the permission.
@@ -24,6 +44,25 @@ pub enum Permission {
     },
 }
 
+/// Runtime update to the allow-list of a [`Permission`].
+#[derive(ReadWriteRPC, CreateTypeSpec, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub enum PermissionUpdate {
+    /// Grant the permission to the given [`Address`].
+    #[discriminant(0)]
+    Grant {
+        /// [`Address`] to grant the permission to.
+        address: Address,
+    },
+
+    /// Revoke the permission from the given [`Address`].
+    #[discriminant(1)]
+    Revoke {
+        /// [`Address`] to revoke the permission from.
+        address: Address,
+    },
+}
+
 impl Permission {
     /// Determines whether the given address have this permission.
     ///
@@ -55,4 +94,111 @@ impl Permission {
             permission_name
         );
     }
+
+    /// Grants this permission to the given address. Granting to an address which already has
+    /// the permission does nothing.
+    ///
+    /// Panics when:
+    ///
+    /// - Permission is [`Permission::Anybody`], as it cannot be restricted to an allow-list.
+    pub fn grant(&mut self, addr: Address) {
+        match self {
+            Permission::Anybody {} => {
+                panic!("Cannot grant to a permission held by anybody")
+            }
+            Permission::Specific { addresses } => {
+                if !addresses.contains(&addr) {
+                    addresses.push(addr);
+                }
+            }
+        }
+    }
+
+    /// Revokes this permission from the given address. Revoking from an address which does not
+    /// have the permission does nothing.
+    ///
+    /// Panics when:
+    ///
+    /// - Permission is [`Permission::Anybody`], as it cannot be restricted to an allow-list.
+    pub fn revoke(&mut self, addr: &Address) {
+        match self {
+            Permission::Anybody {} => {
+                panic!("Cannot revoke from a permission held by anybody")
+            }
+            Permission::Specific { addresses } => addresses.retain(|a| a != addr),
+        }
+    }
+
+    /// Applies the given [`PermissionUpdate`] to this permission.
+    ///
+    /// Panics when:
+    ///
+    /// - Permission is [`Permission::Anybody`].
+    pub fn apply_update(&mut self, update: PermissionUpdate) {
+        match update {
+            PermissionUpdate::Grant { address } => self.grant(address),
+            PermissionUpdate::Revoke { address } => self.revoke(&address),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::account;
+
+    #[test]
+    pub fn grant_adds_address() {
+        let mut permission = Permission::Specific { addresses: vec![] };
+        assert!(!permission.does_address_have_permission(&account(1)));
+
+        permission.grant(account(1));
+        permission.grant(account(1));
+        assert_eq!(
+            permission,
+            Permission::Specific {
+                addresses: vec![account(1)]
+            }
+        );
+        permission.assert_permission_for(&account(1), "test");
+    }
+
+    #[test]
+    pub fn revoke_removes_address() {
+        let mut permission = Permission::Specific {
+            addresses: vec![account(1), account(2)],
+        };
+        permission.revoke(&account(1));
+        permission.revoke(&account(3));
+
+        assert!(!permission.does_address_have_permission(&account(1)));
+        assert!(permission.does_address_have_permission(&account(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "did not have permission \"test\"")]
+    pub fn revoked_address_is_denied() {
+        let mut permission = Permission::Specific {
+            addresses: vec![account(1)],
+        };
+        permission.apply_update(PermissionUpdate::Revoke {
+            address: account(1),
+        });
+        permission.assert_permission_for(&account(1), "test");
+    }
+
+    #[test]
+    pub fn apply_update_grants() {
+        let mut permission = Permission::Specific { addresses: vec![] };
+        permission.apply_update(PermissionUpdate::Grant {
+            address: account(2),
+        });
+        assert!(permission.does_address_have_permission(&account(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot revoke from a permission held by anybody")]
+    pub fn cannot_revoke_from_anybody() {
+        Permission::Anybody {}.revoke(&account(1));
+    }
 }
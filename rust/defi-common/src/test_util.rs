@@ -0,0 +1,75 @@
+//! Utility module for the unit tests of the DeFi contracts.
+//!
+//! `#[cfg(test)]` only applies to the tests of the crate being tested, so this module is also
+//! compiled when the `test_util` feature is enabled. Contracts enable the feature on their
+//! dev-dependency, such that it never becomes part of the deployed contract:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! defi_common = { path = "../defi-common", features = ["test_util"] }
+//! ```
+
+use pbc_contract_common::address::{Address, AddressType};
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::Hash;
+
+/// Identifier of the address of the contract under test. Chosen to not collide with the small
+/// identifiers used for other addresses in tests.
+const CONTRACT_UNDER_TEST_ID: u8 = 0xCC;
+
+/// Creates an account address, with all bytes of the identifier set to `id`.
+///
+/// ### Parameters:
+///
+/// * `id`: [`u8`] - The byte of the identifier.
+///
+/// ### Returns:
+///
+/// The account [`Address`].
+pub fn account(id: u8) -> Address {
+    Address::from_components(AddressType::Account, [id; 20])
+}
+
+/// Creates a public contract address, with all bytes of the identifier set to `id`.
+///
+/// ### Parameters:
+///
+/// * `id`: [`u8`] - The byte of the identifier.
+///
+/// ### Returns:
+///
+/// The public contract [`Address`].
+pub fn contract(id: u8) -> Address {
+    Address::from_components(AddressType::PublicContract, [id; 20])
+}
+
+/// The address of the contract under test, as given by [`context`].
+///
+/// ### Returns:
+///
+/// The public contract [`Address`] of the contract under test.
+pub fn contract_address() -> Address {
+    contract(CONTRACT_UNDER_TEST_ID)
+}
+
+/// Creates the context of a call to the contract under test.
+///
+/// ### Parameters:
+///
+/// * `sender`: [`Address`] - The sender of the call.
+///
+/// * `block_production_time`: [`i64`] - The production time of the block of the call.
+///
+/// ### Returns:
+///
+/// The [`ContractContext`] of the call.
+pub fn context(sender: Address, block_production_time: i64) -> ContractContext {
+    ContractContext {
+        contract_address: contract_address(),
+        sender,
+        block_time: 0,
+        block_production_time,
+        current_transaction: Hash { bytes: [0; 32] },
+        original_transaction: Hash { bytes: [0; 32] },
+    }
+}
@@ -11,6 +11,10 @@
 //! [`TokenBalances::deduct_from_token_balance`] in order to move tokens in and out of the contract
 //! itself.
 //!
+//! [`MultiTokenBalances`] generalizes [`TokenBalances`] to an arbitrary small set of tokens, each
+//! identified by its [`TokenSlot`]. Both are implemented by the same slot based balance handling,
+//! where [`TokenBalances`] names its slots using [`DepositToken`].
+//!
 //! These defi contracts use [`TokenBalances`]:
 //!
 //! - `liquidity-swap`
@@ -53,6 +57,26 @@ impl DepositToken {
 
     /// The value representing liquidity token.
     pub const LIQUIDITY: DepositToken = DepositToken::LiquidityToken {};
+
+    /// The slot of the token when used with [`MultiTokenBalances`] created by
+    /// [`MultiTokenBalances::for_pair`] or [`MultiTokenBalances::for_pair_with_liquidity`].
+    pub const fn slot(self) -> TokenSlot {
+        match self {
+            DepositToken::TokenA {} => 0,
+            DepositToken::TokenB {} => 1,
+            DepositToken::LiquidityToken {} => 2,
+        }
+    }
+
+    /// Inverse of [`DepositToken::slot`].
+    pub const fn from_slot(slot: TokenSlot) -> Option<DepositToken> {
+        match slot {
+            0 => Some(DepositToken::A),
+            1 => Some(DepositToken::B),
+            2 => Some(DepositToken::LIQUIDITY),
+            _ => None,
+        }
+    }
 }
 
 /// Keeps track of how much of a given token a user owns within the scope of the contract.
@@ -81,13 +105,7 @@ impl TokenBalance {
     /// # Returns
     /// A value of type [`TokenAmount`]
     pub fn get_amount_of(&self, token: DepositToken) -> TokenAmount {
-        if token == DepositToken::LIQUIDITY {
-            self.liquidity_tokens
-        } else if token == DepositToken::A {
-            self.a_tokens
-        } else {
-            self.b_tokens
-        }
+        self.amount(token.slot())
     }
 
     /// Retrieves a mutable reference to the amount that matches `token`.
@@ -99,13 +117,7 @@ impl TokenBalance {
     /// # Returns
     /// A mutable value of type [`TokenAmount`]
     pub fn get_mut_amount_of(&mut self, token: DepositToken) -> &mut TokenAmount {
-        if token == DepositToken::LIQUIDITY {
-            &mut self.liquidity_tokens
-        } else if token == DepositToken::A {
-            &mut self.a_tokens
-        } else {
-            &mut self.b_tokens
-        }
+        self.amount_mut(token.slot())
     }
 
     /// Checks that the user has no tokens.
@@ -117,6 +129,30 @@ impl TokenBalance {
     }
 }
 
+impl SlotBalance for TokenBalance {
+    fn amount(&self, slot: TokenSlot) -> TokenAmount {
+        match DepositToken::from_slot(slot) {
+            Some(DepositToken::TokenA {}) => self.a_tokens,
+            Some(DepositToken::TokenB {}) => self.b_tokens,
+            Some(DepositToken::LiquidityToken {}) => self.liquidity_tokens,
+            None => panic!("Unknown token slot {slot}"),
+        }
+    }
+
+    fn amount_mut(&mut self, slot: TokenSlot) -> &mut TokenAmount {
+        match DepositToken::from_slot(slot) {
+            Some(DepositToken::TokenA {}) => &mut self.a_tokens,
+            Some(DepositToken::TokenB {}) => &mut self.b_tokens,
+            Some(DepositToken::LiquidityToken {}) => &mut self.liquidity_tokens,
+            None => panic!("Unknown token slot {slot}"),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.user_has_no_tokens()
+    }
+}
+
 /// Empty token balance.
 pub const EMPTY_BALANCE: TokenBalance = TokenBalance {
     a_tokens: 0,
@@ -124,8 +160,106 @@ pub const EMPTY_BALANCE: TokenBalance = TokenBalance {
     liquidity_tokens: 0,
 };
 
+/// Error returned when a user does not have enough tokens for a deduction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct InsufficientBalance {
+    /// The token that the deduction was attempted for.
+    pub token: DepositToken,
+    /// The amount of `token` that the user owns.
+    pub balance: TokenAmount,
+    /// The amount that was attempted deducted.
+    pub requested: TokenAmount,
+}
+
+impl InsufficientBalance {
+    /// The amount of tokens that the user is missing for the deduction to succeed.
+    pub fn shortfall(&self) -> TokenAmount {
+        self.requested - self.balance
+    }
+}
+
+/// Error returned when tokens cannot be moved between two users.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MoveTokensError {
+    /// The transferring party does not have enough tokens.
+    InsufficientBalance(InsufficientBalance),
+    /// The balance of the receiving party would overflow.
+    DestinationOverflow {
+        /// The token that the move was attempted for.
+        token: DepositToken,
+        /// The amount of `token` that the receiving party owns.
+        balance: TokenAmount,
+        /// The amount that was attempted moved.
+        requested: TokenAmount,
+    },
+}
+
 type Map<K, V> = AvlTreeMap<K, V>;
 
+/// Balance of a single user, with an amount for each [`TokenSlot`].
+///
+/// Implemented by the balances of both [`TokenBalances`] and [`MultiTokenBalances`], such that the
+/// handling of deposits is shared between them, see [`SlotBalanceMap`].
+trait SlotBalance: ReadWriteState + Clone {
+    /// Retrieves a copy of the amount in the given slot.
+    fn amount(&self, slot: TokenSlot) -> TokenAmount;
+
+    /// Retrieves a mutable reference to the amount in the given slot.
+    fn amount_mut(&mut self, slot: TokenSlot) -> &mut TokenAmount;
+
+    /// Checks that the user has no tokens.
+    fn is_empty(&self) -> bool;
+}
+
+/// Balances of all users, indexed by [`TokenSlot`].
+struct SlotBalanceMap<'a, B: SlotBalance> {
+    balances: &'a mut Map<Address, B>,
+    empty: B,
+}
+
+impl<B: SlotBalance> SlotBalanceMap<'_, B> {
+    /// Retrieves a copy of the balance of `user`.
+    fn get(&self, user: &Address) -> B {
+        self.balances
+            .get(user)
+            .unwrap_or_else(|| self.empty.clone())
+    }
+
+    /// Stores the balance of `user` after a deduction, removing the user if they have no tokens.
+    fn set(&mut self, user: Address, balance: B) {
+        if balance.is_empty() {
+            self.balances.remove(&user);
+        } else {
+            self.balances.insert(user, balance);
+        }
+    }
+
+    /// Adds `amount` to the given slot of `user`.
+    fn add(&mut self, user: Address, slot: TokenSlot, amount: TokenAmount) {
+        let mut balance = self.get(&user);
+        *balance.amount_mut(slot) += amount;
+        self.balances.insert(user, balance);
+    }
+
+    /// Deducts `amount` from the given slot of `user`, leaving the balances unchanged when the user
+    /// does not have enough tokens.
+    ///
+    /// # Returns
+    /// The amount owned by `user` if it is less than `amount`.
+    fn try_deduct(
+        &mut self,
+        user: Address,
+        slot: TokenSlot,
+        amount: TokenAmount,
+    ) -> Result<(), TokenAmount> {
+        let mut balance = self.get(&user);
+        let owned = balance.amount(slot);
+        *balance.amount_mut(slot) = owned.checked_sub(amount).ok_or(owned)?;
+        self.set(user, balance);
+        Ok(())
+    }
+}
+
 /// Generalized token balance structure.
 #[derive(ReadWriteState, CreateTypeSpec, Debug)]
 pub struct TokenBalances {
@@ -183,14 +317,14 @@ impl TokenBalances {
         token: DepositToken,
         amount: TokenAmount,
     ) {
-        let mut token_balance = self.get_balance_for(&user);
-        *token_balance.get_mut_amount_of(token) += amount;
-        self.balances.insert(user, token_balance);
+        self.slot_balances().add(user, token.slot(), amount);
     }
 
     /// Deducts tokens from the `balances` map of the contract. <br>
     /// Requires that the user has at least as many tokens as is being deducted.
     ///
+    /// Panicking version of [`TokenBalances::try_deduct_from_token_balance`].
+    ///
     /// ### Parameters:
     ///
     /// * `user`: [`Address`] - A reference to the user to deduct `amount` from.
@@ -205,27 +339,47 @@ impl TokenBalances {
         token: DepositToken,
         amount: TokenAmount,
     ) {
-        let mut user_balances = self.get_balance_for(&user);
-
-        let token_balance = user_balances.get_amount_of(token);
-
-        *user_balances.get_mut_amount_of(token) =
-            token_balance.checked_sub(amount).unwrap_or_else(|| {
-                panic!(
-                    "Insufficient {:?} deposit: {}/{}",
-                    token, token_balance, amount
-                )
-            });
-
-        if user_balances.user_has_no_tokens() {
-            self.balances.remove(&user);
-        } else {
-            self.balances.insert(user, user_balances);
+        if let Err(err) = self.try_deduct_from_token_balance(user, token, amount) {
+            panic!(
+                "Insufficient {:?} deposit: {}/{}",
+                err.token, err.balance, err.requested
+            )
         }
     }
 
+    /// Deducts tokens from the `balances` map of the contract, if the user has at least as many
+    /// tokens as is being deducted. The balances are left unchanged when the deduction fails.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `user`: [`Address`] - A reference to the user to deduct `amount` from.
+    ///
+    /// * `token`: [`DepositToken`] - The token to subtract from.
+    ///
+    /// * `amount`: [`TokenAmount`] - The amount to subtract.
+    ///
+    /// # Returns
+    /// [`InsufficientBalance`] describing the shortfall if the user does not have enough tokens.
+    pub fn try_deduct_from_token_balance(
+        &mut self,
+        user: Address,
+        token: DepositToken,
+        amount: TokenAmount,
+    ) -> Result<(), InsufficientBalance> {
+        self.slot_balances()
+            .try_deduct(user, token.slot(), amount)
+            .map_err(|balance| InsufficientBalance {
+                token,
+                balance,
+                requested: amount,
+            })
+    }
+
     /// Moves internal tokens from the `from`-address to the `to`-address.
     ///
+    /// Panics if `from` does not have enough tokens. See [`TokenBalances::move_tokens_checked`]
+    /// for a version that also validates the balance of `to`.
+    ///
     /// ### Parameters:
     ///
     /// * `from`: [`Address`] - The address of the transferring party.
@@ -247,6 +401,54 @@ impl TokenBalances {
         self.add_to_token_balance(to, moved_token, amount);
     }
 
+    /// Moves internal tokens from the `from`-address to the `to`-address, if `from` has enough
+    /// tokens and the balance of `to` won't overflow. Both are validated before any balance is
+    /// changed, such that the balances are left unchanged when the move fails.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `from`: [`Address`] - The address of the transferring party.
+    ///
+    /// * `to`: [`Address`] - The address of the receiving party.
+    ///
+    /// * `moved_token`: [`DepositToken`] - The token being transferred.
+    ///
+    /// * `amount`: [`TokenAmount`] - The amount being transferred.
+    ///
+    /// # Returns
+    /// [`MoveTokensError`] describing which party prevented the move.
+    pub fn move_tokens_checked(
+        &mut self,
+        from: Address,
+        to: Address,
+        moved_token: DepositToken,
+        amount: TokenAmount,
+    ) -> Result<(), MoveTokensError> {
+        let from_balance = self.get_balance_for(&from).get_amount_of(moved_token);
+        if from_balance < amount {
+            return Err(MoveTokensError::InsufficientBalance(InsufficientBalance {
+                token: moved_token,
+                balance: from_balance,
+                requested: amount,
+            }));
+        }
+
+        // Moving to oneself restores the deducted amount, and can never overflow.
+        if from != to {
+            let to_balance = self.get_balance_for(&to).get_amount_of(moved_token);
+            if to_balance.checked_add(amount).is_none() {
+                return Err(MoveTokensError::DestinationOverflow {
+                    token: moved_token,
+                    balance: to_balance,
+                    requested: amount,
+                });
+            }
+        }
+
+        self.move_tokens(from, to, moved_token, amount);
+        Ok(())
+    }
+
     /// Retrieves a copy of the token balance that matches `user`.
     ///
     /// ### Parameters:
@@ -259,6 +461,26 @@ impl TokenBalances {
         self.balances.get(user).unwrap_or(EMPTY_BALANCE)
     }
 
+    /// The balances of all users, indexed by [`DepositToken::slot`].
+    fn slot_balances(&mut self) -> SlotBalanceMap<'_, TokenBalance> {
+        SlotBalanceMap {
+            balances: &mut self.balances,
+            empty: EMPTY_BALANCE,
+        }
+    }
+
+    /// Iterates over all users with a non-zero amount of any token, in address order.
+    ///
+    /// The balances are read lazily from the underlying map while iterating.
+    ///
+    /// # Returns
+    /// An iterator over pairs of user [`Address`] and a copy of their [`TokenBalance`].
+    pub fn non_zero_balances(&self) -> impl Iterator<Item = (Address, TokenBalance)> + '_ {
+        self.balances
+            .iter()
+            .filter(|(_, balance)| !balance.user_has_no_tokens())
+    }
+
     /// Retrieves a pair of tokens with the `token_in_token_address` being the "token_in"-token
     /// and the remaining token being "token_out". <br>
     /// Requires that `token_in_token_address` matches the contract's pools.
@@ -293,6 +515,186 @@ impl TokenBalances {
     }
 }
 
+/// Index of a token in [`MultiTokenBalances`].
+pub type TokenSlot = u8;
+
+/// Keeps track of how much of each token slot a user owns within the scope of the contract.
+#[derive(ReadWriteState, CreateTypeSpec, Debug, PartialEq, Eq, Clone)]
+pub struct MultiTokenBalance {
+    /// The amount of each token that the user owns, indexed by [`TokenSlot`].
+    pub amounts: Vec<TokenAmount>,
+}
+
+impl MultiTokenBalance {
+    /// Creates an empty balance with the given number of slots.
+    fn empty(number_of_slots: usize) -> Self {
+        Self {
+            amounts: vec![0; number_of_slots],
+        }
+    }
+
+    /// Retrieves a copy of the amount in the given slot.
+    pub fn get_amount_of(&self, slot: TokenSlot) -> TokenAmount {
+        self.amount(slot)
+    }
+
+    /// Checks that the user has no tokens.
+    pub fn user_has_no_tokens(&self) -> bool {
+        self.amounts.iter().all(|amount| *amount == 0)
+    }
+}
+
+impl SlotBalance for MultiTokenBalance {
+    fn amount(&self, slot: TokenSlot) -> TokenAmount {
+        self.amounts[slot as usize]
+    }
+
+    fn amount_mut(&mut self, slot: TokenSlot) -> &mut TokenAmount {
+        &mut self.amounts[slot as usize]
+    }
+
+    fn is_empty(&self) -> bool {
+        self.user_has_no_tokens()
+    }
+}
+
+/// Generalized token balance structure for an arbitrary small set of tokens.
+///
+/// Works like [`TokenBalances`], but tokens are identified by their [`TokenSlot`] rather than by a
+/// [`DepositToken`]. The two and three token layouts of [`TokenBalances`] can be created using
+/// [`MultiTokenBalances::for_pair`] and [`MultiTokenBalances::for_pair_with_liquidity`].
+#[derive(ReadWriteState, CreateTypeSpec, Debug)]
+pub struct MultiTokenBalances {
+    /// The addresses of the tokens, indexed by [`TokenSlot`].
+    pub token_addresses: Vec<Address>,
+    /// The map containing all token balances of all users and the contract itself.
+    balances: Map<Address, MultiTokenBalance>,
+}
+
+impl MultiTokenBalances {
+    /// Creates new token balances structure with a slot for each of the given token addresses.
+    ///
+    /// Checks whether the state is valid, if not it will return an error reason.
+    pub fn new(token_addresses: Vec<Address>) -> Result<Self, &'static str> {
+        if token_addresses.is_empty() || token_addresses.len() > TokenSlot::MAX as usize + 1 {
+            return Result::Err("Number of tokens must be between 1 and 256");
+        }
+        for (idx, token_address) in token_addresses.iter().enumerate() {
+            if token_address.address_type() == AddressType::Account {
+                return Result::Err("DepositToken addresses must be contract addresses");
+            }
+            if token_addresses[..idx].contains(token_address) {
+                return Result::Err("DepositToken addresses must not be the same contract");
+            }
+        }
+        Result::Ok(Self {
+            token_addresses,
+            balances: Map::new(),
+        })
+    }
+
+    /// Creates new token balances structure with slots for token A and B, using the slots given
+    /// by [`DepositToken::slot`].
+    pub fn for_pair(
+        token_a_address: Address,
+        token_b_address: Address,
+    ) -> Result<Self, &'static str> {
+        Self::new(vec![token_a_address, token_b_address])
+    }
+
+    /// Creates new token balances structure with slots for token A, B and the liquidity token,
+    /// using the slots given by [`DepositToken::slot`].
+    pub fn for_pair_with_liquidity(
+        token_lp_address: Address,
+        token_a_address: Address,
+        token_b_address: Address,
+    ) -> Result<Self, &'static str> {
+        Self::new(vec![token_a_address, token_b_address, token_lp_address])
+    }
+
+    /// Number of token slots.
+    pub fn number_of_slots(&self) -> usize {
+        self.token_addresses.len()
+    }
+
+    /// Finds the slot of the given token address, if any.
+    pub fn slot_for_address(&self, token_address: &Address) -> Option<TokenSlot> {
+        self.token_addresses
+            .iter()
+            .position(|address| address == token_address)
+            .map(|idx| idx as TokenSlot)
+    }
+
+    /// Adds tokens to the given slot of the user. <br>
+    /// If the user isn't already present, creates an entry with an empty balance.
+    pub fn add_to_token_balance(&mut self, user: Address, slot: TokenSlot, amount: TokenAmount) {
+        self.assert_valid_slot(slot);
+        self.slot_balances().add(user, slot, amount);
+    }
+
+    /// Deducts tokens from the given slot of the user. <br>
+    /// Requires that the user has at least as many tokens as is being deducted.
+    pub fn deduct_from_token_balance(
+        &mut self,
+        user: Address,
+        slot: TokenSlot,
+        amount: TokenAmount,
+    ) {
+        self.assert_valid_slot(slot);
+        if let Err(balance) = self.slot_balances().try_deduct(user, slot, amount) {
+            panic!(
+                "Insufficient deposit in token slot {}: {}/{}",
+                slot, balance, amount
+            )
+        }
+    }
+
+    /// Moves internal tokens of the given slot from the `from`-address to the `to`-address.
+    pub fn move_tokens(
+        &mut self,
+        from: Address,
+        to: Address,
+        slot: TokenSlot,
+        amount: TokenAmount,
+    ) {
+        self.deduct_from_token_balance(from, slot, amount);
+        self.add_to_token_balance(to, slot, amount);
+    }
+
+    /// Retrieves a copy of the token balance that matches `user`.
+    pub fn get_balance_for(&self, user: &Address) -> MultiTokenBalance {
+        self.balances
+            .get(user)
+            .unwrap_or_else(|| MultiTokenBalance::empty(self.number_of_slots()))
+    }
+
+    /// Iterates over all users with a non-zero amount of any token, in address order.
+    pub fn non_zero_balances(&self) -> impl Iterator<Item = (Address, MultiTokenBalance)> + '_ {
+        self.balances
+            .iter()
+            .filter(|(_, balance)| !balance.user_has_no_tokens())
+    }
+
+    /// The balances of all users, indexed by [`TokenSlot`].
+    fn slot_balances(&mut self) -> SlotBalanceMap<'_, MultiTokenBalance> {
+        let empty = MultiTokenBalance::empty(self.number_of_slots());
+        SlotBalanceMap {
+            balances: &mut self.balances,
+            empty,
+        }
+    }
+
+    /// Checks that the given slot exists.
+    fn assert_valid_slot(&self, slot: TokenSlot) {
+        assert!(
+            (slot as usize) < self.number_of_slots(),
+            "Unknown token slot {}. Contract only supports {} tokens",
+            slot,
+            self.number_of_slots()
+        );
+    }
+}
+
 /// Tracks the from-to pairs for transfers, etc.
 #[non_exhaustive]
 #[derive(ReadWriteState, CreateTypeSpec, Debug)]
@@ -316,3 +718,272 @@ impl TokensInOut {
         token_out: DepositToken::A,
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{account, contract};
+
+    fn token_balances() -> TokenBalances {
+        TokenBalances::new(contract(1), contract(2), contract(3)).unwrap()
+    }
+
+    #[test]
+    pub fn try_deduct_exact_balance() {
+        let mut balances = token_balances();
+        let user = contract(10);
+        balances.add_to_token_balance(user, DepositToken::A, 100);
+
+        assert_eq!(
+            balances.try_deduct_from_token_balance(user, DepositToken::A, 100),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance_for(&user), EMPTY_BALANCE);
+    }
+
+    #[test]
+    pub fn try_deduct_over_draw() {
+        let mut balances = token_balances();
+        let user = contract(10);
+        balances.add_to_token_balance(user, DepositToken::B, 30);
+
+        let err = balances
+            .try_deduct_from_token_balance(user, DepositToken::B, 100)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            InsufficientBalance {
+                token: DepositToken::B,
+                balance: 30,
+                requested: 100,
+            }
+        );
+        assert_eq!(err.shortfall(), 70);
+        assert_eq!(balances.get_balance_for(&user).b_tokens, 30);
+    }
+
+    #[test]
+    pub fn try_deduct_zero_amount() {
+        let mut balances = token_balances();
+        let user = contract(10);
+
+        assert_eq!(
+            balances.try_deduct_from_token_balance(user, DepositToken::A, 0),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance_for(&user), EMPTY_BALANCE);
+
+        balances.add_to_token_balance(user, DepositToken::A, 5);
+        assert_eq!(
+            balances.try_deduct_from_token_balance(user, DepositToken::A, 0),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance_for(&user).a_tokens, 5);
+    }
+
+    #[test]
+    pub fn non_zero_balances_skips_empty_accounts() {
+        let mut balances = token_balances();
+        balances.add_to_token_balance(contract(10), DepositToken::A, 0);
+        balances.add_to_token_balance(contract(11), DepositToken::LIQUIDITY, 3);
+        balances.add_to_token_balance(contract(12), DepositToken::B, 0);
+
+        let non_zero: Vec<(Address, TokenBalance)> = balances.non_zero_balances().collect();
+        assert_eq!(
+            non_zero,
+            vec![(
+                contract(11),
+                TokenBalance {
+                    a_tokens: 0,
+                    b_tokens: 0,
+                    liquidity_tokens: 3,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    pub fn non_zero_balances_reflects_moves() {
+        let mut balances = token_balances();
+        balances.add_to_token_balance(contract(10), DepositToken::A, 100);
+        balances.move_tokens(contract(10), contract(11), DepositToken::A, 40);
+
+        let users: Vec<(Address, TokenAmount)> = balances
+            .non_zero_balances()
+            .map(|(user, balance)| (user, balance.a_tokens))
+            .collect();
+        assert_eq!(users, vec![(contract(10), 60), (contract(11), 40)]);
+
+        balances.move_tokens(contract(10), contract(11), DepositToken::A, 60);
+        let users: Vec<Address> = balances.non_zero_balances().map(|(user, _)| user).collect();
+        assert_eq!(users, vec![contract(11)]);
+    }
+
+    #[test]
+    pub fn move_tokens_checked_moves_tokens() {
+        let mut balances = token_balances();
+        balances.add_to_token_balance(contract(10), DepositToken::A, 100);
+
+        assert_eq!(
+            balances.move_tokens_checked(contract(10), contract(11), DepositToken::A, 40),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance_for(&contract(10)).a_tokens, 60);
+        assert_eq!(balances.get_balance_for(&contract(11)).a_tokens, 40);
+    }
+
+    #[test]
+    pub fn move_tokens_checked_insufficient_balance() {
+        let mut balances = token_balances();
+        balances.add_to_token_balance(contract(10), DepositToken::B, 30);
+
+        assert_eq!(
+            balances.move_tokens_checked(contract(10), contract(11), DepositToken::B, 100),
+            Err(MoveTokensError::InsufficientBalance(InsufficientBalance {
+                token: DepositToken::B,
+                balance: 30,
+                requested: 100,
+            }))
+        );
+        assert_eq!(balances.get_balance_for(&contract(10)).b_tokens, 30);
+        assert_eq!(balances.get_balance_for(&contract(11)), EMPTY_BALANCE);
+    }
+
+    #[test]
+    pub fn move_tokens_checked_destination_overflow() {
+        let mut balances = token_balances();
+        balances.add_to_token_balance(contract(10), DepositToken::A, 100);
+        balances.add_to_token_balance(contract(11), DepositToken::A, TokenAmount::MAX - 50);
+
+        assert_eq!(
+            balances.move_tokens_checked(contract(10), contract(11), DepositToken::A, 100),
+            Err(MoveTokensError::DestinationOverflow {
+                token: DepositToken::A,
+                balance: TokenAmount::MAX - 50,
+                requested: 100,
+            })
+        );
+        assert_eq!(balances.get_balance_for(&contract(10)).a_tokens, 100);
+        assert_eq!(
+            balances.get_balance_for(&contract(11)).a_tokens,
+            TokenAmount::MAX - 50
+        );
+
+        assert_eq!(
+            balances.move_tokens_checked(contract(10), contract(11), DepositToken::A, 50),
+            Ok(())
+        );
+        assert_eq!(balances.get_balance_for(&contract(10)).a_tokens, 50);
+        assert_eq!(
+            balances.get_balance_for(&contract(11)).a_tokens,
+            TokenAmount::MAX
+        );
+    }
+
+    #[test]
+    pub fn move_tokens_checked_to_self_at_max() {
+        let mut balances = token_balances();
+        balances.add_to_token_balance(contract(10), DepositToken::A, TokenAmount::MAX);
+
+        assert_eq!(
+            balances.move_tokens_checked(contract(10), contract(10), DepositToken::A, 1),
+            Ok(())
+        );
+        assert_eq!(
+            balances.get_balance_for(&contract(10)).a_tokens,
+            TokenAmount::MAX
+        );
+    }
+
+    #[test]
+    pub fn deposit_token_slots() {
+        for token in [DepositToken::A, DepositToken::B, DepositToken::LIQUIDITY] {
+            assert_eq!(DepositToken::from_slot(token.slot()), Some(token));
+        }
+        assert_eq!(DepositToken::from_slot(3), None);
+    }
+
+    #[test]
+    pub fn multi_token_balances_with_four_tokens() {
+        let tokens = vec![contract(2), contract(3), contract(4), contract(5)];
+        let mut balances = MultiTokenBalances::new(tokens).unwrap();
+        assert_eq!(balances.number_of_slots(), 4);
+        assert_eq!(balances.slot_for_address(&contract(5)), Some(3));
+        assert_eq!(balances.slot_for_address(&contract(6)), None);
+
+        let user = contract(10);
+        let pool = contract(11);
+        balances.add_to_token_balance(user, 0, 100);
+        balances.add_to_token_balance(user, 3, 50);
+        balances.move_tokens(user, pool, 3, 20);
+        balances.move_tokens(user, pool, 0, 100);
+
+        assert_eq!(balances.get_balance_for(&user).amounts, vec![0, 0, 0, 30]);
+        assert_eq!(balances.get_balance_for(&pool).amounts, vec![100, 0, 0, 20]);
+
+        balances.move_tokens(user, pool, 3, 30);
+        let users: Vec<Address> = balances.non_zero_balances().map(|(user, _)| user).collect();
+        assert_eq!(users, vec![pool]);
+        assert_eq!(balances.get_balance_for(&user).amounts, vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    pub fn multi_token_balances_pair_wrappers() {
+        let balances = MultiTokenBalances::for_pair(contract(2), contract(3)).unwrap();
+        assert_eq!(balances.token_addresses, vec![contract(2), contract(3)]);
+
+        let balances =
+            MultiTokenBalances::for_pair_with_liquidity(contract(1), contract(2), contract(3))
+                .unwrap();
+        assert_eq!(
+            balances.slot_for_address(&contract(1)),
+            Some(DepositToken::LIQUIDITY.slot())
+        );
+        assert_eq!(
+            balances.slot_for_address(&contract(3)),
+            Some(DepositToken::B.slot())
+        );
+    }
+
+    #[test]
+    pub fn multi_token_balances_invalid_tokens() {
+        assert_eq!(
+            MultiTokenBalances::new(vec![]).unwrap_err(),
+            "Number of tokens must be between 1 and 256"
+        );
+        assert_eq!(
+            MultiTokenBalances::new(vec![contract(2), contract(3), contract(2)]).unwrap_err(),
+            "DepositToken addresses must not be the same contract"
+        );
+        assert_eq!(
+            MultiTokenBalances::new(vec![account(2)]).unwrap_err(),
+            "DepositToken addresses must be contract addresses"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient deposit in token slot 2: 0/1")]
+    pub fn multi_token_balances_over_draw_panics() {
+        let mut balances =
+            MultiTokenBalances::new(vec![contract(2), contract(3), contract(4)]).unwrap();
+        balances.deduct_from_token_balance(contract(10), 2, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown token slot 4. Contract only supports 4 tokens")]
+    pub fn multi_token_balances_unknown_slot_panics() {
+        let mut balances =
+            MultiTokenBalances::new(vec![contract(2), contract(3), contract(4), contract(5)])
+                .unwrap();
+        balances.add_to_token_balance(contract(10), 4, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient TokenA deposit: 1/2")]
+    pub fn deduct_over_draw_panics() {
+        let mut balances = token_balances();
+        let user = contract(10);
+        balances.add_to_token_balance(user, DepositToken::A, 1);
+        balances.deduct_from_token_balance(user, DepositToken::A, 2);
+    }
+}
@@ -5,7 +5,7 @@
 extern crate pbc_contract_codegen;
 
 use create_type_spec_derive::CreateTypeSpec;
-use pbc_contract_common::address::Address;
+use pbc_contract_common::address::{Address, Shortname};
 use pbc_contract_common::avl_tree_map::AvlTreeMap;
 use pbc_contract_common::context::{CallbackContext, ContractContext};
 use pbc_contract_common::events::EventGroup;
@@ -14,11 +14,21 @@ use read_write_rpc_derive::{ReadRPC, WriteRPC};
 use read_write_state_derive::ReadWriteState;
 
 use defi_common::deploy;
+use defi_common::deploy::DeploymentRegistry;
+use defi_common::events;
 use defi_common::math::assert_is_per_mille;
 use defi_common::permission::Permission;
 
+#[cfg(test)]
+mod tests;
+
+/// [`Shortname`] of the [`swap_binary_rollback_event()`] action.
+const SHORTNAME_SWAP_BINARY_ROLLBACK_EVENT: Shortname = Shortname::from_u32(0x12);
+
 /// Pair of token contract addresses.
-#[derive(ReadRPC, WriteRPC, ReadWriteState, CreateTypeSpec)]
+#[derive(
+    ReadRPC, WriteRPC, ReadWriteState, CreateTypeSpec, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
 pub struct TokenPair {
     /// The first token address.
     pub token_a_address: Address,
@@ -26,6 +36,24 @@ pub struct TokenPair {
     pub token_b_address: Address,
 }
 
+impl TokenPair {
+    /// Creates a new [`TokenPair`] with the two token addresses sorted, such that the pairs
+    /// `(A, B)` and `(B, A)` produce the same [`TokenPair`].
+    pub fn normalized(token_a_address: Address, token_b_address: Address) -> Self {
+        if token_a_address <= token_b_address {
+            TokenPair {
+                token_a_address,
+                token_b_address,
+            }
+        } else {
+            TokenPair {
+                token_a_address: token_b_address,
+                token_b_address: token_a_address,
+            }
+        }
+    }
+}
+
 /// Information about a deployed swap contract.
 #[derive(ReadWriteState, WriteRPC, CreateTypeSpec)]
 pub struct SwapContractInfo {
@@ -39,6 +67,42 @@ pub struct SwapContractInfo {
     pub supports_locks: bool,
 }
 
+/// Lock settings of a swap lock contract deployed by the factory.
+#[derive(ReadRPC, WriteRPC, ReadWriteState, CreateTypeSpec)]
+pub struct LockSettings {
+    /// Who is permitted to acquire locks at the swap contract.
+    pub lock_permission: Permission,
+    /// How long acquired locks are held before they are released automatically. `None` if locks
+    /// never expire.
+    pub lock_duration_millis: Option<i64>,
+}
+
+/// Information about a swap contract that failed to deploy, such that the deployment can be
+/// retried with [`retry_deploy`].
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct FailedDeployment {
+    /// The [`TokenPair`] of the swap contract.
+    pub token_pair: TokenPair,
+    /// Version of the contract binary that failed to deploy.
+    pub contract_version: deploy::ContractVersion,
+    /// Swap fee per swap, between 0‰ and 1000‰.
+    pub swap_fee_per_mille: u16,
+    /// The lock settings of the swap contract, if the contract supports locks.
+    pub lock_settings: Option<LockSettings>,
+}
+
+/// Record of a [forced replacement](force_set_swap_binary) of the swap contract binary, kept for
+/// auditability.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct SwapBinaryOverride {
+    /// The user that replaced the binary.
+    pub overridden_by: Address,
+    /// Version of the binary before the replacement, if any binary was set.
+    pub previous_version: Option<deploy::ContractVersion>,
+    /// Version of the binary after the replacement.
+    pub new_version: deploy::ContractVersion,
+}
+
 /// Contract state.
 #[state]
 pub struct SwapFactoryState {
@@ -50,8 +114,17 @@ pub struct SwapFactoryState {
     pub permission_delist_swap: Permission,
     /// Deployed swap contracts.
     pub swap_contracts: AvlTreeMap<Address, SwapContractInfo>,
+    /// Index of [deployed swap contracts](SwapFactoryState::swap_contracts) by their token pair.
+    /// Keys are [normalized](TokenPair::normalized), so the order of the tokens does not matter.
+    pub swap_contracts_by_pair: AvlTreeMap<TokenPair, Address>,
+    /// Swap contracts that failed to deploy, by the address they were meant to be deployed at.
+    pub failed_deployments: AvlTreeMap<Address, FailedDeployment>,
     /// Deployment information for new swap contract.
     pub swap_contract_binary: Option<deploy::DeployableContract>,
+    /// Every [forced replacement](force_set_swap_binary) of the swap contract binary, oldest first.
+    pub swap_binary_overrides: Vec<SwapBinaryOverride>,
+    /// Whether the factory is paused. No new swap contracts can be deployed while paused.
+    pub paused: bool,
 }
 
 /// Initial action to create the initial state.
@@ -76,7 +149,88 @@ pub fn initialize(
         permission_deploy_swap,
         permission_delist_swap,
         swap_contracts: AvlTreeMap::new(),
+        swap_contracts_by_pair: AvlTreeMap::new(),
+        failed_deployments: AvlTreeMap::new(),
         swap_contract_binary: None,
+        swap_binary_overrides: vec![],
+        paused: false,
+    }
+}
+
+impl SwapFactoryState {
+    /// Finds the swap contract deployed for the given pair of tokens, if any.
+    ///
+    /// The order of the given tokens does not matter.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `token_a_address`: [`Address`], one of the tokens of the pair.
+    /// * `token_b_address`: [`Address`], the other token of the pair.
+    ///
+    /// ### Returns:
+    ///
+    /// The address of the swap contract for the pair, or [`None`] if no such contract is listed.
+    pub fn find_swap_for_pair(
+        &self,
+        token_a_address: Address,
+        token_b_address: Address,
+    ) -> Option<Address> {
+        self.swap_contracts_by_pair
+            .get(&TokenPair::normalized(token_a_address, token_b_address))
+    }
+
+    /// Asserts that no listed swap contract exists for the given [`TokenPair`], independently of
+    /// the order of the tokens. Delisted swap contracts are not considered.
+    fn assert_pair_not_listed(&self, token_pair: &TokenPair) {
+        if let Some(existing_swap) =
+            self.find_swap_for_pair(token_pair.token_a_address, token_pair.token_b_address)
+        {
+            panic!(
+                "A swap contract already exists for the given token pair: {:?}",
+                existing_swap
+            );
+        }
+    }
+
+    /// Adds a swap contract to the [swap directory](SwapFactoryState::swap_contracts), and indexes
+    /// it by its token pair.
+    fn add_swap_contract(&mut self, swap_address: Address, swap_contract_info: SwapContractInfo) {
+        let pair = TokenPair::normalized(
+            swap_contract_info.token_pair.token_a_address,
+            swap_contract_info.token_pair.token_b_address,
+        );
+        self.swap_contracts_by_pair.insert(pair, swap_address);
+        self.swap_contracts.insert(swap_address, swap_contract_info);
+    }
+
+    /// Removes a swap contract from the [swap directory](SwapFactoryState::swap_contracts), and
+    /// from the pair index. Unknown contracts are ignored.
+    fn remove_swap_contract(&mut self, swap_address: &Address) {
+        if let Some(swap_contract_info) = self.swap_contracts.get(swap_address) {
+            let pair = TokenPair::normalized(
+                swap_contract_info.token_pair.token_a_address,
+                swap_contract_info.token_pair.token_b_address,
+            );
+            if self.swap_contracts_by_pair.get(&pair) == Some(*swap_address) {
+                self.swap_contracts_by_pair.remove(&pair);
+            }
+            self.swap_contracts.remove(swap_address);
+        }
+    }
+}
+
+impl DeploymentRegistry for SwapFactoryState {
+    /// Marks the swap contract as [successfully deployed](SwapContractInfo::successfully_deployed).
+    fn mark_deployed(&mut self, deployed_address: &Address) {
+        let mut swap_contract_info = self.swap_contracts.get(deployed_address).unwrap();
+        swap_contract_info.successfully_deployed = true;
+        self.swap_contracts
+            .insert(*deployed_address, swap_contract_info);
+    }
+
+    /// Removes the swap contract from the [swap directory](SwapFactoryState::swap_contracts).
+    fn remove_failed_deployment(&mut self, deployed_address: &Address) {
+        self.remove_swap_contract(deployed_address);
     }
 }
 
@@ -108,12 +262,141 @@ pub fn update_swap_binary(
     state
 }
 
+/// Action for replacing swap contract binary, without requiring the version to be increasing.
+///
+/// Allows rolling back to a known-good binary, for example after a faulty release. Only people
+/// with [`SwapFactoryState::permission_update_swap`] can replace the binary. The replacement is
+/// recorded in [`SwapFactoryState::swap_binary_overrides`]. If the version goes backwards, an event
+/// is sent to [`swap_binary_rollback_event`].
+///
+/// ### Parameters:
+///
+/// * `ctx`: [`ContractContext`], the context of the action call.
+/// * `state`: [`SwapFactoryState`], the state before the call.
+/// * `swap_contract_binary`: [`Vec<u8>`], wasm bytes of the swap contract.
+/// * `swap_contract_abi`: [`Vec<u8>`], abi bytes of the swap contract.
+/// * `swap_contract_version`: [`deploy::ContractVersion`], version of the swap contract.
+///
+/// ### Returns:
+///
+/// The new state of type [`SwapFactoryState`].
+#[action(shortname = 0x11)]
+pub fn force_set_swap_binary(
+    ctx: ContractContext,
+    mut state: SwapFactoryState,
+    swap_contract_binary: Vec<u8>,
+    swap_contract_abi: Vec<u8>,
+    swap_contract_version: deploy::ContractVersion,
+) -> (SwapFactoryState, Vec<EventGroup>) {
+    state
+        .permission_update_swap
+        .assert_permission_for(&ctx.sender, "update swap");
+    let previous_version = state
+        .swap_contract_binary
+        .as_ref()
+        .map(|binary| binary.version);
+    state.swap_contract_binary = Some(deploy::DeployableContract::new(
+        swap_contract_binary,
+        swap_contract_abi,
+        swap_contract_version,
+    ));
+    state.swap_binary_overrides.push(SwapBinaryOverride {
+        overridden_by: ctx.sender,
+        previous_version,
+        new_version: swap_contract_version,
+    });
+
+    let mut events = vec![];
+    if let Some(previous_version) = previous_version {
+        if swap_contract_version < previous_version {
+            let mut event_group = EventGroup::builder();
+            event_group
+                .call(ctx.contract_address, SHORTNAME_SWAP_BINARY_ROLLBACK_EVENT)
+                .argument(previous_version)
+                .argument(swap_contract_version)
+                .with_cost(events::GAS_COST_EVENT)
+                .done();
+            events.push(event_group.build());
+        }
+    }
+    (state, events)
+}
+
+/// Event noting that the swap contract binary was rolled back to an older version by
+/// [`force_set_swap_binary`]. Can only be sent by the factory itself.
+///
+/// ### Parameters:
+///
+/// * `ctx`: [`ContractContext`], the context of the action call.
+/// * `state`: [`SwapFactoryState`], the state before the call.
+/// * `previous_version`: [`deploy::ContractVersion`], version of the binary before the rollback.
+/// * `new_version`: [`deploy::ContractVersion`], version of the binary after the rollback.
+///
+/// ### Returns:
+///
+/// The unchanged state of type [`SwapFactoryState`].
+#[action(shortname = 0x12)]
+pub fn swap_binary_rollback_event(
+    ctx: ContractContext,
+    state: SwapFactoryState,
+    _previous_version: deploy::ContractVersion,
+    _new_version: deploy::ContractVersion,
+) -> SwapFactoryState {
+    events::assert_event_sent_by_contract(
+        &ctx,
+        "Rollback events can only be sent by the swap factory itself.",
+    );
+    state
+}
+
+/// Action for pausing the factory, halting all new deployments of swap contracts, for example
+/// during a security incident. Only people with [`SwapFactoryState::permission_update_swap`] can
+/// pause the factory. Delisting swap contracts is still possible while paused.
+///
+/// ### Parameters:
+///
+/// * `ctx`: [`ContractContext`], the context of the action call.
+/// * `state`: [`SwapFactoryState`], the state before the call.
+///
+/// ### Returns:
+///
+/// The new state of type [`SwapFactoryState`].
+#[action(shortname = 0x13)]
+pub fn pause(ctx: ContractContext, mut state: SwapFactoryState) -> SwapFactoryState {
+    state
+        .permission_update_swap
+        .assert_permission_for(&ctx.sender, "update swap");
+    state.paused = true;
+    state
+}
+
+/// Action for unpausing the factory, such that new swap contracts can be deployed again. Only
+/// people with [`SwapFactoryState::permission_update_swap`] can unpause the factory.
+///
+/// ### Parameters:
+///
+/// * `ctx`: [`ContractContext`], the context of the action call.
+/// * `state`: [`SwapFactoryState`], the state before the call.
+///
+/// ### Returns:
+///
+/// The new state of type [`SwapFactoryState`].
+#[action(shortname = 0x14)]
+pub fn unpause(ctx: ContractContext, mut state: SwapFactoryState) -> SwapFactoryState {
+    state
+        .permission_update_swap
+        .assert_permission_for(&ctx.sender, "update swap");
+    state.paused = false;
+    state
+}
+
 /// Action to deploy a new swap contract with given [`TokenPair`].
 ///
 /// The address of the new swap contract is computed from the original transaction hash. Only
-/// people with [`SwapFactoryState::permission_update_swap`] can add new swap contracts. and the
-/// [`TokenPair`] has to be unique.  This creates an event to the public deploy contract as well as
-/// creates a callback to [`deploy_swap_contract_callback`].
+/// people with [`SwapFactoryState::permission_deploy_swap`] can add new swap contracts, and the
+/// [`TokenPair`] has to be unique among listed swap contracts, independently of token order.
+/// The two tokens of the pair must be different contracts. This creates an event to the public
+/// deploy contract as well as creates a callback to [`deploy_swap_contract_callback`].
 ///
 /// ### Parameters:
 ///
@@ -128,59 +411,23 @@ pub fn update_swap_binary(
 #[action(shortname = 0x01)]
 pub fn deploy_swap_contract(
     ctx: ContractContext,
-    mut state: SwapFactoryState,
+    state: SwapFactoryState,
     token_pair: TokenPair,
     swap_fee_per_mille: u16,
 ) -> (SwapFactoryState, Vec<EventGroup>) {
     state
         .permission_deploy_swap
         .assert_permission_for(&ctx.sender, "deploy swap");
-    assert_is_per_mille(swap_fee_per_mille);
-
-    let swap_contract_binary = state
-        .swap_contract_binary
-        .as_ref()
-        .expect("Cannot deploy swap contract, when swap contract binary has not be set!");
-
-    let mut event_group = EventGroup::builder();
-
-    let init_msg = SwapContractInitMsg {
-        token_a_address: token_pair.token_a_address,
-        token_b_address: token_pair.token_b_address,
-        swap_fee_per_mille,
-    };
-
-    let contract_address = deploy::deploy_contract(
-        swap_contract_binary,
-        &mut event_group,
-        init_msg.to_init_bytes(),
-        &ctx,
-    );
-
-    state.swap_contracts.insert(
-        contract_address,
-        SwapContractInfo {
-            token_pair,
-            contract_version: swap_contract_binary.version,
-            successfully_deployed: false,
-            supports_locks: false,
-        },
-    );
-
-    event_group
-        .with_callback_rpc(deploy_swap_contract_callback::rpc(contract_address))
-        .with_cost(1000)
-        .done();
-
-    (state, vec![event_group.build()])
+    deploy_swap(&ctx, state, token_pair, swap_fee_per_mille, None)
 }
 
 /// Action to deploy a new swap lock contract with given [`TokenPair`], which supports locks.
 ///
 /// The address of the new swap contract is computed from the original transaction hash. Only
-/// people with [`SwapFactoryState::permission_update_swap`] can add new swap contracts. and the
-/// [`TokenPair`] has to be unique. This creates an event to the public deploy contract as well as
-/// creates a callback to [`deploy_swap_contract_callback`].
+/// people with [`SwapFactoryState::permission_deploy_swap`] can add new swap contracts, and the
+/// [`TokenPair`] has to be unique among listed swap contracts, independently of token order.
+/// The two tokens of the pair must be different contracts. This creates an event to the public
+/// deploy contract as well as creates a callback to [`deploy_swap_contract_callback`].
 ///
 /// ### Parameters:
 ///
@@ -189,6 +436,8 @@ pub fn deploy_swap_contract(
 /// * `token_pair`: [`TokenPair`], the [`TokenPair`] of the new swap contract.
 /// * `swap_fee_per_mille`: [`u16`], swap fee per swap, between 0‰ and 1000‰.
 /// * `lock_permission`: [`Permission`], who is permitted to acquired locks at the swap contract.
+/// * `lock_duration_millis`: [`Option<i64>`], how long acquired locks are held before they are
+///   released automatically. `None` if locks never expire.
 ///
 /// ### Returns:
 ///
@@ -196,15 +445,109 @@ pub fn deploy_swap_contract(
 #[action(shortname = 0x03)]
 pub fn deploy_swap_lock_contract(
     ctx: ContractContext,
-    mut state: SwapFactoryState,
+    state: SwapFactoryState,
     token_pair: TokenPair,
     swap_fee_per_mille: u16,
     lock_permission: Permission,
+    lock_duration_millis: Option<i64>,
 ) -> (SwapFactoryState, Vec<EventGroup>) {
     state
         .permission_deploy_swap
         .assert_permission_for(&ctx.sender, "deploy swap");
+    if let Some(lock_duration_millis) = lock_duration_millis {
+        assert!(
+            lock_duration_millis > 0,
+            "Lock duration must be positive, but was {} ms",
+            lock_duration_millis
+        );
+    }
+    deploy_swap(
+        &ctx,
+        state,
+        token_pair,
+        swap_fee_per_mille,
+        Some(LockSettings {
+            lock_permission,
+            lock_duration_millis,
+        }),
+    )
+}
+
+/// Action to retry a [failed deployment](SwapFactoryState::failed_deployments) of a swap contract.
+///
+/// The swap contract is deployed again with the same [`TokenPair`], swap fee and lock settings
+/// as the failed deployment, using the current
+/// [swap contract binary](SwapFactoryState::swap_contract_binary). The new swap contract will have
+/// a new address. Only people with [`SwapFactoryState::permission_deploy_swap`] can retry
+/// deployments.
+///
+/// ### Parameters:
+///
+/// * `ctx`: [`ContractContext`], the context of the action call.
+/// * `state`: [`SwapFactoryState`], the state before the call.
+/// * `address`: [`Address`], the address of the swap contract that failed to deploy.
+///
+/// ### Returns:
+///
+/// The new state of type [`SwapFactoryState`].
+#[action(shortname = 0x04)]
+pub fn retry_deploy(
+    ctx: ContractContext,
+    mut state: SwapFactoryState,
+    address: Address,
+) -> (SwapFactoryState, Vec<EventGroup>) {
+    state
+        .permission_deploy_swap
+        .assert_permission_for(&ctx.sender, "deploy swap");
+    let failed_deployment = state
+        .failed_deployments
+        .get(&address)
+        .expect("No failed deployment exists for the given address");
+    state.failed_deployments.remove(&address);
+    deploy_swap(
+        &ctx,
+        state,
+        failed_deployment.token_pair,
+        failed_deployment.swap_fee_per_mille,
+        failed_deployment.lock_settings,
+    )
+}
+
+/// Deploys a new swap contract with given [`TokenPair`], and adds it to the
+/// [swap directory](SwapFactoryState::swap_contracts).
+///
+/// If `lock_settings` are given, the swap contract is deployed as a swap lock contract,
+/// supporting locks. Fails if the factory is [paused](SwapFactoryState::paused).
+///
+/// ### Parameters:
+///
+/// * `ctx`: [`ContractContext`], the context of the action call.
+/// * `state`: [`SwapFactoryState`], the state before the call.
+/// * `token_pair`: [`TokenPair`], the [`TokenPair`] of the new swap contract.
+/// * `swap_fee_per_mille`: [`u16`], swap fee per swap, between 0‰ and 1000‰.
+/// * `lock_settings`: [`Option<LockSettings>`], the lock settings of the swap contract, if the
+///   swap contract supports locks.
+///
+/// ### Returns:
+///
+/// The new state of type [`SwapFactoryState`].
+fn deploy_swap(
+    ctx: &ContractContext,
+    mut state: SwapFactoryState,
+    token_pair: TokenPair,
+    swap_fee_per_mille: u16,
+    lock_settings: Option<LockSettings>,
+) -> (SwapFactoryState, Vec<EventGroup>) {
+    assert!(
+        !state.paused,
+        "Swap factory paused. No new swap contracts can be deployed."
+    );
     assert_is_per_mille(swap_fee_per_mille);
+    assert!(
+        token_pair.token_a_address != token_pair.token_b_address,
+        "Tokens A and B must not be the same contract"
+    );
+    state.assert_pair_not_listed(&token_pair);
 
     let swap_contract_binary = state
         .swap_contract_binary
@@ -220,28 +563,47 @@ pub fn deploy_swap_lock_contract(
     };
 
     let mut swap_init_bytes = swap_init_msg.to_init_bytes();
-    lock_permission.rpc_write_to(&mut swap_init_bytes).unwrap();
 
-    let contract_address = deploy::deploy_contract_specific_binder(
-        swap_contract_binary,
-        &mut event_group,
-        swap_init_bytes,
-        &ctx,
-        4,
-    );
+    let contract_address = match &lock_settings {
+        None => {
+            deploy::deploy_contract(swap_contract_binary, &mut event_group, swap_init_bytes, ctx)
+        }
+        Some(lock_settings) => {
+            lock_settings
+                .lock_permission
+                .rpc_write_to(&mut swap_init_bytes)
+                .unwrap();
+            lock_settings
+                .lock_duration_millis
+                .rpc_write_to(&mut swap_init_bytes)
+                .unwrap();
+            deploy::deploy_contract_specific_binder(
+                swap_contract_binary,
+                &mut event_group,
+                swap_init_bytes,
+                ctx,
+                4,
+            )
+        }
+    };
 
-    state.swap_contracts.insert(
+    let contract_version = swap_contract_binary.version;
+    state.add_swap_contract(
         contract_address,
         SwapContractInfo {
             token_pair,
-            contract_version: swap_contract_binary.version,
+            contract_version,
             successfully_deployed: false,
-            supports_locks: true,
+            supports_locks: lock_settings.is_some(),
         },
     );
 
     event_group
-        .with_callback_rpc(deploy_swap_contract_callback::rpc(contract_address))
+        .with_callback_rpc(deploy_swap_contract_callback::rpc(
+            contract_address,
+            swap_fee_per_mille,
+            lock_settings,
+        ))
         .with_cost(1000)
         .done();
 
@@ -259,6 +621,9 @@ pub fn deploy_swap_lock_contract(
 /// * `callback_ctx`: [`CallbackContext`], the context of the callback.
 /// * `state`: [`SwapFactoryState`], the state before the call.
 /// * `swap_address`: [`Address`], the address of the the new swap contract.
+/// * `swap_fee_per_mille`: [`u16`], swap fee the swap contract was deployed with.
+/// * `lock_settings`: [`Option<LockSettings>`], lock settings the swap contract was deployed
+///   with, if any.
 ///
 /// ### Returns:
 /// The new state of type [`SwapFactoryState`].
@@ -268,15 +633,15 @@ pub fn deploy_swap_contract_callback(
     callback_ctx: CallbackContext,
     state: SwapFactoryState,
     swap_address: Address,
+    swap_fee_per_mille: u16,
+    lock_settings: Option<LockSettings>,
 ) -> (SwapFactoryState, Vec<EventGroup>) {
-    let mut event_group = EventGroup::builder();
-
-    event_group.ping(swap_address, None);
-    event_group
-        .with_callback_rpc(swap_contract_exists_callback::rpc(swap_address))
-        .done();
+    let event_group = deploy::ping_deployed_contract(
+        swap_address,
+        swap_contract_exists_callback::rpc(swap_address, swap_fee_per_mille, lock_settings),
+    );
 
-    (state, vec![event_group.build()])
+    (state, vec![event_group])
 }
 
 /// Callback invoked to check whether a swap contract has been deployed successfully. Triggered
@@ -284,7 +649,9 @@ pub fn deploy_swap_contract_callback(
 ///
 /// - If deployed correctly, it will have it's [`SwapContractInfo::successfully_deployed`] flag
 ///   enabled, marking that it is safe to use.
-/// - If deployment fails, the contract's entry will be removed.
+/// - If deployment fails, the contract's entry will be removed, and the deployment is recorded in
+///   [`SwapFactoryState::failed_deployments`], such that it can be retried using
+///   [`retry_deploy`].
 ///
 /// ### Parameters:
 ///
@@ -292,6 +659,9 @@ pub fn deploy_swap_contract_callback(
 /// * `callback_ctx`: [`CallbackContext`], the context of the callback.
 /// * `state`: [`SwapFactoryState`], the state before the call.
 /// * `swap_address`: [`Address`], the address of the the new swap contract.
+/// * `swap_fee_per_mille`: [`u16`], swap fee the swap contract was deployed with.
+/// * `lock_settings`: [`Option<LockSettings>`], lock settings the swap contract was deployed
+///   with, if any.
 ///
 /// ### Returns:
 /// The new state of type [`SwapFactoryState`].
@@ -301,20 +671,29 @@ pub fn swap_contract_exists_callback(
     callback_ctx: CallbackContext,
     mut state: SwapFactoryState,
     swap_address: Address,
+    swap_fee_per_mille: u16,
+    lock_settings: Option<LockSettings>,
 ) -> SwapFactoryState {
-    if !callback_ctx.results[0].succeeded {
-        state.swap_contracts.remove(&swap_address);
-    } else {
-        let mut swap_contract_info = state.swap_contracts.get(&swap_address).unwrap();
-        swap_contract_info.successfully_deployed = true;
-        state
-            .swap_contracts
-            .insert(swap_address, swap_contract_info);
+    let swap_contract_info = state.swap_contracts.get(&swap_address);
+    let deployed = deploy::confirm_deployment(&mut state, &callback_ctx, &swap_address);
+    if !deployed {
+        if let Some(swap_contract_info) = swap_contract_info {
+            state.failed_deployments.insert(
+                swap_address,
+                FailedDeployment {
+                    token_pair: swap_contract_info.token_pair,
+                    contract_version: swap_contract_info.contract_version,
+                    swap_fee_per_mille,
+                    lock_settings,
+                },
+            );
+        }
     }
     state
 }
 
 /// Action for removing a contract from the [swap directory](SwapFactoryState::swap_contracts).
+/// The contract is also removed from the [pair index](SwapFactoryState::swap_contracts_by_pair).
 ///
 /// ### Parameters
 ///
@@ -332,7 +711,7 @@ pub fn delist_swap_contract(
     state
         .permission_deploy_swap
         .assert_permission_for(&ctx.sender, "delist swap");
-    state.swap_contracts.remove(&address);
+    state.remove_swap_contract(&address);
     state
 }
 
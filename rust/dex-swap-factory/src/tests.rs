@@ -0,0 +1,123 @@
+use super::*;
+use defi_common::test_util::{account, context, contract, contract_address};
+
+fn empty_state() -> SwapFactoryState {
+    initialize(
+        context(account(1), 0),
+        Permission::Anybody {},
+        Permission::Anybody {},
+        Permission::Anybody {},
+    )
+}
+
+fn swap_info(token_a_address: Address, token_b_address: Address) -> SwapContractInfo {
+    SwapContractInfo {
+        contract_version: 1,
+        token_pair: TokenPair {
+            token_a_address,
+            token_b_address,
+        },
+        successfully_deployed: true,
+        supports_locks: false,
+    }
+}
+
+#[test]
+fn normalized_pair_is_independent_of_order() {
+    assert!(
+        TokenPair::normalized(contract(1), contract(2))
+            == TokenPair::normalized(contract(2), contract(1))
+    );
+}
+
+#[test]
+fn find_swap_for_pair_regardless_of_order() {
+    let mut state = empty_state();
+    state.add_swap_contract(contract(10), swap_info(contract(2), contract(1)));
+
+    assert_eq!(
+        state.find_swap_for_pair(contract(1), contract(2)),
+        Some(contract(10))
+    );
+    assert_eq!(
+        state.find_swap_for_pair(contract(2), contract(1)),
+        Some(contract(10))
+    );
+    assert_eq!(state.find_swap_for_pair(contract(1), contract(3)), None);
+}
+
+#[test]
+fn find_swap_for_pair_after_delisting() {
+    let mut state = empty_state();
+    state.add_swap_contract(contract(10), swap_info(contract(1), contract(2)));
+    state.add_swap_contract(contract(11), swap_info(contract(1), contract(3)));
+
+    state.remove_swap_contract(&contract(10));
+
+    assert_eq!(state.find_swap_for_pair(contract(1), contract(2)), None);
+    assert_eq!(state.find_swap_for_pair(contract(2), contract(1)), None);
+    assert_eq!(
+        state.find_swap_for_pair(contract(3), contract(1)),
+        Some(contract(11))
+    );
+}
+
+#[test]
+fn removing_unknown_contract_is_ignored() {
+    let mut state = empty_state();
+    state.add_swap_contract(contract(10), swap_info(contract(1), contract(2)));
+
+    state.remove_swap_contract(&contract(11));
+
+    assert_eq!(
+        state.find_swap_for_pair(contract(1), contract(2)),
+        Some(contract(10))
+    );
+}
+
+#[test]
+#[should_panic(expected = "A swap contract already exists for the given token pair")]
+fn listed_pair_cannot_be_deployed_again() {
+    let mut state = empty_state();
+    state.add_swap_contract(contract(10), swap_info(contract(1), contract(2)));
+
+    state.assert_pair_not_listed(&TokenPair {
+        token_a_address: contract(2),
+        token_b_address: contract(1),
+    });
+}
+
+#[test]
+fn delisted_pair_can_be_deployed_again() {
+    let mut state = empty_state();
+    state.add_swap_contract(contract(10), swap_info(contract(1), contract(2)));
+    state.remove_swap_contract(&contract(10));
+
+    state.assert_pair_not_listed(&TokenPair {
+        token_a_address: contract(1),
+        token_b_address: contract(2),
+    });
+}
+
+#[test]
+fn rolling_back_swap_binary_emits_event() {
+    let state = update_swap_binary(context(account(1), 0), empty_state(), vec![1], vec![2], 2);
+    let (state, events) = force_set_swap_binary(context(account(1), 0), state, vec![3], vec![4], 1);
+
+    let mut expected = EventGroup::builder();
+    expected
+        .call(contract_address(), SHORTNAME_SWAP_BINARY_ROLLBACK_EVENT)
+        .argument(2u64)
+        .argument(1u64)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+    assert_eq!(events, vec![expected.build()]);
+    assert_eq!(state.swap_binary_overrides.len(), 1);
+}
+
+#[test]
+fn upgrading_swap_binary_by_force_emits_no_event() {
+    let state = update_swap_binary(context(account(1), 0), empty_state(), vec![1], vec![2], 1);
+    let (_, events) = force_set_swap_binary(context(account(1), 0), state, vec![3], vec![4], 2);
+    assert!(events.is_empty());
+}
@@ -5,6 +5,7 @@ extern crate pbc_contract_codegen;
 
 use create_type_spec_derive::CreateTypeSpec;
 use defi_common::interact_mpc20;
+use defi_common::math::assert_is_per_mille;
 use defi_common::token_balances::{DepositToken, TokenBalances};
 use pbc_contract_common::address::Address;
 use pbc_contract_common::avl_tree_map::AvlTreeMap;
@@ -12,6 +13,10 @@ use pbc_contract_common::context::{CallbackContext, ContractContext};
 use pbc_contract_common::events::EventGroup;
 use read_write_rpc_derive::ReadWriteRPC;
 use read_write_state_derive::ReadWriteState;
+use std::cmp::Reverse;
+
+#[cfg(test)]
+mod tests;
 
 /// The ID of a limit order.
 #[derive(Copy, Clone, Debug, ReadWriteRPC, ReadWriteState, CreateTypeSpec)]
@@ -91,6 +96,11 @@ pub struct CancelationRequest {
     pub cancelation_id: u32,
 }
 
+/// The time after the end of a periodic auction, within which the auction is expected to be
+/// cleared. If it has not been cleared by then, the book is no longer frozen, and orders can be
+/// cancelled to get the tokens back.
+const AUCTION_CLEARING_GRACE_PERIOD_MILLIS: i64 = 60 * 60 * 1000;
+
 /// Received when selling. Also known as "base token".
 const CURRENCY_TOKEN: DepositToken = DepositToken::TokenA {};
 /// Received when buying.
@@ -110,8 +120,9 @@ fn token_from_address(state: &DoubleAuctionContractState, address: Address) -> D
 }
 
 /// Key for priority queue used for sorting limit orders.
-/// Assumes that entries in the AVL tree implementation are sorted by little endian representation,
-/// to ensure that orders are placed in an efficient lookup order.
+/// Entries in the AVL tree implementation are sorted by the lexicographic order of their serialized
+/// keys, so the price and id are stored in big endian representation, to ensure that orders are
+/// sorted by price first and by submission order within the same price.
 #[derive(Copy, Clone, Debug, ReadWriteRPC, ReadWriteState, CreateTypeSpec)]
 pub struct Priority {
     /// The raw key.
@@ -181,6 +192,316 @@ pub struct DoubleAuctionContractState {
     bids: AvlTreeMap<Priority, LimitOrder>,
     /// The asks that have been placed on this contract.
     asks: AvlTreeMap<Priority, LimitOrder>,
+    /// The number of open limit orders of each user. Users without open orders have no entry.
+    open_orders_per_user: AvlTreeMap<Address, u32>,
+    /// The maximum number of open limit orders a single user can have placed at any time.
+    pub max_open_orders_per_user: u32,
+    /// The minimum amount of tokens that a limit order must be placed for.
+    pub min_order_amount: TokenAmount,
+    /// The fee per mille deducted from the proceeds of the order that was already placed, when
+    /// it is met.
+    pub maker_fee_per_mille: u16,
+    /// The fee per mille deducted from the proceeds of the submitted order, when it meets placed
+    /// orders.
+    pub taker_fee_per_mille: u16,
+    /// The account that is credited with the maker and taker fees.
+    pub fee_collector: Address,
+    /// The account that can update the price numerator and denominator.
+    pub administrator: Address,
+    /// The block production time at which the periodic auction ends, after which no orders can be
+    /// submitted. If `None`, orders are matched continuously when submitted.
+    pub auction_end_millis: Option<i64>,
+    /// Whether the periodic auction has been cleared by `clear_auction`.
+    pub auction_cleared: bool,
+}
+
+impl DoubleAuctionContractState {
+    /// Adds an open limit order, counting it towards the open orders of its owner.
+    ///
+    /// Parameters:
+    ///
+    /// * `cancelation_request` The owner and cancelation ID of the order.
+    /// * `order` The limit order to add.
+    fn insert_open_order(&mut self, cancelation_request: CancelationRequest, order: LimitOrder) {
+        if self
+            .orders_by_cancelation_request
+            .get(&cancelation_request)
+            .is_none()
+        {
+            let open_orders = self.open_orders(cancelation_request.owner);
+            self.open_orders_per_user
+                .insert(cancelation_request.owner, open_orders + 1);
+        }
+        self.orders_by_cancelation_request
+            .insert(cancelation_request, order);
+    }
+
+    /// Removes an open limit order, such that it no longer counts towards the open orders of its
+    /// owner.
+    ///
+    /// Parameters:
+    ///
+    /// * `cancelation_request` The owner and cancelation ID of the order.
+    fn remove_open_order(&mut self, cancelation_request: &CancelationRequest) {
+        if self
+            .orders_by_cancelation_request
+            .get(cancelation_request)
+            .is_some()
+        {
+            let open_orders = self.open_orders(cancelation_request.owner) - 1;
+            if open_orders == 0 {
+                self.open_orders_per_user.remove(&cancelation_request.owner);
+            } else {
+                self.open_orders_per_user
+                    .insert(cancelation_request.owner, open_orders);
+            }
+        }
+        self.orders_by_cancelation_request
+            .remove(cancelation_request);
+    }
+
+    /// The number of open limit orders placed by the given owner.
+    ///
+    /// Parameters:
+    ///
+    /// * `owner` The owner of the limit orders.
+    ///
+    /// Returns:
+    ///
+    /// The number of open limit orders of the owner.
+    pub fn open_orders(&self, owner: Address) -> u32 {
+        self.open_orders_per_user.get(&owner).unwrap_or(0)
+    }
+
+    /// Moves the proceeds of a matched order from `from` to `to`, deducting the given fee and
+    /// crediting it to the fee collector.
+    ///
+    /// Parameters:
+    ///
+    /// * `from` The account paying the proceeds.
+    /// * `to` The account receiving the proceeds.
+    /// * `token` The token of the proceeds.
+    /// * `amount` The proceeds before the fee is deducted.
+    /// * `fee_per_mille` The fee per mille to deduct from the proceeds.
+    fn move_proceeds_with_fee(
+        &mut self,
+        from: Address,
+        to: Address,
+        token: DepositToken,
+        amount: TokenAmount,
+        fee_per_mille: u16,
+    ) {
+        let fee = amount * (fee_per_mille as u128) / 1000;
+        self.token_balances
+            .move_tokens(from, to, token, amount - fee);
+        if fee > 0 {
+            self.token_balances
+                .move_tokens(from, self.fee_collector, token, fee);
+        }
+    }
+
+    /// Calculates the depth of the order book at the given price, for building depth charts.
+    /// The placed orders are visited in price-time priority until the price is crossed, or until
+    /// `max_levels` distinct prices have been visited, bounding the work done for deep books.
+    ///
+    /// Parameters:
+    ///
+    /// * `price` The price level to calculate the depth at.
+    /// * `max_levels` The maximum number of distinct prices to include on each side of the book.
+    ///
+    /// Returns:
+    ///
+    /// The total amount of tokens bid at or above `price`, and the total amount of tokens asked
+    /// at or below `price`.
+    pub fn depth_at_price(&self, price: Price, max_levels: u32) -> (TokenAmount, TokenAmount) {
+        let bid_volume =
+            cumulative_volume(&self.bids, |order_price| order_price >= price, max_levels);
+        let ask_volume =
+            cumulative_volume(&self.asks, |order_price| order_price <= price, max_levels);
+        (bid_volume, ask_volume)
+    }
+
+    /// Calculates the uniform clearing price of a periodic auction, as the price at which the most
+    /// tokens can be matched between the placed bids and asks. Ties are broken by the smallest
+    /// imbalance between the tokens bid and asked at the price, and then by the lowest price.
+    ///
+    /// The prices of the placed orders are visited in a single merged pass over the price levels of
+    /// the bids and asks, in increasing order of price. The tokens asked at or below the price are
+    /// accumulated along the way, and the tokens bid at or above it are found by subtracting the
+    /// bids below the price from the total.
+    ///
+    /// Returns:
+    ///
+    /// The clearing price and the amount of tokens matched at it, or `None` if no placed bids and
+    /// asks cross.
+    pub fn clearing_price(&self) -> Option<(Price, TokenAmount)> {
+        let bid_levels = price_levels(&self.bids);
+        let mut bid_volume: TokenAmount = bid_levels.iter().map(|(_, amount)| amount).sum();
+        let mut ask_volume: TokenAmount = 0;
+        let mut bid_levels = bid_levels.into_iter().rev().peekable();
+        let mut ask_levels = price_levels(&self.asks).into_iter().peekable();
+
+        let mut best: Option<(TokenAmount, Reverse<TokenAmount>, Reverse<Price>)> = None;
+        loop {
+            let price = match (bid_levels.peek(), ask_levels.peek()) {
+                (Some(&(bid_price, _)), Some(&(ask_price, _))) => bid_price.min(ask_price),
+                (Some(&(bid_price, _)), None) => bid_price,
+                (None, Some(&(ask_price, _))) => ask_price,
+                (None, None) => break,
+            };
+            let bid_amount_at_price = bid_levels
+                .next_if(|&(bid_price, _)| bid_price == price)
+                .map_or(0, |(_, amount)| amount);
+            if let Some((_, amount)) = ask_levels.next_if(|&(ask_price, _)| ask_price == price) {
+                ask_volume += amount;
+            }
+
+            let volume = bid_volume.min(ask_volume);
+            let candidate = (
+                volume,
+                Reverse(bid_volume.abs_diff(ask_volume)),
+                Reverse(price),
+            );
+            if volume > 0 && best < Some(candidate) {
+                best = Some(candidate);
+            }
+            bid_volume -= bid_amount_at_price;
+        }
+        best.map(|(volume, _, Reverse(price))| (price, volume))
+    }
+
+    /// Whether orders are collected for a periodic auction, rather than matched continuously.
+    fn is_periodic_auction(&self) -> bool {
+        self.auction_end_millis.is_some()
+    }
+
+    /// Asserts that orders can be submitted at the given time, i.e. that the periodic auction, if
+    /// any, has not yet ended.
+    ///
+    /// Parameters:
+    ///
+    /// * `block_production_time` The time of the submission.
+    fn assert_accepting_orders(&self, block_production_time: i64) {
+        if let Some(auction_end_millis) = self.auction_end_millis {
+            assert!(
+                block_production_time < auction_end_millis,
+                "Orders cannot be submitted after the auction ended at {}.",
+                auction_end_millis
+            );
+        }
+    }
+
+    /// Asserts that the order book is not frozen at the given time. The book of a periodic auction
+    /// is frozen from the end of the auction until the auction has been cleared, or until
+    /// [`AUCTION_CLEARING_GRACE_PERIOD_MILLIS`] has passed without the auction being cleared.
+    ///
+    /// Parameters:
+    ///
+    /// * `block_production_time` The time of the cancelation.
+    fn assert_book_not_frozen(&self, block_production_time: i64) {
+        if let Some(auction_end_millis) = self.auction_end_millis {
+            let grace_period_end_millis =
+                auction_end_millis.saturating_add(AUCTION_CLEARING_GRACE_PERIOD_MILLIS);
+            assert!(
+                block_production_time < auction_end_millis
+                    || self.auction_cleared
+                    || block_production_time >= grace_period_end_millis,
+                "Orders cannot be cancelled until the auction has been cleared, or until {} if it is not cleared.",
+                grace_period_end_millis
+            );
+        }
+    }
+}
+
+/// Groups the given orders into price levels, in priority order, summing the amount of tokens of
+/// the orders at each price.
+///
+/// Parameters:
+///
+/// * `orders` The orders, sorted by priority.
+///
+/// Returns:
+///
+/// The distinct prices of the orders, in priority order, with the total amount of tokens at each.
+fn price_levels(orders: &AvlTreeMap<Priority, LimitOrder>) -> Vec<(Price, TokenAmount)> {
+    let mut levels: Vec<(Price, TokenAmount)> = vec![];
+    for (_, order) in orders.iter() {
+        match levels.last_mut() {
+            Some((price, amount)) if *price == order.price_per_token => {
+                *amount += order.token_amount;
+            }
+            _ => levels.push((order.price_per_token, order.token_amount)),
+        }
+    }
+    levels
+}
+
+/// Sums the amount of tokens of the given orders in priority order, while the price of the
+/// orders is within the given bound, visiting at most `max_levels` distinct prices.
+///
+/// Parameters:
+///
+/// * `orders` The orders, sorted by priority.
+/// * `is_within_price` Whether the price of an order is within the bound.
+/// * `max_levels` The maximum number of distinct prices to visit.
+///
+/// Returns:
+///
+/// The total amount of tokens of the visited orders.
+fn cumulative_volume(
+    orders: &AvlTreeMap<Priority, LimitOrder>,
+    is_within_price: impl Fn(Price) -> bool,
+    max_levels: u32,
+) -> TokenAmount {
+    let mut volume = 0;
+    let mut levels = 0;
+    let mut current_price = None;
+    for (_, order) in orders.iter() {
+        if !is_within_price(order.price_per_token) {
+            break;
+        }
+        if current_price != Some(order.price_per_token) {
+            if levels == max_levels {
+                break;
+            }
+            levels += 1;
+            current_price = Some(order.price_per_token);
+        }
+        volume += order.token_amount;
+    }
+    volume
+}
+
+/// Asserts that the given owner is allowed to place another limit order, without exceeding the
+/// maximum number of open limit orders per user.
+///
+/// Parameters:
+///
+/// * `state` The state of the contract.
+/// * `owner` The owner of the limit order to be placed.
+fn assert_open_order_limit_not_reached(state: &DoubleAuctionContractState, owner: Address) {
+    assert!(
+        state.open_orders(owner) < state.max_open_orders_per_user,
+        "User {} has reached the maximum of {} open orders.",
+        owner,
+        state.max_open_orders_per_user
+    );
+}
+
+/// Asserts that the given amount of tokens is at least the minimum amount a limit order can be
+/// placed for.
+///
+/// Parameters:
+///
+/// * `state` The state of the contract.
+/// * `token_amount` The amount of tokens of the limit order.
+fn assert_min_order_amount(state: &DoubleAuctionContractState, token_amount: TokenAmount) {
+    assert!(
+        token_amount >= state.min_order_amount,
+        "Order amount {} is below the minimum order amount of {}.",
+        token_amount,
+        state.min_order_amount
+    );
 }
 
 /// Initialize the order matching contract.
@@ -192,6 +513,15 @@ pub struct DoubleAuctionContractState {
 /// * `asset_token_address` The address of the asset token contract.
 /// * `price_numerator` Price numerator from which to calculate the price of the asset.
 /// * `price_denominator` Price denominator from which to calculate the price of the asset.
+/// * `max_open_orders_per_user` The maximum number of open limit orders a single user can have.
+///   Must be positive.
+/// * `min_order_amount` The minimum amount of tokens a limit order can be placed for.
+/// * `maker_fee_per_mille` The fee per mille deducted from the proceeds of met orders.
+/// * `taker_fee_per_mille` The fee per mille deducted from the proceeds of meeting orders.
+/// * `fee_collector` The account credited with the fees.
+/// * `administrator` The account that can update the price numerator and denominator.
+/// * `auction_end_millis` The time at which the periodic auction ends, or `None` for continuous
+///   matching of orders.
 ///
 /// Returns:
 ///
@@ -204,7 +534,27 @@ pub fn initialize(
     asset_token_address: Address,
     price_numerator: u64,
     price_denominator: u64,
+    max_open_orders_per_user: u32,
+    min_order_amount: TokenAmount,
+    maker_fee_per_mille: u16,
+    taker_fee_per_mille: u16,
+    fee_collector: Address,
+    administrator: Address,
+    auction_end_millis: Option<i64>,
 ) -> DoubleAuctionContractState {
+    assert!(
+        max_open_orders_per_user > 0,
+        "The maximum number of open orders per user must be positive."
+    );
+    assert_is_per_mille(maker_fee_per_mille);
+    assert_is_per_mille(taker_fee_per_mille);
+    if let Some(auction_end_millis) = auction_end_millis {
+        assert!(
+            auction_end_millis > context.block_production_time,
+            "The auction must end in the future."
+        );
+    }
+
     let token_balances = TokenBalances::new(
         context.contract_address,
         currency_token_address,
@@ -223,6 +573,15 @@ pub fn initialize(
         orders_by_cancelation_request: AvlTreeMap::new(),
         bids: AvlTreeMap::new(),
         asks: AvlTreeMap::new(),
+        open_orders_per_user: AvlTreeMap::new(),
+        max_open_orders_per_user,
+        min_order_amount,
+        maker_fee_per_mille,
+        taker_fee_per_mille,
+        fee_collector,
+        administrator,
+        auction_end_millis,
+        auction_cleared: false,
     }
 }
 
@@ -364,7 +723,14 @@ fn wait_withdraw_callback(
 
 /// Submit a bid limit order. If matching asks exist, it will meet those asks until the amount
 /// placed is met or until no more matching asks exist, at which point it will place the bid
-/// for the remaining amount.
+/// for the remaining amount. Placing the remaining amount fails if the caller has already reached
+/// the maximum number of open limit orders. Fails if the amount is below the minimum order amount.
+/// If the remaining amount is below the minimum order amount, it is not placed, and stays on the
+/// balance of the caller. The taker fee is deducted from the tokens received by the caller, and
+/// the maker fee from the tokens received by the owners of the met orders.
+///
+/// In a periodic auction, the bid is placed without meeting any asks, until the auction is
+/// cleared by `clear_auction`. Fails if the auction has ended.
 ///
 /// Parameters:
 ///
@@ -385,9 +751,11 @@ fn submit_bid(
     token_amount: TokenAmount,
     cancelation_id: u32,
 ) -> DoubleAuctionContractState {
+    state.assert_accepting_orders(context.block_production_time);
+    assert_min_order_amount(&state, token_amount);
     let mut rest_amount = token_amount;
 
-    while (rest_amount > 0) && (!state.asks.is_empty()) {
+    while !state.is_periodic_auction() && (rest_amount > 0) && (!state.asks.is_empty()) {
         let (key, mut ask_order) = state.asks.iter().next().unwrap();
 
         if ask_order.price_per_token > price_per_token {
@@ -410,31 +778,33 @@ fn submit_bid(
                 owner: ask_order.owner,
                 cancelation_id: ask_order.cancelation_id,
             };
-            state
-                .orders_by_cancelation_request
-                .remove(&cancelation_request);
+            state.remove_open_order(&cancelation_request);
         }
 
-        state.token_balances.move_tokens(
+        let price = total_price(
+            move_amount,
+            ask_order.price_per_token,
+            state.price_numerator,
+            state.price_denominator,
+        );
+        state.move_proceeds_with_fee(
             context.sender,
             ask_order.owner,
             CURRENCY_TOKEN,
-            total_price(
-                move_amount,
-                ask_order.price_per_token,
-                state.price_numerator,
-                state.price_denominator,
-            ),
+            price,
+            state.maker_fee_per_mille,
         );
-        state.token_balances.move_tokens(
+        state.move_proceeds_with_fee(
             state.double_auction_address,
             context.sender,
             ASSET_TOKEN,
             move_amount,
+            state.taker_fee_per_mille,
         );
     }
 
-    if rest_amount > 0 {
+    if rest_amount > 0 && rest_amount >= state.min_order_amount {
+        assert_open_order_limit_not_reached(&state, context.sender);
         let pri = Priority::expensive_early(price_per_token, state.next_order_id);
         let new_bid_order = LimitOrder {
             price_per_token,
@@ -449,9 +819,7 @@ fn submit_bid(
             owner: context.sender,
             cancelation_id,
         };
-        state
-            .orders_by_cancelation_request
-            .insert(cancelation_request, new_bid_order);
+        state.insert_open_order(cancelation_request, new_bid_order);
         state.token_balances.move_tokens(
             context.sender,
             state.double_auction_address,
@@ -472,7 +840,14 @@ fn submit_bid(
 
 /// Submit an ask limit order. If matching bids exist, it will meet those bids until the amount
 /// placed is met or until no more matching bids exist, at which point it will place the ask
-/// for the remaining amount.
+/// for the remaining amount. Placing the remaining amount fails if the caller has already reached
+/// the maximum number of open limit orders. Fails if the amount is below the minimum order amount.
+/// If the remaining amount is below the minimum order amount, it is not placed, and stays on the
+/// balance of the caller. The taker fee is deducted from the tokens received by the caller, and
+/// the maker fee from the tokens received by the owners of the met orders.
+///
+/// In a periodic auction, the ask is placed without meeting any bids, until the auction is
+/// cleared by `clear_auction`. Fails if the auction has ended.
 ///
 /// Parameters:
 ///
@@ -493,9 +868,11 @@ fn submit_ask(
     token_amount: TokenAmount,
     cancelation_id: u32,
 ) -> DoubleAuctionContractState {
+    state.assert_accepting_orders(context.block_production_time);
+    assert_min_order_amount(&state, token_amount);
     let mut rest_amount = token_amount;
 
-    while (rest_amount > 0) && (!state.bids.is_empty()) {
+    while !state.is_periodic_auction() && (rest_amount > 0) && (!state.bids.is_empty()) {
         let (key, mut bid_order) = state.bids.iter().next().unwrap();
 
         if bid_order.price_per_token < price_per_token {
@@ -520,28 +897,33 @@ fn submit_ask(
                 owner: bid_order.owner,
                 cancelation_id: bid_order.cancelation_id,
             };
-            state
-                .orders_by_cancelation_request
-                .remove(&cancelation_request)
+            state.remove_open_order(&cancelation_request)
         }
 
-        state.token_balances.move_tokens(
+        let price = total_price(
+            move_amount,
+            bid_order.price_per_token,
+            state.price_numerator,
+            state.price_denominator,
+        );
+        state.move_proceeds_with_fee(
             state.double_auction_address,
             context.sender,
             CURRENCY_TOKEN,
-            total_price(
-                move_amount,
-                bid_order.price_per_token,
-                state.price_numerator,
-                state.price_denominator,
-            ),
+            price,
+            state.taker_fee_per_mille,
+        );
+        state.move_proceeds_with_fee(
+            context.sender,
+            bid_order.owner,
+            ASSET_TOKEN,
+            move_amount,
+            state.maker_fee_per_mille,
         );
-        state
-            .token_balances
-            .move_tokens(context.sender, bid_order.owner, ASSET_TOKEN, move_amount);
     }
 
-    if rest_amount > 0 {
+    if rest_amount > 0 && rest_amount >= state.min_order_amount {
+        assert_open_order_limit_not_reached(&state, context.sender);
         let pri = Priority::cheap_early(price_per_token, state.next_order_id);
         let new_ask_order = LimitOrder {
             price_per_token,
@@ -556,9 +938,7 @@ fn submit_ask(
             owner: context.sender,
             cancelation_id,
         };
-        state
-            .orders_by_cancelation_request
-            .insert(cancelation_request, new_ask_order);
+        state.insert_open_order(cancelation_request, new_ask_order);
         state.token_balances.move_tokens(
             context.sender,
             state.double_auction_address,
@@ -573,7 +953,9 @@ fn submit_ask(
 }
 
 /// Cancel a previously placed limit order. Limit orders can only be cancelled by the
-/// same account that placed the order in the first place.
+/// same account that placed the order in the first place. In a periodic auction, orders cannot be
+/// cancelled from the end of the auction until it has been cleared, or until the clearing grace
+/// period has passed.
 ///
 /// Parameters:
 ///
@@ -590,6 +972,7 @@ fn cancel_limit_order(
     mut state: DoubleAuctionContractState,
     cancelation_id: u32,
 ) -> DoubleAuctionContractState {
+    state.assert_book_not_frozen(context.block_production_time);
     let cancelation_request = CancelationRequest {
         owner: context.sender,
         cancelation_id,
@@ -599,12 +982,231 @@ fn cancel_limit_order(
         .get(&cancelation_request)
         .unwrap_or_else(|| panic!("The given cancelation request did not match any orders."));
 
+    remove_and_refund_order(&mut state, cancelation_request, order);
+
+    state
+}
+
+/// Cancel all limit orders previously placed by the caller. Every cancelled order is refunded
+/// to the balance of the caller, as if each order had been cancelled by `cancel_limit_order`.
+/// In a periodic auction, orders cannot be cancelled from the end of the auction until it has been
+/// cleared, or until the clearing grace period has passed.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+///
+/// Returns:
+///
+/// The updated state of the contract, with all limit orders of the caller cancelled.
+#[action(shortname = 0x07)]
+fn cancel_all_orders(
+    context: ContractContext,
+    mut state: DoubleAuctionContractState,
+) -> DoubleAuctionContractState {
+    state.assert_book_not_frozen(context.block_production_time);
+    // Collect the orders before removing any of them, to avoid mutating the tree while iterating.
+    let orders_of_sender: Vec<(CancelationRequest, LimitOrder)> = state
+        .orders_by_cancelation_request
+        .iter()
+        .filter(|(cancelation_request, _)| cancelation_request.owner == context.sender)
+        .collect();
+
+    for (cancelation_request, order) in orders_of_sender {
+        remove_and_refund_order(&mut state, cancelation_request, order);
+    }
+
+    state
+}
+
+/// Update the price numerator and denominator, from which the price of the asset token is
+/// calculated. Only the administrator can update the price, and only while no limit orders are
+/// placed on the contract, such that placed orders are never repriced.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+/// * `price_numerator` The new price numerator.
+/// * `price_denominator` The new price denominator.
+///
+/// Returns:
+///
+/// The updated state of the contract, with the new price.
+#[action(shortname = 0x08)]
+fn update_price(
+    context: ContractContext,
+    mut state: DoubleAuctionContractState,
+    price_numerator: u64,
+    price_denominator: u64,
+) -> DoubleAuctionContractState {
+    assert_eq!(
+        context.sender, state.administrator,
+        "Only the administrator can update the price."
+    );
+    assert!(
+        state.bids.is_empty() && state.asks.is_empty(),
+        "Cannot update the price while limit orders are placed."
+    );
+    assert!(price_denominator > 0, "Price denominator must be positive.");
+
+    state.price_numerator = price_numerator;
+    state.price_denominator = price_denominator;
+    state
+}
+
+/// Clear a periodic auction after it has ended, matching all crossing bids and asks at a single
+/// uniform clearing price, see [`DoubleAuctionContractState::clearing_price`]. Bids and asks are
+/// matched in price-time priority, and the marginal order may be partially matched. Bids matched
+/// below their price are refunded the difference, and the maker fee is deducted from the tokens
+/// received by both sides, as all orders were placed on the book. Unmatched orders stay placed,
+/// and can be cancelled by their owners.
+///
+/// Can be called by anyone, once, after the auction has ended. If the auction has not been cleared
+/// within [`AUCTION_CLEARING_GRACE_PERIOD_MILLIS`] after it ended, owners can cancel their orders
+/// to get their tokens back, and a later clear only matches the orders that are still placed.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+///
+/// Returns:
+///
+/// The updated state of the contract, with the crossing orders matched.
+#[action(shortname = 0x09)]
+fn clear_auction(
+    context: ContractContext,
+    mut state: DoubleAuctionContractState,
+) -> DoubleAuctionContractState {
+    let auction_end_millis = state
+        .auction_end_millis
+        .expect("Only periodic auctions can be cleared.");
+    assert!(
+        context.block_production_time >= auction_end_millis,
+        "The auction cannot be cleared before it ends at {}.",
+        auction_end_millis
+    );
+    assert!(
+        !state.auction_cleared,
+        "The auction has already been cleared."
+    );
+
+    state.auction_cleared = true;
+    if let Some((clearing_price, _)) = state.clearing_price() {
+        match_at_clearing_price(&mut state, clearing_price);
+    }
+    state
+}
+
+/// Matches the best placed bid and ask at the given clearing price, until they no longer cross it.
+///
+/// Parameters:
+///
+/// * `state` The state of the contract.
+/// * `clearing_price` The uniform price at which all orders are matched.
+fn match_at_clearing_price(state: &mut DoubleAuctionContractState, clearing_price: Price) {
+    loop {
+        let best_bid = state.bids.iter().next();
+        let best_ask = state.asks.iter().next();
+        let (Some((bid_key, mut bid_order)), Some((ask_key, mut ask_order))) = (best_bid, best_ask)
+        else {
+            break;
+        };
+        if bid_order.price_per_token < clearing_price || ask_order.price_per_token > clearing_price
+        {
+            break;
+        }
+
+        let move_amount = bid_order.token_amount.min(ask_order.token_amount);
+        let price = total_price(
+            move_amount,
+            clearing_price,
+            state.price_numerator,
+            state.price_denominator,
+        );
+        let reserved_price = total_price(
+            move_amount,
+            bid_order.price_per_token,
+            state.price_numerator,
+            state.price_denominator,
+        );
+
+        state.move_proceeds_with_fee(
+            state.double_auction_address,
+            ask_order.owner,
+            CURRENCY_TOKEN,
+            price,
+            state.maker_fee_per_mille,
+        );
+        if reserved_price > price {
+            state.token_balances.move_tokens(
+                state.double_auction_address,
+                bid_order.owner,
+                CURRENCY_TOKEN,
+                reserved_price - price,
+            );
+        }
+        state.move_proceeds_with_fee(
+            state.double_auction_address,
+            bid_order.owner,
+            ASSET_TOKEN,
+            move_amount,
+            state.maker_fee_per_mille,
+        );
+
+        bid_order.token_amount -= move_amount;
+        ask_order.token_amount -= move_amount;
+        update_matched_order(state, bid_key, bid_order);
+        update_matched_order(state, ask_key, ask_order);
+    }
+}
+
+/// Updates a placed limit order after it has been partially or fully matched. Fully matched orders
+/// are removed from the contract.
+///
+/// Parameters:
+///
+/// * `state` The state of the contract.
+/// * `key` The priority of the order.
+/// * `order` The order with the remaining amount of tokens.
+fn update_matched_order(state: &mut DoubleAuctionContractState, key: Priority, order: LimitOrder) {
+    let orders = if order.is_bid {
+        &mut state.bids
+    } else {
+        &mut state.asks
+    };
+    if order.token_amount > 0 {
+        orders.insert(key, order);
+    } else {
+        orders.remove(&key);
+        state.remove_open_order(&CancelationRequest {
+            owner: order.owner,
+            cancelation_id: order.cancelation_id,
+        });
+    }
+}
+
+/// Removes a placed limit order from the contract, and refunds the tokens reserved by the order to
+/// the owner of the order.
+///
+/// Parameters:
+///
+/// * `state` The state of the contract.
+/// * `cancelation_request` The owner and cancelation ID of the order.
+/// * `order` The limit order to remove.
+fn remove_and_refund_order(
+    state: &mut DoubleAuctionContractState,
+    cancelation_request: CancelationRequest,
+    order: LimitOrder,
+) {
     if order.is_bid {
         let key = Priority::expensive_early(order.price_per_token, order.id);
         let bid_order = state.bids.get(&key).unwrap();
         state.token_balances.move_tokens(
             state.double_auction_address,
-            context.sender,
+            cancelation_request.owner,
             CURRENCY_TOKEN,
             total_price(
                 bid_order.token_amount,
@@ -619,16 +1221,12 @@ fn cancel_limit_order(
         let ask_order = state.asks.get(&key).unwrap();
         state.token_balances.move_tokens(
             state.double_auction_address,
-            context.sender,
+            cancelation_request.owner,
             ASSET_TOKEN,
             ask_order.token_amount,
         );
         state.asks.remove(&key);
     }
 
-    state
-        .orders_by_cancelation_request
-        .remove(&cancelation_request);
-
-    state
+    state.remove_open_order(&cancelation_request);
 }
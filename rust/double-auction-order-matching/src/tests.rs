@@ -0,0 +1,366 @@
+use super::*;
+use defi_common::test_util::{account, context, contract};
+
+/// Creates a state with bids at prices 10, 10, 9 and 7, and asks at prices 12, 13, 13 and 15.
+fn state_with_orders() -> DoubleAuctionContractState {
+    let mut state = initialize(
+        context(account(1), 0),
+        contract(1),
+        contract(2),
+        1,
+        1,
+        10,
+        1,
+        0,
+        0,
+        account(3),
+        account(3),
+        None,
+    );
+    state
+        .token_balances
+        .add_to_token_balance(account(1), CURRENCY_TOKEN, 10_000);
+    state
+        .token_balances
+        .add_to_token_balance(account(2), ASSET_TOKEN, 10_000);
+
+    let state = submit_bid(context(account(1), 0), state, 10, 100, 1);
+    let state = submit_bid(context(account(1), 0), state, 9, 50, 2);
+    let state = submit_bid(context(account(1), 0), state, 10, 20, 3);
+    let state = submit_bid(context(account(1), 0), state, 7, 5, 4);
+
+    let state = submit_ask(context(account(2), 0), state, 13, 30, 1);
+    let state = submit_ask(context(account(2), 0), state, 15, 40, 2);
+    let state = submit_ask(context(account(2), 0), state, 12, 60, 3);
+    submit_ask(context(account(2), 0), state, 13, 10, 4)
+}
+
+#[test]
+fn depth_of_empty_book() {
+    let state = initialize(
+        context(account(1), 0),
+        contract(1),
+        contract(2),
+        1,
+        1,
+        10,
+        1,
+        0,
+        0,
+        account(3),
+        account(3),
+        None,
+    );
+    assert_eq!(state.depth_at_price(10, 100), (0, 0));
+}
+
+#[test]
+fn depth_accumulates_across_price_levels() {
+    let state = state_with_orders();
+
+    assert_eq!(state.depth_at_price(11, 100), (0, 0));
+    assert_eq!(state.depth_at_price(10, 100), (120, 0));
+    assert_eq!(state.depth_at_price(9, 100), (170, 0));
+    assert_eq!(state.depth_at_price(8, 100), (170, 0));
+    assert_eq!(state.depth_at_price(7, 100), (175, 0));
+    assert_eq!(state.depth_at_price(0, 100), (175, 0));
+
+    assert_eq!(state.depth_at_price(12, 100), (0, 60));
+    assert_eq!(state.depth_at_price(13, 100), (0, 100));
+    assert_eq!(state.depth_at_price(14, 100), (0, 100));
+    assert_eq!(state.depth_at_price(15, 100), (0, 140));
+    assert_eq!(state.depth_at_price(Price::MAX, 100), (0, 140));
+}
+
+#[test]
+fn depth_is_capped_by_max_levels() {
+    let state = state_with_orders();
+
+    assert_eq!(state.depth_at_price(0, 0), (0, 0));
+    // Both orders at the best bid price count as a single level.
+    assert_eq!(state.depth_at_price(0, 1), (120, 0));
+    assert_eq!(state.depth_at_price(0, 2), (170, 0));
+    assert_eq!(state.depth_at_price(Price::MAX, 1), (0, 60));
+    assert_eq!(state.depth_at_price(Price::MAX, 2), (0, 100));
+}
+
+#[test]
+fn depth_after_orders_are_met() {
+    let mut state = state_with_orders();
+    state
+        .token_balances
+        .add_to_token_balance(account(4), CURRENCY_TOKEN, 10_000);
+
+    // Meets all asks at 12, and some of the asks at 13.
+    let state = submit_bid(context(account(4), 0), state, 13, 70, 1);
+
+    assert_eq!(state.depth_at_price(13, 100), (0, 30));
+    assert_eq!(state.depth_at_price(15, 100), (0, 70));
+    assert_eq!(state.depth_at_price(10, 100), (120, 0));
+}
+
+#[test]
+fn open_orders_are_counted_per_user() {
+    let state = state_with_orders();
+    assert_eq!(state.open_orders(account(1)), 4);
+    assert_eq!(state.open_orders(account(2)), 4);
+    assert_eq!(state.open_orders(account(3)), 0);
+}
+
+#[test]
+fn met_and_cancelled_orders_are_no_longer_counted() {
+    let state = state_with_orders();
+    let state = submit_ask(context(account(2), 0), state, 10, 100, 5);
+    assert_eq!(state.open_orders(account(1)), 3);
+    assert_eq!(state.open_orders(account(2)), 4);
+
+    let state = cancel_limit_order(context(account(1), 0), state, 3);
+    assert_eq!(state.open_orders(account(1)), 2);
+
+    let state = cancel_all_orders(context(account(2), 0), state);
+    assert_eq!(state.open_orders(account(2)), 0);
+    assert!(state.open_orders_per_user.get(&account(2)).is_none());
+}
+
+#[test]
+#[should_panic(expected = "The maximum number of open orders per user must be positive.")]
+fn zero_max_open_orders_per_user_is_rejected() {
+    initialize(
+        context(account(1), 0),
+        contract(1),
+        contract(2),
+        1,
+        1,
+        0,
+        1,
+        0,
+        0,
+        account(3),
+        account(3),
+        None,
+    );
+}
+
+const AUCTION_END: i64 = 1_000;
+
+/// Creates a periodic auction ending at [`AUCTION_END`], where users 1 and 4 have currency tokens
+/// and users 2 and 5 have asset tokens.
+fn periodic_auction(maker_fee_per_mille: u16) -> DoubleAuctionContractState {
+    let mut state = initialize(
+        context(account(1), 0),
+        contract(1),
+        contract(2),
+        1,
+        1,
+        10,
+        1,
+        maker_fee_per_mille,
+        0,
+        account(3),
+        account(3),
+        Some(AUCTION_END),
+    );
+    for owner in [account(1), account(4)] {
+        state
+            .token_balances
+            .add_to_token_balance(owner, CURRENCY_TOKEN, 10_000);
+    }
+    for owner in [account(2), account(5)] {
+        state
+            .token_balances
+            .add_to_token_balance(owner, ASSET_TOKEN, 10_000);
+    }
+    state
+}
+
+fn balance(state: &DoubleAuctionContractState, owner: Address, token: DepositToken) -> TokenAmount {
+    state
+        .token_balances
+        .get_balance_for(&owner)
+        .get_amount_of(token)
+}
+
+#[test]
+fn periodic_auction_places_crossing_orders_without_matching() {
+    let state = periodic_auction(0);
+    let state = submit_bid(context(account(1), 0), state, 12, 100, 1);
+    let state = submit_ask(context(account(2), 0), state, 8, 100, 1);
+
+    assert_eq!(state.depth_at_price(10, 100), (100, 100));
+    assert_eq!(balance(&state, account(1), ASSET_TOKEN), 0);
+    assert_eq!(balance(&state, account(2), CURRENCY_TOKEN), 0);
+}
+
+#[test]
+#[should_panic(expected = "Orders cannot be submitted after the auction ended at 1000.")]
+fn bids_are_rejected_after_auction_ends() {
+    let state = periodic_auction(0);
+    submit_bid(context(account(1), AUCTION_END), state, 10, 100, 1);
+}
+
+#[test]
+#[should_panic(expected = "Orders cannot be submitted after the auction ended at 1000.")]
+fn asks_are_rejected_after_auction_ends() {
+    let state = periodic_auction(0);
+    submit_ask(context(account(2), AUCTION_END + 1), state, 10, 100, 1);
+}
+
+#[test]
+#[should_panic(expected = "Orders cannot be cancelled until the auction has been cleared")]
+fn book_is_frozen_until_cleared() {
+    let state = periodic_auction(0);
+    let state = submit_bid(context(account(1), 0), state, 10, 100, 1);
+    cancel_limit_order(context(account(1), AUCTION_END), state, 1);
+}
+
+#[test]
+#[should_panic(
+    expected = "Orders cannot be cancelled until the auction has been cleared, or until 3601000 if it is not cleared."
+)]
+fn book_is_frozen_until_grace_period_ends() {
+    let state = periodic_auction(0);
+    let state = submit_bid(context(account(1), 0), state, 10, 100, 1);
+    cancel_limit_order(
+        context(
+            account(1),
+            AUCTION_END + AUCTION_CLEARING_GRACE_PERIOD_MILLIS - 1,
+        ),
+        state,
+        1,
+    );
+}
+
+#[test]
+fn orders_can_be_cancelled_when_auction_is_not_cleared_in_time() {
+    let state = auction_with_crossing_orders(0);
+    let grace_period_end = AUCTION_END + AUCTION_CLEARING_GRACE_PERIOD_MILLIS;
+    let state = cancel_limit_order(context(account(1), grace_period_end), state, 1);
+    let state = cancel_all_orders(context(account(2), grace_period_end), state);
+
+    assert_eq!(balance(&state, account(1), CURRENCY_TOKEN), 10_000);
+    assert_eq!(balance(&state, account(2), ASSET_TOKEN), 10_000);
+
+    // A late clear only matches the orders that are still placed, which no longer cross.
+    let state = clear_auction(context(account(6), grace_period_end), state);
+    assert_eq!(state.depth_at_price(10, 100), (50, 0));
+    assert_eq!(state.depth_at_price(11, 100), (0, 60));
+    assert_eq!(balance(&state, account(4), ASSET_TOKEN), 0);
+}
+
+#[test]
+#[should_panic(expected = "The auction cannot be cleared before it ends at 1000.")]
+fn auction_cannot_be_cleared_before_it_ends() {
+    let state = periodic_auction(0);
+    clear_auction(context(account(6), AUCTION_END - 1), state);
+}
+
+#[test]
+#[should_panic(expected = "The auction has already been cleared.")]
+fn auction_can_only_be_cleared_once() {
+    let state = periodic_auction(0);
+    let state = clear_auction(context(account(6), AUCTION_END), state);
+    clear_auction(context(account(6), AUCTION_END), state);
+}
+
+#[test]
+#[should_panic(expected = "Only periodic auctions can be cleared.")]
+fn continuous_auction_cannot_be_cleared() {
+    let state = state_with_orders();
+    clear_auction(context(account(6), AUCTION_END), state);
+}
+
+/// Bids of 100 at 12 and 50 at 10, and asks of 80 at 9 and 60 at 11. The most tokens are matched
+/// at 11, where 100 tokens are bid and 140 are asked.
+fn auction_with_crossing_orders(maker_fee_per_mille: u16) -> DoubleAuctionContractState {
+    let state = periodic_auction(maker_fee_per_mille);
+    let state = submit_bid(context(account(1), 0), state, 12, 100, 1);
+    let state = submit_bid(context(account(4), 0), state, 10, 50, 1);
+    let state = submit_ask(context(account(2), 0), state, 9, 80, 1);
+    submit_ask(context(account(5), 0), state, 11, 60, 1)
+}
+
+#[test]
+fn clearing_price_maximizes_matched_volume() {
+    let state = auction_with_crossing_orders(0);
+    assert_eq!(state.clearing_price(), Some((11, 100)));
+}
+
+#[test]
+fn clearing_price_of_book_without_crossing_orders() {
+    let state = periodic_auction(0);
+    let state = submit_bid(context(account(1), 0), state, 9, 100, 1);
+    let state = submit_ask(context(account(2), 0), state, 10, 100, 1);
+    assert_eq!(state.clearing_price(), None);
+}
+
+#[test]
+fn clearing_price_breaks_ties_by_imbalance() {
+    let state = periodic_auction(0);
+    // 100 tokens are matched at both 9 and 10, but only 10 balances the bids and asks.
+    let state = submit_bid(context(account(1), 0), state, 10, 100, 1);
+    let state = submit_bid(context(account(4), 0), state, 9, 20, 1);
+    let state = submit_ask(context(account(2), 0), state, 8, 100, 1);
+    assert_eq!(state.clearing_price(), Some((10, 100)));
+}
+
+#[test]
+fn clearing_price_of_one_sided_book() {
+    let state = periodic_auction(0);
+    let state = submit_bid(context(account(1), 0), state, 10, 100, 1);
+    let state = submit_bid(context(account(4), 0), state, 12, 50, 1);
+    assert_eq!(state.clearing_price(), None);
+}
+
+#[test]
+fn clearing_price_sums_orders_at_same_price() {
+    let state = periodic_auction(0);
+    // At 10, 70 tokens are bid and 70 are asked. At 9 and 11 only 40 tokens would be matched.
+    let state = submit_bid(context(account(1), 0), state, 10, 30, 1);
+    let state = submit_bid(context(account(4), 0), state, 10, 40, 1);
+    let state = submit_ask(context(account(2), 0), state, 10, 30, 1);
+    let state = submit_ask(context(account(5), 0), state, 9, 40, 1);
+    assert_eq!(state.clearing_price(), Some((10, 70)));
+}
+
+#[test]
+fn clear_auction_matches_at_uniform_price() {
+    let state = auction_with_crossing_orders(0);
+    let state = clear_auction(context(account(6), AUCTION_END), state);
+
+    // The bid at 12 meets the ask at 9 for 80 tokens and the ask at 11 for 20 tokens, all at 11,
+    // and is refunded the difference to its own price.
+    assert_eq!(balance(&state, account(1), ASSET_TOKEN), 100);
+    assert_eq!(
+        balance(&state, account(1), CURRENCY_TOKEN),
+        10_000 - 100 * 11
+    );
+    assert_eq!(balance(&state, account(2), CURRENCY_TOKEN), 80 * 11);
+    assert_eq!(balance(&state, account(5), CURRENCY_TOKEN), 20 * 11);
+
+    // The bid at 10 and the rest of the ask at 11 do not cross, and stay placed.
+    assert_eq!(balance(&state, account(4), ASSET_TOKEN), 0);
+    assert_eq!(
+        balance(&state, account(4), CURRENCY_TOKEN),
+        10_000 - 50 * 10
+    );
+    assert_eq!(balance(&state, account(5), ASSET_TOKEN), 10_000 - 60);
+    assert_eq!(state.depth_at_price(10, 100), (50, 0));
+    assert_eq!(state.depth_at_price(11, 100), (0, 40));
+    assert!(state.auction_cleared);
+
+    // Unmatched orders can be cancelled once the auction has been cleared.
+    let state = cancel_limit_order(context(account(5), AUCTION_END), state, 1);
+    assert_eq!(balance(&state, account(5), ASSET_TOKEN), 10_000 - 20);
+}
+
+#[test]
+fn clear_auction_deducts_maker_fee_from_both_sides() {
+    let state = auction_with_crossing_orders(100);
+    let state = clear_auction(context(account(6), AUCTION_END), state);
+
+    assert_eq!(balance(&state, account(1), ASSET_TOKEN), 90);
+    assert_eq!(balance(&state, account(2), CURRENCY_TOKEN), 792);
+    assert_eq!(balance(&state, account(5), CURRENCY_TOKEN), 198);
+    assert_eq!(balance(&state, account(3), ASSET_TOKEN), 10);
+    assert_eq!(balance(&state, account(3), CURRENCY_TOKEN), 88 + 22);
+}
@@ -2,18 +2,23 @@
 
 mod upgrade;
 
+#[cfg(test)]
+mod tests;
+
 #[macro_use]
 extern crate pbc_contract_codegen;
 
 use std::ops::Sub;
 
 use create_type_spec_derive::CreateTypeSpec;
+use defi_common::events;
 use defi_common::interact_mpc20;
+use defi_common::math::{assert_is_per_mille, mul_div};
 use defi_common::token_state::AbstractTokenState;
-use pbc_contract_common::address::Address;
+use pbc_contract_common::address::{Address, Shortname};
 use pbc_contract_common::avl_tree_map::AvlTreeMap;
 use pbc_contract_common::context::{CallbackContext, ContractContext};
-use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::events::{EventGroup, EventGroupBuilder, GasCost};
 use pbc_traits::ReadWriteState;
 use read_write_rpc_derive::{ReadRPC, WriteRPC};
 use read_write_state_derive::ReadWriteState;
@@ -24,6 +29,31 @@ type PendingUnlockId = u32;
 /// The initial value for [`PendingUnlockId`] counter.
 const INITIAL_PENDING_UNLOCK_ID: PendingUnlockId = 1;
 
+/// Gas reserved for the callbacks of [`submit`] and [`deposit`]. The gas for the token transfer
+/// itself is attached by [`interact_mpc20::MPC20Contract::transfer_from`].
+const TRANSFER_CALLBACK_GAS_COST: GasCost = 600;
+
+/// Gas reserved for the callback of [`submit`], which must be able to refund the submitted stake
+/// tokens if the slippage limit is exceeded.
+const SUBMIT_CALLBACK_GAS_COST: GasCost =
+    TRANSFER_CALLBACK_GAS_COST + interact_mpc20::MPC20Contract::GAS_COST_TRANSFER;
+
+/// [`Shortname`] of the [`submitted_event()`] action.
+const SHORTNAME_SUBMITTED_EVENT: Shortname = Shortname::from_u32(0x1E);
+
+/// [`Shortname`] of the [`unlock_requested_event()`] action.
+const SHORTNAME_UNLOCK_REQUESTED_EVENT: Shortname = Shortname::from_u32(0x1F);
+
+/// [`Shortname`] of the [`redeemed_event()`] action.
+const SHORTNAME_REDEEMED_EVENT: Shortname = Shortname::from_u32(0x20);
+
+/// [`Shortname`] of the [`minted_event()`] action.
+const SHORTNAME_MINTED_EVENT: Shortname = Shortname::from_u32(0x21);
+
+/// Panic message of the event actions, when called by anyone but the contract itself.
+const EVENT_NOT_SENT_BY_CONTRACT_ERROR: &str =
+    "Events can only be sent by the liquid staking contract itself.";
+
 /// Address pair representing an allowance. Owner allows spender to transfer tokens on behalf of
 /// them.
 #[derive(ReadWriteState, CreateTypeSpec, Eq, Ord, PartialEq, PartialOrd)]
@@ -131,8 +161,18 @@ impl AbstractTokenState for LiquidTokenState {
     }
 }
 
+/// Exchange rate between stake tokens and liquid tokens, given as the amount of stake tokens that
+/// exchange to the amount of liquid tokens.
+#[derive(ReadRPC, WriteRPC, CreateTypeSpec, Clone, Copy, PartialEq, Debug)]
+pub struct ExchangeRate {
+    /// The amount of stake tokens.
+    pub stake_tokens: u128,
+    /// The amount of liquid tokens the stake tokens exchange to.
+    pub liquid_tokens: u128,
+}
+
 /// An unlock request waiting to be redeemed.
-#[derive(ReadRPC, WriteRPC, ReadWriteState, CreateTypeSpec)]
+#[derive(ReadRPC, WriteRPC, ReadWriteState, CreateTypeSpec, Clone)]
 pub struct PendingUnlock {
     /// Identifier for the [`PendingUnlock`]. Contract-unique.
     id: PendingUnlockId,
@@ -178,8 +218,11 @@ pub struct LiquidStakingState {
     pub stake_token_balance: u128,
     /// The account responsible for staking the submitted stake tokens.
     pub staking_responsible: Address,
-    /// The account that can change the buy-in.
-    pub administrator: Address,
+    /// The account that can change the buy-in and the instant unstake penalty.
+    pub fee_administrator: Address,
+    /// The account that can clean up expired [`PendingUnlock`]s, change the denylist and upgrade
+    /// the contract.
+    pub operations_administrator: Address,
     /// The total amount of the stake token (in minimal units) that can be staked.
     /// Used to calculate the exchange rate.
     /// This amount is equal the amount of tokens currently staked (by the staking responsible) plus
@@ -209,6 +252,14 @@ pub struct LiquidStakingState {
     pub buy_in_enabled: bool,
     /// Counter for the next contract-unique [`PendingUnlockId`].
     pending_unlock_id_counter: PendingUnlockId,
+    /// Per mille of the stake tokens withheld when a user unstakes using [`instant_unstake()`].
+    /// The withheld tokens stay in the pool, benefiting the remaining stakers.
+    pub instant_unstake_penalty_per_mille: u16,
+    /// Addresses that are denied from submitting, transferring and requesting unlocks. Denied
+    /// addresses keep their balances.
+    pub denylist: AvlTreeMap<Address, bool>,
+    /// The smallest amount of stake tokens that can be submitted in a single [`submit()`].
+    pub minimum_submit_amount: u128,
 }
 
 impl LiquidStakingState {
@@ -228,6 +279,36 @@ impl LiquidStakingState {
         self.stake_token_balance -= stake_token_amount
     }
 
+    /// Get the total value locked in the contract, in stake tokens. This is the amount of stake
+    /// tokens currently staked by the staking responsible, plus the stake tokens held by the
+    /// contract, i.e. the [`LiquidStakingState::total_pool_stake_token`].
+    ///
+    /// ## Returns
+    /// The total value locked, in stake tokens.
+    pub fn total_value_locked(&self) -> u128 {
+        self.total_pool_stake_token
+    }
+
+    /// Get the breakdown of the [total value locked](LiquidStakingState::total_value_locked).
+    ///
+    /// ## Returns
+    /// The tuple `(staked_out, held_on_contract, buy_in_locked)` of stake tokens, where
+    /// `staked_out` is staked by the staking responsible and `held_on_contract` is held by the
+    /// contract. These sum to the total value locked. `buy_in_locked` is the part of the total
+    /// value locked by the buy-in. Stake tokens held by the contract beyond the total value
+    /// locked, such as stake tokens awaiting redemption, are not part of the breakdown.
+    pub fn total_value_locked_breakdown(&self) -> (u128, u128, u128) {
+        let held_on_contract = self.stake_token_balance.min(self.total_pool_stake_token);
+        let staked_out = self
+            .total_pool_stake_token
+            .saturating_sub(self.stake_token_balance);
+        (
+            staked_out,
+            held_on_contract,
+            self.amount_of_buy_in_locked_stake_tokens,
+        )
+    }
+
     /// Determines whether the contract has enough stake tokens in order to perform some operation.
     ///
     /// ## Parameters
@@ -236,9 +317,14 @@ impl LiquidStakingState {
         stake_token_amount <= self.stake_token_balance
     }
 
-    /// Determines whether the specified `account` is the registered administrator.
-    fn is_the_administrator(&self, account: Address) -> bool {
-        account == self.administrator
+    /// Determines whether the specified `account` is the registered fee administrator.
+    fn is_the_fee_administrator(&self, account: Address) -> bool {
+        account == self.fee_administrator
+    }
+
+    /// Determines whether the specified `account` is the registered operations administrator.
+    fn is_the_operations_administrator(&self, account: Address) -> bool {
+        account == self.operations_administrator
     }
 
     /// Determines whether the specified `account` is the registered staking responsible.
@@ -246,24 +332,66 @@ impl LiquidStakingState {
         account == self.staking_responsible
     }
 
+    /// Determines whether the specified `account` is on the [`LiquidStakingState::denylist`].
+    fn is_denied(&self, account: &Address) -> bool {
+        self.denylist.get(account).unwrap_or(false)
+    }
+
+    /// Panics if the specified `account` is on the [`LiquidStakingState::denylist`].
+    fn assert_not_denied(&self, account: &Address) {
+        if self.is_denied(account) {
+            panic!(
+                "The address {account} is on the denylist, and cannot interact with the contract."
+            )
+        }
+    }
+
     /// Determines whether the specified `account` is allowed to clean [`PendingUnlock`]s.
     fn is_allowed_to_clean_up_pending_unlocks(&self, account: Address) -> bool {
-        self.is_the_administrator(account) || self.is_the_staking_responsible(account)
+        self.is_the_operations_administrator(account) || self.is_the_staking_responsible(account)
     }
 
     /// Exchange the specified amount of stake tokens to liquid tokens.
     ///
+    /// When there are no liquid tokens, the exchange rate is 1:1. Stake tokens left in the pool
+    /// (e.g. from instant unstake penalties) are assigned to the fee administrator by
+    /// [`Self::assign_leftover_stake_tokens`] before minting, such that the first depositor neither
+    /// receives zero liquid tokens, nor the leftover stake tokens.
+    ///
     /// ## Parameters
     /// * `stake_token_amount`: The amount of stake tokens to be exchanged.
     ///
     /// ## Returns
     /// The calculated amount of liquid tokens.
     fn exchange_stake_tokens_for_liquidity_tokens(&self, stake_token_amount: u128) -> u128 {
-        if self.total_pool_stake_token == 0 {
+        if self.total_pool_stake_token == 0 || self.total_pool_liquid == 0 {
             stake_token_amount
         } else {
-            stake_token_amount * self.total_pool_liquid
-                / (self.total_pool_stake_token - self.amount_of_buy_in_locked_stake_tokens)
+            mul_div(
+                stake_token_amount,
+                self.total_pool_liquid,
+                self.total_pool_stake_token - self.amount_of_buy_in_locked_stake_tokens,
+            )
+        }
+    }
+
+    /// The current exchange rate used by [`Self::exchange_stake_tokens_for_liquidity_tokens`].
+    ///
+    /// ## Returns
+    /// The pool of stake tokens (excluding buy in locked stake tokens) and the pool of liquid
+    /// tokens, or 1:1 when there are no liquid tokens.
+    fn exchange_rate(&self) -> ExchangeRate {
+        if self.total_pool_stake_token == 0 || self.total_pool_liquid == 0 {
+            ExchangeRate {
+                stake_tokens: 1,
+                liquid_tokens: 1,
+            }
+        } else {
+            ExchangeRate {
+                stake_tokens: self.total_pool_stake_token
+                    - self.amount_of_buy_in_locked_stake_tokens,
+                liquid_tokens: self.total_pool_liquid,
+            }
         }
     }
 
@@ -275,8 +403,11 @@ impl LiquidStakingState {
     /// ## Returns
     /// The calculated amount of stake tokens.
     fn exchange_liquidity_tokens_for_stake_tokens(&self, liquid_amount: u128) -> u128 {
-        liquid_amount * (self.total_pool_stake_token - self.amount_of_buy_in_locked_stake_tokens)
-            / self.total_pool_liquid
+        mul_div(
+            liquid_amount,
+            self.total_pool_stake_token - self.amount_of_buy_in_locked_stake_tokens,
+            self.total_pool_liquid,
+        )
     }
 
     /// Increase the pool of stake tokens with the specified amount.
@@ -342,12 +473,37 @@ impl LiquidStakingState {
     /// ## Parameters
     /// * `user`: The user whose owns the minted tokens.
     /// * `stake_token_amount`: The stake tokens that must be minted.
-    fn mint_liquid_tokens(&mut self, user: Address, stake_token_amount: u128) {
+    ///
+    /// Panics if no liquid tokens would be minted, as the stake tokens would otherwise be donated
+    /// to the pool.
+    ///
+    /// ## Returns
+    /// The amount of liquid tokens minted to the user.
+    fn mint_liquid_tokens(&mut self, user: Address, stake_token_amount: u128) -> u128 {
+        self.assign_leftover_stake_tokens();
         let amount_after_buy_in = self.lock_buy_in(user, stake_token_amount);
         let liquid_amount = self.exchange_stake_tokens_for_liquidity_tokens(amount_after_buy_in);
+        if liquid_amount == 0 {
+            panic!("Submitting {stake_token_amount} stake tokens would mint zero liquid tokens.")
+        }
         self.add_to_buy_in_locked_token_pool(stake_token_amount - amount_after_buy_in);
         self.add_to_stake_pool(stake_token_amount);
         self.add_liquid_tokens_to_user_balance_and_pool(user, liquid_amount);
+        liquid_amount
+    }
+
+    /// Assign the stake tokens left in the pool when there are no liquid tokens to the fee
+    /// administrator, by minting liquid tokens for them at 1:1. The leftover stake tokens can
+    /// otherwise not be claimed by anyone, and would be captured by the next depositor.
+    fn assign_leftover_stake_tokens(&mut self) {
+        let leftover_stake_tokens =
+            self.total_pool_stake_token - self.amount_of_buy_in_locked_stake_tokens;
+        if self.total_pool_liquid == 0 && leftover_stake_tokens > 0 {
+            self.add_liquid_tokens_to_user_balance_and_pool(
+                self.fee_administrator,
+                leftover_stake_tokens,
+            );
+        }
     }
 
     /// Burn liquid tokens by
@@ -364,6 +520,40 @@ impl LiquidStakingState {
         self.subtract_liquid_tokens_from_user_balance_and_pool(user, liquid_amount);
     }
 
+    /// Instantly unstake liquid tokens, by burning them and paying out the corresponding stake
+    /// tokens minus the instant unstake penalty. The penalty stays in the pool of stake tokens.
+    ///
+    /// ## Parameters
+    /// * `user`: The user who requests to unstake.
+    /// * `liquid_amount`: The amount of liquid tokens to unstake.
+    /// * `current_time`: The block production time.
+    ///
+    /// ## Returns
+    /// The amount of stake tokens to pay out to the user.
+    fn instant_unstake(&mut self, user: Address, liquid_amount: u128, current_time: u64) -> u128 {
+        self.assert_whether_user_have_enough_liquidity(user, liquid_amount, current_time);
+
+        let stake_token_amount = self.exchange_liquidity_tokens_for_stake_tokens(liquid_amount);
+        let penalty =
+            stake_token_amount * u128::from(self.instant_unstake_penalty_per_mille) / 1000;
+        let payout = stake_token_amount - penalty;
+
+        let available = self
+            .stake_token_balance
+            .saturating_sub(self.reserved_stake_tokens(current_time));
+        if payout > available {
+            panic!(
+                "Cannot instantly unstake. Requires {} stake tokens, but only {} are available on the contract.",
+                payout, available
+            )
+        }
+
+        self.burn_liquid_tokens(user, liquid_amount, payout);
+        self.subtract_from_stake_token_balance(payout);
+
+        payout
+    }
+
     /// Adding an unlock request to [`PendingUnlock`]s, if the contract and
     /// the specified user has enough liquidity.
     ///
@@ -371,7 +561,15 @@ impl LiquidStakingState {
     /// * `user`: The user who requests to unlock.
     /// * `liquid_amount`: The amount of liquid tokens to be unlocked.
     /// * `created_at`: The block production time, when the unlock was requested.
-    fn add_to_pending_unlocks(&mut self, user: Address, liquid_amount: u128, created_at: u64) {
+    ///
+    /// ## Returns
+    /// The created [`PendingUnlock`].
+    fn add_to_pending_unlocks(
+        &mut self,
+        user: Address,
+        liquid_amount: u128,
+        created_at: u64,
+    ) -> PendingUnlock {
         self.assert_whether_user_have_enough_liquidity(user, liquid_amount, created_at);
 
         let new_pending_unlock = PendingUnlock {
@@ -386,8 +584,10 @@ impl LiquidStakingState {
         self.pending_unlock_id_counter += 1;
 
         let mut unlocks = self.pending_unlocks.get(&user).unwrap_or_default();
-        unlocks.push(new_pending_unlock);
+        unlocks.push(new_pending_unlock.clone());
         self.pending_unlocks.insert(user, unlocks);
+
+        new_pending_unlock
     }
 
     /// Assert that the specified user has enough liquidity
@@ -483,6 +683,45 @@ impl LiquidStakingState {
         }
     }
 
+    /// Calculate the amount of stake tokens reserved for the non-expired [`PendingUnlock`]s of all
+    /// users, i.e. the stake tokens that must remain on the contract for [`redeem()`]. Stake tokens
+    /// of expired pending unlocks go back into staking, and are not reserved.
+    ///
+    /// Iterates over all pending unlocks.
+    ///
+    /// ## Parameters
+    /// * `current_time`: The block production time.
+    ///
+    /// ## Returns
+    /// The amount of reserved stake tokens.
+    fn reserved_stake_tokens(&self, current_time: u64) -> u128 {
+        self.pending_unlocks
+            .iter()
+            .flat_map(|(_, user_pending_unlocks)| user_pending_unlocks)
+            .filter(|pending_unlock| !pending_unlock.is_expired(current_time))
+            .map(|pending_unlock| pending_unlock.stake_token_amount)
+            .sum()
+    }
+
+    /// Calculate the amount of stake tokens the specified user can currently redeem, i.e. the sum
+    /// of stake tokens over the user's [`PendingUnlock`]s that are within the redeem period.
+    ///
+    /// ## Parameters
+    /// * `user`: The user to calculate the redeemable amount for.
+    /// * `current_time`: The block production time.
+    ///
+    /// ## Returns
+    /// The amount of stake tokens that [`redeem()`] would pay out at `current_time`.
+    pub fn redeemable_stake_tokens(&self, user: &Address, current_time: u64) -> u128 {
+        self.pending_unlocks
+            .get(user)
+            .unwrap_or_default()
+            .iter()
+            .filter(|pending_unlock| pending_unlock.is_within_redeem_period(current_time))
+            .map(|pending_unlock| pending_unlock.stake_token_amount)
+            .sum()
+    }
+
     /// Redeem all redeemable [`PendingUnlock`]s for the specified user.
     ///
     /// ## Parameters
@@ -526,10 +765,17 @@ impl LiquidStakingState {
     }
 
     /// Mint all stake tokens in the buy_in_tokens, and reset the buy_in_tokens map.
+    ///
+    /// Locked amounts that are worth less than a single liquid token are left in the pool of
+    /// stake tokens, rather than preventing the buy-in from being disabled.
     fn exchange_staking_tokens_from_buy_in(&mut self) {
         let mut accounts_to_reset = Vec::new();
         for (token_owner, amount_locked) in self.buy_in_tokens.iter() {
-            self.mint_liquid_tokens(token_owner, amount_locked);
+            if self.exchange_stake_tokens_for_liquidity_tokens(amount_locked) > 0 {
+                self.mint_liquid_tokens(token_owner, amount_locked);
+            } else {
+                self.add_to_stake_pool(amount_locked);
+            }
             accounts_to_reset.push(token_owner);
         }
 
@@ -563,10 +809,45 @@ impl LiquidStakingState {
     ///
     /// ## Returns
     /// The amount of stake tokens to be locked by the buy in.
-    fn calculate_buy_in_amount(&mut self, amount_submitted: u128) -> u128 {
+    fn calculate_buy_in_amount(&self, amount_submitted: u128) -> u128 {
         amount_submitted * self.buy_in_percentage / 100
     }
 
+    /// Calculate the amount of liquid tokens that submitting the specified amount of stake tokens
+    /// would currently mint, after the buy in has been subtracted.
+    ///
+    /// ## Parameters
+    /// * `stake_token_amount`: The amount of stake tokens to submit.
+    ///
+    /// ## Returns
+    /// The amount of liquid tokens that would be minted.
+    fn liquid_tokens_for_submit(&self, stake_token_amount: u128) -> u128 {
+        let buy_in_amount = if self.buy_in_enabled {
+            self.calculate_buy_in_amount(stake_token_amount)
+        } else {
+            0
+        };
+        self.exchange_stake_tokens_for_liquidity_tokens(stake_token_amount - buy_in_amount)
+    }
+
+    /// Assert that submitting the specified amount of stake tokens would currently mint a
+    /// non-zero amount of liquid tokens, and at least `min_liquid_out` liquid tokens.
+    ///
+    /// ## Parameters
+    /// * `stake_token_amount`: The amount of stake tokens to submit.
+    /// * `min_liquid_out`: The smallest amount of liquid tokens the user accepts to receive.
+    fn assert_submit_mints_liquid_tokens(&self, stake_token_amount: u128, min_liquid_out: u128) {
+        let liquid_amount = self.liquid_tokens_for_submit(stake_token_amount);
+        if liquid_amount == 0 {
+            panic!("Submitting {stake_token_amount} stake tokens would mint zero liquid tokens.")
+        }
+        if liquid_amount < min_liquid_out {
+            panic!(
+                "Submitting {stake_token_amount} stake tokens would mint {liquid_amount} liquid tokens, which is below the minimum of {min_liquid_out}."
+            )
+        }
+    }
+
     /// If buy in is enabled, then lock an amount of the submitted stake tokens.
     ///
     /// ## Parameters
@@ -595,10 +876,13 @@ impl LiquidStakingState {
 /// * `_context`: initial context.
 /// * `token_for_staking`: the address of the token used for liquid staking.
 /// * `staking_responsible`: the address of the account responsible for staking.
-/// * `administrator`: the address of the account responsible administrative tasks.
+/// * `fee_administrator`: the address of the account responsible for the buy-in and the instant unstake penalty.
+/// * `operations_administrator`: the address of the account responsible for cleaning up pending unlocks, the denylist and upgrades.
 /// * `length_of_cooldown_period`: Number of milliseconds (ms) from the unlock request was registered until the [`PendingUnlock`] can be redeemed.
 /// * `length_of_redeem_period`: Number of milliseconds (ms) from the [`PendingUnlock`] becomes redeemable until the [`PendingUnlock`] expires.
 /// * `initial_buy_in_percentage`: The initial buy-in percentage used when user submits tokens.
+/// * `instant_unstake_penalty_per_mille`: The per mille withheld when using [`instant_unstake()`].
+/// * `minimum_submit_amount`: The smallest amount of stake tokens that can be submitted at once.
 /// * `liquid_token_name`: The name for the liquid token.  e.g. "LiquidMpcStakingToken".
 /// * `liquid_token_symbol`:  The symbol of the token. E.g. "LMPCST".
 /// * `decimals`: The number of decimals the token uses - e.g. 8,
@@ -610,19 +894,24 @@ pub fn initialize(
     _context: ContractContext,
     token_for_staking: Address,
     staking_responsible: Address,
-    administrator: Address,
+    fee_administrator: Address,
+    operations_administrator: Address,
     length_of_cooldown_period: u64,
     length_of_redeem_period: u64,
     initial_buy_in_percentage: u128,
+    instant_unstake_penalty_per_mille: u16,
+    minimum_submit_amount: u128,
     liquid_token_name: String,
     liquid_token_symbol: String,
     decimals: u8,
 ) -> LiquidStakingState {
+    assert_is_per_mille(instant_unstake_penalty_per_mille);
     LiquidStakingState {
         token_for_staking,
         stake_token_balance: 0,
         staking_responsible,
-        administrator,
+        fee_administrator,
+        operations_administrator,
         total_pool_stake_token: 0,
         total_pool_liquid: 0,
         amount_of_buy_in_locked_stake_tokens: 0,
@@ -638,13 +927,17 @@ pub fn initialize(
         buy_in_percentage: initial_buy_in_percentage,
         buy_in_enabled: true,
         pending_unlock_id_counter: INITIAL_PENDING_UNLOCK_ID,
+        instant_unstake_penalty_per_mille,
+        denylist: AvlTreeMap::new(),
+        minimum_submit_amount,
     }
 }
 
 /// Transfers `amount` of liquid tokens to address `to` from the caller.
 ///
 /// The function throws if the message caller's account
-/// balance does not have enough tokens to spend.
+/// balance does not have enough tokens to spend, or if the caller is on the
+/// [denylist](LiquidStakingState::denylist).
 /// If the sender's account goes to 0, the sender's address is removed from state.
 ///
 /// # Parameters:
@@ -661,6 +954,7 @@ pub fn transfer(
     to: Address,
     amount: u128,
 ) -> (LiquidStakingState, Vec<EventGroup>) {
+    state.assert_not_denied(&context.sender);
     state
         .liquid_token_state
         .transfer(context.sender, to, amount);
@@ -673,6 +967,7 @@ pub fn transfer(
 /// account through the `approve` action.
 /// The function throws if the message caller's account
 /// balance does not have enough tokens to spend, or if the tokens were not approved.
+/// Neither the caller nor `from` may be on the [denylist](LiquidStakingState::denylist).
 ///
 /// # Parameters:
 ///
@@ -690,6 +985,8 @@ pub fn transfer_from(
     to: Address,
     amount: u128,
 ) -> (LiquidStakingState, Vec<EventGroup>) {
+    state.assert_not_denied(&context.sender);
+    state.assert_not_denied(&from);
     state
         .liquid_token_state
         .transfer_from(context.sender, from, to, amount);
@@ -726,23 +1023,41 @@ pub fn approve(
 /// This requires that this contract is allowed to do the transfer on behalf of the user
 /// through the `approve` action on the token contract.
 /// The function throws if the message caller's account balance does not have enough tokens
-/// to spend, or if the tokens were not approved.
+/// to spend, if the tokens were not approved, if the caller is on the
+/// [denylist](LiquidStakingState::denylist), if the amount is below the
+/// [minimum submit amount](LiquidStakingState::minimum_submit_amount), or if the amount would
+/// mint zero liquid tokens, or less than `min_liquid_out` liquid tokens, at the current exchange
+/// rate.
+///
+/// The exchange rate may change before the stake tokens have been transferred, for example when
+/// rewards are accrued. If [`submit_callback()`] would then mint less than `min_liquid_out` liquid
+/// tokens, no liquid tokens are minted, and the already transferred stake tokens are refunded.
 ///
 /// # Parameters:
 ///
 ///  * `context`: The contract context containing sender and chain information.
 ///  * `state`: The current state of the contract.
 ///  * `stake_token_amount`: The amount of stake tokens to submit.
+///  * `min_liquid_out`: The smallest amount of liquid tokens the user accepts to receive.
 ///
 #[action(shortname = 0x10)]
 pub fn submit(
     context: ContractContext,
     state: LiquidStakingState,
     stake_token_amount: u128,
+    min_liquid_out: u128,
 ) -> (LiquidStakingState, Vec<EventGroup>) {
+    state.assert_not_denied(&context.sender);
     if stake_token_amount == 0 {
         panic!("Cannot submit zero tokens for liquid staking.")
     }
+    if stake_token_amount < state.minimum_submit_amount {
+        panic!(
+            "Cannot submit {} tokens, which is below the minimum submit amount of {}.",
+            stake_token_amount, state.minimum_submit_amount
+        )
+    }
+    state.assert_submit_mints_liquid_tokens(stake_token_amount, min_liquid_out);
 
     let mut event_group = EventGroup::builder();
     interact_mpc20::MPC20Contract::at_address(state.token_for_staking).transfer_from(
@@ -752,13 +1067,21 @@ pub fn submit(
         stake_token_amount,
     );
     event_group
-        .with_callback_rpc(submit_callback::rpc(stake_token_amount))
-        .with_cost(600)
+        .with_callback_rpc(submit_callback::rpc(stake_token_amount, min_liquid_out))
+        .with_cost(SUBMIT_CALLBACK_GAS_COST)
         .done();
     (state, vec![event_group.build()])
 }
 
 /// Handles callback from [`submit()`]. <br>
+/// Emits a [`submitted_event()`] with the amount of liquid tokens minted to the user, and a
+/// [`minted_event()`] with the exchange rate the liquid tokens were minted at, which confirms the
+/// outcome of the submission even when multiple submissions are in flight.
+///
+/// If the exchange rate has changed since [`submit()`], such that zero or less than
+/// `min_liquid_out` liquid tokens would be minted, the stake tokens have already been transferred
+/// to the contract. Panicking would leave them in the contract without any liquid tokens minted,
+/// so instead they are transferred back to the user, and nothing is minted.
 ///
 /// # Parameters:
 ///
@@ -766,6 +1089,7 @@ pub fn submit(
 /// * `callback_context`: The callbackContext.
 /// * `state`: The current state of the contract.
 /// * `stake_token_amount`: The amount of the stake token the user submits to the contract.
+/// * `min_liquid_out`: The smallest amount of liquid tokens the user accepts to receive.
 ///
 #[callback(shortname = 0x10)]
 pub fn submit_callback(
@@ -773,13 +1097,42 @@ pub fn submit_callback(
     callback_context: CallbackContext,
     mut state: LiquidStakingState,
     stake_token_amount: u128,
-) -> LiquidStakingState {
+    min_liquid_out: u128,
+) -> (LiquidStakingState, Vec<EventGroup>) {
     assert!(callback_context.success, "Transfer did not succeed");
 
-    state.mint_liquid_tokens(context.sender, stake_token_amount);
+    let liquid_out = state.liquid_tokens_for_submit(stake_token_amount);
+    if liquid_out == 0 || liquid_out < min_liquid_out {
+        let mut event_group = EventGroup::builder();
+        interact_mpc20::MPC20Contract::at_address(state.token_for_staking).transfer(
+            &mut event_group,
+            &context.sender,
+            stake_token_amount,
+        );
+        return (state, vec![event_group.build()]);
+    }
+
+    let exchange_rate = state.exchange_rate();
+    let liquid_amount = state.mint_liquid_tokens(context.sender, stake_token_amount);
     state.add_to_stake_token_balance(stake_token_amount);
 
-    state
+    let mut event_group = EventGroup::builder();
+    add_submitted_event(
+        &mut event_group,
+        &context,
+        context.sender,
+        stake_token_amount,
+        liquid_amount,
+    );
+    add_minted_event(
+        &mut event_group,
+        &context,
+        context.sender,
+        stake_token_amount,
+        liquid_amount,
+        exchange_rate,
+    );
+    (state, vec![event_group.build()])
 }
 
 /// Withdraws an amount of the stake tokens from this contract.
@@ -831,7 +1184,9 @@ pub fn withdraw(
 
 /// Accrue rewards by adding the rewarded amount of the stake tokens to the pool.
 ///
-/// Only the staking responsible is allowed to accrue rewards to the contract.
+/// Only the staking responsible is allowed to accrue rewards to the contract, and only when there
+/// are liquid tokens to receive the rewards. Rewards accrued to an empty pool would otherwise
+/// skew the exchange rate for the first subsequent submission.
 ///
 /// # Parameters:
 ///
@@ -854,6 +1209,9 @@ pub fn accrue_rewards(
     if stake_token_amount == 0 {
         panic!("Cannot accrue rewards of zero tokens.")
     }
+    if state.total_pool_liquid == 0 {
+        panic!("Cannot accrue rewards when there are no liquid tokens to receive them.")
+    }
 
     state.add_to_stake_pool(stake_token_amount);
 
@@ -862,8 +1220,9 @@ pub fn accrue_rewards(
 
 /// Request unlock of liquid tokens.
 ///
-/// Can be cancelled by user in [`cancel_pending_unlock()`] or by administrator through
-/// [`clean_up_pending_unlocks()`].
+/// Can be cancelled by user in [`cancel_pending_unlock()`] or by the operations administrator
+/// through [`clean_up_pending_unlocks()`]. Users on the [denylist](LiquidStakingState::denylist)
+/// cannot request unlocks. Emits an [`unlock_requested_event()`] with the redeem period of the unlock.
 ///
 /// # Parameters:
 ///
@@ -876,18 +1235,21 @@ pub fn request_unlock(
     context: ContractContext,
     mut state: LiquidStakingState,
     liquid_amount: u128,
-) -> LiquidStakingState {
+) -> (LiquidStakingState, Vec<EventGroup>) {
+    state.assert_not_denied(&context.sender);
     if liquid_amount == 0 {
         panic!("Cannot unlock zero tokens.")
     }
 
-    state.add_to_pending_unlocks(
+    let pending_unlock = state.add_to_pending_unlocks(
         context.sender,
         liquid_amount,
         context.block_production_time as u64,
     );
 
-    state
+    let mut event_group = EventGroup::builder();
+    add_unlock_requested_event(&mut event_group, &context, context.sender, &pending_unlock);
+    (state, vec![event_group.build()])
 }
 
 /// Deposit an amount of the stake tokens to this contract.
@@ -928,7 +1290,7 @@ pub fn deposit(
     );
     event_group
         .with_callback_rpc(deposit_callback::rpc(stake_token_amount))
-        .with_cost(600)
+        .with_cost(TRANSFER_CALLBACK_GAS_COST)
         .done();
 
     (state, vec![event_group.build()])
@@ -958,6 +1320,7 @@ pub fn deposit_callback(
 }
 
 /// Redeem all liquid tokens from the users [`PendingUnlock`]s that are in the redeem period.
+/// Emits a [`redeemed_event()`] with the amount of stake tokens returned to the user.
 ///
 /// # Parameters:
 ///
@@ -977,12 +1340,18 @@ pub fn redeem(
         &context.sender,
         stake_token_amount,
     );
+    add_redeemed_event(
+        &mut event_group,
+        &context,
+        context.sender,
+        stake_token_amount,
+    );
     (state, vec![event_group.build()])
 }
 
 /// Change the percentage of buy-in locked tokens taken from all future submission.
 ///
-/// Only the administrator is allowed to change the buy in percentage.
+/// Only the fee administrator is allowed to change the buy in percentage.
 ///
 /// # Parameters:
 ///
@@ -996,10 +1365,10 @@ pub fn change_buy_in(
     mut state: LiquidStakingState,
     new_buy_in_percentage: u128,
 ) -> LiquidStakingState {
-    if !state.is_the_administrator(context.sender) {
+    if !state.is_the_fee_administrator(context.sender) {
         panic!(
-            "Cannot change the buy-in percentage. Only the registered administrator (at address: {}) can change the buy-in percentage.",
-            state.administrator
+            "Cannot change the buy-in percentage. Only the registered fee administrator (at address: {}) can change the buy-in percentage.",
+            state.fee_administrator
         )
     }
 
@@ -1010,7 +1379,7 @@ pub fn change_buy_in(
 /// Disable the buy-in, such that all submission will not get a percentage locked anymore.
 /// Unlock all currently buy-in locked tokens and exchange the tokens to the liquid token.
 ///
-/// Only the administrator is allowed to disable the buy in.
+/// Only the fee administrator is allowed to disable the buy in.
 ///
 /// # Parameters:
 ///
@@ -1022,10 +1391,10 @@ pub fn disable_buy_in(
     context: ContractContext,
     mut state: LiquidStakingState,
 ) -> LiquidStakingState {
-    if !state.is_the_administrator(context.sender) {
+    if !state.is_the_fee_administrator(context.sender) {
         panic!(
-            "Cannot disable buy-in. Only the registered administrator (at address: {}) can disable buy-in.",
-            state.administrator
+            "Cannot disable buy-in. Only the registered fee administrator (at address: {}) can disable buy-in.",
+            state.fee_administrator
         )
     }
     if !state.buy_in_enabled {
@@ -1039,7 +1408,8 @@ pub fn disable_buy_in(
 /// Clean up by removing all expired [`PendingUnlock`]s.
 /// If a user does not redeem a [`PendingUnlock`] within the redeem period, then the [`PendingUnlock`] expires.
 ///
-/// Only the administrator is allowed to clean up the [`PendingUnlock`]s.
+/// Only the operations administrator and the staking responsible are allowed to clean up the
+/// [`PendingUnlock`]s.
 ///
 /// # Parameters:
 ///
@@ -1053,8 +1423,8 @@ pub fn clean_up_pending_unlocks(
 ) -> LiquidStakingState {
     if !state.is_allowed_to_clean_up_pending_unlocks(context.sender) {
         panic!(
-            "Cannot clean up pending unlocks. Only the registered administrator (at address: {}) or staking responsible (at address: {}) can clean up pending unlocks.",
-            state.administrator, state.staking_responsible
+            "Cannot clean up pending unlocks. Only the registered operations administrator (at address: {}) or staking responsible (at address: {}) can clean up pending unlocks.",
+            state.operations_administrator, state.staking_responsible
         )
     }
     state.clean_up_pending_unlocks(context.block_production_time as u64);
@@ -1078,3 +1448,316 @@ pub fn cancel_pending_unlock(
     state.remove_pending_unlock(context.sender, pending_unlock_id);
     state
 }
+
+/// Instantly unstake liquid tokens, without waiting for the cooldown period.
+///
+/// The liquid tokens are burned immediately, and the corresponding stake tokens minus a penalty of
+/// [`LiquidStakingState::instant_unstake_penalty_per_mille`] are transferred to the user. The
+/// penalty stays in the pool, benefiting the remaining stakers.
+///
+/// The contract must hold enough stake tokens to cover the payout, in addition to the stake tokens
+/// reserved for the non-expired pending unlocks of all users. Addresses on the
+/// [`LiquidStakingState::denylist`] cannot unstake.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `liquid_amount`: The amount of liquid tokens to unstake.
+///
+#[action(shortname = 0x1A)]
+pub fn instant_unstake(
+    context: ContractContext,
+    mut state: LiquidStakingState,
+    liquid_amount: u128,
+) -> (LiquidStakingState, Vec<EventGroup>) {
+    state.assert_not_denied(&context.sender);
+    if liquid_amount == 0 {
+        panic!("Cannot instantly unstake zero tokens.")
+    }
+
+    let stake_token_amount = state.instant_unstake(
+        context.sender,
+        liquid_amount,
+        context.block_production_time as u64,
+    );
+
+    let mut event_group = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(state.token_for_staking).transfer(
+        &mut event_group,
+        &context.sender,
+        stake_token_amount,
+    );
+    (state, vec![event_group.build()])
+}
+
+/// Change the penalty withheld when users unstake using [`instant_unstake()`].
+///
+/// Only the fee administrator is allowed to change the instant unstake penalty.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `new_penalty_per_mille`: The new penalty, between 0‰ and 1000‰.
+///
+#[action(shortname = 0x1B)]
+pub fn change_instant_unstake_penalty(
+    context: ContractContext,
+    mut state: LiquidStakingState,
+    new_penalty_per_mille: u16,
+) -> LiquidStakingState {
+    if !state.is_the_fee_administrator(context.sender) {
+        panic!(
+            "Cannot change the instant unstake penalty. Only the registered fee administrator (at address: {}) can change the instant unstake penalty.",
+            state.fee_administrator
+        )
+    }
+    assert_is_per_mille(new_penalty_per_mille);
+
+    state.instant_unstake_penalty_per_mille = new_penalty_per_mille;
+    state
+}
+
+/// Add an address to the [denylist](LiquidStakingState::denylist), denying it from submitting,
+/// transferring and requesting unlocks. The liquid tokens of the address are kept.
+///
+/// Only the operations administrator is allowed to change the denylist.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `address`: The address to deny.
+///
+#[action(shortname = 0x1C)]
+pub fn add_to_denylist(
+    context: ContractContext,
+    mut state: LiquidStakingState,
+    address: Address,
+) -> LiquidStakingState {
+    if !state.is_the_operations_administrator(context.sender) {
+        panic!(
+            "Cannot change the denylist. Only the registered operations administrator (at address: {}) can change the denylist.",
+            state.operations_administrator
+        )
+    }
+
+    state.denylist.insert(address, true);
+    state
+}
+
+/// Remove an address from the [denylist](LiquidStakingState::denylist), allowing it to interact
+/// with the contract again.
+///
+/// Only the operations administrator is allowed to change the denylist.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `address`: The address to allow again.
+///
+#[action(shortname = 0x1D)]
+pub fn remove_from_denylist(
+    context: ContractContext,
+    mut state: LiquidStakingState,
+    address: Address,
+) -> LiquidStakingState {
+    if !state.is_the_operations_administrator(context.sender) {
+        panic!(
+            "Cannot change the denylist. Only the registered operations administrator (at address: {}) can change the denylist.",
+            state.operations_administrator
+        )
+    }
+
+    state.denylist.remove(&address);
+    state
+}
+
+/// Adds a [`submitted_event()`] to the given event group.
+///
+/// # Parameters:
+///
+///  * `event_group`: The event group to add the event to.
+///  * `context`: The contract context of the emitting action.
+///  * `user`: The user who submitted stake tokens.
+///  * `stake_token_amount`: The amount of stake tokens submitted.
+///  * `liquid_amount`: The amount of liquid tokens minted to the user.
+fn add_submitted_event(
+    event_group: &mut EventGroupBuilder,
+    context: &ContractContext,
+    user: Address,
+    stake_token_amount: u128,
+    liquid_amount: u128,
+) {
+    event_group
+        .call(context.contract_address, SHORTNAME_SUBMITTED_EVENT)
+        .argument(user)
+        .argument(stake_token_amount)
+        .argument(liquid_amount)
+        .done();
+}
+
+/// Adds a [`minted_event()`] to the given event group.
+///
+/// # Parameters:
+///
+///  * `event_group`: The event group to add the event to.
+///  * `context`: The contract context of the emitting action.
+///  * `user`: The user who received the liquid tokens.
+///  * `stake_in`: The amount of stake tokens submitted.
+///  * `liquid_out`: The amount of liquid tokens minted to the user.
+///  * `exchange_rate`: The exchange rate the liquid tokens were minted at.
+fn add_minted_event(
+    event_group: &mut EventGroupBuilder,
+    context: &ContractContext,
+    user: Address,
+    stake_in: u128,
+    liquid_out: u128,
+    exchange_rate: ExchangeRate,
+) {
+    event_group
+        .call(context.contract_address, SHORTNAME_MINTED_EVENT)
+        .argument(user)
+        .argument(stake_in)
+        .argument(liquid_out)
+        .argument(exchange_rate)
+        .done();
+}
+
+/// Adds an [`unlock_requested_event()`] for the given [`PendingUnlock`] to the given event group.
+///
+/// # Parameters:
+///
+///  * `event_group`: The event group to add the event to.
+///  * `context`: The contract context of the emitting action.
+///  * `user`: The user who requested the unlock.
+///  * `pending_unlock`: The created [`PendingUnlock`].
+fn add_unlock_requested_event(
+    event_group: &mut EventGroupBuilder,
+    context: &ContractContext,
+    user: Address,
+    pending_unlock: &PendingUnlock,
+) {
+    event_group
+        .call(context.contract_address, SHORTNAME_UNLOCK_REQUESTED_EVENT)
+        .argument(user)
+        .argument(pending_unlock.liquid_amount)
+        .argument(pending_unlock.cooldown_ends_at)
+        .argument(pending_unlock.expires_at)
+        .done();
+}
+
+/// Adds a [`redeemed_event()`] to the given event group.
+///
+/// # Parameters:
+///
+///  * `event_group`: The event group to add the event to.
+///  * `context`: The contract context of the emitting action.
+///  * `user`: The user who redeemed.
+///  * `stake_token_amount`: The amount of stake tokens returned to the user.
+fn add_redeemed_event(
+    event_group: &mut EventGroupBuilder,
+    context: &ContractContext,
+    user: Address,
+    stake_token_amount: u128,
+) {
+    event_group
+        .call(context.contract_address, SHORTNAME_REDEEMED_EVENT)
+        .argument(user)
+        .argument(stake_token_amount)
+        .done();
+}
+
+/// Event emitted by [`submit_callback()`], when stake tokens have been submitted.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `_user`: The user who submitted stake tokens.
+///  * `_stake_token_amount`: The amount of stake tokens submitted.
+///  * `_liquid_amount`: The amount of liquid tokens minted to the user.
+///
+#[action(shortname = 0x1E)]
+pub fn submitted_event(
+    context: ContractContext,
+    state: LiquidStakingState,
+    _user: Address,
+    _stake_token_amount: u128,
+    _liquid_amount: u128,
+) -> LiquidStakingState {
+    events::assert_event_sent_by_contract(&context, EVENT_NOT_SENT_BY_CONTRACT_ERROR);
+    state
+}
+
+/// Event emitted by [`request_unlock()`], when an unlock of liquid tokens has been requested.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `_user`: The user who requested the unlock.
+///  * `_liquid_amount`: The amount of liquid tokens to unlock.
+///  * `_cooldown_ends_at`: The time at which the unlock can be redeemed.
+///  * `_expires_at`: The time at which the unlock expires.
+///
+#[action(shortname = 0x1F)]
+pub fn unlock_requested_event(
+    context: ContractContext,
+    state: LiquidStakingState,
+    _user: Address,
+    _liquid_amount: u128,
+    _cooldown_ends_at: u64,
+    _expires_at: u64,
+) -> LiquidStakingState {
+    events::assert_event_sent_by_contract(&context, EVENT_NOT_SENT_BY_CONTRACT_ERROR);
+    state
+}
+
+/// Event emitted by [`redeem()`], when pending unlocks have been redeemed.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `_user`: The user who redeemed.
+///  * `_stake_token_amount`: The amount of stake tokens returned to the user.
+///
+#[action(shortname = 0x20)]
+pub fn redeemed_event(
+    context: ContractContext,
+    state: LiquidStakingState,
+    _user: Address,
+    _stake_token_amount: u128,
+) -> LiquidStakingState {
+    events::assert_event_sent_by_contract(&context, EVENT_NOT_SENT_BY_CONTRACT_ERROR);
+    state
+}
+
+/// Event emitted by [`submit_callback()`], when liquid tokens have been minted for submitted stake
+/// tokens.
+///
+/// # Parameters:
+///
+///  * `context`: The contract context containing sender and chain information.
+///  * `state`: The current state of the contract.
+///  * `_user`: The user who received the liquid tokens.
+///  * `_stake_in`: The amount of stake tokens submitted.
+///  * `_liquid_out`: The amount of liquid tokens minted to the user.
+///  * `_exchange_rate`: The exchange rate the liquid tokens were minted at, before any buy in was
+///    subtracted from the stake tokens.
+///
+#[action(shortname = 0x21)]
+pub fn minted_event(
+    context: ContractContext,
+    state: LiquidStakingState,
+    _user: Address,
+    _stake_in: u128,
+    _liquid_out: u128,
+    _exchange_rate: ExchangeRate,
+) -> LiquidStakingState {
+    events::assert_event_sent_by_contract(&context, EVENT_NOT_SENT_BY_CONTRACT_ERROR);
+    state
+}
@@ -0,0 +1,642 @@
+use super::*;
+use defi_common::test_util::{account, context, contract};
+
+fn state() -> LiquidStakingState {
+    LiquidStakingState {
+        buy_in_enabled: false,
+        ..initialize(
+            context(account(1), 0),
+            contract(1),
+            account(2),
+            account(3),
+            account(8),
+            100,
+            100,
+            0,
+            0,
+            0,
+            "Liquid".to_string(),
+            "LIQ".to_string(),
+            4,
+        )
+    }
+}
+
+fn pending_unlock(
+    state: &LiquidStakingState,
+    id: PendingUnlockId,
+    stake_token_amount: u128,
+    created_at: u64,
+) -> PendingUnlock {
+    PendingUnlock {
+        id,
+        liquid_amount: stake_token_amount,
+        stake_token_amount,
+        created_at,
+        cooldown_ends_at: created_at + state.length_of_cooldown_period,
+        expires_at: created_at + state.length_of_cooldown_period + state.length_of_redeem_period,
+    }
+}
+
+#[test]
+fn redeemable_stake_tokens_only_counts_redeemable_unlocks() {
+    let mut state = state();
+    let user = account(10);
+    let unlocks = vec![
+        // Expired at 200.
+        pending_unlock(&state, 1, 10, 0),
+        // Redeemable between 150 and 250.
+        pending_unlock(&state, 2, 20, 50),
+        // Redeemable between 160 and 260.
+        pending_unlock(&state, 3, 40, 60),
+        // Redeemable from 300.
+        pending_unlock(&state, 4, 80, 200),
+    ];
+    state.pending_unlocks.insert(user, unlocks);
+
+    assert_eq!(state.redeemable_stake_tokens(&user, 220), 60);
+    assert_eq!(state.redeemable_stake_tokens(&user, 90), 0);
+    assert_eq!(state.redeemable_stake_tokens(&user, 120), 10);
+    assert_eq!(state.redeemable_stake_tokens(&user, 155), 30);
+    assert_eq!(state.redeemable_stake_tokens(&user, 350), 80);
+}
+
+#[test]
+fn redeemable_stake_tokens_for_user_without_unlocks() {
+    let mut state = state();
+    let unlocks = vec![pending_unlock(&state, 1, 10, 0)];
+    state.pending_unlocks.insert(account(10), unlocks);
+
+    assert_eq!(state.redeemable_stake_tokens(&account(11), 150), 0);
+    assert_eq!(state.redeemable_stake_tokens(&account(10), 150), 10);
+}
+
+#[test]
+fn exchange_is_one_to_one_when_pool_has_no_liquid_tokens() {
+    let mut state = state();
+    state.total_pool_stake_token = 10;
+
+    assert_eq!(state.exchange_stake_tokens_for_liquidity_tokens(50), 50);
+
+    state.mint_liquid_tokens(account(10), 50);
+    assert_eq!(state.total_pool_stake_token, 60);
+    assert_eq!(state.total_pool_liquid, 60);
+    assert_eq!(state.liquid_token_state.balance_of(&account(10)), 50);
+    // The leftover stake tokens are assigned to the fee administrator.
+    assert_eq!(state.liquid_token_state.balance_of(&account(3)), 10);
+}
+
+#[test]
+fn exchange_uses_pool_rate_when_pool_has_liquid_tokens() {
+    let mut state = state();
+    state.mint_liquid_tokens(account(10), 100);
+    state.add_to_stake_pool(100);
+
+    assert_eq!(state.exchange_stake_tokens_for_liquidity_tokens(50), 25);
+}
+
+#[test]
+fn exchange_does_not_overflow_for_large_pools() {
+    let mut state = state();
+    let large_amount = u128::MAX / 4;
+    state.total_pool_liquid = large_amount;
+    state.total_pool_stake_token = large_amount * 2;
+
+    assert_eq!(
+        state.exchange_stake_tokens_for_liquidity_tokens(large_amount),
+        large_amount / 2
+    );
+    assert_eq!(
+        state.exchange_liquidity_tokens_for_stake_tokens(large_amount),
+        large_amount * 2
+    );
+}
+
+#[test]
+fn submit_callback_emits_submitted_event() {
+    let mut state = state();
+    let user = account(10);
+    // Exchange rate of two stake tokens per liquid token.
+    state.mint_liquid_tokens(account(11), 100);
+    state.add_to_stake_pool(100);
+
+    let ctx = context(user, 0);
+    let callback_context = CallbackContext {
+        success: true,
+        results: vec![],
+    };
+    let (state, events) = submit_callback(context(user, 0), callback_context, state, 50, 25);
+
+    assert_eq!(state.liquid_token_state.balance_of(&user), 25);
+    let mut expected = EventGroup::builder();
+    add_submitted_event(&mut expected, &ctx, user, 50, 25);
+    add_minted_event(
+        &mut expected,
+        &ctx,
+        user,
+        50,
+        25,
+        ExchangeRate {
+            stake_tokens: 200,
+            liquid_tokens: 100,
+        },
+    );
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn minted_event_liquid_out_equals_balance_delta() {
+    let mut state = state();
+    let user = account(10);
+    state.mint_liquid_tokens(user, 60);
+    state.add_to_stake_pool(90);
+    let balance_before = state.liquid_token_state.balance_of(&user);
+
+    let ctx = context(user, 0);
+    let callback_context = CallbackContext {
+        success: true,
+        results: vec![],
+    };
+    let (state, events) = submit_callback(context(user, 0), callback_context, state, 100, 1);
+
+    // 100 stake tokens at 150 stake tokens per 60 liquid tokens.
+    let liquid_out = state.liquid_token_state.balance_of(&user) - balance_before;
+    assert_eq!(liquid_out, 40);
+    let mut expected = EventGroup::builder();
+    add_submitted_event(&mut expected, &ctx, user, 100, liquid_out);
+    add_minted_event(
+        &mut expected,
+        &ctx,
+        user,
+        100,
+        liquid_out,
+        ExchangeRate {
+            stake_tokens: 150,
+            liquid_tokens: 60,
+        },
+    );
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn exchange_rate_is_one_to_one_without_liquid_tokens() {
+    let mut state = state();
+    state.total_pool_stake_token = 10;
+    assert_eq!(
+        state.exchange_rate(),
+        ExchangeRate {
+            stake_tokens: 1,
+            liquid_tokens: 1,
+        }
+    );
+}
+
+#[test]
+fn request_unlock_emits_unlock_requested_event() {
+    let mut state = state();
+    let user = account(10);
+    state.mint_liquid_tokens(user, 100);
+
+    let ctx = context(user, 1000);
+    let (state, events) = request_unlock(context(user, 1000), state, 40);
+
+    let pending_unlocks = state.pending_unlocks.get(&user).unwrap();
+    assert_eq!(pending_unlocks.len(), 1);
+    assert_eq!(pending_unlocks[0].liquid_amount, 40);
+    assert_eq!(pending_unlocks[0].cooldown_ends_at, 1100);
+    assert_eq!(pending_unlocks[0].expires_at, 1200);
+
+    let mut expected = EventGroup::builder();
+    add_unlock_requested_event(&mut expected, &ctx, user, &pending_unlocks[0]);
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn redeem_emits_redeemed_event() {
+    let mut state = state();
+    let user = account(10);
+    state.mint_liquid_tokens(user, 100);
+    state.add_to_stake_token_balance(100);
+    let unlocks = vec![
+        // Redeemable between 100 and 200.
+        pending_unlock(&state, 1, 30, 0),
+        // Redeemable from 200.
+        pending_unlock(&state, 2, 20, 100),
+    ];
+    state.pending_unlocks.insert(user, unlocks);
+
+    let ctx = context(user, 150);
+    let (state, events) = redeem(context(user, 150), state);
+
+    assert_eq!(state.liquid_token_state.balance_of(&user), 70);
+    assert_eq!(state.stake_token_balance, 70);
+
+    let mut expected = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(state.token_for_staking).transfer(
+        &mut expected,
+        &user,
+        30,
+    );
+    add_redeemed_event(&mut expected, &ctx, user, 30);
+    assert_eq!(events, vec![expected.build()]);
+}
+
+fn submit_succeeded(
+    state: LiquidStakingState,
+    user: Address,
+    stake_token_amount: u128,
+) -> LiquidStakingState {
+    let callback_context = CallbackContext {
+        success: true,
+        results: vec![],
+    };
+    submit_callback(
+        context(user, 0),
+        callback_context,
+        state,
+        stake_token_amount,
+        0,
+    )
+    .0
+}
+
+#[test]
+fn total_value_locked_of_fresh_contract() {
+    let state = state();
+
+    assert_eq!(state.total_value_locked(), 0);
+    assert_eq!(state.total_value_locked_breakdown(), (0, 0, 0));
+}
+
+#[test]
+fn total_value_locked_after_submits() {
+    let state = state();
+    let state = submit_succeeded(state, account(10), 100);
+    let state = submit_succeeded(state, account(11), 50);
+
+    assert_eq!(state.total_value_locked(), 150);
+    assert_eq!(state.total_value_locked_breakdown(), (0, 150, 0));
+
+    let (state, _) = withdraw(context(account(2), 0), state, 120);
+    let state = accrue_rewards(context(account(2), 0), state, 15);
+
+    assert_eq!(state.total_value_locked(), 165);
+    assert_eq!(state.total_value_locked_breakdown(), (135, 30, 0));
+}
+
+#[test]
+fn total_value_locked_with_buy_in_locked_tokens() {
+    let mut state = state();
+    state.change_buy_in(10);
+    let state = submit_succeeded(state, account(10), 100);
+    let state = submit_succeeded(state, account(11), 50);
+
+    assert_eq!(state.total_value_locked(), 150);
+    assert_eq!(state.total_value_locked_breakdown(), (0, 150, 15));
+
+    let (mut state, _) = withdraw(context(account(2), 0), state, 100);
+    assert_eq!(state.total_value_locked_breakdown(), (100, 50, 15));
+
+    // Disabling the buy-in mints the locked tokens, which stay in the pool.
+    state.disable_buy_in();
+    assert_eq!(state.total_value_locked(), 150);
+    assert_eq!(state.total_value_locked_breakdown(), (100, 50, 0));
+}
+
+#[test]
+fn total_value_locked_breakdown_excludes_tokens_awaiting_redemption() {
+    let mut state = state();
+    state.total_pool_stake_token = 150;
+    state.stake_token_balance = 200;
+
+    assert_eq!(state.total_value_locked_breakdown(), (0, 150, 0));
+}
+
+#[test]
+fn submit_of_minimum_amount() {
+    let mut state = state();
+    state.minimum_submit_amount = 10;
+
+    let (_, events) = submit(context(account(10), 0), state, 10, 0);
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot submit 9 tokens, which is below the minimum submit amount of 10."
+)]
+fn submit_below_minimum_amount() {
+    let mut state = state();
+    state.minimum_submit_amount = 10;
+
+    submit(context(account(10), 0), state, 9, 0);
+}
+
+#[test]
+#[should_panic(expected = "Submitting 1 stake tokens would mint zero liquid tokens.")]
+fn submit_minting_zero_liquid_tokens() {
+    let mut state = state();
+    // Exchange rate of two stake tokens per liquid token.
+    state.mint_liquid_tokens(account(11), 100);
+    state.add_to_stake_pool(100);
+
+    submit_succeeded(state, account(10), 1);
+}
+
+#[test]
+#[should_panic(expected = "Submitting 11 stake tokens would mint zero liquid tokens.")]
+fn submit_rejected_when_minting_zero_liquid_tokens_after_buy_in() {
+    let mut state = state();
+    // Exchange rate of twenty stake tokens per liquid token.
+    state.mint_liquid_tokens(account(11), 10);
+    state.add_to_stake_pool(190);
+    state.change_buy_in(10);
+
+    submit(context(account(10), 0), state, 11, 0);
+}
+
+#[test]
+fn submit_within_slippage_limit() {
+    let mut state = state();
+    // Exchange rate of two stake tokens per liquid token.
+    state.mint_liquid_tokens(account(11), 100);
+    state.add_to_stake_pool(100);
+
+    let (_, events) = submit(context(account(10), 0), state, 50, 25);
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+#[should_panic(
+    expected = "Submitting 50 stake tokens would mint 25 liquid tokens, which is below the minimum of 26."
+)]
+fn submit_rejected_when_exceeding_slippage_limit() {
+    let mut state = state();
+    // Exchange rate of two stake tokens per liquid token.
+    state.mint_liquid_tokens(account(11), 100);
+    state.add_to_stake_pool(100);
+
+    submit(context(account(10), 0), state, 50, 26);
+}
+
+#[test]
+fn submit_callback_refunds_when_exchange_rate_exceeds_slippage_limit() {
+    let mut state = state();
+    let user = account(10);
+    state.mint_liquid_tokens(account(11), 100);
+    let (state, _) = submit(context(user, 0), state, 50, 50);
+
+    // Rewards are accrued before the callback, changing the exchange rate to two stake tokens
+    // per liquid token.
+    let state = accrue_rewards(context(account(2), 0), state, 100);
+
+    let callback_context = CallbackContext {
+        success: true,
+        results: vec![],
+    };
+    let (state, events) = submit_callback(context(user, 0), callback_context, state, 50, 50);
+
+    assert_eq!(state.liquid_token_state.balance_of(&user), 0);
+    assert_eq!(state.total_pool_liquid, 100);
+    assert_eq!(state.total_pool_stake_token, 200);
+    assert_eq!(state.stake_token_balance, 0);
+
+    let mut expected = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(state.token_for_staking).transfer(
+        &mut expected,
+        &user,
+        50,
+    );
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn submit_callback_refunds_when_exchange_rate_would_mint_zero_liquid_tokens() {
+    let mut state = state();
+    let user = account(10);
+    state.mint_liquid_tokens(account(11), 100);
+    let (state, _) = submit(context(user, 0), state, 10, 0);
+
+    // Rewards are accrued before the callback, changing the exchange rate to eleven stake tokens
+    // per liquid token.
+    let state = accrue_rewards(context(account(2), 0), state, 1000);
+
+    let callback_context = CallbackContext {
+        success: true,
+        results: vec![],
+    };
+    let (state, events) = submit_callback(context(user, 0), callback_context, state, 10, 0);
+
+    assert_eq!(state.liquid_token_state.balance_of(&user), 0);
+    assert_eq!(state.total_pool_liquid, 100);
+    assert_eq!(state.total_pool_stake_token, 1100);
+    assert_eq!(state.stake_token_balance, 0);
+
+    let mut expected = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(state.token_for_staking).transfer(
+        &mut expected,
+        &user,
+        10,
+    );
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn submit_callback_mints_when_exchange_rate_within_slippage_limit() {
+    let mut state = state();
+    let user = account(10);
+    state.mint_liquid_tokens(account(11), 100);
+    let (state, _) = submit(context(user, 0), state, 50, 20);
+
+    let state = accrue_rewards(context(account(2), 0), state, 100);
+
+    let callback_context = CallbackContext {
+        success: true,
+        results: vec![],
+    };
+    let (state, _) = submit_callback(context(user, 0), callback_context, state, 50, 20);
+
+    assert_eq!(state.liquid_token_state.balance_of(&user), 25);
+    assert_eq!(state.stake_token_balance, 50);
+}
+
+#[test]
+fn disable_buy_in_leaves_dust_in_pool() {
+    let mut state = state();
+    // Exchange rate of two stake tokens per liquid token.
+    state.mint_liquid_tokens(account(11), 100);
+    state.add_to_stake_pool(100);
+    state.change_buy_in(10);
+
+    let mut state = submit_succeeded(state, account(10), 10);
+    assert_eq!(state.liquid_token_state.balance_of(&account(10)), 4);
+    assert_eq!(state.amount_of_buy_in_locked_stake_tokens, 1);
+
+    // The single locked stake token is worth less than a liquid token.
+    state.disable_buy_in();
+    assert_eq!(state.liquid_token_state.balance_of(&account(10)), 4);
+    assert_eq!(state.amount_of_buy_in_locked_stake_tokens, 0);
+    assert_eq!(state.total_pool_stake_token, 210);
+    assert_eq!(state.total_pool_liquid, 104);
+}
+
+#[test]
+#[should_panic(expected = "Events can only be sent by the liquid staking contract itself.")]
+fn users_cannot_send_events() {
+    redeemed_event(context(account(10), 0), state(), account(10), 30);
+}
+
+#[test]
+fn fee_administrator_can_change_fees() {
+    let state = change_buy_in(context(account(3), 0), state(), 10);
+    let state = change_instant_unstake_penalty(context(account(3), 0), state, 50);
+    let state = disable_buy_in(context(account(3), 0), state);
+
+    assert!(!state.buy_in_enabled);
+    assert_eq!(state.instant_unstake_penalty_per_mille, 50);
+}
+
+#[test]
+fn operations_administrator_can_perform_operations() {
+    let state = add_to_denylist(context(account(8), 0), state(), account(10));
+    assert!(state.is_denied(&account(10)));
+
+    let state = remove_from_denylist(context(account(8), 0), state, account(10));
+    assert!(!state.is_denied(&account(10)));
+
+    clean_up_pending_unlocks(context(account(8), 0), state);
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot change the buy-in percentage. Only the registered fee administrator"
+)]
+fn operations_administrator_cannot_change_buy_in() {
+    change_buy_in(context(account(8), 0), state(), 10);
+}
+
+#[test]
+#[should_panic(expected = "Cannot disable buy-in. Only the registered fee administrator")]
+fn operations_administrator_cannot_disable_buy_in() {
+    let state = change_buy_in(context(account(3), 0), state(), 10);
+    disable_buy_in(context(account(8), 0), state);
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot change the instant unstake penalty. Only the registered fee administrator"
+)]
+fn operations_administrator_cannot_change_instant_unstake_penalty() {
+    change_instant_unstake_penalty(context(account(8), 0), state(), 50);
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot clean up pending unlocks. Only the registered operations administrator"
+)]
+fn fee_administrator_cannot_clean_up_pending_unlocks() {
+    clean_up_pending_unlocks(context(account(3), 0), state());
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot change the denylist. Only the registered operations administrator"
+)]
+fn fee_administrator_cannot_add_to_denylist() {
+    add_to_denylist(context(account(3), 0), state(), account(10));
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot change the denylist. Only the registered operations administrator"
+)]
+fn fee_administrator_cannot_remove_from_denylist() {
+    let state = add_to_denylist(context(account(8), 0), state(), account(10));
+    remove_from_denylist(context(account(3), 0), state, account(10));
+}
+
+#[test]
+fn upgrade_from_v2_keeps_pending_unlocks_and_splits_administrator() {
+    let base = state();
+    let user = account(10);
+    let mut pending_unlocks = AvlTreeMap::new();
+    pending_unlocks.insert(user, vec![pending_unlock(&base, 7, 20, 50)]);
+    let v2 = upgrade::LiquidStakingStateV2 {
+        token_for_staking: base.token_for_staking,
+        stake_token_balance: 20,
+        staking_responsible: base.staking_responsible,
+        administrator: account(3),
+        total_pool_stake_token: 100,
+        total_pool_liquid: 80,
+        liquid_token_state: base.liquid_token_state,
+        pending_unlocks,
+        buy_in_tokens: AvlTreeMap::new(),
+        length_of_cooldown_period: 100,
+        length_of_redeem_period: 100,
+        amount_of_buy_in_locked_stake_tokens: 0,
+        buy_in_percentage: 0,
+        buy_in_enabled: false,
+        pending_unlock_id_counter: 8,
+    };
+
+    let state = upgrade::upgrade_state_from_v2_to_current(context(account(3), 0), v2);
+
+    assert_eq!(state.fee_administrator, account(3));
+    assert_eq!(state.operations_administrator, account(3));
+    assert_eq!(state.total_pool_stake_token, 100);
+    assert_eq!(state.total_pool_liquid, 80);
+    assert_eq!(state.pending_unlock_id_counter, 8);
+    assert_eq!(state.redeemable_stake_tokens(&user, 160), 20);
+    assert_eq!(state.instant_unstake_penalty_per_mille, 0);
+    assert_eq!(state.minimum_submit_amount, 0);
+    assert!(!state.is_denied(&user));
+}
+
+/// Creates a state where users 10 and 11 each hold 100 liquid tokens, the contract holds 150 of
+/// the 200 stake tokens in the pool, and user 10 has requested to unlock all their liquid tokens
+/// at time 0.
+fn state_with_pending_unlock() -> LiquidStakingState {
+    let mut state = state();
+    state.mint_liquid_tokens(account(10), 100);
+    state.mint_liquid_tokens(account(11), 100);
+    state.stake_token_balance = 150;
+    state.add_to_pending_unlocks(account(10), 100, 0);
+    state
+}
+
+#[test]
+fn instant_unstake_leaves_stake_tokens_reserved_for_pending_unlocks() {
+    let mut state = state_with_pending_unlock();
+
+    assert_eq!(state.instant_unstake(account(11), 50, 10), 50);
+    assert_eq!(state.stake_token_balance, 100);
+    assert_eq!(state.reserved_stake_tokens(10), 100);
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot instantly unstake. Requires 60 stake tokens, but only 50 are available on the contract."
+)]
+fn instant_unstake_cannot_use_stake_tokens_reserved_for_pending_unlocks() {
+    let mut state = state_with_pending_unlock();
+    state.instant_unstake(account(11), 60, 10);
+}
+
+#[test]
+#[should_panic(expected = "is on the denylist, and cannot interact with the contract.")]
+fn denied_user_cannot_instantly_unstake() {
+    let mut state = state();
+    state.mint_liquid_tokens(account(10), 100);
+    state.stake_token_balance = 100;
+    let state = add_to_denylist(context(account(8), 0), state, account(10));
+
+    instant_unstake(context(account(10), 0), state, 50);
+}
+
+#[test]
+fn stake_tokens_of_expired_pending_unlocks_are_not_reserved() {
+    let mut state = state_with_pending_unlock();
+
+    assert_eq!(state.reserved_stake_tokens(201), 0);
+    assert_eq!(state.instant_unstake(account(11), 60, 201), 60);
+}
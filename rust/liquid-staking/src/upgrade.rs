@@ -1,8 +1,6 @@
 //! Submodule handles upgrade logic for the smart contract.
 
-use crate::{
-    LiquidStakingState, LiquidTokenState, PendingUnlock, PendingUnlockId, INITIAL_PENDING_UNLOCK_ID,
-};
+use crate::{LiquidStakingState, LiquidTokenState, PendingUnlock, PendingUnlockId};
 use create_type_spec_derive::CreateTypeSpec;
 use pbc_contract_codegen::upgrade_is_allowed;
 use pbc_contract_common::address::Address;
@@ -11,42 +9,47 @@ use pbc_contract_common::context::ContractContext;
 use pbc_contract_common::upgrade::ContractHashes;
 use read_write_state_derive::ReadWriteState;
 
-/// An unlock request waiting to be redeemed.
-///
-/// Old version of the `PendingUnlock` structure.
-#[derive(ReadWriteState, CreateTypeSpec)]
-pub struct PendingUnlockV1 {
-    liquid_amount: u128,
-    stake_token_amount: u128,
-    created_at: u64,
-    cooldown_ends_at: u64,
-    expires_at: u64,
-}
-
 /// Liquid Staking contract compatible state.
 ///
-/// Old version of the `LiquidStakingState` structure.
+/// Old version of the `LiquidStakingState` structure, as of version 2 of the contract. Contracts
+/// at version 1 must be upgraded to version 2 before they can be upgraded to the current version.
 #[derive(ReadWriteState, CreateTypeSpec)]
-pub struct LiquidStakingStateV1 {
-    token_for_staking: Address,
-    stake_token_balance: u128,
-    staking_responsible: Address,
-    administrator: Address,
-    total_pool_stake_token: u128,
-    total_pool_liquid: u128,
-    liquid_token_state: LiquidTokenState,
-    pending_unlocks: AvlTreeMap<Address, Vec<PendingUnlockV1>>,
-    buy_in_tokens: AvlTreeMap<Address, u128>,
-    length_of_cooldown_period: u64,
-    length_of_redeem_period: u64,
-    amount_of_buy_in_locked_stake_tokens: u128,
-    buy_in_percentage: u128,
-    buy_in_enabled: bool,
+pub struct LiquidStakingStateV2 {
+    /// The address of the token used for staking.
+    pub token_for_staking: Address,
+    /// The amount of stake tokens held by the contract.
+    pub stake_token_balance: u128,
+    /// The account responsible for staking the stake tokens.
+    pub staking_responsible: Address,
+    /// The administrator, which becomes both the fee and the operations administrator.
+    pub administrator: Address,
+    /// The total amount of stake tokens in the pool.
+    pub total_pool_stake_token: u128,
+    /// The total amount of liquid tokens in the pool.
+    pub total_pool_liquid: u128,
+    /// The state of the liquid token.
+    pub liquid_token_state: LiquidTokenState,
+    /// The pending unlocks of each user.
+    pub pending_unlocks: AvlTreeMap<Address, Vec<PendingUnlock>>,
+    /// The buy in tokens of each user.
+    pub buy_in_tokens: AvlTreeMap<Address, u128>,
+    /// The length of the cooldown period.
+    pub length_of_cooldown_period: u64,
+    /// The length of the redeem period.
+    pub length_of_redeem_period: u64,
+    /// The amount of stake tokens locked by the buy in.
+    pub amount_of_buy_in_locked_stake_tokens: u128,
+    /// The buy in percentage.
+    pub buy_in_percentage: u128,
+    /// Whether the buy in is enabled.
+    pub buy_in_enabled: bool,
+    /// The next [`PendingUnlockId`] to assign.
+    pub pending_unlock_id_counter: PendingUnlockId,
 }
 
 /// Determines whether the contract is upgradable in the current context.
 ///
-/// This contract allows the [`LiquidStakingState::administrator`] to upgrade the contract at any time.
+/// This contract allows the [`LiquidStakingState::operations_administrator`] to upgrade the contract at any time.
 ///
 /// # Parameters:
 ///
@@ -64,64 +67,44 @@ pub fn is_upgrade_allowed(
     _new_contract_hashes: ContractHashes,
     _new_contract_rpc: Vec<u8>,
 ) -> bool {
-    context.sender == state.administrator
-}
-
-/// Upgrades from the [`PendingUnlockV1`] to [`PendingUnlock`].
-fn upgrade_pending_unlock_from_v1_to_current(
-    v1: PendingUnlockV1,
-    id: PendingUnlockId,
-) -> PendingUnlock {
-    PendingUnlock {
-        id,
-        liquid_amount: v1.liquid_amount,
-        stake_token_amount: v1.stake_token_amount,
-        created_at: v1.created_at,
-        cooldown_ends_at: v1.cooldown_ends_at,
-        expires_at: v1.expires_at,
-    }
+    context.sender == state.operations_administrator
 }
 
-/// Upgrades from the [`LiquidStakingStateV1`] to [`LiquidStakingState`].
+/// Upgrades from the [`LiquidStakingStateV2`] to [`LiquidStakingState`].
+///
+/// The administrator of version 2 becomes both the fee administrator and the operations
+/// administrator. State introduced after version 2 is initialized such that the contract behaves
+/// as it did before the upgrade: no instant unstake penalty, an empty denylist and no minimum
+/// submit amount.
 ///
 /// # Parameters:
 ///
 /// * `context`: The context for the action call.
 /// * `state`: The current state of the contract.
 #[upgrade]
-pub fn upgrade_state_from_v1_to_current(
+pub fn upgrade_state_from_v2_to_current(
     _context: ContractContext,
-    state: LiquidStakingStateV1,
+    state: LiquidStakingStateV2,
 ) -> LiquidStakingState {
-    let mut pending_unlocks = AvlTreeMap::new();
-    let mut pending_unlock_id_counter = INITIAL_PENDING_UNLOCK_ID;
-    for (user, user_pending_unlocks_v1) in state.pending_unlocks.iter() {
-        let mut user_pending_unlocks = vec![];
-        for pending_unlock_v1 in user_pending_unlocks_v1 {
-            user_pending_unlocks.push(upgrade_pending_unlock_from_v1_to_current(
-                pending_unlock_v1,
-                pending_unlock_id_counter,
-            ));
-            pending_unlock_id_counter += 1;
-        }
-        pending_unlocks.insert(user, user_pending_unlocks);
-    }
-
     LiquidStakingState {
         token_for_staking: state.token_for_staking,
         stake_token_balance: state.stake_token_balance,
         staking_responsible: state.staking_responsible,
-        administrator: state.administrator,
+        fee_administrator: state.administrator,
+        operations_administrator: state.administrator,
         total_pool_stake_token: state.total_pool_stake_token,
         total_pool_liquid: state.total_pool_liquid,
         liquid_token_state: state.liquid_token_state,
-        pending_unlocks,
+        pending_unlocks: state.pending_unlocks,
         buy_in_tokens: state.buy_in_tokens,
         length_of_cooldown_period: state.length_of_cooldown_period,
         length_of_redeem_period: state.length_of_redeem_period,
         amount_of_buy_in_locked_stake_tokens: state.amount_of_buy_in_locked_stake_tokens,
         buy_in_percentage: state.buy_in_percentage,
         buy_in_enabled: state.buy_in_enabled,
-        pending_unlock_id_counter,
+        pending_unlock_id_counter: state.pending_unlock_id_counter,
+        instant_unstake_penalty_per_mille: 0,
+        denylist: AvlTreeMap::new(),
+        minimum_submit_amount: 0,
     }
 }
@@ -12,17 +12,48 @@ use pbc_contract_common::events::EventGroup;
 
 use create_type_spec_derive::CreateTypeSpec;
 use pbc_contract_common::avl_tree_map::AvlTreeMap;
+use read_write_rpc_derive::ReadWriteRPC;
 use read_write_state_derive::ReadWriteState;
 
 use defi_common::interact_mpc20;
+use defi_common::interact_swap::SwapReserves;
 use defi_common::liquidity_util::{
-    calculate_swap_to_amount, AcquiredLiquidityLockInformation, LiquidityLockId,
+    calculate_swap_to_amount, is_lock_expired, lock_expiry, AcquiredLiquidityLockInformation,
+    LiquidityLockId,
 };
 use defi_common::math::{assert_is_per_mille, u128_sqrt};
 use defi_common::permission::Permission;
 use defi_common::token_balances::DepositToken;
 use defi_common::token_balances::{TokenAmount, TokenBalances, TokensInOut};
 
+/// The maximum number of expired locks released by a single call, bounding the work done when
+/// many locks expire at once. Any remaining expired locks are released by later calls.
+const MAX_EXPIRED_LOCKS_RELEASED_PER_CALL: usize = 20;
+
+/// Key of the expiry index of locks.
+/// Entries in the AVL tree implementation are sorted by the lexicographic order of their serialized
+/// keys, so the expiry is stored in big endian representation with the sign bit flipped, to ensure
+/// that the locks expiring first are visited first.
+#[derive(Copy, Clone, Debug, ReadWriteRPC, ReadWriteState, CreateTypeSpec)]
+pub struct LockExpiry {
+    /// The raw key.
+    key: [u8; 8],
+}
+
+impl LockExpiry {
+    /// Creates the key for locks expiring at `expires_at`.
+    fn new(expires_at: i64) -> Self {
+        Self {
+            key: ((expires_at as u64) ^ (1 << 63)).to_be_bytes(),
+        }
+    }
+
+    /// The block production time at which the locks of this key expire.
+    fn expires_at(&self) -> i64 {
+        (u64::from_be_bytes(self.key) ^ (1 << 63)) as i64
+    }
+}
+
 /// Stores data about a lock, which is later used when the lock is executed or cancelled.
 #[derive(ReadWriteState, CreateTypeSpec)]
 pub struct LiquidityLock {
@@ -30,6 +61,8 @@ pub struct LiquidityLock {
     amount_out: TokenAmount,
     tokens_in_out: TokensInOut,
     owner: Address,
+    /// Block production time after which the lock is released automatically, if any.
+    expires_at: Option<i64>,
 }
 
 /// Keeps track of the 'virtual' liquidity that is held in locks.
@@ -59,6 +92,9 @@ pub struct VirtualState {
     next_lock_id: LiquidityLockId,
     /// Stores lock information needed to execute or cancel locks.
     locks: AvlTreeMap<LiquidityLockId, LiquidityLock>,
+    /// The ids of the locks that expire, grouped by their expiry, such that expired locks can be
+    /// released without visiting every lock.
+    locks_by_expiry: AvlTreeMap<LockExpiry, Vec<LiquidityLockId>>,
     /// The sum total of the liquidity held in locks.
     /// This must maintain the invariant: virtual_liquidity = liquidity_liquidity + `lock_sums`.
     lock_sums: LockSums,
@@ -80,6 +116,7 @@ impl VirtualState {
         Self {
             next_lock_id: LiquidityLockId::initial_id(),
             locks: AvlTreeMap::new(),
+            locks_by_expiry: AvlTreeMap::new(),
             lock_sums,
         }
     }
@@ -96,6 +133,12 @@ impl VirtualState {
             .lock_sums
             .get_mut_sum_of_lock_amounts_of(lock.tokens_in_out.token_in) += lock.amount_in;
 
+        if let Some(expires_at) = lock.expires_at {
+            let expiry = LockExpiry::new(expires_at);
+            let mut lock_ids = self.locks_by_expiry.get(&expiry).unwrap_or_default();
+            lock_ids.push(lock_id);
+            self.locks_by_expiry.insert(expiry, lock_ids);
+        }
         self.locks.insert(lock_id, lock);
 
         lock_id
@@ -116,13 +159,63 @@ impl VirtualState {
             lock_id
         );
 
-        self.locks.remove(&lock_id);
+        if let Some(expires_at) = lock.expires_at {
+            let expiry = LockExpiry::new(expires_at);
+            let mut lock_ids = self.locks_by_expiry.get(&expiry).unwrap();
+            lock_ids.retain(|id| *id != lock_id);
+            if lock_ids.is_empty() {
+                self.locks_by_expiry.remove(&expiry);
+            } else {
+                self.locks_by_expiry.insert(expiry, lock_ids);
+            }
+        }
+        self.release_lock(lock_id, &lock);
 
+        lock
+    }
+
+    /// Removes `lock` from the locks, and subtracts its input amount from the lock sums, to
+    /// maintain the invariant: virtual_liquidity = liquidity_liquidity + `lock_sums`.
+    ///
+    /// Does not update the expiry index.
+    fn release_lock(&mut self, lock_id: LiquidityLockId, lock: &LiquidityLock) {
+        self.locks.remove(&lock_id);
         *self
             .lock_sums
             .get_mut_sum_of_lock_amounts_of(lock.tokens_in_out.token_in) -= lock.amount_in;
+    }
 
-        lock
+    /// Releases the locks that have expired at `now`, earliest expiry first, updating the virtual
+    /// liquidity state as if the locks had been cancelled.
+    ///
+    /// At most [`MAX_EXPIRED_LOCKS_RELEASED_PER_CALL`] locks are released, and only the expiry
+    /// index is visited, such that the work done is bounded regardless of the number of locks.
+    ///
+    /// Returns the number of released locks.
+    fn release_expired_locks(&mut self, now: i64) -> usize {
+        let mut released = 0;
+        while released < MAX_EXPIRED_LOCKS_RELEASED_PER_CALL {
+            let Some((expiry, mut lock_ids)) = self.locks_by_expiry.iter().next() else {
+                break;
+            };
+            if !is_lock_expired(Some(expiry.expires_at()), now) {
+                break;
+            }
+
+            self.locks_by_expiry.remove(&expiry);
+            while released < MAX_EXPIRED_LOCKS_RELEASED_PER_CALL {
+                let Some(lock_id) = lock_ids.pop() else {
+                    break;
+                };
+                let lock = self.locks.get(&lock_id).unwrap();
+                self.release_lock(lock_id, &lock);
+                released += 1;
+            }
+            if !lock_ids.is_empty() {
+                self.locks_by_expiry.insert(expiry, lock_ids);
+            }
+        }
+        released
     }
 
     /// Allocate new [`LiquidityLockId `]. The state will not create the same lock id again.
@@ -149,6 +242,9 @@ pub struct LiquiditySwapContractState {
     pub liquidity_pool_address: Address,
     /// The fee for making swaps per mille. Must be [`assert_is_per_mille`].
     pub swap_fee_per_mille: u16,
+    /// How long acquired locks are held, in milliseconds, before they are released automatically.
+    /// `None` if locks are held until they are executed or cancelled.
+    pub lock_duration_millis: Option<i64>,
     /// The map containing all token balances of all users and the contract itself. <br>
     /// The contract should always have a balance equal to the sum of all token balances.
     pub token_balances: TokenBalances,
@@ -187,6 +283,8 @@ impl LiquiditySwapContractState {
 ///
 ///   * `swap_fee_per_mille`: [`TokenAmount`] - The fee for swapping, in per mille, i.e. a fee set to 3 corresponds to a fee of 0.3%.
 ///
+///   * `lock_duration_millis`: [`Option<i64>`] - How long acquired locks are held before they are released automatically. `None` if locks never expire.
+///
 /// The new state object of type [`LiquiditySwapContractState`] with all address fields initialized to their final state and remaining fields initialized to a default value.
 #[init]
 pub fn initialize(
@@ -195,8 +293,16 @@ pub fn initialize(
     token_b_address: Address,
     swap_fee_per_mille: u16,
     permission_lock_swap: Permission,
+    lock_duration_millis: Option<i64>,
 ) -> (LiquiditySwapContractState, Vec<EventGroup>) {
     assert_is_per_mille(swap_fee_per_mille);
+    if let Some(lock_duration_millis) = lock_duration_millis {
+        assert!(
+            lock_duration_millis > 0,
+            "Lock duration must be positive, but was {} ms",
+            lock_duration_millis
+        );
+    }
 
     let token_balances =
         match TokenBalances::new(context.contract_address, token_a_address, token_b_address) {
@@ -208,6 +314,7 @@ pub fn initialize(
         permission_lock_swap,
         liquidity_pool_address: context.contract_address,
         swap_fee_per_mille,
+        lock_duration_millis,
         token_balances,
         virtual_state: VirtualState::default(),
     };
@@ -329,6 +436,9 @@ pub fn instant_swap(
         state.contract_pools_have_liquidity(),
         "Pools must have existing liquidity to perform a swap"
     );
+    state
+        .virtual_state
+        .release_expired_locks(context.block_production_time);
 
     // Instant swaps can be represented by acquiring a lock, and executing it straight away.
     let (lock_id, _) = lock_internal(
@@ -337,6 +447,7 @@ pub fn instant_swap(
         token_in,
         amount_out_minimum,
         context.sender,
+        None,
     );
     execute_lock_swap_internal(&mut state, lock_id, context.sender);
 
@@ -425,6 +536,9 @@ pub fn provide_liquidity(
     token_address: Address,
     amount: TokenAmount,
 ) -> (LiquiditySwapContractState, Vec<EventGroup>) {
+    state
+        .virtual_state
+        .release_expired_locks(context.block_production_time);
     assert!(
         state.virtual_state.no_locked_liquidity(),
         "Cannot provide liquidity while locks are present."
@@ -482,6 +596,9 @@ pub fn reclaim_liquidity(
     mut state: LiquiditySwapContractState,
     liquidity_token_amount: TokenAmount,
 ) -> (LiquiditySwapContractState, Vec<EventGroup>) {
+    state
+        .virtual_state
+        .release_expired_locks(context.block_production_time);
     assert!(
         state.virtual_state.no_locked_liquidity(),
         "Cannot reclaim liquidity while locks are present."
@@ -582,9 +699,12 @@ pub fn provide_initial_liquidity(
 /// `amount_out_minimum` of the token being swapped to, at a later point in time,
 /// at the minimum exchange rate given by the actual and virtual liquidity pool states,
 /// at the acquisition time of the lock.
-/// The id, and output amount of the lock is returned to any callbacks.
+/// The id, output amount and expiry of the lock is returned to any callbacks.
 /// Other users can still interact with the swap contract while the lock exists.
 ///
+/// If the contract is configured with a lock duration, the lock is released automatically once
+/// it expires. Expired locks are released before the new lock is acquired.
+///
 /// # Fails
 ///
 /// Fails if `amount_out_minimum` is greater than what the current contract state will provide.
@@ -604,14 +724,19 @@ pub fn acquire_swap_lock(
         state.contract_pools_have_liquidity(),
         "Pools must have existing liquidity to acquire a lock"
     );
+    state
+        .virtual_state
+        .release_expired_locks(context.block_production_time);
 
     // Acquire a lock internally.
+    let expires_at = lock_expiry(context.block_production_time, state.lock_duration_millis);
     let (lock_id, amount_out) = lock_internal(
         &mut state,
         amount_in,
         token_in,
         amount_out_minimum,
         context.sender,
+        expires_at,
     );
 
     // Pass the lock id to any callbacks.
@@ -627,12 +752,14 @@ pub fn acquire_swap_lock(
 ///
 /// Fails if the calculated receiving amount is less than `amount_out_minimum`.
 /// The `owner` becomes the address associated with the lock, who has sole permission to execute it.
+/// The lock is released automatically after `expires_at`, if given.
 fn lock_internal(
     state: &mut LiquiditySwapContractState,
     amount_in: TokenAmount,
     token_in: Address,
     amount_out_minimum: TokenAmount,
     owner: Address,
+    expires_at: Option<i64>,
 ) -> (LiquidityLockId, TokenAmount) {
     let tokens = state.token_balances.deduce_tokens_in_out(token_in);
 
@@ -652,6 +779,7 @@ fn lock_internal(
         amount_out,
         tokens_in_out,
         owner,
+        expires_at,
     };
     (state.virtual_state.add_lock(lock), amount_out)
 }
@@ -663,7 +791,7 @@ fn lock_internal(
 ///
 /// # Fails
 ///
-/// If an unknown `lock_id` is provided this fails.
+/// If an unknown or expired `lock_id` is provided this fails.
 /// Also fails if a user who didn't acquire the lock associated with `lock_id` tries to execute it.
 #[action(shortname = 0x08)]
 pub fn execute_lock_swap(
@@ -671,6 +799,15 @@ pub fn execute_lock_swap(
     mut state: LiquiditySwapContractState,
     lock_id: LiquidityLockId,
 ) -> (LiquiditySwapContractState, Vec<EventGroup>) {
+    state
+        .virtual_state
+        .release_expired_locks(context.block_production_time);
+    let lock_expired = state
+        .virtual_state
+        .locks
+        .get(&lock_id)
+        .is_some_and(|lock| is_lock_expired(lock.expires_at, context.block_production_time));
+    assert!(!lock_expired, "{:?} has expired.", lock_id);
     let output_amount = execute_lock_swap_internal(&mut state, lock_id, context.sender);
     (state, vec![EventGroup::with_return_data(output_amount)])
 }
@@ -707,7 +844,7 @@ fn execute_lock_swap_internal(
 /// Cancels a previously acquired lock, updating the virtual balances of the contract,
 /// as if the swap didn't happen.
 ///
-/// If an unknown `lockID` is provided this fails.
+/// If an unknown or expired `lockID` is provided this fails.
 /// Also fails if a user who didn't acquire the lock associated with `lockID` tries to cancel the lock.
 #[action(shortname = 0x09)]
 pub fn cancel_lock(
@@ -715,10 +852,53 @@ pub fn cancel_lock(
     mut state: LiquiditySwapContractState,
     lock_id: LiquidityLockId,
 ) -> (LiquiditySwapContractState, Vec<EventGroup>) {
+    state
+        .virtual_state
+        .release_expired_locks(context.block_production_time);
     state.virtual_state.remove_lock(lock_id, context.sender);
     (state, vec![])
 }
 
+/// Releases the locks that have expired, updating the virtual balances of the contract,
+/// as if the expired locks had been cancelled.
+///
+/// Allows anybody to free liquidity held by locks whose owners never executed or cancelled them,
+/// e.g. because the router holding the locks stopped working.
+/// Expired locks are also released whenever locks are acquired, executed or cancelled, and
+/// before liquidity is provided or reclaimed. Each call releases at most
+/// [`MAX_EXPIRED_LOCKS_RELEASED_PER_CALL`] locks, so sweeping many expired locks may take
+/// several calls.
+#[action(shortname = 0x0B)]
+pub fn release_expired_locks(
+    context: ContractContext,
+    mut state: LiquiditySwapContractState,
+) -> (LiquiditySwapContractState, Vec<EventGroup>) {
+    state
+        .virtual_state
+        .release_expired_locks(context.block_production_time);
+    (state, vec![])
+}
+
+/// Get the reserves of the pools of the contract, and the swap fee.
+///
+/// Returns the [`SwapReserves`] to any registered callbacks, allowing other contracts to quote
+/// swaps using [`SwapContract::get_reserves`](defi_common::interact_swap::SwapContract::get_reserves).
+#[action(shortname = 0x0A)]
+pub fn get_reserves(
+    _context: ContractContext,
+    state: LiquiditySwapContractState,
+) -> (LiquiditySwapContractState, Vec<EventGroup>) {
+    let contract_token_balance = state
+        .token_balances
+        .get_balance_for(&state.liquidity_pool_address);
+    let reserves = SwapReserves {
+        reserve_a: contract_token_balance.get_amount_of(DepositToken::A),
+        reserve_b: contract_token_balance.get_amount_of(DepositToken::B),
+        fee_per_mille: state.swap_fee_per_mille,
+    };
+    (state, vec![EventGroup::with_return_data(reserves)])
+}
+
 /// Determines the initial amount of liquidity tokens, or shares, representing some sensible '100%' of the contract's liquidity. <br>
 /// This implementation is derived from section 3.4 of: [Uniswap v2 whitepaper](https://uniswap.org/whitepaper.pdf). <br>
 /// It guarantees that the value of a liquidity token becomes independent of the ratio at which liquidity was initially token_in.
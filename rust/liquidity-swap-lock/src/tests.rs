@@ -1,4 +1,5 @@
 use super::*;
+use defi_common::test_util::{account, context, contract};
 use proptest::prelude::*;
 
 #[test]
@@ -369,3 +370,186 @@ mod test {
         assert_eq!(b_output, 0);
     }
 }
+
+/// Swap state with initial liquidity, where locks expire after `lock_duration_millis`.
+fn state_with_liquidity(lock_duration_millis: Option<i64>) -> LiquiditySwapContractState {
+    let provider = account(1);
+    let (mut state, _) = initialize(
+        context(provider, 0),
+        contract(2),
+        contract(3),
+        3,
+        Permission::Anybody {},
+        lock_duration_millis,
+    );
+    state
+        .token_balances
+        .add_to_token_balance(provider, DepositToken::A, 20_000);
+    state
+        .token_balances
+        .add_to_token_balance(provider, DepositToken::B, 20_000);
+    let (state, _) = provide_initial_liquidity(context(provider, 0), state, 10_000, 10_000);
+    state
+}
+
+fn acquire_lock(
+    state: LiquiditySwapContractState,
+    block_production_time: i64,
+) -> (LiquiditySwapContractState, LiquidityLockId) {
+    let (state, _) = acquire_swap_lock(
+        context(account(4), block_production_time),
+        state,
+        contract(2),
+        100,
+        0,
+    );
+    let lock_id = state.virtual_state.locks.iter().last().unwrap().0;
+    (state, lock_id)
+}
+
+#[test]
+fn locks_without_duration_are_never_released() {
+    let (mut state, _) = acquire_lock(state_with_liquidity(None), 0);
+    assert_eq!(state.virtual_state.release_expired_locks(i64::MAX), 0);
+    assert!(!state.virtual_state.no_locked_liquidity());
+}
+
+#[test]
+fn expired_lock_is_released() {
+    let (mut state, _) = acquire_lock(state_with_liquidity(Some(1_000)), 0);
+    assert_eq!(state.virtual_state.lock_sums.sum_of_lock_amounts_a, 100);
+
+    assert_eq!(state.virtual_state.release_expired_locks(999), 0);
+    assert!(!state.virtual_state.no_locked_liquidity());
+
+    assert_eq!(state.virtual_state.release_expired_locks(1_000), 1);
+    assert!(state.virtual_state.no_locked_liquidity());
+    assert_eq!(state.virtual_state.lock_sums.sum_of_lock_amounts_a, 0);
+}
+
+#[test]
+fn expired_lock_does_not_block_providing_liquidity() {
+    let provider = account(1);
+    let (state, _) = acquire_lock(state_with_liquidity(Some(1_000)), 0);
+    let (state, _) = provide_liquidity(context(provider, 1_000), state, contract(2), 1_000);
+    assert!(state.virtual_state.no_locked_liquidity());
+}
+
+#[test]
+#[should_panic(expected = "Cannot provide liquidity while locks are present.")]
+fn unexpired_lock_blocks_providing_liquidity() {
+    let provider = account(1);
+    let (state, _) = acquire_lock(state_with_liquidity(Some(1_000)), 0);
+    provide_liquidity(context(provider, 999), state, contract(2), 1_000);
+}
+
+#[test]
+fn expired_lock_does_not_affect_new_lock_price() {
+    let (fresh_state, _) = acquire_lock(state_with_liquidity(Some(1_000)), 1_000);
+    let (state, _) = acquire_lock(state_with_liquidity(Some(1_000)), 0);
+    let (state, _) = acquire_lock(state, 1_000);
+
+    assert_eq!(state.virtual_state.locks.iter().count(), 1);
+    assert_eq!(
+        state
+            .virtual_state
+            .locks
+            .iter()
+            .next()
+            .unwrap()
+            .1
+            .amount_out,
+        fresh_state
+            .virtual_state
+            .locks
+            .iter()
+            .next()
+            .unwrap()
+            .1
+            .amount_out
+    );
+}
+
+#[test]
+#[should_panic(expected = "is not a valid lock id.")]
+fn expired_lock_cannot_be_executed() {
+    let (state, lock_id) = acquire_lock(state_with_liquidity(Some(1_000)), 0);
+    execute_lock_swap(context(account(4), 1_000), state, lock_id);
+}
+
+/// Acquires `count` locks at time 0, on a contract where locks expire after 1000 ms.
+fn state_with_locks(count: usize) -> LiquiditySwapContractState {
+    let mut state = state_with_liquidity(Some(1_000));
+    for _ in 0..count {
+        (state, _) = acquire_lock(state, 0);
+    }
+    state
+}
+
+#[test]
+fn expired_locks_are_released_in_bounded_batches() {
+    let mut state = state_with_locks(MAX_EXPIRED_LOCKS_RELEASED_PER_CALL + 5);
+
+    assert_eq!(
+        state.virtual_state.release_expired_locks(1_000),
+        MAX_EXPIRED_LOCKS_RELEASED_PER_CALL
+    );
+    assert_eq!(state.virtual_state.locks.iter().count(), 5);
+    assert_eq!(state.virtual_state.lock_sums.sum_of_lock_amounts_a, 500);
+
+    assert_eq!(state.virtual_state.release_expired_locks(1_000), 5);
+    assert!(state.virtual_state.no_locked_liquidity());
+    assert_eq!(state.virtual_state.locks_by_expiry.iter().count(), 0);
+    assert_eq!(state.virtual_state.lock_sums.sum_of_lock_amounts_a, 0);
+}
+
+#[test]
+fn locks_are_released_by_expiry() {
+    let (state, _) = acquire_lock(state_with_liquidity(Some(1_000)), 0);
+    let (mut state, _) = acquire_lock(state, 500);
+
+    assert_eq!(state.virtual_state.release_expired_locks(1_000), 1);
+    assert_eq!(state.virtual_state.locks.iter().count(), 1);
+    assert_eq!(state.virtual_state.release_expired_locks(1_499), 0);
+    assert_eq!(state.virtual_state.release_expired_locks(1_500), 1);
+}
+
+#[test]
+fn cancelled_lock_is_removed_from_expiry_index() {
+    let state = state_with_locks(2);
+    let (mut state, _) = cancel_lock(context(account(4), 0), state, LiquidityLockId::initial_id());
+
+    assert_eq!(state.virtual_state.release_expired_locks(1_000), 1);
+    assert!(state.virtual_state.no_locked_liquidity());
+}
+
+#[test]
+#[should_panic(expected = "LiquidityLockId { raw_id: 0 } has expired.")]
+fn expired_lock_left_by_bounded_release_cannot_be_executed() {
+    let state = state_with_locks(MAX_EXPIRED_LOCKS_RELEASED_PER_CALL + 1);
+    execute_lock_swap(
+        context(account(4), 1_000),
+        state,
+        LiquidityLockId::initial_id(),
+    );
+}
+
+#[test]
+fn lock_expires_relative_to_acquisition() {
+    let (state, lock_id) = acquire_lock(state_with_liquidity(Some(1_000)), 500);
+    let lock = state.virtual_state.locks.get(&lock_id).unwrap();
+    assert_eq!(lock.expires_at, Some(1_500));
+}
+
+#[test]
+#[should_panic(expected = "Lock duration must be positive, but was 0 ms")]
+fn lock_duration_must_be_positive() {
+    initialize(
+        context(account(1), 0),
+        contract(2),
+        contract(3),
+        3,
+        Permission::Anybody {},
+        Some(0),
+    );
+}
@@ -8,6 +8,7 @@ use pbc_contract_common::context::{CallbackContext, ContractContext};
 use pbc_contract_common::events::EventGroup;
 
 use defi_common::interact_mpc20;
+use defi_common::interact_swap::SwapReserves;
 use defi_common::liquidity_util::calculate_swap_to_amount;
 use defi_common::math::{assert_is_per_mille, u128_sqrt};
 use defi_common::token_balances::DepositToken;
@@ -453,6 +454,26 @@ pub fn provide_initial_liquidity(
     (state, vec![])
 }
 
+/// Get the reserves of the pools of the contract, and the swap fee.
+///
+/// Returns the [`SwapReserves`] to any registered callbacks, allowing other contracts to quote
+/// swaps using [`SwapContract::get_reserves`](defi_common::interact_swap::SwapContract::get_reserves).
+#[action(shortname = 0x0A)]
+pub fn get_reserves(
+    _context: ContractContext,
+    state: LiquiditySwapContractState,
+) -> (LiquiditySwapContractState, Vec<EventGroup>) {
+    let contract_token_balance = state
+        .token_balances
+        .get_balance_for(&state.liquidity_pool_address);
+    let reserves = SwapReserves {
+        reserve_a: contract_token_balance.get_amount_of(DepositToken::A),
+        reserve_b: contract_token_balance.get_amount_of(DepositToken::B),
+        fee_per_mille: state.swap_fee_per_mille,
+    };
+    (state, vec![EventGroup::with_return_data(reserves)])
+}
+
 /// Determines the initial amount of liquidity tokens, or shares, representing some sensible '100%' of the contract's liquidity. <br>
 /// This implementation is derived from section 3.4 of: [Uniswap v2 whitepaper](https://uniswap.org/whitepaper.pdf). <br>
 /// It guarantees that the value of a liquidity token becomes independent of the ratio at which liquidity was initially token_in.
@@ -1,5 +1,8 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(test)]
+mod tests;
+
 #[macro_use]
 extern crate pbc_contract_codegen;
 
@@ -17,8 +20,14 @@ use defi_common::interact_mpc20::MPC20Contract;
 use crate::ProposalType::NewCriteria;
 use crate::VoteResult::{Approved, Denied};
 
+/// The maximum length in bytes of the description of a proposal.
+pub const MAX_DESCRIPTION_LENGTH: usize = 1024;
+
+/// The maximum number of votes returned by a single call to [`MoccaState::proposal_votes`].
+pub const MAX_VOTES_PER_PAGE: usize = 100;
+
 /// The types of allowed votes.
-#[derive(CreateTypeSpec, ReadWriteState, ReadWriteRPC)]
+#[derive(CreateTypeSpec, ReadWriteState, ReadWriteRPC, Clone, PartialEq, Debug)]
 pub enum Vote {
     /// The `Yes` vote.
     #[discriminant(0)]
@@ -26,15 +35,21 @@ pub enum Vote {
     /// The `No` vote.
     #[discriminant(1)]
     No {},
+    /// The `Abstain` vote. Counts as having voted, but neither for nor against the proposal.
+    #[discriminant(2)]
+    Abstain {},
 }
 
-/// The criteria for a proposal to be approved, defined by the list of allowed voters and the threshold for approval.
+/// The criteria for a proposal to be approved, defined by the list of allowed voters, the threshold for approval and
+/// the quorum of votes required for a result.
 #[derive(CreateTypeSpec, ReadWriteState, ReadWriteRPC, Clone)]
 pub struct Criteria {
     /// The list of allowed voters.
     voters: Vec<Voter>,
     /// The threshold for approval of a proposal.
     threshold: u32,
+    /// The combined weight of votes, including `Abstain` votes, required before a proposal can be approved or denied.
+    quorum: u32,
 }
 
 /// An allowed voter, with the address to identify them, and the weight of their vote.
@@ -92,7 +107,41 @@ impl Criteria {
             .any(|voter: &Voter| voter.address == *address)
     }
 
+    /// Tally the weights of the given votes. Votes from addresses that are not registered voters
+    /// are ignored.
+    ///
+    /// ### Parameters
+    ///
+    /// * `votes`: [`&SortedVecMap<Address, Vote>`], the votes to tally.
+    ///
+    /// ### Returns
+    ///
+    /// The total weight of the `Yes`, `No` and `Abstain` votes, respectively.
+    ///
+    fn tally(&self, votes: &SortedVecMap<Address, Vote>) -> (u32, u32, u32) {
+        let mut yes_vote_weight = 0;
+        let mut no_vote_weight = 0;
+        let mut abstain_vote_weight = 0;
+
+        for (address, vote) in votes.iter() {
+            if self.allowed_voter(address) {
+                let weight = self.get_vote_weight(address);
+                match vote {
+                    Vote::Yes {} => yes_vote_weight += weight,
+                    Vote::No {} => no_vote_weight += weight,
+                    Vote::Abstain {} => abstain_vote_weight += weight,
+                }
+            }
+        }
+
+        (yes_vote_weight, no_vote_weight, abstain_vote_weight)
+    }
+
     /// Check the validity of a criteria, to avoid deadlock of the contract.
+    ///
+    /// A valid criteria has at least one voter, no voter is registered more than once, and the threshold is
+    /// positive and achievable by the combined weight of the voters. The same goes for the quorum, except that it may
+    /// be zero.
     fn check_validity(&self) {
         let total_weight = self.get_total_vote_weight();
 
@@ -102,9 +151,35 @@ impl Criteria {
         if self.threshold > total_weight {
             panic!("Threshold cannot be larger then the total weight of votes.")
         }
+        if self.threshold == 0 {
+            panic!("Threshold must be positive.")
+        }
+        if self.quorum > total_weight {
+            panic!("Quorum cannot be larger then the total weight of votes.")
+        }
+        for (index, voter) in self.voters.iter().enumerate() {
+            if self.voters[..index]
+                .iter()
+                .any(|other| other.address == voter.address)
+            {
+                panic!("Voter {} is registered more than once.", voter.address)
+            }
+        }
     }
 }
 
+/// A vote cast on a proposal, together with the weight it carries in the tally.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CastVote {
+    /// The address of the voter.
+    pub voter: Address,
+    /// The vote of the voter. Only the latest vote of each voter is recorded.
+    pub vote: Vote,
+    /// The weight of the vote under the current criteria. Zero if the voter is no longer registered, in which case
+    /// the vote is ignored by the tally.
+    pub weight: u32,
+}
+
 /// The result af a vote on a proposal.
 #[derive(CreateTypeSpec, ReadWriteState, PartialEq, Clone)]
 pub enum VoteResult {
@@ -121,10 +196,14 @@ pub enum VoteResult {
 pub struct Proposal {
     /// The type of action that will happen if the proposal is approved.
     proposal_type: ProposalType,
+    /// A human-readable description of the proposal, for voters to review.
+    description: String,
     /// The result of the vote on the proposal.
     result: Option<VoteResult>,
     /// The current votes on the proposal.
     votes: SortedVecMap<Address, Vote>,
+    /// The time in utc milliseconds, after which the proposal can no longer be executed.
+    expires_at_millis: i64,
 }
 
 impl Proposal {
@@ -134,15 +213,25 @@ impl Proposal {
     ///
     /// * `proposal_type`: [`ProposalType`]
     ///
+    /// * `description`: [`String`], the human-readable description of the proposal.
+    ///
+    /// * `expires_at_millis`: [`i64`], the time in utc milliseconds, after which the proposal expires.
+    ///
     /// ### Returns
     ///
     /// The newly created Proposal.
     ///
-    pub fn new(proposal_type: ProposalType) -> Proposal {
+    pub fn new(
+        proposal_type: ProposalType,
+        description: String,
+        expires_at_millis: i64,
+    ) -> Proposal {
         Proposal {
             proposal_type,
+            description,
             result: None,
             votes: SortedVecMap::new(),
+            expires_at_millis,
         }
     }
 }
@@ -165,6 +254,22 @@ pub enum ProposalType {
         /// The proposed criteria to replace the old one.
         new_criteria: Criteria,
     },
+
+    /// `BatchTransfer`
+    #[discriminant(2)]
+    BatchTransfer {
+        /// The transfers to make. Either all of them are made, or the proposal can be executed again.
+        payouts: Vec<Payout>,
+    },
+}
+
+/// A single transfer in a batch transfer proposal.
+#[derive(CreateTypeSpec, ReadWriteState, ReadWriteRPC)]
+pub struct Payout {
+    /// The address of the receiver.
+    receiver: Address,
+    /// The amount to transfer.
+    amount: u128,
 }
 
 /// The MOCCA contract state, that holds the current proposals, the criteria for approval and the amount the contract
@@ -182,6 +287,8 @@ pub enum ProposalType {
 ///
 /// * `proposal`: [`AvlTreeMap<u32, Proposal>`], the proposals that is currently voted on or already executed.
 ///
+/// * `proposal_lifetime_millis`: [`i64`], the time in milliseconds a proposal can be executed after being proposed.
+///
 #[state]
 pub struct MoccaState {
     criteria: Criteria,
@@ -189,9 +296,86 @@ pub struct MoccaState {
     amount_of_tokens: u128,
     next_proposal_id: u32,
     proposal: AvlTreeMap<u32, Proposal>,
+    proposal_lifetime_millis: i64,
 }
 
 impl MoccaState {
+    /// Get the current tally of the votes on a proposal, using the current criteria.
+    ///
+    /// ### Parameters
+    ///
+    ///  *  `proposal_id`: [`u32`], the id of the proposal.
+    ///
+    /// ### Returns
+    ///
+    /// The total weight of the `Yes`, `No` and `Abstain` votes, respectively.
+    /// Panics if no proposal exists with the given id.
+    ///
+    pub fn current_tally(&self, proposal_id: u32) -> (u32, u32, u32) {
+        let proposal = self
+            .proposal
+            .get(&proposal_id)
+            .unwrap_or_else(|| panic!("No proposal with id {}", proposal_id));
+        self.criteria.tally(&proposal.votes)
+    }
+
+    /// Enumerate the votes cast on a proposal, ordered by voter address, with the weight each vote carries under
+    /// the current criteria. The votes are returned in pages of at most [`MAX_VOTES_PER_PAGE`] votes, to keep the
+    /// cost of reading them bounded for large sets of voters.
+    ///
+    /// ### Parameters
+    ///
+    ///  *  `proposal_id`: [`u32`], the id of the proposal.
+    ///
+    ///  *  `offset`: [`usize`], the number of votes to skip.
+    ///
+    ///  *  `limit`: [`usize`], the maximum number of votes to return. Capped at [`MAX_VOTES_PER_PAGE`].
+    ///
+    /// ### Returns
+    ///
+    /// The votes from `offset` onwards, at most `limit` of them.
+    /// Panics if no proposal exists with the given id.
+    ///
+    pub fn proposal_votes(&self, proposal_id: u32, offset: usize, limit: usize) -> Vec<CastVote> {
+        let proposal = self
+            .proposal
+            .get(&proposal_id)
+            .unwrap_or_else(|| panic!("No proposal with id {}", proposal_id));
+        proposal
+            .votes
+            .iter()
+            .skip(offset)
+            .take(limit.min(MAX_VOTES_PER_PAGE))
+            .map(|(voter, vote)| CastVote {
+                voter: *voter,
+                vote: vote.clone(),
+                weight: if self.criteria.allowed_voter(voter) {
+                    self.criteria.get_vote_weight(voter)
+                } else {
+                    0
+                },
+            })
+            .collect()
+    }
+
+    /// Get the description of a proposal.
+    ///
+    /// ### Parameters
+    ///
+    ///  *  `proposal_id`: [`u32`], the id of the proposal.
+    ///
+    /// ### Returns
+    ///
+    /// The human-readable description given when the proposal was made.
+    /// Panics if no proposal exists with the given id.
+    ///
+    pub fn proposal_description(&self, proposal_id: u32) -> String {
+        self.proposal
+            .get(&proposal_id)
+            .unwrap_or_else(|| panic!("No proposal with id {}", proposal_id))
+            .description
+    }
+
     /// Check if the current votes for a proposal is enough to make a conclusive decision.
     ///
     /// ### Parameters
@@ -202,19 +386,14 @@ impl MoccaState {
     ///
     /// `Approve`, if the weight of the `Yes` votes is greater than or equal to the threshold.
     /// `Denied`, if the weight of the `No` votes is greater than the threshold.
-    ///  Panics if the vote is inconclusive with the current amount of votes.
+    ///  Panics if the combined weight of all votes has not reached the quorum, or if the vote is inconclusive with the
+    ///  current amount of votes. `Abstain` votes count towards the quorum, but neither approval nor denial.
     ///
     fn check_result(&self, votes: &SortedVecMap<Address, Vote>) -> VoteResult {
-        let mut yes_vote_weight = 0;
-        let mut no_vote_weight = 0;
+        let (yes_vote_weight, no_vote_weight, abstain_vote_weight) = self.criteria.tally(votes);
 
-        for (address, vote) in votes.iter() {
-            if self.criteria.allowed_voter(address) {
-                match vote {
-                    Vote::Yes {} => yes_vote_weight += self.criteria.get_vote_weight(address),
-                    Vote::No {} => no_vote_weight += self.criteria.get_vote_weight(address),
-                }
-            }
+        if yes_vote_weight + no_vote_weight + abstain_vote_weight < self.criteria.quorum {
+            panic!("Voting quorum not reached.")
         }
 
         if yes_vote_weight >= self.criteria.threshold {
@@ -237,6 +416,9 @@ impl MoccaState {
 ///
 ///   * `token_contract`: [`Address`],
 ///
+///   * `proposal_lifetime_millis`: [`i64`], the time in milliseconds a proposal can be executed after being proposed.
+///     Must be positive. The expiry of a proposal is capped at the largest representable time.
+///
 /// ### Returns
 ///
 /// The initial state of the MOCCA contact.
@@ -246,8 +428,12 @@ pub fn initialize(
     _ctx: ContractContext,
     criteria: Criteria,
     token_contract: Address,
+    proposal_lifetime_millis: i64,
 ) -> MoccaState {
     criteria.check_validity();
+    if proposal_lifetime_millis <= 0 {
+        panic!("The lifetime of proposals must be positive.")
+    }
 
     MoccaState {
         criteria,
@@ -255,6 +441,7 @@ pub fn initialize(
         amount_of_tokens: 0,
         next_proposal_id: 0,
         proposal: AvlTreeMap::new(),
+        proposal_lifetime_millis,
     }
 }
 
@@ -293,7 +480,9 @@ pub fn escrow(
     (state, vec![events])
 }
 
-/// Send a new proposal for voting. The proposal can either be transfer of tokens or a new criteria to use.
+/// Send a new proposal for voting. The proposal can either be transfer of tokens, a batch transfer of tokens to
+/// multiple receivers or a new criteria to use.
+/// The proposal expires after the proposal lifetime of the contract, after which it can no longer be executed.
 ///
 /// ### Parameters
 ///
@@ -303,20 +492,39 @@ pub fn escrow(
 ///
 ///   * `proposal_type`: [`ProposalType`],
 ///
+///   * `description`: [`String`], a human-readable description of the proposal. At most
+///     [`MAX_DESCRIPTION_LENGTH`] bytes long.
+///
 /// ### Returns
 ///
 /// The state with the proposal added.
 ///
 #[action(shortname = 0x02)]
 pub fn propose(
-    _ctx: ContractContext,
+    ctx: ContractContext,
     mut state: MoccaState,
     proposal_type: ProposalType,
+    description: String,
 ) -> MoccaState {
-    if let NewCriteria { ref new_criteria } = proposal_type {
-        new_criteria.check_validity()
+    if description.len() > MAX_DESCRIPTION_LENGTH {
+        panic!(
+            "The description cannot be longer than {} bytes.",
+            MAX_DESCRIPTION_LENGTH
+        )
     }
-    let proposal = Proposal::new(proposal_type);
+    match proposal_type {
+        NewCriteria { ref new_criteria } => new_criteria.check_validity(),
+        ProposalType::BatchTransfer { ref payouts } => {
+            if payouts.is_empty() {
+                panic!("Cannot propose a batch transfer without payouts.")
+            }
+        }
+        ProposalType::Transfer { .. } => {}
+    }
+    let expires_at_millis = ctx
+        .block_production_time
+        .saturating_add(state.proposal_lifetime_millis);
+    let proposal = Proposal::new(proposal_type, description, expires_at_millis);
     state.proposal.insert(state.next_proposal_id, proposal);
     state.next_proposal_id += 1;
     state
@@ -332,7 +540,7 @@ pub fn propose(
 ///
 ///   * `proposal_id`: [`u32`], the proposal the vote is for.
 ///
-///   * `vote`: [`Vote`], a `Yes` or `No` vote.
+///   * `vote`: [`Vote`], a `Yes`, `No` or `Abstain` vote. Replaces any previous vote by the sender.
 ///
 /// ### Returns
 ///
@@ -364,7 +572,7 @@ pub fn vote(
     state
 }
 
-/// Execute a proposal.
+/// Execute a proposal. Expired proposals cannot be executed.
 ///
 /// ### Parameters
 ///
@@ -378,11 +586,14 @@ pub fn vote(
 ///
 /// The state with the result of the proposal is set.
 /// If the proposal was `Approved`, then in the case of a transfer proposal, a transfer event to the transfer contract
-/// and a callback is registered. In the case of a new criteria, the new criteria replaces the old in the state.
+/// and a callback is registered. In the case of a batch transfer proposal, a transfer event for each payout and a
+/// single callback for all of them is registered. A batch transfer is only executed if the escrowed tokens cover all
+/// payouts, as a failure of some of the payouts would otherwise reset the proposal after others were paid. In the case
+/// of a new criteria, the validity of the new criteria is checked again, and it replaces the old in the state.
 ///
 #[action(shortname = 0x04)]
 pub fn execute(
-    _ctx: ContractContext,
+    ctx: ContractContext,
     mut state: MoccaState,
     proposal_id: u32,
 ) -> (MoccaState, Vec<EventGroup>) {
@@ -393,6 +604,9 @@ pub fn execute(
     if proposal.result.is_some() {
         panic!("The proposal has already been executed.");
     }
+    if ctx.block_production_time > proposal.expires_at_millis {
+        panic!("The proposal has expired.");
+    }
 
     let result: VoteResult = state.check_result(&proposal.votes);
     let mut event_builder = EventGroup::builder();
@@ -415,8 +629,28 @@ pub fn execute(
                     .done();
             }
             ProposalType::NewCriteria { ref new_criteria } => {
+                new_criteria.check_validity();
                 state.criteria = new_criteria.clone();
             }
+            ProposalType::BatchTransfer { ref payouts } => {
+                let token = MPC20Contract::at_address(state.token_contract);
+                let mut total_amount: u128 = 0;
+                for payout in payouts {
+                    token.transfer(&mut event_builder, &payout.receiver, payout.amount);
+                    total_amount = total_amount
+                        .checked_add(payout.amount)
+                        .expect("The total amount of the batch transfer overflows.");
+                }
+                assert!(
+                    state.amount_of_tokens >= total_amount,
+                    "The escrowed tokens ({}) cannot cover the batch transfer of {} tokens.",
+                    state.amount_of_tokens,
+                    total_amount
+                );
+                event_builder
+                    .with_callback_rpc(transfer_callback::rpc(proposal_id, total_amount))
+                    .done();
+            }
         }
     }
 
@@ -457,7 +691,7 @@ pub fn escrow_callback(
     (state, vec![])
 }
 
-/// Check if the transfer of tokens from a proposal transfer was a success.
+/// Check if the transfer of tokens from a proposal transfer or batch transfer was a success.
 ///
 /// ### Parameters
 ///
@@ -469,7 +703,7 @@ pub fn escrow_callback(
 ///
 ///   * `proposal_id`: [`u32`], the id of the transfer proposal.
 ///
-///   * `amount`: [`u128`], the amount of tokens, that was sent.
+///   * `amount`: [`u128`], the amount of tokens, that was sent. For batch transfers the sum of all payouts.
 ///
 /// ### Returns
 ///
@@ -0,0 +1,398 @@
+use super::*;
+use defi_common::test_util::{self, account, contract};
+
+fn criteria() -> Criteria {
+    Criteria {
+        voters: vec![
+            Voter {
+                address: account(1),
+                weight: 1,
+            },
+            Voter {
+                address: account(2),
+                weight: 2,
+            },
+            Voter {
+                address: account(3),
+                weight: 3,
+            },
+            Voter {
+                address: account(4),
+                weight: 4,
+            },
+        ],
+        threshold: 6,
+        quorum: 8,
+    }
+}
+
+fn context(sender: Address) -> ContractContext {
+    test_util::context(sender, 1000)
+}
+
+fn state() -> MoccaState {
+    initialize(context(account(1)), criteria(), contract(0xAA), 60_000)
+}
+
+fn transfer_proposal() -> ProposalType {
+    ProposalType::Transfer {
+        amount: 1000,
+        receiver: account(9),
+    }
+}
+
+#[test]
+fn tally_counts_all_three_vote_kinds() {
+    let mut votes = SortedVecMap::new();
+    votes.insert(account(1), Vote::Yes {});
+    votes.insert(account(2), Vote::No {});
+    votes.insert(account(3), Vote::Abstain {});
+    votes.insert(account(4), Vote::Yes {});
+
+    assert_eq!(criteria().tally(&votes), (5, 2, 3));
+}
+
+#[test]
+fn tally_ignores_unknown_voters() {
+    let mut votes = SortedVecMap::new();
+    votes.insert(account(3), Vote::Abstain {});
+    votes.insert(account(9), Vote::Yes {});
+
+    assert_eq!(criteria().tally(&votes), (0, 0, 3));
+}
+
+#[test]
+fn tally_of_no_votes_is_empty() {
+    assert_eq!(criteria().tally(&SortedVecMap::new()), (0, 0, 0));
+}
+
+#[test]
+fn valid_criteria() {
+    criteria().check_validity();
+}
+
+#[test]
+#[should_panic(expected = "Cannot use a criteria without assigned voters.")]
+fn criteria_without_voters_is_invalid() {
+    Criteria {
+        voters: vec![],
+        threshold: 0,
+        quorum: 0,
+    }
+    .check_validity();
+}
+
+#[test]
+#[should_panic(expected = "Threshold cannot be larger then the total weight of votes.")]
+fn criteria_with_unachievable_threshold_is_invalid() {
+    Criteria {
+        threshold: 11,
+        ..criteria()
+    }
+    .check_validity();
+}
+
+#[test]
+#[should_panic(expected = "Threshold must be positive.")]
+fn criteria_with_zero_threshold_is_invalid() {
+    Criteria {
+        threshold: 0,
+        ..criteria()
+    }
+    .check_validity();
+}
+
+#[test]
+#[should_panic(expected = "is registered more than once.")]
+fn criteria_with_duplicate_voter_is_invalid() {
+    Criteria {
+        voters: vec![
+            Voter {
+                address: account(1),
+                weight: 5,
+            },
+            Voter {
+                address: account(1),
+                weight: 10,
+            },
+        ],
+        threshold: 15,
+        quorum: 0,
+    }
+    .check_validity();
+}
+
+#[test]
+#[should_panic(expected = "Quorum cannot be larger then the total weight of votes.")]
+fn criteria_with_unachievable_quorum_is_invalid() {
+    Criteria {
+        quorum: 11,
+        ..criteria()
+    }
+    .check_validity();
+}
+
+/// Creates a criteria of the given voters, each with a weight of one, and a threshold of one.
+fn criteria_of(voter_ids: &[u8]) -> Criteria {
+    Criteria {
+        voters: voter_ids
+            .iter()
+            .map(|id| Voter {
+                address: account(*id),
+                weight: 1,
+            })
+            .collect(),
+        threshold: 1,
+        quorum: 0,
+    }
+}
+
+/// Proposes the given new criteria, and votes yes to it with enough weight to approve it.
+fn propose_approved_criteria(state: MoccaState, new_criteria: Criteria) -> MoccaState {
+    let proposal_id = state.next_proposal_id;
+    let mut state = propose(
+        context(account(1)),
+        state,
+        ProposalType::NewCriteria { new_criteria },
+        String::new(),
+    );
+    for id in 2..=4 {
+        state = vote(context(account(id)), state, proposal_id, Vote::Yes {});
+    }
+    state
+}
+
+#[test]
+fn new_criteria_replacing_all_voters_is_executed() {
+    let state = propose_approved_criteria(state(), criteria_of(&[5, 6]));
+
+    let (state, _) = execute(context(account(1)), state, 0);
+
+    assert!(state.criteria.allowed_voter(&account(5)));
+    assert!(state.criteria.allowed_voter(&account(6)));
+    assert!(!state.criteria.allowed_voter(&account(1)));
+}
+
+#[test]
+fn proposal_description_is_persisted() {
+    let state = propose(
+        context(account(9)),
+        state(),
+        transfer_proposal(),
+        "Pay the auditors for the Q3 review.".to_string(),
+    );
+
+    assert_eq!(
+        state.proposal_description(0),
+        "Pay the auditors for the Q3 review."
+    );
+}
+
+#[test]
+fn proposal_description_may_be_empty_or_at_max_length() {
+    let state = propose(
+        context(account(9)),
+        state(),
+        transfer_proposal(),
+        String::new(),
+    );
+    let state = propose(
+        context(account(9)),
+        state,
+        transfer_proposal(),
+        "a".repeat(MAX_DESCRIPTION_LENGTH),
+    );
+
+    assert_eq!(state.proposal_description(0), "");
+    assert_eq!(state.proposal_description(1).len(), MAX_DESCRIPTION_LENGTH);
+}
+
+#[test]
+#[should_panic(expected = "The description cannot be longer than 1024 bytes.")]
+fn over_length_proposal_description_is_rejected() {
+    propose(
+        context(account(9)),
+        state(),
+        transfer_proposal(),
+        "a".repeat(MAX_DESCRIPTION_LENGTH + 1),
+    );
+}
+
+#[test]
+#[should_panic(expected = "No proposal with id 0")]
+fn description_of_unknown_proposal() {
+    state().proposal_description(0);
+}
+
+#[test]
+fn proposal_votes_match_submitted_votes() {
+    let state = propose(
+        context(account(9)),
+        state(),
+        transfer_proposal(),
+        String::new(),
+    );
+    let state = vote(context(account(3)), state, 0, Vote::Abstain {});
+    let state = vote(context(account(2)), state, 0, Vote::Yes {});
+    let state = vote(context(account(4)), state, 0, Vote::No {});
+    let state = vote(context(account(2)), state, 0, Vote::No {});
+
+    assert_eq!(
+        state.proposal_votes(0, 0, MAX_VOTES_PER_PAGE),
+        vec![
+            CastVote {
+                voter: account(2),
+                vote: Vote::No {},
+                weight: 2,
+            },
+            CastVote {
+                voter: account(3),
+                vote: Vote::Abstain {},
+                weight: 3,
+            },
+            CastVote {
+                voter: account(4),
+                vote: Vote::No {},
+                weight: 4,
+            },
+        ]
+    );
+}
+
+#[test]
+fn proposal_votes_are_paged() {
+    let mut state = propose(
+        context(account(9)),
+        state(),
+        transfer_proposal(),
+        String::new(),
+    );
+    for id in 1..=4 {
+        state = vote(context(account(id)), state, 0, Vote::Yes {});
+    }
+
+    let voters = |votes: Vec<CastVote>| -> Vec<Address> {
+        votes.iter().map(|cast_vote| cast_vote.voter).collect()
+    };
+    assert_eq!(
+        voters(state.proposal_votes(0, 0, 3)),
+        vec![account(1), account(2), account(3)]
+    );
+    assert_eq!(voters(state.proposal_votes(0, 3, 3)), vec![account(4)]);
+    assert!(state.proposal_votes(0, 4, 3).is_empty());
+    assert!(state.proposal_votes(0, 0, 0).is_empty());
+}
+
+#[test]
+fn proposal_votes_of_removed_voter_has_no_weight() {
+    let mut state = propose(
+        context(account(9)),
+        state(),
+        transfer_proposal(),
+        String::new(),
+    );
+    state = vote(context(account(1)), state, 0, Vote::Yes {});
+    state.criteria = Criteria {
+        voters: vec![Voter {
+            address: account(2),
+            weight: 2,
+        }],
+        threshold: 2,
+        quorum: 0,
+    };
+
+    assert_eq!(
+        state.proposal_votes(0, 0, MAX_VOTES_PER_PAGE),
+        vec![CastVote {
+            voter: account(1),
+            vote: Vote::Yes {},
+            weight: 0,
+        }]
+    );
+}
+
+#[test]
+#[should_panic(expected = "No proposal with id 0")]
+fn votes_of_unknown_proposal() {
+    state().proposal_votes(0, 0, MAX_VOTES_PER_PAGE);
+}
+
+#[test]
+#[should_panic(expected = "The total amount of the batch transfer overflows.")]
+fn batch_transfer_with_overflowing_total_is_rejected_when_executed() {
+    let payouts = vec![
+        Payout {
+            receiver: account(8),
+            amount: u128::MAX,
+        },
+        Payout {
+            receiver: account(9),
+            amount: 1,
+        },
+    ];
+    let mut state = propose(
+        context(account(1)),
+        state(),
+        ProposalType::BatchTransfer { payouts },
+        String::new(),
+    );
+    for id in 2..=4 {
+        state = vote(context(account(id)), state, 0, Vote::Yes {});
+    }
+
+    execute(context(account(1)), state, 0);
+}
+
+#[test]
+fn proposal_expiry_saturates_for_long_lifetimes() {
+    let state = initialize(context(account(1)), criteria(), contract(0xAA), i64::MAX);
+    let state = propose(
+        context(account(1)),
+        state,
+        transfer_proposal(),
+        String::new(),
+    );
+
+    assert_eq!(state.proposal.get(&0).unwrap().expires_at_millis, i64::MAX);
+}
+
+fn approved_batch_transfer(amount_of_tokens: u128) -> MoccaState {
+    let payouts = vec![
+        Payout {
+            receiver: account(8),
+            amount: 1000,
+        },
+        Payout {
+            receiver: account(9),
+            amount: 2000,
+        },
+    ];
+    let mut state = state();
+    state.amount_of_tokens = amount_of_tokens;
+    let mut state = propose(
+        context(account(1)),
+        state,
+        ProposalType::BatchTransfer { payouts },
+        String::new(),
+    );
+    for id in 2..=4 {
+        state = vote(context(account(id)), state, 0, Vote::Yes {});
+    }
+    state
+}
+
+#[test]
+fn batch_transfer_covered_by_escrow_is_executed() {
+    let (state, events) = execute(context(account(1)), approved_batch_transfer(3000), 0);
+
+    assert!(state.proposal.get(&0).unwrap().result == Some(Approved {}));
+    assert_eq!(events.len(), 1);
+}
+
+#[test]
+#[should_panic(
+    expected = "The escrowed tokens (2999) cannot cover the batch transfer of 3000 tokens."
+)]
+fn batch_transfer_partially_covered_by_escrow_is_rejected() {
+    execute(context(account(1)), approved_batch_transfer(2999), 0);
+}
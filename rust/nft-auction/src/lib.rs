@@ -1,18 +1,29 @@
 #![doc = include_str!("../README.md")]
 #![allow(unused_variables)]
 
+#[cfg(test)]
+mod tests;
+
 #[macro_use]
 extern crate pbc_contract_codegen;
 
 use create_type_spec_derive::CreateTypeSpec;
+use defi_common::events;
 use defi_common::interact_mpc20;
-use pbc_contract_common::address::{Address, AddressType};
+use pbc_contract_common::address::{Address, AddressType, Shortname};
 use pbc_contract_common::avl_tree_map::AvlTreeMap;
 use pbc_contract_common::context::{CallbackContext, ContractContext};
-use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::events::{EventGroup, EventGroupBuilder, GasCost};
 use read_write_rpc_derive::{ReadRPC, WriteRPC};
 use read_write_state_derive::ReadWriteState;
 
+/// [`Shortname`] of the [`refund_available_event()`] action.
+const SHORTNAME_REFUND_AVAILABLE_EVENT: Shortname = Shortname::from_u32(0x0A);
+
+/// Gas reserved for [`bid_callback()`], which must be able to emit a [`refund_available_event()`]
+/// for the refunded bidder.
+const BID_CALLBACK_GAS_COST: GasCost = 1000 + events::GAS_COST_EVENT;
+
 /// Bids in the NFT auction.
 ///
 /// ### Fields:
@@ -26,17 +37,70 @@ pub struct Bid {
     amount: u128,
 }
 
+/// The asset that bids in the NFT auction are placed in.
+///
+/// Both variants are escrowed through the MPC20 interface. Native coins are escrowed through the
+/// BYOC contract for the coin, which exposes the coin balances of accounts as an MPC20 token. BYOC
+/// contracts are system contracts, which is checked on [`initialize()`].
+#[derive(ReadRPC, WriteRPC, ReadWriteState, CreateTypeSpec, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BiddingAsset {
+    /// Bids are placed in an MPC20 token.
+    #[discriminant(0)]
+    Token {
+        /// The address of the MPC20 token contract.
+        token_address: Address,
+    },
+    /// Bids are placed in the native coin of the chain.
+    #[discriminant(1)]
+    Native {
+        /// The address of the BYOC contract of the native coin. Must be a system contract.
+        byoc_address: Address,
+    },
+}
+
+impl BiddingAsset {
+    /// Get the contract that bids are escrowed through.
+    ///
+    /// ### Returns:
+    ///
+    /// The [`interact_mpc20::MPC20Contract`] that bids are transferred through.
+    fn escrow_contract(&self) -> interact_mpc20::MPC20Contract {
+        match self {
+            BiddingAsset::Token { token_address } => {
+                interact_mpc20::MPC20Contract::at_address(*token_address)
+            }
+            BiddingAsset::Native { byoc_address } => {
+                interact_mpc20::MPC20Contract::at_address(*byoc_address)
+            }
+        }
+    }
+}
+
+/// An NFT sold by the auction, as part of the bundle for sale.
+///
+/// ### Fields:
+///
+/// * `address`: [`Address`], the address of the NFT contract.
+///
+/// * `id`: [`u128`], the id of the NFT.
+#[derive(ReadRPC, WriteRPC, ReadWriteState, CreateTypeSpec, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NftForSale {
+    address: Address,
+    id: u128,
+}
+
 /// Claims used by the contract's claim-map.
 ///
 /// ### Fields:
 ///
 /// * `tokens_for_bidding`: [`u128`], The claimable tokens for bidding.
 ///
-/// * `nft_for_sale`: [`Option<Address>`], The claimable nft for sale.
+/// * `nfts_for_sale`: [`Vec<NftForSale>`], The claimable NFTs for sale. Empty if the bundle for
+/// sale cannot be claimed.
 #[derive(ReadWriteState, CreateTypeSpec)]
 pub struct Claim {
     tokens_for_bidding: u128,
-    nft_for_sale: Option<Address>,
+    nfts_for_sale: Vec<NftForSale>,
 }
 
 /// Constants for the different phases of the contract.
@@ -61,31 +125,35 @@ pub const CANCELLED: ContractStatus = 3;
 ///
 /// * `end_time`: [`i64`], the end time in millis UTC.
 ///
-/// * `nft_for_sale`: [`Address`], the address of the NFT sold by the contract.
+/// * `nfts_for_sale`: [`Vec<NftForSale>`], the bundle of NFTs sold together by the contract.
 ///
-/// * `token_for_bidding`: [`Address`], the address of the token used for bids.
+/// * `bidding_asset`: [`BiddingAsset`], the asset used for bids.
 ///
 /// * `highest_bidder`: [`Bid`], the current highest `Bid`.
 ///
-/// * `reserve_price`: [`u128`], the reserve price (minimum cost of the tokens for sale).
+/// * `reserve_price`: [`u128`], the reserve price (minimum cost of the tokens for sale). A reserve
+/// price of 0 means that the auction has no reserve.
 ///
-/// * `min_increment`: [`u128`], the minimum increment of each bid.
+/// * `min_increment`: [`u128`], the minimum increment of each bid. Always positive.
 ///
 /// * `claim_map`: [`AvlTreeMap<Address, Claim>`], the map of all claimable tokens and/or claimable NFT.
 ///
 /// * `status`: [`u8`], the status of the contract.
+///
+/// * `allowed_bidders`: [`AvlTreeMap<Address, bool>`], the bidders allowed to bid in a private
+/// sale. If empty, anyone can bid.
 #[state]
 pub struct NftAuctionContractState {
     contract_owner: Address,
     end_time_millis: i64,
-    nft_for_sale_address: Address,
-    nft_for_sale_id: u128,
-    token_for_bidding: Address,
+    nfts_for_sale: Vec<NftForSale>,
+    bidding_asset: BiddingAsset,
     highest_bidder: Bid,
     reserve_price: u128,
     min_increment: u128,
     claim_map: AvlTreeMap<Address, Claim>,
     status: ContractStatus,
+    allowed_bidders: AvlTreeMap<Address, bool>,
 }
 
 impl NftAuctionContractState {
@@ -103,16 +171,87 @@ impl NftAuctionContractState {
                 bidder,
                 Claim {
                     tokens_for_bidding: 0,
-                    nft_for_sale: None,
+                    nfts_for_sale: vec![],
                 },
             );
         }
 
         let mut value = self.claim_map.get(&bidder).unwrap();
         value.tokens_for_bidding += additional_claim.tokens_for_bidding;
-        value.nft_for_sale = additional_claim.nft_for_sale;
+        value.nfts_for_sale.extend(additional_claim.nfts_for_sale);
         self.claim_map.insert(bidder, value);
     }
+
+    /// Add a refund of `amount` bidding tokens to the `claim_map` of the contract, and add a
+    /// [`refund_available_event()`] to `event_group`, notifying the `bidder` that the refund can
+    /// be claimed. The event is omitted if `amount` is zero, as there is nothing to reclaim.
+    ///
+    /// ### Returns
+    ///
+    /// Whether a [`refund_available_event()`] was added to `event_group`.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `event_group`: The [`EventGroupBuilder`] to add the event to.
+    ///
+    /// * `contract_address`: The [`Address`] of the auction contract.
+    ///
+    /// * `bidder`: The [`Address`] of the bidder being refunded.
+    ///
+    /// * `amount`: The amount of bidding tokens refunded.
+    fn add_refund_to_claim_map(
+        &mut self,
+        event_group: &mut EventGroupBuilder,
+        contract_address: Address,
+        bidder: Address,
+        amount: u128,
+    ) -> bool {
+        self.add_to_claim_map(
+            bidder,
+            Claim {
+                tokens_for_bidding: amount,
+                nfts_for_sale: vec![],
+            },
+        );
+        if amount == 0 {
+            return false;
+        }
+        event_group
+            .call(contract_address, SHORTNAME_REFUND_AVAILABLE_EVENT)
+            .argument(bidder)
+            .argument(amount)
+            .with_cost(events::GAS_COST_EVENT)
+            .done();
+        true
+    }
+
+    /// Check whether `bidder` is allowed to bid. Anyone is allowed to bid if the allow-list of
+    /// bidders is empty.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `bidder`: The [`Address`] of the bidder.
+    ///
+    /// ### Returns:
+    ///
+    /// True if `bidder` is allowed to bid, false otherwise.
+    fn is_allowed_bidder(&self, bidder: &Address) -> bool {
+        self.allowed_bidders.is_empty() || self.allowed_bidders.contains_key(bidder)
+    }
+
+    /// Panics if `sender` is not allowed to change the allowed bidders of the auction.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `sender`: The [`Address`] of the sender of the change.
+    fn assert_can_change_allowed_bidders(&self, sender: &Address) {
+        if *sender != self.contract_owner {
+            panic!("Only the contract owner can change the allowed bidders");
+        }
+        if self.status != CREATION && self.status != BIDDING {
+            panic!("Tried to change the allowed bidders when the status isn't Creation or Bidding");
+        }
+    }
 }
 
 /// Initial function to bootstrap the contracts state.
@@ -121,17 +260,24 @@ impl NftAuctionContractState {
 ///
 /// * `ctx`: [`ContractContext`], initial context.
 ///
-/// * `nft_for_sale`: [`Address`], the address of the NFT for sale.
+/// * `nfts_for_sale`: [`Vec<NftForSale>`], the bundle of NFTs for sale, which are escrowed and
+/// sold together. Must be non-empty.
 ///
-/// * `nft_for_sale_id`: [`u128`], the id of the NFT for sale.
+/// * `bidding_asset`: [`BiddingAsset`], the asset used for bidding, either an MPC20 token or the
+/// native coin. Tokens must be public contracts, while the BYOC contract of the native coin must
+/// be a system contract.
 ///
-/// * `token_for_bidding`: [`Address`], the address of the token used for bidding.
+/// * `reserve_price`: [`u128`], the reserve price (minimum cost of the NFTs for sale). A reserve
+/// price of 0 means that the auction has no reserve.
 ///
-/// * `reserve_price`: [`u128`], the reserve price (minimum cost of the NFT for sale).
-///
-/// * `min_increment`: [`u128`], the minimum increment of each bid.
+/// * `min_increment`: [`u128`], the minimum increment of each bid. Must be positive, such that a
+/// bid equal to the highest bid cannot replace the highest bidder.
 ///
 /// * `auction_duration_hours`: [`u32`], the duration of the auction in hours, from the auction is started by the contract owner.
+/// Must be positive.
+///
+/// * `allowed_bidders`: [`Vec<Address>`], the bidders allowed to bid, for private sales. If empty,
+/// anyone can bid.
 ///
 /// ### Returns:
 ///
@@ -140,27 +286,50 @@ impl NftAuctionContractState {
 #[init]
 pub fn initialize(
     ctx: ContractContext,
-    nft_for_sale_address: Address,
-    nft_for_sale_id: u128,
-    token_for_bidding: Address,
+    nfts_for_sale: Vec<NftForSale>,
+    bidding_asset: BiddingAsset,
     reserve_price: u128,
     min_increment: u128,
     auction_duration_hours: u32,
+    allowed_bidders: Vec<Address>,
 ) -> (NftAuctionContractState, Vec<EventGroup>) {
-    if nft_for_sale_address.address_type() != AddressType::PublicContract {
-        panic!("Tried to create a contract selling a non publicContract NFT");
+    if nfts_for_sale.is_empty() {
+        panic!("Tried to create a contract selling no NFTs");
+    }
+    for nft in &nfts_for_sale {
+        if nft.address.address_type() != AddressType::PublicContract {
+            panic!("Tried to create a contract selling a non publicContract NFT");
+        }
+    }
+    match bidding_asset {
+        BiddingAsset::Token { token_address } => {
+            if token_address.address_type() != AddressType::PublicContract {
+                panic!("Tried to create a contract buying a non publicContract token");
+            }
+        }
+        BiddingAsset::Native { byoc_address } => {
+            if byoc_address.address_type() != AddressType::SystemContract {
+                panic!("Tried to create a contract buying the native coin through a non systemContract");
+            }
+        }
+    }
+    if min_increment == 0 {
+        panic!("Tried to create a contract with a minimum increment of zero");
     }
-    if token_for_bidding.address_type() != AddressType::PublicContract {
-        panic!("Tried to create a contract buying a non publicContract token");
+    if auction_duration_hours == 0 {
+        panic!("Tried to create a contract with an auction duration of zero hours");
     }
     let duration_millis = i64::from(auction_duration_hours) * 60 * 60 * 1000;
     let end_time_millis = ctx.block_production_time + duration_millis;
+    let mut allowed_bidders_map = AvlTreeMap::new();
+    for bidder in allowed_bidders {
+        allowed_bidders_map.insert(bidder, true);
+    }
     let state = NftAuctionContractState {
         contract_owner: ctx.sender,
         end_time_millis,
-        nft_for_sale_address,
-        nft_for_sale_id,
-        token_for_bidding,
+        nfts_for_sale,
+        bidding_asset,
         highest_bidder: Bid {
             bidder: ctx.sender,
             amount: 0,
@@ -169,6 +338,7 @@ pub fn initialize(
         min_increment,
         claim_map: AvlTreeMap::new(),
         status: CREATION,
+        allowed_bidders: allowed_bidders_map,
     };
 
     (state, vec![])
@@ -177,7 +347,7 @@ pub fn initialize(
 /// Action for starting the contract. The function throws an error if the caller isn't the `contract_owner`
 /// or the contracts `status` isn't `STARTING`.
 /// The contract is started by creating a transfer event from the `contract_owner`
-/// to the contract of the tokens being sold as well as a callback to `start_callback`.
+/// to the contract for each of the NFTs being sold as well as a callback to `start_callback`.
 ///
 /// ### Parameters:
 ///
@@ -207,18 +377,20 @@ pub fn start(
 
     event_group.with_callback_rpc(start_callback::rpc()).done();
 
-    interact_mpc20::MPC20Contract::at_address(state.nft_for_sale_address).transfer_from(
-        &mut event_group,
-        &context.sender,
-        &context.contract_address,
-        state.nft_for_sale_id,
-    );
+    for nft in &state.nfts_for_sale {
+        interact_mpc20::MPC20Contract::at_address(nft.address).transfer_from(
+            &mut event_group,
+            &context.sender,
+            &context.contract_address,
+            nft.id,
+        );
+    }
 
     (state, vec![event_group.build()])
 }
 
-/// Callback for starting the contract. If the transfer event was successful the `status`
-/// is updated to `BIDDING`. If the transfer event failed the callback panics.
+/// Callback for starting the contract. If the transfer events were successful the `status`
+/// is updated to `BIDDING`. If any of the transfer events failed the callback panics.
 ///
 /// ### Parameters:
 ///
@@ -246,8 +418,8 @@ pub fn start_callback(
 }
 
 /// Action for bidding on the auction. The function always makes a transfer event
-/// to the token for bidding contract. On callback `bid_callback` is called to actually update
-/// the state.
+/// to the contract of the bidding asset, which for the native coin is its BYOC contract. On callback `bid_callback` is called to actually update
+/// the state. Panics if the auction has an allow-list of bidders, and the caller isn't on it.
 ///
 /// ### Parameters:
 ///
@@ -266,6 +438,10 @@ pub fn bid(
     state: NftAuctionContractState,
     bid_amount: u128,
 ) -> (NftAuctionContractState, Vec<EventGroup>) {
+    if !state.is_allowed_bidder(&context.sender) {
+        panic!("Only allowed bidders can bid on the auction");
+    }
+
     // Potential new bid, create the transfer event
     // transfer(auctionContract, bid_amount)
 
@@ -275,13 +451,16 @@ pub fn bid(
     };
 
     let mut event_group = EventGroup::builder();
-    interact_mpc20::MPC20Contract::at_address(state.token_for_bidding).transfer_from(
+    state.bidding_asset.escrow_contract().transfer_from(
         &mut event_group,
         &context.sender,
         &context.contract_address,
         bid_amount,
     );
-    event_group.with_callback_rpc(bid_callback::rpc(bid)).done();
+    event_group
+        .with_callback_rpc(bid_callback::rpc(bid))
+        .with_cost(BID_CALLBACK_GAS_COST)
+        .done();
     (state, vec![event_group.build()])
 }
 
@@ -289,6 +468,10 @@ pub fn bid(
 /// to the current highest bid and the claim map is updated accordingly.
 /// If the transfer event fails the state is unchanged.
 ///
+/// Whenever bidding tokens are added to the claim map, because the bid was too low, the auction
+/// is no longer accepting bids, or the previous highest bidder was outbid, a
+/// [`refund_available_event()`] is emitted for the refunded bidder.
+///
 /// ### Parameters:
 ///
 /// * `ctx`: [`ContractContext`], the contractContext for the callback.
@@ -311,7 +494,8 @@ pub fn bid_callback(
     bid: Bid,
 ) -> (NftAuctionContractState, Vec<EventGroup>) {
     let mut new_state = state;
-    if !callback_ctx.success {
+    let mut event_group = EventGroup::builder();
+    let refund_event_added = if !callback_ctx.success {
         panic!("Transfer event did not succeed for bid");
     } else if new_state.status != BIDDING
         || ctx.block_production_time >= new_state.end_time_millis
@@ -321,36 +505,38 @@ pub fn bid_callback(
         // transfer succeeded, since we are no longer accepting bids we add
         // this to the claim map so the sender can get his money back
         // if the bid was too small we also add it to the claim map
-        new_state.add_to_claim_map(
+        new_state.add_refund_to_claim_map(
+            &mut event_group,
+            ctx.contract_address,
             bid.bidder,
-            Claim {
-                tokens_for_bidding: bid.amount,
-                nft_for_sale: None as Option<Address>,
-            },
-        );
+            bid.amount,
+        )
     } else {
         // bidding phase and a new highest bid
         let prev_highest_bidder = new_state.highest_bidder;
         // update highest bidder
         new_state.highest_bidder = bid;
         // move previous highest bidders coin into the claim map
-        new_state.add_to_claim_map(
+        new_state.add_refund_to_claim_map(
+            &mut event_group,
+            ctx.contract_address,
             prev_highest_bidder.bidder,
-            Claim {
-                tokens_for_bidding: prev_highest_bidder.amount,
-                nft_for_sale: None as Option<Address>,
-            },
-        );
+            prev_highest_bidder.amount,
+        )
+    };
+    if refund_event_added {
+        (new_state, vec![event_group.build()])
+    } else {
+        (new_state, vec![])
     }
-    (new_state, vec![])
 }
 
-/// Action for claiming tokens and/or the NFT. Can be called at any time during the auction. Only the highest
+/// Action for claiming tokens and/or the NFTs. Can be called at any time during the auction. Only the highest
 /// bidder and the owner of the contract cannot get their escrowed tokens.
 /// If there is any available tokens for the sender in the claim map the contract creates
-/// appropriate transfer calls for the token for bidding. Likewise if there is an NFT for the sender in the claim map,
-/// the contract creates the appropriate transfer calls for the NFT. The entry in
-/// the claim map is then set to 0 for the token for bidding and the NFT for sale is set to none.
+/// appropriate transfer calls for the bidding asset. Likewise if there are NFTs for the sender in the claim map,
+/// the contract creates the appropriate transfer calls for each of the NFTs. The entry in
+/// the claim map is then set to 0 for the token for bidding and the NFTs for sale are cleared.
 ///
 /// ### Parameters:
 ///
@@ -373,26 +559,25 @@ pub fn claim(
         Some(claimable) => {
             let mut event_group = EventGroup::builder();
             if claimable.tokens_for_bidding > 0 {
-                interact_mpc20::MPC20Contract::at_address(new_state.token_for_bidding).transfer(
+                new_state.bidding_asset.escrow_contract().transfer(
                     &mut event_group,
                     &context.sender,
                     claimable.tokens_for_bidding,
                 );
             }
-            if claimable.nft_for_sale.is_some() {
-                interact_mpc20::MPC20Contract::at_address(new_state.nft_for_sale_address)
-                    .transfer_from(
-                        &mut event_group,
-                        &context.contract_address,
-                        &context.sender,
-                        new_state.nft_for_sale_id,
-                    );
+            for nft in &claimable.nfts_for_sale {
+                interact_mpc20::MPC20Contract::at_address(nft.address).transfer_from(
+                    &mut event_group,
+                    &context.contract_address,
+                    &context.sender,
+                    nft.id,
+                );
             }
             new_state.claim_map.insert(
                 context.sender,
                 Claim {
                     tokens_for_bidding: 0,
-                    nft_for_sale: None as Option<Address>,
+                    nfts_for_sale: vec![],
                 },
             );
             (new_state, vec![event_group.build()])
@@ -402,7 +587,7 @@ pub fn claim(
 
 /// Action for executing the auction. Panics if the block time is earlier than the contracts
 /// end time or if the current status is not `BIDDING`. When the contract is executed the status
-/// is changed to `ENDED`, and the highest bidder will be able to claim the sold tokens.
+/// is changed to `ENDED`, and the highest bidder will be able to claim the sold NFTs.
 /// Similarly the contract owner is able to claim the amount of bidding tokens that the highest
 /// bidder bid.
 ///
@@ -431,14 +616,14 @@ pub fn execute(
             new_state.contract_owner,
             Claim {
                 tokens_for_bidding: new_state.highest_bidder.amount,
-                nft_for_sale: None as Option<Address>,
+                nfts_for_sale: vec![],
             },
         );
         new_state.add_to_claim_map(
             new_state.highest_bidder.bidder,
             Claim {
                 tokens_for_bidding: 0,
-                nft_for_sale: Some(new_state.nft_for_sale_address),
+                nfts_for_sale: new_state.nfts_for_sale.clone(),
             },
         );
         (new_state, vec![])
@@ -448,8 +633,9 @@ pub fn execute(
 /// Action for cancelling the auction. Panics if the caller is not the contract owner, the
 /// block time is later than the contracts end time, or if the status is not `BIDDING`.
 /// When the contract is cancelled the status is changed to `CANCELLED`, and the highest bidder
-/// will be able to claim the amount of tokens he bid. Similarly the contract owner is
-/// able to claim the tokens previously for sale.
+/// will be able to claim the amount of tokens he bid, which is announced with a
+/// [`refund_available_event()`]. Similarly the contract owner is able to claim the NFTs
+/// previously for sale.
 ///
 /// ### Parameters:
 ///
@@ -474,20 +660,119 @@ pub fn cancel(
         panic!("Tried to cancel the auction when the status isn't Bidding");
     } else {
         new_state.status = CANCELLED;
-        new_state.add_to_claim_map(
-            new_state.highest_bidder.bidder,
-            Claim {
-                tokens_for_bidding: new_state.highest_bidder.amount,
-                nft_for_sale: None as Option<Address>,
-            },
+        let mut event_group = EventGroup::builder();
+        let highest_bidder = new_state.highest_bidder.bidder;
+        let highest_bid = new_state.highest_bidder.amount;
+        let refund_event_added = new_state.add_refund_to_claim_map(
+            &mut event_group,
+            context.contract_address,
+            highest_bidder,
+            highest_bid,
         );
         new_state.add_to_claim_map(
             new_state.contract_owner,
             Claim {
                 tokens_for_bidding: 0,
-                nft_for_sale: Some(new_state.nft_for_sale_address),
+                nfts_for_sale: new_state.nfts_for_sale.clone(),
             },
         );
-        (new_state, vec![])
+        if refund_event_added {
+            (new_state, vec![event_group.build()])
+        } else {
+            (new_state, vec![])
+        }
+    }
+}
+
+/// Action for adding a bidder to the allow-list of the auction. Panics if the caller is not the
+/// contract owner, or if the status is not `CREATION` or `BIDDING`. Adding a bidder to an empty
+/// allow-list makes the auction private.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`NftAuctionContractState`], the current state of the contract.
+///
+/// * `bidder`: [`Address`], the bidder to allow.
+///
+/// ### Returns
+///
+/// The new state object of type [`NftAuctionContractState`].
+#[action(shortname = 0x08)]
+pub fn add_allowed_bidder(
+    context: ContractContext,
+    state: NftAuctionContractState,
+    bidder: Address,
+) -> (NftAuctionContractState, Vec<EventGroup>) {
+    let mut new_state = state;
+    new_state.assert_can_change_allowed_bidders(&context.sender);
+    new_state.allowed_bidders.insert(bidder, true);
+    (new_state, vec![])
+}
+
+/// Action for removing a bidder from the allow-list of the auction. Panics if the caller is not
+/// the contract owner, or if the status is not `CREATION` or `BIDDING`. The last bidder cannot
+/// be removed, since an empty allow-list allows anyone to bid. Bids already placed by the
+/// removed bidder are unaffected.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`NftAuctionContractState`], the current state of the contract.
+///
+/// * `bidder`: [`Address`], the bidder to disallow.
+///
+/// ### Returns
+///
+/// The new state object of type [`NftAuctionContractState`].
+#[action(shortname = 0x09)]
+pub fn remove_allowed_bidder(
+    context: ContractContext,
+    state: NftAuctionContractState,
+    bidder: Address,
+) -> (NftAuctionContractState, Vec<EventGroup>) {
+    let mut new_state = state;
+    new_state.assert_can_change_allowed_bidders(&context.sender);
+    if !new_state.allowed_bidders.contains_key(&bidder) {
+        panic!("Tried to remove a bidder that isn't allowed to bid");
     }
+    new_state.allowed_bidders.remove(&bidder);
+    if new_state.allowed_bidders.is_empty() {
+        panic!(
+            "Cannot remove the last allowed bidder, since an empty allow-list allows anyone to bid"
+        );
+    }
+    (new_state, vec![])
+}
+
+/// Event emitted when bidding tokens are added to the claim map of `bidder`, e.g. because they
+/// were outbid, notifying them that `amount` tokens can be reclaimed with [`claim()`].
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`NftAuctionContractState`], the current state of the contract.
+///
+/// * `bidder`: [`Address`], the bidder who can claim the refund.
+///
+/// * `amount`: [`u128`], the amount of bidding tokens added to the claim of the bidder.
+///
+/// ### Returns
+///
+/// The unchanged state object of type [`NftAuctionContractState`].
+#[action(shortname = 0x0A)]
+pub fn refund_available_event(
+    context: ContractContext,
+    state: NftAuctionContractState,
+    bidder: Address,
+    amount: u128,
+) -> (NftAuctionContractState, Vec<EventGroup>) {
+    events::assert_event_sent_by_contract(
+        &context,
+        "Refund events can only be sent by the auction contract itself",
+    );
+    (state, vec![])
 }
@@ -0,0 +1,124 @@
+use super::*;
+use defi_common::test_util::{account, context, contract, contract_address};
+
+/// Address of the BYOC contract of the native coin.
+fn byoc() -> Address {
+    Address::from_components(AddressType::SystemContract, [0xB0; 20])
+}
+
+fn auction(bidding_asset: BiddingAsset) -> NftAuctionContractState {
+    let (state, _) = initialize(
+        context(account(1), 0),
+        vec![NftForSale {
+            address: contract(2),
+            id: 42,
+        }],
+        bidding_asset,
+        20,
+        5,
+        2,
+        vec![],
+    );
+    let (state, _) = start_callback(context(account(1), 0), successful_callback(), state);
+    state
+}
+
+fn successful_callback() -> CallbackContext {
+    CallbackContext {
+        success: true,
+        results: vec![],
+    }
+}
+
+fn bid_at(
+    state: NftAuctionContractState,
+    bidder: Address,
+    amount: u128,
+) -> NftAuctionContractState {
+    let (state, _) = bid_callback(
+        context(contract_address(), 1000),
+        successful_callback(),
+        state,
+        Bid { bidder, amount },
+    );
+    state
+}
+
+#[test]
+fn native_coin_bid_is_escrowed_through_byoc() {
+    let state = auction(BiddingAsset::Native {
+        byoc_address: byoc(),
+    });
+    let (_, events) = bid(context(account(3), 1000), state, 50);
+
+    let mut expected = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(byoc()).transfer_from(
+        &mut expected,
+        &account(3),
+        &contract_address(),
+        50,
+    );
+    expected
+        .with_callback_rpc(bid_callback::rpc(Bid {
+            bidder: account(3),
+            amount: 50,
+        }))
+        .with_cost(BID_CALLBACK_GAS_COST)
+        .done();
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn native_coin_outbid_bidder_is_refunded_through_byoc() {
+    let state = auction(BiddingAsset::Native {
+        byoc_address: byoc(),
+    });
+    let state = bid_at(state, account(3), 50);
+    let state = bid_at(state, account(4), 60);
+    assert_eq!(state.highest_bidder.bidder, account(4));
+
+    let (state, events) = claim(context(account(3), 2000), state);
+
+    let mut expected = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(byoc()).transfer(&mut expected, &account(3), 50);
+    assert_eq!(events, vec![expected.build()]);
+    assert_eq!(
+        state.claim_map.get(&account(3)).unwrap().tokens_for_bidding,
+        0
+    );
+}
+
+#[test]
+fn native_coin_highest_bid_is_claimed_by_owner_through_byoc() {
+    let state = auction(BiddingAsset::Native {
+        byoc_address: byoc(),
+    });
+    let state = bid_at(state, account(3), 50);
+    let (state, _) = execute(context(account(1), state.end_time_millis), state);
+
+    let (_, events) = claim(context(account(1), state.end_time_millis), state);
+
+    let mut expected = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(byoc()).transfer(&mut expected, &account(1), 50);
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+#[should_panic(
+    expected = "Tried to create a contract buying the native coin through a non systemContract"
+)]
+fn native_coin_through_public_contract_is_rejected() {
+    auction(BiddingAsset::Native {
+        byoc_address: contract(3),
+    });
+}
+
+#[test]
+#[should_panic(
+    expected = "Tried to create a contract buying the native coin through a non systemContract"
+)]
+fn native_coin_through_account_is_rejected() {
+    auction(BiddingAsset::Native {
+        byoc_address: account(3),
+    });
+}
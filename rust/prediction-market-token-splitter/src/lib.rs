@@ -4,14 +4,21 @@
 extern crate pbc_contract_codegen;
 
 use create_type_spec_derive::CreateTypeSpec;
+use defi_common::events;
 use defi_common::interact_mpc20;
+use defi_common::interact_swap::{SwapContract, SwapReserves};
+use defi_common::math::mul_div;
 use defi_common::token_balances::{DepositToken, TokenBalances};
-use pbc_contract_common::address::Address;
+use pbc_contract_common::address::{Address, Shortname};
+use pbc_contract_common::avl_tree_map::AvlTreeMap;
 use pbc_contract_common::context::{CallbackContext, ContractContext};
-use pbc_contract_common::events::EventGroup;
+use pbc_contract_common::events::{EventGroup, EventGroupBuilder, GasCost};
 use read_write_rpc_derive::ReadWriteRPC;
 use read_write_state_derive::ReadWriteState;
 
+#[cfg(test)]
+mod tests;
+
 /// An amount of tokens that can be transferred.
 pub type TokenAmount = u128;
 
@@ -19,6 +26,34 @@ const TRUE_TOKEN: DepositToken = DepositToken::TokenA {};
 const FALSE_TOKEN: DepositToken = DepositToken::TokenB {};
 const ORIGINAL_TOKEN: DepositToken = DepositToken::LiquidityToken {};
 
+/// Gas reserved for the callbacks of token transfers. The gas for the transfer itself is attached
+/// by [`interact_mpc20::MPC20Contract`].
+const TRANSFER_CALLBACK_GAS_COST: GasCost = 300;
+
+/// Gas reserved for the callback reading the reserves of the price pool. The gas for reading the
+/// reserves is attached by [`SwapContract`].
+const RESERVES_CALLBACK_GAS_COST: GasCost = 300;
+
+/// The number of parts per million making up a whole.
+const PARTS_PER_MILLION: u128 = 1_000_000;
+
+/// Implied probability of the true outcome, until it has been read from the reserves of a price
+/// pool.
+const DEFAULT_IMPLIED_PROBABILITY_PPM: u32 = 500_000;
+
+/// [`Shortname`] of the [`activated_event()`] action.
+const SHORTNAME_ACTIVATED_EVENT: Shortname = Shortname::from_u32(0x20);
+
+/// [`Shortname`] of the [`settled_event()`] action.
+const SHORTNAME_SETTLED_EVENT: Shortname = Shortname::from_u32(0x21);
+
+/// [`Shortname`] of the [`cancelled_event()`] action.
+const SHORTNAME_CANCELLED_EVENT: Shortname = Shortname::from_u32(0x22);
+
+/// Panic message of the event actions, when called by anyone but the contract itself.
+const EVENT_NOT_SENT_BY_CONTRACT_ERROR: &str =
+    "Events can only be sent by the token splitter contract itself.";
+
 fn token_from_address(state: &TokenSplitterContractState, address: Address) -> DepositToken {
     if address == state.true_token_address {
         TRUE_TOKEN
@@ -52,6 +87,184 @@ pub enum LifeStage {
         /// Whether the true or false case of the event has happened.
         outcome: bool,
     },
+    /// The event was not settled before the settlement deadline, and has been cancelled. Each true
+    /// or false token can be redeemed for half an original token.
+    #[discriminant(3)]
+    CANCELLED {},
+}
+
+/// Reward pool paying a bonus in original tokens to users splitting before a deadline, to bootstrap
+/// the market.
+///
+/// Rewards are only kept for the net amount split: the rewarded true and false tokens are locked on
+/// the balance of the user until the event is settled or cancelled, and joining them back into
+/// original tokens returns the corresponding part of the reward to the pool. What is left in the
+/// pool can be reclaimed by the funder, once the deadline has passed or the event has been settled
+/// or cancelled.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct EarlySplitRewards {
+    /// The amount of original tokens funding the reward pool when the contract is prepared.
+    pub pool_size: TokenAmount,
+    /// The bonus paid for a split, in parts per million of the amount split.
+    pub reward_ppm: u32,
+    /// The deadline in utc milliseconds, until which splits are rewarded.
+    pub deadline_millis: i64,
+    /// The amount of original tokens left in the reward pool.
+    pub remaining: TokenAmount,
+    /// The total amount of original tokens paid as rewards.
+    pub total_paid: TokenAmount,
+    /// The amount of original tokens paid as rewards to each user, and not returned by joining.
+    pub paid_per_user: AvlTreeMap<Address, TokenAmount>,
+    /// The amount of original tokens split by each user that earned a reward, and has not been
+    /// joined since. The same amount of true and false tokens is locked on the balance of the user
+    /// until the event is settled or cancelled.
+    pub rewarded_split_per_user: AvlTreeMap<Address, TokenAmount>,
+    /// The account that funded the reward pool in `prepare`, and can reclaim what is left of it.
+    pub funder: Option<Address>,
+}
+
+impl EarlySplitRewards {
+    /// Pay the reward for splitting `amount` original tokens at `time_millis` to `user`, limited by
+    /// the remaining reward pool.
+    ///
+    /// Parameters:
+    ///
+    /// * `user` The user splitting the tokens.
+    /// * `amount` The amount of original tokens split.
+    /// * `time_millis` The time of the split in utc milliseconds.
+    ///
+    /// Returns:
+    ///
+    /// The reward paid to the user, which is zero after the deadline or when the pool is exhausted.
+    fn pay_reward(&mut self, user: Address, amount: TokenAmount, time_millis: i64) -> TokenAmount {
+        if time_millis > self.deadline_millis {
+            return 0;
+        }
+        let reward_ppm = u128::from(self.reward_ppm);
+        let reward = (amount / PARTS_PER_MILLION) * reward_ppm
+            + (amount % PARTS_PER_MILLION) * reward_ppm / PARTS_PER_MILLION;
+        let reward = reward.min(self.remaining);
+        if reward == 0 {
+            return 0;
+        }
+
+        self.remaining -= reward;
+        self.total_paid += reward;
+        let paid_to_user = self.paid_per_user.get(&user).unwrap_or(0);
+        self.paid_per_user.insert(user, paid_to_user + reward);
+        let rewarded_split = self.rewarded_split_per_user.get(&user).unwrap_or(0);
+        self.rewarded_split_per_user
+            .insert(user, rewarded_split + amount);
+        reward
+    }
+
+    /// Claw back the reward for joining `amount` original tokens by `user`, returning it to the
+    /// reward pool.
+    ///
+    /// Joins are counted against the rewarded splits of the user first. The reward clawed back is
+    /// the share of the rewards paid to the user, that the joined amount makes up of the rewarded
+    /// splits, rounded up. Since the rewarded true and false tokens cannot leave the balance of the
+    /// user other than by joining, see [`EarlySplitRewards::locked_amount`], splitting and joining
+    /// repeatedly, from the same or from different accounts, cannot drain the pool.
+    ///
+    /// Parameters:
+    ///
+    /// * `user` The user joining the tokens.
+    /// * `amount` The amount of original tokens joined.
+    ///
+    /// Returns:
+    ///
+    /// The reward clawed back from the user, which is zero if the user has no rewarded splits.
+    fn claw_back_reward(&mut self, user: Address, amount: TokenAmount) -> TokenAmount {
+        let rewarded_split = self.rewarded_split_per_user.get(&user).unwrap_or(0);
+        let joined = amount.min(rewarded_split);
+        if joined == 0 {
+            return 0;
+        }
+
+        let paid_to_user = self.paid_per_user.get(&user).unwrap_or(0);
+        let rewarded_split_left = rewarded_split - joined;
+        let kept_by_user = mul_div(paid_to_user, rewarded_split_left, rewarded_split);
+        let clawed_back = paid_to_user - kept_by_user;
+
+        if rewarded_split_left == 0 {
+            self.rewarded_split_per_user.remove(&user);
+            self.paid_per_user.remove(&user);
+        } else {
+            self.rewarded_split_per_user
+                .insert(user, rewarded_split_left);
+            self.paid_per_user.insert(user, kept_by_user);
+        }
+        self.remaining += clawed_back;
+        self.total_paid -= clawed_back;
+        clawed_back
+    }
+
+    /// Get the amount of true and false tokens locked on the balance of `user` by rewarded splits.
+    /// Locked tokens cannot be withdrawn while the event is active, such that they cannot be
+    /// joined by another account without returning the reward.
+    ///
+    /// Parameters:
+    ///
+    /// * `user` The user to get the locked amount of.
+    ///
+    /// Returns:
+    ///
+    /// The amount of true tokens, and of false tokens, locked on the balance of the user.
+    fn locked_amount(&self, user: &Address) -> TokenAmount {
+        self.rewarded_split_per_user.get(user).unwrap_or(0)
+    }
+}
+
+/// An AMM pool trading true tokens against false tokens, whose reserves imply the probability of
+/// the true outcome.
+#[derive(PartialEq, Debug, ReadWriteState, CreateTypeSpec)]
+pub struct PricePool {
+    /// The address of the swap contract.
+    pub pool_address: Address,
+    /// Whether the true token is token A of the swap contract, and the false token is token B.
+    pub true_token_is_a: bool,
+}
+
+impl PricePool {
+    /// Get the probability of the true outcome implied by the reserves of the pool, in parts per
+    /// million.
+    ///
+    /// The pool is priced as a constant product market maker, where the price of a true token in
+    /// false tokens is `false_reserve / true_reserve`. Since a pair of a true and a false token is
+    /// worth one original token, the implied probability of the true outcome is:
+    ///
+    /// `false_reserve * 1_000_000 / (true_reserve + false_reserve)`
+    ///
+    /// rounded down, such that the scarcer token is priced as the more likely outcome. The swap fee
+    /// of the pool is not taken into account. An empty pool implies a probability of 500 000 ppm.
+    ///
+    /// Parameters:
+    ///
+    /// * `reserves` The reserves of the pool.
+    ///
+    /// Returns:
+    ///
+    /// The implied probability of the true outcome, in parts per million.
+    fn implied_probability(&self, reserves: &SwapReserves) -> u32 {
+        let (mut true_reserve, mut false_reserve) = if self.true_token_is_a {
+            (reserves.reserve_a, reserves.reserve_b)
+        } else {
+            (reserves.reserve_b, reserves.reserve_a)
+        };
+
+        // Scale down the reserves, such that the computation below cannot overflow.
+        while true_reserve.max(false_reserve) > u128::MAX / (2 * PARTS_PER_MILLION) {
+            true_reserve >>= 1;
+            false_reserve >>= 1;
+        }
+
+        let total_reserve = true_reserve + false_reserve;
+        if total_reserve == 0 {
+            return DEFAULT_IMPLIED_PROBABILITY_PPM;
+        }
+        (false_reserve * PARTS_PER_MILLION / total_reserve) as u32
+    }
 }
 
 /// The state of the contract.
@@ -69,10 +282,18 @@ pub struct TokenSplitterContractState {
     pub false_token_address: Address,
     /// The arbitrator that settles the outcome of the event.
     pub arbitrator_address: Address,
+    /// The deadline for settling the event in utc milliseconds, after which anyone can cancel it.
+    pub settlement_deadline_millis: i64,
     /// The life stage of the token splitter.
     pub life_stage: LifeStage,
     /// The balances of original tokens, true tokens and false tokens of all users.
     pub token_balances: TokenBalances,
+    /// The rewards paid to users splitting tokens early.
+    pub early_split_rewards: EarlySplitRewards,
+    /// The AMM pool of true and false tokens used as price oracle, if set by the arbitrator.
+    pub price_pool: Option<PricePool>,
+    /// The probability of the true outcome in parts per million, as last read from the price pool.
+    pub implied_probability_ppm: u32,
 }
 
 /// Initialize the token splitter contract.
@@ -86,20 +307,38 @@ pub struct TokenSplitterContractState {
 /// * `true_token_address` The address of the true token contract.
 /// * `false_token_address` The address of the false token contract.
 /// * `arbitrator_address` The address of the arbitrator, who will settle the event.
+/// * `settlement_deadline_millis` The deadline for settling the event in utc milliseconds.
+/// * `early_split_reward_pool` The amount of original tokens paid as rewards for early splits. The
+///   pool is funded from the balance of the caller of `prepare`. Zero disables the rewards.
+/// * `early_split_reward_ppm` The reward for an early split, in parts per million of the amount split.
+/// * `early_split_deadline_millis` The deadline in utc milliseconds, until which splits are rewarded.
 ///
 /// Returns:
 ///
 /// The initial state `TokenSplitterContractState` of the contract.
 #[init]
 pub fn initialize(
-    _context: ContractContext,
+    context: ContractContext,
     event_description: String,
     event_symbol: String,
     original_token_address: Address,
     true_token_address: Address,
     false_token_address: Address,
     arbitrator_address: Address,
+    settlement_deadline_millis: i64,
+    early_split_reward_pool: TokenAmount,
+    early_split_reward_ppm: u32,
+    early_split_deadline_millis: i64,
 ) -> TokenSplitterContractState {
+    assert!(
+        settlement_deadline_millis > context.block_production_time,
+        "Settlement deadline has to be in the future."
+    );
+    assert!(
+        u128::from(early_split_reward_ppm) <= PARTS_PER_MILLION,
+        "Early split reward cannot exceed the amount split."
+    );
+
     let token_balances = TokenBalances::new(
         original_token_address,
         true_token_address,
@@ -114,8 +353,21 @@ pub fn initialize(
         true_token_address,
         false_token_address,
         arbitrator_address,
+        settlement_deadline_millis,
         life_stage: LifeStage::PREPARING {},
         token_balances,
+        early_split_rewards: EarlySplitRewards {
+            pool_size: early_split_reward_pool,
+            reward_ppm: early_split_reward_ppm,
+            deadline_millis: early_split_deadline_millis,
+            remaining: 0,
+            total_paid: 0,
+            paid_per_user: AvlTreeMap::new(),
+            rewarded_split_per_user: AvlTreeMap::new(),
+            funder: None,
+        },
+        price_pool: None,
+        implied_probability_ppm: DEFAULT_IMPLIED_PROBABILITY_PPM,
     }
 }
 
@@ -153,7 +405,7 @@ pub fn deposit(
 
     event_group_builder
         .with_callback_rpc(deposit_callback::rpc(token, amount))
-        .with_cost(300)
+        .with_cost(TRANSFER_CALLBACK_GAS_COST)
         .done();
 
     (state, vec![event_group_builder.build()])
@@ -214,26 +466,68 @@ pub fn withdraw(
     amount: TokenAmount,
     wait_for_callback: bool,
 ) -> (TokenSplitterContractState, Vec<EventGroup>) {
-    let token = token_from_address(&state, token_address);
+    let mut event_group_builder = EventGroup::builder();
+    withdraw_internal(
+        &context,
+        &mut state,
+        &mut event_group_builder,
+        token_address,
+        amount,
+    );
+
+    let event_group = build_withdraw_event_group(event_group_builder, wait_for_callback);
+    (state, vec![event_group])
+}
+
+/// Deducts `amount` of the token at `token_address` from the balance of the sender, and adds the
+/// transfer of the tokens to the sender to `event_group_builder`.
+///
+/// While the event is active, true and false tokens locked by rewarded early splits cannot be
+/// withdrawn.
+fn withdraw_internal(
+    context: &ContractContext,
+    state: &mut TokenSplitterContractState,
+    event_group_builder: &mut EventGroupBuilder,
+    token_address: Address,
+    amount: TokenAmount,
+) {
+    let token = token_from_address(state, token_address);
 
     state
         .token_balances
         .deduct_from_token_balance(context.sender, token, amount);
 
-    let mut event_group_builder = EventGroup::builder();
+    if state.life_stage == (LifeStage::ACTIVE {}) && token != ORIGINAL_TOKEN {
+        let balance_left = state
+            .token_balances
+            .get_balance_for(&context.sender)
+            .get_amount_of(token);
+        assert!(
+            balance_left >= state.early_split_rewards.locked_amount(&context.sender),
+            "Cannot withdraw true or false tokens locked by rewarded early splits before the event is settled or cancelled."
+        );
+    }
+
     interact_mpc20::MPC20Contract::at_address(token_address).transfer(
-        &mut event_group_builder,
+        event_group_builder,
         &context.sender,
         amount,
     );
+}
 
+/// Builds the event group of a withdrawal, with a callback to `wait_withdraw_callback` if
+/// `wait_for_callback` is set.
+fn build_withdraw_event_group(
+    mut event_group_builder: EventGroupBuilder,
+    wait_for_callback: bool,
+) -> EventGroup {
     if wait_for_callback {
         event_group_builder
             .with_callback_rpc(wait_withdraw_callback::rpc())
-            .with_cost(300)
+            .with_cost(TRANSFER_CALLBACK_GAS_COST)
             .done();
     }
-    (state, vec![event_group_builder.build()])
+    event_group_builder.build()
 }
 
 /// Callback for `withdraw`. Does nothing, but enables waiting for the withdrawal to be executed.
@@ -261,6 +555,9 @@ fn wait_withdraw_callback(
 /// `split` and `join`, to be able to receive true and false tokens from this contract, since the true
 /// and false tokens are withdrawn from the balance of this contract when splitting.
 ///
+/// The early split reward pool is funded from the original tokens on the balance of the caller.
+/// Emits an [`activated_event()`] with the amount of true and false tokens transferred.
+///
 /// Precondition:
 ///
 /// The life stage of the contract is Preparing.
@@ -273,13 +570,13 @@ fn wait_withdraw_callback(
 ///
 /// Returns:
 ///
-/// The activated state of the contract.
+/// The activated state of the contract, and the event group emitting the activation event.
 #[action(shortname = 0x04)]
 pub fn prepare(
     context: ContractContext,
     mut state: TokenSplitterContractState,
     amount: TokenAmount,
-) -> TokenSplitterContractState {
+) -> (TokenSplitterContractState, Vec<EventGroup>) {
     assert_eq!(
         state.life_stage,
         LifeStage::PREPARING {},
@@ -295,14 +592,35 @@ pub fn prepare(
         FALSE_TOKEN,
         amount,
     );
+
+    let reward_pool = state.early_split_rewards.pool_size;
+    if reward_pool > 0 {
+        state.token_balances.move_tokens(
+            context.sender,
+            context.contract_address,
+            ORIGINAL_TOKEN,
+            reward_pool,
+        );
+        state.early_split_rewards.remaining = reward_pool;
+        state.early_split_rewards.funder = Some(context.sender);
+    }
+
     state.life_stage = LifeStage::ACTIVE {};
-    state
+
+    let mut event_group_builder = EventGroup::builder();
+    add_activated_event(&mut event_group_builder, &context, amount);
+    (state, vec![event_group_builder.build()])
 }
 
 /// Splits some amount of original tokens from the balance of the sender, into one true token, and
 /// one false token for each original token split. The true and false tokens are then added to
 /// the balance of the sender.
 ///
+/// Splits before the early split deadline are rewarded with original tokens from the early split
+/// reward pool, proportional to the amount split, until the pool is exhausted. The rewarded true
+/// and false tokens are locked on the balance of the sender until the event is settled or
+/// cancelled, but can be joined, which returns the corresponding part of the reward to the pool.
+///
 /// Precondition:
 ///
 /// The life stage of the contract is Active.
@@ -322,6 +640,85 @@ pub fn split(
     mut state: TokenSplitterContractState,
     amount: TokenAmount,
 ) -> TokenSplitterContractState {
+    split_internal(&context, &mut state, amount);
+
+    let reward =
+        state
+            .early_split_rewards
+            .pay_reward(context.sender, amount, context.block_production_time);
+    if reward > 0 {
+        state.token_balances.move_tokens(
+            context.contract_address,
+            context.sender,
+            ORIGINAL_TOKEN,
+            reward,
+        );
+    }
+    state
+}
+
+/// Splits some amount of original tokens from the balance of the sender, and immediately withdraws
+/// the resulting true and false tokens to the wallet of the sender. This allows market makers to
+/// obtain outcome tokens for providing liquidity elsewhere in one transaction, instead of having
+/// to `split` and `withdraw` each outcome token separately.
+///
+/// Splits using this action are not rewarded from the early split reward pool, since the true and
+/// false tokens leave the contract, and cannot be locked as for `split`.
+///
+/// Precondition:
+///
+/// The life stage of the contract is Active.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+/// * `amount` The amount to be split and withdrawn.
+/// * `wait_for_callback` Whether to create a callback, which is called after both withdrawals.
+///
+/// Returns:
+///
+/// The updated state of the contract with the original tokens split, and the event group
+/// transferring the true and false tokens to the sender.
+#[action(shortname = 0x0A)]
+pub fn split_and_withdraw(
+    context: ContractContext,
+    mut state: TokenSplitterContractState,
+    amount: TokenAmount,
+    wait_for_callback: bool,
+) -> (TokenSplitterContractState, Vec<EventGroup>) {
+    split_internal(&context, &mut state, amount);
+
+    let true_token_address = state.true_token_address;
+    let false_token_address = state.false_token_address;
+
+    let mut event_group_builder = EventGroup::builder();
+    withdraw_internal(
+        &context,
+        &mut state,
+        &mut event_group_builder,
+        true_token_address,
+        amount,
+    );
+    withdraw_internal(
+        &context,
+        &mut state,
+        &mut event_group_builder,
+        false_token_address,
+        amount,
+    );
+
+    let event_group = build_withdraw_event_group(event_group_builder, wait_for_callback);
+    (state, vec![event_group])
+}
+
+/// Moves `amount` original tokens from the balance of the sender to the contract, and the same
+/// amount of true and false tokens from the contract to the sender.
+fn split_internal(
+    context: &ContractContext,
+    state: &mut TokenSplitterContractState,
+    amount: TokenAmount,
+) {
     assert_eq!(
         state.life_stage,
         LifeStage::ACTIVE {},
@@ -343,7 +740,6 @@ pub fn split(
         FALSE_TOKEN,
         amount,
     );
-    state
 }
 
 /// Joins some true and false tokens into some original tokens, taken from the balance of the sender.
@@ -351,6 +747,9 @@ pub fn split(
 /// of the `split` action. To convert true and false tokens to original tokens after the event has been
 /// resolved, see the `prepare`, `settle` and `redeem` actions.
 ///
+/// Joining tokens that were rewarded as an early split returns the corresponding part of the reward
+/// from the balance of the sender to the early split reward pool.
+///
 /// Precondition:
 ///
 /// The life stage of the contract is Active.
@@ -391,10 +790,73 @@ pub fn join(
         ORIGINAL_TOKEN,
         amount,
     );
+
+    let clawed_back = state
+        .early_split_rewards
+        .claw_back_reward(context.sender, amount);
+    if clawed_back > 0 {
+        state.token_balances.move_tokens(
+            context.sender,
+            context.contract_address,
+            ORIGINAL_TOKEN,
+            clawed_back,
+        );
+    }
+    state
+}
+
+/// Reclaim the original tokens left in the early split reward pool, adding them to the balance of
+/// the funder. This action can only be invoked by the account that funded the pool in `prepare`.
+///
+/// Precondition:
+///
+/// The early split deadline has passed, or the life stage of the contract is Settled or Cancelled.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+///
+/// Returns:
+///
+/// The updated state of the contract, with the early split reward pool emptied.
+#[action(shortname = 0x0B)]
+pub fn reclaim_early_split_rewards(
+    context: ContractContext,
+    mut state: TokenSplitterContractState,
+) -> TokenSplitterContractState {
+    assert_eq!(
+        Some(context.sender),
+        state.early_split_rewards.funder,
+        "Only the funder of the early split reward pool can reclaim it."
+    );
+    let reclaimable = match state.life_stage {
+        LifeStage::ACTIVE {} => {
+            context.block_production_time > state.early_split_rewards.deadline_millis
+        }
+        LifeStage::SETTLED { .. } | LifeStage::CANCELLED {} => true,
+        LifeStage::PREPARING {} => false,
+    };
+    assert!(
+        reclaimable,
+        "Can only reclaim the early split reward pool after the early split deadline, or when the event has been settled or cancelled."
+    );
+
+    let remaining = state.early_split_rewards.remaining;
+    if remaining > 0 {
+        state.early_split_rewards.remaining = 0;
+        state.token_balances.move_tokens(
+            context.contract_address,
+            context.sender,
+            ORIGINAL_TOKEN,
+            remaining,
+        );
+    }
     state
 }
 
 /// Settle the outcome of the event. This action can only be invoked by the arbitrator.
+/// Emits a [`settled_event()`] with the outcome of the event.
 ///
 /// Precondition:
 ///
@@ -408,13 +870,14 @@ pub fn join(
 ///
 /// Returns:
 ///
-/// The updated state of the contract, with the event settled.
+/// The updated state of the contract, with the event settled, and the event group emitting the
+/// settlement event.
 #[action(shortname = 0x07)]
 pub fn settle(
     context: ContractContext,
     mut state: TokenSplitterContractState,
     outcome: bool,
-) -> TokenSplitterContractState {
+) -> (TokenSplitterContractState, Vec<EventGroup>) {
     assert_eq!(
         state.life_stage,
         LifeStage::ACTIVE {},
@@ -427,11 +890,52 @@ pub fn settle(
 
     state.life_stage = LifeStage::SETTLED { outcome };
 
-    state
+    let mut event_group_builder = EventGroup::builder();
+    add_settled_event(&mut event_group_builder, &context, outcome);
+    (state, vec![event_group_builder.build()])
+}
+
+/// Cancel the event, if it has not been settled by the arbitrator before the settlement deadline.
+/// This action can be invoked by anyone, and ensures that funds are not locked forever if the
+/// arbitrator disappears. Emits a [`cancelled_event()`].
+///
+/// Precondition:
+///
+/// The life stage of the contract is Active, and the settlement deadline has passed.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+///
+/// Returns:
+///
+/// The updated state of the contract, with the event cancelled, and the event group emitting the
+/// cancellation event.
+#[action(shortname = 0x09)]
+pub fn cancel_unsettled(
+    context: ContractContext,
+    mut state: TokenSplitterContractState,
+) -> (TokenSplitterContractState, Vec<EventGroup>) {
+    assert_eq!(
+        state.life_stage,
+        LifeStage::ACTIVE {},
+        "Can only cancel if life stage is Active."
+    );
+    assert!(
+        context.block_production_time > state.settlement_deadline_millis,
+        "Cannot cancel the event before the settlement deadline has passed."
+    );
+
+    state.life_stage = LifeStage::CANCELLED {};
+
+    let mut event_group_builder = EventGroup::builder();
+    add_cancelled_event(&mut event_group_builder, &context);
+    (state, vec![event_group_builder.build()])
 }
 
 /// Redeem some amount of true or false tokens back into the original tokens, based on the
-/// outcome of the event.
+/// outcome of the event. For a cancelled event, see the `redeem_cancelled` action.
 ///
 /// Precondition:
 ///
@@ -477,3 +981,304 @@ pub fn redeem(
 
     state
 }
+
+/// Redeem some amount of either true or false tokens back into the original tokens, after the
+/// event has been cancelled. Each original token backs one pair of a true and a false token, so
+/// each true or false token is redeemed for half an original token, rounded down. Holders of only
+/// one of the outcome tokens, for example bought elsewhere, can therefore redeem them on their own.
+///
+/// Precondition:
+///
+/// The life stage of the contract is Cancelled.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+/// * `token_address` The address of the true or false token contract to redeem.
+/// * `amount` The amount of tokens to redeem.
+///
+/// Returns:
+///
+/// The updated state of the contract, with the tokens redeemed.
+#[action(shortname = 0x0C)]
+pub fn redeem_cancelled(
+    context: ContractContext,
+    mut state: TokenSplitterContractState,
+    token_address: Address,
+    amount: TokenAmount,
+) -> TokenSplitterContractState {
+    assert_eq!(
+        state.life_stage,
+        LifeStage::CANCELLED {},
+        "Can only redeem single outcome tokens if life stage is Cancelled."
+    );
+    let token = token_from_address(&state, token_address);
+    assert!(
+        token == TRUE_TOKEN || token == FALSE_TOKEN,
+        "Can only redeem true or false tokens."
+    );
+
+    state
+        .token_balances
+        .move_tokens(context.sender, context.contract_address, token, amount);
+    let redeemed = amount / 2;
+    if redeemed > 0 {
+        state.token_balances.move_tokens(
+            context.contract_address,
+            context.sender,
+            ORIGINAL_TOKEN,
+            redeemed,
+        );
+    }
+
+    state
+}
+
+/// Set the AMM pool of true and false tokens, whose reserves are used to imply the probability of
+/// the true outcome. The implied probability is reset to 500 000 ppm, until it is read from the new
+/// pool using `update_implied_probability`.
+///
+/// Only the arbitrator can set the price pool.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+/// * `pool_address` The address of the swap contract trading true tokens against false tokens.
+/// * `true_token_is_a` Whether the true token is token A of the swap contract.
+///
+/// Returns:
+///
+/// The updated state of the contract, with the price pool set.
+#[action(shortname = 0x0D)]
+pub fn set_price_pool(
+    context: ContractContext,
+    mut state: TokenSplitterContractState,
+    pool_address: Address,
+    true_token_is_a: bool,
+) -> TokenSplitterContractState {
+    assert_eq!(
+        context.sender, state.arbitrator_address,
+        "Address other than that of the arbitrator cannot set the price pool."
+    );
+
+    state.price_pool = Some(PricePool {
+        pool_address,
+        true_token_is_a,
+    });
+    state.implied_probability_ppm = DEFAULT_IMPLIED_PROBABILITY_PPM;
+    state
+}
+
+/// Read the reserves of the price pool, and update the implied probability of the true outcome in
+/// [`implied_probability_callback()`]. This action can be invoked by anyone.
+///
+/// Precondition:
+///
+/// A price pool has been set by the arbitrator.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+///
+/// Returns:
+///
+/// The state of the contract, and the event group reading the reserves of the price pool.
+#[action(shortname = 0x0E)]
+pub fn update_implied_probability(
+    _context: ContractContext,
+    state: TokenSplitterContractState,
+) -> (TokenSplitterContractState, Vec<EventGroup>) {
+    let pool_address = state
+        .price_pool
+        .as_ref()
+        .expect("No price pool has been set.")
+        .pool_address;
+
+    let mut event_group_builder = EventGroup::builder();
+    SwapContract::at_address(pool_address).get_reserves(&mut event_group_builder);
+    event_group_builder
+        .with_callback_rpc(implied_probability_callback::rpc(pool_address))
+        .with_cost(RESERVES_CALLBACK_GAS_COST)
+        .done();
+
+    (state, vec![event_group_builder.build()])
+}
+
+/// Callback for `update_implied_probability`.
+/// Updates the implied probability of the true outcome from the reserves of the price pool.
+///
+/// The implied probability is left unchanged if the reserves could not be read, or if the price
+/// pool has been replaced since the reserves were requested.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `callback_context` The context of the callback.
+/// * `state` The state of the contract.
+/// * `pool_address` The address of the price pool the reserves were read from.
+///
+/// Returns:
+///
+/// The updated state of the contract.
+#[callback(shortname = 0x16)]
+pub fn implied_probability_callback(
+    _context: ContractContext,
+    callback_context: CallbackContext,
+    mut state: TokenSplitterContractState,
+    pool_address: Address,
+) -> TokenSplitterContractState {
+    let Some(price_pool) = state
+        .price_pool
+        .as_ref()
+        .filter(|price_pool| price_pool.pool_address == pool_address)
+    else {
+        return state;
+    };
+    if !callback_context.success {
+        return state;
+    }
+
+    let reserves: SwapReserves = callback_context.results[0].get_return_data();
+    state.implied_probability_ppm = price_pool.implied_probability(&reserves);
+    state
+}
+
+/// Get the probability of the true outcome in parts per million, as last read from the price pool
+/// by `update_implied_probability`. See [`PricePool::implied_probability`] for the formula.
+///
+/// Returns the implied probability to any registered callbacks.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+///
+/// Returns:
+///
+/// The unchanged state of the contract, and the event group returning the implied probability.
+#[action(shortname = 0x0F)]
+pub fn implied_probability(
+    _context: ContractContext,
+    state: TokenSplitterContractState,
+) -> (TokenSplitterContractState, Vec<EventGroup>) {
+    let implied_probability_ppm = state.implied_probability_ppm;
+    (
+        state,
+        vec![EventGroup::with_return_data(implied_probability_ppm)],
+    )
+}
+
+/// Adds an [`activated_event()`] to the given event group.
+///
+/// Parameters:
+///
+/// * `event_group` The event group to add the event to.
+/// * `context` The context of the emitting action.
+/// * `amount` The amount of true and false tokens transferred to the contract.
+fn add_activated_event(
+    event_group: &mut EventGroupBuilder,
+    context: &ContractContext,
+    amount: TokenAmount,
+) {
+    event_group
+        .call(context.contract_address, SHORTNAME_ACTIVATED_EVENT)
+        .argument(amount)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+}
+
+/// Adds a [`settled_event()`] to the given event group.
+///
+/// Parameters:
+///
+/// * `event_group` The event group to add the event to.
+/// * `context` The context of the emitting action.
+/// * `outcome` The outcome of the event.
+fn add_settled_event(
+    event_group: &mut EventGroupBuilder,
+    context: &ContractContext,
+    outcome: bool,
+) {
+    event_group
+        .call(context.contract_address, SHORTNAME_SETTLED_EVENT)
+        .argument(outcome)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+}
+
+/// Adds a [`cancelled_event()`] to the given event group.
+///
+/// Parameters:
+///
+/// * `event_group` The event group to add the event to.
+/// * `context` The context of the emitting action.
+fn add_cancelled_event(event_group: &mut EventGroupBuilder, context: &ContractContext) {
+    event_group
+        .call(context.contract_address, SHORTNAME_CANCELLED_EVENT)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+}
+
+/// Event emitted by [`prepare()`], when the life stage changes from Preparing to Active.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+/// * `_amount` The amount of true and false tokens transferred to the contract.
+///
+/// Returns:
+///
+/// The unchanged state of the contract.
+#[action(shortname = 0x20)]
+pub fn activated_event(
+    context: ContractContext,
+    state: TokenSplitterContractState,
+    _amount: TokenAmount,
+) -> TokenSplitterContractState {
+    events::assert_event_sent_by_contract(&context, EVENT_NOT_SENT_BY_CONTRACT_ERROR);
+    state
+}
+
+/// Event emitted by [`settle()`], when the arbitrator has settled the outcome of the event.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+/// * `_outcome` The outcome of the event.
+///
+/// Returns:
+///
+/// The unchanged state of the contract.
+#[action(shortname = 0x21)]
+pub fn settled_event(
+    context: ContractContext,
+    state: TokenSplitterContractState,
+    _outcome: bool,
+) -> TokenSplitterContractState {
+    events::assert_event_sent_by_contract(&context, EVENT_NOT_SENT_BY_CONTRACT_ERROR);
+    state
+}
+
+/// Event emitted by [`cancel_unsettled()`], when the event has been cancelled.
+///
+/// Parameters:
+///
+/// * `context` The context of the call.
+/// * `state` The state of the contract.
+///
+/// Returns:
+///
+/// The unchanged state of the contract.
+#[action(shortname = 0x22)]
+pub fn cancelled_event(
+    context: ContractContext,
+    state: TokenSplitterContractState,
+) -> TokenSplitterContractState {
+    events::assert_event_sent_by_contract(&context, EVENT_NOT_SENT_BY_CONTRACT_ERROR);
+    state
+}
@@ -0,0 +1,669 @@
+use super::*;
+use defi_common::test_util::{account, context, contract, contract_address};
+use pbc_contract_common::context::ExecutionResult;
+use pbc_traits::ReadWriteRPC;
+
+fn state() -> TokenSplitterContractState {
+    initialize(
+        context(account(5), 0),
+        "Event".to_string(),
+        "EVT".to_string(),
+        contract(1),
+        contract(2),
+        contract(3),
+        account(4),
+        1000,
+        0,
+        0,
+        0,
+    )
+}
+
+/// Creates an active token splitter with an early split reward of 1%, until time 500.
+fn rewarded_state(reward_pool: TokenAmount) -> TokenSplitterContractState {
+    let mut state = initialize(
+        context(account(5), 0),
+        "Event".to_string(),
+        "EVT".to_string(),
+        contract(1),
+        contract(2),
+        contract(3),
+        account(4),
+        1000,
+        reward_pool,
+        10_000,
+        500,
+    );
+    state
+        .token_balances
+        .add_to_token_balance(account(5), TRUE_TOKEN, 1_000_000);
+    state
+        .token_balances
+        .add_to_token_balance(account(5), FALSE_TOKEN, 1_000_000);
+    state
+        .token_balances
+        .add_to_token_balance(account(5), ORIGINAL_TOKEN, reward_pool);
+    let (mut state, _) = prepare(context(account(5), 0), state, 1_000_000);
+
+    state
+        .token_balances
+        .add_to_token_balance(account(6), ORIGINAL_TOKEN, 10_000);
+    state
+        .token_balances
+        .add_to_token_balance(account(7), ORIGINAL_TOKEN, 10_000);
+    state
+}
+
+fn original_tokens(state: &TokenSplitterContractState, owner: Address) -> TokenAmount {
+    state
+        .token_balances
+        .get_balance_for(&owner)
+        .get_amount_of(ORIGINAL_TOKEN)
+}
+
+#[test]
+fn prepare_funds_reward_pool() {
+    let state = rewarded_state(1_000);
+    assert_eq!(state.early_split_rewards.remaining, 1_000);
+    assert_eq!(original_tokens(&state, account(5)), 0);
+    assert_eq!(original_tokens(&state, contract_address()), 1_000);
+}
+
+#[test]
+fn early_splitters_receive_reward() {
+    let state = rewarded_state(1_000);
+    let state = split(context(account(6), 100), state, 5_000);
+    let state = split(context(account(7), 500), state, 2_000);
+
+    assert_eq!(original_tokens(&state, account(6)), 5_050);
+    assert_eq!(original_tokens(&state, account(7)), 8_020);
+    assert_eq!(
+        state.early_split_rewards.paid_per_user.get(&account(6)),
+        Some(50)
+    );
+    assert_eq!(
+        state.early_split_rewards.paid_per_user.get(&account(7)),
+        Some(20)
+    );
+    assert_eq!(state.early_split_rewards.total_paid, 70);
+    assert_eq!(state.early_split_rewards.remaining, 930);
+}
+
+#[test]
+fn reward_pool_cannot_be_overdrawn() {
+    let state = rewarded_state(60);
+    let state = split(context(account(6), 100), state, 5_000);
+    let state = split(context(account(7), 100), state, 5_000);
+    let state = split(context(account(6), 100), state, 5_000);
+
+    assert_eq!(original_tokens(&state, account(6)), 50);
+    assert_eq!(original_tokens(&state, account(7)), 5_010);
+    assert_eq!(
+        state.early_split_rewards.paid_per_user.get(&account(6)),
+        Some(50)
+    );
+    assert_eq!(
+        state.early_split_rewards.paid_per_user.get(&account(7)),
+        Some(10)
+    );
+    assert_eq!(state.early_split_rewards.total_paid, 60);
+    assert_eq!(state.early_split_rewards.remaining, 0);
+    assert_eq!(original_tokens(&state, contract_address()), 15_000);
+}
+
+#[test]
+fn splits_after_window_get_no_reward() {
+    let state = rewarded_state(1_000);
+    let state = split(context(account(6), 501), state, 5_000);
+
+    assert_eq!(original_tokens(&state, account(6)), 5_000);
+    assert_eq!(
+        state.early_split_rewards.paid_per_user.get(&account(6)),
+        None
+    );
+    assert_eq!(state.early_split_rewards.total_paid, 0);
+    assert_eq!(state.early_split_rewards.remaining, 1_000);
+}
+
+#[test]
+fn joining_rewarded_split_returns_reward_to_pool() {
+    let mut state = rewarded_state(1_000);
+    for _ in 0..10 {
+        state = split(context(account(6), 100), state, 5_000);
+        state = join(context(account(6), 100), state, 5_000);
+    }
+
+    assert_eq!(original_tokens(&state, account(6)), 10_000);
+    assert_eq!(
+        state.early_split_rewards.paid_per_user.get(&account(6)),
+        None
+    );
+    assert_eq!(
+        state
+            .early_split_rewards
+            .rewarded_split_per_user
+            .get(&account(6)),
+        None
+    );
+    assert_eq!(state.early_split_rewards.total_paid, 0);
+    assert_eq!(state.early_split_rewards.remaining, 1_000);
+}
+
+#[test]
+fn partial_join_claws_back_reward_rounded_up() {
+    let state = rewarded_state(1_000);
+    let state = split(context(account(6), 100), state, 150);
+    assert_eq!(original_tokens(&state, account(6)), 9_851);
+
+    let state = join(context(account(6), 100), state, 50);
+
+    assert_eq!(original_tokens(&state, account(6)), 9_900);
+    assert_eq!(
+        state.early_split_rewards.paid_per_user.get(&account(6)),
+        Some(0)
+    );
+    assert_eq!(
+        state
+            .early_split_rewards
+            .rewarded_split_per_user
+            .get(&account(6)),
+        Some(100)
+    );
+    assert_eq!(state.early_split_rewards.remaining, 1_000);
+}
+
+#[test]
+fn joining_after_window_still_claws_back_reward() {
+    let state = rewarded_state(1_000);
+    let state = split(context(account(6), 100), state, 5_000);
+    let state = join(context(account(6), 600), state, 2_000);
+
+    assert_eq!(original_tokens(&state, account(6)), 7_030);
+    assert_eq!(
+        state.early_split_rewards.paid_per_user.get(&account(6)),
+        Some(30)
+    );
+    assert_eq!(state.early_split_rewards.total_paid, 30);
+    assert_eq!(state.early_split_rewards.remaining, 970);
+}
+
+#[test]
+fn funder_reclaims_reward_pool_after_window() {
+    let state = rewarded_state(1_000);
+    let state = split(context(account(6), 100), state, 5_000);
+    let state = reclaim_early_split_rewards(context(account(5), 501), state);
+
+    assert_eq!(original_tokens(&state, account(5)), 950);
+    assert_eq!(original_tokens(&state, contract_address()), 5_000);
+    assert_eq!(state.early_split_rewards.remaining, 0);
+    assert_eq!(state.early_split_rewards.total_paid, 50);
+}
+
+#[test]
+fn funder_reclaims_reward_pool_when_cancelled() {
+    let state = rewarded_state(1_000);
+    let (state, _) = cancel_unsettled(context(account(6), 1001), state);
+    let state = reclaim_early_split_rewards(context(account(5), 1001), state);
+
+    assert_eq!(original_tokens(&state, account(5)), 1_000);
+    assert_eq!(state.early_split_rewards.remaining, 0);
+}
+
+#[test]
+#[should_panic(
+    expected = "Can only reclaim the early split reward pool after the early split deadline, or when the event has been settled or cancelled."
+)]
+fn funder_cannot_reclaim_reward_pool_during_window() {
+    let state = rewarded_state(1_000);
+    reclaim_early_split_rewards(context(account(5), 500), state);
+}
+
+#[test]
+#[should_panic(expected = "Only the funder of the early split reward pool can reclaim it.")]
+fn others_cannot_reclaim_reward_pool() {
+    let state = rewarded_state(1_000);
+    reclaim_early_split_rewards(context(account(6), 501), state);
+}
+
+#[test]
+#[should_panic(expected = "Early split reward cannot exceed the amount split.")]
+fn early_split_reward_above_amount_split() {
+    initialize(
+        context(account(5), 0),
+        "Event".to_string(),
+        "EVT".to_string(),
+        contract(1),
+        contract(2),
+        contract(3),
+        account(4),
+        1000,
+        0,
+        1_000_001,
+        500,
+    );
+}
+
+fn token_amount(
+    state: &TokenSplitterContractState,
+    owner: Address,
+    token: DepositToken,
+) -> TokenAmount {
+    state
+        .token_balances
+        .get_balance_for(&owner)
+        .get_amount_of(token)
+}
+
+#[test]
+fn split_and_withdraw_transfers_true_and_false_tokens() {
+    let state = rewarded_state(0);
+    let (state, events) = split_and_withdraw(context(account(6), 100), state, 4_000, false);
+
+    let mut expected = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(contract(2)).transfer(
+        &mut expected,
+        &account(6),
+        4_000,
+    );
+    interact_mpc20::MPC20Contract::at_address(contract(3)).transfer(
+        &mut expected,
+        &account(6),
+        4_000,
+    );
+    assert_eq!(events, vec![expected.build()]);
+
+    assert_eq!(original_tokens(&state, account(6)), 6_000);
+    assert_eq!(token_amount(&state, account(6), TRUE_TOKEN), 0);
+    assert_eq!(token_amount(&state, account(6), FALSE_TOKEN), 0);
+    assert_eq!(original_tokens(&state, contract_address()), 4_000);
+    assert_eq!(
+        token_amount(&state, contract_address(), TRUE_TOKEN),
+        996_000
+    );
+    assert_eq!(
+        token_amount(&state, contract_address(), FALSE_TOKEN),
+        996_000
+    );
+}
+
+#[test]
+fn split_and_withdraw_can_wait_for_callback() {
+    let state = rewarded_state(0);
+    let (_, events) = split_and_withdraw(context(account(6), 100), state, 4_000, true);
+
+    let mut expected = EventGroup::builder();
+    interact_mpc20::MPC20Contract::at_address(contract(2)).transfer(
+        &mut expected,
+        &account(6),
+        4_000,
+    );
+    interact_mpc20::MPC20Contract::at_address(contract(3)).transfer(
+        &mut expected,
+        &account(6),
+        4_000,
+    );
+    expected
+        .with_callback_rpc(wait_withdraw_callback::rpc())
+        .with_cost(TRANSFER_CALLBACK_GAS_COST)
+        .done();
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn split_and_withdraw_is_not_rewarded() {
+    let state = rewarded_state(1_000);
+    let (state, _) = split_and_withdraw(context(account(6), 100), state, 5_000, false);
+
+    assert_eq!(original_tokens(&state, account(6)), 5_000);
+    assert_eq!(token_amount(&state, account(6), TRUE_TOKEN), 0);
+    assert_eq!(token_amount(&state, account(6), FALSE_TOKEN), 0);
+    assert_eq!(state.early_split_rewards.total_paid, 0);
+    assert_eq!(state.early_split_rewards.remaining, 1_000);
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot withdraw true or false tokens locked by rewarded early splits before the event is settled or cancelled."
+)]
+fn rewarded_split_tokens_cannot_be_withdrawn() {
+    let state = rewarded_state(1_000);
+    let state = split(context(account(6), 100), state, 5_000);
+    withdraw(context(account(6), 100), state, contract(2), 1, false);
+}
+
+#[test]
+fn tokens_beyond_rewarded_split_can_be_withdrawn() {
+    let mut state = rewarded_state(1_000);
+    state
+        .token_balances
+        .add_to_token_balance(account(6), FALSE_TOKEN, 300);
+    let state = split(context(account(6), 100), state, 5_000);
+    let (state, _) = withdraw(context(account(6), 100), state, contract(3), 300, false);
+
+    assert_eq!(token_amount(&state, account(6), FALSE_TOKEN), 5_000);
+    assert_eq!(state.early_split_rewards.locked_amount(&account(6)), 5_000);
+}
+
+#[test]
+fn rewarded_split_tokens_can_be_withdrawn_once_settled() {
+    let state = rewarded_state(1_000);
+    let state = split(context(account(6), 100), state, 5_000);
+    let (state, _) = settle(context(account(4), 600), state, true);
+    let (state, _) = withdraw(context(account(6), 600), state, contract(2), 5_000, false);
+
+    assert_eq!(token_amount(&state, account(6), TRUE_TOKEN), 0);
+    assert_eq!(original_tokens(&state, account(6)), 5_050);
+}
+
+#[test]
+fn splitting_under_one_account_and_joining_from_another_cannot_drain_pool() {
+    let state = rewarded_state(1_000);
+
+    // Tokens split with split_and_withdraw can be moved to another account, but earn no reward.
+    let (mut state, _) = split_and_withdraw(context(account(6), 100), state, 5_000, false);
+    state
+        .token_balances
+        .add_to_token_balance(account(7), TRUE_TOKEN, 5_000);
+    state
+        .token_balances
+        .add_to_token_balance(account(7), FALSE_TOKEN, 5_000);
+    let state = join(context(account(7), 100), state, 5_000);
+
+    assert_eq!(original_tokens(&state, account(6)), 5_000);
+    assert_eq!(original_tokens(&state, account(7)), 15_000);
+    assert_eq!(state.early_split_rewards.total_paid, 0);
+    assert_eq!(state.early_split_rewards.remaining, 1_000);
+
+    // Tokens split with a reward stay with the splitter, until joined by the splitter.
+    let state = split(context(account(6), 100), state, 5_000);
+    assert_eq!(state.early_split_rewards.locked_amount(&account(6)), 5_000);
+    assert_eq!(state.early_split_rewards.locked_amount(&account(7)), 0);
+
+    let state = join(context(account(6), 100), state, 5_000);
+    assert_eq!(original_tokens(&state, account(6)), 5_000);
+    assert_eq!(state.early_split_rewards.total_paid, 0);
+    assert_eq!(state.early_split_rewards.remaining, 1_000);
+}
+
+#[test]
+#[should_panic(expected = "Can only split if life stage is Active.")]
+fn split_and_withdraw_before_prepare() {
+    let mut state = state();
+    state
+        .token_balances
+        .add_to_token_balance(account(6), ORIGINAL_TOKEN, 10_000);
+    split_and_withdraw(context(account(6), 100), state, 4_000, false);
+}
+
+#[test]
+fn prepare_emits_activated_event() {
+    let mut state = state();
+    state
+        .token_balances
+        .add_to_token_balance(account(5), TRUE_TOKEN, 1_000);
+    state
+        .token_balances
+        .add_to_token_balance(account(5), FALSE_TOKEN, 1_000);
+    let (state, events) = prepare(context(account(5), 0), state, 1_000);
+
+    assert_eq!(state.life_stage, LifeStage::ACTIVE {});
+    let mut expected = EventGroup::builder();
+    expected
+        .call(contract_address(), SHORTNAME_ACTIVATED_EVENT)
+        .argument(1_000u128)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn settle_emits_settled_event_with_outcome() {
+    let (state, events) = settle(context(account(4), 100), rewarded_state(0), true);
+
+    assert_eq!(state.life_stage, LifeStage::SETTLED { outcome: true });
+    let mut expected = EventGroup::builder();
+    expected
+        .call(contract_address(), SHORTNAME_SETTLED_EVENT)
+        .argument(true)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+fn cancel_unsettled_emits_cancelled_event() {
+    let (state, events) = cancel_unsettled(context(account(6), 1001), rewarded_state(0));
+
+    assert_eq!(state.life_stage, LifeStage::CANCELLED {});
+    let mut expected = EventGroup::builder();
+    expected
+        .call(contract_address(), SHORTNAME_CANCELLED_EVENT)
+        .with_cost(events::GAS_COST_EVENT)
+        .done();
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+#[should_panic(expected = "Events can only be sent by the token splitter contract itself.")]
+fn users_cannot_send_events() {
+    settled_event(context(account(6), 100), state(), true);
+}
+
+/// Creates a cancelled token splitter, where user 6 has split 4 000 original tokens and passed
+/// all of the false tokens on to user 7.
+fn cancelled_state_with_one_sided_holders() -> TokenSplitterContractState {
+    let mut state = split(context(account(6), 100), rewarded_state(0), 4_000);
+    state
+        .token_balances
+        .move_tokens(account(6), account(7), FALSE_TOKEN, 4_000);
+    let (state, _) = cancel_unsettled(context(account(6), 1001), state);
+    state
+}
+
+#[test]
+fn one_sided_holders_redeem_half_an_original_token_each_when_cancelled() {
+    let state = cancelled_state_with_one_sided_holders();
+    let original_before_6 = original_tokens(&state, account(6));
+    let original_before_7 = original_tokens(&state, account(7));
+
+    let state = redeem_cancelled(context(account(6), 1002), state, contract(2), 4_000);
+    let state = redeem_cancelled(context(account(7), 1002), state, contract(3), 4_000);
+
+    assert_eq!(
+        original_tokens(&state, account(6)),
+        original_before_6 + 2_000
+    );
+    assert_eq!(
+        original_tokens(&state, account(7)),
+        original_before_7 + 2_000
+    );
+    assert_eq!(token_amount(&state, account(6), TRUE_TOKEN), 0);
+    assert_eq!(token_amount(&state, account(7), FALSE_TOKEN), 0);
+    assert_eq!(original_tokens(&state, contract_address()), 0);
+}
+
+#[test]
+fn redeem_cancelled_rounds_down() {
+    let state = cancelled_state_with_one_sided_holders();
+    let original_before = original_tokens(&state, account(6));
+
+    let state = redeem_cancelled(context(account(6), 1002), state, contract(2), 3);
+
+    assert_eq!(original_tokens(&state, account(6)), original_before + 1);
+    assert_eq!(token_amount(&state, account(6), TRUE_TOKEN), 3_997);
+}
+
+#[test]
+#[should_panic(expected = "Can only redeem true or false tokens.")]
+fn redeem_cancelled_rejects_original_tokens() {
+    let state = cancelled_state_with_one_sided_holders();
+    redeem_cancelled(context(account(6), 1002), state, contract(1), 2);
+}
+
+#[test]
+#[should_panic(expected = "Can only redeem single outcome tokens if life stage is Cancelled.")]
+fn redeem_cancelled_before_cancellation() {
+    let state = split(context(account(6), 100), rewarded_state(0), 4_000);
+    redeem_cancelled(context(account(6), 100), state, contract(2), 4_000);
+}
+
+#[test]
+#[should_panic(expected = "Can only redeem if life stage is Settled.")]
+fn redeem_of_cancelled_event() {
+    let state = cancelled_state_with_one_sided_holders();
+    redeem(context(account(6), 1002), state, 4_000);
+}
+
+/// Creates a token splitter with a price pool at `contract(8)`, trading true tokens as token A.
+fn priced_state() -> TokenSplitterContractState {
+    set_price_pool(context(account(4), 0), state(), contract(8), true)
+}
+
+fn reserves_callback(reserve_a: TokenAmount, reserve_b: TokenAmount) -> CallbackContext {
+    let mut return_data = vec![];
+    SwapReserves {
+        reserve_a,
+        reserve_b,
+        fee_per_mille: 3,
+    }
+    .rpc_write_to(&mut return_data)
+    .unwrap();
+    CallbackContext {
+        success: true,
+        results: vec![ExecutionResult {
+            succeeded: true,
+            return_data,
+        }],
+    }
+}
+
+#[test]
+fn implied_probability_defaults_before_prepare() {
+    let state = state();
+    assert_eq!(state.implied_probability_ppm, 500_000);
+
+    let (_, events) = implied_probability(context(account(6), 0), state);
+    assert_eq!(events, vec![EventGroup::with_return_data(500_000u32)]);
+}
+
+#[test]
+fn update_implied_probability_reads_pool_reserves() {
+    let (_, events) = update_implied_probability(context(account(6), 0), priced_state());
+
+    let mut expected = EventGroup::builder();
+    SwapContract::at_address(contract(8)).get_reserves(&mut expected);
+    expected
+        .with_callback_rpc(implied_probability_callback::rpc(contract(8)))
+        .with_cost(RESERVES_CALLBACK_GAS_COST)
+        .done();
+    assert_eq!(events, vec![expected.build()]);
+}
+
+#[test]
+#[should_panic(expected = "No price pool has been set.")]
+fn update_implied_probability_without_price_pool() {
+    update_implied_probability(context(account(6), 0), state());
+}
+
+#[test]
+#[should_panic(expected = "Address other than that of the arbitrator cannot set the price pool.")]
+fn others_cannot_set_price_pool() {
+    set_price_pool(context(account(6), 0), state(), contract(8), true);
+}
+
+#[test]
+fn implied_probability_of_equal_reserves() {
+    let state = implied_probability_callback(
+        context(contract(8), 0),
+        reserves_callback(1_000, 1_000),
+        priced_state(),
+        contract(8),
+    );
+    assert_eq!(state.implied_probability_ppm, 500_000);
+
+    let (_, events) = implied_probability(context(account(6), 0), state);
+    assert_eq!(events, vec![EventGroup::with_return_data(500_000u32)]);
+}
+
+#[test]
+fn implied_probability_of_skewed_reserves() {
+    // True tokens are scarce in the pool, so the true outcome is priced as more likely.
+    let state = implied_probability_callback(
+        context(contract(8), 0),
+        reserves_callback(250, 750),
+        priced_state(),
+        contract(8),
+    );
+    assert_eq!(state.implied_probability_ppm, 750_000);
+
+    let state = implied_probability_callback(
+        context(contract(8), 0),
+        reserves_callback(2, 1),
+        state,
+        contract(8),
+    );
+    assert_eq!(state.implied_probability_ppm, 333_333);
+
+    let state = implied_probability_callback(
+        context(contract(8), 0),
+        reserves_callback(u128::MAX / 4, u128::MAX / 4 * 3),
+        state,
+        contract(8),
+    );
+    assert_eq!(state.implied_probability_ppm, 750_000);
+}
+
+#[test]
+fn implied_probability_with_true_token_as_token_b() {
+    let state = set_price_pool(context(account(4), 0), state(), contract(8), false);
+    let state = implied_probability_callback(
+        context(contract(8), 0),
+        reserves_callback(250, 750),
+        state,
+        contract(8),
+    );
+    assert_eq!(state.implied_probability_ppm, 250_000);
+}
+
+#[test]
+fn implied_probability_of_empty_pool() {
+    let state = implied_probability_callback(
+        context(contract(8), 0),
+        reserves_callback(0, 0),
+        priced_state(),
+        contract(8),
+    );
+    assert_eq!(state.implied_probability_ppm, 500_000);
+}
+
+#[test]
+fn implied_probability_unchanged_when_reserves_cannot_be_read() {
+    let state = implied_probability_callback(
+        context(contract(8), 0),
+        reserves_callback(250, 750),
+        priced_state(),
+        contract(8),
+    );
+
+    let failed = CallbackContext {
+        success: false,
+        results: vec![ExecutionResult {
+            succeeded: false,
+            return_data: vec![],
+        }],
+    };
+    let state = implied_probability_callback(context(contract(8), 0), failed, state, contract(8));
+    assert_eq!(state.implied_probability_ppm, 750_000);
+}
+
+#[test]
+fn implied_probability_ignores_reserves_of_replaced_pool() {
+    let state = set_price_pool(context(account(4), 0), priced_state(), contract(9), true);
+    let state = implied_probability_callback(
+        context(contract(8), 0),
+        reserves_callback(250, 750),
+        state,
+        contract(8),
+    );
+    assert_eq!(state.implied_probability_ppm, 500_000);
+}
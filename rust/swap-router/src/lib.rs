@@ -3,11 +3,14 @@
 #[macro_use]
 extern crate pbc_contract_codegen;
 
+#[cfg(test)]
+mod tests;
+
 use pbc_contract_common::address::Address;
 use pbc_contract_common::context::{CallbackContext, ContractContext};
 use pbc_contract_common::events::{EventGroup, EventGroupBuilder, GasCost};
 use std::cmp::max;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
 use create_type_spec_derive::CreateTypeSpec;
 use pbc_contract_common::avl_tree_map::AvlTreeMap;
@@ -16,6 +19,7 @@ use read_write_state_derive::ReadWriteState;
 use read_write_rpc_derive::{ReadRPC, WriteRPC};
 
 use defi_common::liquidity_util::{AcquiredLiquidityLockInformation, LiquidityLockId};
+use defi_common::math::assert_is_per_mille;
 use defi_common::token_balances::TokenAmount;
 
 use defi_common::interact_mpc20::MPC20Contract;
@@ -26,13 +30,36 @@ use defi_common::permission::Permission;
 /// Type of route ids.
 pub type RouteId = u128;
 
-/// The maximum length of the token swap route.
+/// The maximum length of the token swap route, if none is given when initializing the router. See
+/// [`RouterState::max_route_length`].
+///
+/// # Why?
+///
+/// Partisia Blockchain limits the number of chained spawned events to around 127, and longer
+/// routes require integrators to supply more gas, see [`calculate_min_total_gas_cost`].
+pub const DEFAULT_MAX_ROUTE_LENGTH: u32 = 5;
+
+/// The largest maximum route length the router can be initialized with.
 ///
 /// # Why?
 ///
-/// Partisia Blockchain limits the number of chained spawned events to around 127, which can easily
-/// be hit when executing routes longer than this constant.
-const MAX_ROUTE_LENGTH: usize = 5;
+/// This is the longest route for which the chained events spawned by the route stay within
+/// [`MAX_CHAINED_EVENTS`].
+pub const MAX_ROUTE_LENGTH_CEILING: u32 =
+    (MAX_CHAINED_EVENTS - CHAINED_EVENTS_PER_ROUTE) / CHAINED_EVENTS_PER_SWAP;
+
+/// The limit of chained spawned events on Partisia Blockchain.
+const MAX_CHAINED_EVENTS: u32 = 127;
+
+/// The number of chained events spawned for each swap on a route: 2 for acquiring the lock,
+/// 2 for approving the swap contract, 4 for depositing into the swap contract (including its
+/// transfer from the router), 2 for executing the lock, and 4 for withdrawing from the swap
+/// contract (including its transfer to the router).
+const CHAINED_EVENTS_PER_SWAP: u32 = 14;
+
+/// The number of chained events spawned for a route independently of its length: 2 for taking
+/// control of the input tokens, and 1 for transferring the output tokens to the user.
+const CHAINED_EVENTS_PER_ROUTE: u32 = 3;
 
 /// Indicates the directional token swap that we intend to make along the route, including what to
 /// input, get as output, and where to make the swap.
@@ -44,6 +71,8 @@ pub struct SwapInformation {
     token_in: Address,
     /// The output token on the swap.
     token_out: Address,
+    /// Whether the input token implements [`MPC20Contract::approve_relative`].
+    token_in_supports_approve_relative: bool,
 }
 
 /// Information about a lock we still need to acquire in a route.
@@ -88,6 +117,151 @@ pub struct SwapContractInfo {
     token_a_address: Address,
     /// The address of the token that the swap considers the B token.
     token_b_address: Address,
+    /// Whether the A token implements [`MPC20Contract::approve_relative`].
+    token_a_supports_approve_relative: bool,
+    /// Whether the B token implements [`MPC20Contract::approve_relative`].
+    token_b_supports_approve_relative: bool,
+}
+
+/// Cached reserves of a known swap contract, used to estimate the output of swaps when
+/// discovering routes in [`route_swap_auto`].
+///
+/// The reserves are pushed to the router using [`update_cached_reserves`], and are not updated when
+/// swaps are made at the swap contract. They are therefore stale, and only reflect the swap contract
+/// at the time they were pushed.
+#[derive(ReadWriteState, CreateTypeSpec, Clone, Copy)]
+pub struct CachedReserves {
+    /// The amount of the A token in the swap contract.
+    reserve_a: TokenAmount,
+    /// The amount of the B token in the swap contract.
+    reserve_b: TokenAmount,
+    /// The fee for making swaps at the swap contract, per mille.
+    swap_fee_per_mille: u16,
+}
+
+impl CachedReserves {
+    /// Estimates the output of swapping `amount_in` of `token_in` at the swap contract described by
+    /// `swap_info`, using the constant product formula after deducting the swap fee, like
+    /// [`calculate_swap_to_amount`](defi_common::liquidity_util::calculate_swap_to_amount).
+    ///
+    /// Returns `None` if the estimate overflows.
+    fn estimate_output(
+        &self,
+        swap_info: &SwapContractInfo,
+        token_in: Address,
+        amount_in: TokenAmount,
+    ) -> Option<TokenAmount> {
+        let (reserve_in, reserve_out) = if token_in == swap_info.token_a_address {
+            (self.reserve_a, self.reserve_b)
+        } else {
+            (self.reserve_b, self.reserve_a)
+        };
+        let remainder_ratio = (1000 - self.swap_fee_per_mille) as TokenAmount;
+        let amount_in_after_fee = remainder_ratio.checked_mul(amount_in)?;
+        let numerator = reserve_out.checked_mul(amount_in_after_fee)?;
+        let denominator = reserve_in
+            .checked_mul(1000)?
+            .checked_add(amount_in_after_fee)?;
+        numerator.checked_div(denominator)
+    }
+}
+
+/// A partially discovered route, used when searching for routes in [`find_best_route`].
+struct PartialRoute {
+    /// The tokens visited by the route, ending with the current token.
+    tokens: Vec<Address>,
+    /// The estimated amount of the current token.
+    amount: TokenAmount,
+    /// The swap contracts used by the route.
+    swaps: Vec<Address>,
+}
+
+/// Searches the known swap contracts for the route from `token_in` to `token_out` with the largest
+/// estimated output, using at most `max_hops` swaps.
+///
+/// The search is breadth-first over the token graph, one swap at a time, and only keeps the route
+/// with the largest estimated amount of each token, like the Bellman-Ford algorithm. A route is
+/// only extended if it reaches its token with a larger amount than any route with fewer swaps, and
+/// never visits a token twice. Only swap contracts with [`CachedReserves`] are considered, and as
+/// these may be stale, the estimates may differ from the actual output. When several routes give
+/// the same estimated output, the shortest is chosen.
+///
+/// Returns `None` if no route with a positive estimated output exists.
+fn find_best_route(
+    known_swap_contracts: &[SwapContractInfo],
+    cached_reserves: &AvlTreeMap<Address, CachedReserves>,
+    token_in: Address,
+    token_out: Address,
+    amount_in: TokenAmount,
+    max_hops: usize,
+) -> Option<Vec<Address>> {
+    let mut best_route: Option<(TokenAmount, Vec<Address>)> = None;
+    let mut best_amount_per_token: BTreeMap<Address, TokenAmount> =
+        BTreeMap::from([(token_in, amount_in)]);
+    let mut frontier = vec![PartialRoute {
+        tokens: vec![token_in],
+        amount: amount_in,
+        swaps: vec![],
+    }];
+
+    for _ in 0..max_hops {
+        // The best route found in this round to each token, which improves on earlier rounds.
+        let mut next_frontier: BTreeMap<Address, PartialRoute> = BTreeMap::new();
+        for route in frontier {
+            let current_token = *route.tokens.last().unwrap();
+            for swap_info in known_swap_contracts {
+                let next_token = if swap_info.token_a_address == current_token {
+                    swap_info.token_b_address
+                } else if swap_info.token_b_address == current_token {
+                    swap_info.token_a_address
+                } else {
+                    continue;
+                };
+                if route.tokens.contains(&next_token) {
+                    continue;
+                }
+                let reserves = match cached_reserves.get(&swap_info.swap_address) {
+                    Some(reserves) => reserves,
+                    None => continue,
+                };
+                let amount_out =
+                    match reserves.estimate_output(swap_info, current_token, route.amount) {
+                        Some(amount_out) if amount_out > 0 => amount_out,
+                        _ => continue,
+                    };
+
+                let is_better = match best_amount_per_token.get(&next_token) {
+                    Some(best_amount) => amount_out > *best_amount,
+                    None => true,
+                };
+                if !is_better {
+                    continue;
+                }
+                best_amount_per_token.insert(next_token, amount_out);
+
+                let mut swaps = route.swaps.clone();
+                swaps.push(swap_info.swap_address);
+
+                if next_token == token_out {
+                    best_route = Some((amount_out, swaps));
+                } else {
+                    let mut tokens = route.tokens.clone();
+                    tokens.push(next_token);
+                    next_frontier.insert(
+                        next_token,
+                        PartialRoute {
+                            tokens,
+                            amount: amount_out,
+                            swaps,
+                        },
+                    );
+                }
+            }
+        }
+        frontier = next_frontier.into_values().collect();
+    }
+
+    best_route.map(|(_, swaps)| swaps)
 }
 
 /// Handles actions and information related to a specific active swap-route.
@@ -109,6 +283,10 @@ struct RouteInformation {
     locks_waiting_for_execution: VecDeque<AcquiredLockInfo>,
     /// Withdrawal to be performed from specific swap contract.
     pending_withdraw: Option<PendingWithdrawInfo>,
+    /// Minimum output of the locks acquired so far, for them to be executed if a lock cannot be
+    /// acquired. `None` if the route must not be partially executed. See
+    /// [`RouteInformation::fall_back_to_partial_route`].
+    partial_amount_out_minimum: Option<TokenAmount>,
 }
 
 impl RouteInformation {
@@ -116,11 +294,19 @@ impl RouteInformation {
     ///
     /// For each swap in `route`, creates a [`WantedLockInfo`] with the first and last missing long
     /// containing the initial swap amount, and the required output amount, respectively.
+    ///
+    /// If `hop_amounts_out_minimum` is given, each lock additionally requires the corresponding
+    /// minimum output amount. Panics if the number of hop minimums doesn't match the route length.
+    ///
+    /// If `partial_amount_out_minimum` is given, the route may be partially executed when a lock
+    /// cannot be acquired. See [`RouteInformation::fall_back_to_partial_route`].
     pub fn new(
         route: Vec<SwapInformation>,
         initial_amount_in: TokenAmount,
         amount_out_minimum: TokenAmount,
+        hop_amounts_out_minimum: Option<Vec<TokenAmount>>,
         user: Address,
+        partial_amount_out_minimum: Option<TokenAmount>,
     ) -> Self {
         let initial_token_in = route.first().unwrap().token_in;
         let final_token_out = route.last().unwrap().token_out;
@@ -138,9 +324,26 @@ impl RouteInformation {
             locks_wanted.push_back(m_lock);
         }
 
+        // Require the minimum output amount of each hop, if given.
+        if let Some(hop_amounts_out_minimum) = hop_amounts_out_minimum {
+            assert_eq!(
+                hop_amounts_out_minimum.len(),
+                locks_wanted.len(),
+                "Number of hop minimum output amounts ({}) doesn't match the route length ({}).",
+                hop_amounts_out_minimum.len(),
+                locks_wanted.len()
+            );
+            for (lock, hop_amount_out_minimum) in
+                locks_wanted.iter_mut().zip(hop_amounts_out_minimum)
+            {
+                lock.amount_out_minimum = hop_amount_out_minimum;
+            }
+        }
+
         // Modify the first and last locks to contain known information about the overall swap.
         locks_wanted.front_mut().unwrap().amount_in = initial_amount_in;
-        locks_wanted.back_mut().unwrap().amount_out_minimum = amount_out_minimum;
+        let last_lock = locks_wanted.back_mut().unwrap();
+        last_lock.amount_out_minimum = max(last_lock.amount_out_minimum, amount_out_minimum);
 
         Self {
             user,
@@ -151,6 +354,7 @@ impl RouteInformation {
             locks_wanted,
             locks_waiting_for_execution: VecDeque::with_capacity(0),
             pending_withdraw: None,
+            partial_amount_out_minimum,
         }
     }
 
@@ -216,6 +420,35 @@ impl RouteInformation {
         self.pending_withdraw.take()
     }
 
+    /// Shortens the route to the locks acquired so far, after failing to acquire the next lock.
+    ///
+    /// The partial route is only used if the route allows partial execution, at least one lock
+    /// has been acquired, and the acquired locks produce a non-zero amount of at least
+    /// [`RouteInformation::partial_amount_out_minimum`]. The user then ends up with the output
+    /// token of the last acquired lock, instead of [`RouteInformation::final_token_out`].
+    ///
+    /// Returns `true` if the route was shortened, and the acquired locks should be executed.
+    /// Returns `false` if the route is unchanged, and should be cancelled.
+    pub fn fall_back_to_partial_route(&mut self) -> bool {
+        let Some(partial_amount_out_minimum) = self.partial_amount_out_minimum else {
+            return false;
+        };
+        let Some(last_acquired_lock) = self.locks_waiting_for_execution.back() else {
+            return false;
+        };
+        // The amount in of the failed lock is the amount produced by the last acquired lock.
+        let partial_amount_out = self
+            .peek_next_wanted_lock()
+            .map_or(0, |failed_lock| failed_lock.amount_in);
+        if partial_amount_out == 0 || partial_amount_out < partial_amount_out_minimum {
+            return false;
+        }
+
+        self.final_token_out = last_acquired_lock.swap_info.token_out;
+        self.locks_wanted.clear();
+        true
+    }
+
     /// Updates the final output token amount of executing the route being tracked to `amount_out`.
     ///
     /// The information is used when transferring the output tokens back to the original user.
@@ -229,6 +462,9 @@ impl RouteInformation {
 struct RouteTracker {
     next_route_id: RouteId,
     active_routes: AvlTreeMap<RouteId, RouteInformation>,
+    /// Number of routes per user, which hold the user's tokens, and have not yet been completed or
+    /// cancelled. Users without such routes are not present.
+    active_routes_per_user: AvlTreeMap<Address, u32>,
 }
 
 impl RouteTracker {
@@ -237,28 +473,75 @@ impl RouteTracker {
         Self {
             next_route_id: 0,
             active_routes: AvlTreeMap::new(),
+            active_routes_per_user: AvlTreeMap::new(),
+        }
+    }
+
+    /// Number of routes of `user`, which hold the user's tokens, and have not yet been completed
+    /// or cancelled.
+    fn number_of_active_routes(&self, user: &Address) -> u32 {
+        self.active_routes_per_user.get(user).unwrap_or(0)
+    }
+
+    /// Records that a route of `user` has been added.
+    fn record_route_started(&mut self, user: Address) {
+        let number_of_routes = self.number_of_active_routes(&user);
+        self.active_routes_per_user
+            .insert(user, number_of_routes + 1);
+    }
+
+    /// Records that a route of `user` has been completed, cancelled or dropped.
+    fn record_route_finished(&mut self, user: Address) {
+        let number_of_routes = self.number_of_active_routes(&user);
+        if number_of_routes <= 1 {
+            self.active_routes_per_user.remove(&user);
+        } else {
+            self.active_routes_per_user
+                .insert(user, number_of_routes - 1);
         }
     }
 
     /// Adds a new active route with a unique id, for which locks should be acquired and executed.
     ///
-    /// Uses `route`, `amount_in`, `minimum_amount_out` and `user` to construct a new `RouteInformation`,
-    /// to keep track of locks to be acquired for the route.
+    /// Uses `route`, `amount_in`, `minimum_amount_out`, `hop_amounts_out_minimum`, `user` and
+    /// `partial_amount_out_minimum` to construct a new `RouteInformation`, to keep track of locks
+    /// to be acquired for the route. The route counts towards the active routes of `user` until it
+    /// is removed with [`RouteTracker::remove_route`].
     fn add_route(
         &mut self,
         route: Vec<SwapInformation>,
         amount_in: TokenAmount,
         minimum_amount_out: TokenAmount,
+        hop_amounts_out_minimum: Option<Vec<TokenAmount>>,
         user: Address,
+        partial_amount_out_minimum: Option<TokenAmount>,
     ) -> RouteId {
         let route_id = self.next_route_id();
-        let route_info = RouteInformation::new(route, amount_in, minimum_amount_out, user);
+        let route_info = RouteInformation::new(
+            route,
+            amount_in,
+            minimum_amount_out,
+            hop_amounts_out_minimum,
+            user,
+            partial_amount_out_minimum,
+        );
 
         self.active_routes.insert(route_id, route_info);
+        self.record_route_started(user);
 
         route_id
     }
 
+    /// Removes the route associated with `route_id`, once it has been completed, cancelled or
+    /// dropped, and stops counting it towards the active routes of its user.
+    ///
+    /// Panics if no route is associated with `route_id`.
+    fn remove_route(&mut self, route_id: RouteId) {
+        let route = self.get_route(route_id);
+        self.active_routes.remove(&route_id);
+        self.record_route_finished(route.user);
+    }
+
     /// Returns an id for a new route, and updates state for a future route id.
     fn next_route_id(&mut self) -> RouteId {
         let res = self.next_route_id;
@@ -287,6 +570,46 @@ impl RouteTracker {
     }
 }
 
+/// Historical statistics of the routes handled by the router.
+#[derive(ReadWriteState, CreateTypeSpec)]
+pub struct RouteStatistics {
+    /// Number of routes that have been completed, with the output tokens transferred to the user.
+    pub completed_routes: u64,
+    /// Number of routes that have been cancelled, due to failing lock acquisition.
+    pub cancelled_routes: u64,
+    /// Total amount of input tokens swapped by completed routes, per input token.
+    pub volume_per_token: AvlTreeMap<Address, TokenAmount>,
+}
+
+impl RouteStatistics {
+    /// Creates new empty statistics.
+    fn new() -> Self {
+        Self {
+            completed_routes: 0,
+            cancelled_routes: 0,
+            volume_per_token: AvlTreeMap::new(),
+        }
+    }
+
+    /// Records the completion of the given route.
+    fn record_completed(&mut self, route: &RouteInformation) {
+        self.completed_routes += 1;
+        let volume = self
+            .volume_per_token
+            .get(&route.initial_token_in)
+            .unwrap_or(0);
+        self.volume_per_token.insert(
+            route.initial_token_in,
+            volume.saturating_add(route.initial_amount_in),
+        );
+    }
+
+    /// Records the cancellation of a route.
+    fn record_cancelled(&mut self) {
+        self.cancelled_routes += 1;
+    }
+}
+
 /// This is the state of the contract which is persisted on the chain.
 ///
 /// The #\[state\] macro generates serialization logic for the struct.
@@ -298,19 +621,50 @@ pub struct RouterState {
     swap_contracts: Vec<SwapContractInfo>,
     /// Tracks routes actively being processed.
     route_tracker: RouteTracker,
+    /// Cached reserves of known swap contracts, used for discovering routes.
+    cached_reserves: AvlTreeMap<Address, CachedReserves>,
+    /// Historical statistics of completed and cancelled routes.
+    route_statistics: RouteStatistics,
+    /// Maximum number of simultaneously active routes per user. See [`route_swap`].
+    max_active_routes_per_user: u32,
+    /// Maximum number of swaps on a route. At most [`MAX_ROUTE_LENGTH_CEILING`].
+    max_route_length: u32,
 }
 
 /// Initialize the routing contract, with `swap_contracts` as the initially known swap contracts.
+///
+/// Each user can have at most `max_active_routes_per_user` routes active at the same time.
+///
+/// Routes can have at most `max_route_length` swaps, which must be between 1 and
+/// [`MAX_ROUTE_LENGTH_CEILING`]. If no `max_route_length` is given, it defaults to
+/// [`DEFAULT_MAX_ROUTE_LENGTH`].
 #[init]
 pub fn initialize(
     _context: ContractContext,
     permission_add_swap: Permission,
     swap_contracts: Vec<SwapContractInfo>,
+    max_active_routes_per_user: u32,
+    max_route_length: Option<u32>,
 ) -> (RouterState, Vec<EventGroup>) {
+    let max_route_length = max_route_length.unwrap_or(DEFAULT_MAX_ROUTE_LENGTH);
+    assert!(
+        max_active_routes_per_user > 0,
+        "Maximum number of active routes per user must be positive."
+    );
+    assert!(
+        (1..=MAX_ROUTE_LENGTH_CEILING).contains(&max_route_length),
+        "Maximum route length ({}) must be between 1 and {}.",
+        max_route_length,
+        MAX_ROUTE_LENGTH_CEILING
+    );
     let new_state = RouterState {
         permission_add_swap,
         swap_contracts,
         route_tracker: RouteTracker::new(),
+        cached_reserves: AvlTreeMap::new(),
+        route_statistics: RouteStatistics::new(),
+        max_active_routes_per_user,
+        max_route_length,
     };
 
     (new_state, vec![])
@@ -323,8 +677,133 @@ pub fn initialize(
 /// If a route is found, locks are acquired, and if this succeeds, then executed to finalized the swap-chain.
 ///
 /// Fails if the found route is of length 1, prompting the user to perform an instant-swap.
+///
+/// Fails if the user already has the maximum number of active routes, i.e. routes that have taken
+/// control of the user's tokens, and have not yet been completed or cancelled.
+///
+/// If `partial_amount_out_minimum` is given, and a lock along the route cannot be acquired, the
+/// locks acquired so far are executed, rather than cancelling the route. The user then receives
+/// the output token of the last acquired lock, instead of `token_out`, and `amount_out_minimum`
+/// does not apply. Instead, the acquired locks must produce at least `partial_amount_out_minimum`
+/// of the output token. If the first lock cannot be acquired, or the acquired locks produce less
+/// than `partial_amount_out_minimum` or nothing at all, the route is cancelled as usual, and the
+/// user keeps `token_in`.
 #[action(shortname = 0x01)]
+#[allow(clippy::too_many_arguments)]
 pub fn route_swap(
+    context: ContractContext,
+    state: RouterState,
+    swap_route: Vec<Address>,
+    token_in: Address,
+    token_out: Address,
+    amount_in: TokenAmount,
+    amount_out_minimum: TokenAmount,
+    partial_amount_out_minimum: Option<TokenAmount>,
+) -> (RouterState, Vec<EventGroup>) {
+    route_swap_internal(
+        context,
+        state,
+        swap_route,
+        token_in,
+        token_out,
+        amount_in,
+        amount_out_minimum,
+        None,
+        partial_amount_out_minimum,
+    )
+}
+
+/// Same as [`route_swap`], but additionally requires the lock of each swap along the route to
+/// produce at least the corresponding amount in `hop_amounts_out_minimum`.
+///
+/// This protects against a swap in the middle of the route giving a bad exchange rate, which
+/// would otherwise be hidden by a later swap with a favorable rate. If a lock cannot guarantee
+/// its minimum output, lock acquisition fails and the route is cancelled.
+///
+/// Fails if the number of hop minimums doesn't match the length of `swap_route`.
+#[action(shortname = 0x09)]
+#[allow(clippy::too_many_arguments)]
+pub fn route_swap_with_hop_minimums(
+    context: ContractContext,
+    state: RouterState,
+    swap_route: Vec<Address>,
+    token_in: Address,
+    token_out: Address,
+    amount_in: TokenAmount,
+    amount_out_minimum: TokenAmount,
+    hop_amounts_out_minimum: Vec<TokenAmount>,
+) -> (RouterState, Vec<EventGroup>) {
+    route_swap_internal(
+        context,
+        state,
+        swap_route,
+        token_in,
+        token_out,
+        amount_in,
+        amount_out_minimum,
+        Some(hop_amounts_out_minimum),
+        None,
+    )
+}
+
+/// Same as [`route_swap`], but discovers the route itself, instead of requiring the caller to
+/// supply it.
+///
+/// Chooses the route among the known swap contracts with the largest estimated output, using at
+/// most `max_hops` swaps. The estimates are based on the reserves cached using
+/// [`update_cached_reserves`], and swap contracts without cached reserves are not used.
+///
+/// Fails if `max_hops` is zero or greater than the maximum route length, or if no route is found.
+#[action(shortname = 0x0A)]
+pub fn route_swap_auto(
+    context: ContractContext,
+    state: RouterState,
+    token_in: Address,
+    token_out: Address,
+    amount_in: TokenAmount,
+    amount_out_minimum: TokenAmount,
+    max_hops: u32,
+) -> (RouterState, Vec<EventGroup>) {
+    assert!(
+        (1..=state.max_route_length).contains(&max_hops),
+        "Maximum number of swaps ({}) must be between 1 and {}.",
+        max_hops,
+        state.max_route_length
+    );
+    let max_hops = max_hops as usize;
+
+    let swap_route = find_best_route(
+        &state.swap_contracts,
+        &state.cached_reserves,
+        token_in,
+        token_out,
+        amount_in,
+        max_hops,
+    )
+    .unwrap_or_else(|| {
+        panic!(
+            "No route found from {} to {} with at most {} swaps.",
+            token_in, token_out, max_hops
+        )
+    });
+
+    route_swap_internal(
+        context,
+        state,
+        swap_route,
+        token_in,
+        token_out,
+        amount_in,
+        amount_out_minimum,
+        None,
+        None,
+    )
+}
+
+/// Validates the route, starts tracking it, and builds the events for taking control of the
+/// user's tokens.
+#[allow(clippy::too_many_arguments)]
+fn route_swap_internal(
     context: ContractContext,
     mut state: RouterState,
     swap_route: Vec<Address>,
@@ -332,18 +811,35 @@ pub fn route_swap(
     token_out: Address,
     amount_in: TokenAmount,
     amount_out_minimum: TokenAmount,
+    hop_amounts_out_minimum: Option<Vec<TokenAmount>>,
+    partial_amount_out_minimum: Option<TokenAmount>,
 ) -> (RouterState, Vec<EventGroup>) {
     assert!(!swap_route.is_empty(), "The given route is empty.");
+    assert!(
+        state.route_tracker.number_of_active_routes(&context.sender)
+            < state.max_active_routes_per_user,
+        "User already has the maximum number of active routes ({}).",
+        state.max_active_routes_per_user
+    );
 
-    let route =
-        validate_route_and_add_info(&swap_route, &state.swap_contracts, token_in, token_out);
+    let route = validate_route_and_add_info(
+        &swap_route,
+        &state.swap_contracts,
+        token_in,
+        token_out,
+        state.max_route_length as usize,
+    );
     let route_length = route.len();
 
     // Insert the found route into our state tracker.
-    let route_id =
-        state
-            .route_tracker
-            .add_route(route, amount_in, amount_out_minimum, context.sender);
+    let route_id = state.route_tracker.add_route(
+        route,
+        amount_in,
+        amount_out_minimum,
+        hop_amounts_out_minimum,
+        context.sender,
+        partial_amount_out_minimum,
+    );
 
     // First, take control of tokens, so the routing contract can approve tokens along the route.
     let route_information: RouteInformation = state.route_tracker.get_route(route_id);
@@ -369,19 +865,21 @@ pub fn route_swap(
 /// Validates that tokens match for all swaps in `swap_route`, and the the start and end match
 /// `token_in` and `token_out`, respectively. Furthermore adds token address information to each swap.
 ///
-/// If any swap is not a part of `known_swap_contracts`, the route is rejected, even if tokens
-/// would be valid.
+/// If any swap is not a part of `known_swap_contracts`, for example because it has been removed
+/// with [`remove_swap_contract`], the route is rejected, even if tokens would be valid. Routes with
+/// more than `max_route_length` swaps are rejected.
 fn validate_route_and_add_info(
     swap_route: &[Address],
     known_swap_contracts: &[SwapContractInfo],
     token_in: Address,
     token_out: Address,
+    max_route_length: usize,
 ) -> Vec<SwapInformation> {
     assert!(
-        swap_route.len() <= MAX_ROUTE_LENGTH,
+        swap_route.len() <= max_route_length,
         "Swap route length ({}) is greater than maximum allowed ({}).",
         swap_route.len(),
-        MAX_ROUTE_LENGTH
+        max_route_length
     );
 
     let mut res = Vec::with_capacity(swap_route.len());
@@ -391,13 +889,27 @@ fn validate_route_and_add_info(
         let swap_info = known_swap_contracts
             .iter()
             .find(|&contract_info| contract_info.swap_address == *swap_address)
-            .unwrap_or_else(|| panic!("Unknown swap address: {:x?}.", swap_address.identifier()));
+            .unwrap_or_else(|| {
+                panic!(
+                    "Swap contract {:x?} at swap number {} is not routable. It may have been removed.",
+                    swap_address.identifier(),
+                    i + 1
+                )
+            });
 
-        let (swap_input_token, swap_output_token) =
+        let (swap_input_token, swap_output_token, token_in_supports_approve_relative) =
             if prev_output_token == swap_info.token_a_address {
-                (swap_info.token_a_address, swap_info.token_b_address)
+                (
+                    swap_info.token_a_address,
+                    swap_info.token_b_address,
+                    swap_info.token_a_supports_approve_relative,
+                )
             } else if prev_output_token == swap_info.token_b_address {
-                (swap_info.token_b_address, swap_info.token_a_address)
+                (
+                    swap_info.token_b_address,
+                    swap_info.token_a_address,
+                    swap_info.token_b_supports_approve_relative,
+                )
             } else {
                 panic!(
                     "No tokens at swap contract {:x?} matches token {:x?}, at swap number {}.",
@@ -412,6 +924,7 @@ fn validate_route_and_add_info(
             swap_address: *swap_address,
             token_in: swap_input_token,
             token_out: swap_output_token,
+            token_in_supports_approve_relative,
         })
     }
 
@@ -425,19 +938,22 @@ fn validate_route_and_add_info(
 
 /// Callback to handle swap-router taking control of tokens.
 /// Starts the lock chain by acquiring the first lock.
+///
+/// If the router could not take control of the tokens, the route is dropped, such that it no
+/// longer counts towards the active routes of the user.
 #[callback(shortname = 0x20)]
 fn start_lock_chain_callback(
     _context: ContractContext,
     callback_context: CallbackContext,
-    state: RouterState,
+    mut state: RouterState,
     route_id: RouteId,
 ) -> (RouterState, Vec<EventGroup>) {
     if !callback_context.success {
-        panic!("Could not take control of tokens.");
+        state.route_tracker.remove_route(route_id);
+        return (state, vec![]);
     }
 
     let route = state.route_tracker.get_route(route_id);
-
     let lock_info = route.peek_next_wanted_lock().unwrap();
 
     // Acquire the first lock, and let callbacks handle the rest.
@@ -455,7 +971,8 @@ fn start_lock_chain_callback(
 /// to execute the acquired locks, and withdraw tokens along the way, on behalf of the user.
 ///
 /// Fails if a lock could not be acquired, which stops execution of the swap-chain,
-/// and cancels any so far acquired locks.
+/// and cancels any so far acquired locks. If the route allows partial execution, the so far
+/// acquired locks are executed instead, see [`RouteInformation::fall_back_to_partial_route`].
 #[callback(shortname = 0x03)]
 fn lock_route_callback(
     _context: ContractContext,
@@ -464,12 +981,26 @@ fn lock_route_callback(
     route_id: RouteId,
 ) -> (RouterState, Vec<EventGroup>) {
     let mut lock_event_builder = EventGroup::builder();
-    state
+    let route_statistics = &mut state.route_statistics;
+    let route_cancelled = state
         .route_tracker
         .modify_route(route_id, |route_information| {
             if !callback_context.success {
-                // We couldn't acquire a lock. Cleanup and throw error.
-                build_events_cancel_route(&mut lock_event_builder, route_information);
+                if route_information.fall_back_to_partial_route() {
+                    // Execute the locks acquired so far.
+                    let pending_lock = route_information.peek_next_pending_lock().unwrap();
+                    build_execute_approve_events(
+                        &mut lock_event_builder,
+                        pending_lock,
+                        route_id,
+                        route_information.initial_amount_in,
+                    );
+                } else {
+                    // We couldn't acquire a lock. Cleanup and throw error.
+                    route_statistics.record_cancelled();
+                    build_events_cancel_route(&mut lock_event_builder, route_information);
+                    return true;
+                }
             } else {
                 // Retrieve the output amount guaranteed from the lock just acquired, and update our state.
                 if let Some(exec_result) = callback_context.results.first() {
@@ -506,7 +1037,11 @@ fn lock_route_callback(
                     }
                 };
             }
+            false
         });
+    if route_cancelled {
+        state.route_tracker.remove_route(route_id);
+    }
     let events = vec![lock_event_builder.build()];
     (state, events)
 }
@@ -527,8 +1062,9 @@ fn execute_route_callback(
     last_output: TokenAmount,
 ) -> (RouterState, Vec<EventGroup>) {
     let mut execute_lock_event_builder = EventGroup::builder();
+    let route_statistics = &mut state.route_statistics;
 
-    state
+    let route_completed = state
         .route_tracker
         .modify_route(route_id, |route_information| {
             match route_information.peek_next_pending_lock() {
@@ -539,6 +1075,7 @@ fn execute_route_callback(
                         route_id,
                         last_output,
                     );
+                    false
                 }
                 None => {
                     // We finished executing the locks, now we just need to transfer the tokens to the original user.
@@ -547,9 +1084,14 @@ fn execute_route_callback(
                         &route_information.user,
                         route_information.final_received_amount,
                     );
+                    route_statistics.record_completed(route_information);
+                    true
                 }
             }
         });
+    if route_completed {
+        state.route_tracker.remove_route(route_id);
+    }
 
     let events = vec![execute_lock_event_builder.build()];
     (state, events)
@@ -679,6 +1221,9 @@ fn could_not_acquire_lock_error(
 /// Update state with the swap address at `swap_address` between token `token_a_address` and `token_b_address`
 /// to the known swap contracts, which can be used for routing.
 ///
+/// `token_a_supports_approve_relative` and `token_b_supports_approve_relative` indicate whether
+/// the tokens implement [`MPC20Contract::approve_relative`], see [`build_approve_event_for_execute`].
+///
 /// Fails if the sender does not have permission for updating the known swap contracts.
 #[action(shortname = 0x08)]
 fn add_swap_contract(
@@ -687,6 +1232,8 @@ fn add_swap_contract(
     swap_address: Address,
     token_a_address: Address,
     token_b_address: Address,
+    token_a_supports_approve_relative: bool,
+    token_b_supports_approve_relative: bool,
 ) -> (RouterState, Vec<EventGroup>) {
     state
         .permission_add_swap
@@ -696,11 +1243,87 @@ fn add_swap_contract(
         swap_address,
         token_a_address,
         token_b_address,
+        token_a_supports_approve_relative,
+        token_b_supports_approve_relative,
     });
 
     (state, vec![])
 }
 
+/// Update the cached reserves and swap fee of the known swap contract at `swap_address`, used by
+/// [`route_swap_auto`] to estimate swap outputs.
+///
+/// The router does not observe swaps made at the swap contract, so the cached reserves become
+/// stale as soon as the reserves of the swap contract change, until they are pushed again.
+///
+/// Fails if the sender does not have permission for updating the known swap contracts, if the
+/// swap contract is unknown, or if `swap_fee_per_mille` is not a valid per mille value.
+#[action(shortname = 0x0B)]
+fn update_cached_reserves(
+    context: ContractContext,
+    mut state: RouterState,
+    swap_address: Address,
+    reserve_a: TokenAmount,
+    reserve_b: TokenAmount,
+    swap_fee_per_mille: u16,
+) -> (RouterState, Vec<EventGroup>) {
+    state
+        .permission_add_swap
+        .assert_permission_for(&context.sender, "add swap");
+    assert_is_per_mille(swap_fee_per_mille);
+    assert!(
+        state
+            .swap_contracts
+            .iter()
+            .any(|contract_info| contract_info.swap_address == swap_address),
+        "Unknown swap address: {:x?}.",
+        swap_address.identifier()
+    );
+
+    state.cached_reserves.insert(
+        swap_address,
+        CachedReserves {
+            reserve_a,
+            reserve_b,
+            swap_fee_per_mille,
+        },
+    );
+
+    (state, vec![])
+}
+
+/// Remove the swap contract at `swap_address` from the known swap contracts, such that it can no
+/// longer be used for routing, for example when it has been delisted. Its cached reserves are
+/// removed as well. Routes that are already in progress are unaffected.
+///
+/// Fails if the sender does not have permission for updating the known swap contracts, or if the
+/// swap contract is unknown.
+#[action(shortname = 0x0C)]
+fn remove_swap_contract(
+    context: ContractContext,
+    mut state: RouterState,
+    swap_address: Address,
+) -> (RouterState, Vec<EventGroup>) {
+    state
+        .permission_add_swap
+        .assert_permission_for(&context.sender, "add swap");
+    assert!(
+        state
+            .swap_contracts
+            .iter()
+            .any(|contract_info| contract_info.swap_address == swap_address),
+        "Unknown swap address: {:x?}.",
+        swap_address.identifier()
+    );
+
+    state
+        .swap_contracts
+        .retain(|contract_info| contract_info.swap_address != swap_address);
+    state.cached_reserves.remove(&swap_address);
+
+    (state, vec![])
+}
+
 /// Builds event set to free acquired locks and to return tokens to owner.
 fn build_events_cancel_route(event_builder: &mut EventGroupBuilder, route: &RouteInformation) {
     // Cancel locks
@@ -748,7 +1371,7 @@ fn build_execute_approve_events(
     approval_amount: TokenAmount,
 ) {
     // Build the initial approve event.
-    build_max_approve_event_for_execute(event_builder, pending_lock);
+    build_approve_event_for_execute(event_builder, pending_lock, approval_amount);
 
     // Callback to start deposits.
     event_builder
@@ -756,6 +1379,30 @@ fn build_execute_approve_events(
         .done();
 }
 
+/// Builds the approval event for swap execution, approving exactly the `approval_amount` that will
+/// be deposited, using [`MPC20Contract::approve_relative`]. Relative approvals do not override each
+/// other, such that multiple routes can be executed at the same time through the same token.
+///
+/// Falls back to [`build_max_approve_event_for_execute`] for token contracts that does not
+/// implement [`MPC20Contract::approve_relative`], or if `approval_amount` does not fit in an
+/// `i128`.
+fn build_approve_event_for_execute(
+    event_builder: &mut EventGroupBuilder,
+    pending_lock: &AcquiredLockInfo,
+    approval_amount: TokenAmount,
+) {
+    match i128::try_from(approval_amount) {
+        Ok(delta) if pending_lock.swap_info.token_in_supports_approve_relative => {
+            MPC20Contract::at_address(pending_lock.swap_info.token_in).approve_relative(
+                event_builder,
+                &pending_lock.swap_info.swap_address,
+                delta,
+            );
+        }
+        _ => build_max_approve_event_for_execute(event_builder, pending_lock),
+    }
+}
+
 /// Builds the approval event for swap execution, with the max possible approval amount ([`TokenAmount::MAX`]).
 ///
 /// This is sub-optimal from a security perspective, but a convenient way to support token
@@ -793,6 +1440,9 @@ const INTERNAL_GAS_COST_EXECUTE_ROUTE_CALLBACK: GasCost = 1500;
 const INTERNAL_GAS_COST_CANCEL_LOCK_ERROR_CALLBACK: GasCost = 1500;
 
 /// Given the number of swaps on a route, calculates the worst-case minimum amount of gas for routing to succeed.
+///
+/// The cost grows linearly with the number of swaps, such that routes longer than
+/// [`DEFAULT_MAX_ROUTE_LENGTH`] require correspondingly more gas.
 fn calculate_min_total_gas_cost(number_of_swaps: usize) -> GasCost {
     let number_of_swaps = number_of_swaps as u64;
     let acquire_lock_cost =
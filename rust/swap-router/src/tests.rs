@@ -0,0 +1,745 @@
+use super::*;
+use defi_common::test_util::{account, context, contract};
+
+fn token(id: u8) -> Address {
+    contract(id)
+}
+
+fn swap(id: u8) -> Address {
+    contract(100 + id)
+}
+
+const A: u8 = 1;
+const B: u8 = 2;
+const C: u8 = 3;
+const D: u8 = 4;
+const E: u8 = 5;
+
+/// Token graph used for testing. Edges are swap contracts with the given reserves.
+///
+/// ```text
+///      2^21   2^20
+///       +----A----+
+/// 2^19  |         | 2^23
+///       B         C
+/// 2^18  |         | 2^22
+///       +----D----+
+///      2^17   2^24
+///
+///            E
+/// ```
+fn token_graph() -> (Vec<SwapContractInfo>, AvlTreeMap<Address, CachedReserves>) {
+    let edges = [
+        (1, A, B, 1 << 21, 1 << 19),
+        (2, A, C, 1 << 20, 1 << 23),
+        (3, B, D, 1 << 18, 1 << 17),
+        (4, C, D, 1 << 22, 1 << 24),
+    ];
+    let mut known_swap_contracts = vec![];
+    let mut cached_reserves = AvlTreeMap::new();
+    for (swap_id, token_a, token_b, reserve_a, reserve_b) in edges {
+        add_swap(
+            &mut known_swap_contracts,
+            &mut cached_reserves,
+            swap_id,
+            token_a,
+            token_b,
+            Some((reserve_a, reserve_b)),
+        );
+    }
+    (known_swap_contracts, cached_reserves)
+}
+
+fn add_swap(
+    known_swap_contracts: &mut Vec<SwapContractInfo>,
+    cached_reserves: &mut AvlTreeMap<Address, CachedReserves>,
+    swap_id: u8,
+    token_a: u8,
+    token_b: u8,
+    reserves: Option<(TokenAmount, TokenAmount)>,
+) {
+    known_swap_contracts.push(SwapContractInfo {
+        swap_address: swap(swap_id),
+        token_a_address: token(token_a),
+        token_b_address: token(token_b),
+        token_a_supports_approve_relative: true,
+        token_b_supports_approve_relative: true,
+    });
+    if let Some((reserve_a, reserve_b)) = reserves {
+        cached_reserves.insert(
+            swap(swap_id),
+            CachedReserves {
+                reserve_a,
+                reserve_b,
+                swap_fee_per_mille: 0,
+            },
+        );
+    }
+}
+
+#[test]
+fn chooses_route_with_largest_output() {
+    let (known, reserves) = token_graph();
+    let route = find_best_route(
+        &known,
+        &reserves,
+        token(A),
+        token(D),
+        512,
+        DEFAULT_MAX_ROUTE_LENGTH as usize,
+    );
+    assert_eq!(route, Some(vec![swap(2), swap(4)]));
+}
+
+#[test]
+fn chooses_route_in_reverse_direction() {
+    let (known, reserves) = token_graph();
+    let route = find_best_route(
+        &known,
+        &reserves,
+        token(D),
+        token(B),
+        512,
+        DEFAULT_MAX_ROUTE_LENGTH as usize,
+    );
+    assert_eq!(route, Some(vec![swap(3)]));
+}
+
+#[test]
+fn respects_maximum_number_of_hops() {
+    let (known, reserves) = token_graph();
+    assert_eq!(
+        find_best_route(&known, &reserves, token(A), token(D), 512, 1),
+        None
+    );
+    assert_eq!(
+        find_best_route(&known, &reserves, token(A), token(B), 512, 1),
+        Some(vec![swap(1)])
+    );
+}
+
+#[test]
+fn no_route_to_disconnected_token() {
+    let (known, reserves) = token_graph();
+    let route = find_best_route(
+        &known,
+        &reserves,
+        token(A),
+        token(E),
+        512,
+        DEFAULT_MAX_ROUTE_LENGTH as usize,
+    );
+    assert_eq!(route, None);
+}
+
+#[test]
+fn chooses_better_of_two_pools_for_same_pair() {
+    let (mut known, mut reserves) = token_graph();
+    add_swap(&mut known, &mut reserves, 5, A, B, Some((1 << 20, 1 << 22)));
+
+    let route = find_best_route(&known, &reserves, token(A), token(B), 512, 1);
+    assert_eq!(route, Some(vec![swap(5)]));
+}
+
+#[test]
+fn ignores_pools_without_cached_reserves() {
+    let (mut known, mut reserves) = token_graph();
+    add_swap(&mut known, &mut reserves, 5, A, D, None);
+
+    let route = find_best_route(
+        &known,
+        &reserves,
+        token(A),
+        token(D),
+        512,
+        DEFAULT_MAX_ROUTE_LENGTH as usize,
+    );
+    assert_eq!(route, Some(vec![swap(2), swap(4)]));
+}
+
+#[test]
+fn ignores_pools_without_liquidity() {
+    let (mut known, mut reserves) = token_graph();
+    add_swap(&mut known, &mut reserves, 5, A, D, Some((0, 0)));
+
+    let route = find_best_route(
+        &known,
+        &reserves,
+        token(A),
+        token(D),
+        512,
+        DEFAULT_MAX_ROUTE_LENGTH as usize,
+    );
+    assert_eq!(route, Some(vec![swap(2), swap(4)]));
+}
+
+#[test]
+fn prefers_shorter_route_on_equal_output() {
+    let (mut known, mut reserves) = token_graph();
+    // Direct pool giving the exact same output as the route through C.
+    let via_c = {
+        let a_to_c = reserves
+            .get(&swap(2))
+            .unwrap()
+            .estimate_output(&known[1], token(A), 512)
+            .unwrap();
+        reserves
+            .get(&swap(4))
+            .unwrap()
+            .estimate_output(&known[3], token(C), a_to_c)
+            .unwrap()
+    };
+    add_swap(&mut known, &mut reserves, 5, A, D, Some((512, 2 * via_c)));
+
+    let route = find_best_route(
+        &known,
+        &reserves,
+        token(A),
+        token(D),
+        512,
+        DEFAULT_MAX_ROUTE_LENGTH as usize,
+    );
+    assert_eq!(route, Some(vec![swap(5)]));
+}
+
+#[test]
+fn swap_fee_is_deducted_from_estimate() {
+    let swap_info = SwapContractInfo {
+        swap_address: swap(1),
+        token_a_address: token(A),
+        token_b_address: token(B),
+        token_a_supports_approve_relative: true,
+        token_b_supports_approve_relative: true,
+    };
+    let reserves = |swap_fee_per_mille| CachedReserves {
+        reserve_a: 1000,
+        reserve_b: 1000,
+        swap_fee_per_mille,
+    };
+    assert_eq!(
+        reserves(0).estimate_output(&swap_info, token(A), 1000),
+        Some(500)
+    );
+    // (997 * 1000 * 1000) / (1000 * 1000 + 997 * 1000)
+    assert_eq!(
+        reserves(3).estimate_output(&swap_info, token(A), 1000),
+        Some(499)
+    );
+    assert_eq!(
+        reserves(1000).estimate_output(&swap_info, token(A), 1000),
+        Some(0)
+    );
+}
+
+#[test]
+fn chooses_pool_with_lower_fee_over_larger_pool() {
+    let (mut known, mut reserves) = token_graph();
+    add_swap(&mut known, &mut reserves, 5, A, B, Some((1 << 20, 1 << 22)));
+    reserves.insert(
+        swap(5),
+        CachedReserves {
+            reserve_a: 1 << 20,
+            reserve_b: 1 << 22,
+            swap_fee_per_mille: 950,
+        },
+    );
+
+    let route = find_best_route(&known, &reserves, token(A), token(B), 512, 1);
+    assert_eq!(route, Some(vec![swap(1)]));
+}
+
+#[test]
+fn only_extends_best_route_to_each_token() {
+    let (mut known, mut reserves) = token_graph();
+    // Two more routes from A to C, through B and E, which are worse than the direct pool.
+    add_swap(&mut known, &mut reserves, 5, B, C, Some((1 << 10, 1 << 10)));
+    add_swap(&mut known, &mut reserves, 6, A, E, Some((1 << 20, 1 << 20)));
+    add_swap(&mut known, &mut reserves, 7, E, C, Some((1 << 20, 1 << 20)));
+
+    let route = find_best_route(
+        &known,
+        &reserves,
+        token(A),
+        token(D),
+        512,
+        DEFAULT_MAX_ROUTE_LENGTH as usize,
+    );
+    assert_eq!(route, Some(vec![swap(2), swap(4)]));
+}
+
+/// Route from A to D through B and C.
+fn route_a_to_d(partial_amount_out_minimum: Option<TokenAmount>) -> RouteInformation {
+    let route = [(1, A, B), (3, B, D), (4, D, C)]
+        .into_iter()
+        .map(|(swap_id, token_in, token_out)| SwapInformation {
+            swap_address: swap(swap_id),
+            token_in: token(token_in),
+            token_out: token(token_out),
+            token_in_supports_approve_relative: true,
+        })
+        .collect();
+    RouteInformation::new(
+        route,
+        512,
+        100,
+        None,
+        account(1),
+        partial_amount_out_minimum,
+    )
+}
+
+/// Simulates acquiring the next lock of the route, producing `amount_out`.
+fn acquire_next_lock(route: &mut RouteInformation, amount_out: TokenAmount) {
+    let wanted_lock = route.pop_next_wanted_lock().unwrap();
+    route.update_next_wanted_lock_amount_in(amount_out);
+    route.update_next_pending_lock_id(wanted_lock.swap_info, LiquidityLockId::initial_id());
+}
+
+#[test]
+fn partial_route_ends_at_last_acquired_lock() {
+    let mut route = route_a_to_d(Some(50));
+    acquire_next_lock(&mut route, 200);
+    acquire_next_lock(&mut route, 50);
+
+    assert!(route.fall_back_to_partial_route());
+    assert_eq!(route.final_token_out, token(D));
+    assert!(route.peek_next_wanted_lock().is_none());
+    assert_eq!(route.locks_waiting_for_execution.len(), 2);
+}
+
+#[test]
+fn partial_route_not_used_unless_allowed() {
+    let mut route = route_a_to_d(None);
+    acquire_next_lock(&mut route, 200);
+
+    assert!(!route.fall_back_to_partial_route());
+    assert_eq!(route.final_token_out, token(C));
+    assert_eq!(route.locks_wanted.len(), 2);
+}
+
+#[test]
+fn partial_route_not_used_without_acquired_locks() {
+    let mut route = route_a_to_d(Some(0));
+
+    assert!(!route.fall_back_to_partial_route());
+    assert_eq!(route.final_token_out, token(C));
+    assert_eq!(route.locks_wanted.len(), 3);
+}
+
+#[test]
+fn partial_route_not_used_without_output() {
+    let mut route = route_a_to_d(Some(0));
+    acquire_next_lock(&mut route, 0);
+
+    assert!(!route.fall_back_to_partial_route());
+    assert_eq!(route.final_token_out, token(C));
+    assert_eq!(route.locks_wanted.len(), 2);
+}
+
+#[test]
+fn partial_route_not_used_below_partial_minimum() {
+    let mut route = route_a_to_d(Some(51));
+    acquire_next_lock(&mut route, 200);
+    acquire_next_lock(&mut route, 50);
+
+    assert!(!route.fall_back_to_partial_route());
+    assert_eq!(route.final_token_out, token(C));
+    assert_eq!(route.locks_wanted.len(), 1);
+}
+
+fn user() -> Address {
+    account(1)
+}
+
+fn callback_context(success: bool) -> CallbackContext {
+    CallbackContext {
+        success,
+        results: vec![],
+    }
+}
+
+fn router_state(max_active_routes_per_user: u32) -> RouterState {
+    let (swap_contracts, _) = token_graph();
+    let (state, _) = initialize(
+        context(user(), 0),
+        Permission::Anybody {},
+        swap_contracts,
+        max_active_routes_per_user,
+        None,
+    );
+    state
+}
+
+/// Requests a route from A to D through C, and lets the router take control of the tokens.
+fn start_route(state: RouterState) -> (RouterState, RouteId) {
+    let route_id = state.route_tracker.next_route_id;
+    let (state, _) = route_swap(
+        context(user(), 0),
+        state,
+        vec![swap(2), swap(4)],
+        token(A),
+        token(D),
+        512,
+        1,
+        None,
+    );
+    let (state, _) =
+        start_lock_chain_callback(context(user(), 0), callback_context(true), state, route_id);
+    (state, route_id)
+}
+
+#[test]
+#[should_panic(expected = "User already has the maximum number of active routes (2).")]
+fn active_route_cap_blocks_third_route() {
+    let (state, _) = start_route(router_state(2));
+    let (state, _) = start_route(state);
+    assert_eq!(state.route_tracker.number_of_active_routes(&user()), 2);
+
+    start_route(state);
+}
+
+#[test]
+fn completed_route_allows_new_route() {
+    let (state, first_route_id) = start_route(router_state(2));
+    let (state, _) = start_route(state);
+
+    // Without any acquired locks, the route is completed by the execution callback.
+    let (state, _) = execute_route_callback(
+        context(user(), 0),
+        callback_context(true),
+        state,
+        first_route_id,
+        0,
+    );
+    assert_eq!(state.route_tracker.number_of_active_routes(&user()), 1);
+
+    let (state, _) = start_route(state);
+    assert_eq!(state.route_tracker.number_of_active_routes(&user()), 2);
+}
+
+#[test]
+fn cancelled_route_allows_new_route() {
+    let (state, first_route_id) = start_route(router_state(1));
+
+    let (state, _) = lock_route_callback(
+        context(user(), 0),
+        callback_context(false),
+        state,
+        first_route_id,
+    );
+    assert_eq!(state.route_tracker.number_of_active_routes(&user()), 0);
+    assert!(state.route_tracker.active_routes_per_user.is_empty());
+
+    let (state, _) = start_route(state);
+    assert_eq!(state.route_tracker.number_of_active_routes(&user()), 1);
+}
+
+#[test]
+fn route_counts_as_active_when_added() {
+    let route_id = 0;
+    let (state, _) = route_swap(
+        context(user(), 0),
+        router_state(1),
+        vec![swap(2), swap(4)],
+        token(A),
+        token(D),
+        512,
+        1,
+        None,
+    );
+    assert_eq!(state.route_tracker.number_of_active_routes(&user()), 1);
+    assert!(state.route_tracker.active_routes.get(&route_id).is_some());
+}
+
+#[test]
+fn route_is_dropped_if_tokens_cannot_be_taken() {
+    let route_id = 0;
+    let (state, _) = route_swap(
+        context(user(), 0),
+        router_state(1),
+        vec![swap(2), swap(4)],
+        token(A),
+        token(D),
+        512,
+        1,
+        None,
+    );
+
+    let (state, events) =
+        start_lock_chain_callback(context(user(), 0), callback_context(false), state, route_id);
+    assert!(events.is_empty());
+    assert!(state.route_tracker.active_routes.is_empty());
+    assert!(state.route_tracker.active_routes_per_user.is_empty());
+}
+
+#[test]
+fn finished_routes_are_removed() {
+    let (state, completed_route_id) = start_route(router_state(2));
+    let (state, cancelled_route_id) = start_route(state);
+
+    let (state, _) = execute_route_callback(
+        context(user(), 0),
+        callback_context(true),
+        state,
+        completed_route_id,
+        0,
+    );
+    let (state, _) = lock_route_callback(
+        context(user(), 0),
+        callback_context(false),
+        state,
+        cancelled_route_id,
+    );
+    assert!(state.route_tracker.active_routes.is_empty());
+    assert!(state.route_tracker.active_routes_per_user.is_empty());
+}
+
+/// Known swap contracts forming a line of `number_of_swaps` swaps, from token 1 to token
+/// `number_of_swaps + 1`.
+fn swap_line(number_of_swaps: u8) -> Vec<SwapContractInfo> {
+    (1..=number_of_swaps)
+        .map(|id| SwapContractInfo {
+            swap_address: swap(id),
+            token_a_address: token(id),
+            token_b_address: token(id + 1),
+            token_a_supports_approve_relative: true,
+            token_b_supports_approve_relative: true,
+        })
+        .collect()
+}
+
+fn route_of_length_six(max_route_length: u32) -> Vec<SwapInformation> {
+    let swap_contracts = swap_line(6);
+    let swap_route: Vec<Address> = (1..=6).map(swap).collect();
+    let (state, _) = initialize(
+        context(user(), 0),
+        Permission::Anybody {},
+        swap_contracts,
+        1,
+        max_route_length,
+    );
+    validate_route_and_add_info(
+        &swap_route,
+        &state.swap_contracts,
+        token(1),
+        token(7),
+        state.max_route_length as usize,
+    )
+}
+
+#[test]
+fn route_of_length_six_accepted_with_raised_maximum() {
+    let route = route_of_length_six(6);
+    assert_eq!(route.len(), 6);
+    assert_eq!(route[5].token_out, token(7));
+}
+
+#[test]
+#[should_panic(expected = "Swap route length (6) is greater than maximum allowed (5).")]
+fn route_of_length_six_rejected_with_default_maximum() {
+    route_of_length_six(DEFAULT_MAX_ROUTE_LENGTH);
+}
+
+fn router_state_with_max_route_length(max_route_length: u32) -> RouterState {
+    let (state, _) = initialize(
+        context(user(), 0),
+        Permission::Anybody {},
+        vec![],
+        1,
+        Some(max_route_length),
+    );
+    state
+}
+
+#[test]
+fn maximum_route_length_defaults_when_not_given() {
+    let state = router_state(1);
+    assert_eq!(state.max_route_length, DEFAULT_MAX_ROUTE_LENGTH);
+
+    let state = router_state_with_max_route_length(MAX_ROUTE_LENGTH_CEILING);
+    assert_eq!(state.max_route_length, MAX_ROUTE_LENGTH_CEILING);
+}
+
+#[test]
+#[should_panic(expected = "Maximum route length (9) must be between 1 and 8.")]
+fn maximum_route_length_is_bounded_by_ceiling() {
+    router_state_with_max_route_length(MAX_ROUTE_LENGTH_CEILING + 1);
+}
+
+#[test]
+#[should_panic(expected = "Maximum route length (0) must be between 1 and 8.")]
+fn maximum_route_length_must_be_positive() {
+    router_state_with_max_route_length(0);
+}
+
+#[test]
+fn gas_estimate_grows_with_route_length() {
+    let per_swap_cost = calculate_min_total_gas_cost(2) - calculate_min_total_gas_cost(1);
+    assert!(per_swap_cost > 0);
+    assert_eq!(
+        calculate_min_total_gas_cost(6),
+        calculate_min_total_gas_cost(5) + per_swap_cost
+    );
+    assert_eq!(
+        calculate_min_total_gas_cost(MAX_ROUTE_LENGTH_CEILING as usize),
+        calculate_min_total_gas_cost(1) + (MAX_ROUTE_LENGTH_CEILING as u64 - 1) * per_swap_cost
+    );
+}
+
+/// Given the number of swaps on a route, calculates the number of chained events spawned when
+/// the route is executed.
+fn chained_event_count(number_of_swaps: u32) -> u32 {
+    CHAINED_EVENTS_PER_ROUTE + number_of_swaps * CHAINED_EVENTS_PER_SWAP
+}
+
+#[test]
+fn route_of_maximum_length_stays_within_chained_event_limit() {
+    assert_eq!(MAX_ROUTE_LENGTH_CEILING, 8);
+    assert_eq!(chained_event_count(DEFAULT_MAX_ROUTE_LENGTH), 73);
+    assert!(chained_event_count(MAX_ROUTE_LENGTH_CEILING) <= MAX_CHAINED_EVENTS);
+    assert!(chained_event_count(MAX_ROUTE_LENGTH_CEILING + 1) > MAX_CHAINED_EVENTS);
+}
+
+#[test]
+#[should_panic(expected = "Maximum number of swaps (6) must be between 1 and 5.")]
+fn auto_route_hops_bounded_by_configured_maximum() {
+    route_swap_auto(
+        context(user(), 0),
+        router_state(1),
+        token(A),
+        token(D),
+        512,
+        1,
+        6,
+    );
+}
+
+fn pending_lock(token_in_supports_approve_relative: bool) -> AcquiredLockInfo {
+    AcquiredLockInfo {
+        swap_info: SwapInformation {
+            swap_address: swap(1),
+            token_in: token(A),
+            token_out: token(B),
+            token_in_supports_approve_relative,
+        },
+        lock_id: LiquidityLockId::initial_id(),
+    }
+}
+
+fn expected_execute_approve_events(
+    approve: impl FnOnce(&mut EventGroupBuilder, &MPC20Contract),
+    approval_amount: TokenAmount,
+) -> EventGroup {
+    let mut event_builder = EventGroup::builder();
+    approve(&mut event_builder, &MPC20Contract::at_address(token(A)));
+    event_builder
+        .with_callback_rpc(approve_callback::rpc(0, approval_amount))
+        .done();
+    event_builder.build()
+}
+
+#[test]
+fn execution_approves_exact_amount_for_relative_approve_tokens() {
+    let mut event_builder = EventGroup::builder();
+    build_execute_approve_events(&mut event_builder, &pending_lock(true), 0, 512);
+
+    let expected = expected_execute_approve_events(
+        |builder, token| {
+            token.approve_relative(builder, &swap(1), 512);
+        },
+        512,
+    );
+    assert_eq!(event_builder.build(), expected);
+}
+
+#[test]
+fn execution_approves_max_amount_for_other_tokens() {
+    let mut event_builder = EventGroup::builder();
+    build_execute_approve_events(&mut event_builder, &pending_lock(false), 0, 512);
+
+    let expected = expected_execute_approve_events(
+        |builder, token| {
+            token.approve(builder, &swap(1), TokenAmount::MAX);
+        },
+        512,
+    );
+    assert_eq!(event_builder.build(), expected);
+}
+
+#[test]
+fn execution_approves_max_amount_if_amount_exceeds_relative_delta() {
+    let approval_amount = i128::MAX as TokenAmount + 1;
+    let mut event_builder = EventGroup::builder();
+    build_execute_approve_events(&mut event_builder, &pending_lock(true), 0, approval_amount);
+
+    let expected = expected_execute_approve_events(
+        |builder, token| {
+            token.approve(builder, &swap(1), TokenAmount::MAX);
+        },
+        approval_amount,
+    );
+    assert_eq!(event_builder.build(), expected);
+}
+
+#[test]
+fn route_uses_approve_relative_support_of_input_token() {
+    let swap_contracts = vec![SwapContractInfo {
+        swap_address: swap(1),
+        token_a_address: token(A),
+        token_b_address: token(B),
+        token_a_supports_approve_relative: false,
+        token_b_supports_approve_relative: true,
+    }];
+
+    let a_to_b = validate_route_and_add_info(&[swap(1)], &swap_contracts, token(A), token(B), 1);
+    assert!(!a_to_b[0].token_in_supports_approve_relative);
+
+    let b_to_a = validate_route_and_add_info(&[swap(1)], &swap_contracts, token(B), token(A), 1);
+    assert!(b_to_a[0].token_in_supports_approve_relative);
+}
+
+#[test]
+fn removing_swap_contract_removes_its_cached_reserves() {
+    let (swap_contracts, cached_reserves) = token_graph();
+    let (mut state, _) = initialize(
+        context(user(), 0),
+        Permission::Anybody {},
+        swap_contracts,
+        2,
+        None,
+    );
+    state.cached_reserves = cached_reserves;
+
+    let (state, _) = remove_swap_contract(context(user(), 0), state, swap(2));
+
+    assert_eq!(state.swap_contracts.len(), 3);
+    assert!(state
+        .swap_contracts
+        .iter()
+        .all(|contract_info| contract_info.swap_address != swap(2)));
+    assert!(state.cached_reserves.get(&swap(2)).is_none());
+    assert!(state.cached_reserves.get(&swap(4)).is_some());
+}
+
+#[test]
+#[should_panic(
+    expected = "Swap contract [66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66, 66] at swap number 1 is not routable. It may have been removed."
+)]
+fn removed_swap_contract_cannot_be_routed_through() {
+    let (state, _) = remove_swap_contract(context(user(), 0), router_state(2), swap(2));
+    start_route(state);
+}
+
+#[test]
+#[should_panic(expected = "Unknown swap address")]
+fn removing_unknown_swap_contract_fails() {
+    remove_swap_contract(context(user(), 0), router_state(2), swap(9));
+}
+
+#[test]
+#[should_panic(expected = "did not have permission \"add swap\"")]
+fn removing_swap_contract_requires_permission() {
+    let mut state = router_state(2);
+    state.permission_add_swap = Permission::Specific { addresses: vec![] };
+    remove_swap_contract(context(user(), 0), state, swap(2));
+}
@@ -1,5 +1,10 @@
 #![doc = include_str!("../README.md")]
 
+mod upgrade;
+
+#[cfg(test)]
+mod tests;
+
 #[macro_use]
 extern crate pbc_contract_codegen;
 
@@ -7,10 +12,20 @@ use create_type_spec_derive::CreateTypeSpec;
 use read_write_rpc_derive::ReadWriteRPC;
 use std::ops::Sub;
 
+use defi_common::events;
 use defi_common::token_state::AbstractTokenState;
-use pbc_contract_common::address::Address;
+use pbc_contract_common::address::{Address, Shortname};
+use pbc_contract_common::avl_tree_map::AvlTreeMap;
 use pbc_contract_common::context::ContractContext;
-use pbc_contract_common::sorted_vec_map::SortedVecMap;
+use pbc_contract_common::events::{EventGroup, EventGroupBuilder};
+use pbc_contract_common::signature::Signature;
+use pbc_contract_common::Hash;
+use pbc_traits::{ReadWriteState, WriteRPC};
+use read_write_state_derive::ReadWriteState;
+
+/// [`Shortname`] of the [`transfer_event()`] action. Transfer hooks must implement an action with
+/// this shortname, taking the same arguments.
+const SHORTNAME_TRANSFER_EVENT: Shortname = Shortname::from_u32(0x0C);
 
 /// MPC-20-v1 token contract compatible state.
 ///
@@ -24,15 +39,165 @@ pub struct TokenState {
     /// The number of decimals the token uses - e.g. 8,
     /// means to divide the token amount by `100000000` to get its user representation.
     pub decimals: u8,
-    /// The owner of the contract. Not used for anything beyond the initial minting.
+    /// The owner of the contract. Receives the initial supply, and is the only one allowed to [`mint()`] new tokens.
     pub owner: Address,
     /// Current amount of tokens for the TokenContract.
     pub total_supply: u128,
+    /// Maximum amount of tokens that can exist. [`mint()`] cannot increase the
+    /// [`TokenState::total_supply`] above this. If not set, the supply is unlimited.
+    pub max_supply: Option<u128>,
     /// Token balances for the accounts associated with the contract.
-    pub balances: SortedVecMap<Address, u128>,
+    pub balances: AvlTreeMap<Address, u128>,
+    /// Number of distinct accounts with a non-zero balance, i.e. the number of entries in
+    /// [`TokenState::balances`]. Maintained by [`AbstractTokenState::update_balance`].
+    pub holder_count: u32,
     /// Ledger for allowances, that allows users or contracts to transfer tokens on behalf of
     /// others.
-    pub allowed: SortedVecMap<Address, SortedVecMap<Address, u128>>,
+    pub allowed: AvlTreeMap<AllowedAddress, u128>,
+    /// The next nonce to be used in a [`permit()`] signed by each owner. Owners without an entry
+    /// have not used any permits, and their next nonce is zero.
+    pub permit_nonces: AvlTreeMap<Address, u64>,
+    /// Accounts frozen by the owner using [`freeze()`]. Frozen accounts can neither send nor
+    /// receive tokens, but keep their balances.
+    pub frozen_accounts: AvlTreeMap<Address, bool>,
+    /// Contract notified of every transfer, set by the owner using [`set_transfer_hook()`]. If not
+    /// set, the transfer events are sent to the token contract itself.
+    pub transfer_hook: Option<Address>,
+}
+
+impl TokenState {
+    /// Adds a transfer event for a single logical transfer to the given event group. The event is
+    /// sent to the [`TokenState::transfer_hook`] if set, and otherwise to the contract itself.
+    ///
+    /// The event is given [`events::GAS_COST_EVENT`] gas, which transfer hooks must not exceed.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `builder`: [`EventGroupBuilder`], the event group to add the event to.
+    ///
+    /// * `contract_address`: [`Address`], the address of this token contract.
+    ///
+    /// * `from`: [`Address`], the address the tokens were transferred from.
+    ///
+    /// * `to`: [`Address`], the address the tokens were transferred to.
+    ///
+    /// * `amount`: [`u128`], the amount transferred.
+    fn add_transfer_event(
+        &self,
+        builder: &mut EventGroupBuilder,
+        contract_address: Address,
+        from: Address,
+        to: Address,
+        amount: u128,
+    ) {
+        builder
+            .call(
+                self.transfer_hook.unwrap_or(contract_address),
+                SHORTNAME_TRANSFER_EVENT,
+            )
+            .argument(from)
+            .argument(to)
+            .argument(amount)
+            .with_cost(events::GAS_COST_EVENT)
+            .done();
+    }
+
+    /// Determines whether `account` has been frozen with [`freeze()`].
+    fn is_frozen(&self, account: &Address) -> bool {
+        self.frozen_accounts.get(account).unwrap_or(false)
+    }
+
+    /// Panics if either `from` or `to` is frozen, as frozen accounts can neither send nor receive
+    /// tokens.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `from`: [`Address`], the address the tokens are transferred from.
+    ///
+    /// * `to`: [`Address`], the address the tokens are transferred to.
+    fn assert_not_frozen(&self, from: &Address, to: &Address) {
+        self.assert_can_send(from);
+        self.assert_can_receive(to);
+    }
+
+    /// Panics if `account` is frozen, as frozen accounts cannot send or burn tokens.
+    fn assert_can_send(&self, account: &Address) {
+        if self.is_frozen(account) {
+            panic!("The account {account} is frozen, and cannot send tokens.");
+        }
+    }
+
+    /// Panics if `account` is frozen, as frozen accounts cannot receive or be minted tokens.
+    fn assert_can_receive(&self, account: &Address) {
+        if self.is_frozen(account) {
+            panic!("The account {account} is frozen, and cannot receive tokens.");
+        }
+    }
+
+    /// Burns `amount` tokens from the balance of `owner`, decreasing the total supply.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `owner`: [`Address`], the address to burn tokens from.
+    ///
+    /// * `amount`: [`u128`], amount to burn.
+    fn burn_tokens(&mut self, owner: Address, amount: u128) {
+        let balance = self.balance_of(&owner);
+        let new_balance = balance.checked_sub(amount).unwrap_or_else(|| {
+            panic!(
+                "Insufficient {} tokens for burn! Have {}, but trying to burn {} (in minimal units)",
+                self.symbol, balance, amount
+            )
+        });
+        self.update_balance(owner, new_balance);
+        self.total_supply -= amount;
+    }
+}
+
+/// Address pair representing an allowance. Owner allows spender to transfer tokens on behalf of
+/// them.
+#[derive(ReadWriteState, CreateTypeSpec, Eq, Ord, PartialEq, PartialOrd)]
+pub struct AllowedAddress {
+    /// Owner of the tokens
+    pub owner: Address,
+    /// User allowed to transfer on behalf of [`AllowedAddress::owner`].
+    pub spender: Address,
+}
+
+/// Allowance change signed off-chain by the owner of the tokens, which can be submitted by anyone
+/// using [`permit()`].
+#[derive(ReadWriteRPC, CreateTypeSpec)]
+pub struct Permit {
+    /// Owner of the tokens, who signed the permit.
+    pub owner: Address,
+    /// User allowed to transfer on behalf of [`Permit::owner`].
+    pub spender: Address,
+    /// The new allowance of the spender.
+    pub amount: u128,
+    /// The nonce of the permit. Must be the next unused nonce of the owner.
+    pub nonce: u64,
+    /// The time in utc milliseconds, after which the permit can no longer be used.
+    pub deadline_millis: i64,
+}
+
+impl Permit {
+    /// Computes the hash signed by the owner of the permit. The hash covers the address of the token
+    /// contract followed by the RPC serialization of the permit, such that a permit cannot be used
+    /// on other token contracts.
+    ///
+    /// ### Parameters:
+    ///
+    /// * `token_contract`: [`Address`], the address of this token contract.
+    ///
+    /// ### Returns
+    ///
+    /// The [`Hash`] of the permit message.
+    fn message_hash(&self, token_contract: Address) -> Hash {
+        let mut message: Vec<u8> = vec![];
+        token_contract.rpc_write_to(&mut message).unwrap();
+        self.rpc_write_to(&mut message).unwrap();
+        Hash::digest(&message)
+    }
 }
 
 /// Extension trait for inserting into a map holding balances.
@@ -51,13 +216,15 @@ trait BalanceMap<K: Ord, V> {
     fn insert_balance(&mut self, key: K, value: V);
 }
 
-/// Extension for [`SortedVecMap`] allowing the use of [`BalanceMap::insert_balance`].
+/// Extension for [`AvlTreeMap`] allowing the use of [`BalanceMap::insert_balance`].
 ///
 /// This implementation defines zero as `forall v: v - v = 0` (the subtract of a value from itself), to support a large variety
 /// of values. Might not work correctly for unusual implementations of [`Sub::sub`].
-impl<V: Sub<V, Output = V> + PartialEq + Copy> BalanceMap<Address, V> for SortedVecMap<Address, V> {
+impl<V: Sub<V, Output = V> + PartialEq + Copy + ReadWriteState, K: ReadWriteState + Ord>
+    BalanceMap<K, V> for AvlTreeMap<K, V>
+{
     #[allow(clippy::eq_op)]
-    fn insert_balance(&mut self, key: Address, value: V) {
+    fn insert_balance(&mut self, key: K, value: V) {
         let zero = value - value;
         if value == zero {
             self.remove(&key);
@@ -73,28 +240,31 @@ impl AbstractTokenState for TokenState {
     }
 
     fn balance_of(&self, owner: &Address) -> u128 {
-        self.balances.get(owner).copied().unwrap_or(0)
+        self.balances.get(owner).unwrap_or(0)
     }
 
     fn update_balance(&mut self, owner: Address, amount: u128) {
-        self.balances.insert_balance(owner, amount)
+        let was_holder = self.balances.contains_key(&owner);
+        self.balances.insert_balance(owner, amount);
+        match (was_holder, amount != 0) {
+            (false, true) => self.holder_count += 1,
+            (true, false) => self.holder_count -= 1,
+            _ => {}
+        }
     }
 
     fn allowance(&self, owner: &Address, spender: &Address) -> u128 {
         self.allowed
-            .get(owner)
-            .and_then(|allowed_from_owner| allowed_from_owner.get(spender))
-            .copied()
+            .get(&AllowedAddress {
+                owner: *owner,
+                spender: *spender,
+            })
             .unwrap_or(0)
     }
 
     fn update_allowance(&mut self, owner: Address, spender: Address, amount: u128) {
-        if !self.allowed.contains_key(&owner) {
-            self.allowed.insert(owner, SortedVecMap::new());
-        }
-        let allowed_from_owner = self.allowed.get_mut(&owner).unwrap();
-
-        allowed_from_owner.insert_balance(spender, amount);
+        self.allowed
+            .insert_balance(AllowedAddress { owner, spender }, amount);
     }
 }
 
@@ -113,6 +283,9 @@ impl AbstractTokenState for TokenState {
 ///
 /// * `total_supply`: [`u128`], current amount of tokens for the TokenContract.
 ///
+/// * `max_supply`: [`Option<u128>`], the maximum amount of tokens that can exist, or `None` for
+/// an unlimited supply. Must be at least `total_supply`.
+///
 /// ### Returns:
 ///
 /// The new state object of type [`TokenState`] with an initialized ledger.
@@ -123,15 +296,28 @@ pub fn initialize(
     symbol: String,
     decimals: u8,
     total_supply: u128,
+    max_supply: Option<u128>,
 ) -> TokenState {
+    if let Some(max_supply) = max_supply {
+        if total_supply > max_supply {
+            panic!(
+                "Initial supply of {total_supply} tokens exceeds the maximum supply of {max_supply} tokens."
+            );
+        }
+    }
     let mut initial_state = TokenState {
         name,
         symbol,
         decimals,
         owner: ctx.sender,
         total_supply,
-        balances: SortedVecMap::new(),
-        allowed: SortedVecMap::new(),
+        max_supply,
+        balances: AvlTreeMap::new(),
+        holder_count: 0,
+        allowed: AvlTreeMap::new(),
+        permit_nonces: AvlTreeMap::new(),
+        frozen_accounts: AvlTreeMap::new(),
+        transfer_hook: None,
     };
 
     initial_state.update_balance(ctx.sender, total_supply);
@@ -150,7 +336,7 @@ pub struct Transfer {
 /// Transfers `amount` of tokens to address `to` from the caller.
 ///
 /// The function throws if the message caller's account
-/// balance does not have enough tokens to spend.
+/// balance does not have enough tokens to spend, or if the caller or `to` is frozen.
 /// If the sender's account goes to 0, the sender's address is removed from state.
 ///
 /// ### Parameters:
@@ -165,22 +351,32 @@ pub struct Transfer {
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenState`] with an updated ledger.
+/// The new state object of type [`TokenState`] with an updated ledger, and a transfer event.
 #[action(shortname = 0x01)]
 pub fn transfer(
     context: ContractContext,
     mut state: TokenState,
     to: Address,
     amount: u128,
-) -> TokenState {
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_frozen(&context.sender, &to);
     state.transfer(context.sender, to, amount);
-    state
+
+    let mut event_group = EventGroup::builder();
+    state.add_transfer_event(
+        &mut event_group,
+        context.contract_address,
+        context.sender,
+        to,
+        amount,
+    );
+    (state, vec![event_group.build()])
 }
 
 /// Transfers a bulk of `amount` of tokens to address `to` from the caller.
 ///
 /// The function throws if the message caller's account
-/// balance does not have enough tokens to spend.
+/// balance does not have enough tokens to spend, or if the caller or any receiver is frozen.
 /// If the sender's account goes to 0, the sender's address is removed from state.
 ///
 /// ### Parameters:
@@ -193,17 +389,27 @@ pub fn transfer(
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenState`] with an updated ledger.
+/// The new state object of type [`TokenState`] with an updated ledger, and a transfer event for
+/// each transfer.
 #[action(shortname = 0x02)]
 pub fn bulk_transfer(
     context: ContractContext,
     mut state: TokenState,
     transfers: Vec<Transfer>,
-) -> TokenState {
+) -> (TokenState, Vec<EventGroup>) {
+    let mut event_group = EventGroup::builder();
     for t in transfers {
+        state.assert_not_frozen(&context.sender, &t.to);
         state.transfer(context.sender, t.to, t.amount);
+        state.add_transfer_event(
+            &mut event_group,
+            context.contract_address,
+            context.sender,
+            t.to,
+            t.amount,
+        );
     }
-    state
+    (state, vec![event_group.build()])
 }
 
 /// Transfers `amount` of tokens from address `from` to address `to`.
@@ -211,7 +417,8 @@ pub fn bulk_transfer(
 /// This requires that the sender is allowed to do the transfer by the `from`
 /// account through the `approve` action.
 /// The function throws if the message caller's account
-/// balance does not have enough tokens to spend, or if the tokens were not approved.
+/// balance does not have enough tokens to spend, if the tokens were not approved, or if `from` or
+/// `to` is frozen.
 ///
 /// ### Parameters:
 ///
@@ -227,7 +434,7 @@ pub fn bulk_transfer(
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenState`] with an updated ledger.
+/// The new state object of type [`TokenState`] with an updated ledger, and a transfer event.
 #[action(shortname = 0x03)]
 pub fn transfer_from(
     context: ContractContext,
@@ -235,9 +442,13 @@ pub fn transfer_from(
     from: Address,
     to: Address,
     amount: u128,
-) -> TokenState {
+) -> (TokenState, Vec<EventGroup>) {
+    state.assert_not_frozen(&from, &to);
     state.transfer_from(context.sender, from, to, amount);
-    state
+
+    let mut event_group = EventGroup::builder();
+    state.add_transfer_event(&mut event_group, context.contract_address, from, to, amount);
+    (state, vec![event_group.build()])
 }
 
 /// Transfers a bulk of `amount` of tokens to address `to` from address `from`.
@@ -245,7 +456,8 @@ pub fn transfer_from(
 /// This requires that the sender is allowed to do the transfer by the `from`
 /// account through the `approve` action.
 /// The function throws if the message caller's account
-/// balance does not have enough tokens to spend, or if the tokens were not approved.
+/// balance does not have enough tokens to spend, if the tokens were not approved, or if `from` or
+/// any receiver is frozen.
 ///
 /// ### Parameters:
 ///
@@ -259,18 +471,28 @@ pub fn transfer_from(
 ///
 /// ### Returns
 ///
-/// The new state object of type [`TokenState`] with an updated ledger.
+/// The new state object of type [`TokenState`] with an updated ledger, and a transfer event for
+/// each transfer.
 #[action(shortname = 0x04)]
 pub fn bulk_transfer_from(
     context: ContractContext,
     mut state: TokenState,
     from: Address,
     transfers: Vec<Transfer>,
-) -> TokenState {
+) -> (TokenState, Vec<EventGroup>) {
+    let mut event_group = EventGroup::builder();
     for t in transfers {
+        state.assert_not_frozen(&from, &t.to);
         state.transfer_from(context.sender, from, t.to, t.amount);
+        state.add_transfer_event(
+            &mut event_group,
+            context.contract_address,
+            from,
+            t.to,
+            t.amount,
+        );
     }
-    state
+    (state, vec![event_group.build()])
 }
 
 /// Allows `spender` to withdraw from the owners account multiple times, up to the `amount`.
@@ -317,3 +539,354 @@ pub fn approve_relative(
     state.update_allowance_relative(context.sender, spender, delta);
     state
 }
+
+/// Allows `spender` to withdraw `delta` additional tokens from the owners account, on top of any
+/// pre-existing allowance.
+///
+/// Unlike [`approve`], the change is relative to the current allowance, such that a spender using
+/// part of the allowance concurrently cannot spend more than intended.
+/// The function throws if the resulting allowance would overflow.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `spender`: [`Address`], the address of the spender.
+///
+/// * `delta`: [`u128`], amount to increase the allowance by.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with an updated ledger.
+#[action(shortname = 0x0A)]
+pub fn increase_allowance(
+    context: ContractContext,
+    mut state: TokenState,
+    spender: Address,
+    delta: u128,
+) -> TokenState {
+    let new_allowance = state
+        .allowance(&context.sender, &spender)
+        .checked_add(delta)
+        .expect("Allowance would overflow.");
+    state.update_allowance(context.sender, spender, new_allowance);
+    state
+}
+
+/// Lowers the amount `spender` is allowed to withdraw from the owners account by `delta`.
+///
+/// If `delta` is larger than the current allowance, the allowance is set to zero.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `spender`: [`Address`], the address of the spender.
+///
+/// * `delta`: [`u128`], amount to decrease the allowance by.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with an updated ledger.
+#[action(shortname = 0x0B)]
+pub fn decrease_allowance(
+    context: ContractContext,
+    mut state: TokenState,
+    spender: Address,
+    delta: u128,
+) -> TokenState {
+    let new_allowance = state
+        .allowance(&context.sender, &spender)
+        .checked_sub(delta)
+        .unwrap_or(0);
+    state.update_allowance(context.sender, spender, new_allowance);
+    state
+}
+
+/// Sets the allowance of `spender` to withdraw from the account of `owner`, as authorized by a
+/// [`Permit`] signed off-chain by the owner. Anyone can submit the permit, such that the owner does
+/// not need to send a transaction, nor pay for gas.
+///
+/// The function throws if the deadline of the permit has passed, if the owner is frozen, if the
+/// nonce is not the next unused nonce of the owner, or if the signature is not made by the owner.
+/// Each nonce can only be used once, preventing the permit from being replayed.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `permit`: [`Permit`], the allowance change signed by the owner.
+///
+/// * `signature`: [`Signature`], the signature of the owner on [`Permit::message_hash`].
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with an updated ledger.
+#[action(shortname = 0x0E)]
+pub fn permit(
+    context: ContractContext,
+    mut state: TokenState,
+    permit: Permit,
+    signature: Signature,
+) -> TokenState {
+    if context.block_production_time > permit.deadline_millis {
+        panic!(
+            "Permit expired at {}, but the current block production time is {}.",
+            permit.deadline_millis, context.block_production_time
+        );
+    }
+    if state.is_frozen(&permit.owner) {
+        panic!(
+            "The account {} is frozen, and cannot approve spenders by permit.",
+            permit.owner
+        );
+    }
+    let expected_nonce = state.permit_nonces.get(&permit.owner).unwrap_or(0);
+    if permit.nonce != expected_nonce {
+        panic!(
+            "Invalid permit nonce. Expected {}, but got {}.",
+            expected_nonce, permit.nonce
+        );
+    }
+    let signer = signature
+        .recover_public_key(&permit.message_hash(context.contract_address))
+        .map(|public_key| public_key.to_address());
+    if signer != Some(permit.owner) {
+        panic!("Permit is not signed by the owner {}.", permit.owner);
+    }
+
+    state.permit_nonces.insert(permit.owner, expected_nonce + 1);
+    state.update_allowance(permit.owner, permit.spender, permit.amount);
+    state
+}
+
+/// Mints `amount` new tokens to address `to`, increasing the total supply.
+///
+/// Only the owner of the contract is allowed to mint tokens, and the total supply cannot be
+/// increased above the [maximum supply](TokenState::max_supply), if set. Tokens cannot be minted to
+/// a frozen account.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `to`: [`Address`], the address to mint the tokens to.
+///
+/// * `amount`: [`u128`], amount to mint.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with an updated ledger.
+#[action(shortname = 0x08)]
+pub fn mint(
+    context: ContractContext,
+    mut state: TokenState,
+    to: Address,
+    amount: u128,
+) -> TokenState {
+    if context.sender != state.owner {
+        panic!("Only the owner of the contract can mint tokens.");
+    }
+    state.assert_can_receive(&to);
+    state.total_supply = state
+        .total_supply
+        .checked_add(amount)
+        .expect("Minting would overflow the total supply.");
+    if let Some(max_supply) = state.max_supply {
+        if state.total_supply > max_supply {
+            panic!(
+                "Minting {amount} tokens would exceed the maximum supply of {max_supply} tokens."
+            );
+        }
+    }
+    let new_balance = state.balance_of(&to) + amount;
+    state.update_balance(to, new_balance);
+    state
+}
+
+/// Burns `amount` of the caller's tokens, decreasing the total supply.
+///
+/// The function throws if the message caller's account is frozen, or if its
+/// balance does not have enough tokens to burn.
+/// If the sender's account goes to 0, the sender's address is removed from state.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `amount`: [`u128`], amount to burn.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with an updated ledger.
+#[action(shortname = 0x09)]
+pub fn burn(context: ContractContext, mut state: TokenState, amount: u128) -> TokenState {
+    state.assert_can_send(&context.sender);
+    state.burn_tokens(context.sender, amount);
+    state
+}
+
+/// Burns `amount` tokens from the account of `from`, decreasing the total supply. The caller must
+/// have been allowed to spend the tokens, and the allowance is decreased by `amount`.
+///
+/// The function throws if `from` is frozen, if the allowance of the caller is too low, or if the
+/// balance of `from` does not have enough tokens to burn.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `from`: [`Address`], the address to burn tokens from.
+///
+/// * `amount`: [`u128`], amount to burn.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with an updated ledger.
+#[action(shortname = 0x0F)]
+pub fn burn_from(
+    context: ContractContext,
+    mut state: TokenState,
+    from: Address,
+    amount: u128,
+) -> TokenState {
+    state.assert_can_send(&from);
+    let allowance = state.allowance(&from, &context.sender);
+    let new_allowance = allowance.checked_sub(amount).unwrap_or_else(|| {
+        panic!(
+            "Insufficient {} allowance for burn_from! Allowed {}, but trying to burn {} (in minimal units)",
+            state.symbol, allowance, amount
+        )
+    });
+    state.update_allowance(from, context.sender, new_allowance);
+    state.burn_tokens(from, amount);
+    state
+}
+
+/// Freezes `account`, such that it can neither send nor receive tokens until it is unfrozen with
+/// [`unfreeze()`]. The balance of the account is kept.
+///
+/// Only the owner of the contract is allowed to freeze accounts.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `account`: [`Address`], the account to freeze.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with the account frozen.
+#[action(shortname = 0x10)]
+pub fn freeze(context: ContractContext, mut state: TokenState, account: Address) -> TokenState {
+    if context.sender != state.owner {
+        panic!("Only the owner of the contract can freeze accounts.");
+    }
+    state.frozen_accounts.insert(account, true);
+    state
+}
+
+/// Unfreezes `account`, previously frozen with [`freeze()`], allowing it to send and receive
+/// tokens again.
+///
+/// Only the owner of the contract is allowed to unfreeze accounts.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `account`: [`Address`], the account to unfreeze.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with the account unfrozen.
+#[action(shortname = 0x11)]
+pub fn unfreeze(context: ContractContext, mut state: TokenState, account: Address) -> TokenState {
+    if context.sender != state.owner {
+        panic!("Only the owner of the contract can unfreeze accounts.");
+    }
+    state.frozen_accounts.remove(&account);
+    state
+}
+
+/// Sets the contract notified of every transfer, replacing any previous transfer hook. If `None`,
+/// transfer events are sent to the token contract itself.
+///
+/// The hook must implement an action with the shortname and arguments of [`transfer_event()`],
+/// which is called with [`events::GAS_COST_EVENT`] gas. A failing hook does not revert the
+/// transfer.
+///
+/// Only the owner of the contract is allowed to set the transfer hook.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `transfer_hook`: [`Option<Address>`], the contract to notify of transfers.
+///
+/// ### Returns
+///
+/// The new state object of type [`TokenState`] with the updated transfer hook.
+#[action(shortname = 0x0D)]
+pub fn set_transfer_hook(
+    context: ContractContext,
+    mut state: TokenState,
+    transfer_hook: Option<Address>,
+) -> TokenState {
+    if context.sender != state.owner {
+        panic!("Only the owner of the contract can set the transfer hook.");
+    }
+    state.transfer_hook = transfer_hook;
+    state
+}
+
+/// Event describing a single transfer of tokens, sent by the token contract to itself when no
+/// transfer hook is set. Does not change the state, but makes transfers observable.
+///
+/// ### Parameters:
+///
+/// * `context`: [`ContractContext`], the context for the action call.
+///
+/// * `state`: [`TokenState`], the current state of the contract.
+///
+/// * `from`: [`Address`], the address the tokens were transferred from.
+///
+/// * `to`: [`Address`], the address the tokens were transferred to.
+///
+/// * `amount`: [`u128`], the amount transferred.
+///
+/// ### Returns
+///
+/// The unchanged state object of type [`TokenState`].
+#[action(shortname = 0x0C)]
+pub fn transfer_event(
+    context: ContractContext,
+    state: TokenState,
+    _from: Address,
+    _to: Address,
+    _amount: u128,
+) -> TokenState {
+    events::assert_event_sent_by_contract(
+        &context,
+        "Transfer events can only be sent by the token contract itself.",
+    );
+    state
+}
@@ -0,0 +1,389 @@
+use super::*;
+use defi_common::test_util::{account, context, contract, contract_address};
+
+fn token(max_supply: Option<u128>) -> TokenState {
+    initialize(
+        context(account(1), 0),
+        "My Cool Token".to_string(),
+        "COOL".to_string(),
+        8,
+        1000,
+        max_supply,
+    )
+}
+
+/// Creates a token where address 1 holds 700 tokens, address 2 holds 200 tokens, address 3 holds
+/// 100 tokens, and address 1 has allowed address 2 and 3 to spend 50 and 25 tokens respectively.
+fn token_with_holders() -> TokenState {
+    let mut state = token(None);
+    state.transfer(account(1), account(2), 200);
+    state.transfer(account(1), account(3), 100);
+    state.update_allowance(account(1), account(2), 50);
+    state.update_allowance(account(1), account(3), 25);
+    state
+}
+
+#[test]
+fn balances_survive_upgrade() {
+    let state = upgrade::upgrade_state(context(account(1), 0), token_with_holders());
+
+    assert_eq!(state.balance_of(&account(1)), 700);
+    assert_eq!(state.balance_of(&account(2)), 200);
+    assert_eq!(state.balance_of(&account(3)), 100);
+    assert_eq!(state.balance_of(&account(4)), 0);
+    assert_eq!(state.balances.iter().count(), 3);
+    assert_eq!(state.holder_count, 3);
+    assert_eq!(state.total_supply, 1000);
+    assert_eq!(state.owner, account(1));
+}
+
+#[test]
+fn allowances_survive_upgrade() {
+    let state = upgrade::upgrade_state(context(account(1), 0), token_with_holders());
+
+    assert_eq!(state.allowance(&account(1), &account(2)), 50);
+    assert_eq!(state.allowance(&account(1), &account(3)), 25);
+    assert_eq!(state.allowance(&account(2), &account(1)), 0);
+    assert_eq!(state.allowed.iter().count(), 2);
+}
+
+#[test]
+fn transfers_of_small_maps() {
+    let mut state = token_with_holders();
+
+    state.transfer(account(3), account(4), 100);
+    state.transfer_from(account(2), account(1), account(2), 50);
+
+    assert_eq!(state.balance_of(&account(1)), 650);
+    assert_eq!(state.balance_of(&account(2)), 250);
+    assert_eq!(state.balance_of(&account(4)), 100);
+    assert_eq!(state.balances.get(&account(3)), None);
+    assert_eq!(state.allowance(&account(1), &account(2)), 0);
+    assert_eq!(state.allowed.iter().count(), 1);
+}
+
+#[test]
+fn mint_up_to_max_supply() {
+    let state = token(Some(1500));
+    let state = mint(context(account(1), 0), state, account(2), 400);
+    let state = mint(context(account(1), 0), state, account(2), 100);
+
+    assert_eq!(state.total_supply, 1500);
+    assert_eq!(state.balance_of(&account(2)), 500);
+}
+
+#[test]
+#[should_panic(expected = "Minting 501 tokens would exceed the maximum supply of 1500 tokens.")]
+fn mint_above_max_supply_is_rejected() {
+    let state = token(Some(1500));
+    mint(context(account(1), 0), state, account(2), 501);
+}
+
+#[test]
+fn uncapped_token_mints_freely() {
+    let state = token(None);
+    let state = mint(context(account(1), 0), state, account(2), u128::MAX - 1000);
+
+    assert_eq!(state.total_supply, u128::MAX);
+    assert_eq!(state.balance_of(&account(2)), u128::MAX - 1000);
+}
+
+#[test]
+#[should_panic(
+    expected = "Initial supply of 1000 tokens exceeds the maximum supply of 999 tokens."
+)]
+fn initial_supply_above_max_supply_is_rejected() {
+    token(Some(999));
+}
+
+fn token_with_frozen(id: u8) -> TokenState {
+    let state = token(None);
+    let (state, _) = transfer(context(account(1), 0), state, account(id), 100);
+    freeze(context(account(1), 0), state, account(id))
+}
+
+#[test]
+fn frozen_account_keeps_balance() {
+    let state = token_with_frozen(2);
+
+    assert!(state.is_frozen(&account(2)));
+    assert!(!state.is_frozen(&account(1)));
+    assert_eq!(state.balance_of(&account(2)), 100);
+}
+
+#[test]
+#[should_panic(expected = "is frozen, and cannot send tokens.")]
+fn frozen_account_cannot_transfer() {
+    let state = token_with_frozen(2);
+    transfer(context(account(2), 0), state, account(3), 10);
+}
+
+#[test]
+#[should_panic(expected = "is frozen, and cannot receive tokens.")]
+fn frozen_account_cannot_be_transferred_to() {
+    let state = token_with_frozen(2);
+    transfer(context(account(1), 0), state, account(2), 10);
+}
+
+#[test]
+#[should_panic(expected = "is frozen, and cannot send tokens.")]
+fn frozen_account_cannot_be_transferred_from() {
+    let state = token_with_frozen(2);
+    let state = approve(context(account(2), 0), state, account(3), 10);
+    transfer_from(context(account(3), 0), state, account(2), account(3), 10);
+}
+
+#[test]
+#[should_panic(expected = "is frozen, and cannot receive tokens.")]
+fn frozen_account_cannot_receive_bulk_transfer() {
+    let state = token_with_frozen(2);
+    let transfers = vec![
+        Transfer {
+            to: account(3),
+            amount: 10,
+        },
+        Transfer {
+            to: account(2),
+            amount: 10,
+        },
+    ];
+    bulk_transfer(context(account(1), 0), state, transfers);
+}
+
+#[test]
+#[should_panic(expected = "is frozen, and cannot receive tokens.")]
+fn tokens_cannot_be_minted_to_frozen_account() {
+    let state = token_with_frozen(2);
+    mint(context(account(1), 0), state, account(2), 10);
+}
+
+#[test]
+#[should_panic(expected = "is frozen, and cannot send tokens.")]
+fn frozen_account_cannot_burn() {
+    let state = token_with_frozen(2);
+    burn(context(account(2), 0), state, 10);
+}
+
+#[test]
+#[should_panic(expected = "is frozen, and cannot send tokens.")]
+fn tokens_of_frozen_account_cannot_be_burned_by_spender() {
+    let state = token_with_frozen(2);
+    let state = approve(context(account(2), 0), state, account(3), 10);
+    burn_from(context(account(3), 0), state, account(2), 10);
+}
+
+#[test]
+fn unfrozen_account_can_send_and_receive() {
+    let state = token_with_frozen(2);
+    let state = unfreeze(context(account(1), 0), state, account(2));
+    assert!(!state.is_frozen(&account(2)));
+
+    let (state, _) = transfer(context(account(2), 0), state, account(3), 10);
+    let (state, _) = transfer(context(account(1), 0), state, account(2), 20);
+    assert_eq!(state.balance_of(&account(2)), 110);
+    assert_eq!(state.balance_of(&account(3)), 10);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner of the contract can freeze accounts.")]
+fn non_owner_cannot_freeze() {
+    let state = token(None);
+    freeze(context(account(2), 0), state, account(3));
+}
+
+#[test]
+#[should_panic(expected = "Only the owner of the contract can unfreeze accounts.")]
+fn non_owner_cannot_unfreeze() {
+    let state = token_with_frozen(2);
+    unfreeze(context(account(2), 0), state, account(2));
+}
+
+#[test]
+fn initial_holder_is_counted() {
+    let state = token(None);
+    assert_eq!(state.holder_count, 1);
+}
+
+#[test]
+fn holder_count_tracks_transfers() {
+    let state = token(None);
+    let (state, _) = transfer(context(account(1), 0), state, account(2), 100);
+    let (state, _) = transfer(context(account(1), 0), state, account(3), 100);
+    assert_eq!(state.holder_count, 3);
+
+    // Adding to an existing holder does not change the count.
+    let (state, _) = transfer(context(account(1), 0), state, account(2), 100);
+    assert_eq!(state.holder_count, 3);
+
+    // Emptying a balance removes the holder, while the receiver is already counted.
+    let (state, _) = transfer(context(account(3), 0), state, account(2), 100);
+    assert_eq!(state.holder_count, 2);
+
+    // Transferring the full balance to a new holder keeps the count.
+    let (state, _) = transfer(context(account(2), 0), state, account(4), 300);
+    assert_eq!(state.holder_count, 2);
+    assert_eq!(state.balances.iter().count(), 2);
+}
+
+#[test]
+fn zero_transfer_does_not_add_holder() {
+    let state = token(None);
+    let (state, _) = transfer(context(account(1), 0), state, account(2), 0);
+    assert_eq!(state.holder_count, 1);
+}
+
+#[test]
+fn holder_count_tracks_mint_and_burn() {
+    let state = token(None);
+    let state = mint(context(account(1), 0), state, account(2), 50);
+    assert_eq!(state.holder_count, 2);
+
+    let state = burn(context(account(2), 0), state, 20);
+    assert_eq!(state.holder_count, 2);
+
+    let state = burn(context(account(2), 0), state, 30);
+    assert_eq!(state.holder_count, 1);
+
+    let state = burn(context(account(1), 0), state, 1000);
+    assert_eq!(state.holder_count, 0);
+    assert_eq!(state.balances.iter().count(), 0);
+}
+
+/// Builds the event group expected from a transfer action, with a transfer event for each of the
+/// given `(from, to, amount)` transfers, sent to `receiver`.
+fn expected_transfer_events(
+    receiver: Address,
+    transfers: &[(Address, Address, u128)],
+) -> EventGroup {
+    let mut event_group = EventGroup::builder();
+    for (from, to, amount) in transfers {
+        event_group
+            .call(receiver, SHORTNAME_TRANSFER_EVENT)
+            .argument(*from)
+            .argument(*to)
+            .argument(*amount)
+            .with_cost(events::GAS_COST_EVENT)
+            .done();
+    }
+    event_group.build()
+}
+
+#[test]
+fn transfer_emits_single_event() {
+    let (_, events) = transfer(context(account(1), 0), token(None), account(2), 100);
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0],
+        expected_transfer_events(contract_address(), &[(account(1), account(2), 100)])
+    );
+}
+
+#[test]
+fn transfer_from_emits_single_event() {
+    let state = approve(context(account(1), 0), token(None), account(3), 100);
+    let (state, events) = transfer_from(context(account(3), 0), state, account(1), account(2), 100);
+
+    assert_eq!(state.allowance(&account(1), &account(3)), 0);
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0],
+        expected_transfer_events(contract_address(), &[(account(1), account(2), 100)])
+    );
+}
+
+fn bulk_of_three() -> Vec<Transfer> {
+    (2..5)
+        .map(|id| Transfer {
+            to: account(id),
+            amount: u128::from(id) * 10,
+        })
+        .collect()
+}
+
+#[test]
+fn bulk_transfer_emits_event_per_transfer() {
+    let (state, events) = bulk_transfer(context(account(1), 0), token(None), bulk_of_three());
+
+    assert_eq!(state.balance_of(&account(1)), 910);
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0],
+        expected_transfer_events(
+            contract_address(),
+            &[
+                (account(1), account(2), 20),
+                (account(1), account(3), 30),
+                (account(1), account(4), 40),
+            ]
+        )
+    );
+}
+
+#[test]
+fn bulk_transfer_from_emits_event_per_transfer() {
+    let state = approve(context(account(1), 0), token(None), account(5), 90);
+    let (state, events) =
+        bulk_transfer_from(context(account(5), 0), state, account(1), bulk_of_three());
+
+    assert_eq!(state.allowance(&account(1), &account(5)), 0);
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0],
+        expected_transfer_events(
+            contract_address(),
+            &[
+                (account(1), account(2), 20),
+                (account(1), account(3), 30),
+                (account(1), account(4), 40),
+            ]
+        )
+    );
+}
+
+#[test]
+fn transfer_events_are_sent_to_hook() {
+    let state = set_transfer_hook(context(account(1), 0), token(None), Some(contract(7)));
+    let (state, events) = transfer(context(account(1), 0), state, account(2), 100);
+    assert_eq!(
+        events[0],
+        expected_transfer_events(contract(7), &[(account(1), account(2), 100)])
+    );
+
+    let state = set_transfer_hook(context(account(1), 0), state, None);
+    let (_, events) = transfer(context(account(1), 0), state, account(2), 100);
+    assert_eq!(
+        events[0],
+        expected_transfer_events(contract_address(), &[(account(1), account(2), 100)])
+    );
+}
+
+#[test]
+#[should_panic(expected = "Only the owner of the contract can set the transfer hook.")]
+fn non_owner_cannot_set_transfer_hook() {
+    set_transfer_hook(context(account(2), 0), token(None), Some(contract(7)));
+}
+
+#[test]
+fn transfer_event_does_not_change_state() {
+    let state = transfer_event(
+        context(contract_address(), 0),
+        token(None),
+        account(1),
+        account(2),
+        100,
+    );
+    assert_eq!(state.balance_of(&account(1)), 1000);
+}
+
+#[test]
+#[should_panic(expected = "Transfer events can only be sent by the token contract itself.")]
+fn users_cannot_send_transfer_events() {
+    transfer_event(
+        context(account(1), 0),
+        token(None),
+        account(1),
+        account(2),
+        100,
+    );
+}
@@ -0,0 +1,47 @@
+//! Submodule handles upgrade logic for the smart contract.
+
+use crate::TokenState;
+use pbc_contract_codegen::upgrade_is_allowed;
+#[cfg(doc)]
+use pbc_contract_common::avl_tree_map::AvlTreeMap;
+use pbc_contract_common::context::ContractContext;
+use pbc_contract_common::upgrade::ContractHashes;
+
+/// Determines whether the contract is upgradable in the current context.
+///
+/// This contract allows the [`TokenState::owner`] to upgrade the contract at any time.
+///
+/// # Parameters:
+///
+/// * `context`: The context for the action call.
+/// * `state`: The current state of the contract.
+/// * `current_contract_hashes`: Hashes of the contract currently running this code.
+/// * `new_contract_hashes`: Hashes of the contract being upgraded to.
+/// * `new_contract_rpc`: RPC for the contract upgrade, if needed.
+///
+#[upgrade_is_allowed]
+pub fn is_upgrade_allowed(
+    context: ContractContext,
+    state: TokenState,
+    _current_contract_hashes: ContractHashes,
+    _new_contract_hashes: ContractHashes,
+    _new_contract_rpc: Vec<u8>,
+) -> bool {
+    context.sender == state.owner
+}
+
+/// Upgrades the state of the contract.
+///
+/// Token contracts deployed before balances and allowances were stored in [`AvlTreeMap`]s do not
+/// allow upgrades, so the first state that can be upgraded from is the current [`TokenState`].
+/// The state is decoded using that layout, and kept as is. Later versions changing the state
+/// must decode this layout, and migrate it.
+///
+/// # Parameters:
+///
+/// * `context`: The context for the action call.
+/// * `state`: The current state of the contract.
+#[upgrade]
+pub fn upgrade_state(_context: ContractContext, state: TokenState) -> TokenState {
+    state
+}
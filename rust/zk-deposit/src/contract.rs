@@ -5,6 +5,7 @@ extern crate pbc_contract_codegen;
 extern crate pbc_contract_common;
 
 use create_type_spec_derive::CreateTypeSpec;
+use defi_common::events;
 use defi_common::interact_mpc20;
 use pbc_contract_common::address::Address;
 use pbc_contract_common::avl_tree_map::AvlTreeMap;
@@ -24,9 +25,19 @@ pub mod zk_compute;
 /// Type used to represent token amounts. Equal to the MPC20 token sizes.
 pub type TokenAmount = interact_mpc20::TokenTransferAmount;
 
+/// Error message used when a new work item is rejected due to [`ContractState::max_queue_length`].
+const WORK_QUEUE_FULL_MESSAGE: &str = "The work queue is full. Please try again later.";
+
+/// Error message used when a new work item is rejected due to [`ContractState::paused`].
+const PAUSED_MESSAGE: &str = "The contract is paused. Please try again later.";
+
 /// Type used as identifiers for [transfers awaiting approval](`ContractState::transfers_yet_to_be_approved`).
 pub type PendingTransferId = u32;
 
+/// Client-supplied nonce of a [transfer request](`request_transfer`). A sender cannot have two
+/// pending transfers with the same nonce, which makes resubmitted requests idempotent.
+pub type TransferNonce = u64;
+
 /// Contract state of the Zk-deposit contract.
 #[state]
 pub struct ContractState {
@@ -35,12 +46,32 @@ pub struct ContractState {
     /// Queue of [`WorkListItem`]s that is still waiting to be started. Items can only be started when the
     /// preceeding computation has finished.
     pub work_queue: VecDeque<WorkListItem>,
-    /// List of transfers that have not been approved by the approver.
+    /// List of transfers that have not been approved by enough approvers.
     pub transfers_yet_to_be_approved: AvlTreeMap<PendingTransferId, TransferData>,
-    /// User that may approve transfers between users.
-    pub transfer_approver: Address,
+    /// Number of transfers in [`ContractState::transfers_yet_to_be_approved`], such that approvers
+    /// can see how many requests are queued without scanning them.
+    pub pending_transfer_count: u32,
+    /// Users that may approve transfers between users. The first approver is the owner of the
+    /// secret-shared transfers while they are under review.
+    pub transfer_approvers: Vec<Address>,
+    /// Number of distinct [`ContractState::transfer_approvers`] that must approve a transfer before
+    /// it is added to the [`ContractState::work_queue`].
+    pub approval_threshold: u32,
     /// Address of the token contract that this contract operates over.
     pub token_address: Address,
+    /// Maximum number of [`WorkListItem`]s that can be waiting in [`ContractState::work_queue`],
+    /// including the [`ContractState::deposits_in_flight`] that will be added to it.
+    /// [`deposit()`], [`deposit_many()`], [`withdraw()`] and [`approve_transfer()`] are rejected when
+    /// the queue is full.
+    pub max_queue_length: u32,
+    /// Number of deposits whose tokens are being transferred to the contract. Each of these adds a
+    /// [`WorkListItem::PendingDeposit`] to the [`ContractState::work_queue`] in
+    /// [`deposit_callback`], and therefore occupies a slot of the queue until then.
+    pub deposits_in_flight: u32,
+    /// Whether the contract is paused for maintenance. While paused, [`deposit()`],
+    /// [`deposit_many()`], [`withdraw()`], [`create_account()`] and [`approve_transfer()`] are
+    /// rejected, while the items already in [`ContractState::work_queue`] are still worked through.
+    pub paused: bool,
     /// List of variables that have been marked redundant. These will be removed after the next
     /// computation have finished, and may contain variables that are used by the computation.
     pub redundant_variables: Vec<SecretVarId>,
@@ -65,7 +96,7 @@ pub enum VariableKind {
     },
     /// Secret-share variable is a work item.
     ///
-    /// Indicates that secret data is [`zk_compute::PendingTransferSecrets`] or [`zk_compute::AccountCreationSecrets`], depending upon the computation.
+    /// Indicates that secret data is [`zk_compute::AccountCreationSecrets`].
     #[discriminant(2)]
     WorkItemData {
         /// Owner of the work.
@@ -85,6 +116,19 @@ pub enum VariableKind {
         /// owned by this address.
         owner: Address,
     },
+    /// Secret-share variable is a requested transfer.
+    ///
+    /// Indicates that secret data is [`zk_compute::PendingTransferSecrets`].
+    #[discriminant(4)]
+    TransferRequestData {
+        /// Owner of the transfer while it is under review.
+        ///
+        /// The [`ContractState::transfer_variables_to_owner`] method ensures that the variable is
+        /// owned by this address.
+        owner: Address,
+        /// Nonce supplied by the sender of the transfer.
+        nonce: TransferNonce,
+    },
 }
 
 impl VariableKind {
@@ -97,6 +141,7 @@ impl VariableKind {
             VariableKind::DepositBalance { owner } => owner,
             VariableKind::WorkItemData { owner } => owner,
             VariableKind::WorkResult { owner } => owner,
+            VariableKind::TransferRequestData { owner, .. } => owner,
         }
     }
 }
@@ -114,6 +159,10 @@ pub struct TransferData {
     sender: Address,
     /// Identifier for [`zk_compute::PendingTransferSecrets`].
     transfer_data_id: SecretVarId,
+    /// Nonce supplied by the sender when requesting the transfer.
+    nonce: TransferNonce,
+    /// Approvers that have approved the transfer so far.
+    approvals: Vec<Address>,
 }
 
 /// Indicates the type of the item in the work list.
@@ -126,7 +175,7 @@ pub enum WorkListItem {
         transfer: TransferData,
     },
 
-    /// Created by the [`deposit()`] invocation.
+    /// Created by the [`deposit()`] and [`deposit_many()`] invocations.
     ///
     /// Invariant: The `account` user will have an account in the contract, as it is
     /// checked by [`deposit()`] invocation.
@@ -159,10 +208,32 @@ pub enum WorkListItem {
 
 impl ContractState {
     /// True if and only if the given address has an account in the contract.
-    fn has_account(&self, owner: &Address) -> bool {
+    ///
+    /// Only reveals the existence of the account; the balance itself stays secret.
+    pub fn account_exists(&self, owner: &Address) -> bool {
         self.get_balance_variable_id(owner).is_some()
     }
 
+    /// True if and only if `sender` has a transfer with the given `nonce` that is still pending,
+    /// either awaiting approval or waiting in the [`ContractState::work_queue`].
+    fn has_pending_transfer_with_nonce(&self, sender: &Address, nonce: TransferNonce) -> bool {
+        let is_same_request =
+            |transfer: &TransferData| transfer.sender == *sender && transfer.nonce == nonce;
+        self.transfers_yet_to_be_approved
+            .iter()
+            .any(|(_, transfer)| is_same_request(&transfer))
+            || self.work_queue.iter().any(|item| match item {
+                WorkListItem::PendingTransfer { transfer } => is_same_request(transfer),
+                _ => false,
+            })
+    }
+
+    /// True if and only if the work queue cannot hold any more items, counting the
+    /// [`ContractState::deposits_in_flight`] as already queued.
+    fn is_work_queue_full(&self) -> bool {
+        self.work_queue.len() + self.deposits_in_flight as usize >= self.max_queue_length as usize
+    }
+
     /// Get the id of the balance variable associated with the given address.
     fn get_balance_variable_id(&self, owner: &Address) -> Option<SecretVarId> {
         self.balances.get(owner)
@@ -226,7 +297,7 @@ impl ContractState {
                 account,
                 account_creation_id,
             } => {
-                if self.has_account(&account) {
+                if self.account_exists(&account) {
                     fail_safely(
                         context,
                         event_groups,
@@ -295,6 +366,7 @@ impl ContractState {
                     TransferData {
                         sender,
                         transfer_data_id,
+                        ..
                     },
             } => {
                 let sender_balance_variable_id = match self.get_balance_variable_id(&sender) {
@@ -405,24 +477,49 @@ impl ContractState {
 
 /// Initializes contract to a minimum state with no balances and no active transfers.
 ///
-/// `transfer_approver` (same as [`ContractState::transfer_approver`]) must be set to the user,
-/// or the governance system that should approve transfers.
+/// `transfer_approvers` (same as [`ContractState::transfer_approvers`]) must be set to the users,
+/// or the governance systems that should approve transfers.
+///
+/// `approval_threshold` (same as [`ContractState::approval_threshold`]) is the number of
+/// approvers that must approve each transfer. Must be between 1 and the number of approvers.
 ///
 /// `token_address` (same as [`ContractState::token_address`]) must be set to the token to use as
 /// the underlying asset.
+///
+/// `max_queue_length` (same as [`ContractState::max_queue_length`]) limits the number of work
+/// items that can be waiting for computation at any time. Must be at least 1.
 #[init(zk = true)]
 pub fn initialize(
     _context: ContractContext,
     _zk_state: ZkState<VariableKind>,
-    transfer_approver: Address,
+    transfer_approvers: Vec<Address>,
+    approval_threshold: u32,
     token_address: Address,
+    max_queue_length: u32,
 ) -> ContractState {
+    assert!(
+        0 < approval_threshold && approval_threshold as usize <= transfer_approvers.len(),
+        "Approval threshold must be between 1 and the number of approvers"
+    );
+    assert!(0 < max_queue_length, "Max queue length must be at least 1");
+    for (idx, approver) in transfer_approvers.iter().enumerate() {
+        assert!(
+            !transfer_approvers[..idx].contains(approver),
+            "Duplicate transfer approver: {approver}"
+        );
+    }
+
     ContractState {
         balances: AvlTreeMap::new(),
         work_queue: VecDeque::new(),
         transfers_yet_to_be_approved: AvlTreeMap::new(),
-        transfer_approver,
+        pending_transfer_count: 0,
+        transfer_approvers,
+        approval_threshold,
         token_address,
+        max_queue_length,
+        deposits_in_flight: 0,
+        paused: false,
         redundant_variables: vec![],
         _ignored_variable_kind: None,
     }
@@ -430,55 +527,104 @@ pub fn initialize(
 
 /// Create a pending transfer from the transaction sender to a secret recipient with a secret amount.
 ///
-/// Transfer must be approved by the approver by calling [`approve_transfer()`]. Once approved, the
-/// transfer will be made through MPC, and be completed async.
+/// Transfer must be approved by [`ContractState::approval_threshold`] approvers by calling
+/// [`approve_transfer()`]. Once approved, the transfer will be made through MPC, and be completed
+/// async.
+///
+/// The `nonce` is chosen by the sender, and is rejected while the sender has a pending transfer
+/// with the same nonce. A client that resubmits a request, for example due to a dropped response,
+/// can thereby not create the same transfer twice.
 #[zk_on_secret_input(shortname = 0x4A)]
 pub fn request_transfer(
-    _context: ContractContext,
+    context: ContractContext,
     state: ContractState,
     _zk_state: ZkState<VariableKind>,
+    nonce: TransferNonce,
 ) -> (
     ContractState,
     Vec<EventGroup>,
     ZkInputDef<VariableKind, zk_compute::PendingTransferSecrets>,
 ) {
+    assert!(
+        !state.has_pending_transfer_with_nonce(&context.sender, nonce),
+        "{} already has a pending transfer with nonce {nonce}",
+        context.sender
+    );
     let input_def = ZkInputDef::with_metadata(
         Some(transfer_request_inputted::SHORTNAME),
-        VariableKind::WorkItemData {
-            owner: state.transfer_approver,
+        VariableKind::TransferRequestData {
+            owner: state.transfer_approvers[0],
+            nonce,
         },
     );
     (state, vec![], input_def)
 }
 
 /// Automatically invoked when user has completed input of [`request_transfer`].
+///
+/// The input is deleted if the sender has gained a pending transfer with the same nonce while the
+/// input was in progress.
 #[zk_on_variable_inputted(shortname = 0x47)]
 pub fn transfer_request_inputted(
-    _context: ContractContext,
+    context: ContractContext,
     mut state: ContractState,
     zk_state: ZkState<VariableKind>,
     transfer_data_id: SecretVarId,
 ) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
+    let variable = zk_state.get_variable(transfer_data_id).unwrap();
+    let nonce = match &variable.metadata {
+        VariableKind::TransferRequestData { nonce, .. } => *nonce,
+        _ => panic!(
+            "Variable with id {} is not a transfer request",
+            transfer_data_id.raw_id
+        ),
+    };
     let transfer = TransferData {
-        sender: zk_state.get_variable(transfer_data_id).unwrap().owner,
+        sender: variable.owner,
         transfer_data_id,
+        nonce,
+        approvals: vec![],
     };
 
     let mut zk_state_change = vec![];
+    if state.has_pending_transfer_with_nonce(&transfer.sender, nonce) {
+        state.redundant_variables.push(transfer_data_id);
+        if zk_state.calculation_state == CalculationStatus::Waiting {
+            state.clean_up_redundant_secret_variables(&mut zk_state_change);
+        }
+        let mut event_groups = vec![];
+        fail_safely(
+            &context,
+            &mut event_groups,
+            &format!(
+                "{} already has a pending transfer with nonce {nonce}",
+                transfer.sender
+            ),
+        );
+        return (state, event_groups, zk_state_change);
+    }
+
     state.transfer_variables_to_owner(&zk_state, vec![transfer_data_id], &mut zk_state_change);
 
     state
         .transfers_yet_to_be_approved
         .insert(transfer_data_id.raw_id, transfer);
+    state.pending_transfer_count += 1;
     (state, vec![], zk_state_change)
 }
 
-/// Approve a previously requested transfer, and add it to the work queue.
+/// Approve a previously requested transfer, and add it to the work queue once enough approvers
+/// have approved it.
 ///
 /// Transfers must be created through [`request_transfer`] before they can be approved.
 ///
-/// The transfer is added to the [`ContractState::work_queue`], and started if is the first in the
-/// queue.
+/// When [`ContractState::approval_threshold`] distinct approvers have approved the transfer, it is
+/// added to the [`ContractState::work_queue`], and started if is the first in the queue.
+///
+/// The secret-shared transfer is only owned by the first of the
+/// [`ContractState::transfer_approvers`], as a secret variable has a single owner. The other
+/// approvers cannot inspect the recipient and amount, and must rely on the first approver, or on
+/// information shared outside the contract, when approving.
 #[action(shortname = 0x4B, zk = true)]
 pub fn approve_transfer(
     context: ContractContext,
@@ -487,21 +633,46 @@ pub fn approve_transfer(
     pending_request_id: PendingTransferId,
 ) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
     assert!(
-        context.sender == state.transfer_approver,
-        "Approver is the only user that can approve transfers"
+        state.transfer_approvers.contains(&context.sender),
+        "Only transfer approvers can approve transfers"
     );
 
-    let transfer = match state.transfers_yet_to_be_approved.get(&pending_request_id) {
+    let mut transfer = match state.transfers_yet_to_be_approved.get(&pending_request_id) {
         Some(transfer) => transfer,
         None => panic!("Could not find a pending request with id {pending_request_id}"),
     };
 
+    assert!(
+        !transfer.approvals.contains(&context.sender),
+        "Approver {} has already approved the pending request with id {pending_request_id}",
+        context.sender
+    );
+
+    let mut zk_state_change = vec![];
+    let mut event_groups = vec![];
+    if state.paused {
+        fail_safely(&context, &mut event_groups, PAUSED_MESSAGE);
+        return (state, event_groups, zk_state_change);
+    }
+
+    transfer.approvals.push(context.sender);
+    if transfer.approvals.len() < state.approval_threshold as usize {
+        state
+            .transfers_yet_to_be_approved
+            .insert(pending_request_id, transfer);
+        return (state, event_groups, zk_state_change);
+    }
+
+    if state.is_work_queue_full() {
+        fail_safely(&context, &mut event_groups, WORK_QUEUE_FULL_MESSAGE);
+        return (state, event_groups, zk_state_change);
+    }
+
     state
         .transfers_yet_to_be_approved
         .remove(&pending_request_id);
+    state.pending_transfer_count -= 1;
 
-    let mut zk_state_change = vec![];
-    let mut event_groups = vec![];
     state.schedule_new_work_item(
         &context,
         &zk_state,
@@ -512,6 +683,100 @@ pub fn approve_transfer(
     (state, event_groups, zk_state_change)
 }
 
+/// Cancel a previously requested transfer that has not yet been approved.
+///
+/// Only the original sender of the transfer can cancel it. The secret variable holding the
+/// transfer data is deleted immediately if no computation is running, and otherwise once the
+/// current computation completes.
+#[action(shortname = 0x4D, zk = true)]
+pub fn cancel_transfer_request(
+    context: ContractContext,
+    mut state: ContractState,
+    zk_state: ZkState<VariableKind>,
+    pending_request_id: PendingTransferId,
+) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
+    let transfer = match state.transfers_yet_to_be_approved.get(&pending_request_id) {
+        Some(transfer) => transfer,
+        None => panic!("Could not find a pending request with id {pending_request_id}"),
+    };
+
+    assert!(
+        context.sender == transfer.sender,
+        "Only the sender of the transfer can cancel it"
+    );
+
+    state
+        .transfers_yet_to_be_approved
+        .remove(&pending_request_id);
+    state.pending_transfer_count -= 1;
+    state.redundant_variables.push(transfer.transfer_data_id);
+
+    let mut zk_state_change = vec![];
+    if zk_state.calculation_state == CalculationStatus::Waiting {
+        state.clean_up_redundant_secret_variables(&mut zk_state_change);
+    }
+    (state, vec![], zk_state_change)
+}
+
+/// Change the token contract that this contract operates over, for example when the underlying
+/// token has been migrated to a new address.
+///
+/// Can only be invoked by the first of the [`ContractState::transfer_approvers`], and only while
+/// the contract is idle: no computation may be running, the [`ContractState::work_queue`] must be
+/// empty, there must be no [`ContractState::deposits_in_flight`], and there must be no
+/// [`ContractState::transfers_yet_to_be_approved`]. A deposit in flight has transferred tokens of
+/// the current token, and would otherwise be credited after the change.
+#[action(shortname = 0x4F, zk = true)]
+pub fn set_token_address(
+    context: ContractContext,
+    mut state: ContractState,
+    zk_state: ZkState<VariableKind>,
+    new_token: Address,
+) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
+    assert!(
+        context.sender == state.transfer_approvers[0],
+        "Only the first transfer approver can change the token address"
+    );
+    assert!(
+        state.work_queue.is_empty() && zk_state.calculation_state == CalculationStatus::Waiting,
+        "Cannot change the token address while work is queued"
+    );
+    assert!(
+        state.deposits_in_flight == 0,
+        "Cannot change the token address while deposits are in flight"
+    );
+    assert!(
+        state.transfers_yet_to_be_approved.is_empty(),
+        "Cannot change the token address while transfers are pending approval"
+    );
+
+    state.token_address = new_token;
+    (state, vec![], vec![])
+}
+
+/// Pause or unpause the contract, for example during maintenance.
+///
+/// While paused, new [`deposit()`], [`deposit_many()`], [`withdraw()`], [`create_account()`] and
+/// [`approve_transfer()`] submissions are rejected, but the items already in the
+/// [`ContractState::work_queue`] are still worked through by [`continue_queue`].
+///
+/// Can only be invoked by the first of the [`ContractState::transfer_approvers`].
+#[action(shortname = 0x50, zk = true)]
+pub fn set_paused(
+    context: ContractContext,
+    mut state: ContractState,
+    _zk_state: ZkState<VariableKind>,
+    paused: bool,
+) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
+    assert!(
+        context.sender == state.transfer_approvers[0],
+        "Only the first transfer approver can pause the contract"
+    );
+
+    state.paused = paused;
+    (state, vec![], vec![])
+}
+
 /// Create a new account for the transaction sender.
 ///
 /// The account is required for:
@@ -550,6 +815,8 @@ pub fn create_account(
 ///
 /// The create_account is added to the [`ContractState::work_queue`], and started if is the first in the
 /// queue.
+///
+/// The input is deleted if the contract has been [paused](ContractState::paused).
 #[zk_on_variable_inputted(shortname = 0x42)]
 pub fn create_account_inputted(
     context: ContractContext,
@@ -559,6 +826,15 @@ pub fn create_account_inputted(
 ) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
     let mut zk_state_change = vec![];
     let mut event_groups = vec![];
+    if state.paused {
+        state.redundant_variables.push(account_creation_id);
+        if zk_state.calculation_state == CalculationStatus::Waiting {
+            state.clean_up_redundant_secret_variables(&mut zk_state_change);
+        }
+        fail_safely(&context, &mut event_groups, PAUSED_MESSAGE);
+        return (state, event_groups, zk_state_change);
+    }
+
     state.schedule_new_work_item(
         &context,
         &zk_state,
@@ -584,14 +860,58 @@ pub fn deposit(
     _zk_state: ZkState<VariableKind>,
     token_address: Address,
     amount: TokenAmount,
+) -> (ContractState, Vec<EventGroup>) {
+    transfer_deposit_to_contract(context, state, token_address, amount)
+}
+
+/// Deposit multiple amounts of token into the calling user's balance on the contract.
+///
+/// Equivalent to invoking [`deposit()`] once with the sum of `amounts`. The sum is transferred
+/// with a single `transfer_from`, and [`deposit_callback`] schedules a single
+/// [`WorkListItem::PendingDeposit`] for the sum, rather than one for each amount.
+#[action(shortname = 0x02, zk = true)]
+pub fn deposit_many(
+    context: ContractContext,
+    state: ContractState,
+    _zk_state: ZkState<VariableKind>,
+    token_address: Address,
+    amounts: Vec<TokenAmount>,
+) -> (ContractState, Vec<EventGroup>) {
+    let total_amount = amounts
+        .iter()
+        .try_fold(0, |total: TokenAmount, amount| total.checked_add(*amount))
+        .expect("Sum of deposit amounts overflows");
+    transfer_deposit_to_contract(context, state, token_address, total_amount)
+}
+
+/// Transfers `amount` of tokens from the sender to the contract, and deposits it into the
+/// sender's balance in [`deposit_callback`]. The deposit is counted in
+/// [`ContractState::deposits_in_flight`] until then.
+fn transfer_deposit_to_contract(
+    context: ContractContext,
+    mut state: ContractState,
+    token_address: Address,
+    amount: TokenAmount,
 ) -> (ContractState, Vec<EventGroup>) {
     state.assert_token_contract(token_address);
     assert!(
-        state.has_account(&context.sender),
+        state.account_exists(&context.sender),
         "User does not possess an account: {}. Please create one before depositing!",
         context.sender
     );
 
+    if state.paused {
+        let mut event_groups = vec![];
+        fail_safely(&context, &mut event_groups, PAUSED_MESSAGE);
+        return (state, event_groups);
+    }
+
+    if state.is_work_queue_full() {
+        let mut event_groups = vec![];
+        fail_safely(&context, &mut event_groups, WORK_QUEUE_FULL_MESSAGE);
+        return (state, event_groups);
+    }
+
     let mut event_group_builder = EventGroup::builder();
     interact_mpc20::MPC20Contract::at_address(token_address).transfer_from(
         &mut event_group_builder,
@@ -604,13 +924,16 @@ pub fn deposit(
         .with_callback_rpc(deposit_callback::rpc(context.sender, amount))
         .done();
 
+    state.deposits_in_flight += 1;
     (state, vec![event_group_builder.build()])
 }
 
-/// Handles callback from [`deposit()`].
+/// Handles callback from [`deposit()`] and [`deposit_many()`].
 ///
 /// If the transfer event is successful,
 /// the caller of [`deposit()`] is registered as a user of the contract with (additional) `amount` added to their balance.
+///
+/// Either way, the deposit is no longer counted in [`ContractState::deposits_in_flight`].
 #[callback(shortname = 0x10, zk = true)]
 pub fn deposit_callback(
     context: ContractContext,
@@ -620,10 +943,15 @@ pub fn deposit_callback(
     account: Address,
     amount: TokenAmount,
 ) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
-    assert!(callback_context.success, "Transfer did not succeed");
+    state.deposits_in_flight -= 1;
 
     let mut zk_state_change = vec![];
     let mut event_groups = vec![];
+    if !callback_context.success {
+        fail_safely(&context, &mut event_groups, "Transfer did not succeed");
+        return (state, event_groups, zk_state_change);
+    }
+
     state.schedule_new_work_item(
         &context,
         &zk_state,
@@ -723,6 +1051,16 @@ pub fn withdraw(
 
     let mut zk_state_change = vec![];
     let mut event_groups = vec![];
+    if state.paused {
+        fail_safely(&context, &mut event_groups, PAUSED_MESSAGE);
+        return (state, event_groups, zk_state_change);
+    }
+
+    if state.is_work_queue_full() {
+        fail_safely(&context, &mut event_groups, WORK_QUEUE_FULL_MESSAGE);
+        return (state, event_groups, zk_state_change);
+    }
+
     state.schedule_new_work_item(
         &context,
         &zk_state,
@@ -789,6 +1127,12 @@ pub fn withdraw_result_opened(
     // Check that deposit with successful
     let mut event_groups = vec![];
     if !result.successful {
+        emit_insufficient_balance_event(
+            &context,
+            &mut event_groups,
+            result_variable.owner,
+            result.amount as TokenAmount,
+        );
         fail_safely(
             &context,
             &mut event_groups,
@@ -813,6 +1157,44 @@ pub fn withdraw_result_opened(
     (state, event_groups, zk_state_change)
 }
 
+/// Event indicating that a withdraw failed, because the account did not have enough tokens
+/// deposited.
+///
+/// Can only be sent by the contract itself, from [`emit_insufficient_balance_event`].
+#[action(shortname = 0x4E, zk = true)]
+pub fn insufficient_balance_event(
+    context: ContractContext,
+    state: ContractState,
+    _zk_state: ZkState<VariableKind>,
+    _account: Address,
+    _amount: TokenAmount,
+) -> (ContractState, Vec<EventGroup>, Vec<ZkStateChange>) {
+    events::assert_event_sent_by_contract(
+        &context,
+        "This is an internal invocation. Must not be invoked by outside users.",
+    );
+    (state, vec![], vec![])
+}
+
+/// Notifies clients that the withdraw of `amount` for `account` failed due to insufficient
+/// balance, by spawning an [`insufficient_balance_event`].
+fn emit_insufficient_balance_event(
+    context: &ContractContext,
+    event_groups: &mut Vec<EventGroup>,
+    account: Address,
+    amount: TokenAmount,
+) {
+    let mut event_group_builder = EventGroup::builder();
+    event_group_builder
+        .call(context.contract_address, Shortname::from_u32(0x09)) // Public invocation prefix
+        .argument(insufficient_balance_event::SHORTNAME) // Shortname
+        .argument(account)
+        .argument(amount)
+        .done();
+
+    event_groups.push(event_group_builder.build());
+}
+
 /// Reads a [`zk_compute::ComputationResultPub`] from the opened secret-shared data of the given
 /// variable.
 fn read_result(result_variable: &ZkClosed<VariableKind>) -> zk_compute::ComputationResultPub {
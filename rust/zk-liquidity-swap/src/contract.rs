@@ -5,7 +5,7 @@ extern crate pbc_contract_codegen;
 
 use create_type_spec_derive::CreateTypeSpec;
 use defi_common::interact_mpc20::MPC20Contract;
-use defi_common::math::u128_division_ceil;
+use defi_common::math::{mul_div, u128_division_ceil, u128_sqrt};
 use defi_common::token_balances::{
     DepositToken, TokenAmount, TokenBalance, TokenBalances, TokensInOut,
 };
@@ -19,6 +19,9 @@ use read_write_rpc_derive::{ReadRPC, WriteRPC};
 use read_write_state_derive::ReadWriteState;
 use std::collections::VecDeque;
 
+#[cfg(test)]
+mod tests;
+
 /**
  * Metadata information associated with each individual variable.
  */
@@ -28,7 +31,17 @@ pub struct SecretVarMetadata {
     is_output_variable: bool,
     /// If an swap input variable is marked with this, it means that the swap should only be
     /// performed if the swap is the first element of the worklist queue.
+    ///
+    /// Checked when the variable has been fully input: if any other swap is queued or being
+    /// evaluated at that point, the swap is dropped without touching any balances. Otherwise the
+    /// swap becomes the head of the worklist, and is evaluated before any later swap.
     only_if_at_front: bool,
+    /// Minimum amount of output tokens that the swap must produce. The swap is not performed if
+    /// it would produce less.
+    min_out: TokenSwapAmount,
+    /// Whether the swap is a two-hop swap between token A and token C through the
+    /// [`SecondPool`], rather than a swap between token A and B.
+    is_two_hop: bool,
 }
 
 /// This is the state of the contract which is persisted on the chain.
@@ -48,8 +61,35 @@ pub struct ContractState {
     pub token_balances: TokenBalances,
     /// Worklist queue containing swaps that have yet to be performed.
     pub worklist: VecDeque<WorklistEntry>,
+    /// The maximum part, in per mille, of the output pool that a single swap may output.
+    pub max_swap_output_per_mille: u16,
+    /// The optional second pool, trading token B against token C, which enables two-hop swaps
+    /// between token A and token C.
+    pub second_pool: Option<SecondPool>,
+}
+
+/// A second pool, trading token B of the first pool against a token C. Together with the first
+/// pool it allows two-hop swaps between token A and token C, through token B.
+#[derive(Debug, ReadWriteState, CreateTypeSpec)]
+pub struct SecondPool {
+    /// Balances of the second pool, where token B of the first pool takes the place of token A,
+    /// and token C takes the place of token B. See [`SECOND_POOL_TOKEN_B`] and
+    /// [`SECOND_POOL_TOKEN_C`].
+    ///
+    /// Users only hold token C in these balances, while their token B is held in
+    /// [`ContractState::token_balances`].
+    pub token_balances: TokenBalances,
+    /// The invariant used to calculate exchange rates of the second pool, based on the
+    /// 'constant product formula'.
+    pub swap_constant: u128,
 }
 
+/// Token B of the first pool, in the balances of the [`SecondPool`].
+pub const SECOND_POOL_TOKEN_B: DepositToken = DepositToken::A;
+
+/// Token C, in the balances of the [`SecondPool`].
+pub const SECOND_POOL_TOKEN_C: DepositToken = DepositToken::B;
+
 /// An entry in the worklist, including the id of the variable containing the swap information, and
 /// the address of the sender of the variable.
 #[derive(Debug, ReadWriteState, CreateTypeSpec, Clone, PartialEq, Eq)]
@@ -58,6 +98,10 @@ pub struct WorklistEntry {
     variable_id: SecretVarId,
     /// Who sent the swap.
     sender: Address,
+    /// Minimum amount of output tokens that the swap must produce.
+    min_out: TokenSwapAmount,
+    /// Whether the swap is a two-hop swap between token A and token C.
+    is_two_hop: bool,
 }
 
 impl ContractState {
@@ -67,6 +111,53 @@ impl ContractState {
             .get_balance_for(&self.liquidity_pool_address)
     }
 
+    /// Retrieves the [`SecondPool`]. Fails if the contract has no second pool.
+    fn second_pool(&self) -> &SecondPool {
+        self.second_pool
+            .as_ref()
+            .expect("The contract has no second pool")
+    }
+
+    /// Retrieves a copy of the pools of the [`SecondPool`]. Fails if the contract has no second
+    /// pool.
+    fn get_second_pools(&self) -> TokenBalance {
+        self.second_pool()
+            .token_balances
+            .get_balance_for(&self.liquidity_pool_address)
+    }
+
+    /// Checks that the contract has a [`SecondPool`] with liquidity.
+    fn second_pool_has_liquidity(&self) -> bool {
+        self.second_pool.as_ref().is_some_and(|second_pool| {
+            let pools = second_pool
+                .token_balances
+                .get_balance_for(&self.liquidity_pool_address);
+            pools.a_tokens != 0 && pools.b_tokens != 0
+        })
+    }
+
+    /// Finds the balances holding the token at `token_address`, and the token within them. Token
+    /// C is held in the balances of the [`SecondPool`], while token A and B are held in
+    /// [`ContractState::token_balances`].
+    ///
+    /// Fails if the token is not traded by the pools of the contract.
+    fn balances_for_token(&mut self, token_address: Address) -> (&mut TokenBalances, DepositToken) {
+        let is_token_c = self
+            .second_pool
+            .as_ref()
+            .is_some_and(|second_pool| second_pool.token_balances.token_b_address == token_address);
+        if is_token_c {
+            let second_pool = self.second_pool.as_mut().unwrap();
+            (&mut second_pool.token_balances, SECOND_POOL_TOKEN_C)
+        } else {
+            let token = self
+                .token_balances
+                .deduce_tokens_in_out(token_address)
+                .token_in;
+            (&mut self.token_balances, token)
+        }
+    }
+
     /// Checks for common invariants.
     fn assert_invariants(&self) {
         let pools = self.get_pools();
@@ -74,6 +165,13 @@ impl ContractState {
             pools.get_amount_of(DepositToken::A) * pools.get_amount_of(DepositToken::B)
                 >= self.swap_constant
         );
+        if let Some(second_pool) = &self.second_pool {
+            let pools = self.get_second_pools();
+            assert!(
+                pools.get_amount_of(SECOND_POOL_TOKEN_B) * pools.get_amount_of(SECOND_POOL_TOKEN_C)
+                    >= second_pool.swap_constant
+            );
+        }
     }
 
     /// Checks that the pools of the contracts have liquidity.
@@ -90,6 +188,127 @@ impl ContractState {
             .get_balance_for(&self.liquidity_pool_address);
         contract_token_balance.a_tokens != 0 && contract_token_balance.b_tokens != 0
     }
+
+    /// Checks that a swap outputting `amount_out` of `token_out` does not drain more than
+    /// [`ContractState::max_swap_output_per_mille`] of the output pool, such that a swap can never
+    /// drive a pool close to zero.
+    ///
+    /// ### Parameters:
+    ///
+    ///  * `token_out`: [`DepositToken`] - The token output by the swap.
+    ///
+    ///  * `amount_out`: [`TokenSwapAmount`] - The amount of tokens output by the swap.
+    fn assert_swap_output_within_limit(
+        &self,
+        token_out: DepositToken,
+        amount_out: TokenSwapAmount,
+    ) {
+        self.assert_pool_output_within_limit(self.get_pools().get_amount_of(token_out), amount_out);
+    }
+
+    /// Checks that a swap outputting `amount_out` from an output pool of `pool_out` tokens does
+    /// not drain more than [`ContractState::max_swap_output_per_mille`] of the pool.
+    ///
+    /// ### Parameters:
+    ///
+    ///  * `pool_out`: [`TokenAmount`] - The amount of tokens in the output pool.
+    ///
+    ///  * `amount_out`: [`TokenSwapAmount`] - The amount of tokens output by the swap.
+    fn assert_pool_output_within_limit(&self, pool_out: TokenAmount, amount_out: TokenSwapAmount) {
+        let per_mille = TokenSwapAmount::from(self.max_swap_output_per_mille);
+        let max_amount_out = pool_out / 1000 * per_mille + pool_out % 1000 * per_mille / 1000;
+        assert!(
+            amount_out <= max_amount_out,
+            "Swap output {} exceeds the maximum output {} of the pool",
+            amount_out,
+            max_amount_out,
+        );
+    }
+
+    /// Computes the output of a single hop of a two-hop swap, swapping `amount_in` of
+    /// `tokens.token_in` to `tokens.token_out` in the given pools, and checks that the output is
+    /// within [`ContractState::max_swap_output_per_mille`] of the output pool.
+    ///
+    /// ### Parameters:
+    ///
+    ///  * `pools`: [`TokenBalance`] - The pools to swap in.
+    ///
+    ///  * `swap_constant`: [`u128`] - The swap constant of the pools.
+    ///
+    ///  * `tokens`: [`TokensInOut`] - The input and output tokens of the hop.
+    ///
+    ///  * `amount_in`: [`TokenSwapAmount`] - The amount of tokens input to the hop.
+    ///
+    /// ### Returns:
+    /// The amount of tokens output by the hop [`TokenSwapAmount`]
+    fn calculate_hop_output(
+        &self,
+        pools: &TokenBalance,
+        swap_constant: u128,
+        tokens: TokensInOut,
+        amount_in: TokenSwapAmount,
+    ) -> TokenSwapAmount {
+        let pool_out = pools.get_amount_of(tokens.token_out);
+        let amount_out = calculate_swap_to_amount(
+            pools.get_amount_of(tokens.token_in),
+            pool_out,
+            swap_constant,
+            amount_in,
+        )
+        .unwrap();
+        self.assert_pool_output_within_limit(pool_out, amount_out);
+        amount_out
+    }
+
+    /// Marginal price of a single unit of token A in units of token B, scaled by
+    /// [`SPOT_PRICE_PRECISION`]. Computed from the public pool reserves as
+    ///
+    /// `pool_b * SPOT_PRICE_PRECISION / pool_a`
+    ///
+    /// rounded down. Reveals no secret data, as the pool reserves are public.
+    ///
+    /// ### Returns:
+    /// The scaled spot price, or 0 if the pools have no liquidity [`u128`]
+    pub fn spot_price_a_in_b(&self) -> u128 {
+        let pools = self.get_pools();
+        scaled_price(
+            pools.get_amount_of(DepositToken::B),
+            pools.get_amount_of(DepositToken::A),
+        )
+    }
+
+    /// Marginal price of a single unit of token B in units of token A, scaled by
+    /// [`SPOT_PRICE_PRECISION`]. Inverse of [`ContractState::spot_price_a_in_b`].
+    ///
+    /// ### Returns:
+    /// The scaled spot price, or 0 if the pools have no liquidity [`u128`]
+    pub fn spot_price_b_in_a(&self) -> u128 {
+        let pools = self.get_pools();
+        scaled_price(
+            pools.get_amount_of(DepositToken::A),
+            pools.get_amount_of(DepositToken::B),
+        )
+    }
+}
+
+/// Scaling factor of the spot prices reported by [`ContractState::spot_price_a_in_b`] and
+/// [`ContractState::spot_price_b_in_a`]. A spot price of `SPOT_PRICE_PRECISION` is a price of 1.
+pub const SPOT_PRICE_PRECISION: u128 = 1_000_000_000;
+
+/// Computes `numerator_pool * SPOT_PRICE_PRECISION / denominator_pool`, or 0 if either pool is
+/// empty.
+fn scaled_price(mut numerator_pool: u128, mut denominator_pool: u128) -> u128 {
+    if numerator_pool == 0 || denominator_pool == 0 {
+        return 0;
+    }
+
+    // Scale down the pools, such that the computation below cannot overflow.
+    while numerator_pool > u128::MAX / SPOT_PRICE_PRECISION {
+        numerator_pool >>= 1;
+        denominator_pool >>= 1;
+    }
+
+    numerator_pool * SPOT_PRICE_PRECISION / denominator_pool.max(1)
 }
 
 /// Initialize the contract.
@@ -100,6 +319,11 @@ impl ContractState {
 ///
 ///   * `token_b_address`: The address of token B.
 ///
+///   * `swap_fee_per_mille`: The fee of swaps. Must be zero.
+///
+///   * `max_swap_output_per_mille`: The maximum part, in per mille, of the output pool that a
+///     single swap may output. Must be positive and at most 1000.
+///
 /// ### Returns
 ///
 /// The new state object of type [`ContractState`] with all address fields initialized to their final state and remaining fields initialized to a default value.
@@ -111,8 +335,14 @@ pub fn initialize(
     token_a_address: Address,
     token_b_address: Address,
     swap_fee_per_mille: u16,
+    max_swap_output_per_mille: u16,
 ) -> (ContractState, Vec<EventGroup>) {
     assert_eq!(swap_fee_per_mille, 0, "Non-zero swap fee not implemented");
+    assert!(
+        0 < max_swap_output_per_mille && max_swap_output_per_mille <= 1000,
+        "Maximum swap output must be between 1 and 1000 per mille, but was {}",
+        max_swap_output_per_mille,
+    );
     let liquidity_pool_address = context.contract_address;
     let token_balances =
         TokenBalances::new(liquidity_pool_address, token_a_address, token_b_address).unwrap();
@@ -123,6 +353,8 @@ pub fn initialize(
         swap_constant: 0,
         token_balances,
         worklist: VecDeque::new(),
+        max_swap_output_per_mille,
+        second_pool: None,
     };
 
     (new_state, vec![])
@@ -131,6 +363,9 @@ pub fn initialize(
 /// Initialize pool {a, b} of the contract.
 /// This can only be done by the contract owner and the contract has to be in its closed state.
 ///
+/// [`MINIMUM_LIQUIDITY`] of the minted liquidity tokens are locked in the pools, and the owner
+/// receives the rest.
+///
 /// ### Parameters:
 ///
 ///  * `token_address`: The address of the token {a, b}.
@@ -156,20 +391,383 @@ pub fn provide_initial_liquidity(
         "Can only initialize when both pools are empty"
     );
 
+    provide_initial_liquidity_internal(&mut state, &context.sender, token_a_amount, token_b_amount);
+
+    assert!(
+        state.contract_pools_have_liquidity(),
+        "Contract pools should have been initialized after calling provide_initial_liquidity."
+    );
+
+    (state, vec![])
+}
+
+/// Add a second pool to the contract, trading token B against `token_c_address`. This enables
+/// two-hop swaps between token A and token C using [`swap_two_hop()`].
+/// This can only be done by the contract owner, and only once.
+///
+/// ### Parameters:
+///
+///  * `token_c_address`: The address of token C.
+///
+/// # Returns
+/// The updated state object of type [`ContractState`] with an empty second pool.
+#[action(shortname = 0x0A, zk = true)]
+pub fn add_second_pool(
+    context: ContractContext,
+    mut state: ContractState,
+    _zk_state: ZkState<SecretVarMetadata>,
+    token_c_address: Address,
+) -> (ContractState, Vec<EventGroup>) {
+    assert_eq!(
+        context.sender, state.contract_owner,
+        "Only the contract owner can add a second pool"
+    );
+    assert!(
+        state.second_pool.is_none(),
+        "The contract already has a second pool"
+    );
+    assert_ne!(
+        token_c_address, state.token_balances.token_a_address,
+        "Token C must not be token A"
+    );
+
+    let token_balances = TokenBalances::new(
+        state.liquidity_pool_address,
+        state.token_balances.token_b_address,
+        token_c_address,
+    )
+    .unwrap();
+    state.second_pool = Some(SecondPool {
+        token_balances,
+        swap_constant: 0,
+    });
+
+    (state, vec![])
+}
+
+/// Initialize the second pool of the contract, by moving token B and token C from the owner's
+/// balance into the second pool. This can only be done by the contract owner, and the second pool
+/// has to be empty. The liquidity of the second pool is returned to the owner by
+/// [`close_pools()`].
+///
+/// ### Parameters:
+///
+///  * `token_b_amount`: The amount of token B to move into the second pool.
+///
+///  * `token_c_amount`: The amount of token C to move into the second pool.
+///
+/// # Returns
+/// The updated state object of type [`ContractState`].
+#[action(shortname = 0x0B, zk = true)]
+pub fn provide_initial_liquidity_second_pool(
+    context: ContractContext,
+    mut state: ContractState,
+    _zk_state: ZkState<SecretVarMetadata>,
+    token_b_amount: TokenAmount,
+    token_c_amount: TokenAmount,
+) -> (ContractState, Vec<EventGroup>) {
+    assert_eq!(
+        context.sender, state.contract_owner,
+        "Only the contract owner can initialize contract pools"
+    );
+    let second_pools = state.get_second_pools();
+    assert!(
+        second_pools.a_tokens == 0 && second_pools.b_tokens == 0,
+        "Can only initialize when the second pool is empty"
+    );
+
+    provide_initial_liquidity_second_pool_internal(
+        &mut state,
+        &context.sender,
+        token_b_amount,
+        token_c_amount,
+    );
+
+    assert!(
+        state.second_pool_has_liquidity(),
+        "The second pool should have been initialized after calling provide_initial_liquidity_second_pool."
+    );
+
+    (state, vec![])
+}
+
+/// Moves token B from the owner's balance in [`ContractState::token_balances`], and token C from
+/// the owner's balance in the [`SecondPool`], into the second pool, and sets the swap constant of
+/// the second pool.
+///
+/// ### Parameters:
+///
+///  * `state`: [`ContractState`] - The current state of the contract.
+///
+///  * `user`: [`Address`] - The address of the owner providing the liquidity.
+///
+///  * `token_b_amount`: [`TokenAmount`] - The amount of token B to move into the second pool.
+///
+///  * `token_c_amount`: [`TokenAmount`] - The amount of token C to move into the second pool.
+fn provide_initial_liquidity_second_pool_internal(
+    state: &mut ContractState,
+    user: &Address,
+    token_b_amount: TokenAmount,
+    token_c_amount: TokenAmount,
+) {
+    let pool_address = state.liquidity_pool_address;
+    state
+        .token_balances
+        .deduct_from_token_balance(*user, DepositToken::B, token_b_amount);
+
+    let second_pool = state.second_pool.as_mut().unwrap();
+    second_pool.token_balances.add_to_token_balance(
+        pool_address,
+        SECOND_POOL_TOKEN_B,
+        token_b_amount,
+    );
+    second_pool.token_balances.move_tokens(
+        *user,
+        pool_address,
+        SECOND_POOL_TOKEN_C,
+        token_c_amount,
+    );
+
+    let pools = state.get_second_pools();
+    state.second_pool.as_mut().unwrap().swap_constant =
+        pools.a_tokens.checked_mul(pools.b_tokens).unwrap();
+}
+
+/// Amount of liquidity tokens that are minted to the pools themselves when the pools are
+/// initialized, and are locked while other liquidity tokens exist. This prevents any single
+/// liquidity provider from owning all liquidity tokens, which would allow them to inflate the
+/// value of each liquidity token at the expense of later providers, due to rounding of the minted
+/// amounts. The locked liquidity tokens are only burned when the pools are emptied, by
+/// [`remove_liquidity()`] of the last liquidity provider or by [`close_pools()`].
+pub const MINIMUM_LIQUIDITY: TokenAmount = 100;
+
+/// The amount of liquidity tokens held by liquidity providers, i.e. all liquidity tokens of the
+/// open `pools` except the locked [`MINIMUM_LIQUIDITY`].
+fn unlocked_liquidity_tokens(pools: &TokenBalance) -> TokenAmount {
+    pools.liquidity_tokens - MINIMUM_LIQUIDITY
+}
+
+/// Maximum allowed deviation, in per mille, between the ratio of the amounts given to
+/// [`provide_liquidity()`] and the current ratio of the pools.
+pub const LIQUIDITY_RATIO_TOLERANCE_PER_MILLE: u128 = 10;
+
+/// Provide liquidity to the open pools, minting liquidity tokens for the calling user.
+/// Can be called by any user once the pools have been initialized.
+///
+/// The amounts must match the current ratio of the pools within
+/// [`LIQUIDITY_RATIO_TOLERANCE_PER_MILLE`]. The minted liquidity tokens are based on the least
+/// valuable of the two amounts, such that deviating from the ratio never benefits the provider.
+///
+/// ### Parameters:
+///
+///  * `token_a_amount`: The amount of token A to move from the user's balance into the pool.
+///
+///  * `token_b_amount`: The amount of token B to move from the user's balance into the pool.
+///
+/// # Returns
+/// The updated state object of type [`ContractState`].
+#[action(shortname = 0x04, zk = true)]
+pub fn provide_liquidity(
+    context: ContractContext,
+    mut state: ContractState,
+    _zk_state: ZkState<SecretVarMetadata>,
+    token_a_amount: TokenAmount,
+    token_b_amount: TokenAmount,
+) -> (ContractState, Vec<EventGroup>) {
+    assert!(
+        state.contract_pools_have_liquidity(),
+        "The contract is closed"
+    );
+
+    let pools = state.get_pools();
+    let expected_b_amount = mul_div(token_a_amount, pools.b_tokens, pools.a_tokens);
+    assert!(
+        expected_b_amount.abs_diff(token_b_amount)
+            <= mul_div(expected_b_amount, LIQUIDITY_RATIO_TOLERANCE_PER_MILLE, 1000),
+        "Provided amounts do not match the pool ratio. Expected approximately {} of token B for {} of token A, but got {}",
+        expected_b_amount,
+        token_a_amount,
+        token_b_amount,
+    );
+
+    let minted_liquidity_tokens =
+        calculate_minted_liquidity_tokens(token_a_amount, token_b_amount, &pools);
+    assert!(
+        minted_liquidity_tokens > 0,
+        "The given input amount yielded 0 minted liquidity"
+    );
+
     provide_liquidity_internal(
         &mut state,
         &context.sender,
         TokensInOut::A_IN_B_OUT,
         token_a_amount,
         token_b_amount,
+        minted_liquidity_tokens,
     );
 
+    (state, vec![])
+}
+
+/// Reclaim the calling user's share of the pools by burning `liquidity_token_amount` of their
+/// liquidity tokens. This is the inverse of [`provide_liquidity()`].
+///
+/// The user receives token A and B in proportion to their share of the total minted liquidity,
+/// including any value accrued in the pools from swaps.
+///
+/// When the user burns all remaining liquidity tokens besides the locked [`MINIMUM_LIQUIDITY`],
+/// the locked liquidity tokens are burned as well, and the user receives the entire pools, leaving
+/// the contract closed. This is not the case while the [`SecondPool`] has liquidity, as the
+/// contract owner must [`close_pools()`] to reclaim it.
+///
+/// ### Parameters:
+///
+///  * `liquidity_token_amount`: The amount of liquidity tokens to burn.
+///
+/// # Returns
+/// The updated state object of type [`ContractState`].
+#[action(shortname = 0x07, zk = true)]
+pub fn remove_liquidity(
+    context: ContractContext,
+    mut state: ContractState,
+    _zk_state: ZkState<SecretVarMetadata>,
+    liquidity_token_amount: TokenAmount,
+) -> (ContractState, Vec<EventGroup>) {
+    remove_liquidity_internal(&mut state, &context.sender, liquidity_token_amount);
+
+    (state, vec![])
+}
+
+/// Burns the liquidity tokens of the user and moves their share of the pools to the user's
+/// balance. Burns the locked [`MINIMUM_LIQUIDITY`] as well if the user is the last liquidity
+/// provider, see [`remove_liquidity()`].
+///
+/// ### Parameters:
+///
+///  * `state`: [`ContractState`] - The current state of the contract.
+///
+///  * `user`: [`Address`] - The address of the user removing liquidity.
+///
+///  * `liquidity_token_amount`: [`TokenAmount`] - The amount of liquidity tokens to burn.
+fn remove_liquidity_internal(
+    state: &mut ContractState,
+    user: &Address,
+    liquidity_token_amount: TokenAmount,
+) {
+    state.token_balances.deduct_from_token_balance(
+        *user,
+        DepositToken::LIQUIDITY,
+        liquidity_token_amount,
+    );
+
+    let pools = state.get_pools();
+    let drains_pools = liquidity_token_amount == unlocked_liquidity_tokens(&pools)
+        && !state.second_pool_has_liquidity();
+    let burned_liquidity_tokens = if drains_pools {
+        pools.liquidity_tokens
+    } else {
+        liquidity_token_amount
+    };
+    let (a_output, b_output) = calculate_reclaim_output(burned_liquidity_tokens, &pools);
+
+    state.token_balances.move_tokens(
+        state.liquidity_pool_address,
+        *user,
+        DepositToken::A,
+        a_output,
+    );
+    state.token_balances.move_tokens(
+        state.liquidity_pool_address,
+        *user,
+        DepositToken::B,
+        b_output,
+    );
+    state.token_balances.deduct_from_token_balance(
+        state.liquidity_pool_address,
+        DepositToken::LIQUIDITY,
+        burned_liquidity_tokens,
+    );
+
+    // Update the swap constant.
+    let pools = state.get_pools();
+    state.swap_constant = pools.a_tokens.checked_mul(pools.b_tokens).unwrap();
+}
+
+/// Calculates the amount of token A and B that the given amount of liquidity tokens correspond
+/// to during [`remove_liquidity()`]. Due to integer rounding, a user may receive slightly less
+/// than expected, except when burning all liquidity tokens.
+pub fn calculate_reclaim_output(
+    liquidity_token_amount: TokenAmount,
+    pools: &TokenBalance,
+) -> (TokenAmount, TokenAmount) {
+    let a_output = mul_div(
+        pools.a_tokens,
+        liquidity_token_amount,
+        pools.liquidity_tokens,
+    );
+    let b_output = mul_div(
+        pools.b_tokens,
+        liquidity_token_amount,
+        pools.liquidity_tokens,
+    );
+    (a_output, b_output)
+}
+
+/// Calculates the amount of liquidity tokens to mint for providing the given amounts to the given
+/// pools. The smallest share of the two amounts determines the minted amount.
+pub fn calculate_minted_liquidity_tokens(
+    token_a_amount: TokenAmount,
+    token_b_amount: TokenAmount,
+    pools: &TokenBalance,
+) -> TokenAmount {
+    let minted_from_a = mul_div(token_a_amount, pools.liquidity_tokens, pools.a_tokens);
+    let minted_from_b = mul_div(token_b_amount, pools.liquidity_tokens, pools.b_tokens);
+    minted_from_a.min(minted_from_b)
+}
+
+/// Moves the initial liquidity from the owner's balance to the pools, and mints liquidity tokens
+/// based on the geometric mean of the amounts. [`MINIMUM_LIQUIDITY`] of the minted liquidity
+/// tokens are minted to the pools themselves, and the rest to the owner.
+///
+/// ### Parameters:
+///
+///  * `state`: [`ContractState`] - The current state of the contract.
+///
+///  * `user`: [`Address`] - The address of the owner providing the initial liquidity.
+///
+///  * `token_a_amount`: [`TokenAmount`] - The amount of token A to move into the pool.
+///
+///  * `token_b_amount`: [`TokenAmount`] - The amount of token B to move into the pool.
+fn provide_initial_liquidity_internal(
+    state: &mut ContractState,
+    user: &Address,
+    token_a_amount: TokenAmount,
+    token_b_amount: TokenAmount,
+) {
+    let minted_liquidity_tokens: TokenAmount = token_a_amount
+        .checked_mul(token_b_amount)
+        .map(|product| u128_sqrt(product).into())
+        .unwrap();
     assert!(
-        state.contract_pools_have_liquidity(),
-        "Contract pools should have been initialized after calling provide_initial_liquidity."
+        minted_liquidity_tokens > MINIMUM_LIQUIDITY,
+        "Initial liquidity must mint more than {} liquidity tokens, but would mint {}",
+        MINIMUM_LIQUIDITY,
+        minted_liquidity_tokens,
     );
 
-    (state, vec![])
+    provide_liquidity_internal(
+        state,
+        user,
+        TokensInOut::A_IN_B_OUT,
+        token_a_amount,
+        token_b_amount,
+        minted_liquidity_tokens - MINIMUM_LIQUIDITY,
+    );
+    state.token_balances.add_to_token_balance(
+        state.liquidity_pool_address,
+        DepositToken::LIQUIDITY,
+        MINIMUM_LIQUIDITY,
+    );
 }
 
 /// Moves tokens from the providing user's balance to the contract's and mints liquidity tokens.
@@ -185,12 +783,15 @@ pub fn provide_initial_liquidity(
 ///  * `token_in_amount`: [`TokenAmount`] - The input token amount.
 ///
 ///  * `token_out_amount`: [`TokenAmount`] - The output token amount. Must be equal value to `token_in_amount` at the current exchange rate.
+///
+///  * `minted_liquidity_tokens`: [`TokenAmount`] - The amount of liquidity tokens that the input tokens yields.
 fn provide_liquidity_internal(
     state: &mut ContractState,
     user: &Address,
     tokens: TokensInOut,
     token_in_amount: TokenAmount,
     token_out_amount: TokenAmount,
+    minted_liquidity_tokens: TokenAmount,
 ) {
     state.token_balances.move_tokens(
         *user,
@@ -205,11 +806,24 @@ fn provide_liquidity_internal(
         token_out_amount,
     );
 
+    state.token_balances.add_to_token_balance(
+        *user,
+        DepositToken::LIQUIDITY,
+        minted_liquidity_tokens,
+    );
+    state.token_balances.add_to_token_balance(
+        state.liquidity_pool_address,
+        DepositToken::LIQUIDITY,
+        minted_liquidity_tokens,
+    );
+
     // Set the swap constant.
-    state.swap_constant = token_in_amount.checked_mul(token_out_amount).unwrap();
+    let pools = state.get_pools();
+    state.swap_constant = pools.a_tokens.checked_mul(pools.b_tokens).unwrap();
 }
 
-/// Deposit token A or B into the calling users balance on the contract.
+/// Deposit token A, B or C into the calling users balance on the contract. Token C can only be
+/// deposited once a [`SecondPool`] has been added.
 /// If the contract is closed, the action fails.
 ///
 /// Requires that the swap contract has been approved at `token_address`
@@ -227,15 +841,13 @@ fn provide_liquidity_internal(
 #[action(shortname = 0x01, zk = true)]
 pub fn deposit(
     context: ContractContext,
-    state: ContractState,
+    mut state: ContractState,
     _zk_state: ZkState<SecretVarMetadata>,
     token_address: Address,
     amount: TokenAmount,
 ) -> (ContractState, Vec<EventGroup>) {
-    let from_token = state
-        .token_balances
-        .deduce_tokens_in_out(token_address)
-        .token_in;
+    // Fails if the token is not traded by the pools of the contract.
+    state.balances_for_token(token_address);
     let mut event_group_builder = EventGroup::builder();
 
     MPC20Contract::at_address(token_address).transfer_from(
@@ -246,21 +858,21 @@ pub fn deposit(
     );
 
     event_group_builder
-        .with_callback_rpc(deposit_callback::rpc(from_token, amount))
+        .with_callback_rpc(deposit_callback::rpc(token_address, amount))
         .done();
 
     (state, vec![event_group_builder.build()])
 }
 
 /// Handles callback from `deposit`.
-/// If the transfer event is successful the caller of `deposit` is added to the `state.token_balances`
-/// adding `amount` to the `token` pool balance.
+/// If the transfer event is successful the caller of `deposit` is added to the balances holding
+/// the token, adding `amount` to the balance of the token.
 ///
 /// ### Parameters:
 ///
-/// * `token`: Indicating the token pool balance of which to add `amount` to.
+/// * `token_address`: The address of the deposited token contract.
 ///
-/// * `amount`: The desired amount to add to `token_type` pool balance.
+/// * `amount`: The desired amount to add to the balance of the token.
 ///
 /// ### Returns
 ///
@@ -271,13 +883,12 @@ pub fn deposit_callback(
     callback_context: CallbackContext,
     mut state: ContractState,
     _zk_state: ZkState<SecretVarMetadata>,
-    token: DepositToken,
+    token_address: Address,
     amount: TokenAmount,
 ) -> (ContractState, Vec<EventGroup>) {
     assert!(callback_context.success, "Transfer did not succeed");
-    state
-        .token_balances
-        .add_to_token_balance(context.sender, token, amount);
+    let (token_balances, token) = state.balances_for_token(token_address);
+    token_balances.add_to_token_balance(context.sender, token, amount);
     (state, vec![])
 }
 
@@ -291,6 +902,13 @@ pub fn deposit_callback(
 ///  is the first in the worklist queue. This feature can be used to prevent frontrunning between
 ///  the time when this invocation is called, and when the swap variable is fully input.
 ///
+///  * `min_out`: The minimum amount of output tokens that the swap must produce. If the swap
+///  would produce less when it is executed, it fails, and the input tokens stay in the user's
+///  balance. `min_out` is public, and reveals a lower bound on the secret `amount`, and usually
+///  also the swap direction, as it is typically chosen close to the expected output. Users
+///  wanting to hide more of their swap can use a lower `min_out`, at the cost of a weaker
+///  slippage bound.
+///
 ///  * `amount` (ZK): The amount to swap of the token matching `input_token`.
 ///
 /// # Returns
@@ -301,6 +919,7 @@ pub fn swap(
     state: ContractState,
     _zk_state: ZkState<SecretVarMetadata>,
     only_if_at_front: bool,
+    min_out: TokenSwapAmount,
 ) -> (
     ContractState,
     Vec<EventGroup>,
@@ -324,6 +943,80 @@ pub fn swap(
         SecretVarMetadata {
             only_if_at_front,
             is_output_variable: false,
+            min_out,
+            is_two_hop: false,
+        },
+    );
+    (state, vec![], input_def)
+}
+
+/// Swap `amount` of token A to token C, or of token C to token A, through token B, by swapping in
+/// the first pool and the [`SecondPool`] one after the other. The two hops are performed
+/// atomically at the exchange rates dictated by the `constant product formula` of each pool, and
+/// produce the same output as two sequential single-hop swaps on the same reserves.
+/// The swap is executed on the user balances of tokens for the calling user.
+/// If either pool is closed or if the caller does not have a sufficient balance of the token, the
+/// action fails.
+///
+/// ### Parameters:
+///
+///  * `only_if_at_front`: If true, the swap will only be performed if the swap variable
+///  is the first in the worklist queue. See [`swap()`].
+///
+///  * `min_out`: The minimum amount of output tokens that the swap must produce. If the swap
+///  would produce less when it is executed, it fails, and the input tokens stay in the user's
+///  balance. Like for [`swap()`], the public `min_out` reveals a lower bound on the secret
+///  `amount`.
+///
+///  * `amount` (ZK): The amount to swap. The direction is from token A to token C if the lowest
+///  bit is set, and from token C to token A otherwise.
+///
+/// # Returns
+/// The updated state object of type [`ContractState`] yielding the result of the swap.
+#[zk_on_secret_input(shortname = 0x0C, secret_type = "SecretAmountAndDirection")]
+pub fn swap_two_hop(
+    context: ContractContext,
+    state: ContractState,
+    _zk_state: ZkState<SecretVarMetadata>,
+    only_if_at_front: bool,
+    min_out: TokenSwapAmount,
+) -> (
+    ContractState,
+    Vec<EventGroup>,
+    ZkInputDef<SecretVarMetadata, SecretAmountAndDirection>,
+) {
+    assert!(
+        state.contract_pools_have_liquidity(),
+        "The contract is closed"
+    );
+    assert!(
+        state.second_pool_has_liquidity(),
+        "The second pool is not open"
+    );
+
+    // Check that sender have a non-zero balance of token A or C. The swap would fail anyway if
+    // they don't have anything to swap with.
+    let a_balance = state
+        .token_balances
+        .get_balance_for(&context.sender)
+        .get_amount_of(DepositToken::A);
+    let c_balance = state
+        .second_pool()
+        .token_balances
+        .get_balance_for(&context.sender)
+        .get_amount_of(SECOND_POOL_TOKEN_C);
+    assert!(
+        a_balance != 0 || c_balance != 0,
+        "Balances of token A and C are both zero; nothing to swap with."
+    );
+
+    let input_def = ZkInputDef::with_metadata(
+        Some(swap_variable_inputted::SHORTNAME),
+        SecretVarMetadata {
+            only_if_at_front,
+            is_output_variable: false,
+            min_out,
+            is_two_hop: true,
         },
     );
     (state, vec![], input_def)
@@ -341,6 +1034,10 @@ fn start_next_in_queue(state: &ContractState, zk_events: &mut Vec<ZkStateChange>
 ///
 /// Will create a new worklist entry, and possibly start computation, if no previous computation is
 /// active.
+///
+/// Swaps marked with [`SecretVarMetadata::only_if_at_front`] are dropped if they would not be the
+/// head of the [`ContractState::worklist`]. Their secret variable is deleted, and the user's
+/// balances are left untouched.
 #[zk_on_variable_inputted(shortname = 0x08)]
 pub fn swap_variable_inputted(
     _context: ContractContext,
@@ -351,10 +1048,12 @@ pub fn swap_variable_inputted(
     let secret_var_info = zk_state.get_variable(variable_id).unwrap();
     let only_if_at_front = secret_var_info.metadata.only_if_at_front;
 
-    // Swap already in progress; let's wait for it to finish
+    // The head of the worklist is the swap currently being evaluated, so an empty worklist means
+    // that this swap will be evaluated next.
     let mut zk_events = Vec::new();
     let is_at_the_front_of_the_queue = state.worklist.is_empty();
     if only_if_at_front && !is_at_the_front_of_the_queue {
+        // Drop the swap, as other swaps may change the exchange rate before it is evaluated.
         zk_events.push(ZkStateChange::DeleteVariables {
             variables_to_delete: vec![variable_id],
         });
@@ -362,6 +1061,8 @@ pub fn swap_variable_inputted(
         let worklist_entry = WorklistEntry {
             variable_id,
             sender: secret_var_info.owner,
+            min_out: secret_var_info.metadata.min_out,
+            is_two_hop: secret_var_info.metadata.is_two_hop,
         };
 
         state.worklist.push_back(worklist_entry);
@@ -395,8 +1096,9 @@ pub fn computation_complete(
 ///
 /// Will:
 ///
-/// 1. Trigger [`execute_swap()`]. This is triggered in a separate event in order to provide
-///    a separate atomic context for making swaps and failing.
+/// 1. Trigger [`execute_swap()`], or [`execute_two_hop_swap()`] for two-hop swaps. This is
+///    triggered in a separate event in order to provide a separate atomic context for making
+///    swaps and failing.
 /// 2. Start the next swap in the [`ContractState::worklist`], if any are present. Should always
 ///    happen, even if the swap turns out to be bad.
 /// 3. Remove unused variables.
@@ -417,13 +1119,26 @@ pub fn swap_opened(
     let worklist_entry_processed = state.worklist.pop_front().unwrap();
 
     // Read the opened swap
-    let swap = {
-        let amount_and_direction: AmountAndDirection = zk_state
-            .get_variable(opened_result_variables[0])
-            .and_then(|v| v.open_value())
-            .unwrap();
+    let amount_and_direction: AmountAndDirection = zk_state
+        .get_variable(opened_result_variables[0])
+        .and_then(|v| v.open_value())
+        .unwrap();
 
-        //
+    // Call execute_swap or execute_two_hop_swap
+    let mut event_group_builder = EventGroup::builder();
+    if worklist_entry_processed.is_two_hop {
+        let swap = TwoHopSwap {
+            sender: worklist_entry_processed.sender,
+            is_from_a: amount_and_direction.is_from_a,
+            amount_in: amount_and_direction.amount,
+            min_out: worklist_entry_processed.min_out,
+        };
+        event_group_builder
+            .call(context.contract_address, SHORTNAME_ZK_PUBLIC_INVOCATION)
+            .argument(execute_two_hop_swap::SHORTNAME)
+            .argument(swap)
+            .done();
+    } else {
         let TokensInOut {
             token_in,
             token_out,
@@ -432,22 +1147,19 @@ pub fn swap_opened(
             .token_balances
             .deduce_tokens_in_out_b(amount_and_direction.is_from_a);
 
-        // Create swap
-        Swap {
+        let swap = Swap {
             sender: worklist_entry_processed.sender,
             token_in,
             token_out,
             amount_in: amount_and_direction.amount,
-        }
-    };
-
-    // Call execute_swap
-    let mut event_group_builder = EventGroup::builder();
-    event_group_builder
-        .call(context.contract_address, SHORTNAME_ZK_PUBLIC_INVOCATION)
-        .argument(execute_swap::SHORTNAME)
-        .argument(swap)
-        .done();
+            min_out: worklist_entry_processed.min_out,
+        };
+        event_group_builder
+            .call(context.contract_address, SHORTNAME_ZK_PUBLIC_INVOCATION)
+            .argument(execute_swap::SHORTNAME)
+            .argument(swap)
+            .done();
+    }
 
     // Start next in worklist
     let mut variables_to_delete = opened_result_variables;
@@ -472,6 +1184,21 @@ pub struct Swap {
     token_out: DepositToken,
     /// Amount of [`Swap::token_in`] tokens to input.
     amount_in: TokenSwapAmount,
+    /// Minimum amount of [`Swap::token_out`] tokens to output.
+    min_out: TokenSwapAmount,
+}
+
+/// Records the publicized two-hop swap.
+#[derive(WriteRPC, ReadRPC, CreateTypeSpec)]
+pub struct TwoHopSwap {
+    /// Sender of the swap.
+    sender: Address,
+    /// Whether to swap from token A to token C, or from token C to token A.
+    is_from_a: bool,
+    /// Amount of tokens to input.
+    amount_in: TokenSwapAmount,
+    /// Minimum amount of tokens to output.
+    min_out: TokenSwapAmount,
 }
 
 /**
@@ -480,6 +1207,10 @@ pub struct Swap {
 const SHORTNAME_ZK_PUBLIC_INVOCATION: Shortname = Shortname::from_u32(0x09);
 
 /// The executor of [`Swap`]s. Can only be called by the contract itself.
+///
+/// Fails if the swap would output less than [`Swap::min_out`], or more than
+/// [`ContractState::max_swap_output_per_mille`] of the output pool, in which case the input
+/// tokens remain in the user's balance.
 #[action(shortname = 0x20, zk = true)]
 pub fn execute_swap(
     context: ContractContext,
@@ -501,6 +1232,8 @@ pub fn execute_swap(
         swap_info.amount_in,
     )
     .unwrap();
+    assert_min_out(amount_out, swap_info.min_out);
+    state.assert_swap_output_within_limit(swap_info.token_out, amount_out);
 
     state.token_balances.move_tokens(
         swap_info.sender,
@@ -520,6 +1253,125 @@ pub fn execute_swap(
     state
 }
 
+/// The executor of [`TwoHopSwap`]s. Can only be called by the contract itself.
+///
+/// Fails if the swap would output less than [`TwoHopSwap::min_out`], or if either hop would
+/// output more than [`ContractState::max_swap_output_per_mille`] of its output pool, in which
+/// case the input tokens remain in the user's balance.
+#[action(shortname = 0x21, zk = true)]
+pub fn execute_two_hop_swap(
+    context: ContractContext,
+    mut state: ContractState,
+    _zk_state: ZkState<SecretVarMetadata>,
+    swap_info: TwoHopSwap,
+) -> ContractState {
+    assert_eq!(context.sender, context.contract_address);
+    execute_two_hop_swap_internal(&mut state, &swap_info);
+    state
+}
+
+/// Performs a [`TwoHopSwap`] as two sequential single-hop swaps: first in the pool holding the
+/// input token, and then, with the token B output by the first hop, in the other pool.
+fn execute_two_hop_swap_internal(state: &mut ContractState, swap_info: &TwoHopSwap) {
+    assert!(
+        state.contract_pools_have_liquidity(),
+        "The contract is closed"
+    );
+    assert!(
+        state.second_pool_has_liquidity(),
+        "The second pool is not open"
+    );
+
+    let pool_address = state.liquidity_pool_address;
+    let first_pools = state.get_pools();
+    let second_pools = state.get_second_pools();
+    let first_swap_constant = state.swap_constant;
+    let second_swap_constant = state.second_pool().swap_constant;
+
+    if swap_info.is_from_a {
+        let amount_b = state.calculate_hop_output(
+            &first_pools,
+            first_swap_constant,
+            TokensInOut::A_IN_B_OUT,
+            swap_info.amount_in,
+        );
+        let amount_c = state.calculate_hop_output(
+            &second_pools,
+            second_swap_constant,
+            TokensInOut::A_IN_B_OUT,
+            amount_b,
+        );
+        assert_min_out(amount_c, swap_info.min_out);
+
+        state.token_balances.move_tokens(
+            swap_info.sender,
+            pool_address,
+            DepositToken::A,
+            swap_info.amount_in,
+        );
+        state
+            .token_balances
+            .deduct_from_token_balance(pool_address, DepositToken::B, amount_b);
+        let second_pool = state.second_pool.as_mut().unwrap();
+        second_pool.token_balances.add_to_token_balance(
+            pool_address,
+            SECOND_POOL_TOKEN_B,
+            amount_b,
+        );
+        second_pool.token_balances.move_tokens(
+            pool_address,
+            swap_info.sender,
+            SECOND_POOL_TOKEN_C,
+            amount_c,
+        );
+    } else {
+        let amount_b = state.calculate_hop_output(
+            &second_pools,
+            second_swap_constant,
+            TokensInOut::B_IN_A_OUT,
+            swap_info.amount_in,
+        );
+        let amount_a = state.calculate_hop_output(
+            &first_pools,
+            first_swap_constant,
+            TokensInOut::B_IN_A_OUT,
+            amount_b,
+        );
+        assert_min_out(amount_a, swap_info.min_out);
+
+        let second_pool = state.second_pool.as_mut().unwrap();
+        second_pool.token_balances.move_tokens(
+            swap_info.sender,
+            pool_address,
+            SECOND_POOL_TOKEN_C,
+            swap_info.amount_in,
+        );
+        second_pool.token_balances.deduct_from_token_balance(
+            pool_address,
+            SECOND_POOL_TOKEN_B,
+            amount_b,
+        );
+        state
+            .token_balances
+            .add_to_token_balance(pool_address, DepositToken::B, amount_b);
+        state
+            .token_balances
+            .move_tokens(pool_address, swap_info.sender, DepositToken::A, amount_a);
+    }
+
+    state.assert_invariants();
+}
+
+/// Checks that a swap outputting `amount_out` produces at least `min_out` tokens.
+fn assert_min_out(amount_out: TokenSwapAmount, min_out: TokenSwapAmount) {
+    assert!(
+        amount_out >= min_out,
+        "Swap output {} is below the minimum output {}",
+        amount_out,
+        min_out,
+    );
+}
+
 /// Computes how many `token_out` tokens should be given for the having swapped in the given amount
 /// of `token_in` tokens.
 pub fn calculate_swap_to_amount(
@@ -538,7 +1390,7 @@ pub fn calculate_swap_to_amount(
         .ok_or("Underflow in token pool")
 }
 
-/// Withdraw `amount` of token A or B from the contract for the calling user.
+/// Withdraw `amount` of token A, B or C from the contract for the calling user.
 /// This fails if `amount` is larger than the user balance of the corresponding token.
 ///
 /// It preemptively updates the state of the user's balance before making the transfer.
@@ -563,14 +1415,8 @@ pub fn withdraw(
     amount: TokenAmount,
     wait_for_callback: bool,
 ) -> (ContractState, Vec<EventGroup>) {
-    let token_in = state
-        .token_balances
-        .deduce_tokens_in_out(token_address)
-        .token_in;
-
-    state
-        .token_balances
-        .deduct_from_token_balance(context.sender, token_in, amount);
+    let (token_balances, token_in) = state.balances_for_token(token_address);
+    token_balances.deduct_from_token_balance(context.sender, token_in, amount);
 
     let mut event_group_builder = EventGroup::builder();
 
@@ -599,8 +1445,12 @@ fn wait_withdraw_callback(
     (state, vec![])
 }
 
-/// Empties the pools into the contract owner's balance and closes the contract.
-/// Fails if called by anyone but the contract owner.
+/// Empties the pools, including the [`SecondPool`] if it has liquidity, into the contract owner's
+/// balance and closes the contract. All liquidity tokens, including the owner's and the locked
+/// [`MINIMUM_LIQUIDITY`], are burned, such that no liquidity tokens can be redeemed against the
+/// pools of a later initialization.
+/// Fails if called by anyone but the contract owner, or if any other user holds liquidity tokens.
+/// Other liquidity providers must [`remove_liquidity()`] before the pools can be closed.
 ///
 /// ### Returns
 ///
@@ -620,7 +1470,42 @@ pub fn close_pools(
         "The contract is closed"
     );
 
-    let liquidity_pools = state.get_pools().clone();
+    close_pools_internal(&mut state);
+
+    (state, vec![])
+}
+
+/// Burns all liquidity tokens and moves the contents of the pools to the contract owner's
+/// balance. Fails if any user besides the owner holds liquidity tokens.
+///
+/// ### Parameters:
+///
+///  * `state`: [`ContractState`] - The current state of the contract.
+fn close_pools_internal(state: &mut ContractState) {
+    let liquidity_pools = state.get_pools();
+    let owner_liquidity_tokens = state
+        .token_balances
+        .get_balance_for(&state.contract_owner)
+        .get_amount_of(DepositToken::LIQUIDITY);
+    let other_liquidity_tokens =
+        unlocked_liquidity_tokens(&liquidity_pools) - owner_liquidity_tokens;
+    assert_eq!(
+        other_liquidity_tokens, 0,
+        "Cannot close the pools while other liquidity providers hold {} liquidity tokens",
+        other_liquidity_tokens,
+    );
+
+    state.token_balances.deduct_from_token_balance(
+        state.contract_owner,
+        DepositToken::LIQUIDITY,
+        owner_liquidity_tokens,
+    );
+    state.token_balances.deduct_from_token_balance(
+        state.liquidity_pool_address,
+        DepositToken::LIQUIDITY,
+        liquidity_pools.liquidity_tokens,
+    );
+
     state.token_balances.move_tokens(
         state.liquidity_pool_address,
         state.contract_owner,
@@ -634,12 +1519,35 @@ pub fn close_pools(
         liquidity_pools.get_amount_of(DepositToken::B),
     );
 
+    if state.second_pool_has_liquidity() {
+        let second_pools = state.get_second_pools();
+        let pool_address = state.liquidity_pool_address;
+        let owner = state.contract_owner;
+        let second_pool = state.second_pool.as_mut().unwrap();
+        second_pool.token_balances.deduct_from_token_balance(
+            pool_address,
+            SECOND_POOL_TOKEN_B,
+            second_pools.get_amount_of(SECOND_POOL_TOKEN_B),
+        );
+        second_pool.token_balances.move_tokens(
+            pool_address,
+            owner,
+            SECOND_POOL_TOKEN_C,
+            second_pools.get_amount_of(SECOND_POOL_TOKEN_C),
+        );
+        state.token_balances.add_to_token_balance(
+            owner,
+            DepositToken::B,
+            second_pools.get_amount_of(SECOND_POOL_TOKEN_B),
+        );
+    }
+
     // Assert correctly closed
     let liquidity_pools = state.get_pools();
     assert_eq!(liquidity_pools.get_amount_of(DepositToken::A), 0);
     assert_eq!(liquidity_pools.get_amount_of(DepositToken::B), 0);
-
-    (state, vec![])
+    assert_eq!(liquidity_pools.get_amount_of(DepositToken::LIQUIDITY), 0);
+    assert!(!state.second_pool_has_liquidity());
 }
 
 // * HELPER FUNCTIONS *
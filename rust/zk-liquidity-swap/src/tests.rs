@@ -0,0 +1,462 @@
+use super::*;
+use defi_common::test_util::{account, contract};
+
+fn state_with_pools(pool_a: u128, pool_b: u128) -> ContractState {
+    let liquidity_pool_address = contract(3);
+    let mut token_balances =
+        TokenBalances::new(liquidity_pool_address, contract(1), contract(2)).unwrap();
+    token_balances.add_to_token_balance(liquidity_pool_address, DepositToken::A, pool_a);
+    token_balances.add_to_token_balance(liquidity_pool_address, DepositToken::B, pool_b);
+    ContractState {
+        contract_owner: account(4),
+        liquidity_pool_address,
+        swap_constant: pool_a.saturating_mul(pool_b),
+        token_balances,
+        worklist: VecDeque::new(),
+        max_swap_output_per_mille: 900,
+        second_pool: None,
+    }
+}
+
+#[test]
+fn spot_price_balanced_pool() {
+    let state = state_with_pools(1_000, 1_000);
+    assert_eq!(state.spot_price_a_in_b(), SPOT_PRICE_PRECISION);
+    assert_eq!(state.spot_price_b_in_a(), SPOT_PRICE_PRECISION);
+}
+
+#[test]
+fn spot_price_skewed_pool() {
+    let state = state_with_pools(1_000, 4_000);
+    assert_eq!(state.spot_price_a_in_b(), 4 * SPOT_PRICE_PRECISION);
+    assert_eq!(state.spot_price_b_in_a(), SPOT_PRICE_PRECISION / 4);
+
+    let state = state_with_pools(3_000, 1_000);
+    assert_eq!(state.spot_price_a_in_b(), 333_333_333);
+    assert_eq!(state.spot_price_b_in_a(), 3 * SPOT_PRICE_PRECISION);
+}
+
+#[test]
+fn spot_price_without_liquidity() {
+    let state = state_with_pools(0, 0);
+    assert_eq!(state.spot_price_a_in_b(), 0);
+    assert_eq!(state.spot_price_b_in_a(), 0);
+
+    let state = state_with_pools(1_000, 0);
+    assert_eq!(state.spot_price_a_in_b(), 0);
+    assert_eq!(state.spot_price_b_in_a(), 0);
+}
+
+#[test]
+fn spot_price_large_pools() {
+    let state = state_with_pools(1 << 126, 1 << 125);
+    assert_eq!(state.spot_price_a_in_b(), SPOT_PRICE_PRECISION / 2);
+    assert_eq!(state.spot_price_b_in_a(), 2 * SPOT_PRICE_PRECISION);
+}
+
+#[test]
+fn swap_output_within_limit() {
+    let state = state_with_pools(1_000, 1_000);
+    state.assert_swap_output_within_limit(DepositToken::A, 0);
+    state.assert_swap_output_within_limit(DepositToken::B, 900);
+}
+
+#[test]
+#[should_panic(expected = "Swap output 970 exceeds the maximum output 900 of the pool")]
+fn swap_output_draining_small_pool() {
+    let state = state_with_pools(1_000, 1_000);
+    let amount_out = calculate_swap_to_amount(1_000, 1_000, state.swap_constant, 32_768).unwrap();
+    state.assert_swap_output_within_limit(DepositToken::B, amount_out);
+}
+
+#[test]
+fn swap_output_limit_of_large_pools() {
+    let state = state_with_pools(1 << 126, 1 << 125);
+    state.assert_swap_output_within_limit(DepositToken::A, (1 << 126) / 1000 * 900);
+    state.assert_swap_output_within_limit(DepositToken::B, (1 << 125) / 1000 * 900);
+}
+
+#[test]
+#[should_panic(expected = "Swap output 901 exceeds the maximum output 900 of the pool")]
+fn swap_output_just_above_limit() {
+    state_with_pools(1_000, 1_000).assert_swap_output_within_limit(DepositToken::A, 901);
+}
+
+fn liquidity_tokens(state: &ContractState, owner: &Address) -> TokenAmount {
+    state
+        .token_balances
+        .get_balance_for(owner)
+        .get_amount_of(DepositToken::LIQUIDITY)
+}
+
+/// Creates a state where the owner has provided the initial liquidity of 10 000 A and 40 000 B.
+fn state_with_initial_liquidity() -> ContractState {
+    let mut state = state_with_pools(0, 0);
+    let owner = state.contract_owner;
+    state
+        .token_balances
+        .add_to_token_balance(owner, DepositToken::A, 10_000);
+    state
+        .token_balances
+        .add_to_token_balance(owner, DepositToken::B, 40_000);
+    provide_initial_liquidity_internal(&mut state, &owner, 10_000, 40_000);
+    state
+}
+
+#[test]
+fn initial_liquidity_locks_minimum_liquidity() {
+    let state = state_with_initial_liquidity();
+
+    assert_eq!(liquidity_tokens(&state, &state.contract_owner), 19_900);
+    assert_eq!(
+        liquidity_tokens(&state, &state.liquidity_pool_address),
+        20_000
+    );
+    assert_eq!(state.swap_constant, 400_000_000);
+}
+
+#[test]
+fn last_liquidity_provider_drains_pools_and_burns_locked_minimum() {
+    let mut state = state_with_initial_liquidity();
+    let owner = state.contract_owner;
+    let owner_liquidity = liquidity_tokens(&state, &owner);
+
+    remove_liquidity_internal(&mut state, &owner, owner_liquidity);
+
+    let owner_balance = state.token_balances.get_balance_for(&owner);
+    assert_eq!(owner_balance.get_amount_of(DepositToken::A), 10_000);
+    assert_eq!(owner_balance.get_amount_of(DepositToken::B), 40_000);
+    assert_eq!(liquidity_tokens(&state, &owner), 0);
+    assert_eq!(liquidity_tokens(&state, &state.liquidity_pool_address), 0);
+    assert_eq!(state.swap_constant, 0);
+    assert!(!state.contract_pools_have_liquidity());
+}
+
+#[test]
+fn locked_minimum_liquidity_is_retained_while_other_providers_remain() {
+    let mut state = state_with_initial_liquidity();
+    let owner = state.contract_owner;
+    provide_liquidity_from(&mut state, account(5));
+    let owner_liquidity = liquidity_tokens(&state, &owner);
+
+    remove_liquidity_internal(&mut state, &owner, owner_liquidity);
+
+    let owner_balance = state.token_balances.get_balance_for(&owner);
+    assert_eq!(owner_balance.get_amount_of(DepositToken::A), 9_950);
+    assert_eq!(owner_balance.get_amount_of(DepositToken::B), 39_800);
+    assert_eq!(
+        liquidity_tokens(&state, &state.liquidity_pool_address),
+        10_000 + MINIMUM_LIQUIDITY
+    );
+
+    remove_liquidity_internal(&mut state, &account(5), 10_000);
+
+    let provider_balance = state.token_balances.get_balance_for(&account(5));
+    assert_eq!(provider_balance.get_amount_of(DepositToken::A), 5_050);
+    assert_eq!(provider_balance.get_amount_of(DepositToken::B), 20_200);
+    assert!(!state.contract_pools_have_liquidity());
+}
+
+#[test]
+fn locked_minimum_liquidity_is_retained_while_second_pool_has_liquidity() {
+    let mut state = state_with_second_pool();
+    let owner = state.contract_owner;
+    let owner_liquidity = liquidity_tokens(&state, &owner);
+
+    remove_liquidity_internal(&mut state, &owner, owner_liquidity);
+
+    assert_eq!(
+        liquidity_tokens(&state, &state.liquidity_pool_address),
+        MINIMUM_LIQUIDITY
+    );
+    assert!(state.contract_pools_have_liquidity());
+}
+
+#[test]
+fn second_provider_receives_fair_share() {
+    let mut state = state_with_initial_liquidity();
+    let provider = account(5);
+    state
+        .token_balances
+        .add_to_token_balance(provider, DepositToken::A, 5_000);
+    state
+        .token_balances
+        .add_to_token_balance(provider, DepositToken::B, 20_000);
+
+    let minted = calculate_minted_liquidity_tokens(5_000, 20_000, &state.get_pools());
+    provide_liquidity_internal(
+        &mut state,
+        &provider,
+        TokensInOut::A_IN_B_OUT,
+        5_000,
+        20_000,
+        minted,
+    );
+
+    // The provider adds half of the pools, and receives half of the existing liquidity tokens,
+    // including the locked minimum.
+    assert_eq!(minted, 10_000);
+    assert_eq!(liquidity_tokens(&state, &provider), 10_000);
+    assert_eq!(
+        liquidity_tokens(&state, &state.liquidity_pool_address),
+        30_000
+    );
+    assert_eq!(
+        calculate_reclaim_output(minted, &state.get_pools()),
+        (5_000, 20_000)
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Initial liquidity must mint more than 100 liquidity tokens, but would mint 100"
+)]
+fn initial_liquidity_must_exceed_minimum_liquidity() {
+    let mut state = state_with_pools(0, 0);
+    let owner = state.contract_owner;
+    state
+        .token_balances
+        .add_to_token_balance(owner, DepositToken::A, 100);
+    state
+        .token_balances
+        .add_to_token_balance(owner, DepositToken::B, 100);
+    provide_initial_liquidity_internal(&mut state, &owner, 100, 100);
+}
+
+/// Adds 5 000 A and 20 000 B of liquidity from the given provider to the pools of
+/// [`state_with_initial_liquidity`].
+fn provide_liquidity_from(state: &mut ContractState, provider: Address) {
+    state
+        .token_balances
+        .add_to_token_balance(provider, DepositToken::A, 5_000);
+    state
+        .token_balances
+        .add_to_token_balance(provider, DepositToken::B, 20_000);
+    let minted = calculate_minted_liquidity_tokens(5_000, 20_000, &state.get_pools());
+    provide_liquidity_internal(
+        state,
+        &provider,
+        TokensInOut::A_IN_B_OUT,
+        5_000,
+        20_000,
+        minted,
+    );
+}
+
+#[test]
+fn close_pools_burns_all_liquidity_tokens() {
+    let mut state = state_with_initial_liquidity();
+    let owner = state.contract_owner;
+
+    close_pools_internal(&mut state);
+
+    let owner_balance = state.token_balances.get_balance_for(&owner);
+    assert_eq!(owner_balance.get_amount_of(DepositToken::A), 10_000);
+    assert_eq!(owner_balance.get_amount_of(DepositToken::B), 40_000);
+    assert_eq!(liquidity_tokens(&state, &owner), 0);
+    assert_eq!(liquidity_tokens(&state, &state.liquidity_pool_address), 0);
+    assert!(!state.contract_pools_have_liquidity());
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot close the pools while other liquidity providers hold 10000 liquidity tokens"
+)]
+fn close_pools_fails_while_other_providers_hold_liquidity() {
+    let mut state = state_with_initial_liquidity();
+    provide_liquidity_from(&mut state, account(5));
+
+    close_pools_internal(&mut state);
+}
+
+#[test]
+fn liquidity_calculations_of_large_pools_do_not_overflow() {
+    let large = u128::MAX / 4;
+    let mut state = state_with_pools(large, large);
+    state.token_balances.add_to_token_balance(
+        state.liquidity_pool_address,
+        DepositToken::LIQUIDITY,
+        large,
+    );
+
+    assert_eq!(
+        calculate_minted_liquidity_tokens(large / 2, large / 2, &state.get_pools()),
+        large / 2
+    );
+    assert_eq!(
+        calculate_reclaim_output(large / 2, &state.get_pools()),
+        (large / 2, large / 2)
+    );
+}
+
+/// Creates a state with the initial liquidity of [`state_with_initial_liquidity`], and a second
+/// pool where the owner has provided 20 000 B and 5 000 C. The user 5 holds 1 000 A and 500 C.
+fn state_with_second_pool() -> ContractState {
+    let mut state = state_with_initial_liquidity();
+    let owner = state.contract_owner;
+    let liquidity_pool_address = state.liquidity_pool_address;
+    state
+        .token_balances
+        .add_to_token_balance(owner, DepositToken::B, 20_000);
+    state
+        .token_balances
+        .add_to_token_balance(account(5), DepositToken::A, 1_000);
+
+    let mut token_balances =
+        TokenBalances::new(liquidity_pool_address, contract(2), contract(6)).unwrap();
+    token_balances.add_to_token_balance(owner, SECOND_POOL_TOKEN_C, 5_000);
+    token_balances.add_to_token_balance(account(5), SECOND_POOL_TOKEN_C, 500);
+    state.second_pool = Some(SecondPool {
+        token_balances,
+        swap_constant: 0,
+    });
+    provide_initial_liquidity_second_pool_internal(&mut state, &owner, 20_000, 5_000);
+    state
+}
+
+fn second_pool_balance(state: &ContractState, owner: &Address, token: DepositToken) -> u128 {
+    state
+        .second_pool()
+        .token_balances
+        .get_balance_for(owner)
+        .get_amount_of(token)
+}
+
+#[test]
+fn second_pool_initial_liquidity() {
+    let state = state_with_second_pool();
+    assert_eq!(state.second_pool().swap_constant, 100_000_000);
+    assert_eq!(
+        state.get_second_pools().get_amount_of(SECOND_POOL_TOKEN_B),
+        20_000
+    );
+    assert_eq!(
+        state.get_second_pools().get_amount_of(SECOND_POOL_TOKEN_C),
+        5_000
+    );
+    assert!(state.second_pool_has_liquidity());
+}
+
+#[test]
+fn two_hop_swap_from_a_to_c_matches_sequential_swaps() {
+    let mut state = state_with_second_pool();
+    let expected_b = calculate_swap_to_amount(10_000, 40_000, 400_000_000, 1_000).unwrap();
+    let expected_c = calculate_swap_to_amount(20_000, 5_000, 100_000_000, expected_b).unwrap();
+    assert_eq!(expected_b, 3_636);
+    assert_eq!(expected_c, 769);
+
+    execute_two_hop_swap_internal(
+        &mut state,
+        &TwoHopSwap {
+            sender: account(5),
+            is_from_a: true,
+            amount_in: 1_000,
+            min_out: 769,
+        },
+    );
+
+    let user_balance = state.token_balances.get_balance_for(&account(5));
+    assert_eq!(user_balance.get_amount_of(DepositToken::A), 0);
+    assert_eq!(user_balance.get_amount_of(DepositToken::B), 0);
+    assert_eq!(
+        second_pool_balance(&state, &account(5), SECOND_POOL_TOKEN_C),
+        500 + expected_c
+    );
+
+    let pools = state.get_pools();
+    assert_eq!(pools.get_amount_of(DepositToken::A), 11_000);
+    assert_eq!(pools.get_amount_of(DepositToken::B), 40_000 - expected_b);
+    let second_pools = state.get_second_pools();
+    assert_eq!(
+        second_pools.get_amount_of(SECOND_POOL_TOKEN_B),
+        20_000 + expected_b
+    );
+    assert_eq!(
+        second_pools.get_amount_of(SECOND_POOL_TOKEN_C),
+        5_000 - expected_c
+    );
+}
+
+#[test]
+fn two_hop_swap_from_c_to_a_matches_sequential_swaps() {
+    let mut state = state_with_second_pool();
+    let expected_b = calculate_swap_to_amount(5_000, 20_000, 100_000_000, 500).unwrap();
+    let expected_a = calculate_swap_to_amount(40_000, 10_000, 400_000_000, expected_b).unwrap();
+    assert_eq!(expected_b, 1_818);
+    assert_eq!(expected_a, 434);
+
+    execute_two_hop_swap_internal(
+        &mut state,
+        &TwoHopSwap {
+            sender: account(5),
+            is_from_a: false,
+            amount_in: 500,
+            min_out: 0,
+        },
+    );
+
+    assert_eq!(
+        state
+            .token_balances
+            .get_balance_for(&account(5))
+            .get_amount_of(DepositToken::A),
+        1_000 + expected_a
+    );
+    assert_eq!(
+        second_pool_balance(&state, &account(5), SECOND_POOL_TOKEN_C),
+        0
+    );
+
+    let pools = state.get_pools();
+    assert_eq!(pools.get_amount_of(DepositToken::A), 10_000 - expected_a);
+    assert_eq!(pools.get_amount_of(DepositToken::B), 40_000 + expected_b);
+    let second_pools = state.get_second_pools();
+    assert_eq!(
+        second_pools.get_amount_of(SECOND_POOL_TOKEN_B),
+        20_000 - expected_b
+    );
+    assert_eq!(second_pools.get_amount_of(SECOND_POOL_TOKEN_C), 5_500);
+}
+
+#[test]
+#[should_panic(expected = "Swap output 769 is below the minimum output 770")]
+fn two_hop_swap_below_minimum_output() {
+    let mut state = state_with_second_pool();
+    execute_two_hop_swap_internal(
+        &mut state,
+        &TwoHopSwap {
+            sender: account(5),
+            is_from_a: true,
+            amount_in: 1_000,
+            min_out: 770,
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "The second pool is not open")]
+fn two_hop_swap_without_second_pool() {
+    let mut state = state_with_initial_liquidity();
+    execute_two_hop_swap_internal(
+        &mut state,
+        &TwoHopSwap {
+            sender: account(5),
+            is_from_a: true,
+            amount_in: 1_000,
+            min_out: 0,
+        },
+    );
+}
+
+#[test]
+fn token_c_is_held_in_second_pool_balances() {
+    let mut state = state_with_second_pool();
+
+    let (token_balances, token) = state.balances_for_token(contract(6));
+    assert_eq!(token, SECOND_POOL_TOKEN_C);
+    assert_eq!(token_balances.token_b_address, contract(6));
+
+    let (token_balances, token) = state.balances_for_token(contract(2));
+    assert_eq!(token, DepositToken::B);
+    assert_eq!(token_balances.token_b_address, contract(2));
+}
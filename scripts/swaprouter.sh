@@ -129,6 +129,8 @@ function deploy_router() {
 # $3 - address of the swap contract to add.
 # $4 - address of the 'a' token of the swap address.
 # $5 - address of the 'b' token of the swap address.
+#
+# The tokens are deployed from the token contract, which supports relative approval.
 function add_swap_to_router() {
   cargo partisia-contract transaction action \
     --net="$NETWORK" \
@@ -138,7 +140,9 @@ function add_swap_to_router() {
     add_swap_contract \
     "$3" \
     "$4" \
-    "$5"
+    "$5" \
+    true \
+    true
 }
 
 # Approves an amount at a token, to allow other contracts to spend